@@ -0,0 +1,122 @@
+use actix_web_template::{
+    config::Settings,
+    dto::{CountMode, PaginationParams},
+    entities::memos,
+    repository::{MemoFilter, MemoRepository},
+    services::MemoService,
+};
+use chrono::Utc;
+use chrono_tz::Tz;
+use criterion::{Criterion, criterion_group, criterion_main};
+use sea_orm::{Database, DatabaseConnection};
+use tokio::runtime::Runtime;
+
+const SEED_COUNT: usize = 50;
+
+async fn seed(db: &DatabaseConnection) -> Vec<memos::Model> {
+    let mut memos = Vec::with_capacity(SEED_COUNT);
+    for i in 0..SEED_COUNT {
+        let memo = MemoRepository::create(
+            db,
+            format!("Benchmark Memo {}", i),
+            Some("Seeded by memo_benchmarks".to_string()),
+            Utc::now(),
+            Tz::UTC,
+            None,
+        )
+        .await
+        .expect("Failed to seed benchmark memo");
+        memos.push(memo);
+    }
+    memos
+}
+
+async fn cleanup(db: &DatabaseConnection, memos: &[memos::Model]) {
+    for memo in memos {
+        MemoRepository::delete(db, memo.id).await.ok();
+    }
+}
+
+fn memo_benchmarks(c: &mut Criterion) {
+    let rt = Runtime::new().expect("Failed to create Tokio runtime");
+
+    let settings = Settings::load().expect("Failed to load settings");
+    let db = rt
+        .block_on(Database::connect(&settings.database.url))
+        .expect("Failed to connect to database");
+    let seeded = rt.block_on(seed(&db));
+    let target_id = seeded[0].id;
+
+    let mut repository = c.benchmark_group("repository");
+    repository.bench_function("find_all_first_page", |b| {
+        b.to_async(&rt).iter(|| async {
+            MemoRepository::find_all(&db, 10, 0, &MemoFilter::default(), "created_at", "desc")
+                .await
+                .unwrap()
+        })
+    });
+    repository.bench_function("find_all_completed_filter", |b| {
+        b.to_async(&rt).iter(|| async {
+            MemoRepository::find_all(
+                &db,
+                10,
+                0,
+                &MemoFilter {
+                    completed: Some(false),
+                    ..MemoFilter::default()
+                },
+                "date_to",
+                "asc",
+            )
+            .await
+            .unwrap()
+        })
+    });
+    repository.bench_function("find_by_id", |b| {
+        b.to_async(&rt)
+            .iter(|| async { MemoRepository::find_by_id(&db, target_id).await.unwrap() })
+    });
+    repository.finish();
+
+    let service = MemoService::new(db.clone().into());
+    let mut service_group = c.benchmark_group("service");
+    service_group.bench_function("get_all_memos_default", |b| {
+        b.to_async(&rt).iter(|| async {
+            service
+                .get_all_memos(PaginationParams::default())
+                .await
+                .unwrap()
+        })
+    });
+    service_group.bench_function("get_all_memos_deep_page", |b| {
+        b.to_async(&rt).iter(|| async {
+            let params = PaginationParams {
+                limit: Some(100),
+                offset: Some(40),
+                ..PaginationParams::default()
+            };
+            service.get_all_memos(params).await.unwrap()
+        })
+    });
+    service_group.bench_function("get_all_memos_deep_page_without_count", |b| {
+        b.to_async(&rt).iter(|| async {
+            let params = PaginationParams {
+                limit: Some(100),
+                offset: Some(40),
+                count: Some(CountMode::Skip),
+                ..PaginationParams::default()
+            };
+            service.get_all_memos(params).await.unwrap()
+        })
+    });
+    service_group.bench_function("get_memo_by_id", |b| {
+        b.to_async(&rt)
+            .iter(|| async { service.get_memo_by_id(target_id).await.unwrap() })
+    });
+    service_group.finish();
+
+    rt.block_on(cleanup(&db, &seeded));
+}
+
+criterion_group!(benches, memo_benchmarks);
+criterion_main!(benches);
@@ -0,0 +1,176 @@
+# English (default) message catalog.
+# Project Fluent syntax (https://projectfluent.org/fluent/guide/), loaded with
+# the `fluent-bundle` crate; keys are message ids like `memo-new`.
+
+app-name = Memos App
+nav-home = Home
+nav-api-docs = API Docs
+footer-built-with = Built with Actix Web.
+
+page-home-title = Memos - Home
+page-my-memos = My Memos
+
+memo-new = New Memo
+memo-edit = Edit Memo
+memo-empty = No memos found. Create your first memo!
+memo-search-placeholder = Search memos...
+memo-title-edit-hint = Click to edit the title
+memo-delete-confirm = Are you sure you want to delete this memo?
+
+filter-all = All
+filter-active = Active
+filter-completed = Completed
+filter-archived = Archived
+filter-overdue = Overdue
+filter-starred = Starred
+filter-color = Color
+filter-color-any = Any color
+
+stats-heading = Memo statistics
+stats-total = Total
+stats-created-30-days = Created in the last 30 days
+
+sort-created-at = Created At
+sort-due-date = Due Date
+sort-title = Title
+sort-position = Manual order
+sort-starred = Starred
+order-desc = Descending
+order-asc = Ascending
+
+action-complete = Complete
+action-undo = Undo
+action-edit = Edit
+action-archive = Archive
+action-unarchive = Unarchive
+action-pin = Pin
+action-unpin = Unpin
+action-star = Star
+action-unstar = Unstar
+action-delete = Delete
+action-create = Create
+action-update = Update
+action-save = Save
+action-cancel = Cancel
+action-dismiss = Dismiss
+action-snooze = Snooze
+snooze-1h = 1 hour
+snooze-tomorrow = Tomorrow 09:00
+snooze-next-week = Next Monday 09:00
+
+flash-memo-created = Memo created.
+flash-memo-updated = Memo updated.
+flash-memo-deleted = Memo deleted.
+flash-memo-restored = Memo restored.
+
+memo-due = Due:
+memo-completion-note = Resolved:
+memo-blocked-by = Blockers:
+status-completed = Completed
+status-pending = Pending
+
+color-red = Red
+color-orange = Orange
+color-yellow = Yellow
+color-green = Green
+color-blue = Blue
+color-purple = Purple
+color-gray = Gray
+
+form-title = Title *
+form-title-placeholder = Enter memo title
+form-description = Description
+form-description-placeholder = Enter memo description (optional)
+form-due-date = Due Date *
+form-due-date-text = …or describe it
+form-due-date-text-placeholder = e.g. tomorrow 5pm, next friday, in 3 days
+form-mark-completed = Mark as completed
+form-template = Template
+form-template-none = No template
+form-color = Color
+form-color-none = No color
+form-images = Images (up to 5)
+
+nav-calendar = Calendar
+page-calendar-title = Memos - Calendar
+calendar-previous = Previous month
+calendar-next = Next month
+
+nav-board = Board
+page-board-title = Memos - Board
+board-overdue = Overdue
+board-today = Today
+board-upcoming = Upcoming
+board-done = Done
+board-empty = Nothing here
+
+page-maintenance-title = Memos - Maintenance
+maintenance-heading = Down for maintenance
+maintenance-message = We are performing scheduled maintenance. Please try again in a few minutes.
+
+month-january = January
+month-february = February
+month-march = March
+month-april = April
+month-may = May
+month-june = June
+month-july = July
+month-august = August
+month-september = September
+month-october = October
+month-november = November
+month-december = December
+
+weekday-mon = Mon
+weekday-tue = Tue
+weekday-wed = Wed
+weekday-thu = Thu
+weekday-fri = Fri
+weekday-sat = Sat
+weekday-sun = Sun
+
+error-memo-not-found = Memo not found
+error-template-not-found = Template not found
+error-operation-not-found = Operation not found
+error-import-not-found = Import not found
+error-notification-not-found = Notification not found
+error-attachment-not-found = Attachment not found
+error-integration-not-found = Integration not found
+error-resource-not-found = Not found
+error-route-not-found = No such endpoint
+error-method-not-allowed = Method not allowed
+error-validation-failed = Validation error
+error-validation-title-length = Invalid title length
+error-validation-description-length = Description too long
+error-validation-search-length = Search text too long
+error-validation-limit-range = Page size out of range
+error-version-conflict = Memo was changed in the meantime
+error-memo-blocked = Blocked by open memos
+error-request-timeout = Request timed out
+error-maintenance-mode = Service temporarily unavailable for maintenance
+error-unauthorized = Unauthorized
+error-rate-limited = Too many requests
+error-quota-exceeded = Monthly API quota used up
+error-payload-too-large = Request body too large
+error-upload-rejected = File rejected
+error-feature-disabled = Feature not available
+error-db-unavailable = Database temporarily unavailable
+error-db-error = Database error
+error-internal-error = Internal error
+
+email-reminder-subject = Reminder
+email-reminder-intro = This memo is coming due:
+email-reminder-due = Due
+email-reminder-open = Open memos
+email-footer = You receive this email because reminders are enabled for this Memos instance.
+
+push-due-title = Due now
+push-enable = Enable notifications
+push-enabled = Notifications enabled
+
+nav-notifications = Notifications
+page-notifications-title = Memos - Notifications
+notifications-heading = Notifications
+notifications-empty = You're all caught up.
+notifications-mark-read = Mark as read
+notifications-read = Read
@@ -0,0 +1,175 @@
+# Spanish message catalog.
+# Missing keys fall back to the English catalog.
+
+app-name = Memos
+nav-home = Inicio
+nav-api-docs = Documentación de la API
+footer-built-with = Construido con Actix Web.
+
+page-home-title = Memos - Inicio
+page-my-memos = Mis memos
+
+memo-new = Nuevo memo
+memo-edit = Editar memo
+memo-empty = No se encontraron memos. ¡Crea tu primer memo!
+memo-search-placeholder = Buscar memos...
+memo-title-edit-hint = Haz clic para editar el título
+memo-delete-confirm = ¿Seguro que quieres eliminar este memo?
+
+filter-all = Todos
+filter-active = Activos
+filter-completed = Completados
+filter-archived = Archivados
+filter-overdue = Vencidos
+filter-starred = Destacados
+filter-color = Color
+filter-color-any = Cualquier color
+
+stats-heading = Estadísticas de memos
+stats-total = Total
+stats-created-30-days = Creados en los últimos 30 días
+
+sort-created-at = Fecha de creación
+sort-due-date = Fecha límite
+sort-title = Título
+sort-position = Orden manual
+sort-starred = Destacados
+order-desc = Descendente
+order-asc = Ascendente
+
+action-complete = Completar
+action-undo = Deshacer
+action-edit = Editar
+action-archive = Archivar
+action-unarchive = Desarchivar
+action-pin = Fijar
+action-unpin = Desfijar
+action-star = Destacar
+action-unstar = Quitar destacado
+action-delete = Eliminar
+action-create = Crear
+action-update = Actualizar
+action-save = Guardar
+action-cancel = Cancelar
+action-dismiss = Cerrar
+action-snooze = Posponer
+snooze-1h = 1 hora
+snooze-tomorrow = Mañana 09:00
+snooze-next-week = Próximo lunes 09:00
+
+flash-memo-created = Memo creado.
+flash-memo-updated = Memo actualizado.
+flash-memo-deleted = Memo eliminado.
+flash-memo-restored = Memo restaurado.
+
+memo-due = Vence:
+memo-completion-note = Resuelto:
+memo-blocked-by = Bloqueos:
+status-completed = Completado
+status-pending = Pendiente
+
+color-red = Rojo
+color-orange = Naranja
+color-yellow = Amarillo
+color-green = Verde
+color-blue = Azul
+color-purple = Morado
+color-gray = Gris
+
+form-title = Título *
+form-title-placeholder = Introduce el título del memo
+form-description = Descripción
+form-description-placeholder = Introduce una descripción (opcional)
+form-due-date = Fecha límite *
+form-due-date-text = …o descríbela (en inglés)
+form-due-date-text-placeholder = p. ej. tomorrow 5pm, next friday, in 3 days
+form-mark-completed = Marcar como completado
+form-template = Plantilla
+form-template-none = Sin plantilla
+form-color = Color
+form-color-none = Sin color
+form-images = Imágenes (hasta 5)
+
+nav-calendar = Calendario
+page-calendar-title = Memos - Calendario
+calendar-previous = Mes anterior
+calendar-next = Mes siguiente
+
+nav-board = Tablero
+page-board-title = Memos - Tablero
+board-overdue = Vencidos
+board-today = Hoy
+board-upcoming = Próximos
+board-done = Hechos
+board-empty = Nada por aquí
+
+page-maintenance-title = Memos - Mantenimiento
+maintenance-heading = En mantenimiento
+maintenance-message = Estamos realizando un mantenimiento programado. Vuelve a intentarlo en unos minutos.
+
+month-january = Enero
+month-february = Febrero
+month-march = Marzo
+month-april = Abril
+month-may = Mayo
+month-june = Junio
+month-july = Julio
+month-august = Agosto
+month-september = Septiembre
+month-october = Octubre
+month-november = Noviembre
+month-december = Diciembre
+
+weekday-mon = Lun
+weekday-tue = Mar
+weekday-wed = Mié
+weekday-thu = Jue
+weekday-fri = Vie
+weekday-sat = Sáb
+weekday-sun = Dom
+
+error-memo-not-found = Memo no encontrado
+error-template-not-found = Plantilla no encontrada
+error-operation-not-found = Operación no encontrada
+error-import-not-found = Importación no encontrada
+error-notification-not-found = Notificación no encontrada
+error-attachment-not-found = Adjunto no encontrado
+error-integration-not-found = Integración no encontrada
+error-resource-not-found = No encontrado
+error-route-not-found = No existe ese endpoint
+error-method-not-allowed = Método no permitido
+error-validation-failed = Error de validación
+error-validation-title-length = Longitud del título no válida
+error-validation-description-length = Descripción demasiado larga
+error-validation-search-length = Texto de búsqueda demasiado largo
+error-validation-limit-range = Tamaño de página fuera de rango
+error-version-conflict = El memo ha cambiado mientras tanto
+error-memo-blocked = Bloqueado por memos pendientes
+error-request-timeout = La solicitud ha excedido el tiempo de espera
+error-maintenance-mode = Servicio no disponible temporalmente por mantenimiento
+error-unauthorized = No autorizado
+error-rate-limited = Demasiadas solicitudes
+error-quota-exceeded = Cuota mensual de la API agotada
+error-payload-too-large = El cuerpo de la solicitud es demasiado grande
+error-upload-rejected = Archivo rechazado
+error-feature-disabled = Función no disponible
+error-db-unavailable = Base de datos no disponible temporalmente
+error-db-error = Error de base de datos
+error-internal-error = Error interno
+
+email-reminder-subject = Recordatorio
+email-reminder-intro = Esta nota vence pronto:
+email-reminder-due = Vence
+email-reminder-open = Abrir notas
+email-footer = Recibes este correo porque los recordatorios están activados en esta instancia de Memos.
+
+push-due-title = Vence ahora
+push-enable = Activar notificaciones
+push-enabled = Notificaciones activadas
+
+nav-notifications = Notificaciones
+page-notifications-title = Memos - Notificaciones
+notifications-heading = Notificaciones
+notifications-empty = No tienes notificaciones pendientes.
+notifications-mark-read = Marcar como leída
+notifications-read = Leída
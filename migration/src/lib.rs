@@ -1,12 +1,60 @@
 pub use sea_orm_migration::prelude::*;
 
 mod m20250109_000001_create_memos_table;
+mod m20250110_000001_add_position_to_memos;
+mod m20250111_000001_add_version_to_memos;
+mod m20250112_000001_create_push_subscriptions;
+mod m20250113_000001_add_archived_to_memos;
+mod m20250114_000001_add_listing_indexes;
+mod m20250115_000001_create_notifications;
+mod m20250116_000001_create_webhook_integrations;
+mod m20250117_000001_create_outbox_events;
+mod m20250118_000001_add_timezone_to_memos;
+mod m20250119_000001_create_memo_snoozes;
+mod m20250120_000001_add_completion_to_memos;
+mod m20250121_000001_create_memo_dependencies;
+mod m20250122_000001_create_memo_templates;
+mod m20250123_000001_add_pinned_and_starred_to_memos;
+mod m20250124_000001_create_undo_tokens;
+mod m20250125_000001_create_operations;
+mod m20250126_000001_add_current_item_to_operations;
+mod m20250127_000001_add_color_to_memos;
+mod m20250128_000001_create_memo_imports;
+mod m20250129_000001_create_memo_attachments;
+mod m20250130_000001_create_api_key_usage;
+mod m20250131_000001_add_retries_to_operations;
+mod m20250201_000001_create_memo_list_view;
 
 pub struct Migrator;
 
 #[async_trait::async_trait]
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
-        vec![Box::new(m20250109_000001_create_memos_table::Migration)]
+        vec![
+            Box::new(m20250109_000001_create_memos_table::Migration),
+            Box::new(m20250110_000001_add_position_to_memos::Migration),
+            Box::new(m20250111_000001_add_version_to_memos::Migration),
+            Box::new(m20250112_000001_create_push_subscriptions::Migration),
+            Box::new(m20250113_000001_add_archived_to_memos::Migration),
+            Box::new(m20250114_000001_add_listing_indexes::Migration),
+            Box::new(m20250115_000001_create_notifications::Migration),
+            Box::new(m20250116_000001_create_webhook_integrations::Migration),
+            Box::new(m20250117_000001_create_outbox_events::Migration),
+            Box::new(m20250118_000001_add_timezone_to_memos::Migration),
+            Box::new(m20250119_000001_create_memo_snoozes::Migration),
+            Box::new(m20250120_000001_add_completion_to_memos::Migration),
+            Box::new(m20250121_000001_create_memo_dependencies::Migration),
+            Box::new(m20250122_000001_create_memo_templates::Migration),
+            Box::new(m20250123_000001_add_pinned_and_starred_to_memos::Migration),
+            Box::new(m20250124_000001_create_undo_tokens::Migration),
+            Box::new(m20250125_000001_create_operations::Migration),
+            Box::new(m20250126_000001_add_current_item_to_operations::Migration),
+            Box::new(m20250127_000001_add_color_to_memos::Migration),
+            Box::new(m20250128_000001_create_memo_imports::Migration),
+            Box::new(m20250129_000001_create_memo_attachments::Migration),
+            Box::new(m20250130_000001_create_api_key_usage::Migration),
+            Box::new(m20250131_000001_add_retries_to_operations::Migration),
+            Box::new(m20250201_000001_create_memo_list_view::Migration),
+        ]
     }
 }
\ No newline at end of file
@@ -0,0 +1,71 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memos::Table)
+                    .add_column(
+                        ColumnDef::new(Memos::Position)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Existing memos keep their creation order as the initial manual order.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE memos SET position = ordered.row_number - 1 \
+                 FROM (SELECT id, ROW_NUMBER() OVER (ORDER BY created_at, id) AS row_number FROM memos) AS ordered \
+                 WHERE memos.id = ordered.id",
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_position")
+                    .table(Memos::Table)
+                    .col(Memos::Position)
+                    .to_owned(),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_memos_position")
+                    .table(Memos::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memos::Table)
+                    .drop_column(Memos::Position)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Memos {
+    Table,
+    Position,
+}
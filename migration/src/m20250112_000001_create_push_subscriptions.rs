@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PushSubscriptions::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PushSubscriptions::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT gen_random_uuid()"),
+                    )
+                    .col(
+                        ColumnDef::new(PushSubscriptions::Endpoint)
+                            .text()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(PushSubscriptions::P256dh).text().not_null())
+                    .col(ColumnDef::new(PushSubscriptions::Auth).text().not_null())
+                    .col(
+                        ColumnDef::new(PushSubscriptions::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PushSubscriptions::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum PushSubscriptions {
+    Table,
+    Id,
+    Endpoint,
+    P256dh,
+    Auth,
+    CreatedAt,
+}
@@ -0,0 +1,92 @@
+use sea_orm_migration::prelude::*;
+
+/// Composite indexes matching the listing queries: every listing filters on
+/// `archived`, optionally on `completed`, and orders by a column plus `id` as
+/// a tie-breaker. They make `idx_memos_archived` redundant.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_archived_created_at")
+                    .table(Memos::Table)
+                    .col(Memos::Archived)
+                    .col((Memos::CreatedAt, IndexOrder::Desc))
+                    .col((Memos::Id, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_archived_completed_created_at")
+                    .table(Memos::Table)
+                    .col(Memos::Archived)
+                    .col(Memos::Completed)
+                    .col((Memos::CreatedAt, IndexOrder::Desc))
+                    .col((Memos::Id, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_archived_date_to")
+                    .table(Memos::Table)
+                    .col(Memos::Archived)
+                    .col(Memos::DateTo)
+                    .col(Memos::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_memos_archived")
+                    .table(Memos::Table)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_archived")
+                    .table(Memos::Table)
+                    .col(Memos::Archived)
+                    .to_owned(),
+            )
+            .await?;
+
+        for name in [
+            "idx_memos_archived_date_to",
+            "idx_memos_archived_completed_created_at",
+            "idx_memos_archived_created_at",
+        ] {
+            manager
+                .drop_index(Index::drop().name(name).table(Memos::Table).to_owned())
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Memos {
+    Table,
+    Id,
+    Archived,
+    Completed,
+    CreatedAt,
+    DateTo,
+}
@@ -0,0 +1,94 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Notifications::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Notifications::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT gen_random_uuid()"),
+                    )
+                    .col(ColumnDef::new(Notifications::Kind).string_len(50).not_null())
+                    .col(ColumnDef::new(Notifications::Title).string_len(300).not_null())
+                    .col(ColumnDef::new(Notifications::Body).text())
+                    .col(ColumnDef::new(Notifications::MemoId).uuid())
+                    .col(
+                        ColumnDef::new(Notifications::DedupeKey)
+                            .string_len(200)
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(Notifications::ReadAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(Notifications::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_notifications_memo_id")
+                            .from(Notifications::Table, Notifications::MemoId)
+                            .to(Memos::Table, Memos::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notifications_created_at")
+                    .table(Notifications::Table)
+                    .col((Notifications::CreatedAt, IndexOrder::Desc))
+                    .col((Notifications::Id, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_notifications_memo_id")
+                    .table(Notifications::Table)
+                    .col(Notifications::MemoId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Notifications::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Notifications {
+    Table,
+    Id,
+    Kind,
+    Title,
+    Body,
+    MemoId,
+    DedupeKey,
+    ReadAt,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Memos {
+    Table,
+    Id,
+}
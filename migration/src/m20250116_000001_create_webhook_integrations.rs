@@ -0,0 +1,60 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(WebhookIntegrations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(WebhookIntegrations::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT gen_random_uuid()"),
+                    )
+                    .col(
+                        ColumnDef::new(WebhookIntegrations::Provider)
+                            .string_len(20)
+                            .not_null(),
+                    )
+                    // Sealed with the field cipher when encryption is configured;
+                    // the URL itself is the credential.
+                    .col(ColumnDef::new(WebhookIntegrations::Url).text().not_null())
+                    .col(
+                        ColumnDef::new(WebhookIntegrations::Events)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(WebhookIntegrations::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(WebhookIntegrations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum WebhookIntegrations {
+    Table,
+    Id,
+    Provider,
+    Url,
+    Events,
+    CreatedAt,
+}
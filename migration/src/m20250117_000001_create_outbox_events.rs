@@ -0,0 +1,85 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OutboxEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OutboxEvents::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    // Insertion order; created_at ties for events written in
+                    // the same transaction.
+                    .col(
+                        ColumnDef::new(OutboxEvents::Seq)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OutboxEvents::EventType)
+                            .string_len(100)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OutboxEvents::AggregateId).uuid().not_null())
+                    .col(ColumnDef::new(OutboxEvents::Payload).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(OutboxEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(ColumnDef::new(OutboxEvents::PublishedAt).timestamp_with_time_zone())
+                    .col(
+                        ColumnDef::new(OutboxEvents::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(OutboxEvents::LastError).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        // The relay only ever scans unpublished rows in order.
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE INDEX IF NOT EXISTS idx_outbox_events_pending \
+                 ON outbox_events (seq) WHERE published_at IS NULL",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OutboxEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OutboxEvents {
+    Table,
+    Id,
+    Seq,
+    EventType,
+    AggregateId,
+    Payload,
+    CreatedAt,
+    PublishedAt,
+    Attempts,
+    LastError,
+}
@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemoSnoozes::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemoSnoozes::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT gen_random_uuid()"),
+                    )
+                    .col(ColumnDef::new(MemoSnoozes::MemoId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(MemoSnoozes::SnoozedFrom)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemoSnoozes::SnoozedUntil)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MemoSnoozes::Preset).string_len(20))
+                    .col(
+                        ColumnDef::new(MemoSnoozes::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_memo_snoozes_memo_id")
+                            .from(MemoSnoozes::Table, MemoSnoozes::MemoId)
+                            .to(Memos::Table, Memos::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memo_snoozes_memo_id_created_at")
+                    .table(MemoSnoozes::Table)
+                    .col(MemoSnoozes::MemoId)
+                    .col((MemoSnoozes::CreatedAt, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemoSnoozes::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemoSnoozes {
+    Table,
+    Id,
+    MemoId,
+    SnoozedFrom,
+    SnoozedUntil,
+    Preset,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Memos {
+    Table,
+    Id,
+}
@@ -0,0 +1,51 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memos::Table)
+                    .add_column(ColumnDef::new(Memos::CompletedAt).timestamp_with_time_zone())
+                    .add_column(ColumnDef::new(Memos::CompletionNote).text())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Best available guess for memos completed before the column existed
+        manager
+            .exec_stmt(
+                Query::update()
+                    .table(Memos::Table)
+                    .value(Memos::CompletedAt, Expr::col(Memos::UpdatedAt))
+                    .and_where(Expr::col(Memos::Completed).eq(true))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memos::Table)
+                    .drop_column(Memos::CompletedAt)
+                    .drop_column(Memos::CompletionNote)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Memos {
+    Table,
+    Completed,
+    UpdatedAt,
+    CompletedAt,
+    CompletionNote,
+}
@@ -0,0 +1,85 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemoDependencies::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MemoDependencies::MemoId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(MemoDependencies::BlockerId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemoDependencies::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(MemoDependencies::MemoId)
+                            .col(MemoDependencies::BlockerId),
+                    )
+                    .check(
+                        Expr::col(MemoDependencies::MemoId)
+                            .ne(Expr::col(MemoDependencies::BlockerId)),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_memo_dependencies_memo_id")
+                            .from(MemoDependencies::Table, MemoDependencies::MemoId)
+                            .to(Memos::Table, Memos::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_memo_dependencies_blocker_id")
+                            .from(MemoDependencies::Table, MemoDependencies::BlockerId)
+                            .to(Memos::Table, Memos::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The primary key covers lookups by memo; this one serves "what does
+        // this memo block".
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memo_dependencies_blocker_id")
+                    .table(MemoDependencies::Table)
+                    .col(MemoDependencies::BlockerId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemoDependencies::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemoDependencies {
+    Table,
+    MemoId,
+    BlockerId,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Memos {
+    Table,
+    Id,
+}
@@ -0,0 +1,68 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemoTemplates::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemoTemplates::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key()
+                            .extra("DEFAULT gen_random_uuid()"),
+                    )
+                    .col(
+                        ColumnDef::new(MemoTemplates::Name)
+                            .string_len(100)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MemoTemplates::Title)
+                            .string_len(200)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MemoTemplates::Description).text())
+                    .col(ColumnDef::new(MemoTemplates::DueText).string_len(100))
+                    .col(
+                        ColumnDef::new(MemoTemplates::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(
+                        ColumnDef::new(MemoTemplates::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemoTemplates::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemoTemplates {
+    Table,
+    Id,
+    Name,
+    Title,
+    Description,
+    DueText,
+    CreatedAt,
+    UpdatedAt,
+}
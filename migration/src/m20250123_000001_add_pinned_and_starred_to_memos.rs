@@ -0,0 +1,156 @@
+use sea_orm_migration::prelude::*;
+
+/// Adds the `pinned` and `starred` flags. Listings put pinned memos first, so
+/// the listing indexes are rebuilt with `pinned` ahead of the sort column.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memos::Table)
+                    .add_column(
+                        ColumnDef::new(Memos::Pinned)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .add_column(
+                        ColumnDef::new(Memos::Starred)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        for name in OLD_INDEXES {
+            manager
+                .drop_index(Index::drop().name(name).table(Memos::Table).to_owned())
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_archived_pinned_created_at")
+                    .table(Memos::Table)
+                    .col(Memos::Archived)
+                    .col((Memos::Pinned, IndexOrder::Desc))
+                    .col((Memos::CreatedAt, IndexOrder::Desc))
+                    .col((Memos::Id, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_archived_completed_pinned_created_at")
+                    .table(Memos::Table)
+                    .col(Memos::Archived)
+                    .col(Memos::Completed)
+                    .col((Memos::Pinned, IndexOrder::Desc))
+                    .col((Memos::CreatedAt, IndexOrder::Desc))
+                    .col((Memos::Id, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_archived_pinned_date_to")
+                    .table(Memos::Table)
+                    .col(Memos::Archived)
+                    .col((Memos::Pinned, IndexOrder::Desc))
+                    .col(Memos::DateTo)
+                    .col(Memos::Id)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for name in NEW_INDEXES {
+            manager
+                .drop_index(Index::drop().name(name).table(Memos::Table).to_owned())
+                .await?;
+        }
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_archived_created_at")
+                    .table(Memos::Table)
+                    .col(Memos::Archived)
+                    .col((Memos::CreatedAt, IndexOrder::Desc))
+                    .col((Memos::Id, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_archived_completed_created_at")
+                    .table(Memos::Table)
+                    .col(Memos::Archived)
+                    .col(Memos::Completed)
+                    .col((Memos::CreatedAt, IndexOrder::Desc))
+                    .col((Memos::Id, IndexOrder::Desc))
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memos_archived_date_to")
+                    .table(Memos::Table)
+                    .col(Memos::Archived)
+                    .col(Memos::DateTo)
+                    .col(Memos::Id)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memos::Table)
+                    .drop_column(Memos::Pinned)
+                    .drop_column(Memos::Starred)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+const OLD_INDEXES: [&str; 3] = [
+    "idx_memos_archived_created_at",
+    "idx_memos_archived_completed_created_at",
+    "idx_memos_archived_date_to",
+];
+
+const NEW_INDEXES: [&str; 3] = [
+    "idx_memos_archived_pinned_created_at",
+    "idx_memos_archived_completed_pinned_created_at",
+    "idx_memos_archived_pinned_date_to",
+];
+
+#[derive(DeriveIden)]
+enum Memos {
+    Table,
+    Id,
+    Archived,
+    Completed,
+    Pinned,
+    Starred,
+    CreatedAt,
+    DateTo,
+}
@@ -0,0 +1,146 @@
+use sea_orm_migration::prelude::*;
+
+/// Soft delete for memos. A deleted memo points at the undo token issued for
+/// its deletion and is hidden until the token is redeemed or expires. Dropping
+/// an expired token cascades to its memos, which finalizes the deletion.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(UndoTokens::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(UndoTokens::Token)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(UndoTokens::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(
+                        ColumnDef::new(UndoTokens::ExpiresAt)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_undo_tokens_expires_at")
+                    .table(UndoTokens::Table)
+                    .col(UndoTokens::ExpiresAt)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memos::Table)
+                    .add_column(ColumnDef::new(Memos::UndoToken).uuid().null())
+                    .add_foreign_key(
+                        TableForeignKey::new()
+                            .name("fk_memos_undo_token")
+                            .from_tbl(Memos::Table)
+                            .from_col(Memos::UndoToken)
+                            .to_tbl(UndoTokens::Table)
+                            .to_col(UndoTokens::Token)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Serves restoring by token and the cascade from expired tokens. Partial,
+        // so it stays small and listings keep using their own indexes.
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_memos_undo_token \
+             ON memos (undo_token) WHERE undo_token IS NOT NULL",
+        )
+        .await?;
+
+        // Listings only ever read live memos, so their indexes leave deleted
+        // ones out.
+        for (name, columns) in LISTING_INDEXES {
+            db.execute_unprepared(&format!("DROP INDEX IF EXISTS {name}"))
+                .await?;
+            db.execute_unprepared(&format!(
+                "CREATE INDEX {name} ON memos ({columns}) WHERE undo_token IS NULL"
+            ))
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Memos still waiting for their undo window to close stay deleted.
+        let db = manager.get_connection();
+        db.execute_unprepared("DELETE FROM memos WHERE undo_token IS NOT NULL")
+            .await?;
+
+        for (name, columns) in LISTING_INDEXES {
+            db.execute_unprepared(&format!("DROP INDEX IF EXISTS {name}"))
+                .await?;
+            db.execute_unprepared(&format!("CREATE INDEX {name} ON memos ({columns})"))
+                .await?;
+        }
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Memos::Table)
+                    .drop_foreign_key(Alias::new("fk_memos_undo_token"))
+                    .drop_column(Memos::UndoToken)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(UndoTokens::Table).to_owned())
+            .await
+    }
+}
+
+/// The listing indexes from the previous migration, as `(name, columns)`.
+const LISTING_INDEXES: [(&str, &str); 3] = [
+    (
+        "idx_memos_archived_pinned_created_at",
+        "archived, pinned DESC, created_at DESC, id DESC",
+    ),
+    (
+        "idx_memos_archived_completed_pinned_created_at",
+        "archived, completed, pinned DESC, created_at DESC, id DESC",
+    ),
+    (
+        "idx_memos_archived_pinned_date_to",
+        "archived, pinned DESC, date_to, id",
+    ),
+];
+
+#[derive(DeriveIden)]
+enum UndoTokens {
+    Table,
+    Token,
+    CreatedAt,
+    ExpiresAt,
+}
+
+#[derive(DeriveIden)]
+enum Memos {
+    Table,
+    UndoToken,
+}
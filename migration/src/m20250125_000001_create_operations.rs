@@ -0,0 +1,93 @@
+use sea_orm_migration::prelude::*;
+
+/// Long-running operations accepted with `202`. The table doubles as the job
+/// queue: workers claim the oldest pending row and record progress and the
+/// outcome on it.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Operations::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Operations::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Operations::Kind).string_len(50).not_null())
+                    // pending, running, succeeded or failed
+                    .col(
+                        ColumnDef::new(Operations::Status)
+                            .string_len(20)
+                            .not_null()
+                            .default("pending"),
+                    )
+                    .col(ColumnDef::new(Operations::Input).json_binary().not_null())
+                    .col(
+                        ColumnDef::new(Operations::Processed)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(ColumnDef::new(Operations::Total).big_integer())
+                    .col(ColumnDef::new(Operations::Result).json_binary())
+                    .col(ColumnDef::new(Operations::Error).json_binary())
+                    .col(
+                        ColumnDef::new(Operations::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .col(ColumnDef::new(Operations::StartedAt).timestamp_with_time_zone())
+                    .col(ColumnDef::new(Operations::FinishedAt).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await?;
+
+        // Workers only ever look for pending rows, oldest first.
+        let db = manager.get_connection();
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_operations_pending \
+             ON operations (created_at) WHERE status = 'pending'",
+        )
+        .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_operations_finished_at")
+                    .table(Operations::Table)
+                    .col(Operations::FinishedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Operations::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Operations {
+    Table,
+    Id,
+    Kind,
+    Status,
+    Input,
+    Processed,
+    Total,
+    Result,
+    Error,
+    CreatedAt,
+    StartedAt,
+    FinishedAt,
+}
@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+/// Adds `current_item`, what a running operation is working on, for the
+/// progress stream.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Operations::Table)
+                    .add_column(ColumnDef::new(Operations::CurrentItem).string_len(200))
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Operations::Table)
+                    .drop_column(Operations::CurrentItem)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Operations {
+    Table,
+    CurrentItem,
+}
@@ -0,0 +1,103 @@
+use sea_orm_migration::prelude::*;
+
+/// Files uploaded for a background import, kept until the import job has
+/// read them, and the lines each import could not turn into a memo.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemoImports::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemoImports::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(MemoImports::Content).text().not_null())
+                    .col(
+                        ColumnDef::new(MemoImports::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemoImportFailures::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemoImportFailures::OperationId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemoImportFailures::Line)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemoImportFailures::Code)
+                            .string_len(50)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MemoImportFailures::Message).text().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(MemoImportFailures::OperationId)
+                            .col(MemoImportFailures::Line),
+                    )
+                    // Failures go when their operation is cleaned up.
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_memo_import_failures_operation_id")
+                            .from(MemoImportFailures::Table, MemoImportFailures::OperationId)
+                            .to(Operations::Table, Operations::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemoImportFailures::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(MemoImports::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemoImports {
+    Table,
+    Id,
+    Content,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum MemoImportFailures {
+    Table,
+    OperationId,
+    Line,
+    Code,
+    Message,
+}
+
+#[derive(DeriveIden)]
+enum Operations {
+    Table,
+    Id,
+}
@@ -0,0 +1,96 @@
+use sea_orm_migration::prelude::*;
+
+/// Images attached to memos, stored with a JPEG thumbnail so every replica can
+/// serve them without shared disk.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemoAttachments::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemoAttachments::Id)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(MemoAttachments::MemoId).uuid().not_null())
+                    .col(
+                        ColumnDef::new(MemoAttachments::FileName)
+                            .string_len(255)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemoAttachments::ContentType)
+                            .string_len(100)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MemoAttachments::SizeBytes)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(MemoAttachments::Width).integer().not_null())
+                    .col(ColumnDef::new(MemoAttachments::Height).integer().not_null())
+                    .col(ColumnDef::new(MemoAttachments::Data).binary().not_null())
+                    .col(ColumnDef::new(MemoAttachments::Thumbnail).binary().not_null())
+                    .col(
+                        ColumnDef::new(MemoAttachments::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_memo_attachments_memo_id")
+                            .from(MemoAttachments::Table, MemoAttachments::MemoId)
+                            .to(Memos::Table, Memos::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_memo_attachments_memo_id")
+                    .table(MemoAttachments::Table)
+                    .col(MemoAttachments::MemoId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemoAttachments::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemoAttachments {
+    Table,
+    Id,
+    MemoId,
+    FileName,
+    ContentType,
+    SizeBytes,
+    Width,
+    Height,
+    Data,
+    Thumbnail,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Memos {
+    Table,
+    Id,
+}
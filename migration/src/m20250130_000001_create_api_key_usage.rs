@@ -0,0 +1,58 @@
+use sea_orm_migration::prelude::*;
+
+/// Requests made with each API key, counted per day; a key's monthly quota
+/// is checked against the sum of its days in the current month.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ApiKeyUsage::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ApiKeyUsage::ApiKeyId)
+                            .string_len(100)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ApiKeyUsage::Day).date().not_null())
+                    .col(
+                        ColumnDef::new(ApiKeyUsage::RequestCount)
+                            .big_integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .col(
+                        ColumnDef::new(ApiKeyUsage::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(ApiKeyUsage::ApiKeyId)
+                            .col(ApiKeyUsage::Day),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ApiKeyUsage::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ApiKeyUsage {
+    Table,
+    ApiKeyId,
+    Day,
+    RequestCount,
+    UpdatedAt,
+}
@@ -0,0 +1,45 @@
+use sea_orm_migration::prelude::*;
+
+/// Adds `attempts` and `run_after`, so kinds of operations that may fail
+/// transiently, like webhook posts, can be tried again later.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Operations::Table)
+                    .add_column(
+                        ColumnDef::new(Operations::Attempts)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .add_column(ColumnDef::new(Operations::RunAfter).timestamp_with_time_zone())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Operations::Table)
+                    .drop_column(Operations::Attempts)
+                    .drop_column(Operations::RunAfter)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Operations {
+    Table,
+    Attempts,
+    RunAfter,
+}
@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+/// Read model for memo listings: each memo's links and attachments, kept up
+/// to date from the domain events, so a page is read with a single join.
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MemoListView::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MemoListView::MemoId)
+                            .uuid()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MemoListView::BlockedBy)
+                            .array(ColumnType::Uuid)
+                            .not_null()
+                            .extra("DEFAULT '{}'"),
+                    )
+                    .col(
+                        ColumnDef::new(MemoListView::Blocks)
+                            .array(ColumnType::Uuid)
+                            .not_null()
+                            .extra("DEFAULT '{}'"),
+                    )
+                    .col(
+                        ColumnDef::new(MemoListView::Attachments)
+                            .json_binary()
+                            .not_null()
+                            .extra("DEFAULT '[]'"),
+                    )
+                    .col(
+                        ColumnDef::new(MemoListView::RefreshedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .extra("DEFAULT NOW()"),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_memo_list_view_memo_id")
+                            .from(MemoListView::Table, MemoListView::MemoId)
+                            .to(Memos::Table, Memos::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let db = manager.get_connection();
+        // Finds the rows still pointing at a memo whose links are gone.
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_memo_list_view_blocked_by \
+             ON memo_list_view USING gin (blocked_by)",
+        )
+        .await?;
+        db.execute_unprepared(
+            "CREATE INDEX IF NOT EXISTS idx_memo_list_view_blocks \
+             ON memo_list_view USING gin (blocks)",
+        )
+        .await?;
+
+        // Links to memos inside their undo window are left out, as in the
+        // API's responses.
+        db.execute_unprepared(
+            "INSERT INTO memo_list_view (memo_id, blocked_by, blocks, attachments) \
+             SELECT m.id, \
+                    ARRAY(SELECT d.blocker_id FROM memo_dependencies d \
+                          JOIN memos b ON b.id = d.blocker_id AND b.undo_token IS NULL \
+                          WHERE d.memo_id = m.id ORDER BY d.created_at, d.blocker_id), \
+                    ARRAY(SELECT d.memo_id FROM memo_dependencies d \
+                          JOIN memos w ON w.id = d.memo_id AND w.undo_token IS NULL \
+                          WHERE d.blocker_id = m.id ORDER BY d.created_at, d.memo_id), \
+                    COALESCE((SELECT jsonb_agg(jsonb_build_object( \
+                                  'id', a.id, 'memo_id', a.memo_id, 'file_name', a.file_name, \
+                                  'content_type', a.content_type, 'size_bytes', a.size_bytes, \
+                                  'width', a.width, 'height', a.height) \
+                                  ORDER BY a.created_at, a.id) \
+                              FROM memo_attachments a WHERE a.memo_id = m.id), '[]'::jsonb) \
+             FROM memos m \
+             ON CONFLICT (memo_id) DO NOTHING",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MemoListView::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MemoListView {
+    Table,
+    MemoId,
+    BlockedBy,
+    Blocks,
+    Attachments,
+    RefreshedAt,
+}
+
+#[derive(DeriveIden)]
+enum Memos {
+    Table,
+    Id,
+}
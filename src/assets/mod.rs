@@ -0,0 +1,138 @@
+//! Static files compiled into the binary.
+//!
+//! Release builds embed everything under `static/`; debug builds read the
+//! folder from disk so edits show up without a rebuild. Templates link assets
+//! through [`asset_url`], which puts a content hash in the file name so the
+//! response can be cached forever and still change on every deploy.
+
+use std::{borrow::Cow, collections::HashMap, sync::LazyLock};
+
+use rust_embed::RustEmbed;
+
+/// URL prefix the assets are served under.
+pub const PREFIX: &str = "/static";
+
+#[derive(RustEmbed)]
+#[folder = "static/"]
+struct StaticAssets;
+
+/// A resolved asset ready to be written to a response.
+pub struct Asset {
+    pub data: Cow<'static, [u8]>,
+    pub content_type: String,
+    /// Hex-encoded SHA-256 of the content, used as the `ETag`.
+    pub hash: String,
+    /// Whether the request used the hashed file name.
+    pub fingerprinted: bool,
+}
+
+/// Maps logical paths (`css/style.css`) to hashed ones (`css/style.1a2b3c4d.css`)
+/// and back.
+struct Manifest {
+    hashed: HashMap<String, String>,
+    logical: HashMap<String, String>,
+}
+
+static MANIFEST: LazyLock<Manifest> = LazyLock::new(|| {
+    let mut hashed = HashMap::new();
+    let mut logical = HashMap::new();
+    for path in StaticAssets::iter() {
+        let Some(file) = StaticAssets::get(&path) else {
+            continue;
+        };
+        let name = fingerprint(&path, &hex(&file.metadata.sha256_hash()));
+        logical.insert(name.clone(), path.to_string());
+        hashed.insert(path.to_string(), name);
+    }
+    Manifest { hashed, logical }
+});
+
+/// Public URL of a static asset, with its content hash in the file name.
+/// Unknown paths are returned unhashed so a typo shows up as a 404 rather
+/// than a panic.
+pub fn asset_url(path: &str) -> String {
+    match MANIFEST.hashed.get(path) {
+        Some(hashed) => format!("{}/{}", PREFIX, hashed),
+        None => format!("{}/{}", PREFIX, path),
+    }
+}
+
+/// Looks up an asset by its hashed or logical path (relative to [`PREFIX`]).
+pub fn get(path: &str) -> Option<Asset> {
+    let (logical, fingerprinted) = match MANIFEST.logical.get(path) {
+        Some(logical) => (logical.as_str(), true),
+        None => (path, false),
+    };
+    let file = StaticAssets::get(logical)?;
+
+    Some(Asset {
+        content_type: file.metadata.mimetype().to_string(),
+        hash: hex(&file.metadata.sha256_hash()),
+        data: file.data,
+        fingerprinted,
+    })
+}
+
+/// Inserts the first eight hex digits of `hash` before the file extension.
+fn fingerprint(path: &str, hash: &str) -> String {
+    let short = &hash[..8];
+    let (dir, file) = match path.rsplit_once('/') {
+        Some((dir, file)) => (Some(dir), file),
+        None => (None, path),
+    };
+    let file = match file.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => format!("{}.{}.{}", stem, short, ext),
+        _ => format!("{}.{}", file, short),
+    };
+    match dir {
+        Some(dir) => format!("{}/{}", dir, file),
+        None => file,
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_goes_before_the_extension() {
+        assert_eq!(
+            fingerprint("css/style.css", "0123456789abcdef"),
+            "css/style.01234567.css"
+        );
+        assert_eq!(fingerprint("sw.js", "0123456789abcdef"), "sw.01234567.js");
+        assert_eq!(
+            fingerprint("LICENSE", "0123456789abcdef"),
+            "LICENSE.01234567"
+        );
+        assert_eq!(
+            fingerprint("img/.hidden", "0123456789abcdef"),
+            "img/.hidden.01234567"
+        );
+    }
+
+    #[test]
+    fn test_hashed_and_logical_paths_resolve_to_the_same_file() {
+        let url = asset_url("css/style.css");
+        assert_ne!(url, "/static/css/style.css");
+
+        let hashed = get(url.trim_start_matches("/static/")).expect("hashed asset");
+        assert!(hashed.fingerprinted);
+        assert_eq!(hashed.content_type, "text/css");
+
+        let plain = get("css/style.css").expect("logical asset");
+        assert!(!plain.fingerprinted);
+        assert_eq!(plain.hash, hashed.hash);
+    }
+
+    #[test]
+    fn test_unknown_assets_are_not_found() {
+        assert_eq!(asset_url("missing.css"), "/static/missing.css");
+        assert!(get("missing.css").is_none());
+        assert!(get("../Cargo.toml").is_none());
+    }
+}
@@ -0,0 +1,245 @@
+//! Image attachments of memos.
+//!
+//! Uploaded files are decoded here to check that they really are images of a
+//! supported format, and get a JPEG thumbnail for memo items. The original
+//! bytes are kept as uploaded. Decoding is CPU-bound, so callers run
+//! [`prepare`] on a blocking thread, after the uploads passed [`scan`].
+
+pub mod scan;
+
+use image::{ImageError, ImageFormat, ImageReader, Limits, codecs::jpeg::JpegEncoder};
+use std::io::Cursor;
+
+use crate::error::{AppError, FieldError};
+
+/// Images one memo can be created with.
+pub const MAX_IMAGES_PER_MEMO: usize = 5;
+/// Longest side of a thumbnail, in pixels.
+pub const THUMBNAIL_SIZE: u32 = 320;
+/// Content type of every thumbnail.
+pub const THUMBNAIL_CONTENT_TYPE: &str = "image/jpeg";
+
+/// Longest side of an image accepted for upload, in pixels.
+pub const MAX_IMAGE_DIMENSION: u32 = 8192;
+/// Most memory decoding one image may allocate. Headers can declare
+/// dimensions far beyond what the file holds, so this is enforced before the
+/// pixel buffer is allocated.
+const MAX_DECODE_ALLOC: u64 = 256 * 1024 * 1024;
+
+const THUMBNAIL_QUALITY: u8 = 80;
+const MAX_FILE_NAME_LEN: usize = 255;
+
+/// A file as received from a form.
+#[derive(Debug, Clone)]
+pub struct Upload {
+    /// Name the browser sent, if any; only used for display
+    pub file_name: Option<String>,
+    pub data: Vec<u8>,
+}
+
+/// An upload checked and thumbnailed, ready to store.
+#[derive(Debug, Clone)]
+pub struct NewAttachment {
+    pub file_name: String,
+    /// Detected from the content, not taken from the request
+    pub content_type: &'static str,
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+    pub thumbnail: Vec<u8>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AttachmentError {
+    #[error("At most {max} images can be attached")]
+    TooMany { max: usize },
+    #[error("{file_name} is not a PNG, JPEG, GIF or WebP image")]
+    UnsupportedType { file_name: String },
+    #[error("{file_name} is larger than {max}x{max} pixels")]
+    TooLarge { file_name: String, max: u32 },
+    #[error("{file_name} could not be read as an image: {reason}")]
+    Unreadable { file_name: String, reason: String },
+}
+
+impl AttachmentError {
+    fn code(&self) -> &'static str {
+        match self {
+            AttachmentError::TooMany { .. } => "too_many",
+            AttachmentError::UnsupportedType { .. } => "image_type",
+            AttachmentError::TooLarge { .. } => "image_size",
+            AttachmentError::Unreadable { .. } => "image",
+        }
+    }
+}
+
+impl From<AttachmentError> for AppError {
+    fn from(err: AttachmentError) -> Self {
+        AppError::InvalidFields {
+            message: err.to_string(),
+            errors: vec![FieldError {
+                field: "images".to_string(),
+                code: err.code().to_string(),
+                message: err.to_string(),
+            }],
+        }
+    }
+}
+
+/// Checks that `upload` is a supported image and makes its thumbnail.
+pub fn prepare(upload: Upload) -> Result<NewAttachment, AttachmentError> {
+    let file_name = file_name(upload.file_name.as_deref());
+
+    let mut reader = ImageReader::new(Cursor::new(&upload.data))
+        .with_guessed_format()
+        .map_err(|e| unreadable(&file_name, e))?;
+    let content_type = match reader.format() {
+        Some(ImageFormat::Png) => "image/png",
+        Some(ImageFormat::Jpeg) => "image/jpeg",
+        Some(ImageFormat::Gif) => "image/gif",
+        Some(ImageFormat::WebP) => "image/webp",
+        _ => return Err(AttachmentError::UnsupportedType { file_name }),
+    };
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_DIMENSION);
+    limits.max_image_height = Some(MAX_IMAGE_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODE_ALLOC);
+    reader.limits(limits);
+    let image = reader.decode().map_err(|e| match e {
+        ImageError::Limits(_) => AttachmentError::TooLarge {
+            file_name: file_name.clone(),
+            max: MAX_IMAGE_DIMENSION,
+        },
+        e => unreadable(&file_name, e),
+    })?;
+
+    let mut thumbnail = Vec::new();
+    let small = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).into_rgb8();
+    JpegEncoder::new_with_quality(&mut thumbnail, THUMBNAIL_QUALITY)
+        .encode_image(&small)
+        .map_err(|e| unreadable(&file_name, e))?;
+
+    Ok(NewAttachment {
+        file_name,
+        content_type,
+        width: image.width(),
+        height: image.height(),
+        data: upload.data,
+        thumbnail,
+    })
+}
+
+/// The non-empty `uploads`, as long as there are not too many of them;
+/// file inputs left blank arrive as empty files.
+pub fn select(uploads: Vec<Upload>) -> Result<Vec<Upload>, AttachmentError> {
+    let uploads: Vec<Upload> = uploads
+        .into_iter()
+        .filter(|upload| !upload.data.is_empty())
+        .collect();
+    if uploads.len() > MAX_IMAGES_PER_MEMO {
+        return Err(AttachmentError::TooMany {
+            max: MAX_IMAGES_PER_MEMO,
+        });
+    }
+    Ok(uploads)
+}
+
+/// [`prepare`]s each of the [`select`]ed `uploads`.
+pub fn prepare_all(uploads: Vec<Upload>) -> Result<Vec<NewAttachment>, AttachmentError> {
+    select(uploads)?.into_iter().map(prepare).collect()
+}
+
+/// The last path segment of a client file name, without control
+/// characters and cut to the column size; `image` when nothing is left.
+pub fn file_name(raw: Option<&str>) -> String {
+    let name: String = raw
+        .unwrap_or_default()
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILE_NAME_LEN)
+        .collect();
+    match name.trim() {
+        "" => "image".to_string(),
+        name => name.to_string(),
+    }
+}
+
+fn unreadable(file_name: &str, error: impl std::fmt::Display) -> AttachmentError {
+    AttachmentError::Unreadable {
+        file_name: file_name.to_string(),
+        reason: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbImage};
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut data = Vec::new();
+        DynamicImage::ImageRgb8(RgbImage::new(width, height))
+            .write_to(&mut Cursor::new(&mut data), ImageFormat::Png)
+            .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_thumbnails_keep_aspect_ratio() {
+        let attachment = prepare(Upload {
+            file_name: Some("C:\\photos\\wide.png".to_string()),
+            data: png(800, 400),
+        })
+        .unwrap();
+
+        assert_eq!(attachment.file_name, "wide.png");
+        assert_eq!(attachment.content_type, "image/png");
+        assert_eq!((attachment.width, attachment.height), (800, 400));
+        let thumbnail = image::load_from_memory(&attachment.thumbnail).unwrap();
+        assert_eq!((thumbnail.width(), thumbnail.height()), (320, 160));
+    }
+
+    #[test]
+    fn test_non_images_are_rejected() {
+        let err = prepare(Upload {
+            file_name: Some("notes.png".to_string()),
+            data: b"just text".to_vec(),
+        })
+        .unwrap_err();
+        assert!(matches!(err, AttachmentError::UnsupportedType { .. }));
+    }
+
+    #[test]
+    fn test_oversized_images_are_rejected() {
+        let err = prepare(Upload {
+            file_name: Some("panorama.png".to_string()),
+            data: png(MAX_IMAGE_DIMENSION + 1, 1),
+        })
+        .unwrap_err();
+        assert!(matches!(err, AttachmentError::TooLarge { .. }));
+        assert!(matches!(
+            AppError::from(err),
+            AppError::InvalidFields { .. }
+        ));
+    }
+
+    #[test]
+    fn test_empty_inputs_are_skipped_before_counting() {
+        let mut uploads = vec![
+            Upload {
+                file_name: None,
+                data: Vec::new(),
+            };
+            MAX_IMAGES_PER_MEMO
+        ];
+        uploads.push(Upload {
+            file_name: None,
+            data: png(1, 1),
+        });
+        let attachments = prepare_all(uploads).unwrap();
+        assert_eq!(attachments.len(), 1);
+        assert_eq!(attachments[0].file_name, "image");
+    }
+}
@@ -0,0 +1,384 @@
+//! Checks run on uploaded files before anything is stored.
+//!
+//! Each [`UploadScanner`] looks at one file and either lets it through or
+//! rejects it. [`UploadScanners`] runs the configured ones in order: the file
+//! extension allowlist, magic-byte sniffing of the content, and, when
+//! `CLAMAV_ADDRESS` is set, a ClamAV daemon. More can be plugged in with
+//! [`UploadScanners::with`].
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::config::settings::UploadScanConfig;
+use crate::error::AppError;
+
+/// Content types attachments may have.
+pub const IMAGE_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// Bytes sent to clamd per `INSTREAM` chunk.
+const CLAMAV_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Why a scanner turned a file away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// The file name has no extension or one outside the allowlist
+    Extension(Option<String>),
+    /// The content is not of an allowed type; `detected` names what it looks like
+    FileType { detected: Option<&'static str> },
+    /// The virus scanner matched `signature`
+    Malware { signature: String },
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rejection::Extension(None) => f.write_str("file name has no extension"),
+            Rejection::Extension(Some(extension)) => {
+                write!(f, ".{} files are not accepted", extension)
+            }
+            Rejection::FileType { detected: None } => f.write_str("content type not recognized"),
+            Rejection::FileType {
+                detected: Some(detected),
+            } => write!(f, "content looks like {}", detected),
+            Rejection::Malware { signature } => write!(f, "malware found ({})", signature),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ScanError {
+    #[error("{0}")]
+    Rejected(Rejection),
+
+    /// The scanner could not give a verdict; the upload is refused
+    #[error("upload scanner failed: {0}")]
+    Unavailable(String),
+}
+
+type ScanFuture<'a> = Pin<Box<dyn Future<Output = Result<(), ScanError>> + Send + 'a>>;
+
+/// Decides whether an uploaded file may be stored.
+pub trait UploadScanner: Send + Sync {
+    /// `file_name` is the sanitized client name, `data` the whole file.
+    fn scan<'a>(&'a self, file_name: &'a str, data: &'a [u8]) -> ScanFuture<'a>;
+}
+
+/// The scanners every upload goes through; cheap to clone.
+#[derive(Clone, Default)]
+pub struct UploadScanners {
+    scanners: Vec<Arc<dyn UploadScanner>>,
+}
+
+impl UploadScanners {
+    /// Extension allowlist and magic bytes, plus ClamAV when configured.
+    pub fn from_config(config: &UploadScanConfig) -> Self {
+        let scanners = Self::default()
+            .with(ExtensionAllowlist::new(&config.allowed_extensions))
+            .with(MagicBytes::new(&IMAGE_TYPES));
+        match &config.clamav_address {
+            Some(address) => scanners.with(ClamAv::new(
+                address,
+                Duration::from_millis(config.clamav_timeout_ms),
+            )),
+            None => scanners,
+        }
+    }
+
+    /// Runs `scanner` after the ones already added.
+    pub fn with(mut self, scanner: impl UploadScanner + 'static) -> Self {
+        self.scanners.push(Arc::new(scanner));
+        self
+    }
+
+    /// Passes `data` to each scanner in turn, stopping at the first objection.
+    pub async fn scan(&self, file_name: &str, data: &[u8]) -> Result<(), AppError> {
+        for scanner in &self.scanners {
+            match scanner.scan(file_name, data).await {
+                Ok(()) => {}
+                Err(ScanError::Rejected(reason)) => {
+                    tracing::warn!(file_name, %reason, "Upload rejected");
+                    return Err(AppError::UploadRejected {
+                        file_name: file_name.to_string(),
+                        reason: reason.to_string(),
+                    });
+                }
+                Err(ScanError::Unavailable(e)) => {
+                    return Err(AppError::Internal(format!("Upload scanner failed: {}", e)));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Accepts only file names ending in one of the listed extensions.
+pub struct ExtensionAllowlist {
+    extensions: Vec<String>,
+}
+
+impl ExtensionAllowlist {
+    pub fn new(extensions: &[String]) -> Self {
+        Self {
+            extensions: extensions
+                .iter()
+                .map(|extension| extension.trim_start_matches('.').to_ascii_lowercase())
+                .collect(),
+        }
+    }
+}
+
+impl UploadScanner for ExtensionAllowlist {
+    fn scan<'a>(&'a self, file_name: &'a str, _data: &'a [u8]) -> ScanFuture<'a> {
+        let extension = file_name
+            .rsplit_once('.')
+            .map(|(_, extension)| extension.to_ascii_lowercase());
+        let result = match extension {
+            Some(extension) if self.extensions.contains(&extension) => Ok(()),
+            extension => Err(ScanError::Rejected(Rejection::Extension(extension))),
+        };
+        Box::pin(std::future::ready(result))
+    }
+}
+
+/// Accepts only content whose leading bytes match one of the allowed types,
+/// whatever the file is called.
+pub struct MagicBytes {
+    allowed: Vec<&'static str>,
+}
+
+impl MagicBytes {
+    pub fn new(allowed: &[&'static str]) -> Self {
+        Self {
+            allowed: allowed.to_vec(),
+        }
+    }
+}
+
+impl UploadScanner for MagicBytes {
+    fn scan<'a>(&'a self, _file_name: &'a str, data: &'a [u8]) -> ScanFuture<'a> {
+        let result = match sniff(data) {
+            Some(detected) if self.allowed.contains(&detected) => Ok(()),
+            detected => Err(ScanError::Rejected(Rejection::FileType { detected })),
+        };
+        Box::pin(std::future::ready(result))
+    }
+}
+
+/// The type `data` looks like from its first bytes. Besides the image
+/// formats this knows executables, scripts, archives and markup, so
+/// rejections can say what was sent.
+pub fn sniff(data: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: [(&[u8], &str); 13] = [
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"MZ", "application/x-msdownload"),
+        (b"\x7fELF", "application/x-elf"),
+        (b"\xfe\xed\xfa\xce", "application/x-mach-binary"),
+        (b"\xfe\xed\xfa\xcf", "application/x-mach-binary"),
+        (b"\xce\xfa\xed\xfe", "application/x-mach-binary"),
+        (b"\xcf\xfa\xed\xfe", "application/x-mach-binary"),
+        (b"#!", "text/x-shellscript"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+    ];
+
+    if let Some((_, content_type)) = SIGNATURES.iter().find(|(magic, _)| data.starts_with(magic)) {
+        return Some(content_type);
+    }
+    if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    // HTML, SVG and XML can all carry scripts
+    if data.trim_ascii_start().starts_with(b"<") {
+        return Some("text/html");
+    }
+    None
+}
+
+/// Streams files to a clamd daemon with `INSTREAM` and rejects those it
+/// reports as infected. The address is `host:port`, or a Unix socket path.
+pub struct ClamAv {
+    address: String,
+    timeout: Duration,
+}
+
+impl ClamAv {
+    pub fn new(address: &str, timeout: Duration) -> Self {
+        Self {
+            address: address.to_string(),
+            timeout,
+        }
+    }
+
+    async fn verdict(&self, data: &[u8]) -> std::io::Result<String> {
+        #[cfg(unix)]
+        if self.address.starts_with('/') {
+            let stream = tokio::net::UnixStream::connect(&self.address).await?;
+            return instream(stream, data).await;
+        }
+        let stream = tokio::net::TcpStream::connect(&self.address).await?;
+        instream(stream, data).await
+    }
+}
+
+impl UploadScanner for ClamAv {
+    fn scan<'a>(&'a self, _file_name: &'a str, data: &'a [u8]) -> ScanFuture<'a> {
+        Box::pin(async move {
+            let reply = tokio::time::timeout(self.timeout, self.verdict(data))
+                .await
+                .map_err(|_| ScanError::Unavailable("clamd timed out".to_string()))?
+                .map_err(|e| ScanError::Unavailable(format!("clamd: {}", e)))?;
+
+            let reply = reply.strip_prefix("stream: ").unwrap_or(&reply);
+            if reply == "OK" {
+                Ok(())
+            } else if let Some(signature) = reply.strip_suffix(" FOUND") {
+                Err(ScanError::Rejected(Rejection::Malware {
+                    signature: signature.to_string(),
+                }))
+            } else {
+                Err(ScanError::Unavailable(format!("clamd: {}", reply)))
+            }
+        })
+    }
+}
+
+/// Sends `data` over the clamd `INSTREAM` protocol and returns the reply.
+async fn instream<S>(mut stream: S, data: &[u8]) -> std::io::Result<String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    stream.write_all(b"zINSTREAM\0").await?;
+    for chunk in data.chunks(CLAMAV_CHUNK_SIZE) {
+        stream
+            .write_all(&(chunk.len() as u32).to_be_bytes())
+            .await?;
+        stream.write_all(chunk).await?;
+    }
+    stream.write_all(&0u32.to_be_bytes()).await?;
+    stream.flush().await?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    let reply = reply.split(|&b| b == 0).next().unwrap_or_default();
+    Ok(String::from_utf8_lossy(reply).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    fn rejection(result: Result<(), ScanError>) -> Rejection {
+        match result {
+            Err(ScanError::Rejected(reason)) => reason,
+            other => panic!("expected a rejection, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_extensions_outside_the_allowlist_are_rejected() {
+        let allowlist = ExtensionAllowlist::new(&["png".to_string(), ".JPG".to_string()]);
+
+        assert!(allowlist.scan("photo.PNG", b"").await.is_ok());
+        assert!(allowlist.scan("photo.jpg", b"").await.is_ok());
+        assert_eq!(
+            rejection(allowlist.scan("photo.png.exe", b"").await),
+            Rejection::Extension(Some("exe".to_string()))
+        );
+        assert_eq!(
+            rejection(allowlist.scan("photo", b"").await),
+            Rejection::Extension(None)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_content_is_judged_by_its_magic_bytes() {
+        let sniffer = MagicBytes::new(&IMAGE_TYPES);
+
+        assert!(
+            sniffer
+                .scan("a.png", b"\x89PNG\r\n\x1a\nrest")
+                .await
+                .is_ok()
+        );
+        assert!(
+            sniffer
+                .scan("a.webp", b"RIFF\0\0\0\0WEBPVP8 ")
+                .await
+                .is_ok()
+        );
+        assert_eq!(
+            rejection(sniffer.scan("a.png", b"MZ\x90\0").await),
+            Rejection::FileType {
+                detected: Some("application/x-msdownload")
+            }
+        );
+        assert_eq!(
+            rejection(sniffer.scan("a.gif", b"  <svg onload=alert(1)>").await),
+            Rejection::FileType {
+                detected: Some("text/html")
+            }
+        );
+        assert_eq!(
+            rejection(sniffer.scan("a.jpg", b"plain text").await),
+            Rejection::FileType { detected: None }
+        );
+    }
+
+    /// A clamd stand-in that reads one `INSTREAM` request and answers `reply`.
+    async fn fake_clamd(reply: &'static str) -> (String, tokio::task::JoinHandle<Vec<u8>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut command = [0u8; 10];
+            socket.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+            let mut received = Vec::new();
+            loop {
+                let len = socket.read_u32().await.unwrap() as usize;
+                if len == 0 {
+                    break;
+                }
+                let mut chunk = vec![0; len];
+                socket.read_exact(&mut chunk).await.unwrap();
+                received.extend(chunk);
+            }
+            socket.write_all(reply.as_bytes()).await.unwrap();
+            received
+        });
+        (address, server)
+    }
+
+    #[tokio::test]
+    async fn test_clamav_verdicts() {
+        let (address, server) = fake_clamd("stream: OK\0").await;
+        let clamav = ClamAv::new(&address, Duration::from_secs(5));
+        assert!(clamav.scan("a.png", b"clean bytes").await.is_ok());
+        assert_eq!(server.await.unwrap(), b"clean bytes");
+
+        let (address, _) = fake_clamd("stream: Win.Test.EICAR_HDB-1 FOUND\0").await;
+        let clamav = ClamAv::new(&address, Duration::from_secs(5));
+        assert_eq!(
+            rejection(clamav.scan("a.png", b"X5O!P%@AP").await),
+            Rejection::Malware {
+                signature: "Win.Test.EICAR_HDB-1".to_string()
+            }
+        );
+
+        let (address, _) = fake_clamd("INSTREAM size limit exceeded. ERROR\0").await;
+        let clamav = ClamAv::new(&address, Duration::from_secs(5));
+        assert!(matches!(
+            clamav.scan("a.png", b"big").await,
+            Err(ScanError::Unavailable(_))
+        ));
+    }
+}
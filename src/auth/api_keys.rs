@@ -0,0 +1,293 @@
+//! API keys from `API_KEYS`, their monthly quotas and request accounting.
+//!
+//! Requests are counted in memory and added to `api_key_usage` by
+//! [`ApiKeys::flush`] every `API_KEY_USAGE_FLUSH_SECS`, which also reads back
+//! what other replicas recorded. Quotas are checked against those counts, so
+//! with several replicas a key may overshoot by the requests the others made
+//! since their last flush.
+
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use sea_orm::DbErr;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::config::settings::{ApiKeyConfig, ApiKeysConfig};
+use crate::db::GuardedConnection;
+use crate::repository::ApiKeyUsageRepository;
+
+/// Where a key stands against its monthly quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuotaStatus {
+    /// Requests allowed this month
+    pub limit: u64,
+    /// Requests left this month
+    pub remaining: u64,
+    /// Seconds until the quota resets at the start of next month (UTC)
+    pub reset_secs: u64,
+}
+
+/// Outcome of charging a request to a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaCheck {
+    /// The key has no quota; the request was counted
+    Unlimited,
+    /// The request was counted and fits the quota
+    Within(QuotaStatus),
+    /// The quota is used up; the request was not counted
+    Exceeded(QuotaStatus),
+}
+
+/// A key's requests in the current month.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonthUsage {
+    pub period_start: NaiveDate,
+    pub resets_at: DateTime<Utc>,
+    /// Requests per day, oldest first; days without requests are left out
+    pub days: Vec<(NaiveDate, u64)>,
+}
+
+impl MonthUsage {
+    pub fn requests(&self) -> u64 {
+        self.days.iter().map(|(_, requests)| requests).sum()
+    }
+}
+
+#[derive(Debug, Default)]
+struct KeyUsage {
+    /// Month `recorded` belongs to
+    month: NaiveDate,
+    /// Requests of `month` in `api_key_usage` as of the last flush
+    recorded: u64,
+    /// Requests per day not written yet
+    pending: BTreeMap<NaiveDate, u64>,
+}
+
+impl KeyUsage {
+    fn used(&self) -> u64 {
+        self.recorded
+            + self
+                .pending
+                .range(self.month..)
+                .map(|(_, n)| n)
+                .sum::<u64>()
+    }
+}
+
+/// The configured keys and their usage counters; cheap to clone.
+#[derive(Clone)]
+pub struct ApiKeys {
+    keys: Arc<[ApiKeyConfig]>,
+    db: GuardedConnection,
+    usage: Arc<Mutex<HashMap<String, KeyUsage>>>,
+}
+
+impl ApiKeys {
+    pub fn new(config: &ApiKeysConfig, db: GuardedConnection) -> Self {
+        Self {
+            keys: config.keys.clone().into(),
+            db,
+            usage: Arc::default(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// The key whose secret is `provided`, compared in constant time.
+    pub fn authenticate(&self, provided: &str) -> Option<&ApiKeyConfig> {
+        self.keys
+            .iter()
+            .find(|key| super::constant_time_eq(provided.as_bytes(), key.key.as_bytes()))
+    }
+
+    pub fn find(&self, id: &str) -> Option<&ApiKeyConfig> {
+        self.keys.iter().find(|key| key.id == id)
+    }
+
+    /// Counts one request against `key`, unless that would exceed its quota.
+    pub async fn charge(&self, key: &ApiKeyConfig) -> Result<QuotaCheck, DbErr> {
+        let now = Utc::now();
+        let today = now.date_naive();
+        let month = month_start(today);
+
+        if self
+            .counters()
+            .get(&key.id)
+            .is_none_or(|usage| usage.month != month)
+        {
+            let recorded = self.recorded_since(&key.id, month).await?;
+            let mut counters = self.counters();
+            let usage = counters.entry(key.id.clone()).or_default();
+            if usage.month != month {
+                usage.month = month;
+                usage.recorded = recorded;
+            }
+        }
+
+        let mut counters = self.counters();
+        let usage = counters.entry(key.id.clone()).or_default();
+        let used = usage.used();
+        let check = match key.monthly_quota {
+            None => QuotaCheck::Unlimited,
+            Some(limit) => {
+                let reset_secs = (next_month_start(today) - now).num_seconds().max(1) as u64;
+                if used >= limit {
+                    return Ok(QuotaCheck::Exceeded(QuotaStatus {
+                        limit,
+                        remaining: 0,
+                        reset_secs,
+                    }));
+                }
+                QuotaCheck::Within(QuotaStatus {
+                    limit,
+                    remaining: limit - used - 1,
+                    reset_secs,
+                })
+            }
+        };
+        *usage.pending.entry(today).or_default() += 1;
+        Ok(check)
+    }
+
+    /// Writes the pending counts to `api_key_usage` and reloads this
+    /// month's totals, which include other replicas' requests. Counts that
+    /// could not be written are kept for the next flush.
+    #[tracing::instrument(skip(self))]
+    pub async fn flush(&self) -> Result<(), DbErr> {
+        let pending: Vec<(String, NaiveDate, u64)> = {
+            let mut counters = self.counters();
+            let mut pending = Vec::new();
+            for (id, usage) in counters.iter_mut() {
+                // Counted as recorded already, so quota checks do not dip
+                // while the write is under way
+                usage.recorded = usage.used();
+                pending.extend(
+                    std::mem::take(&mut usage.pending)
+                        .into_iter()
+                        .map(|(day, requests)| (id.clone(), day, requests)),
+                );
+            }
+            pending
+        };
+
+        let rows = pending
+            .iter()
+            .map(|(id, day, requests)| (id.clone(), *day, *requests as i64))
+            .collect();
+        if let Err(e) = ApiKeyUsageRepository::add(&self.db, rows).await {
+            let mut counters = self.counters();
+            for (id, day, requests) in pending {
+                let usage = counters.entry(id).or_default();
+                if day >= usage.month {
+                    usage.recorded = usage.recorded.saturating_sub(requests);
+                }
+                *usage.pending.entry(day).or_default() += requests;
+            }
+            return Err(e);
+        }
+
+        let month = month_start(Utc::now().date_naive());
+        let totals: HashMap<String, i64> = ApiKeyUsageRepository::totals_since(&self.db, month)
+            .await?
+            .into_iter()
+            .collect();
+        for (id, usage) in self.counters().iter_mut() {
+            if usage.month == month {
+                usage.recorded = totals.get(id).copied().unwrap_or_default() as u64;
+            }
+        }
+        Ok(())
+    }
+
+    /// Spawns a task that [`flush`](Self::flush)es every `every`.
+    pub fn start_flusher(&self, every: Duration) -> JoinHandle<()> {
+        let keys = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(every);
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                if let Err(e) = keys.flush().await {
+                    tracing::warn!(error = %e, "Failed to record API key usage, retrying later");
+                }
+            }
+        })
+    }
+
+    /// Requests made with `id` this month, recorded or not.
+    pub async fn month_usage(&self, id: &str) -> Result<MonthUsage, DbErr> {
+        let today = Utc::now().date_naive();
+        let month = month_start(today);
+
+        let mut days: BTreeMap<NaiveDate, u64> =
+            ApiKeyUsageRepository::find_days(&self.db, id, month)
+                .await?
+                .into_iter()
+                .map(|row| (row.day, row.request_count as u64))
+                .collect();
+        if let Some(usage) = self.counters().get(id) {
+            for (day, requests) in usage.pending.range(month..) {
+                *days.entry(*day).or_default() += requests;
+            }
+        }
+
+        Ok(MonthUsage {
+            period_start: month,
+            resets_at: next_month_start(today),
+            days: days.into_iter().collect(),
+        })
+    }
+
+    async fn recorded_since(&self, id: &str, month: NaiveDate) -> Result<u64, DbErr> {
+        let days = ApiKeyUsageRepository::find_days(&self.db, id, month).await?;
+        Ok(days.iter().map(|row| row.request_count as u64).sum())
+    }
+
+    fn counters(&self) -> MutexGuard<'_, HashMap<String, KeyUsage>> {
+        self.usage.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+fn month_start(day: NaiveDate) -> NaiveDate {
+    day.with_day(1).unwrap_or(day)
+}
+
+fn next_month_start(day: NaiveDate) -> DateTime<Utc> {
+    let next = month_start(day) + Months::new(1);
+    next.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_months_start_on_the_first() {
+        let day = NaiveDate::from_ymd_opt(2025, 12, 17).unwrap();
+        assert_eq!(
+            month_start(day),
+            NaiveDate::from_ymd_opt(2025, 12, 1).unwrap()
+        );
+        assert_eq!(
+            next_month_start(day).to_rfc3339(),
+            "2026-01-01T00:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_usage_counts_only_pending_days_of_the_month() {
+        let month = NaiveDate::from_ymd_opt(2025, 2, 1).unwrap();
+        let usage = KeyUsage {
+            month,
+            recorded: 10,
+            pending: BTreeMap::from([
+                (NaiveDate::from_ymd_opt(2025, 1, 31).unwrap(), 4),
+                (NaiveDate::from_ymd_opt(2025, 2, 3).unwrap(), 2),
+            ]),
+        };
+        assert_eq!(usage.used(), 12);
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt;
+
+pub mod api_keys;
+pub mod signed_url;
+
+pub use api_keys::{ApiKeys, QuotaCheck, QuotaStatus};
+pub use signed_url::{SignedRequest, SignedUrl, UrlSigner};
+
+/// The authenticated caller of a request.
+///
+/// Authentication middleware inserts it into the request extensions; anything
+/// that budgets or audits per caller (e.g. rate limiting) reads it from there
+/// and falls back to the peer IP for anonymous requests. Such middleware must be
+/// wrapped outside of its consumers so the extension is set before they run.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Principal {
+    ApiKey(String),
+    User(String),
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Principal::ApiKey(id) => write!(f, "api-key:{}", id),
+            Principal::User(id) => write!(f, "user:{}", id),
+        }
+    }
+}
+
+/// Compares secrets without returning early on the first differing byte.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
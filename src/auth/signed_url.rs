@@ -0,0 +1,218 @@
+//! HMAC-signed, expiring URLs.
+//!
+//! A signed URL carries `expires` (Unix seconds) and `signature`, an
+//! HMAC-SHA256 over the path and expiry. Anyone holding the URL may fetch the
+//! path until it expires, without further credentials. Other query parameters
+//! are not covered by the signature, so handlers behind [`SignedRequest`] must
+//! not let them widen what is returned.
+
+use std::future::{Ready, ready};
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, web};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use serde::Deserialize;
+use sha2::Sha256;
+
+use crate::{config::settings::UrlSigningConfig, error::AppError, state::AppState};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// A path with its signature, valid until `expires_at`.
+#[derive(Debug, Clone)]
+pub struct SignedUrl {
+    /// Path and query, relative to the application root
+    pub url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Clone)]
+pub struct UrlSigner {
+    key: Vec<u8>,
+    max_ttl: Duration,
+}
+
+impl UrlSigner {
+    pub fn new(key: &[u8], max_ttl: Duration) -> Self {
+        Self {
+            key: key.to_vec(),
+            max_ttl,
+        }
+    }
+
+    /// Uses `URL_SIGNING_KEY`, or a random per-process key when it is unset.
+    pub fn from_config(config: &UrlSigningConfig) -> Self {
+        let max_ttl = Duration::seconds(config.max_ttl_secs as i64);
+        match &config.key {
+            Some(key) => Self::new(key.as_bytes(), max_ttl),
+            None => {
+                tracing::warn!(
+                    "URL_SIGNING_KEY is not set; signed URLs only work on this instance until restart"
+                );
+                Self::new(&rand::random::<[u8; 32]>(), max_ttl)
+            }
+        }
+    }
+
+    pub fn max_ttl(&self) -> Duration {
+        self.max_ttl
+    }
+
+    /// Signs `path` for `ttl` from `now`. A `ttl` outside `(0, max_ttl]` is a
+    /// validation error.
+    pub fn sign(
+        &self,
+        path: &str,
+        ttl: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<SignedUrl, AppError> {
+        if ttl <= Duration::zero() || ttl > self.max_ttl {
+            return Err(AppError::Validation(format!(
+                "ttl must be between 1 and {} seconds",
+                self.max_ttl.num_seconds()
+            )));
+        }
+
+        let expires_at = now + ttl;
+        let expires = expires_at.timestamp();
+        let signature = URL_SAFE_NO_PAD.encode(self.mac(path, expires).finalize().into_bytes());
+
+        Ok(SignedUrl {
+            url: format!("{}?expires={}&signature={}", path, expires, signature),
+            expires_at: DateTime::from_timestamp(expires, 0).unwrap_or(expires_at),
+        })
+    }
+
+    /// Checks that `signature` was issued for `path` and `expires`, and that
+    /// `expires` has not passed.
+    pub fn verify(
+        &self,
+        path: &str,
+        expires: i64,
+        signature: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), AppError> {
+        let invalid = || AppError::Unauthorized("Invalid URL signature".to_string());
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| invalid())?;
+        self.mac(path, expires)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+
+        if now.timestamp() >= expires {
+            return Err(AppError::Unauthorized("Signed URL has expired".to_string()));
+        }
+        Ok(())
+    }
+
+    fn mac(&self, path: &str, expires: i64) -> HmacSha256 {
+        let mut mac = HmacSha256::new_from_slice(&self.key).expect("HMAC accepts any key length");
+        mac.update(path.as_bytes());
+        mac.update(b"\n");
+        mac.update(expires.to_string().as_bytes());
+        mac
+    }
+}
+
+#[derive(Deserialize)]
+struct SignatureParams {
+    expires: i64,
+    signature: String,
+}
+
+/// Extractor that admits a request only when its URL carries a valid,
+/// unexpired signature for the request path.
+#[derive(Debug, Clone, Copy)]
+pub struct SignedRequest {
+    pub expires_at: DateTime<Utc>,
+}
+
+impl FromRequest for SignedRequest {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(verify_request(req))
+    }
+}
+
+fn verify_request(req: &HttpRequest) -> Result<SignedRequest, AppError> {
+    let state = req
+        .app_data::<web::Data<AppState>>()
+        .ok_or_else(|| AppError::Internal("Application state is not configured".to_string()))?;
+    let params = web::Query::<SignatureParams>::from_query(req.query_string())
+        .map_err(|_| AppError::Unauthorized("Missing URL signature".to_string()))?;
+
+    state
+        .url_signer
+        .verify(req.path(), params.expires, &params.signature, Utc::now())?;
+
+    Ok(SignedRequest {
+        expires_at: DateTime::from_timestamp(params.expires, 0).unwrap_or_default(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> UrlSigner {
+        UrlSigner::new(b"test-signing-key", Duration::hours(1))
+    }
+
+    fn params(url: &str) -> (String, i64, String) {
+        let (path, query) = url.split_once('?').unwrap();
+        let params = web::Query::<SignatureParams>::from_query(query).unwrap();
+        (path.to_string(), params.expires, params.signature.clone())
+    }
+
+    #[test]
+    fn test_signed_url_verifies_until_it_expires() {
+        let now = Utc::now();
+        let signed = signer()
+            .sign("/files/1", Duration::minutes(5), now)
+            .unwrap();
+        let (path, expires, signature) = params(&signed.url);
+
+        assert_eq!(path, "/files/1");
+        assert!(signer().verify(&path, expires, &signature, now).is_ok());
+        assert!(matches!(
+            signer().verify(&path, expires, &signature, now + Duration::minutes(5)),
+            Err(AppError::Unauthorized(message)) if message.contains("expired")
+        ));
+    }
+
+    #[test]
+    fn test_tampering_invalidates_the_signature() {
+        let now = Utc::now();
+        let signed = signer()
+            .sign("/files/1", Duration::minutes(5), now)
+            .unwrap();
+        let (path, expires, signature) = params(&signed.url);
+
+        assert!(
+            signer()
+                .verify("/files/2", expires, &signature, now)
+                .is_err()
+        );
+        assert!(
+            signer()
+                .verify(&path, expires + 3600, &signature, now)
+                .is_err()
+        );
+        assert!(
+            UrlSigner::new(b"other-key", Duration::hours(1))
+                .verify(&path, expires, &signature, now)
+                .is_err()
+        );
+        assert!(signer().verify(&path, expires, "not base64!", now).is_err());
+    }
+
+    #[test]
+    fn test_ttl_is_bounded() {
+        let now = Utc::now();
+        assert!(signer().sign("/files/1", Duration::hours(2), now).is_err());
+        assert!(signer().sign("/files/1", Duration::zero(), now).is_err());
+        assert!(signer().sign("/files/1", Duration::hours(1), now).is_ok());
+    }
+}
@@ -0,0 +1,225 @@
+//! Small HTTP load generator for the memos API.
+//!
+//! Starts the API on an ephemeral port and seeds memos, or targets a running
+//! server at `BENCH_BASE_URL` as is. Fires concurrent paginated list requests
+//! and reports latency percentiles. Every response is checked against the
+//! pagination contract so regressions under load fail the run; the local run
+//! also checks the total counts at least the seeded memos.
+//!
+//! ```bash
+//! cargo run --release --bin bench
+//! BENCH_REQUESTS=5000 BENCH_CONCURRENCY=64 cargo run --release --bin bench
+//! ```
+
+use actix_web::{App, HttpServer, web};
+use actix_web_template::{
+    config::Settings,
+    dto::{MemoResponseDto, PaginatedResponse},
+    handlers,
+    repository::MemoRepository,
+    state::AppState,
+};
+use chrono::Utc;
+use chrono_tz::Tz;
+use sea_orm::Database;
+use std::{
+    env,
+    time::{Duration, Instant},
+};
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+struct BenchConfig {
+    base_url: Option<String>,
+    requests: usize,
+    concurrency: usize,
+    seed: usize,
+}
+
+impl BenchConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        let config = Self {
+            base_url: env::var("BENCH_BASE_URL").ok(),
+            requests: env::var("BENCH_REQUESTS")
+                .unwrap_or_else(|_| "1000".to_string())
+                .parse()?,
+            concurrency: env::var("BENCH_CONCURRENCY")
+                .unwrap_or_else(|_| "32".to_string())
+                .parse()?,
+            seed: env::var("BENCH_SEED")
+                .unwrap_or_else(|_| "50".to_string())
+                .parse()?,
+        };
+        if config.concurrency == 0 {
+            anyhow::bail!("BENCH_CONCURRENCY must be greater than 0");
+        }
+        Ok(config)
+    }
+}
+
+#[derive(Default)]
+struct WorkerReport {
+    latencies: Vec<Duration>,
+    errors: usize,
+    violations: Vec<String>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let config = BenchConfig::from_env()?;
+
+    // A remote server is benchmarked as it is: nothing is seeded there, so
+    // there is no count to check the totals against.
+    let local = match config.base_url.clone() {
+        Some(_) => None,
+        None => {
+            let settings = Settings::load()?;
+            let db = Database::connect(&settings.database.url).await?;
+
+            let mut seeded: Vec<Uuid> = Vec::with_capacity(config.seed);
+            for i in 0..config.seed {
+                let memo = MemoRepository::create(
+                    &db,
+                    format!("Load Test Memo {}", i),
+                    Some("Seeded by the bench binary".to_string()),
+                    Utc::now(),
+                    Tz::UTC,
+                    None,
+                )
+                .await?;
+                seeded.push(memo.id);
+            }
+
+            let state = AppState::new(settings.clone(), db.clone());
+            let server = HttpServer::new(move || {
+                App::new()
+                    .app_data(web::Data::new(state.clone()))
+                    .service(handlers::list_memos)
+                    .service(handlers::get_memo)
+            })
+            .bind("127.0.0.1:0")?;
+            let addr = server.addrs()[0];
+            tokio::spawn(server.run());
+            Some((format!("http://{}", addr), db, seeded))
+        }
+    };
+    let base_url = match (&config.base_url, &local) {
+        (Some(url), _) => url.clone(),
+        (None, Some((url, _, _))) => url.clone(),
+        (None, None) => unreachable!("a local server is started without BENCH_BASE_URL"),
+    };
+
+    println!(
+        "Running {} requests against {} with concurrency {}",
+        config.requests, base_url, config.concurrency
+    );
+
+    let client = reqwest::Client::new();
+    let seeded_total = local.as_ref().map(|(_, _, seeded)| seeded.len() as u64);
+    let started = Instant::now();
+
+    let mut workers = JoinSet::new();
+    for worker in 0..config.concurrency {
+        let client = client.clone();
+        let base_url = base_url.clone();
+        let share = config.requests / config.concurrency
+            + usize::from(worker < config.requests % config.concurrency);
+
+        workers.spawn(async move {
+            let mut report = WorkerReport::default();
+            for i in 0..share {
+                let limit = (i % 20 + 1) as u64;
+                let offset = ((worker * 7 + i) % 40) as u64;
+                let url = format!(
+                    "{}/api/v1/memos?limit={}&offset={}",
+                    base_url, limit, offset
+                );
+
+                let request_started = Instant::now();
+                let response = match client.get(&url).send().await {
+                    Ok(response) if response.status().is_success() => response,
+                    _ => {
+                        report.errors += 1;
+                        continue;
+                    }
+                };
+                let page: PaginatedResponse<MemoResponseDto> = match response.json().await {
+                    Ok(page) => page,
+                    Err(_) => {
+                        report.errors += 1;
+                        continue;
+                    }
+                };
+                report.latencies.push(request_started.elapsed());
+
+                if page.limit != limit || page.offset != offset {
+                    report.violations.push(format!(
+                        "{}: echoed limit/offset {}/{}",
+                        url, page.limit, page.offset
+                    ));
+                }
+                if page.data.len() as u64 > limit {
+                    report
+                        .violations
+                        .push(format!("{}: returned {} items", url, page.data.len()));
+                }
+                if let Some(seeded_total) = seeded_total {
+                    match page.total {
+                        Some(total) if total >= seeded_total => {}
+                        total => report.violations.push(format!(
+                            "{}: total {:?} below seeded {}",
+                            url, total, seeded_total
+                        )),
+                    }
+                }
+            }
+            report
+        });
+    }
+
+    let mut latencies = Vec::with_capacity(config.requests);
+    let mut errors = 0;
+    let mut violations = Vec::new();
+    while let Some(report) = workers.join_next().await {
+        let report = report?;
+        latencies.extend(report.latencies);
+        errors += report.errors;
+        violations.extend(report.violations);
+    }
+    let elapsed = started.elapsed();
+
+    if let Some((_, db, seeded)) = local {
+        for id in seeded {
+            MemoRepository::delete(&db, id).await.ok();
+        }
+    }
+
+    latencies.sort();
+    println!("Completed {} requests in {:.2?}", latencies.len(), elapsed);
+    println!(
+        "Throughput: {:.1} req/s",
+        latencies.len() as f64 / elapsed.as_secs_f64()
+    );
+    println!("p50: {:.2?}", percentile(&latencies, 50.0));
+    println!("p95: {:.2?}", percentile(&latencies, 95.0));
+    println!("p99: {:.2?}", percentile(&latencies, 99.0));
+    println!("Errors: {}", errors);
+    println!("Pagination violations: {}", violations.len());
+    for violation in violations.iter().take(10) {
+        println!("  {}", violation);
+    }
+
+    if errors > 0 || !violations.is_empty() {
+        anyhow::bail!("Load test failed");
+    }
+
+    Ok(())
+}
+
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let rank = ((pct / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank]
+}
@@ -0,0 +1,378 @@
+//! Operator CLI sharing the application's services and configuration.
+//!
+//! ```bash
+//! cargo run --bin cli -- memo list --completed false
+//! cargo run --bin cli -- memo create "Renew certificate" --due 2025-03-01
+//! cargo run --bin cli -- memo delete 7b1c...
+//! cargo run --bin cli -- migrate up
+//! cargo run --bin cli -- seed --count 50
+//! cargo run --bin cli -- config check --effective
+//! cargo run --bin cli -- encryption backfill
+//! ```
+
+use actix_web_template::{
+    config::Settings,
+    crypto,
+    db::{CircuitBreaker, GuardedConnection},
+    dto::{CreateMemoDto, PaginationParams},
+    repository::{MemoRepository, WebhookIntegrationRepository},
+    seed::seed_demo_memos,
+    services::MemoService,
+    utils::sanitize,
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::Database;
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[command(
+    name = "cli",
+    version,
+    about = "Manage memos without crafting HTTP calls"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List, create and delete memos
+    #[command(subcommand)]
+    Memo(MemoCommand),
+    /// Apply or inspect database migrations
+    #[command(subcommand)]
+    Migrate(MigrateCommand),
+    /// Insert deterministic demo memos (refused in production)
+    Seed {
+        #[arg(long, env = "SEED_COUNT", default_value_t = 25)]
+        count: usize,
+    },
+    /// Inspect configuration
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Manage encryption of memo descriptions
+    #[command(subcommand)]
+    Encryption(EncryptionCommand),
+}
+
+#[derive(Subcommand)]
+enum MemoCommand {
+    /// List memos, newest first
+    List(ListArgs),
+    /// Create a memo
+    Create {
+        title: String,
+        #[arg(long)]
+        description: Option<String>,
+        /// Due date as RFC 3339 (`2025-03-01T09:00:00Z`) or a day (`2025-03-01`)
+        #[arg(long, value_parser = parse_due)]
+        due: DateTime<Utc>,
+        /// Color label: red, orange, yellow, green, blue, purple or gray
+        #[arg(long)]
+        color: Option<String>,
+    },
+    /// Delete a memo by id; prints a token for `memo undo`
+    Delete { id: Uuid },
+    /// Restore memos deleted within the undo window
+    Undo { token: Uuid },
+}
+
+#[derive(Args)]
+struct ListArgs {
+    #[arg(long, default_value_t = 20)]
+    limit: u64,
+    #[arg(long, default_value_t = 0)]
+    offset: u64,
+    #[arg(long)]
+    completed: Option<bool>,
+    /// Search in title and description
+    #[arg(long)]
+    search: Option<String>,
+    /// Only memos with this color label
+    #[arg(long)]
+    color: Option<String>,
+    /// Print JSON instead of a table
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Subcommand)]
+enum MigrateCommand {
+    /// Apply all pending migrations
+    Up,
+    /// Roll back the last `steps` migrations
+    Down {
+        #[arg(long, default_value_t = 1)]
+        steps: u32,
+    },
+    /// Show applied and pending migrations
+    Status,
+}
+
+#[derive(Subcommand)]
+enum ConfigCommand {
+    /// Load and validate settings, optionally checking the database connection
+    Check {
+        #[arg(long)]
+        connect: bool,
+        /// Print every resolved setting, defaults included, with secrets masked
+        #[arg(long)]
+        effective: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum EncryptionCommand {
+    /// Encrypt existing descriptions and webhook URLs, and re-encrypt those under older keys
+    Backfill {
+        #[arg(long, default_value_t = 500)]
+        batch_size: u64,
+    },
+    /// Print a random key to prepend to FIELD_ENCRYPTION_KEYS
+    GenerateKey {
+        /// Key id stored with every value it encrypts
+        id: String,
+    },
+}
+
+fn parse_due(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date_time.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map(|date| date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc())
+        .map_err(|_| format!("expected RFC 3339 or YYYY-MM-DD, got '{}'", value))
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    let settings = Settings::load()?;
+
+    match cli.command {
+        Command::Memo(command) => run_memo(command, &settings, &connect(&settings).await?).await,
+        Command::Migrate(command) => run_migrate(command, &connect(&settings).await?).await,
+        Command::Seed { count } => {
+            if settings.is_production() {
+                anyhow::bail!("Refusing to seed demo data with APP_ENV=production");
+            }
+            let report = seed_demo_memos(&connect(&settings).await?, count).await?;
+            println!(
+                "Seeded {} demo memos ({} created, {} already present)",
+                count, report.created, report.skipped
+            );
+            Ok(())
+        }
+        Command::Config(ConfigCommand::Check {
+            connect: ping,
+            effective,
+        }) => check_config(&settings, ping, effective).await,
+        Command::Encryption(command) => run_encryption(command, &settings).await,
+    }
+}
+
+/// Connects with the server's circuit breaker settings, so a long-running
+/// command stops counting failures as the server would.
+async fn connect(settings: &Settings) -> anyhow::Result<GuardedConnection> {
+    crypto::init(&settings.encryption)?;
+    sanitize::init(&settings.sanitize);
+    let db = Database::connect(&settings.database.url)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to connect to the database: {}", e))?;
+    Ok(GuardedConnection::new(
+        db,
+        CircuitBreaker::from_config(&settings.database),
+    ))
+}
+
+async fn run_memo(
+    command: MemoCommand,
+    settings: &Settings,
+    db: &GuardedConnection,
+) -> anyhow::Result<()> {
+    let service = MemoService::new(db.clone());
+
+    match command {
+        MemoCommand::List(args) => {
+            let page = service
+                .get_all_memos(PaginationParams {
+                    limit: Some(args.limit),
+                    offset: Some(args.offset),
+                    completed: args.completed,
+                    overdue: None,
+                    archived: None,
+                    pinned: None,
+                    starred: None,
+                    color: args.color,
+                    sort_by: None,
+                    order: None,
+                    q: args.search,
+                    count: None,
+                })
+                .await?;
+
+            if args.json {
+                println!("{}", serde_json::to_string_pretty(&page.data)?);
+                return Ok(());
+            }
+            for memo in &page.data {
+                println!(
+                    "{}  [{}]  {}  {}",
+                    memo.id,
+                    if memo.completed { "x" } else { " " },
+                    memo.date_to.format("%Y-%m-%d"),
+                    memo.title
+                );
+            }
+            println!(
+                "-- showing {} of {} memos (offset {})",
+                page.data.len(),
+                page.total.unwrap_or_default(),
+                page.offset
+            );
+        }
+        MemoCommand::Create {
+            title,
+            description,
+            due,
+            color,
+        } => {
+            let memo = service
+                .create_memo(CreateMemoDto {
+                    title,
+                    description,
+                    date_to: Some(due.into()),
+                    date_to_text: None,
+                    timezone: None,
+                    color,
+                })
+                .await?;
+            println!("Created memo {}", memo.id);
+        }
+        MemoCommand::Delete { id } => {
+            let result = service.delete_memo(id, settings.undo.window()).await?;
+            println!("Deleted memo {}", id);
+            if let (Some(token), Some(expires_at)) = (result.undo_token, result.undo_expires_at) {
+                println!("-- undo with `memo undo {}` before {}", token, expires_at);
+            }
+        }
+        MemoCommand::Undo { token } => {
+            for memo in service.undo_delete(token).await? {
+                println!("Restored memo {} {}", memo.id, memo.title);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_migrate(command: MigrateCommand, db: &GuardedConnection) -> anyhow::Result<()> {
+    // The migrator takes the pool itself
+    let db = db.inner();
+    match command {
+        MigrateCommand::Up => {
+            let pending = Migrator::get_pending_migrations(db).await?.len();
+            Migrator::up(db, None).await?;
+            println!("Applied {} migration(s)", pending);
+        }
+        MigrateCommand::Down { steps } => {
+            Migrator::down(db, Some(steps)).await?;
+            println!("Rolled back {} migration(s)", steps);
+        }
+        MigrateCommand::Status => {
+            for migration in Migrator::get_migration_with_status(db).await? {
+                println!("{:<10} {}", migration.status(), migration.name());
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn run_encryption(command: EncryptionCommand, settings: &Settings) -> anyhow::Result<()> {
+    match command {
+        EncryptionCommand::Backfill { batch_size } => {
+            let db = connect(settings).await?;
+            let Some(cipher) = crypto::cipher() else {
+                anyhow::bail!("Set FIELD_ENCRYPTION_KEYS before running the backfill");
+            };
+
+            let (mut after, mut updated) = (None, 0);
+            while let Some((changed, last)) =
+                MemoRepository::reencrypt_descriptions(&db, after, batch_size).await?
+            {
+                updated += changed;
+                after = Some(last);
+                println!("Re-encrypted {} description(s) so far", updated);
+            }
+            let urls = WebhookIntegrationRepository::reencrypt_urls(&db).await?;
+            println!("Re-encrypted {} webhook URL(s)", urls);
+            println!(
+                "All descriptions and webhook URLs are encrypted with key {}",
+                cipher.current_key_id()
+            );
+        }
+        EncryptionCommand::GenerateKey { id } => {
+            let key: [u8; 32] = rand::random();
+            println!("{}:{}", id, STANDARD.encode(key));
+        }
+    }
+    Ok(())
+}
+
+async fn check_config(settings: &Settings, ping: bool, effective: bool) -> anyhow::Result<()> {
+    settings.validate()?;
+
+    if effective {
+        println!("{}", serde_json::to_string_pretty(settings)?);
+    }
+
+    println!("environment:     {:?}", settings.app.env);
+    println!(
+        "listen address:  {}:{}",
+        settings.server.host, settings.server.port
+    );
+    println!(
+        "database:        {}",
+        settings
+            .database
+            .url
+            .split('@')
+            .next_back()
+            .unwrap_or("***")
+    );
+    println!(
+        "admin api:       {}",
+        if settings.admin.token.is_some() {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+    println!(
+        "rate limits:     {}",
+        if settings.rate_limit.redis_url.is_some() {
+            "redis"
+        } else {
+            "in-memory"
+        }
+    );
+    println!(
+        "encryption:      {}",
+        match settings.encryption.keys.first() {
+            Some(key) => format!("enabled (current key {})", key.id),
+            None => "disabled".to_string(),
+        }
+    );
+
+    if ping {
+        connect(settings).await?.inner().ping().await?;
+        println!("database ping:   ok");
+    }
+
+    println!("Configuration is valid");
+    Ok(())
+}
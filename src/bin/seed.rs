@@ -0,0 +1,50 @@
+//! Populates the database with deterministic demo memos.
+//!
+//! Safe to run repeatedly: memos that were already seeded are skipped. Refuses
+//! to run with `APP_ENV=production`.
+//!
+//! ```bash
+//! cargo run --bin seed
+//! SEED_COUNT=100 cargo run --bin seed
+//! ```
+
+use actix_web_template::{
+    config::Settings,
+    crypto,
+    db::{CircuitBreaker, GuardedConnection},
+    seed::seed_demo_memos,
+    utils::sanitize,
+};
+use sea_orm::Database;
+use std::env;
+
+const DEFAULT_SEED_COUNT: usize = 25;
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let settings = Settings::load()?;
+
+    if settings.is_production() {
+        anyhow::bail!("Refusing to seed demo data with APP_ENV=production");
+    }
+
+    let count = match env::var("SEED_COUNT") {
+        Ok(value) => value.parse()?,
+        Err(_) => DEFAULT_SEED_COUNT,
+    };
+
+    crypto::init(&settings.encryption)?;
+    sanitize::init(&settings.sanitize);
+    let db = GuardedConnection::new(
+        Database::connect(&settings.database.url).await?,
+        CircuitBreaker::from_config(&settings.database),
+    );
+    let report = seed_demo_memos(&db, count).await?;
+
+    println!(
+        "Seeded {} demo memos ({} created, {} already present)",
+        count, report.created, report.skipped
+    );
+
+    Ok(())
+}
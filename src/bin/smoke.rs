@@ -0,0 +1,275 @@
+//! Post-deploy smoke test against a running instance.
+//!
+//! Checks `/health`, `/ready` and `/metrics`, then creates a memo, reads it
+//! back and deletes it. Prints a JSON report on stdout and exits non-zero if
+//! any check failed, so a deploy pipeline can gate on it.
+//!
+//! ```bash
+//! cargo run --bin smoke -- https://staging.example.com
+//! SMOKE_BASE_URL=http://localhost:8080 cargo run --bin smoke -- --timeout-secs 5
+//! cargo run --bin smoke -- https://api.example.com --management-url http://10.0.0.5:9090
+//! ```
+
+use actix_web_template::dto::MemoResponseDto;
+use chrono::{Duration as ChronoDuration, Utc};
+use clap::Parser;
+use reqwest::{Client, Response, StatusCode};
+use serde::Serialize;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+#[derive(Parser)]
+#[command(
+    name = "smoke",
+    version,
+    about = "Check that a deployed instance serves traffic"
+)]
+struct Args {
+    /// Base URL of the instance, e.g. `https://staging.example.com`
+    #[arg(env = "SMOKE_BASE_URL")]
+    base_url: String,
+    /// Base URL of the management listener, when `MANAGEMENT_PORT` moves the
+    /// probes and `/metrics` off the main port
+    #[arg(long, env = "SMOKE_MANAGEMENT_URL")]
+    management_url: Option<String>,
+    /// Timeout for each request
+    #[arg(long, env = "SMOKE_TIMEOUT_SECS", default_value_t = 10)]
+    timeout_secs: u64,
+    /// Bearer token for `/metrics` when the instance protects it
+    #[arg(long, env = "OBSERVABILITY_AUTH_TOKEN", hide_env_values = true)]
+    metrics_token: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Report {
+    base_url: String,
+    passed: bool,
+    checks: Vec<Check>,
+}
+
+#[derive(Serialize)]
+struct Check {
+    name: &'static str,
+    passed: bool,
+    duration_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+type CheckResult<T = ()> = Result<T, String>;
+
+struct Smoke {
+    client: Client,
+    base_url: String,
+    checks: Vec<Check>,
+}
+
+impl Smoke {
+    /// Runs `check` and records its outcome, returning its value on success.
+    async fn run<T>(
+        &mut self,
+        name: &'static str,
+        check: impl AsyncFnOnce(&Client, &str) -> CheckResult<T>,
+    ) -> Option<T> {
+        let started = Instant::now();
+        let result = check(&self.client, &self.base_url).await;
+        let (passed, value, error) = match result {
+            Ok(value) => (true, Some(value), None),
+            Err(error) => (false, None, Some(error)),
+        };
+        self.checks.push(Check {
+            name,
+            passed,
+            duration_ms: started.elapsed().as_millis(),
+            error,
+        });
+        value
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+    let client = match Client::builder()
+        .timeout(Duration::from_secs(args.timeout_secs))
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to build HTTP client: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut smoke = Smoke {
+        client,
+        base_url: args.base_url.trim_end_matches('/').to_string(),
+        checks: Vec::new(),
+    };
+
+    let management_url = args.management_url.as_deref().map_or_else(
+        || smoke.base_url.clone(),
+        |url| url.trim_end_matches('/').to_string(),
+    );
+    smoke
+        .run("health", async |client, _| {
+            check_health(client, &management_url).await
+        })
+        .await;
+    smoke
+        .run("ready", async |client, _| {
+            check_ready(client, &management_url).await
+        })
+        .await;
+    smoke
+        .run("metrics", async |client, _| {
+            check_metrics(client, &management_url, args.metrics_token.as_deref()).await
+        })
+        .await;
+    // The memo steps depend on each other, so stop at the first failure.
+    if let Some(memo) = smoke.run("create_memo", create_memo).await
+        && smoke
+            .run("get_memo", async |client, base_url| {
+                get_memo(client, base_url, &memo).await
+            })
+            .await
+            .is_some()
+    {
+        smoke
+            .run("delete_memo", async |client, base_url| {
+                delete_memo(client, base_url, memo.id).await
+            })
+            .await;
+    }
+
+    let passed = smoke.checks.iter().all(|check| check.passed);
+    let report = Report {
+        base_url: smoke.base_url,
+        passed,
+        checks: smoke.checks,
+    };
+    match serde_json::to_string_pretty(&report) {
+        Ok(json) => println!("{}", json),
+        Err(e) => {
+            eprintln!("Failed to serialize report: {}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if passed {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+async fn check_health(client: &Client, base_url: &str) -> CheckResult {
+    let body: serde_json::Value = json(expect_status(
+        get(client, &format!("{}/health", base_url)).await?,
+        StatusCode::OK,
+    )?)
+    .await?;
+    if body["status"] != "healthy" {
+        return Err(format!("status is {}", body["status"]));
+    }
+    Ok(())
+}
+
+async fn check_ready(client: &Client, base_url: &str) -> CheckResult {
+    let response = get(client, &format!("{}/ready", base_url)).await?;
+    if response.status() == StatusCode::SERVICE_UNAVAILABLE {
+        let body: serde_json::Value = json(response).await?;
+        return Err(format!(
+            "not ready, pending migrations: {}",
+            body["pending_migrations"]
+        ));
+    }
+    expect_status(response, StatusCode::OK)?;
+    Ok(())
+}
+
+async fn check_metrics(client: &Client, base_url: &str, token: Option<&str>) -> CheckResult {
+    let mut request = client.get(format!("{}/metrics", base_url));
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("request failed: {}", e))?;
+    let body = expect_status(response, StatusCode::OK)?
+        .text()
+        .await
+        .map_err(|e| format!("failed to read body: {}", e))?;
+    if !body.contains("_http_requests_total") {
+        return Err("no HTTP request metrics exposed".to_string());
+    }
+    Ok(())
+}
+
+async fn create_memo(client: &Client, base_url: &str) -> CheckResult<MemoResponseDto> {
+    let response = client
+        .post(format!("{}/api/v1/memos", base_url))
+        .json(&serde_json::json!({
+            "title": format!("Smoke test {}", Uuid::new_v4()),
+            "description": "Created by the smoke binary; deleted right away",
+            "date_to": Utc::now() + ChronoDuration::days(1),
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("request failed: {}", e))?;
+    json(expect_status(response, StatusCode::CREATED)?).await
+}
+
+async fn get_memo(client: &Client, base_url: &str, created: &MemoResponseDto) -> CheckResult {
+    let memo: MemoResponseDto = json(expect_status(
+        get(client, &format!("{}/api/v1/memos/{}", base_url, created.id)).await?,
+        StatusCode::OK,
+    )?)
+    .await?;
+    if memo.title != created.title {
+        return Err(format!(
+            "expected title '{}', got '{}'",
+            created.title, memo.title
+        ));
+    }
+    Ok(())
+}
+
+async fn delete_memo(client: &Client, base_url: &str, id: Uuid) -> CheckResult {
+    let response = client
+        .delete(format!("{}/api/v1/memos/{}", base_url, id))
+        .send()
+        .await
+        .map_err(|e| format!("request failed: {}", e))?;
+    expect_status(response, StatusCode::OK)?;
+    expect_status(
+        get(client, &format!("{}/api/v1/memos/{}", base_url, id)).await?,
+        StatusCode::NOT_FOUND,
+    )?;
+    Ok(())
+}
+
+async fn get(client: &Client, url: &str) -> CheckResult<Response> {
+    client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("request failed: {}", e))
+}
+
+fn expect_status(response: Response, expected: StatusCode) -> CheckResult<Response> {
+    if response.status() == expected {
+        Ok(response)
+    } else {
+        Err(format!("expected {}, got {}", expected, response.status()))
+    }
+}
+
+async fn json<T: serde::de::DeserializeOwned>(response: Response) -> CheckResult<T> {
+    response
+        .json()
+        .await
+        .map_err(|e| format!("invalid JSON body: {}", e))
+}
@@ -0,0 +1,120 @@
+use std::env;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Why [`super::Settings::load`] failed, naming the variable at fault.
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("{var} must be set")]
+    Missing { var: String },
+
+    /// The value could not be parsed; `value` is `<redacted>` for secrets.
+    #[error("{var} must be {expected}, got '{value}'")]
+    Invalid {
+        var: String,
+        expected: &'static str,
+        value: String,
+        #[source]
+        source: Option<Box<dyn std::error::Error + Send + Sync>>,
+    },
+
+    #[error("Failed to read {path} (from {var})")]
+    Unreadable {
+        var: String,
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl ConfigError {
+    pub fn invalid(var: &str, expected: &'static str, value: &str) -> Self {
+        ConfigError::Invalid {
+            var: var.to_string(),
+            expected,
+            value: value.to_string(),
+            source: None,
+        }
+    }
+}
+
+/// Types settings are parsed into, with how to describe them in errors.
+pub trait ConfigValue: FromStr<Err: std::error::Error + Send + Sync + 'static> {
+    const EXPECTED: &'static str;
+}
+
+impl ConfigValue for bool {
+    const EXPECTED: &'static str = "true or false";
+}
+
+impl ConfigValue for u8 {
+    const EXPECTED: &'static str = "an integer from 0 to 255";
+}
+
+impl ConfigValue for u16 {
+    const EXPECTED: &'static str = "an integer from 0 to 65535";
+}
+
+impl ConfigValue for u32 {
+    const EXPECTED: &'static str = "an integer from 0 to 4294967295";
+}
+
+impl ConfigValue for u64 {
+    const EXPECTED: &'static str = "a non-negative integer";
+}
+
+impl ConfigValue for usize {
+    const EXPECTED: &'static str = "a non-negative integer";
+}
+
+impl ConfigValue for f64 {
+    const EXPECTED: &'static str = "a number";
+}
+
+/// Parses `value`, read from `var`.
+pub fn parse_value<T: ConfigValue>(var: &str, value: &str) -> Result<T, ConfigError> {
+    value.trim().parse().map_err(|e| ConfigError::Invalid {
+        var: var.to_string(),
+        expected: T::EXPECTED,
+        value: value.to_string(),
+        source: Some(Box::new(e)),
+    })
+}
+
+/// Parses `var`, or `default` when it is unset.
+pub fn parse_var<T: ConfigValue>(var: &str, default: &str) -> Result<T, ConfigError> {
+    match env::var(var) {
+        Ok(value) => parse_value(var, &value),
+        Err(_) => parse_value(var, default),
+    }
+}
+
+/// Parses `var`, or `None` when it is unset or empty.
+pub fn parse_optional_var<T: ConfigValue>(var: &str) -> Result<Option<T>, ConfigError> {
+    match env::var(var) {
+        Ok(value) if !value.is_empty() => parse_value(var, &value).map(Some),
+        _ => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_value_trims_whitespace() {
+        assert_eq!(parse_value::<u16>("SERVER_PORT", " 8080 ").unwrap(), 8080);
+        assert!(parse_value::<bool>("HSTS_PRELOAD", "true").unwrap());
+    }
+
+    #[test]
+    fn test_invalid_value_names_variable_type_and_value() {
+        let error = parse_value::<u16>("SERVER_PORT", "70000").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "SERVER_PORT must be an integer from 0 to 65535, got '70000'"
+        );
+        let source = std::error::Error::source(&error).unwrap();
+        assert_eq!(source.to_string(), "number too large to fit in target type");
+    }
+}
@@ -1,47 +1,207 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize, Serializer};
 use std::env;
 
-#[derive(Debug, Clone, Deserialize)]
+use super::error::{ConfigError, parse_optional_var, parse_value, parse_var};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Settings {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub cors: CorsConfig,
+    pub security: SecurityConfig,
     pub api: ApiConfig,
     pub app: AppConfig,
     pub logging: LoggingConfig,
+    pub observability: ObservabilityConfig,
+    pub maintenance: MaintenanceConfig,
+    pub chaos: ChaosConfig,
+    pub admin: AdminConfig,
+    pub rate_limit: RateLimitConfig,
+    pub scheduler: SchedulerConfig,
+    pub email: EmailConfig,
+    pub push: PushConfig,
+    pub webhooks: WebhookConfig,
+    pub events: EventsConfig,
+    pub retention: RetentionConfig,
+    pub undo: UndoConfig,
+    pub jobs: JobsConfig,
+    pub health: HealthConfig,
+    pub encryption: EncryptionConfig,
+    pub url_signing: UrlSigningConfig,
+    pub sanitize: SanitizeConfig,
+    pub upload_scan: UploadScanConfig,
+    pub api_keys: ApiKeysConfig,
+    pub memo_cache: MemoCacheConfig,
+    pub http_cache: HttpCacheConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ServerConfig {
     pub host: String,
     pub port: u16,
+    /// Default time a handler may take before the request is aborted with 504
+    pub request_timeout_ms: u64,
+    /// Per-route overrides as `(path prefix, timeout in ms)`; the longest matching prefix wins
+    pub request_timeout_overrides: Vec<(String, u64)>,
+    /// HTTP worker threads
+    pub workers: usize,
+    /// How long idle keep-alive connections stay open; 0 disables keep-alive
+    pub keep_alive_secs: u64,
+    /// Time a client has to send the request head; 0 disables the timeout
+    pub client_request_timeout_ms: u64,
+    /// Time a client has to acknowledge a connection shutdown; 0 disables the timeout
+    pub client_disconnect_timeout_ms: u64,
+    /// Pending connections the listen socket queues
+    pub backlog: u32,
+    /// Concurrent connections per worker; further ones wait in the backlog
+    pub max_connections: usize,
+    /// Port of the management listener serving probes, `/metrics` and the
+    /// admin API; they stay on the main port when unset
+    pub management_port: Option<u16>,
+    /// Interface the management listener binds to
+    pub management_host: String,
+}
+
+impl ServerConfig {
+    pub fn keep_alive(&self) -> Option<std::time::Duration> {
+        (self.keep_alive_secs > 0).then(|| std::time::Duration::from_secs(self.keep_alive_secs))
+    }
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DatabaseConfig {
+    #[serde(serialize_with = "redact_password")]
     pub url: String,
     pub max_connections: u32,
     pub connect_timeout: u64,
+    /// Connection attempts at startup before giving up
+    pub connect_max_attempts: u32,
+    /// Delay before the first retry, doubled after each failed attempt
+    pub connect_backoff_initial_ms: u64,
+    /// Upper bound for the retry delay
+    pub connect_backoff_max_ms: u64,
+    /// Read replicas for memo listings, search and lookups; see [`crate::db::ReadReplicas`]
+    #[serde(serialize_with = "redact_passwords")]
+    pub replica_urls: Vec<String>,
+    /// Consecutive connection failures that open the circuit breaker; 0 disables it
+    pub circuit_failure_threshold: u32,
+    /// How long the open circuit fails requests before letting one through
+    pub circuit_open_secs: u64,
+    /// Statements running longer are logged and counted; 0 disables it
+    pub slow_query_ms: u64,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CorsConfig {
+    /// `*`, exact origins, or subdomain patterns like `https://*.example.com`
     pub allowed_origins: Vec<String>,
+    /// Allow cookies and `Authorization` on cross-origin requests; not with `*`
+    pub allow_credentials: bool,
+    /// Response headers browsers may read from cross-origin responses
+    pub exposed_headers: Vec<String>,
+    /// How long browsers may cache preflight results
+    pub max_age_secs: u64,
+}
+
+/// Response headers set by [`crate::middleware::SecurityHeaders`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SecurityConfig {
+    /// `X-Frame-Options`; omitted when `None`, e.g. when `frame-ancestors` allows embedding
+    pub frame_options: Option<String>,
+    /// `Referrer-Policy`; omitted when empty
+    pub referrer_policy: String,
+    /// `Permissions-Policy`; omitted when empty
+    pub permissions_policy: String,
+    /// `Strict-Transport-Security`; omitted when `None`
+    pub hsts: Option<HstsConfig>,
+    pub csp: CspConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HstsConfig {
+    pub max_age_secs: u64,
+    pub include_subdomains: bool,
+    /// Opt in to browser preload lists; needs subdomains and a max-age of a year
+    pub preload: bool,
+}
+
+/// `Content-Security-Policy`; see [`crate::middleware::security_headers::ContentSecurityPolicy`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CspConfig {
+    /// Sources per directive, in header order; the header is omitted when empty
+    pub directives: Vec<(String, Vec<String>)>,
+    pub report_uri: Option<String>,
+    /// Add a per-request nonce to `script-src` for the templates' inline scripts
+    pub script_nonce: bool,
+}
+
+/// CSP directives configurable through `CSP_*` variables, with their defaults.
+const CSP_DIRECTIVES: &[(&str, &str)] = &[
+    ("default-src", "'self'"),
+    ("script-src", "'self'"),
+    ("style-src", "'self' 'unsafe-inline'"),
+    ("img-src", "'self' data:"),
+    ("font-src", "'self' data:"),
+    ("connect-src", "'self'"),
+    ("frame-src", ""),
+    ("frame-ancestors", "'none'"),
+];
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ApiConfig {
-    pub max_request_size: usize,
+    pub body_limits: BodyLimits,
+    /// Reject request body fields the DTO does not declare instead of
+    /// ignoring them
+    pub strict_bodies: bool,
+    /// New memos must be due in the future, unless imported as historical
+    pub require_future_due_dates: bool,
     pub enable_swagger: bool,
+    /// Renderer served at `/docs` from the same OpenAPI document; off when unset
+    pub docs_ui: Option<DocsUi>,
+    /// Script the `/docs` page loads the renderer from; CSP `script-src` must allow it
+    pub docs_script_url: String,
+    /// Check JSON responses against the OpenAPI document; development only.
+    /// Defaults to on for debug builds in development.
+    pub validate_responses: bool,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Request body size limits in bytes, per route group.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+pub struct BodyLimits {
+    /// JSON API, admin and other non-page routes
+    pub api: usize,
+    /// Server-rendered pages and their form posts
+    pub web: usize,
+    /// Import and attachment uploads
+    pub upload: usize,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DocsUi {
+    Redoc,
+    Rapidoc,
+}
+
+impl DocsUi {
+    /// Where the renderer's script is published.
+    pub fn default_script_url(self) -> &'static str {
+        match self {
+            DocsUi::Redoc => "https://cdn.redoc.ly/redoc/latest/bundles/redoc.standalone.js",
+            DocsUi::Rapidoc => "https://unpkg.com/rapidoc/dist/rapidoc-min.js",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct AppConfig {
     pub env: Environment,
     pub version: String,
+    /// Externally reachable base URL, used for links in emails
+    pub public_url: String,
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum Environment {
     Development,
@@ -49,13 +209,447 @@ pub enum Environment {
     Production,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+impl Environment {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Environment::Development => "development",
+            Environment::Staging => "staging",
+            Environment::Production => "production",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct LoggingConfig {
     pub level: String,
     pub format: LogFormat,
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq)]
+/// Prometheus metrics served at `/metrics`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ObservabilityConfig {
+    /// Bearer token for `/metrics` and the API docs; see [`crate::middleware::ObservabilityAuth`]
+    #[serde(serialize_with = "redact")]
+    pub auth_token: Option<String>,
+    /// Basic credentials for the same routes, for browsers and scrapers without bearer support
+    pub basic_auth: Option<BasicCredentials>,
+    /// Prefix of the HTTP request metrics, e.g. `actix_web_http_requests_total`
+    pub metrics_namespace: String,
+    /// Labels added to every metric, e.g. `env` and `instance`
+    pub metrics_const_labels: Vec<(String, String)>,
+    /// Upper bounds in seconds of the request latency histogram buckets
+    pub metrics_latency_buckets: Vec<f64>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct BasicCredentials {
+    pub username: String,
+    #[serde(skip_serializing)]
+    pub password: String,
+}
+
+impl std::fmt::Debug for BasicCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BasicCredentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MaintenanceConfig {
+    /// Start with maintenance mode on; it can be toggled at runtime via `/admin/maintenance`
+    pub enabled: bool,
+    /// Value of the `Retry-After` header sent with maintenance responses
+    pub retry_after_secs: u64,
+}
+
+/// Fault injection for resilience testing; see [`crate::middleware::Chaos`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ChaosConfig {
+    /// Development only; rejected in production
+    pub enabled: bool,
+    /// Only requests whose path starts with this are affected
+    pub path_prefix: String,
+    /// Share of requests delayed by `latency_ms`
+    pub latency_percent: u8,
+    pub latency_ms: u64,
+    /// Share of requests answered with 500 without running the handler
+    pub error_percent: u8,
+    /// Share of requests whose connection is dropped after the handler ran
+    pub drop_percent: u8,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AdminConfig {
+    /// Bearer token for `/admin/*` endpoints; the admin API is disabled when unset
+    #[serde(serialize_with = "redact")]
+    pub token: Option<String>,
+}
+
+/// Request budgets per route class, tracked separately for every caller.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RateLimitConfig {
+    /// `GET`/`HEAD`/`OPTIONS` requests
+    pub read: RateLimitBudget,
+    /// Mutating API requests
+    pub write: RateLimitBudget,
+    /// Mutating `/web` requests (form posts from the UI)
+    pub web_form: RateLimitBudget,
+    /// Shared store so limits hold across replicas; in-memory only when unset
+    #[serde(serialize_with = "redact_optional_password")]
+    pub redis_url: Option<String>,
+    /// Budget for a Redis round trip before falling back to the in-memory limiter
+    pub redis_timeout_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub struct RateLimitBudget {
+    /// Sustained rate
+    pub per_minute: u32,
+    /// Requests allowed in a single burst
+    pub burst: u32,
+}
+
+impl RateLimitBudget {
+    fn from_env(prefix: &str, per_minute: u32, burst: u32) -> Result<Self, ConfigError> {
+        Ok(Self {
+            per_minute: parse_var(&format!("{}_PER_MINUTE", prefix), &per_minute.to_string())?,
+            burst: parse_var(&format!("{}_BURST", prefix), &burst.to_string())?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SchedulerConfig {
+    /// Run periodic background tasks in this process
+    pub enabled: bool,
+    /// Cron expression (`sec min hour day month weekday`) for due-date reminders
+    pub due_reminders_cron: String,
+    /// How long before a memo's due date its reminder is sent
+    pub reminder_lead_minutes: u64,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EmailConfig {
+    /// SMTP relay; email is disabled when unset
+    pub smtp_host: Option<String>,
+    pub smtp_port: u16,
+    pub smtp_username: Option<String>,
+    #[serde(serialize_with = "redact")]
+    pub smtp_password: Option<String>,
+    pub smtp_tls: SmtpTls,
+    /// Sender address, e.g. `Memos <noreply@example.com>`
+    pub from: String,
+    /// Recipient of due-date reminders
+    pub reminder_to: Option<String>,
+    /// Messages buffered for the sender task before new ones are rejected
+    pub queue_capacity: usize,
+}
+
+impl EmailConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.smtp_host.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PushConfig {
+    /// VAPID private key (raw P-256 scalar, URL-safe base64); web push is disabled when unset
+    #[serde(serialize_with = "redact")]
+    pub vapid_private_key: Option<String>,
+    /// Contact for push services, `mailto:` or `https:` URL
+    pub vapid_subject: String,
+    /// Cron expression for checking which memos became due
+    pub due_cron: String,
+}
+
+/// Slack/Discord webhook delivery.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhookConfig {
+    /// Per-request timeout when posting to a provider
+    pub timeout_secs: u64,
+    /// Cron expression for checking which memos became overdue
+    pub overdue_cron: String,
+}
+
+/// Domain event publishing through the outbox; see [`crate::events`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EventsConfig {
+    pub broker: EventBroker,
+    /// Kafka bootstrap servers (comma-separated) or NATS server URL
+    #[serde(serialize_with = "redact_optional_password")]
+    pub broker_url: Option<String>,
+    /// Kafka topic, or NATS subject prefix followed by the event type
+    pub topic: String,
+    /// Outbox events published per relay run
+    pub relay_batch_size: u64,
+    /// Pause between relay runs
+    pub relay_interval_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventBroker {
+    /// Events are not recorded
+    None,
+    Kafka,
+    Nats,
+}
+
+/// Automatic clean-up of old completed memos.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RetentionConfig {
+    /// Purge memos completed (last updated) more than this many days ago; disabled when unset
+    pub completed_after_days: Option<u32>,
+    pub action: RetentionAction,
+    /// Memos purged per statement, so a large backlog never holds long locks
+    pub batch_size: u64,
+    /// Cron expression for the purge job
+    pub cron: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RetentionAction {
+    /// Set the archived flag; archived memos are left alone
+    Archive,
+    /// Delete the memos, archived or not
+    Delete,
+}
+
+impl RetentionAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionAction::Archive => "archive",
+            RetentionAction::Delete => "delete",
+        }
+    }
+}
+
+/// Deleted memos stay restorable for a while before they are removed for good.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UndoConfig {
+    /// How long the undo token returned by a delete stays valid
+    pub window_secs: u64,
+    /// Cron expression for the job that removes memos whose undo window has closed
+    pub finalize_cron: String,
+}
+
+impl UndoConfig {
+    pub fn window(&self) -> chrono::Duration {
+        chrono::Duration::seconds(self.window_secs as i64)
+    }
+}
+
+/// Workers running long-running operations accepted with `202`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct JobsConfig {
+    /// Operations run at the same time by this instance
+    pub workers: usize,
+    /// How often idle workers look for operations submitted elsewhere
+    pub poll_interval_ms: u64,
+}
+
+/// Caching and timeouts for the database checks behind `/ready` and `/startup`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HealthConfig {
+    /// How long a check result is reused; 0 checks on every probe
+    pub cache_ttl_ms: u64,
+    /// How long a check may take before it counts as failed
+    pub check_timeout_ms: u64,
+}
+
+/// In-process cache of memo lookups by ID and of the index page listing; see
+/// [`crate::services::MemoCache`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct MemoCacheConfig {
+    /// Memos kept at most; 0 disables the cache
+    pub max_entries: u64,
+    /// How long an entry is served before it is loaded again; 0 disables the
+    /// cache. Bounds how stale a memo changed by another process can get.
+    pub ttl_secs: u64,
+}
+
+/// `Cache-Control` of `GET` responses; see [`crate::middleware::CacheHeaders`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HttpCacheConfig {
+    /// `(path, directives)`; a path ending in `*` matches everything below it
+    pub rules: Vec<(String, String)>,
+    /// Request headers cacheable responses vary on, besides what they set
+    pub vary: Vec<String>,
+}
+
+/// Rules used when `HTTP_CACHE_RULES` is not set: the public memo list may be
+/// served slightly stale while it is refreshed, everything else is checked
+/// with the server first.
+pub const DEFAULT_HTTP_CACHE_RULES: &str = "/api/v1/memos=public, max-age=5, stale-while-revalidate=30;\
+     /api/*=private, no-cache;\
+     /web/*=private, no-cache;\
+     /=private, no-cache";
+
+/// HMAC-signed, expiring URLs for fetching resources without auth headers.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UrlSigningConfig {
+    /// Shared secret; without one each process signs with a random key, so
+    /// links break on restart and across replicas. Required in production.
+    #[serde(serialize_with = "redact")]
+    pub key: Option<String>,
+    /// Longest validity a client may request for a signed URL
+    pub max_ttl_secs: u64,
+}
+
+/// HTML allowed in memo text; see [`crate::utils::sanitize`].
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct SanitizeConfig {
+    /// Tags kept when memo input is sanitized; `None` keeps ammonia's default
+    /// set and an empty list strips all markup
+    pub allowed_tags: Option<Vec<String>>,
+}
+
+/// Checks uploads must pass before they are stored; see [`crate::attachments::scan`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UploadScanConfig {
+    /// File name extensions accepted, lowercase and without the dot
+    pub allowed_extensions: Vec<String>,
+    /// clamd address, `host:port` or a Unix socket path; `None` skips virus scanning
+    pub clamav_address: Option<String>,
+    /// How long clamd may take per file before the upload is refused
+    pub clamav_timeout_ms: u64,
+}
+
+/// Keys integrators authenticate API requests with; see [`crate::auth::api_keys`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ApiKeysConfig {
+    /// Empty leaves the API anonymous-only
+    pub keys: Vec<ApiKeyConfig>,
+    /// How often request counts are written to `api_key_usage`
+    pub usage_flush_secs: u64,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ApiKeyConfig {
+    /// Names the key in usage records and logs
+    pub id: String,
+    /// Sent by clients in `X-API-Key`
+    #[serde(skip_serializing)]
+    pub key: String,
+    /// Requests allowed per calendar month (UTC); `None` is unlimited
+    pub monthly_quota: Option<u64>,
+}
+
+impl std::fmt::Debug for ApiKeyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ApiKeyConfig")
+            .field("id", &self.id)
+            .field("key", &"<redacted>")
+            .field("monthly_quota", &self.monthly_quota)
+            .finish()
+    }
+}
+
+/// Parses `id:key` or `id:key:monthly_quota` entries separated by commas or
+/// newlines; keys cannot contain `:`, and a quota of 0 means unlimited.
+pub fn parse_api_keys(spec: &str) -> Result<Vec<ApiKeyConfig>, ConfigError> {
+    spec.split([',', '\n'])
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            // The entry holds a key, so it is never echoed back.
+            let invalid =
+                || ConfigError::invalid("API_KEYS", "id:key[:monthly_quota] entries", "<redacted>");
+            let mut parts = entry.splitn(3, ':').map(str::trim);
+            let (Some(id), Some(key)) = (parts.next(), parts.next()) else {
+                return Err(invalid());
+            };
+            let monthly_quota = match parts.next() {
+                Some(quota) => Some(quota.parse::<u64>().map_err(|_| invalid())?),
+                None => None,
+            };
+            if id.is_empty() || key.is_empty() {
+                return Err(invalid());
+            }
+            Ok(ApiKeyConfig {
+                id: id.to_string(),
+                key: key.to_string(),
+                monthly_quota: monthly_quota.filter(|quota| *quota > 0),
+            })
+        })
+        .collect()
+}
+
+/// Application-level encryption of memo descriptions; see [`crate::crypto`].
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct EncryptionConfig {
+    /// Newest first: the first key encrypts, all of them decrypt. Empty disables encryption.
+    pub keys: Vec<EncryptionKey>,
+}
+
+#[derive(Clone, Deserialize, Serialize)]
+pub struct EncryptionKey {
+    /// Stored with every value so the right key can be picked after a rotation
+    pub id: String,
+    /// 32 bytes, standard base64
+    #[serde(skip_serializing)]
+    pub key: String,
+}
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EncryptionKey")
+            .field("id", &self.id)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl EncryptionConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+}
+
+/// Parses `id:base64key` entries separated by commas or newlines.
+pub fn parse_encryption_keys(spec: &str) -> Result<Vec<EncryptionKey>, ConfigError> {
+    spec.split([',', '\n'])
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            // The entry holds a key, so it is never echoed back.
+            let (id, key) = entry.split_once(':').ok_or_else(|| {
+                ConfigError::invalid(
+                    "FIELD_ENCRYPTION_KEYS",
+                    "id:base64key entries",
+                    "<redacted>",
+                )
+            })?;
+            Ok(EncryptionKey {
+                id: id.trim().to_string(),
+                key: key.trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+impl PushConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.vapid_private_key.is_some()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    /// Plain connection upgraded with STARTTLS (port 587)
+    StartTls,
+    /// Implicit TLS (port 465)
+    Tls,
+    /// No encryption; only for local relays such as Mailpit
+    None,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum LogFormat {
     Pretty,
@@ -64,25 +658,73 @@ pub enum LogFormat {
 }
 
 impl Settings {
-    pub fn load() -> anyhow::Result<Self> {
+    /// Reads settings from the environment and the dotenv files of the
+    /// `APP_ENV` profile, failing on the first variable that is missing or
+    /// does not parse. Cross-field rules are checked separately by
+    /// [`Self::validate`].
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::load_profile(&dotenv_profile())
+    }
+
+    /// Like [`Self::load`], but reads `.env.<profile>` whatever `APP_ENV`
+    /// says. Tests use the `test` profile.
+    pub fn load_profile(profile: &str) -> Result<Self, ConfigError> {
+        // Neither file overrides a variable that is already set, so the
+        // process environment wins over `.env.<profile>`, which wins over `.env`.
+        dotenvy::from_filename(format!(".env.{}", profile)).ok();
         dotenvy::dotenv().ok();
 
+        let host = env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string());
         let server = ServerConfig {
-            host: env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
-            port: env::var("SERVER_PORT")
-                .unwrap_or_else(|_| "3737".to_string())
-                .parse()?,
+            management_host: env::var("MANAGEMENT_HOST").unwrap_or_else(|_| host.clone()),
+            host,
+            port: parse_var("SERVER_PORT", "3737")?,
+            request_timeout_ms: parse_var("REQUEST_TIMEOUT_MS", "30000")?,
+            request_timeout_overrides: parse_timeout_overrides(
+                &env::var("REQUEST_TIMEOUT_OVERRIDES").unwrap_or_default(),
+            )?,
+            workers: parse_optional_var("SERVER_WORKERS")?.unwrap_or_else(|| num_cpus::get() * 2),
+            keep_alive_secs: parse_var("SERVER_KEEP_ALIVE_SECS", "75")?,
+            client_request_timeout_ms: parse_var("SERVER_CLIENT_REQUEST_TIMEOUT_MS", "60000")?,
+            client_disconnect_timeout_ms: parse_var("SERVER_CLIENT_DISCONNECT_TIMEOUT_MS", "5000")?,
+            backlog: parse_var("SERVER_BACKLOG", "2048")?,
+            max_connections: parse_var("SERVER_MAX_CONNECTIONS", "25000")?,
+            management_port: parse_optional_var("MANAGEMENT_PORT")?,
         };
 
         let database = DatabaseConfig {
-            url: env::var("DATABASE_URL")
-                .map_err(|_| anyhow::anyhow!("DATABASE_URL must be set"))?,
-            max_connections: env::var("DATABASE_MAX_CONNECTIONS")
-                .unwrap_or_else(|_| "10".to_string())
-                .parse()?,
-            connect_timeout: env::var("DATABASE_CONNECT_TIMEOUT")
-                .unwrap_or_else(|_| "30".to_string())
-                .parse()?,
+            url: env::var("DATABASE_URL").map_err(|_| ConfigError::Missing {
+                var: "DATABASE_URL".to_string(),
+            })?,
+            max_connections: parse_var("DATABASE_MAX_CONNECTIONS", "10")?,
+            connect_timeout: parse_var("DATABASE_CONNECT_TIMEOUT", "30")?,
+            connect_max_attempts: parse_var("DATABASE_CONNECT_MAX_ATTEMPTS", "10")?,
+            connect_backoff_initial_ms: parse_var("DATABASE_CONNECT_BACKOFF_INITIAL_MS", "500")?,
+            connect_backoff_max_ms: parse_var("DATABASE_CONNECT_BACKOFF_MAX_MS", "30000")?,
+            replica_urls: env::var("DATABASE_REPLICA_URLS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect(),
+            circuit_failure_threshold: parse_var("DATABASE_CIRCUIT_FAILURE_THRESHOLD", "5")?,
+            circuit_open_secs: parse_var("DATABASE_CIRCUIT_OPEN_SECS", "30")?,
+            slow_query_ms: parse_var("DATABASE_SLOW_QUERY_MS", "500")?,
+        };
+
+        let app_env_str = env::var("APP_ENV").unwrap_or_else(|_| "development".to_string());
+        let app = AppConfig {
+            env: match app_env_str.to_lowercase().as_str() {
+                "production" => Environment::Production,
+                "staging" => Environment::Staging,
+                _ => Environment::Development,
+            },
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            public_url: env::var("APP_PUBLIC_URL")
+                .unwrap_or_else(|_| format!("http://{}:{}", server.host, server.port))
+                .trim_end_matches('/')
+                .to_string(),
         };
 
         let cors = CorsConfig {
@@ -90,27 +732,112 @@ impl Settings {
                 .unwrap_or_else(|_| "*".to_string())
                 .split(',')
                 .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
                 .collect(),
+            allow_credentials: parse_var("CORS_ALLOW_CREDENTIALS", "false")?,
+            exposed_headers: match env::var("CORS_EXPOSED_HEADERS") {
+                Ok(headers) => headers
+                    .split(',')
+                    .map(|s| s.trim().to_string())
+                    .filter(|s| !s.is_empty())
+                    .collect(),
+                Err(_) => {
+                    let mut headers = vec![
+                        "ETag",
+                        "Link",
+                        "Preference-Applied",
+                        "Retry-After",
+                        "X-RateLimit-Limit",
+                        "X-RateLimit-Remaining",
+                        "X-RateLimit-Reset",
+                        "X-Request-Id",
+                    ];
+                    if app.env == Environment::Development {
+                        headers.push("X-Response-Schema-Errors");
+                    }
+                    headers.into_iter().map(str::to_string).collect()
+                }
+            },
+            max_age_secs: parse_var("CORS_MAX_AGE_SECS", "3600")?,
         };
 
+        let security = SecurityConfig {
+            frame_options: Some(
+                env::var("SECURITY_FRAME_OPTIONS").unwrap_or_else(|_| "DENY".to_string()),
+            )
+            .filter(|value| !value.is_empty()),
+            referrer_policy: env::var("SECURITY_REFERRER_POLICY")
+                .unwrap_or_else(|_| "strict-origin-when-cross-origin".to_string()),
+            permissions_policy: env::var("SECURITY_PERMISSIONS_POLICY")
+                .unwrap_or_else(|_| "geolocation=(), microphone=(), camera=()".to_string()),
+            hsts: match parse_var("HSTS_MAX_AGE_SECS", "31536000")? {
+                0 => None,
+                max_age_secs => Some(HstsConfig {
+                    max_age_secs,
+                    include_subdomains: parse_var("HSTS_INCLUDE_SUBDOMAINS", "true")?,
+                    preload: parse_var("HSTS_PRELOAD", "false")?,
+                }),
+            },
+            csp: CspConfig {
+                // `CSP_SCRIPT_SRC` etc. replace a directive's sources; empty drops it
+                directives: CSP_DIRECTIVES
+                    .iter()
+                    .map(|(name, default)| {
+                        let var = format!("CSP_{}", name.to_uppercase().replace('-', "_"));
+                        let sources = env::var(var).unwrap_or_else(|_| default.to_string());
+                        let sources: Vec<String> =
+                            sources.split_whitespace().map(str::to_string).collect();
+                        (name.to_string(), sources)
+                    })
+                    .filter(|(_, sources)| !sources.is_empty())
+                    .collect(),
+                report_uri: env::var("CSP_REPORT_URI")
+                    .ok()
+                    .filter(|uri| !uri.is_empty()),
+                script_nonce: parse_var("CSP_SCRIPT_NONCE", "true")?,
+            },
+        };
+
+        let docs_ui = match env::var("API_DOCS_UI")
+            .unwrap_or_default()
+            .to_lowercase()
+            .as_str()
+        {
+            "" | "none" => None,
+            "redoc" => Some(DocsUi::Redoc),
+            "rapidoc" => Some(DocsUi::Rapidoc),
+            other => {
+                return Err(ConfigError::invalid(
+                    "API_DOCS_UI",
+                    "none, redoc or rapidoc",
+                    other,
+                ));
+            }
+        };
         let api = ApiConfig {
-            max_request_size: env::var("MAX_REQUEST_SIZE")
-                .unwrap_or_else(|_| "262144".to_string())
-                .parse()?,
+            body_limits: BodyLimits {
+                // MAX_REQUEST_SIZE is the name from before the limits were split.
+                api: match parse_optional_var("API_BODY_LIMIT")? {
+                    Some(limit) => limit,
+                    None => parse_var("MAX_REQUEST_SIZE", "262144")?,
+                },
+                web: parse_var("WEB_BODY_LIMIT", "65536")?,
+                upload: parse_var("UPLOAD_BODY_LIMIT", "10485760")?,
+            },
+            strict_bodies: parse_var("STRICT_REQUEST_BODIES", "true")?,
+            require_future_due_dates: parse_var("REQUIRE_FUTURE_DUE_DATES", "false")?,
             enable_swagger: env::var("ENABLE_SWAGGER")
                 .unwrap_or_else(|_| "true".to_string())
                 .parse()
                 .unwrap_or(true),
-        };
-
-        let app_env_str = env::var("APP_ENV").unwrap_or_else(|_| "development".to_string());
-        let app = AppConfig {
-            env: match app_env_str.to_lowercase().as_str() {
-                "production" => Environment::Production,
-                "staging" => Environment::Staging,
-                _ => Environment::Development,
-            },
-            version: env!("CARGO_PKG_VERSION").to_string(),
+            docs_ui,
+            docs_script_url: env::var("API_DOCS_SCRIPT_URL")
+                .ok()
+                .filter(|url| !url.is_empty())
+                .or_else(|| docs_ui.map(|ui| ui.default_script_url().to_string()))
+                .unwrap_or_default(),
+            validate_responses: parse_optional_var("VALIDATE_RESPONSES")?
+                .unwrap_or_else(|| cfg!(debug_assertions) && app.env == Environment::Development),
         };
 
         let logging = LoggingConfig {
@@ -126,6 +853,243 @@ impl Settings {
             },
         };
 
+        let observability = ObservabilityConfig {
+            auth_token: env::var("OBSERVABILITY_AUTH_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+            basic_auth: match env::var("OBSERVABILITY_BASIC_AUTH") {
+                Ok(value) if !value.is_empty() => {
+                    let Some((username, password)) = value.split_once(':') else {
+                        return Err(ConfigError::invalid(
+                            "OBSERVABILITY_BASIC_AUTH",
+                            "username:password",
+                            "<redacted>",
+                        ));
+                    };
+                    Some(BasicCredentials {
+                        username: username.to_string(),
+                        password: password.to_string(),
+                    })
+                }
+                _ => None,
+            },
+            metrics_namespace: env::var("METRICS_NAMESPACE")
+                .unwrap_or_else(|_| "actix_web".to_string()),
+            metrics_const_labels: parse_const_labels(
+                &env::var("METRICS_CONST_LABELS").unwrap_or_default(),
+            )?,
+            metrics_latency_buckets: match env::var("METRICS_LATENCY_BUCKETS") {
+                Ok(value) if !value.is_empty() => value
+                    .split(',')
+                    .map(|bound| parse_value("METRICS_LATENCY_BUCKETS", bound))
+                    .collect::<Result<_, _>>()?,
+                _ => DEFAULT_LATENCY_BUCKETS.to_vec(),
+            },
+        };
+
+        let maintenance = MaintenanceConfig {
+            enabled: parse_var("MAINTENANCE_MODE", "false")?,
+            retry_after_secs: parse_var("MAINTENANCE_RETRY_AFTER_SECS", "300")?,
+        };
+
+        let chaos = ChaosConfig {
+            enabled: parse_var("CHAOS_ENABLED", "false")?,
+            path_prefix: env::var("CHAOS_PATH_PREFIX").unwrap_or_else(|_| "/api".to_string()),
+            latency_percent: parse_var("CHAOS_LATENCY_PERCENT", "0")?,
+            latency_ms: parse_var("CHAOS_LATENCY_MS", "0")?,
+            error_percent: parse_var("CHAOS_ERROR_PERCENT", "0")?,
+            drop_percent: parse_var("CHAOS_DROP_PERCENT", "0")?,
+        };
+
+        let admin = AdminConfig {
+            token: env::var("ADMIN_TOKEN")
+                .ok()
+                .filter(|token| !token.is_empty()),
+        };
+
+        let rate_limit = RateLimitConfig {
+            read: RateLimitBudget::from_env("RATE_LIMIT_READ", 100, 100)?,
+            write: RateLimitBudget::from_env("RATE_LIMIT_WRITE", 30, 10)?,
+            web_form: RateLimitBudget::from_env("RATE_LIMIT_WEB_FORM", 30, 10)?,
+            redis_url: env::var("RATE_LIMIT_REDIS_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            redis_timeout_ms: parse_var("RATE_LIMIT_REDIS_TIMEOUT_MS", "50")?,
+        };
+
+        let scheduler = SchedulerConfig {
+            enabled: parse_var("SCHEDULER_ENABLED", "true")?,
+            due_reminders_cron: env::var("SCHEDULER_DUE_REMINDERS_CRON")
+                .unwrap_or_else(|_| "0 */5 * * * *".to_string()),
+            reminder_lead_minutes: parse_var("REMINDER_LEAD_MINUTES", "60")?,
+        };
+
+        let email = EmailConfig {
+            smtp_host: env::var("SMTP_HOST").ok().filter(|host| !host.is_empty()),
+            smtp_port: parse_var("SMTP_PORT", "587")?,
+            smtp_username: env::var("SMTP_USERNAME").ok().filter(|v| !v.is_empty()),
+            smtp_password: env::var("SMTP_PASSWORD").ok().filter(|v| !v.is_empty()),
+            smtp_tls: match env::var("SMTP_TLS")
+                .unwrap_or_else(|_| "starttls".to_string())
+                .to_lowercase()
+                .as_str()
+            {
+                "tls" => SmtpTls::Tls,
+                "none" => SmtpTls::None,
+                _ => SmtpTls::StartTls,
+            },
+            from: env::var("EMAIL_FROM")
+                .unwrap_or_else(|_| "Memos <noreply@localhost>".to_string()),
+            reminder_to: env::var("REMINDER_EMAIL_TO").ok().filter(|v| !v.is_empty()),
+            queue_capacity: parse_var("EMAIL_QUEUE_CAPACITY", "100")?,
+        };
+
+        let push = PushConfig {
+            vapid_private_key: env::var("VAPID_PRIVATE_KEY")
+                .ok()
+                .filter(|key| !key.is_empty()),
+            vapid_subject: env::var("VAPID_SUBJECT")
+                .unwrap_or_else(|_| "mailto:admin@localhost".to_string()),
+            due_cron: env::var("SCHEDULER_DUE_PUSH_CRON")
+                .unwrap_or_else(|_| "0 * * * * *".to_string()),
+        };
+
+        let webhooks = WebhookConfig {
+            timeout_secs: parse_var("WEBHOOK_TIMEOUT_SECS", "10")?,
+            overdue_cron: env::var("SCHEDULER_OVERDUE_WEBHOOK_CRON")
+                .unwrap_or_else(|_| "0 * * * * *".to_string()),
+        };
+
+        let events = EventsConfig {
+            broker: match env::var("EVENTS_BROKER")
+                .unwrap_or_default()
+                .to_lowercase()
+                .as_str()
+            {
+                "" | "none" => EventBroker::None,
+                "kafka" => EventBroker::Kafka,
+                "nats" => EventBroker::Nats,
+                other => {
+                    return Err(ConfigError::invalid(
+                        "EVENTS_BROKER",
+                        "none, kafka or nats",
+                        other,
+                    ));
+                }
+            },
+            broker_url: env::var("EVENTS_BROKER_URL")
+                .ok()
+                .filter(|url| !url.is_empty()),
+            topic: env::var("EVENTS_TOPIC").unwrap_or_else(|_| "memos.events".to_string()),
+            relay_batch_size: parse_var("EVENTS_RELAY_BATCH_SIZE", "100")?,
+            relay_interval_ms: parse_var("EVENTS_RELAY_INTERVAL_MS", "1000")?,
+        };
+
+        let retention = RetentionConfig {
+            completed_after_days: parse_optional_var("RETENTION_COMPLETED_DAYS")?,
+            // Anything but an explicit "delete" archives, so a typo never destroys data.
+            action: match env::var("RETENTION_ACTION")
+                .unwrap_or_else(|_| "archive".to_string())
+                .to_lowercase()
+                .as_str()
+            {
+                "delete" => RetentionAction::Delete,
+                _ => RetentionAction::Archive,
+            },
+            batch_size: parse_var("RETENTION_BATCH_SIZE", "500")?,
+            cron: env::var("SCHEDULER_RETENTION_CRON")
+                .unwrap_or_else(|_| "0 30 3 * * *".to_string()),
+        };
+
+        let undo = UndoConfig {
+            window_secs: parse_var("UNDO_WINDOW_SECS", "30")?,
+            finalize_cron: env::var("SCHEDULER_UNDO_FINALIZE_CRON")
+                .unwrap_or_else(|_| "0 * * * * *".to_string()),
+        };
+
+        let jobs = JobsConfig {
+            workers: parse_var("JOBS_WORKERS", "2")?,
+            poll_interval_ms: parse_var("JOBS_POLL_INTERVAL_MS", "5000")?,
+        };
+
+        let health = HealthConfig {
+            cache_ttl_ms: parse_var("HEALTH_CACHE_TTL_MS", "2000")?,
+            check_timeout_ms: parse_var("HEALTH_CHECK_TIMEOUT_MS", "1000")?,
+        };
+
+        let url_signing = UrlSigningConfig {
+            key: env::var("URL_SIGNING_KEY")
+                .ok()
+                .filter(|key| !key.is_empty()),
+            max_ttl_secs: parse_var("SIGNED_URL_MAX_TTL_SECS", "3600")?,
+        };
+
+        // A mounted secret file wins over the plain variable.
+        let encryption_keys = match env::var("FIELD_ENCRYPTION_KEYS_FILE") {
+            Ok(path) if !path.is_empty() => {
+                std::fs::read_to_string(&path).map_err(|source| ConfigError::Unreadable {
+                    var: "FIELD_ENCRYPTION_KEYS_FILE".to_string(),
+                    path,
+                    source,
+                })?
+            }
+            _ => env::var("FIELD_ENCRYPTION_KEYS").unwrap_or_default(),
+        };
+        let encryption = EncryptionConfig {
+            keys: parse_encryption_keys(&encryption_keys)?,
+        };
+
+        let sanitize = SanitizeConfig {
+            allowed_tags: env::var("SANITIZE_ALLOWED_TAGS").ok().map(|tags| {
+                tags.split(',')
+                    .map(|tag| tag.trim().to_ascii_lowercase())
+                    .filter(|tag| !tag.is_empty())
+                    .collect()
+            }),
+        };
+
+        let upload_scan = UploadScanConfig {
+            allowed_extensions: env::var("UPLOAD_ALLOWED_EXTENSIONS")
+                .unwrap_or_else(|_| "png,jpg,jpeg,gif,webp".to_string())
+                .split(',')
+                .map(|extension| {
+                    extension
+                        .trim()
+                        .trim_start_matches('.')
+                        .to_ascii_lowercase()
+                })
+                .filter(|extension| !extension.is_empty())
+                .collect(),
+            clamav_address: env::var("CLAMAV_ADDRESS")
+                .ok()
+                .filter(|address| !address.is_empty()),
+            clamav_timeout_ms: parse_var("CLAMAV_TIMEOUT_MS", "10000")?,
+        };
+
+        let api_keys = ApiKeysConfig {
+            keys: parse_api_keys(&env::var("API_KEYS").unwrap_or_default())?,
+            usage_flush_secs: parse_var("API_KEY_USAGE_FLUSH_SECS", "10")?,
+        };
+
+        let memo_cache = MemoCacheConfig {
+            max_entries: parse_var("MEMO_CACHE_MAX_ENTRIES", "10000")?,
+            ttl_secs: parse_var("MEMO_CACHE_TTL_SECS", "10")?,
+        };
+
+        let http_cache = HttpCacheConfig {
+            rules: parse_cache_rules(
+                &env::var("HTTP_CACHE_RULES")
+                    .unwrap_or_else(|_| DEFAULT_HTTP_CACHE_RULES.to_string()),
+            )?,
+            vary: env::var("HTTP_CACHE_VARY")
+                .unwrap_or_else(|_| "Accept, Prefer, Time-Zone".to_string())
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect(),
+        };
+
         tracing::info!("Configuration loaded successfully");
         tracing::debug!(?app.env, ?logging.format, "Application configuration");
 
@@ -133,9 +1097,31 @@ impl Settings {
             server,
             database,
             cors,
+            security,
             api,
             app,
             logging,
+            observability,
+            maintenance,
+            chaos,
+            admin,
+            rate_limit,
+            scheduler,
+            email,
+            push,
+            webhooks,
+            events,
+            retention,
+            undo,
+            jobs,
+            health,
+            encryption,
+            url_signing,
+            sanitize,
+            upload_scan,
+            api_keys,
+            memo_cache,
+            http_cache,
         })
     }
 
@@ -146,14 +1132,277 @@ impl Settings {
             anyhow::bail!("CORS wildcard (*) is not allowed in production");
         }
 
+        for origin in &self.cors.allowed_origins {
+            if let Err(e) = crate::middleware::cors::OriginPattern::parse(origin) {
+                anyhow::bail!("Invalid CORS_ALLOWED_ORIGINS entry {}", e);
+            }
+        }
+
+        if self.cors.allow_credentials && self.cors.allowed_origins.iter().any(|o| o == "*") {
+            anyhow::bail!("CORS_ALLOW_CREDENTIALS cannot be combined with the * origin");
+        }
+
+        for name in &self.cors.exposed_headers {
+            if actix_web::http::header::HeaderName::try_from(name.as_str()).is_err() {
+                anyhow::bail!("Invalid CORS_EXPOSED_HEADERS entry '{}'", name);
+            }
+        }
+
+        if let Err(e) = crate::middleware::SecurityHeaders::from_config(&self.security) {
+            anyhow::bail!("Invalid security header configuration: {}", e);
+        }
+
+        let limits = &self.api.body_limits;
+        if limits.api == 0 || limits.web == 0 || limits.upload == 0 {
+            anyhow::bail!("API_BODY_LIMIT, WEB_BODY_LIMIT and UPLOAD_BODY_LIMIT must be positive");
+        }
+
+        for tag in self.sanitize.allowed_tags.iter().flatten() {
+            if crate::utils::sanitize::CONTENT_TAGS.contains(&tag.as_str()) {
+                anyhow::bail!("SANITIZE_ALLOWED_TAGS cannot allow <{}>", tag);
+            }
+        }
+
+        if self.api.validate_responses && self.app.env == Environment::Production {
+            anyhow::bail!("VALIDATE_RESPONSES is not allowed in production");
+        }
+
+        if self.chaos.enabled {
+            if self.app.env == Environment::Production {
+                anyhow::bail!("CHAOS_ENABLED is not allowed in production");
+            }
+            if self.chaos.latency_percent > 100
+                || self.chaos.error_percent as u16 + self.chaos.drop_percent as u16 > 100
+            {
+                anyhow::bail!(
+                    "CHAOS_LATENCY_PERCENT and CHAOS_ERROR_PERCENT + CHAOS_DROP_PERCENT must not exceed 100"
+                );
+            }
+        }
+
         if self.server.port == 0 {
             anyhow::bail!("Server port must be greater than 0");
         }
 
+        if let Some(port) = self.server.management_port
+            && (port == 0 || port == self.server.port)
+        {
+            anyhow::bail!("MANAGEMENT_PORT must be greater than 0 and differ from SERVER_PORT");
+        }
+
         if self.database.max_connections == 0 {
             anyhow::bail!("Database max_connections must be greater than 0");
         }
 
+        if self.server.request_timeout_ms == 0 {
+            anyhow::bail!("Request timeout must be greater than 0");
+        }
+
+        if self.server.workers == 0 || self.server.backlog == 0 || self.server.max_connections == 0
+        {
+            anyhow::bail!(
+                "SERVER_WORKERS, SERVER_BACKLOG and SERVER_MAX_CONNECTIONS must be greater than 0"
+            );
+        }
+
+        if self.database.connect_max_attempts == 0 {
+            anyhow::bail!("Database connect_max_attempts must be greater than 0");
+        }
+
+        for (name, budget) in [
+            ("read", self.rate_limit.read),
+            ("write", self.rate_limit.write),
+            ("web_form", self.rate_limit.web_form),
+        ] {
+            if budget.per_minute == 0 || budget.burst == 0 {
+                anyhow::bail!("Rate limit '{}' budget must be greater than 0", name);
+            }
+        }
+
+        if let Err(e) = self.scheduler.due_reminders_cron.parse::<cron::Schedule>() {
+            anyhow::bail!("Invalid SCHEDULER_DUE_REMINDERS_CRON: {}", e);
+        }
+
+        if let Err(e) = self.push.due_cron.parse::<cron::Schedule>() {
+            anyhow::bail!("Invalid SCHEDULER_DUE_PUSH_CRON: {}", e);
+        }
+
+        if let Err(e) = self.webhooks.overdue_cron.parse::<cron::Schedule>() {
+            anyhow::bail!("Invalid SCHEDULER_OVERDUE_WEBHOOK_CRON: {}", e);
+        }
+
+        if self.webhooks.timeout_secs == 0 {
+            anyhow::bail!("WEBHOOK_TIMEOUT_SECS must be greater than 0");
+        }
+
+        if self.events.broker != EventBroker::None {
+            if self.events.broker_url.is_none() {
+                anyhow::bail!("EVENTS_BROKER_URL is required when EVENTS_BROKER is set");
+            }
+            // The relay runs on the scheduler; without it the outbox only grows.
+            if !self.scheduler.enabled {
+                anyhow::bail!("EVENTS_BROKER requires SCHEDULER_ENABLED=true");
+            }
+            if self.events.topic.is_empty() {
+                anyhow::bail!("EVENTS_TOPIC must not be empty");
+            }
+            if self.events.relay_batch_size == 0 || self.events.relay_interval_ms == 0 {
+                anyhow::bail!(
+                    "EVENTS_RELAY_BATCH_SIZE and EVENTS_RELAY_INTERVAL_MS must be greater than 0"
+                );
+            }
+        }
+
+        if let Err(e) = self.retention.cron.parse::<cron::Schedule>() {
+            anyhow::bail!("Invalid SCHEDULER_RETENTION_CRON: {}", e);
+        }
+
+        if !(1..=86_400).contains(&self.undo.window_secs) {
+            anyhow::bail!("UNDO_WINDOW_SECS must be between 1 and 86400");
+        }
+
+        if let Err(e) = self.undo.finalize_cron.parse::<cron::Schedule>() {
+            anyhow::bail!("Invalid SCHEDULER_UNDO_FINALIZE_CRON: {}", e);
+        }
+
+        if self.jobs.workers == 0 || self.jobs.poll_interval_ms == 0 {
+            anyhow::bail!("JOBS_WORKERS and JOBS_POLL_INTERVAL_MS must be greater than 0");
+        }
+
+        if !is_metric_name(&self.observability.metrics_namespace) {
+            anyhow::bail!("METRICS_NAMESPACE must match [a-zA-Z_][a-zA-Z0-9_]*");
+        }
+
+        for (name, _) in &self.observability.metrics_const_labels {
+            if !is_metric_name(name) || name.starts_with("__") {
+                anyhow::bail!("Invalid METRICS_CONST_LABELS label name: {}", name);
+            }
+            if RESERVED_METRIC_LABELS.contains(&name.as_str()) {
+                anyhow::bail!("METRICS_CONST_LABELS can't set the reserved label {}", name);
+            }
+        }
+
+        let buckets = &self.observability.metrics_latency_buckets;
+        if buckets.is_empty()
+            || buckets
+                .iter()
+                .any(|bound| !bound.is_finite() || *bound <= 0.0)
+            || buckets.windows(2).any(|pair| pair[0] >= pair[1])
+        {
+            anyhow::bail!("METRICS_LATENCY_BUCKETS must be positive and strictly increasing");
+        }
+
+        if let Some(credentials) = &self.observability.basic_auth
+            && (credentials.username.is_empty() || credentials.password.is_empty())
+        {
+            anyhow::bail!("OBSERVABILITY_BASIC_AUTH needs a username and a password");
+        }
+
+        if self.is_production()
+            && (self
+                .observability
+                .auth_token
+                .as_ref()
+                .is_some_and(|token| token.len() < 32)
+                || self
+                    .observability
+                    .basic_auth
+                    .as_ref()
+                    .is_some_and(|credentials| credentials.password.len() < 16))
+        {
+            anyhow::bail!(
+                "In production OBSERVABILITY_AUTH_TOKEN must be at least 32 characters and the OBSERVABILITY_BASIC_AUTH password at least 16"
+            );
+        }
+
+        if self.health.check_timeout_ms == 0 {
+            anyhow::bail!("HEALTH_CHECK_TIMEOUT_MS must be greater than 0");
+        }
+
+        if self.encryption.is_enabled()
+            && let Err(e) = crate::crypto::FieldCipher::new(&self.encryption.keys)
+        {
+            anyhow::bail!("Invalid FIELD_ENCRYPTION_KEYS: {}", e);
+        }
+
+        if self.retention.batch_size == 0 {
+            anyhow::bail!("RETENTION_BATCH_SIZE must be greater than 0");
+        }
+
+        if let Some(key) = &self.push.vapid_private_key
+            && web_push::VapidSignatureBuilder::from_base64_no_sub(key).is_err()
+        {
+            anyhow::bail!("VAPID_PRIVATE_KEY is not a valid URL-safe base64 P-256 key");
+        }
+
+        if self.email.is_enabled() {
+            if self.email.from.parse::<lettre::message::Mailbox>().is_err() {
+                anyhow::bail!("EMAIL_FROM is not a valid address: {}", self.email.from);
+            }
+            if let Some(to) = &self.email.reminder_to
+                && to.parse::<lettre::message::Mailbox>().is_err()
+            {
+                anyhow::bail!("REMINDER_EMAIL_TO is not a valid address: {}", to);
+            }
+            if self.email.queue_capacity == 0 {
+                anyhow::bail!("EMAIL_QUEUE_CAPACITY must be greater than 0");
+            }
+            if self.is_production() && self.email.smtp_tls == SmtpTls::None {
+                anyhow::bail!("SMTP_TLS=none is not allowed in production");
+            }
+        }
+
+        if self.is_production()
+            && self
+                .admin
+                .token
+                .as_ref()
+                .is_some_and(|token| token.len() < 32)
+        {
+            anyhow::bail!("ADMIN_TOKEN must be at least 32 characters in production");
+        }
+
+        if self.is_production()
+            && self
+                .url_signing
+                .key
+                .as_ref()
+                .is_none_or(|key| key.len() < 32)
+        {
+            anyhow::bail!("URL_SIGNING_KEY must be set to at least 32 characters in production");
+        }
+
+        if self.url_signing.max_ttl_secs == 0 {
+            anyhow::bail!("SIGNED_URL_MAX_TTL_SECS must be greater than 0");
+        }
+
+        let mut key_ids = std::collections::HashSet::new();
+        for key in &self.api_keys.keys {
+            if key.id.len() > 100 {
+                anyhow::bail!("API key id {} is longer than 100 characters", key.id);
+            }
+            if !key_ids.insert(key.id.as_str()) {
+                anyhow::bail!("API key id {} is used more than once in API_KEYS", key.id);
+            }
+            if self.app.env == Environment::Production && key.key.len() < 32 {
+                anyhow::bail!(
+                    "API key {} must be at least 32 characters in production",
+                    key.id
+                );
+            }
+        }
+        if self.database.circuit_failure_threshold > 0 && self.database.circuit_open_secs == 0 {
+            anyhow::bail!("DATABASE_CIRCUIT_OPEN_SECS must be greater than 0");
+        }
+
+        if self.api_keys.usage_flush_secs == 0 {
+            anyhow::bail!("API_KEY_USAGE_FLUSH_SECS must be greater than 0");
+        }
+
+        if self.upload_scan.clamav_address.is_some() && self.upload_scan.clamav_timeout_ms == 0 {
+            anyhow::bail!("CLAMAV_TIMEOUT_MS must be greater than 0");
+        }
+
         tracing::info!("Configuration validation passed");
         Ok(())
     }
@@ -166,3 +1415,201 @@ impl Settings {
         self.app.env == Environment::Production
     }
 }
+
+/// The dotenv profile to load: `APP_ENV` from the process environment, else
+/// from `.env`, else `development`.
+fn dotenv_profile() -> String {
+    env::var("APP_ENV")
+        .ok()
+        .or_else(|| {
+            dotenvy::from_filename_iter(".env")
+                .ok()?
+                .filter_map(Result::ok)
+                .find_map(|(name, value)| (name == "APP_ENV").then_some(value))
+        })
+        .filter(|profile| !profile.is_empty())
+        .unwrap_or_else(|| "development".to_string())
+        .to_lowercase()
+}
+
+/// Prometheus' default histogram buckets, in seconds.
+const DEFAULT_LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Labels the HTTP request metrics set themselves.
+const RESERVED_METRIC_LABELS: [&str; 4] = ["endpoint", "method", "status", "le"];
+
+/// Parses `METRICS_CONST_LABELS`, e.g. `env=production,instance=api-1`.
+fn parse_const_labels(value: &str) -> Result<Vec<(String, String)>, ConfigError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (name, value) = entry.split_once('=').ok_or_else(|| {
+                ConfigError::invalid("METRICS_CONST_LABELS", "name=value entries", entry)
+            })?;
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Whether `name` is a valid Prometheus metric prefix or label name.
+fn is_metric_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parses `REQUEST_TIMEOUT_OVERRIDES`, e.g. `/api/v1/memos=5000,/web/calendar=10000`.
+fn parse_timeout_overrides(value: &str) -> Result<Vec<(String, u64)>, ConfigError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (prefix, ms) = entry.split_once('=').ok_or_else(|| {
+                ConfigError::invalid(
+                    "REQUEST_TIMEOUT_OVERRIDES",
+                    "path=milliseconds entries",
+                    entry,
+                )
+            })?;
+            Ok((
+                prefix.trim().to_string(),
+                parse_value("REQUEST_TIMEOUT_OVERRIDES", ms)?,
+            ))
+        })
+        .collect()
+}
+
+/// Parses `HTTP_CACHE_RULES`, e.g. `/api/v1/memos=public, max-age=5;/web/*=no-store`.
+/// Rules are separated by `;`, as directives contain commas.
+pub fn parse_cache_rules(value: &str) -> Result<Vec<(String, String)>, ConfigError> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((path, directives))
+                if path.trim().starts_with('/') && !directives.trim().is_empty() =>
+            {
+                Ok((path.trim().to_string(), directives.trim().to_string()))
+            }
+            _ => Err(ConfigError::invalid(
+                "HTTP_CACHE_RULES",
+                "path=directives entries separated by ';'",
+                entry,
+            )),
+        })
+        .collect()
+}
+
+/// Serializes a secret as `***`, or `null` when unset.
+fn redact<S: Serializer>(secret: &Option<String>, serializer: S) -> Result<S::Ok, S::Error> {
+    match secret {
+        Some(_) => serializer.serialize_str("***"),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn redact_password<T: AsRef<str>, S: Serializer>(
+    url: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&without_password(url.as_ref()))
+}
+
+fn redact_passwords<S: Serializer>(urls: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(urls.iter().map(|url| without_password(url)))
+}
+
+fn redact_optional_password<S: Serializer>(
+    url: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match url {
+        Some(url) => serializer.serialize_some(&without_password(url)),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// `url` with the password of its `user:password@` part replaced by `***`.
+/// Credentials without a colon are masked whole, as NATS tokens are passed
+/// that way.
+pub fn without_password(url: &str) -> String {
+    let Some((scheme, rest)) = url.split_once("://") else {
+        return url.to_string();
+    };
+    match rest.rsplit_once('@') {
+        Some((credentials, host)) => match credentials.split_once(':') {
+            Some((user, _)) => format!("{}://{}:***@{}", scheme, user, host),
+            None => format!("{}://***@{}", scheme, host),
+        },
+        None => url.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_api_keys() {
+        let keys = parse_api_keys("acme:s3cret:1000,\n ops : other-key ,free:k3y:0").unwrap();
+        let keys: Vec<_> = keys
+            .iter()
+            .map(|key| (key.id.as_str(), key.key.as_str(), key.monthly_quota))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                ("acme", "s3cret", Some(1000)),
+                ("ops", "other-key", None),
+                ("free", "k3y", None),
+            ]
+        );
+
+        assert!(parse_api_keys("no-secret").is_err());
+        assert!(parse_api_keys("acme:s3cret:lots").is_err());
+        assert!(parse_api_keys("acme:s3c:ret:10").is_err());
+    }
+
+    #[test]
+    fn test_parse_cache_rules() {
+        let rules = parse_cache_rules(DEFAULT_HTTP_CACHE_RULES).unwrap();
+        assert_eq!(
+            rules[0],
+            (
+                "/api/v1/memos".to_string(),
+                "public, max-age=5, stale-while-revalidate=30".to_string()
+            )
+        );
+        assert_eq!(rules.len(), 4);
+
+        assert!(parse_cache_rules("").unwrap().is_empty());
+        assert!(parse_cache_rules("/api=").is_err());
+        assert!(parse_cache_rules("api=no-store").is_err());
+        assert!(parse_cache_rules("no-store").is_err());
+    }
+
+    #[test]
+    fn test_without_password_masks_only_the_password() {
+        assert_eq!(
+            without_password("postgres://app:s3cr3t@db:5432/memos"),
+            "postgres://app:***@db:5432/memos"
+        );
+        assert_eq!(without_password("redis://cache:6379"), "redis://cache:6379");
+        assert_eq!(
+            without_password("nats://s3cr3t@nats:4222"),
+            "nats://***@nats:4222"
+        );
+        assert_eq!(
+            without_password("kafka-1:9092,kafka-2:9092"),
+            "kafka-1:9092,kafka-2:9092"
+        );
+    }
+}
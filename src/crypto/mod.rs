@@ -0,0 +1,264 @@
+//! Application-level encryption for sensitive columns.
+//!
+//! Values are sealed with AES-256-GCM and stored as
+//! `enc:v1:<key id>:<base64(nonce || ciphertext)>`, with the row id as
+//! associated data so a ciphertext copied onto another row fails to open.
+//! Writes use the first configured key; reads use whichever key the value
+//! names, so old rows stay readable after a rotation until
+//! `cli encryption backfill` re-seals them. Values without the prefix predate
+//! encryption and are returned unchanged.
+//!
+//! The repository reads the process-wide cipher set with [`install`].
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use aes_gcm::{
+    Aes256Gcm, Nonce,
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::config::settings::{EncryptionConfig, EncryptionKey};
+
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+static CIPHER: OnceLock<FieldCipher> = OnceLock::new();
+
+#[derive(Debug, thiserror::Error)]
+pub enum FieldCryptoError {
+    #[error("no encryption keys configured")]
+    NoKeys,
+    #[error("encryption key {0} must be 32 bytes of base64")]
+    InvalidKey(String),
+    #[error("encryption key id {0:?} is empty, duplicated or contains ':'")]
+    InvalidKeyId(String),
+    #[error("value was encrypted with unknown key {0}")]
+    UnknownKey(String),
+    #[error("value is encrypted but no encryption keys are configured")]
+    NotConfigured,
+    #[error("encrypted value is malformed")]
+    Malformed,
+    #[error("encrypted value failed authentication")]
+    Decrypt,
+}
+
+pub struct FieldCipher {
+    current: String,
+    keys: HashMap<String, Aes256Gcm>,
+}
+
+impl FieldCipher {
+    /// Builds a cipher from `keys`; the first one encrypts new values.
+    pub fn new(keys: &[EncryptionKey]) -> Result<Self, FieldCryptoError> {
+        let current = keys.first().ok_or(FieldCryptoError::NoKeys)?.id.clone();
+        let mut ciphers = HashMap::new();
+        for key in keys {
+            if key.id.is_empty() || key.id.contains(':') || ciphers.contains_key(&key.id) {
+                return Err(FieldCryptoError::InvalidKeyId(key.id.clone()));
+            }
+            let cipher = STANDARD
+                .decode(key.key.trim())
+                .ok()
+                .and_then(|bytes| Aes256Gcm::new_from_slice(&bytes).ok())
+                .ok_or_else(|| FieldCryptoError::InvalidKey(key.id.clone()))?;
+            ciphers.insert(key.id.clone(), cipher);
+        }
+        Ok(Self {
+            current,
+            keys: ciphers,
+        })
+    }
+
+    /// Id of the key new values are encrypted with.
+    pub fn current_key_id(&self) -> &str {
+        &self.current
+    }
+
+    /// Whether `stored` is already sealed with the current key.
+    pub fn is_current(&self, stored: &str) -> bool {
+        stored
+            .strip_prefix(PREFIX)
+            .and_then(|rest| rest.split_once(':'))
+            .is_some_and(|(key_id, _)| key_id == self.current)
+    }
+
+    /// Seals `plaintext` under the current key, bound to `context`.
+    pub fn encrypt(&self, plaintext: &str, context: &[u8]) -> Result<String, FieldCryptoError> {
+        let cipher = &self.keys[&self.current];
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext.as_bytes(),
+                    aad: context,
+                },
+            )
+            .map_err(|_| FieldCryptoError::Malformed)?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!(
+            "{}{}:{}",
+            PREFIX,
+            self.current,
+            STANDARD.encode(sealed)
+        ))
+    }
+
+    /// Opens a value produced by [`Self::encrypt`] with the same `context`.
+    /// Unprefixed values are returned as they are.
+    pub fn decrypt(&self, stored: &str, context: &[u8]) -> Result<String, FieldCryptoError> {
+        let Some(rest) = stored.strip_prefix(PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let (key_id, encoded) = rest.split_once(':').ok_or(FieldCryptoError::Malformed)?;
+        let cipher = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| FieldCryptoError::UnknownKey(key_id.to_string()))?;
+
+        let sealed = STANDARD
+            .decode(encoded)
+            .map_err(|_| FieldCryptoError::Malformed)?;
+        let (nonce, ciphertext) = sealed
+            .split_first_chunk::<NONCE_LEN>()
+            .ok_or(FieldCryptoError::Malformed)?;
+        let plaintext = cipher
+            .decrypt(
+                &Nonce::from(*nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: context,
+                },
+            )
+            .map_err(|_| FieldCryptoError::Decrypt)?;
+
+        String::from_utf8(plaintext).map_err(|_| FieldCryptoError::Malformed)
+    }
+}
+
+/// Sets the cipher used by the repository for the rest of the process.
+/// Returns the cipher back if one was already installed.
+pub fn install(cipher: FieldCipher) -> Result<(), FieldCipher> {
+    CIPHER.set(cipher)
+}
+
+/// Installs a cipher built from `config` when any keys are configured.
+/// Returns whether encryption is enabled.
+pub fn init(config: &EncryptionConfig) -> Result<bool, FieldCryptoError> {
+    if !config.is_enabled() {
+        return Ok(false);
+    }
+    // A second call in the same process keeps the first cipher.
+    let _ = install(FieldCipher::new(&config.keys)?);
+    Ok(true)
+}
+
+/// The installed cipher, or `None` when encryption is disabled.
+pub fn cipher() -> Option<&'static FieldCipher> {
+    CIPHER.get()
+}
+
+/// Whether `value` starts like a sealed value. Plaintext that does would be
+/// read back as ciphertext, so such input is rejected before it is stored.
+pub fn has_reserved_prefix(value: &str) -> bool {
+    value.starts_with(PREFIX)
+}
+
+/// Encrypts `value` with the installed cipher, or passes it through when
+/// encryption is disabled.
+pub fn encrypt_field(value: &str, context: &[u8]) -> Result<String, FieldCryptoError> {
+    match cipher() {
+        Some(cipher) => cipher.encrypt(value, context),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// Decrypts `value` with the installed cipher. Plaintext passes through;
+/// an encrypted value without a cipher is an error rather than leaked as-is.
+pub fn decrypt_field(value: &str, context: &[u8]) -> Result<String, FieldCryptoError> {
+    match cipher() {
+        Some(cipher) => cipher.decrypt(value, context),
+        None if value.starts_with(PREFIX) => Err(FieldCryptoError::NotConfigured),
+        None => Ok(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(id: &str, byte: u8) -> EncryptionKey {
+        EncryptionKey {
+            id: id.to_string(),
+            key: STANDARD.encode([byte; 32]),
+        }
+    }
+
+    #[test]
+    fn test_round_trips_and_binds_context() {
+        let cipher = FieldCipher::new(&[key("k1", 1)]).unwrap();
+        let sealed = cipher.encrypt("secret plans", b"memo-1").unwrap();
+
+        assert!(sealed.starts_with("enc:v1:k1:"));
+        assert!(!sealed.contains("secret"));
+        assert_eq!(cipher.decrypt(&sealed, b"memo-1").unwrap(), "secret plans");
+        assert!(matches!(
+            cipher.decrypt(&sealed, b"memo-2"),
+            Err(FieldCryptoError::Decrypt)
+        ));
+    }
+
+    #[test]
+    fn test_old_keys_still_decrypt_after_rotation() {
+        let old = FieldCipher::new(&[key("k1", 1)]).unwrap();
+        let sealed = old.encrypt("before rotation", b"ctx").unwrap();
+
+        let rotated = FieldCipher::new(&[key("k2", 2), key("k1", 1)]).unwrap();
+        assert_eq!(rotated.current_key_id(), "k2");
+        assert!(!rotated.is_current(&sealed));
+        assert_eq!(rotated.decrypt(&sealed, b"ctx").unwrap(), "before rotation");
+        assert!(rotated.is_current(&rotated.encrypt("after", b"ctx").unwrap()));
+
+        let retired = FieldCipher::new(&[key("k2", 2)]).unwrap();
+        assert!(matches!(
+            retired.decrypt(&sealed, b"ctx"),
+            Err(FieldCryptoError::UnknownKey(id)) if id == "k1"
+        ));
+    }
+
+    #[test]
+    fn test_plaintext_passes_through() {
+        let cipher = FieldCipher::new(&[key("k1", 1)]).unwrap();
+        assert_eq!(
+            cipher.decrypt("legacy text", b"ctx").unwrap(),
+            "legacy text"
+        );
+        assert!(!cipher.is_current("legacy text"));
+    }
+
+    #[test]
+    fn test_invalid_keys_are_rejected() {
+        assert!(matches!(
+            FieldCipher::new(&[]),
+            Err(FieldCryptoError::NoKeys)
+        ));
+        assert!(matches!(
+            FieldCipher::new(&[EncryptionKey {
+                id: "short".to_string(),
+                key: STANDARD.encode([0u8; 16]),
+            }]),
+            Err(FieldCryptoError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            FieldCipher::new(&[key("a:b", 1)]),
+            Err(FieldCryptoError::InvalidKeyId(_))
+        ));
+        assert!(matches!(
+            FieldCipher::new(&[key("k1", 1), key("k1", 2)]),
+            Err(FieldCryptoError::InvalidKeyId(_))
+        ));
+    }
+}
@@ -0,0 +1,322 @@
+use prometheus::{IntCounter, IntGauge, Registry};
+use sea_orm::DbErr;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+use crate::config::settings::DatabaseConfig;
+use crate::error::AppError;
+
+/// Where the database circuit breaker stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests reach the database
+    Closed,
+    /// Requests fail fast until the open period ends
+    Open,
+    /// The open period ended; the next database call is let through as a
+    /// trial
+    HalfOpen,
+}
+
+impl CircuitState {
+    /// Value of the `db_circuit_state` gauge.
+    fn gauge_value(&self) -> i64 {
+        match self {
+            CircuitState::Closed => 0,
+            CircuitState::HalfOpen => 1,
+            CircuitState::Open => 2,
+        }
+    }
+}
+
+enum State {
+    Closed {
+        failures: u32,
+    },
+    Open {
+        until: Instant,
+    },
+    /// A trial whose outcome is never recorded, like a call whose future was
+    /// dropped, stops blocking others after the open period.
+    HalfOpen {
+        trial_started: Instant,
+    },
+}
+
+/// Stops sending work to a database that keeps refusing connections.
+///
+/// `DATABASE_CIRCUIT_FAILURE_THRESHOLD` consecutive connection failures open
+/// the circuit: [`Self::check`] then fails fast with
+/// [`AppError::DatabaseUnavailable`] instead of waiting out the pool's acquire
+/// timeout. After `DATABASE_CIRCUIT_OPEN_SECS` it half-opens, and the next
+/// database call is the trial whose outcome closes or reopens the circuit.
+/// Query errors such as constraint violations don't count; see
+/// [`is_connection_error`].
+///
+/// Outcomes are recorded by [`super::GuardedConnection`] around each call on
+/// the pool, so requests, jobs, scheduled tasks and the CLI all count, and
+/// only calls that used the database do. Requests are failed fast by
+/// [`crate::middleware::DatabaseCircuit`].
+///
+/// Cheap to clone; clones share the state. A threshold of 0 disables it.
+#[derive(Clone)]
+pub struct CircuitBreaker {
+    inner: Arc<Inner>,
+    metrics: Option<CircuitMetrics>,
+}
+
+struct Inner {
+    threshold: u32,
+    open_for: Duration,
+    state: Mutex<State>,
+}
+
+#[derive(Clone)]
+struct CircuitMetrics {
+    state: IntGauge,
+    opened: IntCounter,
+    rejected: IntCounter,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(0, Duration::ZERO)
+    }
+}
+
+impl CircuitBreaker {
+    pub fn new(threshold: u32, open_for: Duration) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                threshold,
+                open_for,
+                state: Mutex::new(State::Closed { failures: 0 }),
+            }),
+            metrics: None,
+        }
+    }
+
+    pub fn from_config(config: &DatabaseConfig) -> Self {
+        Self::new(
+            config.circuit_failure_threshold,
+            Duration::from_secs(config.circuit_open_secs),
+        )
+    }
+
+    /// Reports the state as `db_circuit_state` (0 closed, 1 half-open,
+    /// 2 open) and counts `db_circuit_opened_total` and
+    /// `db_circuit_rejected_total`.
+    pub fn with_metrics(mut self, registry: &Registry) -> prometheus::Result<Self> {
+        let metrics = CircuitMetrics {
+            state: IntGauge::new(
+                "db_circuit_state",
+                "Database circuit breaker state: 0 closed, 1 half-open, 2 open",
+            )?,
+            opened: IntCounter::new(
+                "db_circuit_opened_total",
+                "Times the database circuit breaker opened",
+            )?,
+            rejected: IntCounter::new(
+                "db_circuit_rejected_total",
+                "Requests failed fast while the database circuit was open",
+            )?,
+        };
+        registry.register(Box::new(metrics.state.clone()))?;
+        registry.register(Box::new(metrics.opened.clone()))?;
+        registry.register(Box::new(metrics.rejected.clone()))?;
+        self.metrics = Some(metrics);
+        Ok(self)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.threshold > 0
+    }
+
+    /// The current state; an open circuit whose period has ended reports
+    /// half-open, as the next request will be the trial.
+    pub fn state(&self) -> CircuitState {
+        match &*self.lock() {
+            State::Closed { .. } => CircuitState::Closed,
+            State::Open { until } if Instant::now() < *until => CircuitState::Open,
+            State::Open { .. } | State::HalfOpen { .. } => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Whether to start work that needs the database. Fails while the circuit
+    /// is open, and while the half-open trial is running; doesn't start the
+    /// trial, as the work may not touch the database at all.
+    pub fn check(&self) -> Result<(), AppError> {
+        if !self.is_enabled() {
+            return Ok(());
+        }
+        let state = self.lock();
+        let retry_after = match &*state {
+            State::Closed { .. } => return Ok(()),
+            State::Open { until } if Instant::now() >= *until => return Ok(()),
+            State::HalfOpen { trial_started } if trial_started.elapsed() >= self.inner.open_for => {
+                return Ok(());
+            }
+            State::Open { until } => until.saturating_duration_since(Instant::now()),
+            State::HalfOpen { trial_started } => {
+                self.inner.open_for.saturating_sub(trial_started.elapsed())
+            }
+        };
+        drop(state);
+
+        if let Some(metrics) = &self.metrics {
+            metrics.rejected.inc();
+        }
+        Err(AppError::DatabaseUnavailable {
+            // Rounded up, so clients don't come back before it half-opens
+            retry_after_secs: (retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0))
+                .max(1),
+        })
+    }
+
+    /// Marks the start of a call on the database. Once the open period has
+    /// ended, the first call is the trial; report its outcome, and that of
+    /// any other call, with [`Self::record`].
+    pub fn begin_call(&self) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut state = self.lock();
+        match &*state {
+            State::Open { until } if Instant::now() >= *until => {
+                tracing::info!("Database circuit half-open, letting a trial call through");
+            }
+            State::HalfOpen { trial_started } if trial_started.elapsed() >= self.inner.open_for => {
+            }
+            _ => return,
+        }
+        *state = State::HalfOpen {
+            trial_started: Instant::now(),
+        };
+        self.set_gauge(CircuitState::HalfOpen);
+    }
+
+    /// Records whether a call reached the database.
+    pub fn record(&self, reachable: bool) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut state = self.lock();
+        match (&*state, reachable) {
+            (State::Closed { .. }, true) => *state = State::Closed { failures: 0 },
+            (State::HalfOpen { .. }, true) => {
+                tracing::info!("Database reachable again, closing circuit");
+                *state = State::Closed { failures: 0 };
+                self.set_gauge(CircuitState::Closed);
+            }
+            (State::Closed { failures }, false) if failures + 1 < self.inner.threshold => {
+                *state = State::Closed {
+                    failures: failures + 1,
+                };
+            }
+            (State::Closed { .. } | State::HalfOpen { .. }, false) => {
+                tracing::warn!(
+                    open_secs = self.inner.open_for.as_secs(),
+                    "Database unreachable, opening circuit"
+                );
+                *state = State::Open {
+                    until: Instant::now() + self.inner.open_for,
+                };
+                self.set_gauge(CircuitState::Open);
+                if let Some(metrics) = &self.metrics {
+                    metrics.opened.inc();
+                }
+            }
+            // Calls started before the circuit opened
+            (State::Open { .. }, _) => {}
+        }
+    }
+
+    fn set_gauge(&self, state: CircuitState) {
+        if let Some(metrics) = &self.metrics {
+            metrics.state.set(state.gauge_value());
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        // The state is a plain enum, so a panic while holding the lock can't
+        // leave it half-updated.
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+/// Whether `e` means the database could not be reached, as opposed to a
+/// query failing.
+pub fn is_connection_error(e: &DbErr) -> bool {
+    matches!(e, DbErr::Conn(_) | DbErr::ConnectionAcquire(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_after_consecutive_failures_and_closes_after_a_trial() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(50));
+
+        breaker.record(false);
+        breaker.record(true);
+        breaker.record(false);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record(false);
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(matches!(
+            breaker.check(),
+            Err(AppError::DatabaseUnavailable {
+                retry_after_secs: 1
+            })
+        ));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        // Work that never reaches the database doesn't use up the trial
+        assert!(breaker.check().is_ok());
+        assert!(breaker.check().is_ok());
+        breaker.begin_call();
+        // Only the trial goes through
+        assert!(breaker.check().is_err());
+        breaker.record(false);
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        std::thread::sleep(Duration::from_millis(60));
+        breaker.begin_call();
+        breaker.record(true);
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.check().is_ok());
+    }
+
+    #[test]
+    fn test_an_abandoned_trial_stops_blocking_after_the_open_period() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        breaker.record(false);
+        std::thread::sleep(Duration::from_millis(60));
+        breaker.begin_call();
+        assert!(breaker.check().is_err());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(breaker.check().is_ok());
+        breaker.begin_call();
+        assert!(breaker.check().is_err());
+    }
+
+    #[test]
+    fn test_a_threshold_of_zero_disables_it() {
+        let breaker = CircuitBreaker::default();
+        for _ in 0..10 {
+            breaker.begin_call();
+            breaker.record(false);
+        }
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert!(breaker.check().is_ok());
+    }
+}
@@ -0,0 +1,147 @@
+use sea_orm::{
+    AccessMode, ConnectionTrait, DatabaseConnection, DatabaseTransaction, DbBackend, DbErr,
+    ExecResult, IsolationLevel, QueryResult, Statement, TransactionError, TransactionTrait,
+};
+use std::future::Future;
+use std::pin::Pin;
+
+use super::circuit_breaker::{CircuitBreaker, is_connection_error};
+
+/// The primary's pool, with every call reporting to a [`CircuitBreaker`].
+///
+/// A call that fails with a connection error counts as a failure, any other
+/// outcome (including a query error) as a success, as the database answered.
+/// Calls inside a transaction aren't seen, but beginning one is, and that's
+/// where a pool that can't reach the database fails.
+///
+/// Cheap to clone; clones share the pool and the breaker.
+#[derive(Clone)]
+pub struct GuardedConnection {
+    db: DatabaseConnection,
+    breaker: CircuitBreaker,
+}
+
+impl GuardedConnection {
+    pub fn new(db: DatabaseConnection, breaker: CircuitBreaker) -> Self {
+        Self { db, breaker }
+    }
+
+    pub fn breaker(&self) -> &CircuitBreaker {
+        &self.breaker
+    }
+
+    /// The pool itself, for what isn't a query, like closing it; calls made
+    /// on it aren't recorded.
+    pub fn inner(&self) -> &DatabaseConnection {
+        &self.db
+    }
+
+    async fn guard<T>(&self, call: impl Future<Output = Result<T, DbErr>>) -> Result<T, DbErr> {
+        self.breaker.begin_call();
+        let result = call.await;
+        self.breaker
+            .record(!matches!(&result, Err(e) if is_connection_error(e)));
+        result
+    }
+
+    async fn guard_transaction<T, E>(
+        &self,
+        call: impl Future<Output = Result<T, TransactionError<E>>>,
+    ) -> Result<T, TransactionError<E>> {
+        self.breaker.begin_call();
+        let result = call.await;
+        self.breaker.record(
+            !matches!(&result, Err(TransactionError::Connection(e)) if is_connection_error(e)),
+        );
+        result
+    }
+}
+
+/// Calls on a connection that isn't guarded, like a read replica's, are not
+/// recorded.
+impl From<DatabaseConnection> for GuardedConnection {
+    fn from(db: DatabaseConnection) -> Self {
+        Self::new(db, CircuitBreaker::default())
+    }
+}
+
+#[async_trait::async_trait]
+impl ConnectionTrait for GuardedConnection {
+    fn get_database_backend(&self) -> DbBackend {
+        self.db.get_database_backend()
+    }
+
+    async fn execute(&self, stmt: Statement) -> Result<ExecResult, DbErr> {
+        self.guard(self.db.execute(stmt)).await
+    }
+
+    async fn execute_unprepared(&self, sql: &str) -> Result<ExecResult, DbErr> {
+        self.guard(self.db.execute_unprepared(sql)).await
+    }
+
+    async fn query_one(&self, stmt: Statement) -> Result<Option<QueryResult>, DbErr> {
+        self.guard(self.db.query_one(stmt)).await
+    }
+
+    async fn query_all(&self, stmt: Statement) -> Result<Vec<QueryResult>, DbErr> {
+        self.guard(self.db.query_all(stmt)).await
+    }
+
+    fn support_returning(&self) -> bool {
+        self.db.support_returning()
+    }
+
+    fn is_mock_connection(&self) -> bool {
+        self.db.is_mock_connection()
+    }
+}
+
+#[async_trait::async_trait]
+impl TransactionTrait for GuardedConnection {
+    async fn begin(&self) -> Result<DatabaseTransaction, DbErr> {
+        self.guard(self.db.begin()).await
+    }
+
+    async fn begin_with_config(
+        &self,
+        isolation_level: Option<IsolationLevel>,
+        access_mode: Option<AccessMode>,
+    ) -> Result<DatabaseTransaction, DbErr> {
+        self.guard(self.db.begin_with_config(isolation_level, access_mode))
+            .await
+    }
+
+    async fn transaction<F, T, E>(&self, callback: F) -> Result<T, TransactionError<E>>
+    where
+        F: for<'c> FnOnce(
+                &'c DatabaseTransaction,
+            ) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'c>>
+            + Send,
+        T: Send,
+        E: std::fmt::Display + std::fmt::Debug + Send,
+    {
+        self.guard_transaction(self.db.transaction(callback)).await
+    }
+
+    async fn transaction_with_config<F, T, E>(
+        &self,
+        callback: F,
+        isolation_level: Option<IsolationLevel>,
+        access_mode: Option<AccessMode>,
+    ) -> Result<T, TransactionError<E>>
+    where
+        F: for<'c> FnOnce(
+                &'c DatabaseTransaction,
+            ) -> Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'c>>
+            + Send,
+        T: Send,
+        E: std::fmt::Display + std::fmt::Debug + Send,
+    {
+        self.guard_transaction(self.db.transaction_with_config(
+            callback,
+            isolation_level,
+            access_mode,
+        ))
+        .await
+    }
+}
@@ -0,0 +1,211 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use super::GuardedConnection;
+use crate::config::settings::HealthConfig;
+
+/// Database checks behind `/ready` and `/startup`.
+///
+/// Each result is reused for `cache_ttl_ms`, and a check still running makes
+/// concurrent probes wait for its result instead of starting their own, so
+/// aggressive probe intervals cost at most one query per TTL. A check taking
+/// longer than `check_timeout_ms` is abandoned and counts as failed.
+#[derive(Clone)]
+pub struct HealthProbes {
+    /// `None` when the database could not be asked
+    migrations: CachedCheck<Option<Vec<String>>>,
+}
+
+impl HealthProbes {
+    pub fn from_config(config: &HealthConfig) -> Self {
+        let ttl = Duration::from_millis(config.cache_ttl_ms);
+        let timeout = Duration::from_millis(config.check_timeout_ms);
+        Self {
+            migrations: CachedCheck::new(ttl, timeout),
+        }
+    }
+
+    /// Migrations the database is missing, or `None` when it could not be
+    /// reached. See [`super::pending_migrations`].
+    pub async fn pending_migrations(&self, db: &GuardedConnection) -> Option<Vec<String>> {
+        self.migrations
+            .get(
+                "pending_migrations",
+                async {
+                    super::pending_migrations(db)
+                        .await
+                        .inspect_err(|e| {
+                            tracing::warn!(error = %e, "Readiness check could not reach the database")
+                        })
+                        .ok()
+                },
+                None,
+            )
+            .await
+    }
+}
+
+/// What `/startup` waits for before the instance counts as started.
+///
+/// Each step latches: once the schema is migrated and the scheduler is
+/// running, the probe answers without touching the database again.
+#[derive(Clone)]
+pub struct StartupChecks {
+    migrations_applied: Arc<AtomicBool>,
+    scheduler_started: Arc<AtomicBool>,
+}
+
+impl StartupChecks {
+    /// With the scheduler disabled there is nothing to wait for on its side.
+    pub fn new(scheduler_enabled: bool) -> Self {
+        Self {
+            migrations_applied: Arc::new(AtomicBool::new(false)),
+            scheduler_started: Arc::new(AtomicBool::new(!scheduler_enabled)),
+        }
+    }
+
+    /// Called once the scheduler's task loops are spawned.
+    pub fn mark_scheduler_started(&self) {
+        self.scheduler_started.store(true, Ordering::Relaxed);
+    }
+
+    /// Steps still in progress (`"migrations"`, `"scheduler"`); empty once
+    /// started.
+    pub async fn pending(
+        &self,
+        probes: &HealthProbes,
+        db: &GuardedConnection,
+    ) -> Vec<&'static str> {
+        if !self.migrations_applied.load(Ordering::Relaxed)
+            && probes
+                .pending_migrations(db)
+                .await
+                .is_some_and(|pending| pending.is_empty())
+        {
+            self.migrations_applied.store(true, Ordering::Relaxed);
+        }
+
+        let mut pending = Vec::new();
+        if !self.migrations_applied.load(Ordering::Relaxed) {
+            pending.push("migrations");
+        }
+        if !self.scheduler_started.load(Ordering::Relaxed) {
+            pending.push("scheduler");
+        }
+        pending
+    }
+}
+
+/// The last result of one check and when it was taken.
+#[derive(Clone)]
+struct CachedCheck<T> {
+    last: Arc<Mutex<Option<(Instant, T)>>>,
+    ttl: Duration,
+    timeout: Duration,
+}
+
+impl<T: Clone> CachedCheck<T> {
+    fn new(ttl: Duration, timeout: Duration) -> Self {
+        Self {
+            last: Arc::new(Mutex::new(None)),
+            ttl,
+            timeout,
+        }
+    }
+
+    /// The cached result while fresh, otherwise the result of `check`, or
+    /// `timed_out` when it doesn't finish in time.
+    async fn get(&self, name: &str, check: impl Future<Output = T>, timed_out: T) -> T {
+        // Held across the check, so probes arriving meanwhile wait for its
+        // result (at most one timeout) rather than querying in parallel.
+        let mut last = self.last.lock().await;
+        if let Some((taken_at, value)) = last.as_ref()
+            && taken_at.elapsed() < self.ttl
+        {
+            return value.clone();
+        }
+
+        let value = match tokio::time::timeout(self.timeout, check).await {
+            Ok(value) => value,
+            Err(_) => {
+                tracing::warn!(
+                    check = name,
+                    timeout_ms = self.timeout.as_millis() as u64,
+                    "Health check timed out"
+                );
+                timed_out
+            }
+        };
+        *last = Some((Instant::now(), value.clone()));
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_result_is_reused_within_ttl() {
+        let cache = CachedCheck::new(Duration::from_secs(60), Duration::from_secs(1));
+        let calls = AtomicUsize::new(0);
+        let check = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            true
+        };
+
+        assert!(cache.get("test", check(), false).await);
+        assert!(cache.get("test", check(), false).await);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_zero_ttl_checks_every_time() {
+        let cache = CachedCheck::new(Duration::ZERO, Duration::from_secs(1));
+        let calls = AtomicUsize::new(0);
+        let check = || async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            true
+        };
+
+        cache.get("test", check(), false).await;
+        cache.get("test", check(), false).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_slow_check_times_out_and_is_cached() {
+        let cache = CachedCheck::new(Duration::from_secs(60), Duration::from_millis(20));
+        let slow = async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            true
+        };
+
+        assert!(!cache.get("test", slow, false).await);
+        assert!(!cache.get("test", async { true }, false).await);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_probes_share_one_check() {
+        let cache = CachedCheck::new(Duration::from_secs(60), Duration::from_secs(1));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let check = || {
+            let calls = calls.clone();
+            async move {
+                calls.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                true
+            }
+        };
+
+        let results =
+            futures_util::future::join_all((0..10).map(|_| cache.get("test", check(), false)))
+                .await;
+        assert!(results.into_iter().all(|ok| ok));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
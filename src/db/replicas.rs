@@ -0,0 +1,83 @@
+use sea_orm::{Database, DatabaseConnection, DbErr};
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::circuit_breaker::is_connection_error;
+use super::{GuardedConnection, SlowQueryLog};
+use crate::config::settings::DatabaseConfig;
+use crate::error::AppError;
+
+/// Read replicas from `DATABASE_REPLICA_URLS`, taken in turn for reads that
+/// can tolerate replication lag. Empty when none are configured.
+#[derive(Clone, Default)]
+pub struct ReadReplicas {
+    connections: Arc<[DatabaseConnection]>,
+    next: Arc<AtomicUsize>,
+}
+
+impl ReadReplicas {
+    pub fn new(connections: Vec<DatabaseConnection>) -> Self {
+        Self {
+            connections: connections.into(),
+            next: Arc::default(),
+        }
+    }
+
+    /// Opens a lazy pool per replica URL with the primary's pool settings, so
+    /// a replica that is down at startup doesn't hold it up; its reads fall
+    /// back to the primary until it is back.
+    pub async fn connect(config: &DatabaseConfig) -> Result<Self, DbErr> {
+        let mut connections = Vec::with_capacity(config.replica_urls.len());
+        for url in &config.replica_urls {
+            let mut options = super::connect_options(url, config);
+            options.connect_lazy(true);
+            connections.push(Database::connect(options).await?);
+        }
+        Ok(Self::new(connections))
+    }
+
+    /// Times the replicas' statements too.
+    pub fn with_slow_query_log(self, log: &SlowQueryLog) -> Self {
+        let mut connections = self.connections.to_vec();
+        connections.iter_mut().for_each(|db| log.install(db));
+        Self::new(connections)
+    }
+
+    pub fn len(&self) -> usize {
+        self.connections.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.connections.is_empty()
+    }
+
+    /// The next replica, round robin.
+    pub fn pick(&self) -> Option<&DatabaseConnection> {
+        if self.connections.is_empty() {
+            return None;
+        }
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.connections.len();
+        self.connections.get(index)
+    }
+
+    /// Runs `read` on the next replica, and on `primary` when there is none or
+    /// the replica could not be reached. Errors of the query itself are
+    /// returned as they are. Only the primary's calls reach its circuit
+    /// breaker.
+    pub async fn read<T, F, Fut>(&self, primary: &GuardedConnection, read: F) -> Result<T, AppError>
+    where
+        F: Fn(GuardedConnection) -> Fut,
+        Fut: Future<Output = Result<T, AppError>>,
+    {
+        if let Some(replica) = self.pick() {
+            match read(replica.clone().into()).await {
+                Err(AppError::Database(e)) if is_connection_error(&e) => {
+                    tracing::warn!(error = %e, "Read replica unreachable, falling back to the primary");
+                }
+                result => return result,
+            }
+        }
+        read(primary.clone()).await
+    }
+}
@@ -0,0 +1,173 @@
+use prometheus::{IntCounterVec, Opts};
+use sea_orm::DatabaseConnection;
+use sea_orm::metric::Info;
+use std::time::Duration;
+use tracing_subscriber::Registry;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config::settings::DatabaseConfig;
+
+/// Logs and counts statements slower than `DATABASE_SLOW_QUERY_MS`, to catch
+/// missing indexes before they become outages.
+///
+/// Hooks SeaORM's per-statement metric callback, so every statement on an
+/// instrumented pool is timed, including those in transactions. A slow one is
+/// logged at `WARN` with its name, duration, SQL (with `$n` placeholders) and
+/// the types of its bound values, which are never logged themselves, and
+/// counted in `db_slow_queries_total{query}`.
+///
+/// The name is that of the current span, e.g. `memo_repository::find_all` for
+/// a repository method, or else the operation and table from the SQL
+/// (`SELECT memos`) when spans are filtered out.
+#[derive(Clone)]
+pub struct SlowQueryLog {
+    threshold: Duration,
+    counter: Option<IntCounterVec>,
+}
+
+impl SlowQueryLog {
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            counter: None,
+        }
+    }
+
+    pub fn from_config(config: &DatabaseConfig) -> Self {
+        Self::new(Duration::from_millis(config.slow_query_ms))
+    }
+
+    pub fn with_metrics(mut self, registry: &prometheus::Registry) -> prometheus::Result<Self> {
+        let counter = IntCounterVec::new(
+            Opts::new(
+                "db_slow_queries_total",
+                "Database statements slower than DATABASE_SLOW_QUERY_MS, by query name",
+            ),
+            &["query"],
+        )?;
+        registry.register(Box::new(counter.clone()))?;
+        self.counter = Some(counter);
+        Ok(self)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.threshold.is_zero()
+    }
+
+    /// Times the statements run on `db`; clones made earlier aren't affected.
+    pub fn install(&self, db: &mut DatabaseConnection) {
+        if !self.is_enabled() {
+            return;
+        }
+        let log = self.clone();
+        db.set_metric_callback(move |info| log.observe(info));
+    }
+
+    fn observe(&self, info: &Info<'_>) {
+        if info.elapsed < self.threshold {
+            return;
+        }
+        let sql = info.statement.sql.as_str();
+        let query = current_span_name().unwrap_or_else(|| statement_name(sql));
+        let params = info
+            .statement
+            .values
+            .as_ref()
+            .map(|values| {
+                values
+                    .0
+                    .iter()
+                    .map(|value| {
+                        let debug = format!("{value:?}");
+                        debug.split('(').next().unwrap_or_default().to_string()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+            .unwrap_or_default();
+
+        tracing::warn!(
+            query = %query,
+            duration_ms = info.elapsed.as_millis() as u64,
+            threshold_ms = self.threshold.as_millis() as u64,
+            failed = info.failed,
+            statement = sql,
+            params = %params,
+            "Slow database query"
+        );
+        if let Some(counter) = &self.counter {
+            counter.with_label_values(&[query.as_str()]).inc();
+        }
+    }
+}
+
+/// `memo_repository::find_all` for a span named `find_all` in
+/// `crate::repository::memo_repository`: the innermost span outside SeaORM,
+/// which runs statements in spans of its own.
+fn current_span_name() -> Option<String> {
+    let id = tracing::Span::current().id()?;
+    tracing::dispatcher::get_default(|dispatch| {
+        let registry = dispatch.downcast_ref::<Registry>()?;
+        let span = registry
+            .span(&id)?
+            .scope()
+            .find(|span| !span.metadata().target().starts_with("sea_orm"))?;
+        let metadata = span.metadata();
+        let module = metadata.target().rsplit("::").next().unwrap_or_default();
+        Some(format!("{module}::{}", metadata.name()))
+    })
+}
+
+/// The operation and first table of `sql`, like `SELECT memos`.
+fn statement_name(sql: &str) -> String {
+    let mut words = sql.split_whitespace();
+    let operation = words.next().unwrap_or_default().to_ascii_uppercase();
+    let table = if operation == "UPDATE" {
+        words.next()
+    } else {
+        words
+            .skip_while(|word| !matches!(word.to_ascii_uppercase().as_str(), "FROM" | "INTO"))
+            .nth(1)
+    }
+    .map(|table| table.trim_matches(|c: char| c == '"' || c == '(' || c == ';'));
+    match table {
+        Some(table) if !table.is_empty() => format!("{operation} {table}"),
+        _ => operation,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_statements_by_operation_and_table() {
+        for (sql, name) in [
+            (
+                r#"SELECT "memos"."id" FROM "memos" WHERE "memos"."id" = $1"#,
+                "SELECT memos",
+            ),
+            (
+                r#"INSERT INTO "memo_snoozes" ("memo_id") VALUES ($1)"#,
+                "INSERT memo_snoozes",
+            ),
+            (
+                r#"UPDATE "memos" SET "completed" = $1 WHERE "memos"."id" = $2"#,
+                "UPDATE memos",
+            ),
+            (
+                r#"DELETE FROM "operations" WHERE "expires_at" < $1"#,
+                "DELETE operations",
+            ),
+            ("SELECT 1", "SELECT"),
+        ] {
+            assert_eq!(statement_name(sql), name);
+        }
+    }
+
+    #[test]
+    fn test_a_zero_threshold_disables_it() {
+        assert!(!SlowQueryLog::new(Duration::ZERO).is_enabled());
+        assert!(SlowQueryLog::new(Duration::from_millis(1)).is_enabled());
+    }
+}
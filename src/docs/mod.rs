@@ -1,3 +1,3 @@
 pub mod openapi;
 
-pub use openapi::ApiDoc;
+pub use openapi::{ApiDoc, ApiSection, SECTIONS};
@@ -1,17 +1,144 @@
-use utoipa::OpenApi;
+use utoipa::{
+    Modify, OpenApi,
+    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+};
 
 use crate::{
-    dto::{CreateMemoDto, MemoResponseDto, PaginatedMemoResponse, PatchMemoDto, UpdateMemoDto},
-    error::ErrorResponse,
-    handlers::{health, memos},
+    db::CircuitState,
+    dto::{
+        ApiKeyUsageDto, AttachmentDto, BatchCompleteDto, BatchCompleteResponse, BatchDeleteDto,
+        BatchFilterDto, BlockerDto, BoardColumn, CompleteMemoDto, CountMode, CreateMemoDto,
+        CreateWebhookIntegrationDto, DailyCountDto, DailyUsageDto, DeleteMemosResponse,
+        ExportFormat, InstantiateTemplateDto, LinkBlockerDto, MemoImportFailureDto,
+        MemoImportResult, MemoResponseDto, MemoSnoozeDto, MemoStatsDto, MemoTemplateDto,
+        MoveMemoDto, NotificationDto, NotificationListResponse, OperationDto, OperationErrorDto,
+        OperationStatus, PaginatedMemoResponse, PatchMemoDto, PushKeysDto, PushSubscriptionDto,
+        PushUnsubscribeDto, SaveMemoTemplateDto, SignedAttachmentUrlDto, SnoozeMemoDto,
+        SnoozePreset, UndoResponse, UpdateMemoDto, VapidPublicKeyResponse, WebhookIntegrationDto,
+    },
+    error::{ErrorCode, ErrorResponse, FieldError},
+    handlers::{
+        admin, attachments, health, imports, integrations, memos, notifications, operations, push,
+        templates, usage, web,
+    },
+    integrations::{WebhookEvent, WebhookProvider},
+    observability::metrics,
 };
 
+/// The whole API: every [`SECTIONS`] document merged under one title.
+pub struct ApiDoc;
+
+impl OpenApi for ApiDoc {
+    fn openapi() -> utoipa::openapi::OpenApi {
+        SECTIONS
+            .iter()
+            .fold(ApiInfo::openapi(), |mut doc, section| {
+                doc.merge((section.paths)());
+                doc
+            })
+    }
+}
+
+/// One tag's operations, also served on their own at `/api-docs/{name}.json`
+/// so the document stays navigable as the API grows.
+pub struct ApiSection {
+    /// Name in the document's title and Swagger UI's document picker
+    pub name: &'static str,
+    /// Where the document is served
+    pub url: &'static str,
+    /// Tag of every operation in the section
+    pub tag: &'static str,
+    paths: fn() -> utoipa::openapi::OpenApi,
+}
+
+impl ApiSection {
+    pub fn find(name: &str) -> Option<&'static ApiSection> {
+        SECTIONS.iter().find(|section| section.name == name)
+    }
+
+    /// The section's operations and schemas, with the shared info and error
+    /// schemas and only its own tag.
+    pub fn openapi(&self) -> utoipa::openapi::OpenApi {
+        let mut doc = ApiInfo::openapi();
+        doc.info.title = format!("{} ({})", doc.info.title, self.name);
+        if let Some(tags) = doc.tags.as_mut() {
+            tags.retain(|tag| tag.name == self.tag);
+        }
+        doc.merge((self.paths)());
+        doc
+    }
+}
+
+pub const SECTIONS: &[ApiSection] = &[
+    ApiSection {
+        name: "memos",
+        url: "/api-docs/memos.json",
+        tag: "memos",
+        paths: MemosApi::openapi,
+    },
+    ApiSection {
+        name: "templates",
+        url: "/api-docs/templates.json",
+        tag: "templates",
+        paths: TemplatesApi::openapi,
+    },
+    ApiSection {
+        name: "operations",
+        url: "/api-docs/operations.json",
+        tag: "operations",
+        paths: OperationsApi::openapi,
+    },
+    ApiSection {
+        name: "push",
+        url: "/api-docs/push.json",
+        tag: "push",
+        paths: PushApi::openapi,
+    },
+    ApiSection {
+        name: "notifications",
+        url: "/api-docs/notifications.json",
+        tag: "notifications",
+        paths: NotificationsApi::openapi,
+    },
+    ApiSection {
+        name: "integrations",
+        url: "/api-docs/integrations.json",
+        tag: "integrations",
+        paths: IntegrationsApi::openapi,
+    },
+    ApiSection {
+        name: "usage",
+        url: "/api-docs/usage.json",
+        tag: "usage",
+        paths: UsageApi::openapi,
+    },
+    ApiSection {
+        name: "observability",
+        url: "/api-docs/observability.json",
+        tag: "Observability",
+        paths: ObservabilityApi::openapi,
+    },
+    ApiSection {
+        name: "admin",
+        url: "/api-docs/admin.json",
+        tag: "Admin",
+        paths: AdminApi::openapi,
+    },
+    ApiSection {
+        name: "web",
+        url: "/api-docs/web.json",
+        tag: "web",
+        paths: WebApi::openapi,
+    },
+];
+
+/// Title, tags, error schemas and security schemes shared by every document.
 #[derive(OpenApi)]
 #[openapi(
     info(
         title = "Memos API",
         version = "0.1.0",
-        description = "A RESTful API for managing memos with full CRUD operations, pagination, filtering, and sorting. Includes observability endpoints for health checks and metrics.",
+        description = "A RESTful API for managing memos with full CRUD operations, pagination, filtering, and sorting. Includes observability endpoints for health checks and metrics. Each tag is also available as its own document at `/api-docs/{tag}.json`.",
         contact(
             name = "API Support",
             email = "support@example.com"
@@ -20,32 +147,265 @@ use crate::{
             name = "MIT",
         )
     ),
+    components(schemas(ErrorResponse, FieldError, ErrorCode)),
+    tags(
+        (name = "memos", description = "Memo management endpoints. Request bodies may also be sent as `application/msgpack` or `application/cbor`, and memo responses follow the `Accept` header (JSON, XML, YAML, MessagePack or CBOR). Memos are rendered as JSON:API resources for `Accept: application/vnd.api+json`. With `Prefer: envelope`, response bodies are wrapped as `{ data, meta: { request_id, duration_ms }, links: { self, ... } }`."),
+        (name = "templates", description = "Reusable memo templates with `{{placeholder}}` substitution"),
+        (name = "push", description = "Browser push notifications for due memos (requires `VAPID_PRIVATE_KEY`)"),
+        (name = "integrations", description = "Slack and Discord incoming webhooks for memo events"),
+        (name = "operations", description = "Long-running requests. Batch endpoints sent with `Prefer: respond-async` answer `202 Accepted` with an operation to poll"),
+        (name = "notifications", description = "In-app notification inbox, filled by the scheduler (e.g. memos about to become due)"),
+        (name = "usage", description = "Request counts and monthly quotas of API keys. Requests sent with `X-API-Key` count against the key's quota and carry `X-Quota-Limit`, `X-Quota-Remaining` and `X-Quota-Reset` (seconds); past the quota they get `429 QUOTA_EXCEEDED` until the next month (UTC)"),
+        (name = "Observability", description = "Health checks, build information and Prometheus metrics"),
+        (name = "Admin", description = "Operator endpoints, authenticated with `Authorization: Bearer <ADMIN_TOKEN>`"),
+        (name = "web", description = "Server-rendered HTML pages and the htmx fragments they load. Forms are posted as `application/x-www-form-urlencoded`; successful changes set a flash message cookie, and errors answer with the usual JSON error body")
+    ),
+    modifiers(&AdminSecurity, &ApiKeySecurity)
+)]
+struct ApiInfo;
+
+#[derive(OpenApi)]
+#[openapi(
     paths(
         memos::list_memos,
+        memos::memo_stats,
+        memos::export_memos,
+        imports::import_memos_async,
+        imports::list_import_failures,
         memos::get_memo,
         memos::create_memo,
         memos::update_memo,
         memos::patch_memo,
         memos::delete_memo,
+        memos::delete_memos,
+        memos::undo_delete,
         memos::toggle_complete,
+        memos::move_memo,
+        memos::complete_memos,
+        memos::duplicate_memo,
+        memos::archive_memo,
+        memos::unarchive_memo,
+        memos::toggle_pinned,
+        memos::toggle_starred,
+        memos::snooze_memo,
+        memos::list_memo_snoozes,
+        memos::add_memo_blocker,
+        memos::remove_memo_blocker,
+        attachments::get_attachment_url,
+    ),
+    components(schemas(
+        MemoResponseDto,
+        AttachmentDto,
+        SignedAttachmentUrlDto,
+        MemoStatsDto,
+        BatchCompleteDto,
+        CompleteMemoDto,
+        BatchFilterDto,
+        CountMode,
+        ExportFormat,
+        MemoImportResult,
+        MemoImportFailureDto,
+        BatchCompleteResponse,
+        BatchDeleteDto,
+        DeleteMemosResponse,
+        UndoResponse,
+        OperationDto,
+        OperationStatus,
+        OperationErrorDto,
+        DailyCountDto,
+        CreateMemoDto,
+        UpdateMemoDto,
+        PatchMemoDto,
+        MoveMemoDto,
+        SnoozeMemoDto,
+        SnoozePreset,
+        MemoSnoozeDto,
+        LinkBlockerDto,
+        BlockerDto,
+        PaginatedMemoResponse,
+    ))
+)]
+struct MemosApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        templates::list_memo_templates,
+        templates::create_memo_template,
+        templates::get_memo_template,
+        templates::update_memo_template,
+        templates::delete_memo_template,
+        templates::create_memo_from_template,
+    ),
+    components(schemas(
+        SaveMemoTemplateDto,
+        MemoTemplateDto,
+        InstantiateTemplateDto,
+        MemoResponseDto,
+        AttachmentDto,
+    ))
+)]
+struct TemplatesApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(operations::get_operation, operations::operation_events),
+    components(schemas(OperationDto, OperationStatus, OperationErrorDto))
+)]
+struct OperationsApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        push::get_push_public_key,
+        push::subscribe_push,
+        push::unsubscribe_push
+    ),
+    components(schemas(
+        PushSubscriptionDto,
+        PushKeysDto,
+        PushUnsubscribeDto,
+        VapidPublicKeyResponse,
+    ))
+)]
+struct PushApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        notifications::list_notifications,
+        notifications::mark_notification_read,
+    ),
+    components(schemas(NotificationDto, NotificationListResponse))
+)]
+struct NotificationsApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        integrations::list_webhook_integrations,
+        integrations::create_webhook_integration,
+        integrations::delete_webhook_integration,
+    ),
+    components(schemas(
+        CreateWebhookIntegrationDto,
+        WebhookIntegrationDto,
+        WebhookProvider,
+        WebhookEvent,
+    ))
+)]
+struct IntegrationsApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(usage::get_my_usage),
+    components(schemas(ApiKeyUsageDto, DailyUsageDto))
+)]
+struct UsageApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
         health::health,
         health::ready,
+        health::startup,
+        health::version,
+        metrics::metrics
     ),
-    components(
-        schemas(
-            MemoResponseDto,
-            CreateMemoDto,
-            UpdateMemoDto,
-            PatchMemoDto,
-            PaginatedMemoResponse,
-            ErrorResponse,
-            health::HealthResponse,
-            health::ReadyResponse,
-        )
+    components(schemas(
+        health::HealthResponse,
+        health::ReadyResponse,
+        health::StartupResponse,
+        health::VersionResponse,
+        CircuitState,
+    ))
+)]
+struct ObservabilityApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        admin::get_maintenance,
+        admin::set_maintenance,
+        admin::preview_retention,
+        admin::get_runtime_diagnostics,
     ),
-    tags(
-        (name = "memos", description = "Memo management endpoints"),
-        (name = "Observability", description = "Health checks and monitoring endpoints. Metrics available at /metrics endpoint (Prometheus format).")
-    )
+    components(schemas(
+        admin::MaintenanceStatus,
+        admin::SetMaintenanceDto,
+        admin::RetentionPreview,
+        admin::RuntimeStats,
+        admin::RuntimeDiagnostics,
+    ))
 )]
-pub struct ApiDoc;
+struct AdminApi;
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        web::index,
+        web::get_memos_list,
+        web::get_new_memo_form,
+        web::create_memo_web,
+        web::get_edit_memo_form,
+        web::update_memo_web,
+        web::get_edit_title_form,
+        web::update_memo_title_web,
+        web::delete_memo_web,
+        web::undo_delete_web,
+        web::get_flash,
+        web::toggle_memo_complete_web,
+        web::toggle_memo_pinned_web,
+        web::toggle_memo_starred_web,
+        web::snooze_memo_web,
+        web::archive_memo_web,
+        web::reorder_memos_web,
+        web::get_calendar,
+        web::get_calendar_grid,
+        web::get_board,
+        web::get_board_columns,
+        web::move_memo_on_board_web,
+        web::get_notifications,
+        attachments::get_attachment,
+        attachments::get_attachment_thumbnail,
+    ),
+    components(schemas(
+        web::WebCreateMemoForm,
+        web::WebCreateMemoUpload,
+        web::WebUpdateMemoForm,
+        web::WebUpdateTitleForm,
+        web::WebSnoozeForm,
+        web::WebArchiveForm,
+        web::WebReorderMemosForm,
+        web::WebBoardMoveForm,
+        BoardColumn,
+        SnoozePreset,
+    ))
+)]
+struct WebApi;
+
+struct AdminSecurity;
+
+impl Modify for AdminSecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "admin_token",
+                SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+            );
+        }
+    }
+}
+
+struct ApiKeySecurity;
+
+impl Modify for ApiKeySecurity {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        if let Some(components) = openapi.components.as_mut() {
+            components.add_security_scheme(
+                "api_key",
+                SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-API-Key"))),
+            );
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::repository::AttachmentMeta;
+
+/// An image attached to a memo.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct AttachmentDto {
+    #[schema(example = "3f2b8c1d-7e6a-4b59-8d0c-1a2b3c4d5e6f")]
+    pub id: Uuid,
+    #[schema(example = "receipt.jpg")]
+    pub file_name: String,
+    /// Detected from the file: image/png, image/jpeg, image/gif or image/webp
+    #[schema(example = "image/jpeg")]
+    pub content_type: String,
+    #[schema(example = 482133)]
+    pub size_bytes: i64,
+    #[schema(example = 1600)]
+    pub width: i32,
+    #[schema(example = 1200)]
+    pub height: i32,
+    /// Path of the file as uploaded; it is only served with a signature from
+    /// `GET /api/v1/attachments/{id}/url`
+    #[schema(example = "/web/attachments/3f2b8c1d-7e6a-4b59-8d0c-1a2b3c4d5e6f")]
+    pub url: String,
+    /// Path of a JPEG at most 320 pixels on its longest side, signed like `url`
+    #[schema(example = "/web/attachments/3f2b8c1d-7e6a-4b59-8d0c-1a2b3c4d5e6f/thumbnail")]
+    pub thumbnail_url: String,
+}
+
+impl From<AttachmentMeta> for AttachmentDto {
+    fn from(meta: AttachmentMeta) -> Self {
+        Self {
+            url: format!("/web/attachments/{}", meta.id),
+            thumbnail_url: format!("/web/attachments/{}/thumbnail", meta.id),
+            id: meta.id,
+            file_name: meta.file_name,
+            content_type: meta.content_type,
+            size_bytes: meta.size_bytes,
+            width: meta.width,
+            height: meta.height,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AttachmentUrlParams {
+    /// Seconds the URLs stay valid, up to `SIGNED_URL_MAX_TTL_SECS`
+    pub ttl: Option<u64>,
+}
+
+/// Expiring URLs of an attachment that work without auth headers.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct SignedAttachmentUrlDto {
+    #[schema(
+        example = "/web/attachments/3f2b8c1d-7e6a-4b59-8d0c-1a2b3c4d5e6f?expires=1767225600&signature=q1w2e3"
+    )]
+    pub url: String,
+    #[schema(
+        example = "/web/attachments/3f2b8c1d-7e6a-4b59-8d0c-1a2b3c4d5e6f/thumbnail?expires=1767225600&signature=r4t5y6"
+    )]
+    pub thumbnail_url: String,
+    pub expires_at: DateTime<Utc>,
+}
@@ -1,22 +1,204 @@
-use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use chrono::{
+    DateTime, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Utc,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use url::Url;
 use utoipa::ToSchema;
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
+
+use crate::dto::AttachmentDto;
+use crate::entities::memo_import_failures;
+use crate::utils::markdown;
+
+/// A due date as sent by clients: RFC 3339 with an offset
+/// (`2025-01-31T12:00:00Z`), or a local date and time without one
+/// (`2025-01-31T12:00`) that is read in the memo's timezone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DueDate {
+    Absolute(DateTime<FixedOffset>),
+    Local(NaiveDateTime),
+}
+
+impl DueDate {
+    /// The instant this due date denotes, reading local times in `tz`.
+    /// Local times skipped or repeated by a daylight saving change are
+    /// rejected, since they name no single instant.
+    pub fn resolve(self, tz: Tz) -> Result<DateTime<Utc>, String> {
+        match self {
+            DueDate::Absolute(date) => Ok(date.with_timezone(&Utc)),
+            DueDate::Local(local) => match tz.from_local_datetime(&local) {
+                LocalResult::Single(date) => Ok(date.with_timezone(&Utc)),
+                LocalResult::Ambiguous(_, _) => Err(format!(
+                    "date_to {} occurs twice in {} (daylight saving time ends); include an offset",
+                    local, tz
+                )),
+                LocalResult::None => Err(format!(
+                    "date_to {} does not exist in {} (daylight saving time starts)",
+                    local, tz
+                )),
+            },
+        }
+    }
+
+    /// Reads an informal due date such as `tomorrow 5pm` or `next friday`
+    /// relative to `now`, in `now`'s timezone.
+    pub fn parse_natural(text: &str, now: DateTime<Tz>) -> Result<Self, String> {
+        super::natural_date::parse(text, now)
+    }
+}
+
+impl From<DateTime<Utc>> for DueDate {
+    fn from(date: DateTime<Utc>) -> Self {
+        DueDate::Absolute(date.fixed_offset())
+    }
+}
+
+impl std::str::FromStr for DueDate {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+            return Ok(DueDate::Absolute(date));
+        }
+        ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%dT%H:%M"]
+            .iter()
+            .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+            .map(DueDate::Local)
+            .ok_or_else(|| {
+                format!(
+                    "Invalid date_to '{}': expected RFC 3339 (2025-01-31T12:00:00Z) or a local time (2025-01-31T12:00)",
+                    value
+                )
+            })
+    }
+}
+
+impl fmt::Display for DueDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DueDate::Absolute(date) => write!(f, "{}", date.to_rfc3339()),
+            DueDate::Local(local) => write!(f, "{}", local.format("%Y-%m-%dT%H:%M:%S%.f")),
+        }
+    }
+}
+
+impl Serialize for DueDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for DueDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+fn validate_timezone(name: &str) -> Result<(), ValidationError> {
+    crate::i18n::timezone::parse(name)
+        .map(|_| ())
+        .map_err(|e| ValidationError::new("timezone").with_message(e.into()))
+}
+
+/// Colors a memo can be labelled with, each with a matching accent in the
+/// web UI.
+pub const MEMO_COLORS: [&str; 7] = ["red", "orange", "yellow", "green", "blue", "purple", "gray"];
+
+fn validate_color(color: &str) -> Result<(), ValidationError> {
+    if MEMO_COLORS.contains(&color) {
+        return Ok(());
+    }
+    Err(ValidationError::new("color")
+        .with_message(format!("color must be one of {}", MEMO_COLORS.join(", ")).into()))
+}
+
+/// Like [`validate_color`], also accepting the empty string that removes a
+/// label in a patch.
+fn validate_patch_color(color: &str) -> Result<(), ValidationError> {
+    if color.is_empty() {
+        return Ok(());
+    }
+    validate_color(color)
+}
+
+/// Descriptions starting with the prefix of encrypted values would be read
+/// back as ciphertext.
+fn validate_description(description: &str) -> Result<(), ValidationError> {
+    if crate::crypto::has_reserved_prefix(description) {
+        return Err(ValidationError::new("reserved_prefix")
+            .with_message("Description must not start with \"enc:v1:\"".into()));
+    }
+    Ok(())
+}
+
+fn validate_create_due_date(dto: &CreateMemoDto) -> Result<(), ValidationError> {
+    single_due_date(dto.date_to.is_some(), dto.date_to_text.is_some())
+}
+
+fn validate_patch(dto: &PatchMemoDto) -> Result<(), ValidationError> {
+    if dto.is_empty() {
+        return Err(ValidationError::new("empty").with_message(
+            "Provide at least one of title, description, date_to, date_to_text, completed, timezone or color"
+                .into(),
+        ));
+    }
+    single_due_date(dto.date_to.is_some(), dto.date_to_text.is_some())
+}
+
+fn single_due_date(date_to: bool, date_to_text: bool) -> Result<(), ValidationError> {
+    if date_to && date_to_text {
+        return Err(ValidationError::new("due_date")
+            .with_message("Provide either date_to or date_to_text, not both".into()));
+    }
+    Ok(())
+}
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[validate(schema(function = "validate_create_due_date"))]
 pub struct CreateMemoDto {
     #[validate(length(
         min = 1,
         max = 200,
         message = "Title must be between 1 and 200 characters"
     ))]
+    #[schema(example = "Pay rent")]
     pub title: String,
 
-    #[validate(length(max = 1000, message = "Description must not exceed 1000 characters"))]
+    #[validate(
+        length(max = 1000, message = "Description must not exceed 1000 characters"),
+        custom(function = "validate_description")
+    )]
+    #[schema(example = "Transfer to landlord before noon")]
     pub description: Option<String>,
 
-    pub date_to: DateTime<Utc>,
+    /// Required unless `date_to_text` is given
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "2025-01-31T12:00:00Z")]
+    pub date_to: Option<DueDate>,
+
+    /// Informal due date such as `tomorrow 5pm`, `next friday` or
+    /// `in 3 days`, read in the memo's timezone; replaces `date_to`
+    #[serde(default)]
+    #[validate(length(max = 100, message = "date_to_text must not exceed 100 characters"))]
+    #[schema(example = "tomorrow 5pm")]
+    pub date_to_text: Option<String>,
+
+    /// IANA timezone local due dates are read in; defaults to the viewer's
+    /// `Time-Zone`, else UTC
+    #[serde(default)]
+    #[validate(custom(function = "validate_timezone"))]
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: Option<String>,
+
+    /// Color label: red, orange, yellow, green, blue, purple or gray
+    #[serde(default)]
+    #[validate(custom(function = "validate_color"))]
+    #[schema(example = "blue")]
+    pub color: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
@@ -26,48 +208,554 @@ pub struct UpdateMemoDto {
         max = 200,
         message = "Title must be between 1 and 200 characters"
     ))]
+    #[schema(example = "Pay rent")]
     pub title: String,
 
-    #[validate(length(max = 1000, message = "Description must not exceed 1000 characters"))]
+    #[validate(
+        length(max = 1000, message = "Description must not exceed 1000 characters"),
+        custom(function = "validate_description")
+    )]
+    #[schema(example = "Transfer to landlord before noon")]
     pub description: Option<String>,
 
-    pub date_to: DateTime<Utc>,
+    #[schema(value_type = String, example = "2025-01-31T12:00:00Z")]
+    pub date_to: DueDate,
+    #[schema(example = false)]
     pub completed: bool,
+
+    /// New timezone for the memo; keeps the current one when omitted
+    #[serde(default)]
+    #[validate(custom(function = "validate_timezone"))]
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: Option<String>,
+
+    /// Color label from the palette; omitting it removes the label, like
+    /// the description
+    #[serde(default)]
+    #[validate(custom(function = "validate_color"))]
+    #[schema(example = "blue")]
+    pub color: Option<String>,
+
+    /// Version the client last read; a stale value is rejected with 409 Conflict.
+    /// May also be sent as an `If-Match` header.
+    #[serde(default)]
+    #[schema(example = 3)]
+    pub version: Option<i32>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[validate(schema(function = "validate_patch"))]
 pub struct PatchMemoDto {
     #[validate(length(
         min = 1,
         max = 200,
         message = "Title must be between 1 and 200 characters"
     ))]
+    #[schema(example = "Pay rent")]
     pub title: Option<String>,
 
-    #[validate(length(max = 1000, message = "Description must not exceed 1000 characters"))]
+    #[validate(
+        length(max = 1000, message = "Description must not exceed 1000 characters"),
+        custom(function = "validate_description")
+    )]
+    #[schema(example = "Transfer to landlord before noon")]
     pub description: Option<String>,
 
-    pub date_to: Option<DateTime<Utc>>,
+    #[schema(value_type = Option<String>, example = "2025-01-31T12:00")]
+    pub date_to: Option<DueDate>,
+
+    /// Informal due date such as `next friday`; replaces `date_to`
+    #[serde(default)]
+    #[validate(length(max = 100, message = "date_to_text must not exceed 100 characters"))]
+    #[schema(example = "next friday")]
+    pub date_to_text: Option<String>,
+    #[schema(example = true)]
     pub completed: Option<bool>,
+
+    /// New timezone for the memo; changing it keeps the due instant
+    #[validate(custom(function = "validate_timezone"))]
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: Option<String>,
+
+    /// New color label from the palette; an empty string removes the label
+    #[serde(default)]
+    #[validate(custom(function = "validate_patch_color"))]
+    #[schema(example = "blue")]
+    pub color: Option<String>,
+
+    /// Version the client last read; a stale value is rejected with 409 Conflict.
+    /// May also be sent as an `If-Match` header.
+    #[serde(default)]
+    #[schema(example = 3)]
+    pub version: Option<i32>,
+}
+
+impl PatchMemoDto {
+    /// A patch that only sets `completed`.
+    pub fn completed(completed: bool) -> Self {
+        Self {
+            title: None,
+            description: None,
+            date_to: None,
+            date_to_text: None,
+            completed: Some(completed),
+            timezone: None,
+            color: None,
+            version: None,
+        }
+    }
+
+    /// Whether the patch leaves every field alone; `version` only guards the
+    /// update and does not count as a change.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none()
+            && self.description.is_none()
+            && self.date_to.is_none()
+            && self.date_to_text.is_none()
+            && self.completed.is_none()
+            && self.timezone.is_none()
+            && self.color.is_none()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MoveMemoDto {
+    /// Zero-based index in the manual ordering; values past the end move the memo last
+    #[schema(example = 0)]
+    pub position: u32,
+}
+
+/// Selects the memos to complete: either explicit `ids` or a `filter`, not both.
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[validate(schema(function = "validate_batch_target"))]
+pub struct BatchCompleteDto {
+    #[validate(length(min = 1, max = 1000, message = "ids must contain 1 to 1000 entries"))]
+    #[schema(example = json!(["550e8400-e29b-41d4-a716-446655440000"]))]
+    pub ids: Option<Vec<Uuid>>,
+
+    #[validate(nested)]
+    pub filter: Option<BatchFilterDto>,
+}
+
+/// Same semantics as the `GET /api/v1/memos` query parameters.
+#[derive(Debug, Default, Serialize, Deserialize, Validate, ToSchema)]
+pub struct BatchFilterDto {
+    #[schema(example = true)]
+    pub overdue: Option<bool>,
+    #[schema(example = false)]
+    pub archived: Option<bool>,
+    #[validate(length(max = 100, message = "Search query must not exceed 100 characters"))]
+    #[schema(example = "report")]
+    pub q: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BatchCompleteResponse {
+    /// Memos that were incomplete and are now completed
+    #[schema(example = json!(["550e8400-e29b-41d4-a716-446655440000"]))]
+    pub updated: Vec<Uuid>,
+    /// Requested ids that do not exist; always empty for filter requests
+    #[schema(example = json!([]))]
+    pub not_found: Vec<Uuid>,
+    /// Memos left open because a blocker outside the batch is still open
+    #[schema(example = json!([]))]
+    pub blocked: Vec<Uuid>,
+}
+
+fn validate_batch_target(dto: &BatchCompleteDto) -> Result<(), ValidationError> {
+    match (&dto.ids, &dto.filter) {
+        (Some(_), None) | (None, Some(_)) => Ok(()),
+        _ => Err(ValidationError::new("batch_target")
+            .with_message("Provide exactly one of ids or filter".into())),
+    }
+}
+
+/// Body of `POST /api/v1/memos/delete`.
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct BatchDeleteDto {
+    #[validate(length(min = 1, max = 1000, message = "ids must contain 1 to 1000 entries"))]
+    #[schema(example = json!(["550e8400-e29b-41d4-a716-446655440000"]))]
+    pub ids: Vec<Uuid>,
+}
+
+/// Outcome of an import, stored as its operation's `result`. Lines that
+/// failed are listed by `GET /api/v1/memos/import/{operation_id}/failures`.
+#[derive(Debug, Default, Serialize, Deserialize, ToSchema)]
+pub struct MemoImportResult {
+    /// Memos created
+    #[schema(example = 998)]
+    pub imported: usize,
+    /// Lines that did not become memos
+    #[schema(example = 2)]
+    pub failed: usize,
+}
+
+/// A line of an import file that did not become a memo.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MemoImportFailureDto {
+    /// 1-based line number in the uploaded file
+    #[schema(example = 17)]
+    pub line: i32,
+    /// Error code, as in error responses
+    #[schema(example = "VALIDATION_TITLE_LENGTH")]
+    pub code: String,
+    #[schema(
+        example = "Validation error: Validation failed: title: Title must be between 1 and 200 characters"
+    )]
+    pub message: String,
+}
+
+impl From<memo_import_failures::Model> for MemoImportFailureDto {
+    fn from(model: memo_import_failures::Model) -> Self {
+        Self {
+            line: model.line,
+            code: model.code,
+            message: model.message,
+        }
+    }
+}
+
+/// Memos removed by a delete. They can be brought back with
+/// `POST /api/v1/undo/{undo_token}` until `undo_expires_at`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct DeleteMemosResponse {
+    #[schema(example = json!(["550e8400-e29b-41d4-a716-446655440000"]))]
+    pub deleted: Vec<Uuid>,
+    /// Requested ids that do not exist or are already deleted
+    #[schema(example = json!([]))]
+    pub not_found: Vec<Uuid>,
+    /// `null` when nothing was deleted
+    #[schema(example = "0b9f6a52-4a3e-4c55-9d1e-6f3f1c2a7b10")]
+    pub undo_token: Option<Uuid>,
+    #[schema(example = "2025-01-15T10:30:30Z")]
+    pub undo_expires_at: Option<DateTime<Utc>>,
+}
+
+/// Result of `POST /api/v1/undo/{token}`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UndoResponse {
+    /// The memos brought back, in manual order
+    pub restored: Vec<MemoResponseDto>,
+}
+
+/// Optional extras of memo responses.
+#[derive(Debug, Default, Deserialize)]
+pub struct MemoRenderParams {
+    /// Add `description_html`, the description rendered from Markdown
+    #[serde(default)]
+    pub description_html: bool,
+}
+
+impl MemoRenderParams {
+    pub fn apply(&self, memo: &mut MemoResponseDto) {
+        if self.description_html {
+            memo.description_html = memo.description_as_html();
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateMemoParams {
+    /// Import a memo that was due in the past, even when
+    /// `REQUIRE_FUTURE_DUE_DATES` is on
+    #[serde(default)]
+    pub historical: bool,
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct DuplicateMemoParams {
+    /// Days to shift the copy's due date by; negative values move it earlier
+    #[validate(range(
+        min = -3650,
+        max = 3650,
+        message = "days_offset must be between -3650 and 3650"
+    ))]
+    pub days_offset: Option<i64>,
+}
+
+/// Optional body of `PATCH /api/v1/memos/{id}/complete`.
+#[derive(Debug, Default, Serialize, Deserialize, Validate, ToSchema)]
+pub struct CompleteMemoDto {
+    /// How the memo was resolved; only allowed when completing it
+    #[validate(length(max = 1000, message = "Note must not exceed 1000 characters"))]
+    #[schema(example = "Paid by bank transfer")]
+    pub note: Option<String>,
+}
+
+/// Body of `POST /api/v1/memos/{id}/blockers`.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct LinkBlockerDto {
+    /// Memo that has to be completed first
+    #[schema(example = "9b2f4e1c-0c55-4b8e-9d6a-2f1e0d3c4b5a")]
+    pub blocker_id: Uuid,
+}
+
+/// An open memo preventing another from being completed, listed in
+/// `MEMO_BLOCKED` errors.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct BlockerDto {
+    #[schema(example = "9b2f4e1c-0c55-4b8e-9d6a-2f1e0d3c4b5a")]
+    pub id: Uuid,
+    #[schema(example = "Get invoice from landlord")]
+    pub title: String,
+}
+
+/// Named snooze lengths, counted from the memo's due date (or from now if it
+/// is overdue) in the memo's timezone.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum SnoozePreset {
+    /// One hour later
+    #[serde(rename = "1h")]
+    OneHour,
+    /// 09:00 the following day
+    #[serde(rename = "tomorrow")]
+    Tomorrow,
+    /// 09:00 the following Monday
+    #[serde(rename = "next_week")]
+    NextWeek,
+}
+
+impl SnoozePreset {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SnoozePreset::OneHour => "1h",
+            SnoozePreset::Tomorrow => "tomorrow",
+            SnoozePreset::NextWeek => "next_week",
+        }
+    }
+
+    fn phrase(self) -> &'static str {
+        match self {
+            SnoozePreset::OneHour => "in 1 hour",
+            SnoozePreset::Tomorrow => "tomorrow",
+            SnoozePreset::NextWeek => "next monday",
+        }
+    }
+}
+
+/// How far to push a memo's due date: a `preset` or a number of `minutes`,
+/// not both.
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+#[validate(schema(function = "validate_snooze_length"))]
+pub struct SnoozeMemoDto {
+    #[schema(example = "tomorrow")]
+    pub preset: Option<SnoozePreset>,
+
+    #[validate(range(
+        min = 1,
+        max = 525600,
+        message = "minutes must be between 1 and 525600"
+    ))]
+    #[schema(example = 90)]
+    pub minutes: Option<u32>,
+}
+
+impl SnoozeMemoDto {
+    /// The new due date for a memo snoozed starting at `from`.
+    pub fn snoozed_until(&self, from: DateTime<Tz>) -> Result<DueDate, String> {
+        match (self.preset, self.minutes) {
+            (Some(preset), None) => DueDate::parse_natural(preset.phrase(), from),
+            (None, Some(minutes)) => Ok(DueDate::Absolute(
+                (from + Duration::minutes(minutes.into())).fixed_offset(),
+            )),
+            _ => Err("Provide exactly one of preset or minutes".to_string()),
+        }
+    }
+}
+
+fn validate_snooze_length(dto: &SnoozeMemoDto) -> Result<(), ValidationError> {
+    match (&dto.preset, &dto.minutes) {
+        (Some(_), None) | (None, Some(_)) => Ok(()),
+        _ => Err(ValidationError::new("snooze_length")
+            .with_message("Provide exactly one of preset or minutes".into())),
+    }
+}
+
+/// One entry in a memo's snooze history.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct MemoSnoozeDto {
+    #[schema(example = "9b2f4e1c-0c55-4b8e-9d6a-2f1e0d3c4b5a")]
+    pub id: Uuid,
+    /// Due date before the snooze, in the viewer's timezone
+    #[serde(serialize_with = "crate::i18n::timezone::serialize_in_viewer_timezone")]
+    #[schema(example = "2025-01-31T12:00:00Z")]
+    pub snoozed_from: DateTime<Utc>,
+    /// Due date after the snooze, in the viewer's timezone
+    #[serde(serialize_with = "crate::i18n::timezone::serialize_in_viewer_timezone")]
+    #[schema(example = "2025-02-01T09:00:00Z")]
+    pub snoozed_until: DateTime<Utc>,
+    /// Preset used, or null for a number of minutes
+    #[schema(example = "tomorrow")]
+    pub preset: Option<String>,
+    #[schema(example = "2025-01-31T12:05:00Z")]
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
 pub struct MemoResponseDto {
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
     pub id: Uuid,
+    #[schema(example = "Pay rent")]
     pub title: String,
+    /// Markdown source, as written; render it with care or ask for
+    /// `description_html`
+    #[schema(example = "Transfer to **landlord** before noon")]
     pub description: Option<String>,
+    /// The description rendered from Markdown to sanitized HTML, included
+    /// when requested with `description_html=true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(example = "<p>Transfer to <strong>landlord</strong> before noon</p>")]
+    pub description_html: Option<String>,
+    /// In the viewer's timezone (`Time-Zone` header), with its offset
+    #[serde(serialize_with = "crate::i18n::timezone::serialize_in_viewer_timezone")]
+    #[schema(example = "2025-01-31T13:00:00+01:00")]
     pub date_to: DateTime<Utc>,
+    /// IANA timezone of the memo's creator
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: String,
+    #[schema(example = false)]
     pub completed: bool,
+    #[schema(example = "2025-01-20T09:30:00Z")]
     pub created_at: DateTime<Utc>,
+    #[schema(example = "2025-01-21T16:05:00Z")]
     pub updated_at: DateTime<Utc>,
+    #[schema(example = 0)]
+    pub position: i32,
+    /// Incremented on every change; send it back to detect concurrent edits
+    #[schema(example = 3)]
+    pub version: i32,
+    /// Archived memos are hidden from listings unless `archived=true` is requested
+    #[schema(example = false)]
+    pub archived: bool,
+    /// Pinned memos are listed before all others
+    #[schema(example = false)]
+    pub pinned: bool,
+    #[schema(example = true)]
+    pub starred: bool,
+    /// Color label from the palette; null when the memo has none
+    #[schema(example = "blue")]
+    pub color: Option<String>,
+    /// When the memo was completed; null while it is open
+    #[schema(example = "2025-01-31T11:42:00Z")]
+    pub completed_at: Option<DateTime<Utc>>,
+    /// Note recorded when completing the memo
+    #[schema(example = "Paid by bank transfer")]
+    pub completion_note: Option<String>,
+    /// Memos that have to be completed before this one can be
+    #[schema(example = json!(["9b2f4e1c-0c55-4b8e-9d6a-2f1e0d3c4b5a"]))]
+    pub blocked_by: Vec<Uuid>,
+    /// Memos waiting on this one
+    #[schema(example = json!([]))]
+    pub blocks: Vec<Uuid>,
+    /// Attached images, oldest first; left out when there are none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<AttachmentDto>,
+    /// The memo's URL and the actions it currently allows, filled in by the
+    /// API from its routes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub links: Option<MemoLinks>,
+}
+
+impl MemoResponseDto {
+    /// The description rendered from Markdown to sanitized HTML.
+    pub fn description_as_html(&self) -> Option<String> {
+        self.description.as_deref().map(markdown::to_html)
+    }
+}
+
+/// Hypermedia links of a memo. Actions the memo does not allow right now, or
+/// that are not served, are left out.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct MemoLinks {
+    #[serde(rename = "self")]
+    pub self_: Link,
+    /// Marks the memo completed; only while it is open
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub complete: Option<Link>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete: Option<Link>,
+}
+
+/// Where and how to perform an action.
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct Link {
+    #[schema(example = "http://localhost:3737/api/v1/memos/550e8400-e29b-41d4-a716-446655440000")]
+    pub href: String,
+    #[schema(example = "GET")]
+    pub method: String,
 }
 
 impl MemoResponseDto {
+    /// `date_to` in the viewer's timezone, for `datetime-local` inputs.
     pub fn date_to_local_format(&self) -> String {
-        self.date_to.format("%Y-%m-%dT%H:%M").to_string()
+        self.date_to
+            .with_timezone(&crate::i18n::ViewerTimezone::current())
+            .format("%Y-%m-%dT%H:%M")
+            .to_string()
+    }
+
+    /// `date_to` in the viewer's timezone, for display.
+    pub fn date_to_display(&self) -> String {
+        self.date_to
+            .with_timezone(&crate::i18n::ViewerTimezone::current())
+            .format("%Y-%m-%d %H:%M %Z")
+            .to_string()
+    }
+}
+
+/// Columns of the web UI's Kanban board. Open memos are placed by due date
+/// in the viewer's timezone; completed ones are all `Done`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum BoardColumn {
+    /// Open and past due; memos leave it by being moved elsewhere
+    Overdue,
+    /// Open and due before midnight
+    Today,
+    /// Open and due after today
+    Upcoming,
+    Done,
+}
+
+impl BoardColumn {
+    pub const ALL: [BoardColumn; 4] = [
+        BoardColumn::Overdue,
+        BoardColumn::Today,
+        BoardColumn::Upcoming,
+        BoardColumn::Done,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            BoardColumn::Overdue => "overdue",
+            BoardColumn::Today => "today",
+            BoardColumn::Upcoming => "upcoming",
+            BoardColumn::Done => "done",
+        }
+    }
+
+    /// Locale key of the column heading.
+    pub fn label_key(self) -> &'static str {
+        match self {
+            BoardColumn::Overdue => "board-overdue",
+            BoardColumn::Today => "board-today",
+            BoardColumn::Upcoming => "board-upcoming",
+            BoardColumn::Done => "board-done",
+        }
+    }
+
+    /// Whether memos can be dropped into the column.
+    pub fn accepts_drops(self) -> bool {
+        self != BoardColumn::Overdue
     }
 }
 
+/// The Kanban board: every [`BoardColumn`] in order with its memos.
+#[derive(Debug, Clone)]
+pub struct MemoBoard {
+    pub columns: Vec<(BoardColumn, Vec<MemoResponseDto>)>,
+}
+
 #[derive(Debug, Deserialize, Validate)]
 pub struct PaginationParams {
     #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
@@ -78,10 +766,60 @@ pub struct PaginationParams {
 
     pub completed: Option<bool>,
 
+    pub overdue: Option<bool>,
+
+    /// List archived memos instead of the default, unarchived ones
+    pub archived: Option<bool>,
+
+    pub pinned: Option<bool>,
+
+    pub starred: Option<bool>,
+
+    /// Only memos with this color label
+    #[validate(custom(function = "validate_color"))]
+    pub color: Option<String>,
+
     #[validate(length(max = 50, message = "Sort field must not exceed 50 characters"))]
     pub sort_by: Option<String>,
 
     pub order: Option<String>,
+
+    #[validate(length(max = 100, message = "Search query must not exceed 100 characters"))]
+    pub q: Option<String>,
+
+    /// How to compute `total`; `none` skips the `COUNT(*)`
+    pub count: Option<CountMode>,
+}
+
+/// How a listing reports its `total`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum CountMode {
+    /// Count every matching memo
+    #[default]
+    #[serde(alias = "true")]
+    Exact,
+    /// Table-wide row estimate from the planner statistics; ignores filters
+    Estimated,
+    /// No total; use `has_next` to page
+    #[serde(rename = "none", alias = "false")]
+    Skip,
+}
+
+/// Query parameters of the memo export.
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportParams {
+    /// Output format; only `ndjson` for now
+    pub format: Option<ExportFormat>,
+}
+
+/// Formats the memo export can be written in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One memo JSON object per line
+    #[default]
+    Ndjson,
 }
 
 impl PaginationParams {
@@ -102,35 +840,284 @@ impl Default for PaginationParams {
             limit: Some(10),
             offset: Some(0),
             completed: None,
+            overdue: None,
+            archived: None,
+            pinned: None,
+            starred: None,
+            color: None,
             sort_by: Some("created_at".to_string()),
             order: Some("desc".to_string()),
+            q: None,
+            count: None,
         }
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PaginatedResponse<T> {
     pub data: Vec<T>,
-    pub total: u64,
+    /// `None` when the count was skipped
+    pub total: Option<u64>,
+    /// Whether `total` is an estimate rather than an exact count
+    pub total_estimated: bool,
     pub limit: u64,
     pub offset: u64,
+    /// 1-based page number containing `offset`
+    pub page: u64,
+    pub total_pages: Option<u64>,
+    pub has_next: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize, ToSchema)]
 pub struct PaginatedMemoResponse {
     pub data: Vec<MemoResponseDto>,
-    pub total: u64,
+    /// Number of memos matching the filters, across all pages; null with `count=none`
+    #[schema(example = 42)]
+    pub total: Option<u64>,
+    /// Whether `total` is an estimate (`count=estimated`)
+    #[schema(example = false)]
+    pub total_estimated: bool,
+    #[schema(example = 10)]
     pub limit: u64,
+    #[schema(example = 0)]
     pub offset: u64,
+    /// 1-based page number containing `offset`
+    #[schema(example = 1)]
+    pub page: u64,
+    /// Null when `total` is
+    #[schema(example = 5)]
+    pub total_pages: Option<u64>,
+    #[schema(example = true)]
+    pub has_next: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct MemoStatsDto {
+    #[schema(example = 42)]
+    pub total: u64,
+    #[schema(example = 30)]
+    pub active: u64,
+    #[schema(example = 12)]
+    pub completed: u64,
+    /// Incomplete memos past their due date
+    #[schema(example = 4)]
+    pub overdue: u64,
+    /// Memos created per UTC day, oldest first, one entry per day including today
+    pub created_per_day: Vec<DailyCountDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct DailyCountDto {
+    #[schema(example = "2025-01-31")]
+    pub date: NaiveDate,
+    #[schema(example = 3)]
+    pub count: u64,
+}
+
+impl MemoStatsDto {
+    /// Bar height for `count` in the dashboard chart, as a percentage of the
+    /// busiest day.
+    pub fn bar_percent(&self, count: &u64) -> u64 {
+        let max = self
+            .created_per_day
+            .iter()
+            .map(|day| day.count)
+            .max()
+            .unwrap_or(0);
+        (count * 100).checked_div(max).unwrap_or(0)
+    }
 }
 
 impl<T> PaginatedResponse<T> {
+    /// Page with an exact `total`.
     pub fn new(data: Vec<T>, total: u64, limit: u64, offset: u64) -> Self {
+        let has_next = offset.saturating_add(limit.max(1)) < total;
+        Self::with_total(data, Some(total), false, has_next, limit, offset)
+    }
+
+    /// Page whose `total` may be estimated or missing, with `has_next`
+    /// worked out by the caller.
+    pub fn with_total(
+        data: Vec<T>,
+        total: Option<u64>,
+        total_estimated: bool,
+        has_next: bool,
+        limit: u64,
+        offset: u64,
+    ) -> Self {
+        let page_size = limit.max(1);
         Self {
             data,
             total,
+            total_estimated,
             limit,
             offset,
+            page: offset / page_size + 1,
+            total_pages: total.map(|total| total.div_ceil(page_size)),
+            has_next,
         }
     }
+
+    /// Page URLs by relation: `first`, `prev`, `next` and `last`, built by
+    /// rewriting `limit`/`offset` in `url` and keeping its other query
+    /// parameters. `prev`/`next` are omitted at the ends, and `last` unless
+    /// the total is exact.
+    pub fn page_links(&self, url: &Url) -> Vec<(&'static str, Url)> {
+        let page_size = self.limit.max(1);
+        let last_offset = match self.total_pages {
+            Some(pages) if !self.total_estimated => Some(pages.saturating_sub(1) * page_size),
+            _ => None,
+        };
+
+        let mut links = vec![(0, "first")];
+        if self.offset > 0 {
+            let prev = self.offset.saturating_sub(page_size);
+            links.push((last_offset.map_or(prev, |last| prev.min(last)), "prev"));
+        }
+        if self.has_next {
+            links.push((self.offset + page_size, "next"));
+        }
+        if let Some(last_offset) = last_offset {
+            links.push((last_offset, "last"));
+        }
+
+        links
+            .into_iter()
+            .map(|(offset, rel)| (rel, page_url(url, page_size, offset)))
+            .collect()
+    }
+
+    /// RFC 8288 `Link` header value with the [`page_links`](Self::page_links).
+    pub fn link_header(&self, url: &Url) -> String {
+        self.page_links(url)
+            .into_iter()
+            .map(|(rel, url)| format!("<{}>; rel=\"{}\"", url, rel))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+fn page_url(url: &Url, limit: u64, offset: u64) -> Url {
+    let mut page = url.clone();
+    let kept: Vec<(String, String)> = url
+        .query_pairs()
+        .filter(|(key, _)| key != "limit" && key != "offset")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    page.query_pairs_mut()
+        .clear()
+        .extend_pairs(kept)
+        .append_pair("limit", &limit.to_string())
+        .append_pair("offset", &offset.to_string());
+    page
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(total: u64, limit: u64, offset: u64) -> PaginatedResponse<()> {
+        PaginatedResponse::new(vec![], total, limit, offset)
+    }
+
+    #[test]
+    fn test_page_metadata() {
+        let middle = page(45, 10, 20);
+        assert_eq!(middle.page, 3);
+        assert_eq!(middle.total_pages, Some(5));
+        assert!(middle.has_next);
+
+        let last = page(45, 10, 40);
+        assert_eq!(last.page, 5);
+        assert!(!last.has_next);
+
+        let empty = page(0, 10, 0);
+        assert_eq!(empty.page, 1);
+        assert_eq!(empty.total_pages, Some(0));
+        assert!(!empty.has_next);
+    }
+
+    #[test]
+    fn test_link_header_keeps_filters_and_omits_missing_relations() {
+        let url =
+            Url::parse("http://localhost/api/v1/memos?completed=true&limit=10&offset=20").unwrap();
+
+        assert_eq!(
+            page(45, 10, 20).link_header(&url),
+            "<http://localhost/api/v1/memos?completed=true&limit=10&offset=0>; rel=\"first\", \
+             <http://localhost/api/v1/memos?completed=true&limit=10&offset=10>; rel=\"prev\", \
+             <http://localhost/api/v1/memos?completed=true&limit=10&offset=30>; rel=\"next\", \
+             <http://localhost/api/v1/memos?completed=true&limit=10&offset=40>; rel=\"last\""
+        );
+
+        let first = page(5, 10, 0).link_header(&url);
+        assert!(!first.contains("rel=\"prev\""));
+        assert!(!first.contains("rel=\"next\""));
+    }
+
+    #[test]
+    fn test_uncounted_pages_have_no_last_link() {
+        let url = Url::parse("http://localhost/api/v1/memos?count=none").unwrap();
+        let page = PaginatedResponse::<()>::with_total(vec![], None, false, true, 10, 10);
+        assert_eq!(page.total_pages, None);
+        assert_eq!(
+            page.link_header(&url),
+            "<http://localhost/api/v1/memos?count=none&limit=10&offset=0>; rel=\"first\", \
+             <http://localhost/api/v1/memos?count=none&limit=10&offset=0>; rel=\"prev\", \
+             <http://localhost/api/v1/memos?count=none&limit=10&offset=20>; rel=\"next\""
+        );
+
+        let estimated = PaginatedResponse::<()>::with_total(vec![], Some(500), true, true, 10, 0);
+        assert!(!estimated.link_header(&url).contains("rel=\"last\""));
+    }
+
+    #[test]
+    fn test_local_due_dates_resolve_in_the_memo_timezone() {
+        let local: DueDate = "2025-01-31T12:00".parse().unwrap();
+        assert_eq!(
+            local.resolve(Tz::Europe__Berlin).unwrap().to_rfc3339(),
+            "2025-01-31T11:00:00+00:00"
+        );
+
+        let absolute: DueDate = "2025-01-31T12:00:00-05:00".parse().unwrap();
+        assert_eq!(
+            absolute.resolve(Tz::Europe__Berlin).unwrap().to_rfc3339(),
+            "2025-01-31T17:00:00+00:00"
+        );
+
+        assert!("31/01/2025".parse::<DueDate>().is_err());
+    }
+
+    #[test]
+    fn test_local_due_dates_in_daylight_saving_transitions_are_rejected() {
+        let skipped: DueDate = "2025-03-30T02:30".parse().unwrap();
+        let repeated: DueDate = "2025-10-26T02:30".parse().unwrap();
+
+        assert!(
+            skipped
+                .resolve(Tz::Europe__Berlin)
+                .unwrap_err()
+                .contains("does not exist")
+        );
+        assert!(
+            repeated
+                .resolve(Tz::Europe__Berlin)
+                .unwrap_err()
+                .contains("occurs twice")
+        );
+    }
+
+    #[test]
+    fn test_count_mode_accepts_boolean_aliases() {
+        let parse = |query: &str| {
+            actix_web::web::Query::<PaginationParams>::from_query(query)
+                .unwrap()
+                .count
+        };
+        assert_eq!(parse("count=false"), Some(CountMode::Skip));
+        assert_eq!(parse("count=none"), Some(CountMode::Skip));
+        assert_eq!(parse("count=true"), Some(CountMode::Exact));
+        assert_eq!(parse("count=estimated"), Some(CountMode::Estimated));
+    }
 }
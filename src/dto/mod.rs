@@ -1,6 +1,27 @@
+pub mod attachment_dto;
+pub mod integration_dto;
 pub mod memo_dto;
+pub mod notification_dto;
+pub mod operation_dto;
+pub mod push_dto;
+pub mod template_dto;
+pub mod usage_dto;
 
+mod natural_date;
+
+pub use attachment_dto::{AttachmentDto, AttachmentUrlParams, SignedAttachmentUrlDto};
+pub use integration_dto::{CreateWebhookIntegrationDto, WebhookIntegrationDto};
 pub use memo_dto::{
-    CreateMemoDto, MemoResponseDto, PaginatedMemoResponse, PaginatedResponse, PaginationParams,
-    PatchMemoDto, UpdateMemoDto,
+    BatchCompleteDto, BatchCompleteResponse, BatchDeleteDto, BatchFilterDto, BlockerDto,
+    BoardColumn, CompleteMemoDto, CountMode, CreateMemoDto, CreateMemoParams, DailyCountDto,
+    DeleteMemosResponse, DueDate, DuplicateMemoParams, ExportFormat, ExportParams, Link,
+    LinkBlockerDto, MEMO_COLORS, MemoBoard, MemoImportFailureDto, MemoImportResult, MemoLinks,
+    MemoRenderParams, MemoResponseDto, MemoSnoozeDto, MemoStatsDto, MoveMemoDto,
+    PaginatedMemoResponse, PaginatedResponse, PaginationParams, PatchMemoDto, SnoozeMemoDto,
+    SnoozePreset, UndoResponse, UpdateMemoDto,
 };
+pub use notification_dto::{NotificationDto, NotificationListParams, NotificationListResponse};
+pub use operation_dto::{OperationDto, OperationErrorDto, OperationStatus};
+pub use push_dto::{PushKeysDto, PushSubscriptionDto, PushUnsubscribeDto, VapidPublicKeyResponse};
+pub use template_dto::{InstantiateTemplateDto, MemoTemplateDto, SaveMemoTemplateDto};
+pub use usage_dto::{ApiKeyUsageDto, DailyUsageDto};
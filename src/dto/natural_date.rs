@@ -0,0 +1,326 @@
+//! Informal due dates such as `tomorrow 5pm`, `next friday` or `in 3 days`.
+//!
+//! Phrases name a day, a time of day, or an offset from now, in any order.
+//! A day without a time is due at [`DEFAULT_TIME`]; a time without a day is
+//! its next occurrence. Anything that could reasonably mean two different
+//! moments is rejected rather than guessed.
+
+use super::memo_dto::DueDate;
+use chrono::{DateTime, Datelike, Days, Duration, Month, Months, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+
+/// Time of day for phrases that name only a day.
+pub const DEFAULT_TIME: NaiveTime = NaiveTime::from_hms_opt(9, 0, 0).unwrap();
+
+const NOON: NaiveTime = NaiveTime::from_hms_opt(12, 0, 0).unwrap();
+
+const EXAMPLES: &str = "try 'tomorrow 5pm', 'next friday', 'march 14 9:30am' or 'in 3 days'";
+
+enum Phrase {
+    Day(NaiveDate),
+    Time(NaiveTime),
+    After(Duration),
+    Filler,
+}
+
+/// Parses `text` relative to `now`; day and time phrases become a
+/// [`DueDate::Local`] in `now`'s timezone, `in N hours`/`minutes` an
+/// absolute instant.
+pub fn parse(text: &str, now: DateTime<Tz>) -> Result<DueDate, String> {
+    parse_phrases(text, now)
+        .map_err(|reason| format!("Could not read date_to_text '{}': {}", text, reason))
+}
+
+fn parse_phrases(text: &str, now: DateTime<Tz>) -> Result<DueDate, String> {
+    let lowered = text.to_lowercase();
+    let tokens: Vec<&str> = lowered
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .collect();
+
+    let today = now.date_naive();
+    let (mut day, mut time, mut after) = (None, None, None);
+    let mut rest = &tokens[..];
+    while !rest.is_empty() {
+        let (phrase, consumed) = next_phrase(rest, today)?;
+        match phrase {
+            Phrase::Day(_) if day.is_some() => return Err("it names more than one day".into()),
+            Phrase::Time(_) if time.is_some() => return Err("it names more than one time".into()),
+            Phrase::After(_) if after.is_some() => {
+                return Err("it names more than one offset".into());
+            }
+            Phrase::Day(date) => day = Some(date),
+            Phrase::Time(at) => time = Some(at),
+            Phrase::After(offset) => after = Some(offset),
+            Phrase::Filler => {}
+        }
+        rest = &rest[consumed..];
+    }
+
+    match (day, time, after) {
+        (None, None, Some(offset)) => now
+            .checked_add_signed(offset)
+            .map(|due| DueDate::Absolute(due.fixed_offset()))
+            .ok_or_else(|| "it is too far in the future".into()),
+        (_, _, Some(_)) => Err("'in N hours/minutes' cannot be combined with a day or time".into()),
+        (Some(date), time, None) => Ok(DueDate::Local(date.and_time(time.unwrap_or(DEFAULT_TIME)))),
+        (None, Some(at), None) => {
+            let date = if at > now.time() {
+                today
+            } else {
+                today + Days::new(1)
+            };
+            Ok(DueDate::Local(date.and_time(at)))
+        }
+        (None, None, None) => Err(format!("it names no day or time; {}", EXAMPLES)),
+    }
+}
+
+fn next_phrase(tokens: &[&str], today: NaiveDate) -> Result<(Phrase, usize), String> {
+    let token = tokens[0];
+    let next = tokens.get(1).copied();
+
+    match token {
+        "at" | "on" | "by" => return Ok((Phrase::Filler, 1)),
+        "today" => return Ok((Phrase::Day(today), 1)),
+        "tomorrow" => return Ok((Phrase::Day(today + Days::new(1)), 1)),
+        "noon" | "midday" => return Ok((Phrase::Time(NOON), 1)),
+        "midnight" => {
+            return Err(
+                "'midnight' could mean the start or the end of the day; use 00:00 or 23:59".into(),
+            );
+        }
+        "this" | "next" => {
+            let weekday = next
+                .and_then(|name| name.parse::<Weekday>().ok())
+                .ok_or_else(|| format!("expected a weekday after '{}'", token))?;
+            // "this friday" may be today; "next friday" is always after it
+            let skip_today = token == "next";
+            return Ok((Phrase::Day(upcoming(today, weekday, skip_today)), 2));
+        }
+        "in" => return relative(tokens, today),
+        _ => {}
+    }
+
+    if let Ok(weekday) = token.parse::<Weekday>() {
+        if weekday == today.weekday() {
+            return Err(format!(
+                "'{}' is today; say 'today' or 'next {}'",
+                token, token
+            ));
+        }
+        return Ok((Phrase::Day(upcoming(today, weekday, true)), 1));
+    }
+
+    if let Ok(month) = token.parse::<Month>() {
+        return month_day(month, &tokens[1..], today);
+    }
+
+    if let Ok(date) = NaiveDate::parse_from_str(token, "%Y-%m-%d") {
+        return Ok((Phrase::Day(date), 1));
+    }
+
+    if token.contains('/') || (token.contains('.') && token.chars().any(|c| c.is_ascii_digit())) {
+        return Err(format!(
+            "'{}' could be read day-first or month-first; write it as 2025-03-04 or 'march 4'",
+            token
+        ));
+    }
+
+    if let Some(meridiem) = next.filter(|next| *next == "am" || *next == "pm")
+        && let Some(at) = clock_time(token, Some(meridiem))
+    {
+        return Ok((Phrase::Time(at), 2));
+    }
+    if let Some(at) = clock_time(token, None) {
+        return Ok((Phrase::Time(at), 1));
+    }
+    if token.parse::<u32>().is_ok() {
+        return Err(format!(
+            "'{}' could be morning or evening; write {}am, {}pm or a 24-hour time like 17:00",
+            token, token, token
+        ));
+    }
+
+    Err(format!("'{}' is not a day or time; {}", token, EXAMPLES))
+}
+
+/// The next `weekday` from `today`, which counts unless `skip_today`.
+fn upcoming(today: NaiveDate, weekday: Weekday, skip_today: bool) -> NaiveDate {
+    let mut ahead =
+        (7 + weekday.num_days_from_monday() - today.weekday().num_days_from_monday()) % 7;
+    if ahead == 0 && skip_today {
+        ahead = 7;
+    }
+    today + Days::new(ahead.into())
+}
+
+/// `in <n> <unit>`, where `n` may also be `a`/`an`.
+fn relative(tokens: &[&str], today: NaiveDate) -> Result<(Phrase, usize), String> {
+    let (Some(count), Some(unit)) = (tokens.get(1), tokens.get(2)) else {
+        return Err("expected 'in <number> <minutes|hours|days|weeks|months>'".into());
+    };
+    let count: u32 = match *count {
+        "a" | "an" => 1,
+        count => count
+            .parse()
+            .map_err(|_| format!("'{}' is not a whole number", count))?,
+    };
+
+    let too_far = || "it is too far in the future".to_string();
+    let phrase = match unit.trim_end_matches('s') {
+        "minute" | "min" => Phrase::After(Duration::minutes(count.into())),
+        "hour" | "hr" => Phrase::After(Duration::hours(count.into())),
+        "day" => Phrase::Day(
+            today
+                .checked_add_days(Days::new(count.into()))
+                .ok_or_else(too_far)?,
+        ),
+        "week" => Phrase::Day(
+            today
+                .checked_add_days(Days::new(u64::from(count) * 7))
+                .ok_or_else(too_far)?,
+        ),
+        "month" => Phrase::Day(
+            today
+                .checked_add_months(Months::new(count))
+                .ok_or_else(too_far)?,
+        ),
+        _ => return Err(format!("'{}' is not a unit of time", unit)),
+    };
+    Ok((phrase, 3))
+}
+
+/// `<month> <day>[st|nd|rd|th] [year]`; without a year, the next such day
+/// from `today`.
+fn month_day(month: Month, tokens: &[&str], today: NaiveDate) -> Result<(Phrase, usize), String> {
+    let day: u32 = tokens
+        .first()
+        .map(|day| day.trim_end_matches(|c: char| c.is_ascii_alphabetic()))
+        .and_then(|day| day.parse().ok())
+        .ok_or_else(|| format!("expected a day of the month after '{}'", month.name()))?;
+    let invalid = || format!("{} {} is not a valid date", month.name(), day);
+
+    let year = tokens
+        .get(1)
+        .filter(|year| year.len() == 4)
+        .and_then(|year| year.parse::<i32>().ok());
+    if let Some(year) = year {
+        let date =
+            NaiveDate::from_ymd_opt(year, month.number_from_month(), day).ok_or_else(invalid)?;
+        return Ok((Phrase::Day(date), 3));
+    }
+
+    let this_year = NaiveDate::from_ymd_opt(today.year(), month.number_from_month(), day);
+    let date = match this_year {
+        Some(date) if date >= today => date,
+        _ => NaiveDate::from_ymd_opt(today.year() + 1, month.number_from_month(), day)
+            .ok_or_else(invalid)?,
+    };
+    Ok((Phrase::Day(date), 2))
+}
+
+/// `5pm`, `5:30pm`, `12am`, or with a separate `meridiem`; `17:00` and
+/// `5:30` are read on a 24-hour clock.
+fn clock_time(token: &str, meridiem: Option<&str>) -> Option<NaiveTime> {
+    let (clock, meridiem) = match meridiem {
+        Some(meridiem) => (token, Some(meridiem)),
+        None => match token
+            .strip_suffix("am")
+            .or_else(|| token.strip_suffix("pm"))
+        {
+            Some(clock) => (clock, Some(&token[clock.len()..])),
+            None if token.contains(':') => (token, None),
+            None => return None,
+        },
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour.parse().ok()?, minute.parse().ok()?),
+        Some(_) => return None,
+        None => (clock.parse::<u32>().ok()?, 0),
+    };
+    let hour = match meridiem {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some("am") => hour % 12,
+        Some(_) => hour % 12 + 12,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDateTime, TimeZone};
+
+    // A Wednesday afternoon in Berlin
+    fn now() -> DateTime<Tz> {
+        Tz::Europe__Berlin
+            .with_ymd_and_hms(2025, 7, 2, 15, 30, 0)
+            .unwrap()
+    }
+
+    fn local(text: &str) -> NaiveDateTime {
+        match parse(text, now()) {
+            Ok(DueDate::Local(local)) => local,
+            other => panic!("{:?} parsed as {:?}", text, other),
+        }
+    }
+
+    fn at(date: &str, time: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(&format!("{}T{}", date, time), "%Y-%m-%dT%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_days_and_times_combine_in_either_order() {
+        assert_eq!(local("tomorrow 5pm"), at("2025-07-03", "17:00"));
+        assert_eq!(local("5:30 pm tomorrow"), at("2025-07-03", "17:30"));
+        assert_eq!(local("Today at noon"), at("2025-07-02", "12:00"));
+        assert_eq!(local("next friday"), at("2025-07-04", "09:00"));
+        assert_eq!(local("next wednesday 17:45"), at("2025-07-09", "17:45"));
+        assert_eq!(local("this wednesday 12am"), at("2025-07-02", "00:00"));
+        assert_eq!(local("on Monday"), at("2025-07-07", "09:00"));
+        assert_eq!(local("march 14th, 8am"), at("2026-03-14", "08:00"));
+        assert_eq!(local("jul 2 2027"), at("2027-07-02", "09:00"));
+        assert_eq!(local("2025-12-24 18:00"), at("2025-12-24", "18:00"));
+        assert_eq!(local("in 3 days"), at("2025-07-05", "09:00"));
+        assert_eq!(local("in a month 10am"), at("2025-08-02", "10:00"));
+    }
+
+    #[test]
+    fn test_a_bare_time_is_its_next_occurrence() {
+        assert_eq!(local("4pm"), at("2025-07-02", "16:00"));
+        assert_eq!(local("9am"), at("2025-07-03", "09:00"));
+    }
+
+    #[test]
+    fn test_hour_offsets_are_absolute() {
+        let due = parse("in 2 hours", now()).unwrap();
+        assert_eq!(
+            due.resolve(Tz::UTC).unwrap(),
+            "2025-07-02T15:30:00Z"
+                .parse::<DateTime<chrono::Utc>>()
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_and_unknown_phrases_are_rejected() {
+        for (text, reason) in [
+            ("5", "could be morning or evening"),
+            ("3/4", "day-first or month-first"),
+            ("wednesday", "is today"),
+            ("friday midnight", "start or the end of the day"),
+            ("tomorrow friday", "more than one day"),
+            ("5pm 6pm", "more than one time"),
+            ("in 2 hours tomorrow", "cannot be combined"),
+            ("february 30", "not a valid date"),
+            ("someday", "not a day or time"),
+            ("", "names no day or time"),
+        ] {
+            let err = parse(text, now()).unwrap_err();
+            assert!(err.contains(reason), "{:?}: {}", text, err);
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::entities::notifications;
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct NotificationDto {
+    #[schema(example = "0b6e1c9a-4f7d-4c1e-9a53-2f1d7c8e5b40")]
+    pub id: Uuid,
+    /// What happened; currently `memo_due_soon`
+    #[schema(example = "memo_due_soon")]
+    pub kind: String,
+    #[schema(example = "Pay rent is due soon")]
+    pub title: String,
+    #[schema(example = "Due 2025-01-31 12:00 UTC")]
+    pub body: Option<String>,
+    /// Memo the notification is about; deleting the memo removes it
+    #[schema(example = "550e8400-e29b-41d4-a716-446655440000")]
+    pub memo_id: Option<Uuid>,
+    /// Null while unread
+    #[schema(example = "2025-01-31T11:05:00Z")]
+    pub read_at: Option<DateTime<Utc>>,
+    #[schema(example = "2025-01-31T11:00:00Z")]
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<notifications::Model> for NotificationDto {
+    fn from(model: notifications::Model) -> Self {
+        Self {
+            id: model.id,
+            kind: model.kind,
+            title: model.title,
+            body: model.body,
+            memo_id: model.memo_id,
+            read_at: model.read_at.map(Into::into),
+            created_at: model.created_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct NotificationListParams {
+    #[validate(range(min = 1, max = 100, message = "Limit must be between 1 and 100"))]
+    pub limit: Option<u64>,
+
+    pub offset: Option<u64>,
+
+    /// Only notifications that have not been read
+    pub unread: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationListResponse {
+    pub data: Vec<NotificationDto>,
+    /// Notifications matching the filter, across all pages
+    #[schema(example = 12)]
+    pub total: u64,
+    /// Unread notifications overall, for the inbox badge
+    #[schema(example = 3)]
+    pub unread: u64,
+    #[schema(example = 20)]
+    pub limit: u64,
+    #[schema(example = 0)]
+    pub offset: u64,
+    #[schema(example = false)]
+    pub has_next: bool,
+}
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::entities::operations;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OperationStatus {
+    /// Waiting for a worker
+    Pending,
+    Running,
+    /// Finished; `result` holds what the synchronous request would have returned
+    Succeeded,
+    /// Finished; `error` says why
+    Failed,
+}
+
+impl OperationStatus {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "pending" => Some(OperationStatus::Pending),
+            "running" => Some(OperationStatus::Running),
+            "succeeded" => Some(OperationStatus::Succeeded),
+            "failed" => Some(OperationStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Why an operation failed, as the synchronous request would have reported it.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OperationErrorDto {
+    #[schema(example = "VALIDATION_FAILED")]
+    pub code: String,
+    #[schema(example = "Validation error: Provide exactly one of ids or filter")]
+    pub message: String,
+    #[schema(example = 400)]
+    pub status: u16,
+}
+
+/// A long-running request accepted with `202 Accepted`. Poll
+/// `GET /api/v1/operations/{id}` until `status` is `succeeded` or `failed`.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OperationDto {
+    #[schema(example = "7c1e8f0a-2b4d-4e6f-8a9b-0c1d2e3f4a5b")]
+    pub id: Uuid,
+    /// What was requested: `complete_memos`, `delete_memos` or `import_memos`
+    #[schema(example = "complete_memos")]
+    pub kind: String,
+    pub status: OperationStatus,
+    /// Items handled so far
+    #[schema(example = 250)]
+    pub processed: i64,
+    /// Items to handle, when known up front
+    #[schema(example = 1000)]
+    pub total: Option<i64>,
+    /// `processed` as a share of `total`, 0 to 100; `null` while the total is unknown
+    #[schema(example = 25)]
+    pub percent: Option<u8>,
+    /// What a running operation is working on
+    #[schema(example = "Pay rent")]
+    pub current_item: Option<String>,
+    /// Response body of the synchronous request, once succeeded
+    #[schema(value_type = Option<Object>)]
+    pub result: Option<serde_json::Value>,
+    pub error: Option<OperationErrorDto>,
+    #[schema(example = "2025-01-15T10:30:00Z")]
+    pub created_at: DateTime<Utc>,
+    #[schema(example = "2025-01-15T10:30:01Z")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[schema(example = "2025-01-15T10:30:04Z")]
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl OperationDto {
+    /// Whether the operation has finished and will not change any more.
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self.status,
+            OperationStatus::Succeeded | OperationStatus::Failed
+        )
+    }
+}
+
+impl From<operations::Model> for OperationDto {
+    fn from(model: operations::Model) -> Self {
+        let percent = match model.total {
+            Some(0) => Some(100),
+            Some(total) if total > 0 => Some((model.processed.clamp(0, total) * 100 / total) as u8),
+            _ => None,
+        };
+
+        Self {
+            id: model.id,
+            kind: model.kind,
+            // The column only ever holds the four statuses.
+            status: OperationStatus::parse(&model.status).unwrap_or(OperationStatus::Failed),
+            processed: model.processed,
+            total: model.total,
+            percent,
+            current_item: model.current_item,
+            result: model.result,
+            error: model
+                .error
+                .and_then(|error| serde_json::from_value(error).ok()),
+            created_at: model.created_at.into(),
+            started_at: model.started_at.map(Into::into),
+            finished_at: model.finished_at.map(Into::into),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+/// A browser `PushSubscription`, as returned by `subscription.toJSON()`.
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct PushSubscriptionDto {
+    /// Push service URL
+    #[validate(
+        length(max = 2048, message = "Endpoint must not exceed 2048 characters"),
+        custom(function = "validate_https_url")
+    )]
+    #[schema(example = "https://fcm.googleapis.com/fcm/send/abc123")]
+    pub endpoint: String,
+
+    #[validate(nested)]
+    pub keys: PushKeysDto,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct PushKeysDto {
+    /// Client public key, URL-safe base64
+    #[validate(length(
+        min = 1,
+        max = 256,
+        message = "p256dh must be between 1 and 256 characters"
+    ))]
+    #[schema(
+        example = "BNcRdreALRFXTkOOUHK1EtK2wtaz5Ry4YfYCA_0QTpQtUbVlUls0VJXg7A8u-Ts1XbjhazAkj7I99e8QcYP7DkM"
+    )]
+    pub p256dh: String,
+    /// Authentication secret, URL-safe base64
+    #[validate(length(
+        min = 1,
+        max = 64,
+        message = "auth must be between 1 and 64 characters"
+    ))]
+    #[schema(example = "tBHItJI5svbpez7KI4CCXg")]
+    pub auth: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct PushUnsubscribeDto {
+    #[validate(length(
+        min = 1,
+        max = 2048,
+        message = "Endpoint must be between 1 and 2048 characters"
+    ))]
+    #[schema(example = "https://fcm.googleapis.com/fcm/send/abc123")]
+    pub endpoint: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VapidPublicKeyResponse {
+    /// Application server key for `pushManager.subscribe`, URL-safe base64
+    #[schema(
+        example = "BEl62iUYgUivxIkv69yViEuiBIa-Ib9-SkvMeAtA3LFgDzkrxZJjSgSnfckjBJuBkr3qBUYIHBQFLXYp5Nksh8U"
+    )]
+    pub public_key: String,
+}
+
+/// Push services are always HTTPS; anything else could make the server call
+/// arbitrary internal hosts.
+fn validate_https_url(endpoint: &str) -> Result<(), ValidationError> {
+    match url::Url::parse(endpoint) {
+        Ok(url) if url.scheme() == "https" && url.host_str().is_some() => Ok(()),
+        _ => Err(ValidationError::new("url").with_message("Endpoint must be an https URL".into())),
+    }
+}
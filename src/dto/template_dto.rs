@@ -0,0 +1,123 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+use super::DueDate;
+use crate::entities::memo_templates;
+use crate::utils::placeholders;
+
+/// Body of `POST /api/v1/memo-templates` and `PUT /api/v1/memo-templates/{id}`.
+///
+/// `title` and `description` may contain placeholders such as `{{date}}`,
+/// filled in when a memo is created from the template.
+#[derive(Debug, Serialize, Deserialize, Validate, ToSchema)]
+pub struct SaveMemoTemplateDto {
+    /// Unique name shown when picking a template
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    #[schema(example = "Monthly invoice")]
+    pub name: String,
+
+    #[validate(length(
+        min = 1,
+        max = 200,
+        message = "Title must be between 1 and 200 characters"
+    ))]
+    #[schema(example = "Send invoice to {{client}} for {{month}}")]
+    pub title: String,
+
+    #[validate(length(max = 1000, message = "Description must not exceed 1000 characters"))]
+    #[schema(example = "Prepared on {{date}}")]
+    pub description: Option<String>,
+
+    /// Informal due date of created memos, such as `tomorrow 5pm`
+    #[validate(length(max = 100, message = "due_text must not exceed 100 characters"))]
+    #[schema(example = "in 3 days")]
+    pub due_text: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, ToSchema)]
+pub struct MemoTemplateDto {
+    #[schema(example = "5d1f0e9a-2b7c-4c3e-9f4a-8e6b1d2c3a40")]
+    pub id: Uuid,
+    #[schema(example = "Monthly invoice")]
+    pub name: String,
+    #[schema(example = "Send invoice to {{client}} for {{month}}")]
+    pub title: String,
+    #[schema(example = "Prepared on {{date}}")]
+    pub description: Option<String>,
+    #[schema(example = "in 3 days")]
+    pub due_text: Option<String>,
+    #[schema(example = "2025-01-15T10:30:00Z")]
+    pub created_at: DateTime<Utc>,
+    #[schema(example = "2025-01-15T10:30:00Z")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<memo_templates::Model> for MemoTemplateDto {
+    fn from(model: memo_templates::Model) -> Self {
+        Self {
+            id: model.id,
+            name: model.name,
+            title: model.title,
+            description: model.description,
+            due_text: model.due_text,
+            created_at: model.created_at.into(),
+            updated_at: model.updated_at.into(),
+        }
+    }
+}
+
+/// Optional body of `POST /api/v1/memos/from-template/{id}`.
+///
+/// The due date defaults to the template's `due_text`; `date_to`,
+/// `date_to_text` and `timezone` behave as when creating a memo.
+#[derive(Debug, Default, Serialize, Deserialize, Validate, ToSchema)]
+pub struct InstantiateTemplateDto {
+    /// Values for the template's own placeholders, e.g. `{{client}}`.
+    /// The built-ins `date`, `time`, `weekday`, `month` and `year` are
+    /// filled in automatically; a variable of the same name replaces them.
+    #[serde(default)]
+    #[validate(custom(function = "validate_variables"))]
+    #[schema(example = json!({ "client": "ACME" }))]
+    pub variables: BTreeMap<String, String>,
+
+    #[serde(default)]
+    #[schema(value_type = Option<String>, example = "2025-01-31T12:00:00Z")]
+    pub date_to: Option<DueDate>,
+
+    #[serde(default)]
+    #[schema(example = "friday 5pm")]
+    pub date_to_text: Option<String>,
+
+    #[serde(default)]
+    #[schema(example = "Europe/Berlin")]
+    pub timezone: Option<String>,
+}
+
+fn validate_variables(variables: &BTreeMap<String, String>) -> Result<(), ValidationError> {
+    if variables.len() > 20 {
+        return Err(ValidationError::new("variables")
+            .with_message("At most 20 variables can be given".into()));
+    }
+    if let Some(name) = variables.keys().find(|name| !placeholders::is_name(name)) {
+        return Err(ValidationError::new("variables").with_message(
+            format!(
+                "Variable name '{}' may only contain letters, digits and underscores",
+                name
+            )
+            .into(),
+        ));
+    }
+    if variables.values().any(|value| value.chars().count() > 200) {
+        return Err(ValidationError::new("variables")
+            .with_message("Variable values must not exceed 200 characters".into()));
+    }
+    Ok(())
+}
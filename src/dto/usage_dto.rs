@@ -0,0 +1,58 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::auth::api_keys::MonthUsage;
+
+/// Requests made with the caller's API key this month.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct ApiKeyUsageDto {
+    #[schema(example = "acme-sync")]
+    pub api_key_id: String,
+    /// First day of the quota period, a calendar month in UTC
+    #[schema(example = "2025-01-01")]
+    pub period_start: NaiveDate,
+    /// When the quota starts over
+    #[schema(example = "2025-02-01T00:00:00Z")]
+    pub resets_at: DateTime<Utc>,
+    /// Requests counted this month
+    #[schema(example = 1234)]
+    pub requests: u64,
+    /// Requests allowed per month; absent for unlimited keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 10000)]
+    pub monthly_quota: Option<u64>,
+    /// Requests left this month; absent for unlimited keys
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 8766)]
+    pub remaining: Option<u64>,
+    /// Days of this month with requests, oldest first
+    pub days: Vec<DailyUsageDto>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, ToSchema)]
+pub struct DailyUsageDto {
+    #[schema(example = "2025-01-14")]
+    pub day: NaiveDate,
+    #[schema(example = 87)]
+    pub requests: u64,
+}
+
+impl ApiKeyUsageDto {
+    pub fn new(api_key_id: String, monthly_quota: Option<u64>, usage: MonthUsage) -> Self {
+        let requests = usage.requests();
+        Self {
+            api_key_id,
+            period_start: usage.period_start,
+            resets_at: usage.resets_at,
+            requests,
+            monthly_quota,
+            remaining: monthly_quota.map(|quota| quota.saturating_sub(requests)),
+            days: usage
+                .days
+                .into_iter()
+                .map(|(day, requests)| DailyUsageDto { day, requests })
+                .collect(),
+        }
+    }
+}
@@ -15,9 +15,65 @@ pub struct Model {
     pub completed: bool,
     pub created_at: DateTimeWithTimeZone,
     pub updated_at: DateTimeWithTimeZone,
+    pub position: i32,
+    pub version: i32,
+    pub archived: bool,
+    /// IANA name of the creator's timezone; local due dates are read in it
+    pub timezone: String,
+    /// When the memo was last marked completed; cleared when it is reopened
+    pub completed_at: Option<DateTimeWithTimeZone>,
+    /// How the memo was resolved, recorded when completing it
+    #[sea_orm(column_type = "Text", nullable)]
+    pub completion_note: Option<String>,
+    /// Pinned memos are listed before all others
+    pub pinned: bool,
+    pub starred: bool,
+    /// Label from the palette in `dto::MEMO_COLORS`, if any
+    pub color: Option<String>,
+    /// Set while the memo is deleted but the deletion can still be undone
+    pub undo_token: Option<Uuid>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_one = "super::memo_list_view::Entity")]
+    MemoListView,
+    #[sea_orm(has_many = "super::memo_snoozes::Entity")]
+    MemoSnoozes,
+    #[sea_orm(has_many = "super::notifications::Entity")]
+    Notifications,
+    #[sea_orm(
+        belongs_to = "super::undo_tokens::Entity",
+        from = "Column::UndoToken",
+        to = "super::undo_tokens::Column::Token",
+        on_update = "NoAction",
+        on_delete = "Cascade"
+    )]
+    UndoTokens,
+}
+
+impl Related<super::memo_list_view::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemoListView.def()
+    }
+}
+
+impl Related<super::memo_snoozes::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::MemoSnoozes.def()
+    }
+}
+
+impl Related<super::notifications::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Notifications.def()
+    }
+}
+
+impl Related<super::undo_tokens::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::UndoTokens.def()
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
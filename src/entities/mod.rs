@@ -2,4 +2,18 @@
 
 pub mod prelude;
 
+pub mod api_key_usage;
+pub mod memo_attachments;
+pub mod memo_dependencies;
+pub mod memo_import_failures;
+pub mod memo_imports;
+pub mod memo_list_view;
+pub mod memo_snoozes;
+pub mod memo_templates;
 pub mod memos;
+pub mod notifications;
+pub mod operations;
+pub mod outbox_events;
+pub mod push_subscriptions;
+pub mod undo_tokens;
+pub mod webhook_integrations;
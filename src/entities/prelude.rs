@@ -1,3 +1,17 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.17
 
+pub use super::api_key_usage::Entity as ApiKeyUsage;
+pub use super::memo_attachments::Entity as MemoAttachments;
+pub use super::memo_dependencies::Entity as MemoDependencies;
+pub use super::memo_import_failures::Entity as MemoImportFailures;
+pub use super::memo_imports::Entity as MemoImports;
+pub use super::memo_list_view::Entity as MemoListView;
+pub use super::memo_snoozes::Entity as MemoSnoozes;
+pub use super::memo_templates::Entity as MemoTemplates;
 pub use super::memos::Entity as Memos;
+pub use super::notifications::Entity as Notifications;
+pub use super::operations::Entity as Operations;
+pub use super::outbox_events::Entity as OutboxEvents;
+pub use super::push_subscriptions::Entity as PushSubscriptions;
+pub use super::undo_tokens::Entity as UndoTokens;
+pub use super::webhook_integrations::Entity as WebhookIntegrations;
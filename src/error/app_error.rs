@@ -1,35 +1,261 @@
-use actix_web::{HttpResponse, error::ResponseError, http::StatusCode};
+use super::ErrorCode;
+use crate::i18n::Locale;
+use actix_web::{
+    HttpResponse,
+    error::ResponseError,
+    http::{StatusCode, header},
+};
 use sea_orm::DbErr;
 use serde::Serialize;
 use thiserror::Error;
 use utoipa::ToSchema;
-use validator::ValidationErrors;
+use validator::{ValidationErrors, ValidationErrorsKind};
 
 #[derive(Debug, Error)]
 pub enum AppError {
     #[error("Database error: {0}")]
     Database(#[from] DbErr),
 
-    #[error("Not found: {0}")]
-    NotFound(String),
+    /// A missing resource; `code` tells clients which kind.
+    #[error("Not found: {detail}")]
+    NotFound { code: ErrorCode, detail: String },
+
+    /// No route matches the request path.
+    #[error("No route for {0}")]
+    RouteNotFound(String),
+
+    /// The path exists but not for this method; `allowed` feeds the `Allow` header.
+    #[error("Method {method} not allowed")]
+    MethodNotAllowed {
+        method: String,
+        allowed: Vec<String>,
+    },
 
     #[error("Validation error: {0}")]
     Validation(String),
 
+    /// Input failed `validator` rules; `errors` lists each offending field.
+    #[error("Validation error: {message}")]
+    InvalidFields {
+        message: String,
+        errors: Vec<FieldError>,
+    },
+
     #[error("Internal error: {0}")]
     Internal(String),
+
+    /// The handler did not finish within the configured request timeout.
+    #[error("Timeout: {0}")]
+    Timeout(String),
+
+    /// The service is in maintenance mode; clients should retry after `retry_after_secs`.
+    #[error("Service unavailable: maintenance in progress")]
+    Maintenance { retry_after_secs: u64 },
+
+    /// The database circuit breaker is open after repeated connection
+    /// failures; the next attempt is let through in `retry_after_secs`.
+    #[error("Service unavailable: database circuit open")]
+    DatabaseUnavailable { retry_after_secs: u64 },
+
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// An optional feature (e.g. web push) is not configured on this instance.
+    #[error("Feature disabled: {0}")]
+    FeatureDisabled(String),
+
+    /// The caller exhausted its rate limit budget; it may retry after `retry_after_secs`.
+    #[error("Too many requests")]
+    RateLimited { retry_after_secs: u64 },
+
+    /// The API key used up its monthly quota; it resets in `retry_after_secs`.
+    #[error("Monthly quota exceeded")]
+    QuotaExceeded { retry_after_secs: u64 },
+
+    /// The request body is larger than the route group's `limit` in bytes.
+    #[error("Payload too large: limit is {limit} bytes")]
+    PayloadTooLarge { limit: usize },
+
+    /// An upload scanner refused `file_name`, e.g. for its type or malware.
+    #[error("Upload rejected: {file_name}: {reason}")]
+    UploadRejected { file_name: String, reason: String },
+
+    /// The resource changed since the client read it; `current` is its latest state.
+    #[error("Conflict: {message}")]
+    Conflict {
+        message: String,
+        current: Option<serde_json::Value>,
+    },
+
+    /// The memo cannot be completed while `blockers` (the open memos it
+    /// depends on) are still open.
+    #[error("Blocked: {message}")]
+    Blocked {
+        message: String,
+        blockers: serde_json::Value,
+    },
+}
+
+/// A single failed validation rule.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct FieldError {
+    /// Path of the invalid field, e.g. `title` or `items[2].title`
+    #[schema(example = "title")]
+    pub field: String,
+    /// Validator rule that failed, e.g. `length`, `range` or `email`
+    #[schema(example = "length")]
+    pub code: String,
+    /// Human-readable description of the problem
+    #[schema(example = "Title must be between 1 and 200 characters")]
+    pub message: String,
 }
 
 #[derive(Serialize, ToSchema)]
 pub struct ErrorResponse {
+    #[schema(example = "NotFound")]
     pub error: String,
+    /// Stable code to branch on; `message` is translated and may change
+    pub code: ErrorCode,
+    #[schema(example = "Not found: Memo with id 550e8400-e29b-41d4-a716-446655440000 not found")]
     pub message: String,
+    #[schema(example = 404)]
     pub status: u16,
+    /// Per-field details, included with validation errors
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub errors: Option<Vec<FieldError>>,
+    /// Current state of the resource, included with 409 Conflict responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub current: Option<serde_json::Value>,
+    /// Open memos standing in the way, included with `MEMO_BLOCKED` responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Vec<Object>>)]
+    pub blockers: Option<serde_json::Value>,
+    /// Body size limit in bytes, included with `PAYLOAD_TOO_LARGE` responses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = 262144)]
+    pub limit: Option<usize>,
+}
+
+impl AppError {
+    pub fn not_found(code: ErrorCode, detail: impl Into<String>) -> Self {
+        AppError::NotFound {
+            code,
+            detail: detail.into(),
+        }
+    }
+
+    /// Machine-readable code for this error, also logged as `error_code`.
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::Database(DbErr::ConnectionAcquire(_) | DbErr::Conn(_)) => {
+                ErrorCode::DbUnavailable
+            }
+            AppError::Database(_) => ErrorCode::DbError,
+            AppError::NotFound { code, .. } => *code,
+            AppError::RouteNotFound(_) => ErrorCode::RouteNotFound,
+            AppError::MethodNotAllowed { .. } => ErrorCode::MethodNotAllowed,
+            AppError::Validation(_) => ErrorCode::ValidationFailed,
+            AppError::InvalidFields { errors, .. } => {
+                errors.first().map_or(ErrorCode::ValidationFailed, |e| {
+                    ErrorCode::for_field(&e.field, &e.code)
+                })
+            }
+            AppError::Internal(_) => ErrorCode::InternalError,
+            AppError::Timeout(_) => ErrorCode::RequestTimeout,
+            AppError::Maintenance { .. } => ErrorCode::MaintenanceMode,
+            AppError::DatabaseUnavailable { .. } => ErrorCode::DbUnavailable,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::RateLimited { .. } => ErrorCode::RateLimited,
+            AppError::QuotaExceeded { .. } => ErrorCode::QuotaExceeded,
+            AppError::PayloadTooLarge { .. } => ErrorCode::PayloadTooLarge,
+            AppError::UploadRejected { .. } => ErrorCode::UploadRejected,
+            AppError::FeatureDisabled(_) => ErrorCode::FeatureDisabled,
+            AppError::Conflict { .. } => ErrorCode::VersionConflict,
+            AppError::Blocked { .. } => ErrorCode::MemoBlocked,
+        }
+    }
+
+    /// Error message translated into `locale`, keyed by the error's
+    /// [`ErrorCode`].
+    ///
+    /// The detail part comes from the failing operation and is not translated.
+    pub fn localized_message(&self, locale: Locale) -> String {
+        let detail = match self {
+            AppError::Database(e) => e.to_string(),
+            AppError::NotFound { detail, .. } => detail.clone(),
+            AppError::RouteNotFound(path) => path.clone(),
+            AppError::MethodNotAllowed { method, allowed } => {
+                format!("{} (allowed: {})", method, allowed.join(", "))
+            }
+            AppError::Validation(detail)
+            | AppError::Internal(detail)
+            | AppError::Timeout(detail)
+            | AppError::Unauthorized(detail)
+            | AppError::FeatureDisabled(detail) => detail.clone(),
+            AppError::InvalidFields { message, .. }
+            | AppError::Conflict { message, .. }
+            | AppError::Blocked { message, .. } => message.clone(),
+            AppError::Maintenance { retry_after_secs }
+            | AppError::DatabaseUnavailable { retry_after_secs }
+            | AppError::RateLimited { retry_after_secs } => {
+                format!("retry after {} seconds", retry_after_secs)
+            }
+            AppError::QuotaExceeded { retry_after_secs } => {
+                format!("resets in {} seconds", retry_after_secs)
+            }
+            AppError::PayloadTooLarge { limit } => format!("limit is {} bytes", limit),
+            AppError::UploadRejected { file_name, reason } => {
+                format!("{}: {}", file_name, reason)
+            }
+        };
+        format!("{}: {}", locale.t(&self.code().message_key()), detail)
+    }
 }
 
 impl From<ValidationErrors> for AppError {
     fn from(err: ValidationErrors) -> Self {
-        AppError::Validation(format!("Validation failed: {}", err))
+        let mut errors = Vec::new();
+        collect_field_errors(&err, "", &mut errors);
+        errors.sort_by(|a, b| a.field.cmp(&b.field).then_with(|| a.code.cmp(&b.code)));
+
+        AppError::InvalidFields {
+            message: format!("Validation failed: {}", err),
+            errors,
+        }
+    }
+}
+
+/// Flattens nested struct and list errors into dotted/indexed field paths.
+fn collect_field_errors(errors: &ValidationErrors, prefix: &str, out: &mut Vec<FieldError>) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(field_errors) => {
+                out.extend(field_errors.iter().map(|e| {
+                    FieldError {
+                        field: path.clone(),
+                        code: e.code.to_string(),
+                        message: e
+                            .message
+                            .as_ref()
+                            .map(|m| m.to_string())
+                            .unwrap_or_else(|| format!("{} is invalid ({})", path, e.code)),
+                    }
+                }));
+            }
+            ValidationErrorsKind::Struct(nested) => collect_field_errors(nested, &path, out),
+            ValidationErrorsKind::List(items) => {
+                for (index, nested) in items {
+                    collect_field_errors(nested, &format!("{}[{}]", path, index), out);
+                }
+            }
+        }
     }
 }
 
@@ -42,33 +268,164 @@ impl From<String> for AppError {
 impl ResponseError for AppError {
     fn status_code(&self) -> StatusCode {
         match self {
+            // The database is unreachable rather than rejecting the query, so
+            // a retry may succeed
+            AppError::Database(DbErr::ConnectionAcquire(_) | DbErr::Conn(_)) => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
             AppError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            AppError::NotFound(_) => StatusCode::NOT_FOUND,
-            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::NotFound { .. } | AppError::RouteNotFound(_) => StatusCode::NOT_FOUND,
+            AppError::MethodNotAllowed { .. } => StatusCode::METHOD_NOT_ALLOWED,
+            AppError::Validation(_) | AppError::InvalidFields { .. } => StatusCode::BAD_REQUEST,
             AppError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            AppError::Maintenance { .. } | AppError::DatabaseUnavailable { .. } => {
+                StatusCode::SERVICE_UNAVAILABLE
+            }
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::RateLimited { .. } | AppError::QuotaExceeded { .. } => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            AppError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::UploadRejected { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            AppError::FeatureDisabled(_) => StatusCode::NOT_FOUND,
+            AppError::Conflict { .. } | AppError::Blocked { .. } => StatusCode::CONFLICT,
         }
     }
 
     fn error_response(&self) -> HttpResponse {
         let status = self.status_code();
         let error_type = match self {
+            AppError::Database(DbErr::ConnectionAcquire(_) | DbErr::Conn(_)) => {
+                "ServiceUnavailable"
+            }
             AppError::Database(_) => "DatabaseError",
-            AppError::NotFound(_) => "NotFound",
-            AppError::Validation(_) => "ValidationError",
+            AppError::NotFound { .. } | AppError::RouteNotFound(_) => "NotFound",
+            AppError::MethodNotAllowed { .. } => "MethodNotAllowed",
+            AppError::Validation(_) | AppError::InvalidFields { .. } => "ValidationError",
             AppError::Internal(_) => "InternalError",
+            AppError::Timeout(_) => "Timeout",
+            AppError::Maintenance { .. } | AppError::DatabaseUnavailable { .. } => {
+                "ServiceUnavailable"
+            }
+            AppError::Unauthorized(_) => "Unauthorized",
+            AppError::RateLimited { .. } | AppError::QuotaExceeded { .. } => "TooManyRequests",
+            AppError::PayloadTooLarge { .. } => "PayloadTooLarge",
+            AppError::UploadRejected { .. } => "UploadRejected",
+            AppError::FeatureDisabled(_) => "FeatureDisabled",
+            AppError::Conflict { .. } | AppError::Blocked { .. } => "Conflict",
         };
 
+        let code = self.code();
+
         tracing::error!(
             error_type = error_type,
+            error_code = %code,
             status_code = status.as_u16(),
             message = %self,
             "Error occurred"
         );
 
-        HttpResponse::build(status).json(ErrorResponse {
+        let mut builder = HttpResponse::build(status);
+        if let AppError::Maintenance { retry_after_secs }
+        | AppError::DatabaseUnavailable { retry_after_secs }
+        | AppError::RateLimited { retry_after_secs }
+        | AppError::QuotaExceeded { retry_after_secs } = self
+        {
+            builder.insert_header((header::RETRY_AFTER, retry_after_secs.to_string()));
+        }
+        if let AppError::MethodNotAllowed { allowed, .. } = self {
+            builder.insert_header((header::ALLOW, allowed.join(", ")));
+        }
+
+        builder.json(ErrorResponse {
             error: error_type.to_string(),
-            message: self.to_string(),
+            code,
+            message: self.localized_message(Locale::current()),
             status: status.as_u16(),
+            errors: match self {
+                AppError::InvalidFields { errors, .. } => Some(errors.clone()),
+                _ => None,
+            },
+            current: match self {
+                AppError::Conflict { current, .. } => current.clone(),
+                _ => None,
+            },
+            blockers: match self {
+                AppError::Blocked { blockers, .. } => Some(blockers.clone()),
+                _ => None,
+            },
+            limit: match self {
+                AppError::PayloadTooLarge { limit } => Some(*limit),
+                _ => None,
+            },
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validator::Validate;
+
+    #[derive(Validate)]
+    struct Inner {
+        #[validate(range(min = 1, message = "Must be positive"))]
+        amount: i32,
+    }
+
+    #[derive(Validate)]
+    struct Outer {
+        #[validate(length(min = 1))]
+        title: String,
+        #[validate(nested)]
+        items: Vec<Inner>,
+    }
+
+    #[test]
+    fn test_unreachable_database_is_service_unavailable() {
+        let unavailable = [
+            AppError::Database(DbErr::ConnectionAcquire(sea_orm::ConnAcquireErr::Timeout)),
+            AppError::Database(DbErr::Conn(sea_orm::RuntimeErr::Internal(
+                "connection refused".to_string(),
+            ))),
+        ];
+        for err in unavailable {
+            assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+            assert_eq!(err.code(), ErrorCode::DbUnavailable);
+        }
+
+        let failed = AppError::Database(DbErr::Custom("constraint violated".to_string()));
+        assert_eq!(failed.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(failed.code(), ErrorCode::DbError);
+    }
+
+    #[test]
+    fn test_validation_errors_are_flattened_per_field() {
+        let input = Outer {
+            title: String::new(),
+            items: vec![Inner { amount: 5 }, Inner { amount: 0 }],
+        };
+
+        let err = AppError::from(input.validate().unwrap_err());
+        let AppError::InvalidFields { errors, .. } = err else {
+            panic!("expected InvalidFields");
+        };
+
+        assert_eq!(
+            errors,
+            vec![
+                FieldError {
+                    field: "items[1].amount".to_string(),
+                    code: "range".to_string(),
+                    message: "Must be positive".to_string(),
+                },
+                FieldError {
+                    field: "title".to_string(),
+                    code: "length".to_string(),
+                    message: "title is invalid (length)".to_string(),
+                },
+            ]
+        );
+    }
+}
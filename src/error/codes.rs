@@ -0,0 +1,169 @@
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Stable, machine-readable error identifiers.
+///
+/// Codes are part of the API contract: clients branch on them instead of the
+/// (translated) message, so existing codes must never be renamed or reused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    MemoNotFound,
+    TemplateNotFound,
+    OperationNotFound,
+    ImportNotFound,
+    NotificationNotFound,
+    AttachmentNotFound,
+    IntegrationNotFound,
+    /// Any other missing resource, like an asset or API document
+    ResourceNotFound,
+    RouteNotFound,
+    MethodNotAllowed,
+    ValidationFailed,
+    ValidationTitleLength,
+    ValidationDescriptionLength,
+    ValidationSearchLength,
+    ValidationLimitRange,
+    VersionConflict,
+    MemoBlocked,
+    RequestTimeout,
+    MaintenanceMode,
+    Unauthorized,
+    RateLimited,
+    QuotaExceeded,
+    PayloadTooLarge,
+    UploadRejected,
+    FeatureDisabled,
+    DbUnavailable,
+    DbError,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::MemoNotFound => "MEMO_NOT_FOUND",
+            ErrorCode::TemplateNotFound => "TEMPLATE_NOT_FOUND",
+            ErrorCode::OperationNotFound => "OPERATION_NOT_FOUND",
+            ErrorCode::ImportNotFound => "IMPORT_NOT_FOUND",
+            ErrorCode::NotificationNotFound => "NOTIFICATION_NOT_FOUND",
+            ErrorCode::AttachmentNotFound => "ATTACHMENT_NOT_FOUND",
+            ErrorCode::IntegrationNotFound => "INTEGRATION_NOT_FOUND",
+            ErrorCode::ResourceNotFound => "RESOURCE_NOT_FOUND",
+            ErrorCode::RouteNotFound => "ROUTE_NOT_FOUND",
+            ErrorCode::MethodNotAllowed => "METHOD_NOT_ALLOWED",
+            ErrorCode::ValidationFailed => "VALIDATION_FAILED",
+            ErrorCode::ValidationTitleLength => "VALIDATION_TITLE_LENGTH",
+            ErrorCode::ValidationDescriptionLength => "VALIDATION_DESCRIPTION_LENGTH",
+            ErrorCode::ValidationSearchLength => "VALIDATION_SEARCH_LENGTH",
+            ErrorCode::ValidationLimitRange => "VALIDATION_LIMIT_RANGE",
+            ErrorCode::VersionConflict => "VERSION_CONFLICT",
+            ErrorCode::MemoBlocked => "MEMO_BLOCKED",
+            ErrorCode::RequestTimeout => "REQUEST_TIMEOUT",
+            ErrorCode::MaintenanceMode => "MAINTENANCE_MODE",
+            ErrorCode::Unauthorized => "UNAUTHORIZED",
+            ErrorCode::RateLimited => "RATE_LIMITED",
+            ErrorCode::QuotaExceeded => "QUOTA_EXCEEDED",
+            ErrorCode::PayloadTooLarge => "PAYLOAD_TOO_LARGE",
+            ErrorCode::UploadRejected => "UPLOAD_REJECTED",
+            ErrorCode::FeatureDisabled => "FEATURE_DISABLED",
+            ErrorCode::DbUnavailable => "DB_UNAVAILABLE",
+            ErrorCode::DbError => "DB_ERROR",
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+        }
+    }
+
+    /// Catalog key of the code's message, e.g. `error-memo-not-found`.
+    pub fn message_key(&self) -> String {
+        format!(
+            "error-{}",
+            self.as_str().to_ascii_lowercase().replace('_', "-")
+        )
+    }
+
+    /// Code for a failed `validator` rule on a given field.
+    pub fn for_field(field: &str, rule: &str) -> Self {
+        match (field, rule) {
+            ("title", "length") => ErrorCode::ValidationTitleLength,
+            ("description", "length") => ErrorCode::ValidationDescriptionLength,
+            ("q", "length") => ErrorCode::ValidationSearchLength,
+            ("limit", "range") => ErrorCode::ValidationLimitRange,
+            _ => ErrorCode::ValidationFailed,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Locale;
+
+    #[test]
+    fn test_every_code_has_a_message() {
+        for code in [
+            ErrorCode::MemoNotFound,
+            ErrorCode::TemplateNotFound,
+            ErrorCode::OperationNotFound,
+            ErrorCode::ImportNotFound,
+            ErrorCode::NotificationNotFound,
+            ErrorCode::AttachmentNotFound,
+            ErrorCode::IntegrationNotFound,
+            ErrorCode::ResourceNotFound,
+            ErrorCode::RouteNotFound,
+            ErrorCode::MethodNotAllowed,
+            ErrorCode::ValidationFailed,
+            ErrorCode::ValidationTitleLength,
+            ErrorCode::ValidationDescriptionLength,
+            ErrorCode::ValidationSearchLength,
+            ErrorCode::ValidationLimitRange,
+            ErrorCode::VersionConflict,
+            ErrorCode::MemoBlocked,
+            ErrorCode::RequestTimeout,
+            ErrorCode::MaintenanceMode,
+            ErrorCode::Unauthorized,
+            ErrorCode::RateLimited,
+            ErrorCode::QuotaExceeded,
+            ErrorCode::PayloadTooLarge,
+            ErrorCode::UploadRejected,
+            ErrorCode::FeatureDisabled,
+            ErrorCode::DbUnavailable,
+            ErrorCode::DbError,
+            ErrorCode::InternalError,
+        ] {
+            let key = code.message_key();
+            assert_ne!(Locale::En.t(&key), key, "{code} has no message");
+        }
+        assert_eq!(
+            ErrorCode::MemoNotFound.message_key(),
+            "error-memo-not-found"
+        );
+    }
+
+    #[test]
+    fn test_as_str_matches_serialized_form() {
+        for code in [
+            ErrorCode::MemoNotFound,
+            ErrorCode::TemplateNotFound,
+            ErrorCode::ResourceNotFound,
+            ErrorCode::ValidationTitleLength,
+            ErrorCode::ValidationLimitRange,
+            ErrorCode::DbUnavailable,
+            ErrorCode::VersionConflict,
+            ErrorCode::MemoBlocked,
+            ErrorCode::PayloadTooLarge,
+            ErrorCode::UploadRejected,
+            ErrorCode::QuotaExceeded,
+        ] {
+            assert_eq!(
+                serde_json::to_value(code).unwrap(),
+                serde_json::Value::String(code.as_str().to_string())
+            );
+        }
+    }
+}
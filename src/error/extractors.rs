@@ -0,0 +1,311 @@
+//! Extractor configs whose error handlers turn deserialization failures into
+//! [`AppError`] responses, so a bad UUID or a typo in a JSON body gets the
+//! same error body as any other validation failure.
+
+use actix_multipart::{MultipartError, form::MultipartFormConfig};
+use actix_web::{
+    HttpRequest, Scope,
+    error::{JsonPayloadError, PathError, PayloadError, QueryPayloadError, UrlencodedError},
+    web::{self, FormConfig, JsonConfig, PathConfig, PayloadConfig, QueryConfig},
+};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::{AppError, FieldError};
+
+/// Body size limit of the route group handling a request, for the `limit`
+/// of `PAYLOAD_TOO_LARGE` errors whose source does not report it.
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimit(pub usize);
+
+/// Scope without a path prefix whose JSON, form, multipart and raw bodies are
+/// capped at `limit` bytes. Route groups are told apart with a guard on the
+/// path.
+pub fn limited_scope(limit: usize) -> Scope {
+    web::scope("")
+        .app_data(json_config(limit))
+        .app_data(form_config(limit))
+        .app_data(multipart_config(limit))
+        .app_data(PayloadConfig::new(limit))
+        .app_data(BodyLimit(limit))
+}
+
+/// `JsonConfig` with the given body limit and [`AppError`] deserialization errors.
+pub fn json_config(limit: usize) -> JsonConfig {
+    JsonConfig::default().limit(limit).error_handler(json_error)
+}
+
+/// `FormConfig` with the given body limit and [`AppError`] size errors.
+pub fn form_config(limit: usize) -> FormConfig {
+    FormConfig::default().limit(limit).error_handler(form_error)
+}
+
+/// `MultipartFormConfig` keeping up to `limit` bytes of fields and files in
+/// memory, with [`AppError`] errors.
+pub fn multipart_config(limit: usize) -> MultipartFormConfig {
+    MultipartFormConfig::default()
+        .total_limit(limit)
+        .memory_limit(limit)
+        .error_handler(move |err, _req| multipart_error(err, limit))
+}
+
+pub fn query_config() -> QueryConfig {
+    QueryConfig::default().error_handler(query_error)
+}
+
+pub fn path_config() -> PathConfig {
+    PathConfig::default().error_handler(path_error)
+}
+
+fn json_error(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    match err {
+        JsonPayloadError::Deserialize(e) if e.is_data() => {
+            field_error(&e.to_string(), "body").into()
+        }
+        JsonPayloadError::Deserialize(e) => AppError::Validation(format!(
+            "Malformed JSON at line {} column {}",
+            e.line(),
+            e.column()
+        ))
+        .into(),
+        JsonPayloadError::Overflow { limit }
+        | JsonPayloadError::OverflowKnownLength { limit, .. } => {
+            AppError::PayloadTooLarge { limit }.into()
+        }
+        // Content type and transport errors keep their own status codes.
+        other => other.into(),
+    }
+}
+
+fn form_error(err: UrlencodedError, _req: &HttpRequest) -> actix_web::Error {
+    match err {
+        UrlencodedError::Overflow { limit, .. } => AppError::PayloadTooLarge { limit }.into(),
+        other => other.into(),
+    }
+}
+
+/// Turns a raw body read past the `PayloadConfig` limit (e.g. from
+/// [`web::Bytes`]) into a `PAYLOAD_TOO_LARGE` error; other errors pass through.
+pub fn payload_error(err: actix_web::Error, req: &HttpRequest) -> actix_web::Error {
+    match (err.as_error::<PayloadError>(), req.app_data::<BodyLimit>()) {
+        (Some(PayloadError::Overflow), Some(BodyLimit(limit))) => {
+            AppError::PayloadTooLarge { limit: *limit }.into()
+        }
+        _ => err,
+    }
+}
+
+fn multipart_error(err: MultipartError, limit: usize) -> actix_web::Error {
+    match err {
+        MultipartError::Payload(PayloadError::Overflow) => {
+            AppError::PayloadTooLarge { limit }.into()
+        }
+        MultipartError::Field { source, .. }
+            if matches!(
+                source.as_error::<MultipartError>(),
+                Some(MultipartError::Payload(PayloadError::Overflow))
+            ) =>
+        {
+            AppError::PayloadTooLarge { limit }.into()
+        }
+        MultipartError::Field { name, source } => AppError::InvalidFields {
+            message: format!("Invalid multipart field {}: {}", name, source),
+            errors: vec![FieldError {
+                code: "invalid".to_string(),
+                message: source.to_string(),
+                field: name,
+            }],
+        }
+        .into(),
+        MultipartError::MissingField(field) => AppError::InvalidFields {
+            message: format!("Missing multipart field {}", field),
+            errors: vec![FieldError {
+                message: format!("{} is required", field),
+                field,
+                code: "required".to_string(),
+            }],
+        }
+        .into(),
+        other => other.into(),
+    }
+}
+
+fn query_error(err: QueryPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    match err {
+        QueryPayloadError::Deserialize(e) => field_error(&e.to_string(), "query").into(),
+        other => other.into(),
+    }
+}
+
+fn path_error(err: PathError, req: &HttpRequest) -> actix_web::Error {
+    let PathError::Deserialize(e) = err else {
+        return err.into();
+    };
+    // Handlers here take at most one path parameter, so the failing one is
+    // the only one.
+    let field = req
+        .match_info()
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .next()
+        .unwrap_or_else(|| "path".to_string());
+
+    AppError::InvalidFields {
+        message: format!("Invalid path parameter {}: {}", field, e),
+        errors: vec![FieldError {
+            field,
+            code: "invalid".to_string(),
+            message: e.to_string(),
+        }],
+    }
+    .into()
+}
+
+/// Deserializes a request body already parsed into a JSON value. In `strict`
+/// mode, fields the DTO does not declare are rejected, each listed as an
+/// `unknown_field` error, instead of being silently dropped.
+pub fn from_body_value<T: DeserializeOwned>(value: Value, strict: bool) -> Result<T, AppError> {
+    if !strict {
+        return serde_json::from_value(value).map_err(|e| field_error(&e.to_string(), "body"));
+    }
+
+    let mut unknown = Vec::new();
+    let dto = serde_ignored::deserialize(value, |path| unknown.push(field_path(&path)))
+        .map_err(|e: serde_json::Error| field_error(&e.to_string(), "body"))?;
+    if unknown.is_empty() {
+        return Ok(dto);
+    }
+
+    Err(AppError::InvalidFields {
+        message: format!("Invalid body: unknown fields {}", unknown.join(", ")),
+        errors: unknown
+            .into_iter()
+            .map(|field| FieldError {
+                message: format!("Unknown field {}", field),
+                field,
+                code: "unknown_field".to_string(),
+            })
+            .collect(),
+    })
+}
+
+/// Renders a path the way validation errors name fields, e.g. `items[2].title`.
+fn field_path(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+
+    match path {
+        Path::Root => String::new(),
+        Path::Seq { parent, index } => format!("{}[{}]", field_path(parent), index),
+        Path::Map { parent, key } => match field_path(parent) {
+            parent if parent.is_empty() => key.clone(),
+            parent => format!("{}.{}", parent, key),
+        },
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => field_path(parent),
+    }
+}
+
+/// Builds a field-level error from a serde message. serde names the field
+/// only for missing and unknown fields; other failures are reported against
+/// `fallback_field`.
+fn field_error(message: &str, fallback_field: &str) -> AppError {
+    let (field, code) = if let Some(field) = quoted_after(message, "missing field ") {
+        (field, "required")
+    } else if let Some(field) = quoted_after(message, "unknown field ") {
+        (field, "unknown_field")
+    } else {
+        (fallback_field, "invalid")
+    };
+
+    AppError::InvalidFields {
+        message: format!("Invalid {}: {}", fallback_field, message),
+        errors: vec![FieldError {
+            field: field.to_string(),
+            code: code.to_string(),
+            message: message.to_string(),
+        }],
+    }
+}
+
+/// The backtick-quoted name serde puts after `prefix`, e.g. `title` in
+/// ``missing field `title` at line 1 column 2``.
+fn quoted_after<'a>(message: &'a str, prefix: &str) -> Option<&'a str> {
+    let rest = message.split_once(prefix)?.1.strip_prefix('`')?;
+    rest.split_once('`').map(|(name, _)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first_error(err: AppError) -> FieldError {
+        match err {
+            AppError::InvalidFields { mut errors, .. } => errors.remove(0),
+            other => panic!("expected InvalidFields, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_missing_and_unknown_fields_are_named() {
+        let missing = first_error(field_error(
+            "missing field `title` at line 1 column 2",
+            "body",
+        ));
+        assert_eq!(missing.field, "title");
+        assert_eq!(missing.code, "required");
+
+        let unknown = first_error(field_error(
+            "unknown field `descripton`, expected `title` or `description`",
+            "body",
+        ));
+        assert_eq!(unknown.field, "descripton");
+        assert_eq!(unknown.code, "unknown_field");
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Memo {
+        title: String,
+        #[serde(default)]
+        items: Vec<Item>,
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    #[allow(dead_code)]
+    struct Item {
+        title: String,
+    }
+
+    #[test]
+    fn test_strict_bodies_list_every_unknown_field() {
+        let body = serde_json::json!({
+            "title": "Pay rent",
+            "descripton": "typo",
+            "items": [{ "title": "a" }, { "title": "b", "done": true }],
+        });
+
+        let AppError::InvalidFields { message, errors } =
+            from_body_value::<Memo>(body.clone(), true).unwrap_err()
+        else {
+            panic!("expected InvalidFields");
+        };
+        assert_eq!(
+            message,
+            "Invalid body: unknown fields descripton, items[1].done"
+        );
+        assert_eq!(errors[0].field, "descripton");
+        assert_eq!(errors[0].code, "unknown_field");
+        assert_eq!(errors[1].field, "items[1].done");
+
+        assert!(from_body_value::<Memo>(body, false).is_ok());
+    }
+
+    #[test]
+    fn test_other_failures_use_the_fallback_field() {
+        let err = first_error(field_error("invalid digit found in string", "query"));
+        assert_eq!(err.field, "query");
+        assert_eq!(err.code, "invalid");
+        assert_eq!(err.message, "invalid digit found in string");
+    }
+}
@@ -1,3 +1,6 @@
 pub mod app_error;
+pub mod codes;
+pub mod extractors;
 
-pub use app_error::{AppError, ErrorResponse};
+pub use app_error::{AppError, ErrorResponse, FieldError};
+pub use codes::ErrorCode;
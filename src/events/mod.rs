@@ -0,0 +1,229 @@
+//! Domain events, published to a message broker through a transactional outbox.
+//!
+//! Services call [`record`] with the transaction that changes a memo, so an
+//! event is stored if and only if the change commits. The
+//! [`OutboxRelay`](crate::scheduler::tasks::OutboxRelay) task later publishes stored events in order, with
+//! at-least-once delivery: consumers should deduplicate on the envelope `id`.
+//!
+//! Recording is off until [`enable`] is called at startup, which happens only
+//! when a broker is configured; otherwise nothing would drain the outbox. The
+//! [`projection`] read models are updated from every event either way.
+
+pub mod projection;
+pub mod publisher;
+
+use chrono::{DateTime, Utc};
+use sea_orm::{ConnectionTrait, DbErr};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use uuid::Uuid;
+
+use crate::entities::memos;
+use crate::repository::OutboxRepository;
+
+pub use publisher::{EventPublisher, PublishError};
+
+/// Bumped on breaking changes to the envelope or event data.
+pub const SCHEMA_VERSION: u32 = 1;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Starts writing events to the outbox.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Memo state carried by events. The description is left out: it may be
+/// encrypted at rest and should not be copied to the broker in plaintext.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoSnapshot {
+    pub id: Uuid,
+    pub title: String,
+    pub date_to: DateTime<Utc>,
+    pub completed: bool,
+    pub archived: bool,
+    pub pinned: bool,
+    pub starred: bool,
+    #[serde(default)]
+    pub color: Option<String>,
+    pub version: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<&memos::Model> for MemoSnapshot {
+    fn from(memo: &memos::Model) -> Self {
+        Self {
+            id: memo.id,
+            title: memo.title.clone(),
+            date_to: memo.date_to.into(),
+            completed: memo.completed,
+            archived: memo.archived,
+            pinned: memo.pinned,
+            starred: memo.starred,
+            color: memo.color.clone(),
+            version: memo.version,
+            updated_at: memo.updated_at.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoRef {
+    pub id: Uuid,
+}
+
+/// A dependency between two memos: `memo_id` waits on `blocker_id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MemoLink {
+    pub memo_id: Uuid,
+    pub blocker_id: Uuid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttachmentRef {
+    pub id: Uuid,
+    pub memo_id: Uuid,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", content = "data")]
+pub enum DomainEvent {
+    #[serde(rename = "memo.created")]
+    MemoCreated(MemoSnapshot),
+    /// Any change to a memo's fields, archive state included
+    #[serde(rename = "memo.updated")]
+    MemoUpdated(MemoSnapshot),
+    /// Completed by a batch operation
+    #[serde(rename = "memo.completed")]
+    MemoCompleted(MemoRef),
+    /// Deleted; the memo can still be restored until its undo window closes
+    #[serde(rename = "memo.deleted")]
+    MemoDeleted(MemoRef),
+    /// Brought back with an undo token after being deleted
+    #[serde(rename = "memo.restored")]
+    MemoRestored(MemoSnapshot),
+    #[serde(rename = "memo.blocker_added")]
+    BlockerAdded(MemoLink),
+    #[serde(rename = "memo.blocker_removed")]
+    BlockerRemoved(MemoLink),
+    #[serde(rename = "memo.attachment_added")]
+    AttachmentAdded(AttachmentRef),
+}
+
+impl DomainEvent {
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            DomainEvent::MemoCreated(_) => "memo.created",
+            DomainEvent::MemoUpdated(_) => "memo.updated",
+            DomainEvent::MemoCompleted(_) => "memo.completed",
+            DomainEvent::MemoDeleted(_) => "memo.deleted",
+            DomainEvent::MemoRestored(_) => "memo.restored",
+            DomainEvent::BlockerAdded(_) => "memo.blocker_added",
+            DomainEvent::BlockerRemoved(_) => "memo.blocker_removed",
+            DomainEvent::AttachmentAdded(_) => "memo.attachment_added",
+        }
+    }
+
+    /// The memo the event is about; used as the broker message key.
+    pub fn aggregate_id(&self) -> Uuid {
+        match self {
+            DomainEvent::MemoCreated(memo)
+            | DomainEvent::MemoUpdated(memo)
+            | DomainEvent::MemoRestored(memo) => memo.id,
+            DomainEvent::MemoCompleted(memo) | DomainEvent::MemoDeleted(memo) => memo.id,
+            DomainEvent::BlockerAdded(link) | DomainEvent::BlockerRemoved(link) => link.memo_id,
+            DomainEvent::AttachmentAdded(attachment) => attachment.memo_id,
+        }
+    }
+}
+
+/// What consumers receive:
+///
+/// ```json
+/// {"id": "…", "schema_version": 1, "occurred_at": "…", "aggregate_id": "…",
+///  "type": "memo.created", "data": {…}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    pub id: Uuid,
+    pub schema_version: u32,
+    pub occurred_at: DateTime<Utc>,
+    pub aggregate_id: Uuid,
+    #[serde(flatten)]
+    pub event: DomainEvent,
+}
+
+impl EventEnvelope {
+    pub fn new(event: DomainEvent) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            schema_version: SCHEMA_VERSION,
+            occurred_at: Utc::now(),
+            aggregate_id: event.aggregate_id(),
+            event,
+        }
+    }
+}
+
+/// Applies `event` to the [`projection`]s and writes it to the outbox through
+/// `db`, normally the transaction making the change. The outbox is skipped
+/// unless [`enable`] was called.
+pub async fn record<C: ConnectionTrait>(db: &C, event: DomainEvent) -> Result<(), DbErr> {
+    projection::apply(db, &event).await?;
+    if !is_enabled() {
+        return Ok(());
+    }
+
+    let envelope = EventEnvelope::new(event);
+    let payload =
+        serde_json::to_value(&envelope).map_err(|e| DbErr::Custom(format!("Event JSON: {}", e)))?;
+    OutboxRepository::append(
+        db,
+        envelope.id,
+        envelope.event.event_type(),
+        envelope.aggregate_id,
+        payload,
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_envelope_uses_the_documented_shape() {
+        let id = Uuid::new_v4();
+        let envelope = EventEnvelope::new(DomainEvent::MemoDeleted(MemoRef { id }));
+
+        let json = serde_json::to_value(&envelope).unwrap();
+
+        assert_eq!(json["type"], "memo.deleted");
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["aggregate_id"], id.to_string());
+        assert_eq!(json["data"]["id"], id.to_string());
+        assert_eq!(
+            serde_json::from_value::<EventEnvelope>(json).unwrap(),
+            envelope
+        );
+    }
+
+    #[test]
+    fn test_link_events_are_keyed_by_the_blocked_memo() {
+        let link = MemoLink {
+            memo_id: Uuid::new_v4(),
+            blocker_id: Uuid::new_v4(),
+        };
+        let envelope = EventEnvelope::new(DomainEvent::BlockerRemoved(link.clone()));
+
+        let json = serde_json::to_value(&envelope).unwrap();
+
+        assert_eq!(json["type"], "memo.blocker_removed");
+        assert_eq!(json["aggregate_id"], link.memo_id.to_string());
+        assert_eq!(json["data"]["blocker_id"], link.blocker_id.to_string());
+    }
+}
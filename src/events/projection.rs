@@ -0,0 +1,32 @@
+//! Read models kept up to date from domain events, in the transaction that
+//! records them.
+//!
+//! `memo_list_view` holds each memo's links and attachments, so a listing
+//! page is read with one join instead of a query per relation. Memos that
+//! never had either may have no row.
+
+use sea_orm::{ConnectionTrait, DbErr};
+
+use super::DomainEvent;
+use crate::repository::MemoListViewRepository;
+
+/// Updates the read models affected by `event`.
+pub async fn apply<C: ConnectionTrait>(db: &C, event: &DomainEvent) -> Result<(), DbErr> {
+    match event {
+        // A new memo has nothing to list yet, and the listed relations don't
+        // depend on a memo's own fields.
+        DomainEvent::MemoCreated(_)
+        | DomainEvent::MemoUpdated(_)
+        | DomainEvent::MemoCompleted(_) => Ok(()),
+        // Deleting or restoring a memo hides or shows its links on the other
+        // side.
+        DomainEvent::MemoDeleted(_)
+        | DomainEvent::MemoRestored(_)
+        | DomainEvent::BlockerAdded(_)
+        | DomainEvent::BlockerRemoved(_)
+        | DomainEvent::AttachmentAdded(_) => {
+            MemoListViewRepository::refresh(db, event.aggregate_id()).await?;
+            Ok(())
+        }
+    }
+}
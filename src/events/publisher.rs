@@ -0,0 +1,164 @@
+//! Broker clients for the outbox relay.
+//!
+//! Kafka and NATS support are behind the `kafka` and `nats` cargo features,
+//! so builds that do not publish events do not pull in either client.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::config::settings::{EventBroker, EventsConfig};
+use crate::entities::outbox_events;
+
+#[derive(Debug, Error)]
+pub enum PublishError {
+    #[error("broker error: {0}")]
+    Broker(String),
+
+    #[error("{0} support is not compiled in; rebuild with `--features {0}`")]
+    NotCompiled(&'static str),
+}
+
+type PublishFuture<'a> = Pin<Box<dyn Future<Output = Result<(), PublishError>> + Send + 'a>>;
+
+/// Delivers outbox events to a broker.
+pub trait EventPublisher: Send + Sync {
+    /// Publishes one event, resolving once the broker has acknowledged it.
+    fn publish<'a>(&'a self, event: &'a outbox_events::Model) -> PublishFuture<'a>;
+}
+
+/// Connects to the configured broker; `None` when event publishing is disabled.
+pub async fn connect(
+    config: &EventsConfig,
+) -> Result<Option<Arc<dyn EventPublisher>>, PublishError> {
+    let url = config.broker_url.as_deref().unwrap_or_default();
+    match config.broker {
+        EventBroker::None => Ok(None),
+        #[cfg(feature = "kafka")]
+        EventBroker::Kafka => Ok(Some(Arc::new(
+            kafka::KafkaPublisher::connect(url, &config.topic).await?,
+        ))),
+        #[cfg(not(feature = "kafka"))]
+        EventBroker::Kafka => {
+            let _ = url;
+            Err(PublishError::NotCompiled("kafka"))
+        }
+        #[cfg(feature = "nats")]
+        EventBroker::Nats => Ok(Some(Arc::new(
+            nats::NatsPublisher::connect(url, &config.topic).await?,
+        ))),
+        #[cfg(not(feature = "nats"))]
+        EventBroker::Nats => {
+            let _ = url;
+            Err(PublishError::NotCompiled("nats"))
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+pub mod kafka {
+    use super::*;
+    use rskafka::client::ClientBuilder;
+    use rskafka::client::partition::{Compression, PartitionClient, UnknownTopicHandling};
+    use rskafka::record::Record;
+    use std::collections::BTreeMap;
+
+    /// Produces to partition 0 of the topic, which keeps every event in
+    /// outbox order. Records are keyed by memo id.
+    pub struct KafkaPublisher {
+        partition: PartitionClient,
+    }
+
+    impl KafkaPublisher {
+        /// `brokers` is a comma-separated bootstrap list, e.g. `kafka-1:9092,kafka-2:9092`.
+        pub async fn connect(brokers: &str, topic: &str) -> Result<Self, PublishError> {
+            let brokers = brokers
+                .split(',')
+                .map(str::trim)
+                .filter(|broker| !broker.is_empty())
+                .map(str::to_string)
+                .collect();
+            let client = ClientBuilder::new(brokers)
+                .build()
+                .await
+                .map_err(|e| PublishError::Broker(e.to_string()))?;
+            let partition = client
+                .partition_client(topic.to_string(), 0, UnknownTopicHandling::Retry)
+                .await
+                .map_err(|e| PublishError::Broker(e.to_string()))?;
+            Ok(Self { partition })
+        }
+    }
+
+    impl EventPublisher for KafkaPublisher {
+        fn publish<'a>(&'a self, event: &'a outbox_events::Model) -> PublishFuture<'a> {
+            Box::pin(async move {
+                let record = Record {
+                    key: Some(event.aggregate_id.to_string().into_bytes()),
+                    value: Some(event.payload.to_string().into_bytes()),
+                    headers: BTreeMap::from([
+                        ("event_id".to_string(), event.id.to_string().into_bytes()),
+                        (
+                            "event_type".to_string(),
+                            event.event_type.clone().into_bytes(),
+                        ),
+                    ]),
+                    timestamp: event.created_at.into(),
+                };
+                self.partition
+                    .produce(vec![record], Compression::NoCompression)
+                    .await
+                    .map_err(|e| PublishError::Broker(e.to_string()))?;
+                Ok(())
+            })
+        }
+    }
+}
+
+#[cfg(feature = "nats")]
+pub mod nats {
+    use super::*;
+    use async_nats::HeaderMap;
+    use async_nats::header::NATS_MESSAGE_ID;
+    use async_nats::jetstream::{self, Context};
+
+    /// Publishes to JetStream on `<prefix>.<event type>`, e.g.
+    /// `memos.events.memo.created`; a stream must cover those subjects.
+    /// The event id is sent as `Nats-Msg-Id`, so JetStream drops redeliveries
+    /// within its duplicate window.
+    pub struct NatsPublisher {
+        jetstream: Context,
+        prefix: String,
+    }
+
+    impl NatsPublisher {
+        pub async fn connect(url: &str, prefix: &str) -> Result<Self, PublishError> {
+            let client = async_nats::connect(url)
+                .await
+                .map_err(|e| PublishError::Broker(e.to_string()))?;
+            Ok(Self {
+                jetstream: jetstream::new(client),
+                prefix: prefix.to_string(),
+            })
+        }
+    }
+
+    impl EventPublisher for NatsPublisher {
+        fn publish<'a>(&'a self, event: &'a outbox_events::Model) -> PublishFuture<'a> {
+            Box::pin(async move {
+                let mut headers = HeaderMap::new();
+                headers.insert(NATS_MESSAGE_ID, event.id.to_string().as_str());
+                let subject = format!("{}.{}", self.prefix, event.event_type);
+
+                self.jetstream
+                    .publish_with_headers(subject, headers, event.payload.to_string().into())
+                    .await
+                    .map_err(|e| PublishError::Broker(e.to_string()))?
+                    .await
+                    .map_err(|e| PublishError::Broker(e.to_string()))?;
+                Ok(())
+            })
+        }
+    }
+}
@@ -0,0 +1,154 @@
+use actix_web::{
+    FromRequest, HttpRequest, HttpResponse,
+    cookie::{Cookie, SameSite},
+    dev::Payload,
+    error::ErrorNotFound,
+    http::header::{HeaderValue, SET_COOKIE},
+};
+use std::future::{Ready, ready};
+use uuid::Uuid;
+
+/// Name of the cookie carrying a pending flash message between requests.
+pub const FLASH_COOKIE: &str = "flash";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlashLevel {
+    Success,
+    Error,
+}
+
+impl FlashLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FlashLevel::Success => "success",
+            FlashLevel::Error => "error",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "success" => Some(FlashLevel::Success),
+            "error" => Some(FlashLevel::Error),
+            _ => None,
+        }
+    }
+}
+
+/// One-shot banner shown on the next page or fragment that renders flashes.
+///
+/// The message is stored percent-encoded in a short-lived cookie as
+/// `level:message`; whoever renders it must call [`FlashMessage::clear`] so it
+/// is only shown once. Use it as an extractor via `Option<FlashMessage>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlashMessage {
+    pub level: FlashLevel,
+    pub message: String,
+    /// Offers to undo the deletion the message reports. Not kept in the
+    /// cookie, so only set it on messages rendered straight away.
+    pub undo_token: Option<Uuid>,
+}
+
+impl FlashMessage {
+    pub fn success(message: impl Into<String>) -> Self {
+        Self {
+            level: FlashLevel::Success,
+            message: message.into(),
+            undo_token: None,
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            level: FlashLevel::Error,
+            message: message.into(),
+            undo_token: None,
+        }
+    }
+
+    pub fn with_undo(mut self, token: Option<Uuid>) -> Self {
+        self.undo_token = token;
+        self
+    }
+
+    /// Reads the pending flash message from the request cookies, if any.
+    pub fn read(req: &HttpRequest) -> Option<Self> {
+        let cookie = req.cookie(FLASH_COOKIE)?;
+        let (level, message) = cookie.value().split_once(':')?;
+
+        Some(Self {
+            level: FlashLevel::parse(level)?,
+            message: message.to_string(),
+            undo_token: None,
+        })
+    }
+
+    /// Stores this message in the flash cookie on `response`.
+    pub fn attach(&self, response: &mut HttpResponse) {
+        let cookie = flash_cookie(format!("{}:{}", self.level.as_str(), self.message));
+        append_cookie(response, &cookie);
+    }
+
+    /// Expires the flash cookie once its message has been rendered.
+    pub fn clear(response: &mut HttpResponse) {
+        let mut cookie = flash_cookie(String::new());
+        cookie.make_removal();
+        append_cookie(response, &cookie);
+    }
+}
+
+impl FromRequest for FlashMessage {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(FlashMessage::read(req).ok_or_else(|| ErrorNotFound("No flash message")))
+    }
+}
+
+fn flash_cookie(value: String) -> Cookie<'static> {
+    Cookie::build(FLASH_COOKIE, value)
+        .path("/")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .finish()
+}
+
+fn append_cookie(response: &mut HttpResponse, cookie: &Cookie<'_>) {
+    // `HttpResponse::add_cookie` does not percent-encode, and messages may
+    // contain characters that are not valid in a cookie value.
+    match HeaderValue::from_str(&cookie.encoded().to_string()) {
+        Ok(value) => {
+            response.headers_mut().append(SET_COOKIE, value);
+        }
+        Err(err) => tracing::warn!(error = ?err, "Failed to encode flash cookie"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_flash_round_trips_through_cookie() {
+        let flash = FlashMessage::error("Validation error: título; 100% required");
+
+        let mut response = HttpResponse::Ok().finish();
+        flash.attach(&mut response);
+        let cookie = response.cookies().next().expect("flash cookie set");
+
+        let req = TestRequest::default().cookie(cookie).to_http_request();
+        assert_eq!(FlashMessage::read(&req), Some(flash));
+    }
+
+    #[test]
+    fn test_clear_expires_cookie() {
+        let mut response = HttpResponse::Ok().finish();
+        FlashMessage::clear(&mut response);
+
+        let cookie = response.cookies().next().expect("removal cookie set");
+        assert_eq!(cookie.name(), FLASH_COOKIE);
+        assert_eq!(cookie.value(), "");
+        assert!(cookie.max_age().is_some_and(|age| age.is_zero()));
+    }
+}
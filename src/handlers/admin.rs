@@ -0,0 +1,286 @@
+use actix_web::{HttpRequest, HttpResponse, Result, get, http::header, put, web};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    auth::constant_time_eq,
+    config::settings::RetentionAction,
+    dto::MemoResponseDto,
+    error::{AppError, ErrorResponse},
+    handlers::negotiate::ApiBody,
+    services::MemoService,
+    state::AppState,
+};
+
+/// Memos listed in a retention preview.
+const RETENTION_PREVIEW_SAMPLE: u64 = 20;
+
+#[derive(Serialize, ToSchema)]
+pub struct MaintenanceStatus {
+    /// Whether maintenance mode is active
+    #[schema(example = false)]
+    enabled: bool,
+    /// `Retry-After` value sent to rejected clients, in seconds
+    #[schema(example = 300)]
+    retry_after_secs: u64,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetMaintenanceDto {
+    /// Turn maintenance mode on or off
+    #[schema(example = true)]
+    enabled: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RetentionPreviewQuery {
+    older_than_days: Option<u32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RetentionPreview {
+    /// What the purge job does with matching memos: `archive` or `delete`
+    #[schema(example = "archive")]
+    action: &'static str,
+    /// Completed memos last updated this many days ago or earlier match
+    #[schema(example = 90)]
+    older_than_days: u32,
+    /// Memos last updated before this instant match
+    #[schema(example = "2025-01-01T00:00:00Z")]
+    cutoff: DateTime<Utc>,
+    /// Number of memos a run now would purge
+    #[schema(example = 42)]
+    matching: u64,
+    /// The oldest matching memos, at most 20
+    oldest: Vec<MemoResponseDto>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RuntimeStats {
+    /// Worker threads of the runtime
+    #[schema(example = 8)]
+    workers: usize,
+    /// Spawned tasks that have not finished yet
+    #[schema(example = 12)]
+    alive_tasks: usize,
+    /// Tasks waiting in the shared queue for a free worker
+    #[schema(example = 0)]
+    global_queue_depth: usize,
+    /// Threads in the blocking pool; `null` unless built with `--cfg tokio_unstable`
+    #[schema(example = 2)]
+    blocking_threads: Option<usize>,
+    /// Blocking pool threads waiting for work; `null` unless built with `--cfg tokio_unstable`
+    #[schema(example = 1)]
+    idle_blocking_threads: Option<usize>,
+    /// Blocking calls waiting for a thread; `null` unless built with `--cfg tokio_unstable`
+    #[schema(example = 0)]
+    blocking_queue_depth: Option<usize>,
+}
+
+impl RuntimeStats {
+    fn of(runtime: &tokio::runtime::Handle) -> Self {
+        let metrics = runtime.metrics();
+        #[cfg(tokio_unstable)]
+        let (blocking_threads, idle_blocking_threads, blocking_queue_depth) = (
+            Some(metrics.num_blocking_threads()),
+            Some(metrics.num_idle_blocking_threads()),
+            Some(metrics.blocking_queue_depth()),
+        );
+        #[cfg(not(tokio_unstable))]
+        let (blocking_threads, idle_blocking_threads, blocking_queue_depth) = (None, None, None);
+
+        Self {
+            workers: metrics.num_workers(),
+            alive_tasks: metrics.num_alive_tasks(),
+            global_queue_depth: metrics.global_queue_depth(),
+            blocking_threads,
+            idle_blocking_threads,
+            blocking_queue_depth,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RuntimeDiagnostics {
+    /// The runtime running background work: jobs, the scheduler, queues
+    background: Option<RuntimeStats>,
+    /// The runtime of the HTTP worker that answered this request
+    http_worker: RuntimeStats,
+    /// Whether the build serves `tokio-console` (feature `tokio-console`)
+    #[schema(example = false)]
+    tokio_console: bool,
+}
+
+/// Checks the `Authorization: Bearer <ADMIN_TOKEN>` header.
+///
+/// Every admin request is rejected while `ADMIN_TOKEN` is unset.
+fn require_admin(req: &HttpRequest, state: &AppState) -> Result<(), AppError> {
+    let Some(expected) = state.config.admin.token.as_deref() else {
+        return Err(AppError::Unauthorized(
+            "Admin API is disabled (ADMIN_TOKEN is not set)".to_string(),
+        ));
+    };
+
+    let provided = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    if constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        Ok(())
+    } else {
+        tracing::warn!("Rejected admin request with missing or invalid token");
+        Err(AppError::Unauthorized(
+            "Missing or invalid admin token".to_string(),
+        ))
+    }
+}
+
+fn status(state: &AppState) -> MaintenanceStatus {
+    MaintenanceStatus {
+        enabled: state.maintenance.is_enabled(),
+        retry_after_secs: state.maintenance.retry_after_secs(),
+    }
+}
+
+/// Get maintenance mode status
+#[utoipa::path(
+    get,
+    path = "/admin/maintenance",
+    tag = "Admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Current maintenance mode status", body = MaintenanceStatus),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+    )
+)]
+#[get("/admin/maintenance")]
+#[tracing::instrument(name = "GET /admin/maintenance", skip(req, state))]
+pub async fn get_maintenance(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    require_admin(&req, &state)?;
+
+    Ok(HttpResponse::Ok().json(status(&state)))
+}
+
+/// Turn maintenance mode on or off
+///
+/// While enabled, all API and web routes except health, readiness, metrics and
+/// the admin API answer 503 with a `Retry-After` header.
+#[utoipa::path(
+    put,
+    path = "/admin/maintenance",
+    tag = "Admin",
+    security(("admin_token" = [])),
+    request_body = SetMaintenanceDto,
+    responses(
+        (status = 200, description = "Maintenance mode updated", body = MaintenanceStatus),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+    )
+)]
+#[put("/admin/maintenance")]
+#[tracing::instrument(name = "PUT /admin/maintenance", skip(req, state, body))]
+pub async fn set_maintenance(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: ApiBody<SetMaintenanceDto>,
+) -> Result<HttpResponse, AppError> {
+    require_admin(&req, &state)?;
+
+    state.maintenance.set_enabled(body.enabled);
+    tracing::warn!(enabled = body.enabled, "Maintenance mode changed");
+
+    Ok(HttpResponse::Ok().json(status(&state)))
+}
+
+/// Preview the retention purge
+///
+/// Dry run of the purge job: reports which completed memos it would archive or
+/// delete right now, without changing anything. `older_than_days` overrides
+/// `RETENTION_COMPLETED_DAYS` and is required when that is unset.
+#[utoipa::path(
+    get,
+    path = "/admin/retention/preview",
+    tag = "Admin",
+    security(("admin_token" = [])),
+    params(
+        ("older_than_days" = Option<u32>, Query, description = "Retention period in days (default: RETENTION_COMPLETED_DAYS)"),
+    ),
+    responses(
+        (status = 200, description = "Memos the purge job would process", body = RetentionPreview),
+        (status = 400, description = "No retention period configured or given", body = ErrorResponse),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+    )
+)]
+#[get("/admin/retention/preview")]
+#[tracing::instrument(name = "GET /admin/retention/preview", skip(req, state))]
+pub async fn preview_retention(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    query: web::Query<RetentionPreviewQuery>,
+) -> Result<HttpResponse, AppError> {
+    require_admin(&req, &state)?;
+
+    let retention = &state.config.retention;
+    let older_than_days = query
+        .older_than_days
+        .or(retention.completed_after_days)
+        .ok_or_else(|| {
+            AppError::Validation(
+                "RETENTION_COMPLETED_DAYS is not set; pass older_than_days".to_string(),
+            )
+        })?;
+    let cutoff = Utc::now() - Duration::days(i64::from(older_than_days));
+
+    let (matching, oldest) = MemoService::new(state.db.clone())
+        .preview_purge(
+            cutoff,
+            retention.action == RetentionAction::Delete,
+            RETENTION_PREVIEW_SAMPLE,
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok().json(RetentionPreview {
+        action: retention.action.as_str(),
+        older_than_days,
+        cutoff,
+        matching,
+        oldest,
+    }))
+}
+
+/// Inspect the async runtimes
+///
+/// Task counts and queue depths of the runtime running jobs and scheduled
+/// tasks, and of the HTTP worker answering, for telling a stuck future from a
+/// saturated runtime. Blocking pool figures need a `--cfg tokio_unstable`
+/// build, which `tokio-console` requires anyway.
+#[utoipa::path(
+    get,
+    path = "/debug/runtime",
+    tag = "Admin",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, description = "Runtime statistics", body = RuntimeDiagnostics),
+        (status = 401, description = "Missing or invalid admin token", body = ErrorResponse),
+    )
+)]
+#[get("/debug/runtime")]
+#[tracing::instrument(name = "GET /debug/runtime", skip(req, state))]
+pub async fn get_runtime_diagnostics(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    require_admin(&req, &state)?;
+
+    Ok(HttpResponse::Ok().json(RuntimeDiagnostics {
+        background: state.runtime.as_ref().map(RuntimeStats::of),
+        http_worker: RuntimeStats::of(&tokio::runtime::Handle::current()),
+        tokio_console: cfg!(feature = "tokio-console"),
+    }))
+}
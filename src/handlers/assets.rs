@@ -0,0 +1,61 @@
+use actix_web::{
+    HttpRequest, HttpResponse,
+    http::header::{CacheControl, CacheDirective, ETag, EntityTag, Header, IfNoneMatch},
+    route, web,
+};
+
+use crate::{
+    assets,
+    error::{AppError, ErrorCode},
+};
+
+/// One year, the conventional maximum for immutable assets.
+const IMMUTABLE_MAX_AGE: u32 = 31_536_000;
+
+/// Serve an embedded static file
+///
+/// Hashed file names (from `assets::asset_url`) are cached as immutable;
+/// plain names are revalidated with their `ETag` on every use.
+#[tracing::instrument(skip(req))]
+#[route("/static/{path:.*}", method = "GET", method = "HEAD")]
+pub async fn static_asset(
+    req: HttpRequest,
+    path: web::Path<String>,
+) -> Result<HttpResponse, AppError> {
+    let path = path.into_inner();
+    let asset = assets::get(&path).ok_or_else(|| {
+        AppError::not_found(
+            ErrorCode::ResourceNotFound,
+            format!("Asset {} not found", path),
+        )
+    })?;
+
+    let etag = EntityTag::new_strong(asset.hash);
+    let cache_control = if asset.fingerprinted {
+        CacheControl(vec![
+            CacheDirective::Public,
+            CacheDirective::MaxAge(IMMUTABLE_MAX_AGE),
+            CacheDirective::Extension("immutable".to_string(), None),
+        ])
+    } else {
+        CacheControl(vec![CacheDirective::NoCache])
+    };
+
+    let not_modified = match IfNoneMatch::parse(&req) {
+        Ok(IfNoneMatch::Any) => true,
+        Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(&etag)),
+        Err(_) => false,
+    };
+    if not_modified {
+        return Ok(HttpResponse::NotModified()
+            .insert_header(ETag(etag))
+            .insert_header(cache_control)
+            .finish());
+    }
+
+    Ok(HttpResponse::Ok()
+        .content_type(asset.content_type)
+        .insert_header(ETag(etag))
+        .insert_header(cache_control)
+        .body(asset.data.into_owned()))
+}
@@ -0,0 +1,142 @@
+use actix_web::{
+    HttpResponse, get,
+    http::header::{CacheControl, CacheDirective},
+    web,
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    auth::SignedRequest,
+    dto::{AttachmentUrlParams, SignedAttachmentUrlDto},
+    error::{AppError, ErrorResponse},
+    services::{AttachmentService, AttachmentVariant},
+    state::AppState,
+};
+
+/// Validity of signed URLs when the request doesn't ask for one, capped at
+/// `SIGNED_URL_MAX_TTL_SECS`.
+const DEFAULT_URL_TTL_SECS: u64 = 300;
+
+/// Get signed attachment URLs
+///
+/// URLs of the image and its thumbnail that work without auth headers until
+/// `expires_at`, e.g. for `<img>` tags or sharing.
+#[utoipa::path(
+    get,
+    path = "/api/v1/attachments/{id}/url",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Attachment ID"),
+        ("ttl" = Option<u64>, Query, description = "Seconds the URLs stay valid (default: 300, at most SIGNED_URL_MAX_TTL_SECS)"),
+    ),
+    responses(
+        (status = 200, description = "Signed URLs", body = SignedAttachmentUrlDto),
+        (status = 400, description = "ttl out of range", body = ErrorResponse),
+        (status = 404, description = "Attachment not found", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(name = "GET /api/v1/attachments/{id}/url", skip(state))]
+#[get("/api/v1/attachments/{id}/url")]
+pub async fn get_attachment_url(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    query: web::Query<AttachmentUrlParams>,
+) -> Result<HttpResponse, AppError> {
+    let max_ttl = state.url_signer.max_ttl().num_seconds() as u64;
+    let ttl = query.ttl.unwrap_or(DEFAULT_URL_TTL_SECS.min(max_ttl));
+    // Anything past the maximum is rejected alike, without overflowing
+    let ttl = Duration::seconds(ttl.min(max_ttl + 1) as i64);
+    let urls = AttachmentService::new(state.db.clone())
+        .signed_urls(id.into_inner(), &state.url_signer, ttl)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(urls))
+}
+
+/// Get an attachment
+///
+/// The image as uploaded. Needs a signed URL, from
+/// `GET /api/v1/attachments/{id}/url` or a rendered page.
+#[utoipa::path(
+    get,
+    path = "/web/attachments/{id}",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Attachment ID"),
+        ("expires" = i64, Query, description = "Expiry of the signed URL (Unix seconds)"),
+        ("signature" = String, Query, description = "Signature of the signed URL"),
+    ),
+    responses(
+        (status = 200, description = "The image", content_type = "image/*", body = Vec<u8>),
+        (status = 401, description = "Missing, invalid or expired signature", body = ErrorResponse),
+        (status = 404, description = "Attachment not found", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state, signed), fields(attachment_id = %id))]
+#[get("/web/attachments/{id}")]
+pub async fn get_attachment(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    signed: SignedRequest,
+) -> Result<HttpResponse, AppError> {
+    serve(&state, id.into_inner(), AttachmentVariant::Original, signed).await
+}
+
+/// Get an attachment thumbnail
+///
+/// JPEG of the image at most 320 pixels on its longest side. Needs a signed
+/// URL, like the image.
+#[utoipa::path(
+    get,
+    path = "/web/attachments/{id}/thumbnail",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Attachment ID"),
+        ("expires" = i64, Query, description = "Expiry of the signed URL (Unix seconds)"),
+        ("signature" = String, Query, description = "Signature of the signed URL"),
+    ),
+    responses(
+        (status = 200, description = "The thumbnail", content_type = "image/jpeg", body = Vec<u8>),
+        (status = 401, description = "Missing, invalid or expired signature", body = ErrorResponse),
+        (status = 404, description = "Attachment not found", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state, signed), fields(attachment_id = %id))]
+#[get("/web/attachments/{id}/thumbnail")]
+pub async fn get_attachment_thumbnail(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    signed: SignedRequest,
+) -> Result<HttpResponse, AppError> {
+    serve(
+        &state,
+        id.into_inner(),
+        AttachmentVariant::Thumbnail,
+        signed,
+    )
+    .await
+}
+
+async fn serve(
+    state: &AppState,
+    id: Uuid,
+    variant: AttachmentVariant,
+    signed: SignedRequest,
+) -> Result<HttpResponse, AppError> {
+    let (content_type, data) = AttachmentService::new(state.db.clone())
+        .get_content(id, variant)
+        .await?;
+
+    // Attachments never change once stored, so browsers may keep them for
+    // as long as the URL is valid.
+    let max_age = (signed.expires_at - Utc::now()).num_seconds().max(0) as u32;
+    Ok(HttpResponse::Ok()
+        .content_type(content_type)
+        .insert_header(CacheControl(vec![
+            CacheDirective::Private,
+            CacheDirective::MaxAge(max_age),
+            CacheDirective::Extension("immutable".to_string(), None),
+        ]))
+        .body(data))
+}
@@ -0,0 +1,70 @@
+use actix_web::{HttpResponse, get, web};
+use askama::Template;
+use utoipa::OpenApi;
+
+use crate::{
+    config::settings::DocsUi,
+    docs::{ApiDoc, ApiSection},
+    error::{AppError, ErrorCode},
+    state::AppState,
+};
+
+/// Where the OpenAPI document is served, for Swagger UI and `/docs` alike.
+pub const OPENAPI_JSON_PATH: &str = "/api-docs/openapi.json";
+
+#[derive(Template)]
+#[template(path = "pages/api_docs.html")]
+struct ApiDocsTemplate<'a> {
+    ui: DocsUi,
+    title: &'a str,
+    script_url: &'a str,
+    spec_url: &'static str,
+}
+
+/// Reference documentation rendered by ReDoc or RapiDoc (`API_DOCS_UI`)
+///
+/// Only mounted when a renderer is configured.
+#[get("/docs")]
+#[tracing::instrument(skip(state))]
+pub async fn api_docs(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let api = &state.config.api;
+    let ui = api.docs_ui.ok_or_else(|| {
+        AppError::not_found(
+            ErrorCode::ResourceNotFound,
+            "API documentation is disabled".to_string(),
+        )
+    })?;
+    let openapi = ApiDoc::openapi();
+    let template = ApiDocsTemplate {
+        ui,
+        title: &openapi.info.title,
+        script_url: &api.docs_script_url,
+        spec_url: OPENAPI_JSON_PATH,
+    };
+
+    match template.render() {
+        Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render API docs template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+/// The OpenAPI document, or one section of it by name, for `/docs` when
+/// Swagger UI (which serves them too) is off.
+#[get("/api-docs/{name}.json")]
+pub async fn openapi_json(name: web::Path<String>) -> Result<HttpResponse, AppError> {
+    let openapi = match name.as_str() {
+        "openapi" => ApiDoc::openapi(),
+        name => ApiSection::find(name)
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::ResourceNotFound,
+                    format!("No API document named '{}'", name),
+                )
+            })?
+            .openapi(),
+    };
+    Ok(HttpResponse::Ok().json(openapi))
+}
@@ -0,0 +1,118 @@
+use std::sync::LazyLock;
+
+use actix_web::{HttpRequest, HttpResponse, dev::ResourceDef, http::Method};
+use utoipa::OpenApi;
+
+use crate::{docs::ApiDoc, error::AppError};
+
+/// A documented path template and the methods it answers.
+struct DocumentedRoute {
+    def: ResourceDef,
+    dynamic: bool,
+    methods: Vec<Method>,
+}
+
+/// Route table taken from the OpenAPI document, which the contract tests keep
+/// in sync with the registered handlers.
+static ROUTES: LazyLock<Vec<DocumentedRoute>> = LazyLock::new(|| {
+    ApiDoc::openapi()
+        .paths
+        .paths
+        .into_iter()
+        .map(|(template, item)| {
+            let methods = [
+                (Method::GET, &item.get),
+                (Method::POST, &item.post),
+                (Method::PUT, &item.put),
+                (Method::PATCH, &item.patch),
+                (Method::DELETE, &item.delete),
+            ]
+            .into_iter()
+            .filter(|(_, operation)| operation.is_some())
+            .map(|(method, _)| method)
+            .collect();
+
+            DocumentedRoute {
+                dynamic: template.contains('{'),
+                def: ResourceDef::new(template),
+                methods,
+            }
+        })
+        .collect()
+});
+
+/// Methods documented for `path`. Literal templates win over dynamic ones, the
+/// same way `/api/v1/memos/stats` is registered ahead of `/api/v1/memos/{id}`.
+fn allowed_methods(path: &str) -> Vec<Method> {
+    let matching: Vec<&DocumentedRoute> = ROUTES
+        .iter()
+        .filter(|route| route.def.is_match(path))
+        .collect();
+    let literal: Vec<&DocumentedRoute> = matching
+        .iter()
+        .copied()
+        .filter(|route| !route.dynamic)
+        .collect();
+    let winners = if literal.is_empty() {
+        matching
+    } else {
+        literal
+    };
+
+    let mut methods: Vec<Method> = winners
+        .into_iter()
+        .flat_map(|route| route.methods.iter().cloned())
+        .collect();
+    methods.sort_by_key(|method| method.to_string());
+    methods.dedup();
+    methods
+}
+
+/// Default service: answers unmatched requests with the standard JSON error
+/// body, 405 plus `Allow` when the path exists under other methods.
+pub async fn fallback(req: HttpRequest) -> Result<HttpResponse, AppError> {
+    let allowed = allowed_methods(req.path());
+    if allowed.is_empty() || allowed.contains(req.method()) {
+        return Err(AppError::RouteNotFound(format!(
+            "{} {}",
+            req.method(),
+            req.path()
+        )));
+    }
+
+    Err(AppError::MethodNotAllowed {
+        method: req.method().to_string(),
+        allowed: allowed.iter().map(Method::to_string).collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_methods_for_collection_and_item() {
+        assert_eq!(
+            allowed_methods("/api/v1/memos"),
+            vec![Method::GET, Method::POST]
+        );
+        assert_eq!(
+            allowed_methods("/api/v1/memos/550e8400-e29b-41d4-a716-446655440000"),
+            vec![Method::DELETE, Method::GET, Method::PATCH, Method::PUT]
+        );
+    }
+
+    #[test]
+    fn test_literal_route_shadows_template() {
+        assert_eq!(allowed_methods("/api/v1/memos/stats"), vec![Method::GET]);
+        assert_eq!(
+            allowed_methods("/api/v1/memos/complete"),
+            vec![Method::PATCH]
+        );
+    }
+
+    #[test]
+    fn test_unknown_path_has_no_methods() {
+        assert!(allowed_methods("/api/v1/nothing-here").is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{error::AppError, state::AppState};
+use crate::{db::CircuitState, error::AppError, state::AppState};
 use actix_web::{HttpResponse, Result, get, web};
 use serde::Serialize;
 use utoipa::ToSchema;
@@ -6,70 +6,106 @@ use utoipa::ToSchema;
 #[derive(Serialize, ToSchema)]
 pub struct HealthResponse {
     /// Service health status
+    #[schema(example = "healthy")]
     status: String,
-    /// Database connection status
-    database: String,
     /// Application version
+    #[schema(example = "0.1.0")]
     version: String,
     /// Service uptime in seconds
+    #[schema(example = 3600)]
     uptime_seconds: u64,
+    /// Database circuit breaker state; `open` means database requests fail fast
+    #[schema(example = "closed")]
+    database_circuit: CircuitState,
 }
 
 #[derive(Serialize, ToSchema)]
 pub struct ReadyResponse {
     /// Service readiness status
+    #[schema(example = true)]
     ready: bool,
+    /// Migrations the database is missing; non-empty means not ready
+    #[schema(example = json!([]))]
+    pending_migrations: Vec<String>,
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct StartupResponse {
+    /// `started`, or `starting` while steps are pending
+    #[schema(example = "started")]
+    status: String,
+    /// Startup steps still in progress: `migrations`, `scheduler`
+    #[schema(example = json!([]))]
+    pending: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct VersionResponse {
+    /// Application version
+    #[schema(example = "0.1.0")]
+    version: String,
+    /// Git commit the binary was built from, when `GIT_COMMIT` was set at build time
+    #[schema(example = "1b217f5")]
+    commit: Option<String>,
+    /// Deployment environment (`APP_ENV`)
+    #[schema(example = "production")]
+    environment: String,
+}
+
+/// Liveness probe
+///
+/// Answers as long as the process serves requests; it checks no dependency,
+/// so a database outage doesn't get the instance restarted. Use `/ready` to
+/// take it out of rotation instead.
 #[utoipa::path(
     get,
     path = "/health",
     tag = "Observability",
     responses(
-        (status = 200, description = "Service is healthy", body = HealthResponse),
+        (status = 200, description = "Service is alive", body = HealthResponse),
     )
 )]
 #[get("/health")]
 #[tracing::instrument(name = "GET /health", skip(state))]
-pub async fn health(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
-    let db_status = match state.db.ping().await {
-        Ok(_) => "connected",
-        Err(_) => "disconnected",
-    };
-
-    tracing::debug!(
-        database_status = db_status,
-        uptime = state.uptime_seconds(),
-        "Health check performed"
-    );
-
-    let response = HealthResponse {
+pub async fn health(state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(HealthResponse {
         status: "healthy".to_string(),
-        database: db_status.to_string(),
         version: state.config.app.version.clone(),
         uptime_seconds: state.uptime_seconds(),
-    };
-
-    Ok(HttpResponse::Ok().json(response))
+        database_circuit: state.db.breaker().state(),
+    })
 }
 
+/// Readiness probe
+///
+/// Ready when the database answers and has every migration this build expects.
+/// Like `/health`, reuses its check for `HEALTH_CACHE_TTL_MS`.
 #[utoipa::path(
     get,
     path = "/ready",
     tag = "Observability",
     responses(
         (status = 200, description = "Service is ready", body = ReadyResponse),
-        (status = 503, description = "Service is not ready", body = ReadyResponse),
+        (status = 503, description = "Database unreachable or migrations pending", body = ReadyResponse),
     )
 )]
 #[get("/ready")]
 #[tracing::instrument(name = "GET /ready", skip(state))]
 pub async fn ready(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
-    let is_ready = state.db.ping().await.is_ok();
+    let (is_ready, pending_migrations) = match state.health.pending_migrations(&state.db).await {
+        Some(pending) => (pending.is_empty(), pending),
+        None => (false, Vec::new()),
+    };
 
+    if !pending_migrations.is_empty() {
+        tracing::warn!(pending = ?pending_migrations, "Database schema is out of date");
+    }
     tracing::debug!(ready = is_ready, "Readiness check performed");
 
-    let response = ReadyResponse { ready: is_ready };
+    let response = ReadyResponse {
+        ready: is_ready,
+        pending_migrations,
+    };
 
     if is_ready {
         Ok(HttpResponse::Ok().json(response))
@@ -77,3 +113,60 @@ pub async fn ready(state: web::Data<AppState>) -> Result<HttpResponse, AppError>
         Ok(HttpResponse::ServiceUnavailable().json(response))
     }
 }
+
+/// Startup probe
+///
+/// `starting` with `503` until the database has every migration this build
+/// expects and the scheduler is running (when enabled), then `started` for
+/// good. Point a Kubernetes `startupProbe` here so liveness checks only begin
+/// once the instance is up.
+#[utoipa::path(
+    get,
+    path = "/startup",
+    tag = "Observability",
+    responses(
+        (status = 200, description = "Startup finished", body = StartupResponse),
+        (status = 503, description = "Still starting", body = StartupResponse),
+    )
+)]
+#[get("/startup")]
+#[tracing::instrument(name = "GET /startup", skip(state))]
+pub async fn startup(state: web::Data<AppState>) -> HttpResponse {
+    let pending = state.startup.pending(&state.health, &state.db).await;
+    tracing::debug!(?pending, "Startup check performed");
+
+    if pending.is_empty() {
+        HttpResponse::Ok().json(StartupResponse {
+            status: "started".to_string(),
+            pending: Vec::new(),
+        })
+    } else {
+        HttpResponse::ServiceUnavailable().json(StartupResponse {
+            status: "starting".to_string(),
+            pending: pending.into_iter().map(str::to_string).collect(),
+        })
+    }
+}
+
+/// Build information
+///
+/// Version and commit of the running binary, for checking what a deploy rolled out.
+#[utoipa::path(
+    get,
+    path = "/version",
+    tag = "Observability",
+    responses(
+        (status = 200, description = "Build information", body = VersionResponse),
+    )
+)]
+#[get("/version")]
+#[tracing::instrument(name = "GET /version", skip(state))]
+pub async fn version(state: web::Data<AppState>) -> HttpResponse {
+    HttpResponse::Ok().json(VersionResponse {
+        version: state.config.app.version.clone(),
+        commit: option_env!("GIT_COMMIT")
+            .filter(|commit| !commit.is_empty())
+            .map(str::to_string),
+        environment: state.config.app.env.as_str().to_string(),
+    })
+}
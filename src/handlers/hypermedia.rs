@@ -0,0 +1,42 @@
+//! Hypermedia links in response bodies, resolved from the app's named routes
+//! with [`HttpRequest::url_for`] so they follow the route configuration.
+//! A route that is not registered simply yields no link.
+
+use actix_web::HttpRequest;
+
+use crate::dto::{Link, MemoLinks, MemoResponseDto, PaginatedResponse};
+
+/// A response body that carries links to itself and its actions.
+pub trait Hypermedia {
+    fn add_links(&mut self, req: &HttpRequest);
+}
+
+impl Hypermedia for MemoResponseDto {
+    fn add_links(&mut self, req: &HttpRequest) {
+        let id = self.id.to_string();
+        let link = |route: &str, method: &str| {
+            req.url_for(route, [&id]).ok().map(|url| Link {
+                href: url.to_string(),
+                method: method.to_string(),
+            })
+        };
+
+        // Handler names double as route names.
+        let Some(self_) = link("get_memo", "GET") else {
+            return;
+        };
+        self.links = Some(MemoLinks {
+            self_,
+            complete: link("toggle_complete", "PATCH").filter(|_| !self.completed),
+            delete: link("delete_memo", "DELETE"),
+        });
+    }
+}
+
+impl<T: Hypermedia> Hypermedia for PaginatedResponse<T> {
+    fn add_links(&mut self, req: &HttpRequest) {
+        for item in &mut self.data {
+            item.add_links(req);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use actix_web::{HttpRequest, HttpResponse, get, post, web};
+use uuid::Uuid;
+
+use crate::{
+    dto::{CreateMemoParams, MemoImportFailureDto, OperationDto},
+    error::{AppError, ErrorCode, ErrorResponse},
+    handlers::operations,
+    jobs::{ImportMemosInput, OperationKind},
+    repository::{MemoImportRepository, OperationRepository},
+    state::AppState,
+};
+
+/// Import memos in the background
+///
+/// Upload a file with one memo per line, each a JSON object shaped like the body of
+/// `POST /api/v1/memos`; blank lines are skipped and `GET /api/v1/memos/export` output can be
+/// uploaded as is. The file is stored and imported by a background job in chunks of 500 lines,
+/// each in its own transaction. Poll the operation at `Location` for progress; its result counts
+/// the memos imported and the lines that failed, which are listed by
+/// `GET /api/v1/memos/import/{operation_id}/failures`. The body may be as large as
+/// `UPLOAD_BODY_LIMIT`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/memos/import/async",
+    tag = "memos",
+    params(
+        ("historical" = Option<bool>, Query, description = "Allow due dates in the past even when future due dates are required (default: false)")
+    ),
+    request_body(content = String, content_type = "application/x-ndjson", description = "One memo JSON object per line"),
+    responses(
+        (status = 202, description = "Import accepted; poll the operation at `Location`", body = OperationDto),
+        (status = 400, description = "Empty file or not UTF-8", body = ErrorResponse),
+        (status = 413, description = "File larger than the upload limit", body = ErrorResponse),
+        (status = 404, description = "Background jobs are not running on this instance", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(req, state, body), fields(bytes = body.len()))]
+#[post("/api/v1/memos/import/async")]
+pub async fn import_memos_async(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    params: web::Query<CreateMemoParams>,
+    body: web::Bytes,
+) -> Result<HttpResponse, AppError> {
+    let jobs = state.jobs.as_ref().ok_or_else(|| {
+        AppError::FeatureDisabled("Background jobs are not running on this instance".to_string())
+    })?;
+
+    let content = String::from_utf8(body.to_vec())
+        .map_err(|_| AppError::Validation("Import file must be UTF-8 text".to_string()))?;
+    let total = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .count();
+    if total == 0 {
+        return Err(AppError::Validation("Import file has no memos".to_string()));
+    }
+
+    let upload = MemoImportRepository::create(&state.db, content).await?;
+    let input = ImportMemosInput {
+        import_id: upload.id,
+        require_future_due_dates: state.config.api.require_future_due_dates && !params.historical,
+    };
+    let operation = jobs
+        .submit(OperationKind::ImportMemos, &input, Some(total))
+        .await?;
+
+    Ok(operations::accepted(&req, operation))
+}
+
+/// List failed import lines
+///
+/// Lines of an import file that did not become memos, in file order, with the error each one hit.
+/// Failures are kept as long as the operation.
+#[utoipa::path(
+    get,
+    path = "/api/v1/memos/import/{operation_id}/failures",
+    tag = "memos",
+    params(
+        ("operation_id" = Uuid, Path, description = "Operation ID returned by the import")
+    ),
+    responses(
+        (status = 200, description = "Failed lines, possibly none", body = Vec<MemoImportFailureDto>),
+        (status = 404, description = "No import with this operation ID", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state), fields(operation_id = %operation_id))]
+#[get("/api/v1/memos/import/{operation_id}/failures")]
+pub async fn list_import_failures(
+    state: web::Data<AppState>,
+    operation_id: web::Path<Uuid>,
+) -> Result<web::Json<Vec<MemoImportFailureDto>>, AppError> {
+    let operation_id = operation_id.into_inner();
+    OperationRepository::find_by_id(&state.db, operation_id)
+        .await?
+        .filter(|operation| operation.kind == OperationKind::ImportMemos.as_str())
+        .ok_or_else(|| {
+            AppError::not_found(
+                ErrorCode::ImportNotFound,
+                format!("Import with operation id {} not found", operation_id),
+            )
+        })?;
+
+    let failures = MemoImportRepository::find_failures(&state.db, operation_id).await?;
+
+    Ok(web::Json(
+        failures
+            .into_iter()
+            .map(MemoImportFailureDto::from)
+            .collect(),
+    ))
+}
@@ -0,0 +1,86 @@
+use actix_web::{HttpResponse, Result, delete, get, post, web};
+use uuid::Uuid;
+
+use crate::{
+    dto::{CreateWebhookIntegrationDto, WebhookIntegrationDto},
+    error::{AppError, ErrorResponse},
+    handlers::negotiate::ApiBody,
+    services::IntegrationService,
+    state::AppState,
+};
+
+/// List webhook integrations
+///
+/// URLs are returned masked; the full URL is a credential.
+#[utoipa::path(
+    get,
+    path = "/api/v1/integrations/webhooks",
+    tag = "integrations",
+    responses(
+        (status = 200, description = "Registered webhooks", body = Vec<WebhookIntegrationDto>),
+    )
+)]
+#[get("/api/v1/integrations/webhooks")]
+#[tracing::instrument(name = "GET /api/v1/integrations/webhooks", skip(state))]
+pub async fn list_webhook_integrations(
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let integrations = IntegrationService::new(state.db.clone())
+        .list_webhooks()
+        .await?;
+
+    Ok(HttpResponse::Ok().json(integrations))
+}
+
+/// Register a Slack or Discord webhook
+///
+/// The URL must be an incoming-webhook URL issued by the chosen provider
+/// (`https://hooks.slack.com/services/...` or `https://discord.com/api/webhooks/...`).
+#[utoipa::path(
+    post,
+    path = "/api/v1/integrations/webhooks",
+    tag = "integrations",
+    request_body = CreateWebhookIntegrationDto,
+    responses(
+        (status = 201, description = "Webhook registered", body = WebhookIntegrationDto),
+        (status = 400, description = "Invalid provider, URL or events", body = ErrorResponse),
+    )
+)]
+#[post("/api/v1/integrations/webhooks")]
+#[tracing::instrument(name = "POST /api/v1/integrations/webhooks", skip(state, body))]
+pub async fn create_webhook_integration(
+    state: web::Data<AppState>,
+    body: ApiBody<CreateWebhookIntegrationDto>,
+) -> Result<HttpResponse, AppError> {
+    let integration = IntegrationService::new(state.db.clone())
+        .create_webhook(body.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Created().json(integration))
+}
+
+/// Remove a webhook integration
+#[utoipa::path(
+    delete,
+    path = "/api/v1/integrations/webhooks/{id}",
+    tag = "integrations",
+    params(
+        ("id" = Uuid, Path, description = "Integration ID")
+    ),
+    responses(
+        (status = 204, description = "Webhook removed"),
+        (status = 404, description = "Integration not found", body = ErrorResponse),
+    )
+)]
+#[delete("/api/v1/integrations/webhooks/{id}")]
+#[tracing::instrument(name = "DELETE /api/v1/integrations/webhooks/{id}", skip(state))]
+pub async fn delete_webhook_integration(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    IntegrationService::new(state.db.clone())
+        .delete_webhook(id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,200 @@
+//! [JSON:API](https://jsonapi.org) rendering of the response DTOs.
+//!
+//! [`Negotiated`](super::negotiate::Negotiated) responses opted in with
+//! `.json_api()` are rendered as JSON:API documents when the `Accept` header
+//! asks for `application/vnd.api+json`. The DTOs stay as they are: their
+//! serialized fields become the resource's `attributes`, except the id and the
+//! fields listed as relationships.
+
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::collections::BTreeMap;
+
+use crate::dto::{MemoResponseDto, PaginatedResponse};
+
+pub const MEDIA_TYPE: &str = "application/vnd.api+json";
+
+const VERSION: &str = "1.1";
+
+/// A DTO rendered as a JSON:API resource object.
+pub trait Resource: Serialize {
+    /// Resource type, e.g. `memos`
+    const TYPE: &'static str;
+
+    fn id(&self) -> String;
+
+    /// To-many relationships by field name, as `(type, id)` linkage. These
+    /// fields are left out of `attributes`.
+    fn relationships(&self) -> Vec<(&'static str, &'static str, Vec<String>)> {
+        Vec::new()
+    }
+
+    /// URL of the resource itself
+    fn self_link(&self) -> Option<String> {
+        None
+    }
+
+    fn to_resource(&self) -> serde_json::Result<Value> {
+        let relationships = self.relationships();
+        let mut attributes = match serde_json::to_value(self)? {
+            Value::Object(map) => map,
+            other => Map::from_iter([("value".to_string(), other)]),
+        };
+        // Both are reserved member names in JSON:API resources.
+        attributes.remove("id");
+        attributes.remove("links");
+        for (name, _, _) in &relationships {
+            attributes.remove(*name);
+        }
+
+        let mut resource = json!({
+            "type": Self::TYPE,
+            "id": self.id(),
+            "attributes": attributes,
+        });
+        if !relationships.is_empty() {
+            resource["relationships"] = relationships
+                .into_iter()
+                .map(|(name, kind, ids)| {
+                    let data: Vec<Value> = ids
+                        .into_iter()
+                        .map(|id| json!({ "type": kind, "id": id }))
+                        .collect();
+                    (name.to_string(), json!({ "data": data }))
+                })
+                .collect::<Map<_, _>>()
+                .into();
+        }
+        if let Some(link) = self.self_link() {
+            resource["links"] = json!({ "self": link });
+        }
+        Ok(resource)
+    }
+}
+
+/// A response body rendered as a JSON:API top-level document.
+pub trait Document {
+    /// The document, with `links` (at least `self`) as its top-level links.
+    fn to_document(&self, links: &BTreeMap<&'static str, String>) -> serde_json::Result<Value>;
+}
+
+impl<T: Resource> Document for T {
+    fn to_document(&self, links: &BTreeMap<&'static str, String>) -> serde_json::Result<Value> {
+        Ok(json!({
+            "jsonapi": { "version": VERSION },
+            "data": self.to_resource()?,
+            "links": links,
+        }))
+    }
+}
+
+/// A collection; the pagination fields go into `meta`.
+impl<T: Resource> Document for PaginatedResponse<T> {
+    fn to_document(&self, links: &BTreeMap<&'static str, String>) -> serde_json::Result<Value> {
+        let data = self
+            .data
+            .iter()
+            .map(Resource::to_resource)
+            .collect::<serde_json::Result<Vec<_>>>()?;
+        let mut meta = serde_json::to_value(self)?;
+        if let Some(meta) = meta.as_object_mut() {
+            meta.remove("data");
+        }
+
+        Ok(json!({
+            "jsonapi": { "version": VERSION },
+            "data": data,
+            "meta": meta,
+            "links": links,
+        }))
+    }
+}
+
+impl Resource for MemoResponseDto {
+    const TYPE: &'static str = "memos";
+
+    fn id(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn relationships(&self) -> Vec<(&'static str, &'static str, Vec<String>)> {
+        let ids = |ids: &[uuid::Uuid]| ids.iter().map(ToString::to_string).collect();
+        vec![
+            ("blocked_by", Self::TYPE, ids(&self.blocked_by)),
+            ("blocks", Self::TYPE, ids(&self.blocks)),
+        ]
+    }
+
+    fn self_link(&self) -> Option<String> {
+        Some(match &self.links {
+            Some(links) => links.self_.href.clone(),
+            None => format!("/api/v1/memos/{}", self.id),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn memo() -> MemoResponseDto {
+        MemoResponseDto {
+            id: Uuid::nil(),
+            title: "Pay rent".to_string(),
+            description: None,
+            description_html: None,
+            date_to: Utc::now(),
+            timezone: "UTC".to_string(),
+            completed: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+            position: 0,
+            version: 1,
+            archived: false,
+            pinned: false,
+            starred: false,
+            color: None,
+            completed_at: None,
+            completion_note: None,
+            blocked_by: vec![Uuid::max()],
+            blocks: vec![],
+            attachments: vec![],
+            links: None,
+        }
+    }
+
+    #[test]
+    fn test_memo_resource_splits_attributes_and_relationships() {
+        let resource = memo().to_resource().unwrap();
+
+        assert_eq!(resource["type"], "memos");
+        assert_eq!(resource["id"], Uuid::nil().to_string());
+        assert_eq!(resource["attributes"]["title"], "Pay rent");
+        assert!(resource["attributes"].get("id").is_none());
+        assert!(resource["attributes"].get("blocked_by").is_none());
+        assert_eq!(
+            resource["relationships"]["blocked_by"]["data"],
+            json!([{ "type": "memos", "id": Uuid::max().to_string() }])
+        );
+        assert_eq!(resource["relationships"]["blocks"]["data"], json!([]));
+        assert_eq!(
+            resource["links"]["self"],
+            format!("/api/v1/memos/{}", Uuid::nil())
+        );
+    }
+
+    #[test]
+    fn test_collections_move_pagination_into_meta() {
+        let page = PaginatedResponse::new(vec![memo()], 11, 10, 10);
+        let links = BTreeMap::from([("self", "http://localhost/api/v1/memos".to_string())]);
+        let document = page.to_document(&links).unwrap();
+
+        assert_eq!(document["jsonapi"]["version"], VERSION);
+        assert_eq!(document["data"].as_array().unwrap().len(), 1);
+        assert_eq!(document["meta"]["total"], 11);
+        assert!(document["meta"].get("data").is_none());
+        assert_eq!(document["links"]["self"], "http://localhost/api/v1/memos");
+    }
+}
@@ -1,21 +1,45 @@
 use actix_web::{
-    HttpResponse, Responder, delete, error::ResponseError, get, patch, post, put, web,
+    HttpRequest, HttpResponse, Responder, delete,
+    error::ResponseError,
+    get,
+    http::{
+        StatusCode,
+        header::{self, ContentEncoding, ETag, EntityTag, IfMatch},
+    },
+    patch, post, put,
+    web::{self, Bytes},
 };
+use futures_util::StreamExt;
 use uuid::Uuid;
+use validator::Validate;
 
 use crate::{
     dto::{
-        CreateMemoDto, MemoResponseDto, PaginatedMemoResponse, PaginationParams, PatchMemoDto,
-        UpdateMemoDto,
+        BatchCompleteDto, BatchCompleteResponse, BatchDeleteDto, CompleteMemoDto, CountMode,
+        CreateMemoDto, CreateMemoParams, DeleteMemosResponse, DuplicateMemoParams, ExportFormat,
+        ExportParams, LinkBlockerDto, MemoRenderParams, MemoResponseDto, MemoSnoozeDto,
+        MemoStatsDto, MoveMemoDto, OperationDto, PaginatedMemoResponse, PaginationParams,
+        PatchMemoDto, SnoozeMemoDto, UndoResponse, UpdateMemoDto,
+    },
+    error::{AppError, ErrorResponse},
+    handlers::{
+        negotiate::{ApiBody, Negotiated, OptionalApiBody, PREFER_RETURN_MINIMAL, prefers},
+        operations,
     },
-    error::ErrorResponse,
+    integrations::{WebhookEvent, WebhookMessage},
+    jobs::OperationKind,
     services::MemoService,
     state::AppState,
 };
 
+/// Length of the created-per-day series returned by the stats endpoint.
+pub const STATS_DAYS: u32 = 30;
+
 /// List all memos
 ///
-/// Retrieve a paginated list of memos with optional filtering by completion status and sorting by various fields
+/// Retrieve a paginated list of memos with optional filtering by completion status and sorting by various fields.
+/// The `Link` header carries `first`, `prev`, `next` and `last` page URLs.
+/// Responds with XML, YAML, MessagePack or CBOR instead of JSON when the `Accept` header asks for it.
 #[utoipa::path(
     get,
     path = "/api/v1/memos",
@@ -24,32 +48,66 @@ use crate::{
         ("limit" = Option<u64>, Query, description = "Number of items per page (1-100, default: 10)"),
         ("offset" = Option<u64>, Query, description = "Number of items to skip (default: 0)"),
         ("completed" = Option<bool>, Query, description = "Filter by completion status"),
-        ("sort_by" = Option<String>, Query, description = "Field to sort by (created_at, title, date_to, completed, updated_at)"),
-        ("order" = Option<String>, Query, description = "Sort order (asc or desc, default: desc)")
+        ("overdue" = Option<bool>, Query, description = "Filter to incomplete memos past their due date (true) or exclude them (false)"),
+        ("sort_by" = Option<String>, Query, description = "Field to sort by (created_at, title, date_to, completed, updated_at, position, starred); pinned memos always come first"),
+        ("order" = Option<String>, Query, description = "Sort order (asc or desc, default: desc)"),
+        ("q" = Option<String>, Query, description = "Case-insensitive search in title and description (max 100 characters)"),
+        ("archived" = Option<bool>, Query, description = "List archived memos instead of unarchived ones (default: false)"),
+        ("pinned" = Option<bool>, Query, description = "Filter by pinned status"),
+        ("starred" = Option<bool>, Query, description = "Filter by starred status"),
+        ("color" = Option<String>, Query, description = "Filter by color label (red, orange, yellow, green, blue, purple or gray)"),
+        ("count" = Option<CountMode>, Query, description = "How to compute `total`: exact (default), estimated (planner statistics, ignores filters) or none (skip the count; `false` also works)"),
+        ("description_html" = Option<bool>, Query, description = "Add `description_html`, the Markdown description rendered to sanitized HTML (default: false)")
     ),
     responses(
-        (status = 200, description = "List of memos retrieved successfully", body = PaginatedMemoResponse),
+        (status = 200, description = "List of memos retrieved successfully",
+            content(
+                (PaginatedMemoResponse = "application/json"),
+                (PaginatedMemoResponse = "application/xml"),
+                (PaginatedMemoResponse = "application/yaml"),
+                (PaginatedMemoResponse = "application/msgpack"),
+                (PaginatedMemoResponse = "application/cbor")
+            ),
+            headers(("Link" = String, description = "RFC 8288 pagination links (first, prev, next, last)"))),
         (status = 400, description = "Invalid query parameters", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     )
 )]
-#[tracing::instrument(skip(state, params), fields(limit, offset, completed, sort_by, order))]
+#[tracing::instrument(
+    skip(req, state, params, render),
+    fields(limit, offset, completed, sort_by, order)
+)]
 #[get("/api/v1/memos")]
 pub async fn list_memos(
+    req: HttpRequest,
     state: web::Data<AppState>,
     params: web::Query<PaginationParams>,
+    render: web::Query<MemoRenderParams>,
 ) -> impl Responder {
     tracing::debug!("Listing memos with pagination");
 
-    let service = MemoService::new(state.db.clone());
+    let service = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .with_replicas(state.replicas.clone());
     match service.get_all_memos(params.into_inner()).await {
-        Ok(response) => {
+        Ok(mut response) => {
+            response.data.iter_mut().for_each(|memo| render.apply(memo));
             tracing::info!(
                 count = response.data.len(),
                 total = response.total,
                 "Memos listed successfully"
             );
-            HttpResponse::Ok().json(response)
+            let url = req.full_url();
+            let link = response.link_header(&url);
+            let links = response.page_links(&url);
+            Negotiated::new("memos", response)
+                .json_api()
+                .hypermedia()
+                .with_links(links)
+                .customize()
+                .insert_header((header::LINK, link))
+                .respond_to(&req)
+                .map_into_boxed_body()
         }
         Err(e) => {
             tracing::error!(error = %e, "Failed to list memos");
@@ -58,32 +116,138 @@ pub async fn list_memos(
     }
 }
 
+/// Memo statistics
+///
+/// Counts by completion status, overdue count, and memos created per day over the last 30 days
+#[utoipa::path(
+    get,
+    path = "/api/v1/memos/stats",
+    tag = "memos",
+    responses(
+        (status = 200, description = "Memo statistics", body = MemoStatsDto),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state))]
+#[get("/api/v1/memos/stats")]
+pub async fn memo_stats(state: web::Data<AppState>) -> Result<Negotiated<MemoStatsDto>, AppError> {
+    let stats = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .get_stats(STATS_DAYS)
+        .await?;
+
+    Ok(Negotiated::new("stats", stats))
+}
+
+/// Memos per chunk of the export stream.
+pub const EXPORT_BATCH_SIZE: u64 = 500;
+
+/// Export all memos
+///
+/// Streams every memo, archived ones included, oldest first, as newline-delimited JSON: one object
+/// per line, shaped like `GET /api/v1/memos/{id}`. Rows are read from a database cursor while the
+/// response is written, so large exports use constant memory. A response that stops before the
+/// final newline was cut short by an error.
+#[utoipa::path(
+    get,
+    path = "/api/v1/memos/export",
+    tag = "memos",
+    params(
+        ("format" = Option<ExportFormat>, Query, description = "Output format (default: ndjson)")
+    ),
+    responses(
+        (status = 200, description = "One memo per line", content_type = "application/x-ndjson", body = String),
+        (status = 400, description = "Unsupported format", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state, params))]
+#[get("/api/v1/memos/export")]
+pub async fn export_memos(
+    state: web::Data<AppState>,
+    params: web::Query<ExportParams>,
+) -> Result<HttpResponse, AppError> {
+    let ExportFormat::Ndjson = params.format.unwrap_or_default();
+
+    let batches = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .export_memos(EXPORT_BATCH_SIZE)
+        .await?;
+    let lines = batches.map(|batch| {
+        let mut chunk = Vec::new();
+        for memo in batch.map_err(|e| {
+            tracing::error!(error = %e, "Memo export failed");
+            actix_web::Error::from(e)
+        })? {
+            serde_json::to_writer(&mut chunk, &memo)?;
+            chunk.push(b'\n');
+        }
+        Ok::<_, actix_web::Error>(Bytes::from(chunk))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            "attachment; filename=\"memos.ndjson\"",
+        ))
+        // Compressing would buffer the stream.
+        .insert_header(ContentEncoding::Identity)
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(lines))
+}
+
 /// Get a memo by ID
 ///
-/// Retrieve a single memo by its unique identifier
+/// Retrieve a single memo by its unique identifier. Responds with XML, YAML,
+/// MessagePack or CBOR instead of JSON when the `Accept` header asks for it.
 #[utoipa::path(
     get,
     path = "/api/v1/memos/{id}",
     tag = "memos",
     params(
-        ("id" = Uuid, Path, description = "Memo ID")
+        ("id" = Uuid, Path, description = "Memo ID"),
+        ("description_html" = Option<bool>, Query, description = "Add `description_html`, the Markdown description rendered to sanitized HTML (default: false)")
     ),
     responses(
-        (status = 200, description = "Memo retrieved successfully", body = MemoResponseDto),
+        (status = 200, description = "Memo retrieved successfully",
+            content(
+                (MemoResponseDto = "application/json"),
+                (MemoResponseDto = "application/xml"),
+                (MemoResponseDto = "application/yaml"),
+                (MemoResponseDto = "application/msgpack"),
+                (MemoResponseDto = "application/cbor")
+            ),
+            headers(("ETag" = String, description = "Current memo version"))),
         (status = 404, description = "Memo not found", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     )
 )]
-#[tracing::instrument(skip(state), fields(memo_id = %id))]
+#[tracing::instrument(skip(req, state, render), fields(memo_id = %id))]
 #[get("/api/v1/memos/{id}")]
-pub async fn get_memo(state: web::Data<AppState>, id: web::Path<Uuid>) -> impl Responder {
+pub async fn get_memo(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    render: web::Query<MemoRenderParams>,
+) -> impl Responder {
     tracing::debug!("Getting memo by ID");
 
-    let service = MemoService::new(state.db.clone());
+    let service = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .with_replicas(state.replicas.clone());
     match service.get_memo_by_id(id.into_inner()).await {
-        Ok(memo) => {
+        Ok(mut memo) => {
+            render.apply(&mut memo);
             tracing::info!("Memo retrieved successfully");
-            HttpResponse::Ok().json(memo)
+            let etag = version_etag(memo.version);
+            Negotiated::new("memo", memo)
+                .json_api()
+                .hypermedia()
+                .customize()
+                .insert_header(etag)
+                .respond_to(&req)
+                .map_into_boxed_body()
         }
         Err(e) => {
             tracing::error!(error = %e, "Failed to get memo");
@@ -94,11 +258,16 @@ pub async fn get_memo(state: web::Data<AppState>, id: web::Path<Uuid>) -> impl R
 
 /// Create a new memo
 ///
-/// Create a new memo with title, optional description, and due date
+/// Create a new memo with title, optional description, and due date. With
+/// `REQUIRE_FUTURE_DUE_DATES` on, the due date must not have passed unless the
+/// memo is imported with `historical=true`.
 #[utoipa::path(
     post,
     path = "/api/v1/memos",
     tag = "memos",
+    params(
+        ("historical" = Option<bool>, Query, description = "Allow a due date in the past even when future due dates are required (default: false)")
+    ),
     request_body = CreateMemoDto,
     responses(
         (status = 201, description = "Memo created successfully", body = MemoResponseDto),
@@ -106,19 +275,37 @@ pub async fn get_memo(state: web::Data<AppState>, id: web::Path<Uuid>) -> impl R
         (status = 500, description = "Internal server error", body = ErrorResponse)
     )
 )]
-#[tracing::instrument(skip(state, dto), fields(title = %dto.title, has_description = dto.description.is_some()))]
+#[tracing::instrument(skip(req, state, dto, params), fields(title = %dto.title, has_description = dto.description.is_some()))]
 #[post("/api/v1/memos")]
 pub async fn create_memo(
+    req: HttpRequest,
     state: web::Data<AppState>,
-    dto: web::Json<CreateMemoDto>,
+    dto: ApiBody<CreateMemoDto>,
+    params: web::Query<CreateMemoParams>,
 ) -> impl Responder {
     tracing::debug!("Creating new memo");
 
-    let service = MemoService::new(state.db.clone());
+    let service = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .require_future_due_dates(state.config.api.require_future_due_dates && !params.historical);
     match service.create_memo(dto.into_inner()).await {
         Ok(memo) => {
             tracing::info!(memo_id = %memo.id, "Memo created successfully");
-            HttpResponse::Created().json(memo)
+            state
+                .notify_webhooks(WebhookMessage {
+                    event: WebhookEvent::MemoCreated,
+                    memo_id: memo.id,
+                    title: memo.title.clone(),
+                    due: memo.date_to,
+                })
+                .await;
+            Negotiated::new("memo", memo)
+                .json_api()
+                .hypermedia()
+                .customize()
+                .with_status(StatusCode::CREATED)
+                .respond_to(&req)
+                .map_into_boxed_body()
         }
         Err(e) => {
             tracing::error!(error = %e, "Failed to create memo");
@@ -142,23 +329,39 @@ pub async fn create_memo(
         (status = 200, description = "Memo updated successfully", body = MemoResponseDto),
         (status = 400, description = "Invalid request body", body = ErrorResponse),
         (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 409, description = "Version is stale (`VERSION_CONFLICT`, `current` holds the latest memo), or completing a memo with open blockers (`MEMO_BLOCKED`, listed in `blockers`)", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     )
 )]
-#[tracing::instrument(skip(state, dto), fields(memo_id = %id, title = %dto.title, has_description = dto.description.is_some(), completed = dto.completed))]
+#[tracing::instrument(skip(req, state, dto), fields(memo_id = %id, title = %dto.title, has_description = dto.description.is_some(), completed = dto.completed))]
 #[put("/api/v1/memos/{id}")]
 pub async fn update_memo(
+    req: HttpRequest,
     state: web::Data<AppState>,
     id: web::Path<Uuid>,
-    dto: web::Json<UpdateMemoDto>,
+    dto: ApiBody<UpdateMemoDto>,
+    if_match: Option<web::Header<IfMatch>>,
 ) -> impl Responder {
     tracing::debug!("Updating memo");
 
-    let service = MemoService::new(state.db.clone());
-    match service.update_memo(id.into_inner(), dto.into_inner()).await {
+    let mut dto = dto.into_inner();
+    match expected_version(dto.version, if_match) {
+        Ok(version) => dto.version = version,
+        Err(e) => return e.error_response(),
+    }
+
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    match service.update_memo(id.into_inner(), dto).await {
         Ok(memo) => {
             tracing::info!(memo_id = %memo.id, "Memo updated successfully");
-            HttpResponse::Ok().json(memo)
+            let etag = version_etag(memo.version);
+            Negotiated::new("memo", memo)
+                .json_api()
+                .hypermedia()
+                .customize()
+                .insert_header(etag)
+                .respond_to(&req)
+                .map_into_boxed_body()
         }
         Err(e) => {
             tracing::error!(error = %e, "Failed to update memo");
@@ -169,7 +372,10 @@ pub async fn update_memo(
 
 /// Partially update a memo
 ///
-/// Update one or more fields of an existing memo. Only provided fields will be updated.
+/// Update one or more fields of an existing memo. Only provided fields will be updated;
+/// a body without any of them is rejected. A patch that changes nothing leaves the
+/// memo (and its version) as is, and answers `304 Not Modified` when sent with
+/// `Prefer: return=minimal`.
 #[utoipa::path(
     patch,
     path = "/api/v1/memos/{id}",
@@ -180,25 +386,46 @@ pub async fn update_memo(
     request_body = PatchMemoDto,
     responses(
         (status = 200, description = "Memo partially updated successfully", body = MemoResponseDto),
-        (status = 400, description = "Invalid request body", body = ErrorResponse),
+        (status = 304, description = "The patch changed nothing and the client sent `Prefer: return=minimal`"),
+        (status = 400, description = "Invalid request body, or no field to change", body = ErrorResponse),
         (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 409, description = "Version is stale (`VERSION_CONFLICT`, `current` holds the latest memo), or completing a memo with open blockers (`MEMO_BLOCKED`, listed in `blockers`)", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     )
 )]
-#[tracing::instrument(skip(state, dto), fields(memo_id = %id))]
+#[tracing::instrument(skip(req, state, dto), fields(memo_id = %id))]
 #[patch("/api/v1/memos/{id}")]
 pub async fn patch_memo(
+    req: HttpRequest,
     state: web::Data<AppState>,
     id: web::Path<Uuid>,
-    dto: web::Json<PatchMemoDto>,
+    dto: ApiBody<PatchMemoDto>,
+    if_match: Option<web::Header<IfMatch>>,
 ) -> impl Responder {
     tracing::debug!("Patching memo");
 
-    let service = MemoService::new(state.db.clone());
-    match service.patch_memo(id.into_inner(), dto.into_inner()).await {
-        Ok(memo) => {
+    let mut dto = dto.into_inner();
+    match expected_version(dto.version, if_match) {
+        Ok(version) => dto.version = version,
+        Err(e) => return e.error_response(),
+    }
+
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    match service.patch_memo_changed(id.into_inner(), dto).await {
+        Ok((memo, false)) if prefers(&req, PREFER_RETURN_MINIMAL) => HttpResponse::NotModified()
+            .insert_header(version_etag(memo.version))
+            .append_header(("Preference-Applied", PREFER_RETURN_MINIMAL))
+            .finish(),
+        Ok((memo, _)) => {
             tracing::info!(memo_id = %memo.id, "Memo patched successfully");
-            HttpResponse::Ok().json(memo)
+            let etag = version_etag(memo.version);
+            Negotiated::new("memo", memo)
+                .json_api()
+                .hypermedia()
+                .customize()
+                .insert_header(etag)
+                .respond_to(&req)
+                .map_into_boxed_body()
         }
         Err(e) => {
             tracing::error!(error = %e, "Failed to patch memo");
@@ -209,7 +436,8 @@ pub async fn patch_memo(
 
 /// Delete a memo
 ///
-/// Permanently delete a memo by its ID
+/// Delete a memo by its ID. The memo disappears at once but can be restored with
+/// `POST /api/v1/undo/{undo_token}` until `undo_expires_at`; after that it is removed for good.
 #[utoipa::path(
     delete,
     path = "/api/v1/memos/{id}",
@@ -218,32 +446,110 @@ pub async fn patch_memo(
         ("id" = Uuid, Path, description = "Memo ID")
     ),
     responses(
-        (status = 204, description = "Memo deleted successfully"),
+        (status = 200, description = "Memo deleted; the undo token restores it", body = DeleteMemosResponse),
         (status = 404, description = "Memo not found", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     )
 )]
 #[tracing::instrument(skip(state), fields(memo_id = %id))]
 #[delete("/api/v1/memos/{id}")]
-pub async fn delete_memo(state: web::Data<AppState>, id: web::Path<Uuid>) -> impl Responder {
+pub async fn delete_memo(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<Negotiated<DeleteMemosResponse>, AppError> {
     tracing::debug!("Deleting memo");
 
-    let service = MemoService::new(state.db.clone());
-    match service.delete_memo(id.into_inner()).await {
-        Ok(()) => {
-            tracing::info!("Memo deleted successfully");
-            HttpResponse::NoContent().finish()
-        }
-        Err(e) => {
-            tracing::error!(error = %e, "Failed to delete memo");
-            e.error_response()
-        }
+    let result = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .delete_memo(id.into_inner(), state.config.undo.window())
+        .await?;
+
+    Ok(Negotiated::new("deletion", result))
+}
+
+/// Delete memos in bulk
+///
+/// Delete the memos listed in `ids` in a single transaction. All of them share one undo token.
+/// Ids that do not exist are reported in `not_found`; when none of the memos exist, nothing is
+/// deleted and no token is issued. With `Prefer: respond-async` the deletion runs in the background:
+/// the response is `202` with the operation to poll, whose `result` is this endpoint's `200` body.
+#[utoipa::path(
+    post,
+    path = "/api/v1/memos/delete",
+    tag = "memos",
+    params(
+        ("Prefer" = Option<String>, Header, description = "`respond-async` to run the deletion as an operation")
+    ),
+    request_body = BatchDeleteDto,
+    responses(
+        (status = 200, description = "Memos deleted; the undo token restores them", body = DeleteMemosResponse),
+        (status = 202, description = "Deletion accepted; poll the operation at `Location`", body = OperationDto),
+        (status = 400, description = "Empty or too many ids", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(req, state, body))]
+#[post("/api/v1/memos/delete")]
+pub async fn delete_memos(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: ApiBody<BatchDeleteDto>,
+) -> Result<HttpResponse, AppError> {
+    let dto = body.into_inner();
+    if let Some(jobs) = operations::async_queue(&req, &state) {
+        dto.validate()?;
+        let total = dto.ids.len();
+        let operation = jobs
+            .submit(OperationKind::DeleteMemos, &dto, Some(total))
+            .await?;
+        return Ok(operations::accepted(&req, operation));
     }
+
+    let result = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .delete_memos(dto, state.config.undo.window())
+        .await?;
+
+    Ok(Negotiated::new("deletion", result).respond_to(&req))
+}
+
+/// Undo a deletion
+///
+/// Restore the memos removed by a delete, using the `undo_token` it returned. A token works once
+/// and only until its `undo_expires_at`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/undo/{token}",
+    tag = "memos",
+    params(
+        ("token" = Uuid, Path, description = "Undo token returned by the delete")
+    ),
+    responses(
+        (status = 200, description = "Memos restored", body = UndoResponse),
+        (status = 404, description = "Unknown token, already used, or its undo window has closed", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state), fields(token = %token))]
+#[post("/api/v1/undo/{token}")]
+pub async fn undo_delete(
+    state: web::Data<AppState>,
+    token: web::Path<Uuid>,
+) -> Result<Negotiated<UndoResponse>, AppError> {
+    let restored = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .undo_delete(token.into_inner())
+        .await?;
+
+    Ok(Negotiated::new("undo", UndoResponse { restored }))
 }
 
 /// Toggle memo completion
 ///
-/// Toggle the completion status of a memo (completed ↔ incomplete)
+/// Toggle the completion status of a memo (completed ↔ incomplete). When completing, an optional `note`
+/// records how the memo was resolved; it is returned as `completion_note` next to `completed_at`.
+/// Reopening a memo clears both. The body may be omitted. A memo cannot be completed while any memo in
+/// its `blocked_by` list is open.
 #[utoipa::path(
     patch,
     path = "/api/v1/memos/{id}/complete",
@@ -251,22 +557,34 @@ pub async fn delete_memo(state: web::Data<AppState>, id: web::Path<Uuid>) -> imp
     params(
         ("id" = Uuid, Path, description = "Memo ID")
     ),
+    request_body(content = Option<CompleteMemoDto>, description = "Optional completion note"),
     responses(
         (status = 200, description = "Memo completion status toggled successfully", body = MemoResponseDto),
+        (status = 400, description = "Note too long, or sent when reopening a memo", body = ErrorResponse),
         (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 409, description = "The memo has open blockers (`MEMO_BLOCKED`); they are listed in `blockers`", body = ErrorResponse),
         (status = 500, description = "Internal server error", body = ErrorResponse)
     )
 )]
-#[tracing::instrument(skip(state), fields(memo_id = %id))]
+#[tracing::instrument(skip(req, state, body), fields(memo_id = %id))]
 #[patch("/api/v1/memos/{id}/complete")]
-pub async fn toggle_complete(state: web::Data<AppState>, id: web::Path<Uuid>) -> impl Responder {
+pub async fn toggle_complete(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    body: OptionalApiBody<CompleteMemoDto>,
+) -> impl Responder {
     tracing::debug!("Toggling memo completion status");
 
-    let service = MemoService::new(state.db.clone());
-    match service.toggle_complete(id.into_inner()).await {
+    let dto = body.into_inner().unwrap_or_default();
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    match service.toggle_complete(id.into_inner(), dto).await {
         Ok(memo) => {
             tracing::info!(memo_id = %memo.id, completed = memo.completed, "Memo completion toggled successfully");
-            HttpResponse::Ok().json(memo)
+            Negotiated::new("memo", memo)
+                .json_api()
+                .hypermedia()
+                .respond_to(&req)
         }
         Err(e) => {
             tracing::error!(error = %e, "Failed to toggle memo completion");
@@ -274,3 +592,406 @@ pub async fn toggle_complete(state: web::Data<AppState>, id: web::Path<Uuid>) ->
         }
     }
 }
+
+/// Complete memos in bulk
+///
+/// Mark the memos selected by `ids` or by `filter` completed in a single transaction.
+/// Already completed memos are left untouched and reported in neither list. Memos waiting on an open
+/// blocker that is not completed by the same batch stay open and are reported in `blocked`.
+/// With `Prefer: respond-async` the batch runs in the background: the response is `202` with the
+/// operation to poll, whose `result` is this endpoint's `200` body.
+#[utoipa::path(
+    patch,
+    path = "/api/v1/memos/complete",
+    tag = "memos",
+    params(
+        ("Prefer" = Option<String>, Header, description = "`respond-async` to run the batch as an operation")
+    ),
+    request_body = BatchCompleteDto,
+    responses(
+        (status = 200, description = "Memos completed", body = BatchCompleteResponse),
+        (status = 202, description = "Batch accepted; poll the operation at `Location`", body = OperationDto),
+        (status = 400, description = "Neither or both of ids and filter given, or invalid values", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(req, state, body))]
+#[patch("/api/v1/memos/complete")]
+pub async fn complete_memos(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    body: ApiBody<BatchCompleteDto>,
+) -> Result<HttpResponse, AppError> {
+    let dto = body.into_inner();
+    if let Some(jobs) = operations::async_queue(&req, &state) {
+        dto.validate()?;
+        // Filter batches learn their size when they run.
+        let total = dto.ids.as_ref().map(Vec::len);
+        let operation = jobs
+            .submit(OperationKind::CompleteMemos, &dto, total)
+            .await?;
+        return Ok(operations::accepted(&req, operation));
+    }
+
+    let result = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .complete_memos(dto)
+        .await?;
+
+    Ok(Negotiated::new("batch_complete", result).respond_to(&req))
+}
+
+/// Duplicate a memo
+///
+/// Create an incomplete copy of a memo's title, description and due date, optionally shifting the due date
+#[utoipa::path(
+    post,
+    path = "/api/v1/memos/{id}/duplicate",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID to copy"),
+        ("days_offset" = Option<i64>, Query, description = "Days to shift the copy's due date by (-3650 to 3650, default: 0)")
+    ),
+    responses(
+        (status = 201, description = "Memo duplicated", body = MemoResponseDto),
+        (status = 400, description = "Invalid days_offset", body = ErrorResponse),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state, params), fields(memo_id = %id))]
+#[post("/api/v1/memos/{id}/duplicate")]
+pub async fn duplicate_memo(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    params: web::Query<DuplicateMemoParams>,
+) -> Result<impl Responder, AppError> {
+    let memo = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .duplicate_memo(id.into_inner(), params.into_inner())
+        .await?;
+
+    Ok(Negotiated::new("memo", memo)
+        .json_api()
+        .hypermedia()
+        .customize()
+        .with_status(StatusCode::CREATED))
+}
+
+/// Archive a memo
+///
+/// Hide a memo from default listings, the calendar, statistics and reminders without deleting it
+#[utoipa::path(
+    post,
+    path = "/api/v1/memos/{id}/archive",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Memo archived", body = MemoResponseDto),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state), fields(memo_id = %id))]
+#[post("/api/v1/memos/{id}/archive")]
+pub async fn archive_memo(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<Negotiated<MemoResponseDto>, AppError> {
+    let memo = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .archive_memo(id.into_inner())
+        .await?;
+
+    Ok(Negotiated::new("memo", memo).json_api().hypermedia())
+}
+
+/// Unarchive a memo
+///
+/// Return an archived memo to the default listings
+#[utoipa::path(
+    post,
+    path = "/api/v1/memos/{id}/unarchive",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Memo unarchived", body = MemoResponseDto),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state), fields(memo_id = %id))]
+#[post("/api/v1/memos/{id}/unarchive")]
+pub async fn unarchive_memo(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<Negotiated<MemoResponseDto>, AppError> {
+    let memo = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .unarchive_memo(id.into_inner())
+        .await?;
+
+    Ok(Negotiated::new("memo", memo).json_api().hypermedia())
+}
+
+/// Toggle memo pin
+///
+/// Pin or unpin a memo. Pinned memos are listed before all others, whatever the sort order
+#[utoipa::path(
+    patch,
+    path = "/api/v1/memos/{id}/pin",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Memo pinned or unpinned", body = MemoResponseDto),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state), fields(memo_id = %id))]
+#[patch("/api/v1/memos/{id}/pin")]
+pub async fn toggle_pinned(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<Negotiated<MemoResponseDto>, AppError> {
+    let memo = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .toggle_pinned(id.into_inner())
+        .await?;
+
+    Ok(Negotiated::new("memo", memo).json_api().hypermedia())
+}
+
+/// Toggle memo star
+///
+/// Star or unstar a memo; `starred=true` lists starred memos only
+#[utoipa::path(
+    patch,
+    path = "/api/v1/memos/{id}/star",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Memo starred or unstarred", body = MemoResponseDto),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state), fields(memo_id = %id))]
+#[patch("/api/v1/memos/{id}/star")]
+pub async fn toggle_starred(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<Negotiated<MemoResponseDto>, AppError> {
+    let memo = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .toggle_starred(id.into_inner())
+        .await?;
+
+    Ok(Negotiated::new("memo", memo).json_api().hypermedia())
+}
+
+/// Snooze a memo
+///
+/// Push an incomplete memo's due date forward by a preset (`1h`, `tomorrow` at 09:00, `next_week` on Monday at 09:00)
+/// or a number of minutes, counted from the due date or, for overdue memos, from now. Presets use the memo's timezone.
+/// Each snooze is recorded in the memo's snooze history.
+#[utoipa::path(
+    post,
+    path = "/api/v1/memos/{id}/snooze",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    request_body = SnoozeMemoDto,
+    responses(
+        (status = 200, description = "Memo snoozed", body = MemoResponseDto),
+        (status = 400, description = "Neither or both of preset and minutes given, or the memo is completed", body = ErrorResponse),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state, body), fields(memo_id = %id))]
+#[post("/api/v1/memos/{id}/snooze")]
+pub async fn snooze_memo(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    body: ApiBody<SnoozeMemoDto>,
+) -> Result<Negotiated<MemoResponseDto>, AppError> {
+    let memo = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .snooze_memo(id.into_inner(), body.into_inner())
+        .await?;
+
+    Ok(Negotiated::new("memo", memo).json_api().hypermedia())
+}
+
+/// List a memo's snoozes
+///
+/// Snooze history of a memo, newest first
+#[utoipa::path(
+    get,
+    path = "/api/v1/memos/{id}/snoozes",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Snooze history", body = Vec<MemoSnoozeDto>),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state), fields(memo_id = %id))]
+#[get("/api/v1/memos/{id}/snoozes")]
+pub async fn list_memo_snoozes(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<Negotiated<Vec<MemoSnoozeDto>>, AppError> {
+    let snoozes = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .get_snoozes(id.into_inner())
+        .await?;
+
+    Ok(Negotiated::new("snoozes", snoozes))
+}
+
+/// Add a blocker to a memo
+///
+/// Record that the memo cannot be completed before `blocker_id` is. Linking an existing pair again changes
+/// nothing; a memo cannot block itself, and a link that would make two memos wait on each other (directly or
+/// through other memos) is rejected.
+#[utoipa::path(
+    post,
+    path = "/api/v1/memos/{id}/blockers",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    request_body = LinkBlockerDto,
+    responses(
+        (status = 200, description = "Blocker linked; the memo lists it in `blocked_by`", body = MemoResponseDto),
+        (status = 400, description = "Self-link, dependency cycle, or the memo is completed", body = ErrorResponse),
+        (status = 404, description = "Memo or blocker not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state, body), fields(memo_id = %id))]
+#[post("/api/v1/memos/{id}/blockers")]
+pub async fn add_memo_blocker(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    body: ApiBody<LinkBlockerDto>,
+) -> Result<Negotiated<MemoResponseDto>, AppError> {
+    let memo = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .add_blocker(id.into_inner(), body.into_inner().blocker_id)
+        .await?;
+
+    Ok(Negotiated::new("memo", memo).json_api().hypermedia())
+}
+
+/// Remove a blocker from a memo
+#[utoipa::path(
+    delete,
+    path = "/api/v1/memos/{id}/blockers/{blocker_id}",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID"),
+        ("blocker_id" = Uuid, Path, description = "ID of the blocking memo")
+    ),
+    responses(
+        (status = 204, description = "Blocker unlinked"),
+        (status = 404, description = "The memo is not blocked by that memo", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(state), fields(memo_id = %path.0, blocker_id = %path.1))]
+#[delete("/api/v1/memos/{id}/blockers/{blocker_id}")]
+pub async fn remove_memo_blocker(
+    state: web::Data<AppState>,
+    path: web::Path<(Uuid, Uuid)>,
+) -> Result<HttpResponse, AppError> {
+    let (id, blocker_id) = path.into_inner();
+    MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .remove_blocker(id, blocker_id)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Move a memo in the manual ordering
+///
+/// Place a memo at the given zero-based position of the manual ordering (`sort_by=position`), shifting the memos in between
+#[utoipa::path(
+    patch,
+    path = "/api/v1/memos/{id}/move",
+    tag = "memos",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    request_body = MoveMemoDto,
+    responses(
+        (status = 200, description = "Memo moved successfully", body = MemoResponseDto),
+        (status = 400, description = "Invalid request body", body = ErrorResponse),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[tracing::instrument(skip(req, state, dto), fields(memo_id = %id, position = dto.position))]
+#[patch("/api/v1/memos/{id}/move")]
+pub async fn move_memo(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    dto: ApiBody<MoveMemoDto>,
+) -> impl Responder {
+    tracing::debug!("Moving memo");
+
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    match service.move_memo(id.into_inner(), dto.into_inner()).await {
+        Ok(memo) => {
+            tracing::info!(memo_id = %memo.id, position = memo.position, "Memo moved successfully");
+            Negotiated::new("memo", memo)
+                .json_api()
+                .hypermedia()
+                .respond_to(&req)
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to move memo");
+            e.error_response()
+        }
+    }
+}
+
+fn version_etag(version: i32) -> ETag {
+    ETag(EntityTag::new_strong(version.to_string()))
+}
+
+/// Version to check before writing: the body field wins, then an `If-Match` ETag.
+fn expected_version(
+    body_version: Option<i32>,
+    if_match: Option<web::Header<IfMatch>>,
+) -> Result<Option<i32>, AppError> {
+    if body_version.is_some() {
+        return Ok(body_version);
+    }
+
+    match if_match.map(web::Header::into_inner) {
+        Some(IfMatch::Items(tags)) => match tags.first() {
+            Some(tag) => tag.tag().parse().map(Some).map_err(|_| {
+                AppError::Validation(format!("If-Match must be a memo version, got {}", tag))
+            }),
+            None => Ok(None),
+        },
+        Some(IfMatch::Any) | None => Ok(None),
+    }
+}
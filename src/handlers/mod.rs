@@ -1,20 +1,82 @@
+pub mod admin;
+pub mod assets;
+pub mod attachments;
+pub mod docs;
+pub mod fallback;
 pub mod health;
+pub mod hypermedia;
+pub mod imports;
+pub mod integrations;
+pub mod jsonapi;
 pub mod memos;
+pub mod negotiate;
+pub mod notifications;
+pub mod operations;
+pub mod push;
+pub mod templates;
+#[cfg(feature = "dev-routes")]
 pub mod test_dto;
+#[cfg(feature = "dev-routes")]
 pub mod test_errors;
+#[cfg(feature = "dev-routes")]
 pub mod test_repository;
+#[cfg(feature = "dev-routes")]
 pub mod test_service;
+pub mod usage;
 pub mod web;
 
-pub use health::{health as health_check, ready};
+pub use admin::{get_maintenance, get_runtime_diagnostics, preview_retention, set_maintenance};
+pub use assets::static_asset;
+pub use attachments::{get_attachment, get_attachment_thumbnail, get_attachment_url};
+pub use docs::{api_docs, openapi_json};
+pub use fallback::fallback;
+pub use health::{health as health_check, ready, startup, version};
+pub use imports::{import_memos_async, list_import_failures};
+pub use integrations::{
+    create_webhook_integration, delete_webhook_integration, list_webhook_integrations,
+};
 pub use memos::{
-    create_memo, delete_memo, get_memo, list_memos, patch_memo, toggle_complete, update_memo,
+    add_memo_blocker, archive_memo, complete_memos, create_memo, delete_memo, delete_memos,
+    duplicate_memo, export_memos, get_memo, list_memo_snoozes, list_memos, memo_stats, move_memo,
+    patch_memo, remove_memo_blocker, snooze_memo, toggle_complete, toggle_pinned, toggle_starred,
+    unarchive_memo, undo_delete, update_memo,
+};
+pub use notifications::{list_notifications, mark_notification_read};
+pub use operations::{get_operation, operation_events};
+pub use push::{get_push_public_key, subscribe_push, unsubscribe_push};
+pub use templates::{
+    create_memo_from_template, create_memo_template, delete_memo_template, get_memo_template,
+    list_memo_templates, update_memo_template,
 };
+#[cfg(feature = "dev-routes")]
 pub use test_dto::test_create_dto;
+#[cfg(feature = "dev-routes")]
 pub use test_errors::{test_database, test_internal, test_not_found, test_validation};
+#[cfg(feature = "dev-routes")]
 pub use test_repository::test_repository as test_repo;
+#[cfg(feature = "dev-routes")]
 pub use test_service::test_service as test_svc;
+pub use usage::get_my_usage;
 pub use web::{
-    create_memo_web, delete_memo_web, get_edit_memo_form, get_memos_list, get_new_memo_form, index,
-    toggle_memo_complete_web, update_memo_web,
+    archive_memo_web, create_memo_web, delete_memo_web, get_board, get_board_columns, get_calendar,
+    get_calendar_grid, get_edit_memo_form, get_edit_title_form, get_flash, get_memos_list,
+    get_new_memo_form, get_notifications, index, move_memo_on_board_web, reorder_memos_web,
+    snooze_memo_web, toggle_memo_complete_web, toggle_memo_pinned_web, toggle_memo_starred_web,
+    undo_delete_web, update_memo_title_web, update_memo_web,
 };
+
+/// Registers the `/test/*` diagnostics routes. Does nothing when the crate is
+/// built without the `dev-routes` feature.
+pub fn configure_test_routes(cfg: &mut actix_web::web::ServiceConfig) {
+    #[cfg(feature = "dev-routes")]
+    cfg.service(test_not_found)
+        .service(test_validation)
+        .service(test_internal)
+        .service(test_database)
+        .service(test_create_dto)
+        .service(test_repo)
+        .service(test_svc);
+
+    #[cfg(not(feature = "dev-routes"))]
+    let _ = cfg;
+}
@@ -0,0 +1,490 @@
+use std::{future::Future, ops::Deref, pin::Pin};
+
+use actix_web::{
+    FromRequest, HttpMessage, HttpRequest, HttpResponse, Responder,
+    body::BoxBody,
+    dev::Payload,
+    error::ResponseError,
+    http::header::{self, Accept, Header},
+    web,
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use url::Url;
+
+use crate::{
+    AppState,
+    error::{AppError, extractors},
+    handlers::{hypermedia::Hypermedia, jsonapi},
+    middleware::RequestId,
+};
+
+/// `Prefer` token asking for responses wrapped in an [`Envelope`].
+pub const PREFER_ENVELOPE: &str = "envelope";
+
+/// `Prefer` token (RFC 7240) for clients that do not need the resource back.
+pub const PREFER_RETURN_MINIMAL: &str = "return=minimal";
+
+/// Whether the request's `Prefer` headers (RFC 7240) include `preference`,
+/// ignoring its parameters.
+pub fn prefers(req: &HttpRequest, preference: &str) -> bool {
+    req.headers()
+        .get_all(header::HeaderName::from_static("prefer"))
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .any(|token| {
+            token
+                .split(';')
+                .next()
+                .is_some_and(|token| token.trim().eq_ignore_ascii_case(preference))
+        })
+}
+
+/// Serialization formats a client can ask for through the `Accept` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    Json,
+    Xml,
+    Yaml,
+    MessagePack,
+    Cbor,
+    /// JSON:API documents, for responses that support them
+    JsonApi,
+}
+
+impl ResponseFormat {
+    /// Picks the highest-ranked supported format from the `Accept` header.
+    /// Missing, malformed or unsupported headers fall back to JSON.
+    pub fn from_request(req: &HttpRequest) -> Self {
+        Accept::parse(req)
+            .ok()
+            .and_then(|accept| {
+                accept
+                    .ranked()
+                    .iter()
+                    .find_map(|mime| Self::from_media_type(mime.essence_str()))
+            })
+            .unwrap_or(Self::Json)
+    }
+
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            "application/json" | "application/*" | "*/*" => Some(Self::Json),
+            "application/xml" | "text/xml" => Some(Self::Xml),
+            "application/yaml" | "application/x-yaml" | "text/yaml" => Some(Self::Yaml),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Self::MessagePack)
+            }
+            "application/cbor" => Some(Self::Cbor),
+            jsonapi::MEDIA_TYPE => Some(Self::JsonApi),
+            _ => None,
+        }
+    }
+
+    pub fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Xml => "application/xml",
+            Self::Yaml => "application/yaml",
+            Self::MessagePack => "application/msgpack",
+            Self::Cbor => "application/cbor",
+            Self::JsonApi => jsonapi::MEDIA_TYPE,
+        }
+    }
+
+    fn serialize<T: Serialize>(self, root: &str, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Json | Self::JsonApi => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            Self::Xml => quick_xml::se::to_string_with_root(root, value)
+                .map(String::into_bytes)
+                .map_err(|e| e.to_string()),
+            Self::Yaml => serde_norway::to_string(value)
+                .map(String::into_bytes)
+                .map_err(|e| e.to_string()),
+            Self::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            Self::Cbor => {
+                let mut buf = Vec::new();
+                ciborium::into_writer(value, &mut buf).map_err(|e| e.to_string())?;
+                Ok(buf)
+            }
+        }
+    }
+}
+
+/// Binary encodings accepted for request bodies, chosen by `Content-Type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BinaryBody {
+    MessagePack,
+    Cbor,
+}
+
+impl BinaryBody {
+    fn from_request(req: &HttpRequest) -> Option<Self> {
+        let mime = req.mime_type().ok().flatten()?;
+        match ResponseFormat::from_media_type(mime.essence_str()) {
+            Some(ResponseFormat::MessagePack) => Some(Self::MessagePack),
+            Some(ResponseFormat::Cbor) => Some(Self::Cbor),
+            _ => None,
+        }
+    }
+
+    fn deserialize<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T, AppError> {
+        match self {
+            Self::MessagePack => rmp_serde::from_slice(bytes)
+                .map_err(|e| AppError::Validation(format!("Invalid MessagePack body: {}", e))),
+            Self::Cbor => ciborium::from_reader(bytes)
+                .map_err(|e| AppError::Validation(format!("Invalid CBOR body: {}", e))),
+        }
+    }
+}
+
+/// Request body extractor that accepts JSON, MessagePack or CBOR depending on
+/// the `Content-Type` header. JSON goes through [`web::Json`] and its
+/// `JsonConfig`; binary bodies share the route group's `PayloadConfig` size limit.
+/// With `STRICT_REQUEST_BODIES`, fields the DTO does not declare are rejected
+/// rather than ignored.
+#[derive(Debug)]
+pub struct ApiBody<T>(pub T);
+
+impl<T> ApiBody<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ApiBody<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for ApiBody<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let strict = req
+            .app_data::<web::Data<AppState>>()
+            .is_some_and(|state| state.config.api.strict_bodies);
+
+        match BinaryBody::from_request(req) {
+            Some(encoding) => {
+                let bytes = web::Bytes::from_request(req, payload);
+                let req = req.clone();
+                Box::pin(async move {
+                    let bytes = bytes
+                        .await
+                        .map_err(|e| extractors::payload_error(e, &req))?;
+                    if !strict {
+                        return Ok(ApiBody(encoding.deserialize(&bytes)?));
+                    }
+                    let value = encoding.deserialize::<Value>(&bytes)?;
+                    Ok(ApiBody(extractors::from_body_value(value, true)?))
+                })
+            }
+            None if strict => {
+                let json = web::Json::<Value>::from_request(req, payload);
+                Box::pin(async move {
+                    let value = json.await?.into_inner();
+                    Ok(ApiBody(extractors::from_body_value(value, true)?))
+                })
+            }
+            None => {
+                let json = web::Json::<T>::from_request(req, payload);
+                Box::pin(async move { Ok(ApiBody(json.await?.into_inner())) })
+            }
+        }
+    }
+}
+
+/// [`ApiBody`] for endpoints whose body may be left out: a request with no
+/// `Content-Type` and no content yields `None`. Anything else is parsed, so
+/// malformed bodies are still rejected rather than ignored.
+#[derive(Debug)]
+pub struct OptionalApiBody<T>(pub Option<T>);
+
+impl<T> OptionalApiBody<T> {
+    pub fn into_inner(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for OptionalApiBody<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let headers = req.headers();
+        let empty = !headers.contains_key(header::CONTENT_TYPE)
+            && !headers.contains_key(header::TRANSFER_ENCODING)
+            && headers
+                .get(header::CONTENT_LENGTH)
+                .is_none_or(|length| length.as_bytes() == b"0");
+        if empty {
+            return Box::pin(async { Ok(OptionalApiBody(None)) });
+        }
+
+        let body = ApiBody::<T>::from_request(req, payload);
+        Box::pin(async move { Ok(OptionalApiBody(Some(body.await?.into_inner()))) })
+    }
+}
+
+/// Responder that serializes its value as JSON, XML, YAML, MessagePack or CBOR
+/// depending on the request's `Accept` header. XML documents use `root` as the top-level
+/// element name. Values opted in with [`json_api`](Self::json_api) are also
+/// rendered as JSON:API documents; others answer JSON:API requests with JSON.
+/// Values opted in with [`hypermedia`](Self::hypermedia) get their links
+/// filled in first.
+///
+/// Requests sent with `Prefer: envelope` get the value wrapped in an
+/// [`Envelope`] instead, and `Preference-Applied: envelope` back. JSON:API
+/// documents have their own top-level shape and are never wrapped.
+///
+/// Status and headers can be adjusted with [`Responder::customize`].
+pub struct Negotiated<T> {
+    value: T,
+    root: &'static str,
+    links: Vec<(&'static str, Url)>,
+    json_api: Option<JsonApiRenderer<T>>,
+    hypermedia: Option<fn(&mut T, &HttpRequest)>,
+}
+
+type JsonApiRenderer<T> = fn(&T, &BTreeMap<&'static str, String>) -> serde_json::Result<Value>;
+
+impl<T: Serialize> Negotiated<T> {
+    pub fn new(root: &'static str, value: T) -> Self {
+        Self {
+            value,
+            root,
+            links: Vec::new(),
+            json_api: None,
+            hypermedia: None,
+        }
+    }
+
+    /// Related URLs by relation, e.g. pagination, for the envelope's or the
+    /// JSON:API document's `links`.
+    pub fn with_links(mut self, links: Vec<(&'static str, Url)>) -> Self {
+        self.links = links;
+        self
+    }
+}
+
+impl<T: Serialize + jsonapi::Document> Negotiated<T> {
+    /// Renders the value as a JSON:API document when that is what the client
+    /// accepts.
+    pub fn json_api(mut self) -> Self {
+        self.json_api = Some(T::to_document);
+        self
+    }
+}
+
+impl<T: Serialize + Hypermedia> Negotiated<T> {
+    /// Fills in the value's links to itself and its actions, in every format.
+    pub fn hypermedia(mut self) -> Self {
+        self.hypermedia = Some(T::add_links);
+        self
+    }
+}
+
+impl<T: Serialize> Responder for Negotiated<T> {
+    type Body = BoxBody;
+
+    fn respond_to(mut self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        if let Some(add_links) = self.hypermedia {
+            add_links(&mut self.value, req);
+        }
+        let mut format = ResponseFormat::from_request(req);
+        let mut envelope = prefers(req, PREFER_ENVELOPE);
+        let serialized = match (format, self.json_api) {
+            (ResponseFormat::JsonApi, Some(render)) => {
+                envelope = false;
+                render(&self.value, &links_with_self(req, self.links))
+                    .and_then(|document| serde_json::to_vec(&document))
+                    .map_err(|e| e.to_string())
+            }
+            _ => {
+                if format == ResponseFormat::JsonApi {
+                    format = ResponseFormat::Json;
+                }
+                if envelope {
+                    format.serialize(self.root, &Envelope::new(req, self.value, self.links))
+                } else {
+                    format.serialize(self.root, &self.value)
+                }
+            }
+        };
+        match serialized {
+            Ok(body) => {
+                let mut response = HttpResponse::Ok();
+                response
+                    .content_type(format.content_type())
+                    .insert_header((header::VARY, "Accept, Prefer"));
+                if envelope {
+                    response.append_header(("Preference-Applied", PREFER_ENVELOPE));
+                }
+                response.body(body)
+            }
+            Err(e) => {
+                tracing::error!(error = %e, format = ?format, "Failed to serialize response");
+                AppError::Internal(format!("Failed to serialize response: {}", e)).error_response()
+            }
+        }
+    }
+}
+
+/// `links` by relation, plus `self` for the request's own URL.
+fn links_with_self(
+    req: &HttpRequest,
+    links: Vec<(&'static str, Url)>,
+) -> BTreeMap<&'static str, String> {
+    let mut links: BTreeMap<_, _> = links
+        .into_iter()
+        .map(|(rel, url)| (rel, url.to_string()))
+        .collect();
+    links.insert("self", req.full_url().to_string());
+    links
+}
+
+/// Uniform top-level shape for clients that ask for it with
+/// `Prefer: envelope`: the usual body under `data`, request metadata under
+/// `meta` and related URLs under `links`.
+#[derive(Debug, Serialize)]
+pub struct Envelope<T> {
+    pub data: T,
+    pub meta: EnvelopeMeta,
+    /// `self` plus any relations the endpoint adds, such as pagination
+    pub links: BTreeMap<&'static str, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EnvelopeMeta {
+    /// Also sent as `X-Request-Id`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+    /// Time from receiving the request to building the response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<f64>,
+}
+
+impl<T> Envelope<T> {
+    fn new(req: &HttpRequest, data: T, links: Vec<(&'static str, Url)>) -> Self {
+        let request_id = RequestId::for_request(req);
+
+        Self {
+            data,
+            meta: EnvelopeMeta {
+                request_id: request_id.as_ref().map(|id| id.as_str().to_string()),
+                duration_ms: request_id.map(|id| id.elapsed().as_secs_f64() * 1000.0),
+            },
+            links: links_with_self(req, links),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn format_for(accept: &str) -> ResponseFormat {
+        let req = TestRequest::default()
+            .insert_header((header::ACCEPT, accept))
+            .to_http_request();
+        ResponseFormat::from_request(&req)
+    }
+
+    #[test]
+    fn test_defaults_to_json() {
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(ResponseFormat::from_request(&req), ResponseFormat::Json);
+        assert_eq!(format_for("*/*"), ResponseFormat::Json);
+        assert_eq!(format_for("text/csv"), ResponseFormat::Json);
+    }
+
+    #[test]
+    fn test_picks_xml_and_yaml() {
+        assert_eq!(format_for("application/xml"), ResponseFormat::Xml);
+        assert_eq!(format_for("text/xml"), ResponseFormat::Xml);
+        assert_eq!(format_for("application/yaml"), ResponseFormat::Yaml);
+        assert_eq!(format_for("application/x-yaml"), ResponseFormat::Yaml);
+    }
+
+    #[test]
+    fn test_picks_binary_formats() {
+        assert_eq!(
+            format_for("application/msgpack"),
+            ResponseFormat::MessagePack
+        );
+        assert_eq!(
+            format_for("application/x-msgpack"),
+            ResponseFormat::MessagePack
+        );
+        assert_eq!(format_for("application/cbor"), ResponseFormat::Cbor);
+    }
+
+    #[test]
+    fn test_respects_quality_values() {
+        assert_eq!(
+            format_for("application/json;q=0.5, application/xml"),
+            ResponseFormat::Xml
+        );
+        assert_eq!(
+            format_for("application/xml;q=0.2, text/csv, application/yaml;q=0.9"),
+            ResponseFormat::Yaml
+        );
+    }
+
+    #[test]
+    fn test_prefer_tokens_ignore_case_parameters_and_order() {
+        let req = TestRequest::default()
+            .insert_header(("Prefer", "wait=5, Envelope; x=1"))
+            .to_http_request();
+        assert!(prefers(&req, PREFER_ENVELOPE));
+        assert!(!prefers(&req, "respond-async"));
+        assert!(!prefers(
+            &TestRequest::default().to_http_request(),
+            PREFER_ENVELOPE
+        ));
+    }
+
+    #[actix_web::test]
+    async fn test_envelope_wraps_the_value_only_when_preferred() {
+        let links = || {
+            vec![(
+                "next",
+                Url::parse("http://localhost/items?offset=10").unwrap(),
+            )]
+        };
+
+        let req = TestRequest::default().uri("/items").to_http_request();
+        let resp = Negotiated::new("items", vec![1, 2])
+            .with_links(links())
+            .respond_to(&req);
+        assert!(!resp.headers().contains_key("preference-applied"));
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        assert_eq!(body, "[1,2]");
+
+        let req = TestRequest::default()
+            .uri("/items")
+            .insert_header(("Prefer", "envelope"))
+            .to_http_request();
+        let resp = Negotiated::new("items", vec![1, 2])
+            .with_links(links())
+            .respond_to(&req);
+        assert_eq!(
+            resp.headers().get("preference-applied").unwrap(),
+            "envelope"
+        );
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["data"], serde_json::json!([1, 2]));
+        assert_eq!(body["links"]["self"], "http://localhost:8080/items");
+        assert_eq!(body["links"]["next"], "http://localhost/items?offset=10");
+        // No request ID middleware in this test
+        assert_eq!(body["meta"], serde_json::json!({}));
+    }
+}
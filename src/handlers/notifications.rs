@@ -0,0 +1,68 @@
+use actix_web::{HttpResponse, Result, get, post, web};
+use uuid::Uuid;
+
+use crate::{
+    dto::{NotificationDto, NotificationListParams, NotificationListResponse},
+    error::{AppError, ErrorResponse},
+    services::NotificationService,
+    state::AppState,
+};
+
+/// List inbox notifications
+///
+/// Newest first. The `unread` field always counts every unread notification,
+/// so a badge can poll with `unread=true&limit=1`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/notifications",
+    tag = "notifications",
+    params(
+        ("limit" = Option<u64>, Query, description = "Number of items per page (1-100, default: 20)"),
+        ("offset" = Option<u64>, Query, description = "Number of items to skip (default: 0)"),
+        ("unread" = Option<bool>, Query, description = "Only return unread notifications"),
+    ),
+    responses(
+        (status = 200, description = "Notifications retrieved successfully", body = NotificationListResponse),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+    )
+)]
+#[get("/api/v1/notifications")]
+#[tracing::instrument(name = "GET /api/v1/notifications", skip(state))]
+pub async fn list_notifications(
+    state: web::Data<AppState>,
+    query: web::Query<NotificationListParams>,
+) -> Result<HttpResponse, AppError> {
+    let response = NotificationService::new(state.db.clone())
+        .list(query.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(response))
+}
+
+/// Mark a notification as read
+///
+/// Marking an already read notification keeps its original `read_at`.
+#[utoipa::path(
+    post,
+    path = "/api/v1/notifications/{id}/read",
+    tag = "notifications",
+    params(
+        ("id" = Uuid, Path, description = "Notification ID")
+    ),
+    responses(
+        (status = 200, description = "Notification marked as read", body = NotificationDto),
+        (status = 404, description = "Notification not found", body = ErrorResponse),
+    )
+)]
+#[post("/api/v1/notifications/{id}/read")]
+#[tracing::instrument(name = "POST /api/v1/notifications/{id}/read", skip(state))]
+pub async fn mark_notification_read(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let notification = NotificationService::new(state.db.clone())
+        .mark_read(id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(notification))
+}
@@ -0,0 +1,187 @@
+use actix_web::{
+    HttpRequest, HttpResponse, Responder, get,
+    http::{
+        StatusCode,
+        header::{self, ContentEncoding},
+    },
+    web::{self, Bytes},
+};
+use futures_util::stream;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+use crate::{
+    dto::{OperationDto, OperationStatus},
+    entities::operations,
+    error::{AppError, ErrorResponse},
+    handlers::negotiate::{self, Negotiated},
+    jobs::JobQueue,
+    services::OperationService,
+    state::AppState,
+};
+
+/// Get an operation
+///
+/// Progress and outcome of a request accepted with `202 Accepted`. Once `status` is `succeeded`,
+/// `result` holds the body the request would have returned synchronously; once `failed`, `error`
+/// holds its error code, message and status.
+#[utoipa::path(
+    get,
+    path = "/api/v1/operations/{id}",
+    tag = "operations",
+    params(
+        ("id" = Uuid, Path, description = "Operation ID from the 202 response")
+    ),
+    responses(
+        (status = 200, description = "Operation found", body = OperationDto),
+        (status = 404, description = "Operation not found, or finished more than 7 days ago", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[get("/api/v1/operations/{id}")]
+#[tracing::instrument(skip(state), fields(operation_id = %id))]
+pub async fn get_operation(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<Negotiated<OperationDto>, AppError> {
+    let operation = OperationService::new(state.db.clone())
+        .get_operation(id.into_inner())
+        .await?;
+
+    Ok(Negotiated::new("operation", operation))
+}
+
+/// How often the progress stream checks its operation for changes.
+const EVENTS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// Quiet time after which the progress stream sends a comment, so proxies
+/// keep the connection open.
+const EVENTS_KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Stream operation progress
+///
+/// Server-sent events for a progress bar. A `progress` event carries the operation each time it
+/// changes, starting with its current state; the stream ends with a `succeeded` or `failed` event.
+/// Every event's `data` is the operation as returned by `GET /api/v1/operations/{id}`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/operations/{id}/events",
+    tag = "operations",
+    params(
+        ("id" = Uuid, Path, description = "Operation ID from the 202 response")
+    ),
+    responses(
+        (status = 200, description = "Event stream of the operation's progress", content_type = "text/event-stream", body = String),
+        (status = 404, description = "Operation not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[get("/api/v1/operations/{id}/events")]
+#[tracing::instrument(skip(state), fields(operation_id = %id))]
+pub async fn operation_events(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let id = id.into_inner();
+    let service = OperationService::new(state.db.clone());
+    let operation = service.get_operation(id).await?;
+
+    let progress = ProgressStream {
+        service,
+        id,
+        next: Some(operation),
+        last_data: String::new(),
+        last_sent: Instant::now(),
+        finished: false,
+    };
+    let events = stream::unfold(progress, |mut progress| async move {
+        let event = progress.next_event().await?;
+        Some((Ok::<_, actix_web::Error>(event), progress))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // Compressing would buffer the events.
+        .insert_header(ContentEncoding::Identity)
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(events))
+}
+
+/// Polls an operation and turns its changes into server-sent events.
+struct ProgressStream {
+    service: OperationService,
+    id: Uuid,
+    /// Snapshot to send before polling again
+    next: Option<OperationDto>,
+    /// `data` of the last event sent, to skip polls where nothing changed
+    last_data: String,
+    last_sent: Instant,
+    finished: bool,
+}
+
+impl ProgressStream {
+    /// The next event, or `None` once the operation finished or can no
+    /// longer be read.
+    async fn next_event(&mut self) -> Option<Bytes> {
+        if self.finished {
+            return None;
+        }
+
+        loop {
+            let operation = match self.next.take() {
+                Some(operation) => operation,
+                None => {
+                    tokio::time::sleep(EVENTS_POLL_INTERVAL).await;
+                    match self.service.get_operation(self.id).await {
+                        Ok(operation) => operation,
+                        Err(e) => {
+                            tracing::warn!(operation_id = %self.id, error = %e, "Ending operation event stream");
+                            return None;
+                        }
+                    }
+                }
+            };
+
+            let data = serde_json::to_string(&operation).ok()?;
+            self.finished = operation.is_finished();
+            if self.finished || data != self.last_data {
+                let event = match operation.status {
+                    OperationStatus::Succeeded => "succeeded",
+                    OperationStatus::Failed => "failed",
+                    OperationStatus::Pending | OperationStatus::Running => "progress",
+                };
+                self.last_sent = Instant::now();
+                let event = format!("event: {}\ndata: {}\n\n", event, data);
+                self.last_data = data;
+                return Some(Bytes::from(event));
+            }
+
+            if self.last_sent.elapsed() >= EVENTS_KEEP_ALIVE {
+                self.last_sent = Instant::now();
+                return Some(Bytes::from_static(b": keep-alive\n\n"));
+            }
+        }
+    }
+}
+
+/// The job queue, when the client sent `Prefer: respond-async` and this
+/// instance runs workers. Otherwise the request is served synchronously, as
+/// RFC 7240 allows.
+pub(crate) fn async_queue<'a>(req: &HttpRequest, state: &'a AppState) -> Option<&'a JobQueue> {
+    let respond_async = negotiate::prefers(req, "respond-async");
+    state.jobs.as_ref().filter(|_| respond_async)
+}
+
+/// `202 Accepted` for a submitted operation, pointing at its status URL.
+/// `Preference-Applied` is only sent to clients that asked to respond async.
+pub(crate) fn accepted(req: &HttpRequest, operation: operations::Model) -> HttpResponse {
+    let location = format!("/api/v1/operations/{}", operation.id);
+    let mut response = Negotiated::new("operation", OperationDto::from(operation))
+        .customize()
+        .with_status(StatusCode::ACCEPTED)
+        .insert_header((header::LOCATION, location));
+    if negotiate::prefers(req, "respond-async") {
+        response = response.append_header(("Preference-Applied", "respond-async"));
+    }
+    response.respond_to(req).map_into_boxed_body()
+}
@@ -0,0 +1,90 @@
+use actix_web::{HttpResponse, Result, delete, get, post, web};
+
+use crate::{
+    dto::{PushSubscriptionDto, PushUnsubscribeDto, VapidPublicKeyResponse},
+    error::{AppError, ErrorResponse},
+    handlers::negotiate::ApiBody,
+    notifications::push::WebPushSender,
+    services::PushService,
+    state::AppState,
+};
+
+fn sender(state: &AppState) -> Result<&WebPushSender, AppError> {
+    state.push.as_ref().ok_or_else(|| {
+        AppError::FeatureDisabled("Web push is disabled (VAPID_PRIVATE_KEY is not set)".to_string())
+    })
+}
+
+/// Get the VAPID public key
+///
+/// Pass it as `applicationServerKey` to `pushManager.subscribe()`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/push/public-key",
+    tag = "push",
+    responses(
+        (status = 200, description = "VAPID public key", body = VapidPublicKeyResponse),
+        (status = 404, description = "Web push is not configured", body = ErrorResponse),
+    )
+)]
+#[get("/api/v1/push/public-key")]
+#[tracing::instrument(name = "GET /api/v1/push/public-key", skip(state))]
+pub async fn get_push_public_key(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let public_key = sender(&state)?.public_key();
+
+    Ok(HttpResponse::Ok().json(VapidPublicKeyResponse { public_key }))
+}
+
+/// Register a browser push subscription
+///
+/// Subscribed browsers get a notification when a memo becomes due.
+/// Re-registering an endpoint replaces its keys.
+#[utoipa::path(
+    post,
+    path = "/api/v1/push/subscribe",
+    tag = "push",
+    request_body = PushSubscriptionDto,
+    responses(
+        (status = 201, description = "Subscription stored"),
+        (status = 400, description = "Invalid subscription", body = ErrorResponse),
+        (status = 404, description = "Web push is not configured", body = ErrorResponse),
+    )
+)]
+#[post("/api/v1/push/subscribe")]
+#[tracing::instrument(name = "POST /api/v1/push/subscribe", skip(state, body))]
+pub async fn subscribe_push(
+    state: web::Data<AppState>,
+    body: ApiBody<PushSubscriptionDto>,
+) -> Result<HttpResponse, AppError> {
+    sender(&state)?;
+
+    PushService::new(state.db.clone())
+        .subscribe(body.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Created().finish())
+}
+
+/// Remove a browser push subscription
+#[utoipa::path(
+    delete,
+    path = "/api/v1/push/subscribe",
+    tag = "push",
+    request_body = PushUnsubscribeDto,
+    responses(
+        (status = 204, description = "Subscription removed (or was not registered)"),
+        (status = 400, description = "Invalid request", body = ErrorResponse),
+    )
+)]
+#[delete("/api/v1/push/subscribe")]
+#[tracing::instrument(name = "DELETE /api/v1/push/subscribe", skip(state, body))]
+pub async fn unsubscribe_push(
+    state: web::Data<AppState>,
+    body: ApiBody<PushUnsubscribeDto>,
+) -> Result<HttpResponse, AppError> {
+    PushService::new(state.db.clone())
+        .unsubscribe(body.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,195 @@
+use actix_web::{HttpResponse, Responder, delete, get, http::StatusCode, post, put, web};
+use uuid::Uuid;
+
+use crate::{
+    dto::{InstantiateTemplateDto, MemoResponseDto, MemoTemplateDto, SaveMemoTemplateDto},
+    error::{AppError, ErrorResponse},
+    handlers::negotiate::{ApiBody, Negotiated, OptionalApiBody},
+    integrations::{WebhookEvent, WebhookMessage},
+    services::MemoTemplateService,
+    state::AppState,
+};
+
+/// List memo templates
+///
+/// All templates, ordered by name
+#[utoipa::path(
+    get,
+    path = "/api/v1/memo-templates",
+    tag = "templates",
+    responses(
+        (status = 200, description = "Memo templates", body = Vec<MemoTemplateDto>),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[get("/api/v1/memo-templates")]
+#[tracing::instrument(skip(state))]
+pub async fn list_memo_templates(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+    let templates = MemoTemplateService::new(state.db.clone())
+        .list_templates()
+        .await?;
+
+    Ok(HttpResponse::Ok().json(templates))
+}
+
+/// Create a memo template
+///
+/// `title` and `description` may use `{{date}}`, `{{time}}`, `{{weekday}}`, `{{month}}` and `{{year}}`, plus any
+/// `{{name}}` supplied as a variable when a memo is created from the template. `due_text` is an informal due
+/// date such as `tomorrow 5pm`, read when the memo is created.
+#[utoipa::path(
+    post,
+    path = "/api/v1/memo-templates",
+    tag = "templates",
+    request_body = SaveMemoTemplateDto,
+    responses(
+        (status = 201, description = "Template created", body = MemoTemplateDto),
+        (status = 400, description = "Invalid fields, unreadable due_text or name already taken", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[post("/api/v1/memo-templates")]
+#[tracing::instrument(skip(state, body))]
+pub async fn create_memo_template(
+    state: web::Data<AppState>,
+    body: ApiBody<SaveMemoTemplateDto>,
+) -> Result<HttpResponse, AppError> {
+    let template = MemoTemplateService::new(state.db.clone())
+        .create_template(body.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Created().json(template))
+}
+
+/// Get a memo template
+#[utoipa::path(
+    get,
+    path = "/api/v1/memo-templates/{id}",
+    tag = "templates",
+    params(
+        ("id" = Uuid, Path, description = "Template ID")
+    ),
+    responses(
+        (status = 200, description = "Template found", body = MemoTemplateDto),
+        (status = 404, description = "Template not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[get("/api/v1/memo-templates/{id}")]
+#[tracing::instrument(skip(state), fields(template_id = %id))]
+pub async fn get_memo_template(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    let template = MemoTemplateService::new(state.db.clone())
+        .get_template(id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(template))
+}
+
+/// Replace a memo template
+#[utoipa::path(
+    put,
+    path = "/api/v1/memo-templates/{id}",
+    tag = "templates",
+    params(
+        ("id" = Uuid, Path, description = "Template ID")
+    ),
+    request_body = SaveMemoTemplateDto,
+    responses(
+        (status = 200, description = "Template updated", body = MemoTemplateDto),
+        (status = 400, description = "Invalid fields, unreadable due_text or name already taken", body = ErrorResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[put("/api/v1/memo-templates/{id}")]
+#[tracing::instrument(skip(state, body), fields(template_id = %id))]
+pub async fn update_memo_template(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    body: ApiBody<SaveMemoTemplateDto>,
+) -> Result<HttpResponse, AppError> {
+    let template = MemoTemplateService::new(state.db.clone())
+        .update_template(id.into_inner(), body.into_inner())
+        .await?;
+
+    Ok(HttpResponse::Ok().json(template))
+}
+
+/// Delete a memo template
+///
+/// Memos created from the template are kept.
+#[utoipa::path(
+    delete,
+    path = "/api/v1/memo-templates/{id}",
+    tag = "templates",
+    params(
+        ("id" = Uuid, Path, description = "Template ID")
+    ),
+    responses(
+        (status = 204, description = "Template deleted"),
+        (status = 404, description = "Template not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[delete("/api/v1/memo-templates/{id}")]
+#[tracing::instrument(skip(state), fields(template_id = %id))]
+pub async fn delete_memo_template(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    MemoTemplateService::new(state.db.clone())
+        .delete_template(id.into_inner())
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Create a memo from a template
+///
+/// Fill in the template's placeholders and create a memo from it. The due date is the template's `due_text`
+/// unless `date_to` or `date_to_text` is sent. The body may be omitted when the template needs no variables.
+#[utoipa::path(
+    post,
+    path = "/api/v1/memos/from-template/{id}",
+    tag = "templates",
+    params(
+        ("id" = Uuid, Path, description = "Template ID")
+    ),
+    request_body(content = Option<InstantiateTemplateDto>, description = "Variables and due date overrides"),
+    responses(
+        (status = 201, description = "Memo created", body = MemoResponseDto),
+        (status = 400, description = "Unknown placeholder, invalid variables, or no due date", body = ErrorResponse),
+        (status = 404, description = "Template not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[post("/api/v1/memos/from-template/{id}")]
+#[tracing::instrument(skip(state, body), fields(template_id = %id))]
+pub async fn create_memo_from_template(
+    state: web::Data<AppState>,
+    id: web::Path<Uuid>,
+    body: OptionalApiBody<InstantiateTemplateDto>,
+) -> Result<impl Responder, AppError> {
+    let memo = MemoTemplateService::new(state.db.clone())
+        .with_memo_cache(state.memo_cache.clone())
+        .instantiate(id.into_inner(), body.into_inner().unwrap_or_default())
+        .await?;
+
+    state
+        .notify_webhooks(WebhookMessage {
+            event: WebhookEvent::MemoCreated,
+            memo_id: memo.id,
+            title: memo.title.clone(),
+            due: memo.date_to,
+        })
+        .await;
+
+    Ok(Negotiated::new("memo", memo)
+        .json_api()
+        .hypermedia()
+        .customize()
+        .with_status(StatusCode::CREATED))
+}
@@ -4,13 +4,13 @@ use validator::Validate;
 
 #[post("/test/dto/create")]
 pub async fn test_create_dto(dto: web::Json<CreateMemoDto>) -> Result<HttpResponse, AppError> {
-    dto.validate()
-        .map_err(|e| AppError::Validation(format!("{}", e)))?;
+    dto.validate()?;
 
     tracing::info!(
         title = %dto.title,
         description = ?dto.description,
-        date_to = %dto.date_to,
+        date_to = ?dto.date_to,
+        date_to_text = ?dto.date_to_text,
         "Valid CreateMemoDto received"
     );
 
@@ -18,6 +18,7 @@ pub async fn test_create_dto(dto: web::Json<CreateMemoDto>) -> Result<HttpRespon
         "message": "Valid memo DTO received",
         "title": dto.title,
         "description": dto.description,
-        "date_to": dto.date_to
+        "date_to": dto.date_to,
+        "date_to_text": dto.date_to_text
     })))
 }
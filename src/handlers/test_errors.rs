@@ -1,9 +1,12 @@
-use crate::error::AppError;
+use crate::error::{AppError, ErrorCode};
 use actix_web::{HttpResponse, Result, get};
 
 #[get("/test/error/notfound")]
 pub async fn test_not_found() -> Result<HttpResponse, AppError> {
-    Err(AppError::NotFound("Test resource".to_string()))
+    Err(AppError::not_found(
+        ErrorCode::ResourceNotFound,
+        "Test resource".to_string(),
+    ))
 }
 
 #[get("/test/error/validation")]
@@ -1,6 +1,10 @@
-use crate::{repository::MemoRepository, state::AppState};
+use crate::{
+    repository::{MemoFilter, MemoRepository},
+    state::AppState,
+};
 use actix_web::{HttpResponse, Responder, get, web};
 use chrono::Utc;
+use chrono_tz::Tz;
 use serde_json::json;
 
 #[get("/test/repository")]
@@ -15,6 +19,8 @@ pub async fn test_repository(state: web::Data<AppState>) -> impl Responder {
         "Test Repository Memo".to_string(),
         Some("Testing repository CRUD operations".to_string()),
         test_date,
+        Tz::UTC,
+        None,
     )
     .await
     {
@@ -60,6 +66,8 @@ pub async fn test_repository(state: web::Data<AppState>) -> impl Responder {
         Some("Updated description".to_string()),
         test_date,
         true,
+        Tz::UTC,
+        None,
     )
     .await
     {
@@ -77,44 +85,63 @@ pub async fn test_repository(state: web::Data<AppState>) -> impl Responder {
     };
 
     tracing::debug!("Step 4: Finding all memos");
-    let (all_memos, total) =
-        match MemoRepository::find_all(&state.db, 10, 0, None, "created_at", "desc").await {
-            Ok(result) => {
-                tracing::info!(
-                    count = result.0.len(),
-                    total = result.1,
-                    "Fetched all memos"
-                );
-                result
-            }
-            Err(e) => {
-                tracing::error!(error = %e, "Failed to find all memos");
-                return HttpResponse::InternalServerError().json(json!({
-                    "error": "Failed to find all memos",
-                    "details": e.to_string()
-                }));
-            }
-        };
+    let (all_memos, total) = match MemoRepository::find_all(
+        &state.db,
+        10,
+        0,
+        &MemoFilter::default(),
+        "created_at",
+        "desc",
+    )
+    .await
+    {
+        Ok(result) => {
+            tracing::info!(
+                count = result.0.len(),
+                total = result.1,
+                "Fetched all memos"
+            );
+            result
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to find all memos");
+            return HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to find all memos",
+                "details": e.to_string()
+            }));
+        }
+    };
 
     tracing::debug!("Step 5: Finding completed memos only");
-    let (completed_memos, completed_total) =
-        match MemoRepository::find_all(&state.db, 10, 0, Some(true), "created_at", "desc").await {
-            Ok(result) => {
-                tracing::info!(
-                    count = result.0.len(),
-                    total = result.1,
-                    "Fetched completed memos"
-                );
-                result
-            }
-            Err(e) => {
-                tracing::error!(error = %e, "Failed to find completed memos");
-                return HttpResponse::InternalServerError().json(json!({
-                    "error": "Failed to find completed memos",
-                    "details": e.to_string()
-                }));
-            }
-        };
+    let (completed_memos, completed_total) = match MemoRepository::find_all(
+        &state.db,
+        10,
+        0,
+        &MemoFilter {
+            completed: Some(true),
+            ..MemoFilter::default()
+        },
+        "created_at",
+        "desc",
+    )
+    .await
+    {
+        Ok(result) => {
+            tracing::info!(
+                count = result.0.len(),
+                total = result.1,
+                "Fetched completed memos"
+            );
+            result
+        }
+        Err(e) => {
+            tracing::error!(error = %e, "Failed to find completed memos");
+            return HttpResponse::InternalServerError().json(json!({
+                "error": "Failed to find completed memos",
+                "details": e.to_string()
+            }));
+        }
+    };
 
     tracing::debug!("Step 6: Deleting the test memo");
     let deleted = match MemoRepository::delete(&state.db, created_memo.id).await {
@@ -19,7 +19,10 @@ pub async fn test_service(state: web::Data<AppState>) -> impl Responder {
     let create_dto = CreateMemoDto {
         title: "Service Layer Test Memo".to_string(),
         description: Some("Testing service layer operations".to_string()),
-        date_to: test_date,
+        date_to: Some(test_date.into()),
+        date_to_text: None,
+        timezone: None,
+        color: None,
     };
 
     let created_memo = match service.create_memo(create_dto).await {
@@ -55,8 +58,11 @@ pub async fn test_service(state: web::Data<AppState>) -> impl Responder {
     let update_dto = UpdateMemoDto {
         title: "Updated Service Test Memo".to_string(),
         description: Some("Updated via service layer".to_string()),
-        date_to: test_date,
+        date_to: test_date.into(),
         completed: true,
+        timezone: None,
+        color: None,
+        version: None,
     };
 
     let updated_memo = match service.update_memo(created_memo.id, update_dto).await {
@@ -78,7 +84,11 @@ pub async fn test_service(state: web::Data<AppState>) -> impl Responder {
         title: Some("Patched Title".to_string()),
         description: None,
         date_to: None,
+        date_to_text: None,
         completed: None,
+        timezone: None,
+        color: None,
+        version: None,
     };
 
     let patched_memo = match service.patch_memo(created_memo.id, patch_dto).await {
@@ -96,7 +106,10 @@ pub async fn test_service(state: web::Data<AppState>) -> impl Responder {
     };
 
     tracing::debug!("Step 5: Toggling completion status via service");
-    let toggled_memo = match service.toggle_complete(created_memo.id).await {
+    let toggled_memo = match service
+        .toggle_complete(created_memo.id, Default::default())
+        .await
+    {
         Ok(memo) => {
             tracing::info!(completed = memo.completed, "Memo toggled via service");
             memo
@@ -115,15 +128,22 @@ pub async fn test_service(state: web::Data<AppState>) -> impl Responder {
         limit: Some(10),
         offset: Some(0),
         completed: None,
+        overdue: None,
+        archived: None,
+        pinned: None,
+        starred: None,
+        color: None,
         sort_by: Some("created_at".to_string()),
         order: Some("desc".to_string()),
+        q: None,
+        count: None,
     };
 
     let all_memos = match service.get_all_memos(params).await {
         Ok(response) => {
             tracing::info!(
                 count = response.data.len(),
-                total = response.total,
+                total = ?response.total,
                 "Fetched all memos via service"
             );
             response
@@ -142,15 +162,22 @@ pub async fn test_service(state: web::Data<AppState>) -> impl Responder {
         limit: Some(10),
         offset: Some(0),
         completed: Some(false),
+        overdue: None,
+        archived: None,
+        pinned: None,
+        starred: None,
+        color: None,
         sort_by: Some("created_at".to_string()),
         order: Some("desc".to_string()),
+        q: None,
+        count: None,
     };
 
     let completed_memos = match service.get_all_memos(completed_params).await {
         Ok(response) => {
             tracing::info!(
                 count = response.data.len(),
-                total = response.total,
+                total = ?response.total,
                 "Fetched completed memos via service"
             );
             response
@@ -165,7 +192,7 @@ pub async fn test_service(state: web::Data<AppState>) -> impl Responder {
     };
 
     tracing::debug!("Step 8: Deleting memo via service");
-    match service.delete_memo(created_memo.id).await {
+    match service.delete_memo_permanently(created_memo.id).await {
         Ok(_) => {
             tracing::info!("Memo deleted via service");
         }
@@ -0,0 +1,45 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Result, get, web};
+
+use crate::{
+    auth::Principal,
+    dto::ApiKeyUsageDto,
+    error::{AppError, ErrorResponse},
+    state::AppState,
+};
+
+/// Get the caller's API usage
+///
+/// Requests made with the `X-API-Key` of this request in the current month,
+/// per day and against the key's monthly quota. Checking usage is not counted,
+/// and works after the quota is used up. Counts from other instances show up
+/// within `API_KEY_USAGE_FLUSH_SECS`.
+#[utoipa::path(
+    get,
+    path = "/api/v1/me/usage",
+    tag = "usage",
+    responses(
+        (status = 200, description = "Usage of the caller's API key", body = ApiKeyUsageDto),
+        (status = 401, description = "Missing or invalid API key", body = ErrorResponse),
+    ),
+    security(("api_key" = []))
+)]
+#[get("/api/v1/me/usage")]
+#[tracing::instrument(name = "GET /api/v1/me/usage", skip(req, state))]
+pub async fn get_my_usage(
+    req: HttpRequest,
+    state: web::Data<AppState>,
+) -> Result<HttpResponse, AppError> {
+    let principal = req.extensions().get::<Principal>().cloned();
+    let key = match &principal {
+        Some(Principal::ApiKey(id)) => state.api_keys.find(id),
+        _ => None,
+    }
+    .ok_or_else(|| AppError::Unauthorized("Send an API key in X-API-Key".to_string()))?;
+
+    let usage = state.api_keys.month_usage(&key.id).await?;
+    Ok(HttpResponse::Ok().json(ApiKeyUsageDto::new(
+        key.id.clone(),
+        key.monthly_quota,
+        usage,
+    )))
+}
@@ -1,14 +1,32 @@
-use actix_web::{HttpResponse, delete, get, patch, post, put, web};
+use actix_multipart::form::{MultipartForm, bytes::Bytes as MultipartBytes, text::Text};
+use actix_web::{
+    FromRequest, HttpRequest, HttpResponse, ResponseError, delete,
+    dev::Payload,
+    get,
+    http::header::{self, HeaderMap},
+    patch, post, put, web,
+};
 use askama::Template;
-use chrono::{DateTime, Utc};
+use chrono::{Datelike, Days, NaiveDate, Utc};
+use futures_util::future::LocalBoxFuture;
 use serde::Deserialize;
+use utoipa::ToSchema;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    dto::{MemoResponseDto, PaginationParams},
-    error::AppError,
-    services::MemoService,
+    attachments::Upload,
+    auth::UrlSigner,
+    dto::{
+        BoardColumn, DueDate, MemoBoard, MemoResponseDto, MemoStatsDto, MemoTemplateDto,
+        NotificationDto, NotificationListParams, PaginationParams, SnoozeMemoDto, SnoozePreset,
+    },
+    error::{AppError, ErrorResponse},
+    flash::FlashMessage,
+    i18n::{Locale, ViewerTimezone},
+    integrations::{WebhookEvent, WebhookMessage},
+    middleware::CspNonce,
+    services::{AttachmentService, MemoService, MemoTemplateService, NotificationService},
     state::AppState,
 };
 
@@ -16,56 +34,307 @@ use crate::{
 #[template(path = "pages/index.html")]
 pub struct IndexTemplate {
     pub memos: Vec<MemoResponseDto>,
+    pub stats: MemoStatsDto,
+    pub flash: Option<FlashMessage>,
+    pub locale: Locale,
+    pub csp_nonce: CspNonce,
+}
+
+#[derive(Template)]
+#[template(path = "components/flash.html")]
+pub struct FlashTemplate {
+    pub flash: Option<FlashMessage>,
+    pub locale: Locale,
 }
 
 #[derive(Template)]
 #[template(path = "components/memo_list.html")]
 pub struct MemoListTemplate {
     pub memos: Vec<MemoResponseDto>,
+    pub locale: Locale,
 }
 
 #[derive(Template)]
 #[template(path = "components/memo_item.html")]
 pub struct MemoItemTemplate {
     pub memo: MemoResponseDto,
+    pub locale: Locale,
+}
+
+#[derive(Template)]
+#[template(path = "components/memo_title.html")]
+pub struct MemoTitleTemplate {
+    pub memo: MemoResponseDto,
+    pub locale: Locale,
+}
+
+#[derive(Template)]
+#[template(path = "components/memo_title_form.html")]
+pub struct MemoTitleFormTemplate {
+    pub memo: MemoResponseDto,
+    pub locale: Locale,
 }
 
 #[derive(Template)]
 #[template(path = "components/memo_form.html")]
 pub struct MemoFormTemplate {
     pub memo: Option<MemoResponseDto>,
+    /// Templates offered when creating a memo; empty when editing
+    pub templates: Vec<MemoTemplateDto>,
+    /// The chosen template, used to prefill a new memo
+    pub prefill: Option<MemoTemplateDto>,
+    pub locale: Locale,
+}
+
+const MONTH_KEYS: [&str; 12] = [
+    "month-january",
+    "month-february",
+    "month-march",
+    "month-april",
+    "month-may",
+    "month-june",
+    "month-july",
+    "month-august",
+    "month-september",
+    "month-october",
+    "month-november",
+    "month-december",
+];
+
+const WEEKDAY_KEYS: [&str; 7] = [
+    "weekday-mon",
+    "weekday-tue",
+    "weekday-wed",
+    "weekday-thu",
+    "weekday-fri",
+    "weekday-sat",
+    "weekday-sun",
+];
+
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    pub day: u32,
+    pub in_month: bool,
+    pub is_today: bool,
+    pub memos: Vec<MemoResponseDto>,
+}
+
+pub struct CalendarMonth {
+    pub title: String,
+    pub weeks: Vec<Vec<CalendarDay>>,
+    pub weekday_keys: [&'static str; 7],
+    pub prev_year: i32,
+    pub prev_month: u32,
+    pub next_year: i32,
+    pub next_month: u32,
+}
+
+#[derive(Template)]
+#[template(path = "pages/calendar.html")]
+pub struct CalendarTemplate {
+    pub calendar: CalendarMonth,
+    pub flash: Option<FlashMessage>,
+    pub locale: Locale,
+    pub csp_nonce: CspNonce,
+}
+
+#[derive(Template)]
+#[template(path = "pages/board.html")]
+pub struct BoardTemplate {
+    pub board: MemoBoard,
+    pub flash: Option<FlashMessage>,
+    pub locale: Locale,
+    pub csp_nonce: CspNonce,
+}
+
+#[derive(Template)]
+#[template(path = "components/board_columns.html")]
+pub struct BoardColumnsTemplate {
+    pub board: MemoBoard,
+    pub locale: Locale,
+}
+
+/// Most memos shown in one board column.
+const BOARD_COLUMN_LIMIT: u64 = 50;
+
+#[derive(Template)]
+#[template(path = "pages/notifications.html")]
+pub struct NotificationsTemplate {
+    pub notifications: Vec<NotificationDto>,
+    pub flash: Option<FlashMessage>,
+    pub locale: Locale,
+}
+
+#[derive(Template)]
+#[template(path = "components/calendar_grid.html")]
+pub struct CalendarGridTemplate {
+    pub calendar: CalendarMonth,
+    pub locale: Locale,
 }
 
 #[derive(Debug, Deserialize, Validate)]
+pub struct CalendarParams {
+    #[validate(range(min = 1970, max = 9999, message = "Year must be between 1970 and 9999"))]
+    pub year: Option<i32>,
+    #[validate(range(min = 1, max = 12, message = "Month must be between 1 and 12"))]
+    pub month: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct WebCreateMemoForm {
     #[validate(length(min = 1, max = 200))]
     pub title: String,
     pub description: Option<String>,
+    /// `datetime-local` value; ignored when `date_to_text` is filled in
+    #[serde(default)]
     pub date_to: String,
+    #[validate(length(max = 100))]
+    pub date_to_text: Option<String>,
+    /// Color label from the palette; empty for none
+    pub color: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Validate)]
+/// [`WebCreateMemoForm`] sent as `multipart/form-data`, as the web form does
+/// when images are attached.
+#[derive(MultipartForm, ToSchema)]
+pub struct WebCreateMemoUpload {
+    #[schema(value_type = String)]
+    pub title: Text<String>,
+    #[schema(value_type = Option<String>)]
+    pub description: Option<Text<String>>,
+    #[schema(value_type = Option<String>)]
+    pub date_to: Option<Text<String>>,
+    #[schema(value_type = Option<String>)]
+    pub date_to_text: Option<Text<String>>,
+    #[schema(value_type = Option<String>)]
+    pub color: Option<Text<String>>,
+    /// PNG, JPEG, GIF or WebP images, at most 5; empty file inputs are ignored
+    #[schema(value_type = Vec<String>, format = Binary)]
+    pub images: Vec<MultipartBytes>,
+}
+
+/// The new-memo form, URL-encoded or, with images, multipart.
+pub struct WebCreateMemoSubmission {
+    pub form: WebCreateMemoForm,
+    pub images: Vec<Upload>,
+}
+
+impl FromRequest for WebCreateMemoSubmission {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        if !is_multipart(req.headers()) {
+            let form = web::Form::<WebCreateMemoForm>::from_request(req, payload);
+            return Box::pin(async move {
+                Ok(Self {
+                    form: form.await?.into_inner(),
+                    images: Vec::new(),
+                })
+            });
+        }
+
+        let upload = MultipartForm::<WebCreateMemoUpload>::from_request(req, payload);
+        Box::pin(async move {
+            let upload = upload.await?.into_inner();
+            Ok(Self {
+                form: WebCreateMemoForm {
+                    title: upload.title.into_inner(),
+                    description: upload.description.map(Text::into_inner),
+                    date_to: upload.date_to.map(Text::into_inner).unwrap_or_default(),
+                    date_to_text: upload.date_to_text.map(Text::into_inner),
+                    color: upload.color.map(Text::into_inner),
+                },
+                images: upload
+                    .images
+                    .into_iter()
+                    .map(|file| Upload {
+                        file_name: file.file_name,
+                        data: file.data.to_vec(),
+                    })
+                    .collect(),
+            })
+        })
+    }
+}
+
+/// Whether a request body is `multipart/form-data`.
+pub fn is_multipart(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value
+                .trim_start()
+                .to_ascii_lowercase()
+                .starts_with("multipart/form-data")
+        })
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WebReorderMemosForm {
+    /// Comma-separated memo ids in their new order
+    pub order: String,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
+pub struct WebUpdateTitleForm {
+    #[validate(length(min = 1, max = 200))]
+    pub title: String,
+    pub version: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, Validate, ToSchema)]
 pub struct WebUpdateMemoForm {
     #[validate(length(min = 1, max = 200))]
     pub title: String,
     pub description: Option<String>,
+    /// `datetime-local` value; ignored when `date_to_text` is filled in
+    #[serde(default)]
     pub date_to: String,
+    #[validate(length(max = 100))]
+    pub date_to_text: Option<String>,
+    /// Color label from the palette; empty removes it
+    pub color: Option<String>,
     pub completed: Option<String>,
+    pub version: Option<i32>,
 }
 
+/// Home page
+#[utoipa::path(
+    get,
+    path = "/",
+    tag = "web",
+    responses(
+        (status = 200, description = "Memo list with statistics", content_type = "text/html", body = String),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
 #[get("/")]
-pub async fn index(state: web::Data<AppState>) -> Result<HttpResponse, AppError> {
+pub async fn index(
+    state: web::Data<AppState>,
+    flash: Option<FlashMessage>,
+    locale: Locale,
+    csp_nonce: CspNonce,
+) -> Result<HttpResponse, AppError> {
     tracing::debug!("Rendering index page");
 
-    let service = MemoService::new(state.db.clone());
-    let params = PaginationParams::default();
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
 
-    let result = service.get_all_memos(params).await?;
+    let mut result = service.get_index_memos().await?;
+    let stats = service.get_stats(super::memos::STATS_DAYS).await?;
+    sign_attachment_links(&state.url_signer, &mut result.data)?;
 
-    let template = IndexTemplate { memos: result.data };
+    let template = IndexTemplate {
+        memos: result.data,
+        stats,
+        flash,
+        locale,
+        csp_nonce,
+    };
 
     match template.render() {
-        Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
+        Ok(html) => Ok(render_page(html, template.flash.is_some())),
         Err(err) => {
             tracing::error!(error = ?err, "Failed to render index template");
             Err(AppError::Internal("Failed to render template".to_string()))
@@ -73,18 +342,46 @@ pub async fn index(state: web::Data<AppState>) -> Result<HttpResponse, AppError>
     }
 }
 
+/// Memo list fragment
+///
+/// Takes the same filters as `GET /api/v1/memos`.
+#[utoipa::path(
+    get,
+    path = "/web/memos",
+    tag = "web",
+    params(
+        ("limit" = Option<u64>, Query, description = "Maximum number of memos"),
+        ("offset" = Option<u64>, Query, description = "Number of memos to skip"),
+        ("completed" = Option<bool>, Query, description = "Filter by completion status"),
+        ("overdue" = Option<bool>, Query, description = "Only memos past their due date"),
+        ("archived" = Option<bool>, Query, description = "Show archived memos instead of active ones"),
+        ("q" = Option<String>, Query, description = "Search in title and description"),
+        ("sort_by" = Option<String>, Query, description = "Sort field"),
+        ("order" = Option<String>, Query, description = "Sort order: asc or desc")
+    ),
+    responses(
+        (status = 200, description = "Rendered memo list", content_type = "text/html", body = String),
+        (status = 400, description = "Invalid query parameters", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
 #[get("/web/memos")]
 pub async fn get_memos_list(
     state: web::Data<AppState>,
     query: web::Query<PaginationParams>,
+    locale: Locale,
 ) -> Result<HttpResponse, AppError> {
     tracing::debug!("Fetching memos list for web");
 
-    let service = MemoService::new(state.db.clone());
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
 
-    let result = service.get_all_memos(query.into_inner()).await?;
+    let mut result = service.get_all_memos(query.into_inner()).await?;
+    sign_attachment_links(&state.url_signer, &mut result.data)?;
 
-    let template = MemoListTemplate { memos: result.data };
+    let template = MemoListTemplate {
+        memos: result.data,
+        locale,
+    };
 
     match template.render() {
         Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
@@ -95,11 +392,53 @@ pub async fn get_memos_list(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct NewMemoFormQuery {
+    pub template_id: Option<Uuid>,
+}
+
+/// New memo form
+#[utoipa::path(
+    get,
+    path = "/web/memos/new",
+    tag = "web",
+    params(
+        ("template_id" = Option<Uuid>, Query, description = "Template to prefill the form from")
+    ),
+    responses(
+        (status = 200, description = "Rendered form", content_type = "text/html", body = String),
+        (status = 404, description = "Template not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
 #[get("/web/memos/new")]
-pub async fn get_new_memo_form() -> Result<HttpResponse, AppError> {
-    tracing::debug!("Rendering new memo form");
+pub async fn get_new_memo_form(
+    state: web::Data<AppState>,
+    query: web::Query<NewMemoFormQuery>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    tracing::debug!(template_id = ?query.template_id, "Rendering new memo form");
 
-    let template = MemoFormTemplate { memo: None };
+    let service = MemoTemplateService::new(state.db.clone());
+    let templates = service.list_templates().await?;
+    let prefill = match query.template_id {
+        // Placeholders that need a variable are left for the user to fill in
+        Some(id) => match service
+            .render_template(id, &Default::default(), ViewerTimezone::current())
+            .await
+        {
+            Err(AppError::Validation(_)) => Some(service.get_template(id).await?),
+            rendered => Some(rendered?),
+        },
+        None => None,
+    };
+
+    let template = MemoFormTemplate {
+        memo: None,
+        templates,
+        prefill,
+        locale,
+    };
 
     match template.render() {
         Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
@@ -110,37 +449,90 @@ pub async fn get_new_memo_form() -> Result<HttpResponse, AppError> {
     }
 }
 
+/// Create a memo from the web form
+///
+/// Responds with the refreshed memo list and a flash message cookie. Forms with images are sent as
+/// `multipart/form-data`, which may be as large as `UPLOAD_BODY_LIMIT`; each image gets a
+/// thumbnail shown in the memo item.
+#[utoipa::path(
+    post,
+    path = "/web/memos",
+    tag = "web",
+    request_body(content(
+        (WebCreateMemoForm = "application/x-www-form-urlencoded"),
+        (WebCreateMemoUpload = "multipart/form-data")
+    )),
+    responses(
+        (status = 200, description = "Memo created; rendered memo list", content_type = "text/html", body = String),
+        (status = 400, description = "Invalid form fields, due date or images", body = ErrorResponse),
+        (status = 413, description = "Form larger than the body limit", body = ErrorResponse),
+        (status = 422, description = "An image was rejected by the upload scanners", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
 #[post("/web/memos")]
 pub async fn create_memo_web(
     state: web::Data<AppState>,
-    form: web::Form<WebCreateMemoForm>,
+    submission: WebCreateMemoSubmission,
+    locale: Locale,
 ) -> Result<HttpResponse, AppError> {
-    tracing::debug!("Creating memo from web form");
+    tracing::debug!(
+        images = submission.images.len(),
+        "Creating memo from web form"
+    );
 
-    form.validate()
-        .map_err(|e| AppError::Validation(format!("Validation failed: {}", e)))?;
+    let result = create_memo_fragment(&state, submission, locale).await;
+    Ok(with_flash(result, "flash-memo-created", locale))
+}
 
-    let date_to: DateTime<Utc> =
-        chrono::NaiveDateTime::parse_from_str(&form.date_to, "%Y-%m-%dT%H:%M")
-            .map_err(|_| {
-                AppError::Validation("Invalid date format. Expected YYYY-MM-DDTHH:MM".to_string())
-            })?
-            .and_utc();
+async fn create_memo_fragment(
+    state: &AppState,
+    submission: WebCreateMemoSubmission,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    let WebCreateMemoSubmission { form, images } = submission;
+    form.validate()?;
 
-    let service = MemoService::new(state.db.clone());
+    // Form dates are in the viewer's timezone, which the memo is created in
+    let date_to = form_due_date(&form.date_to, form.date_to_text.as_deref())?;
+
+    let service = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .require_future_due_dates(state.config.api.require_future_due_dates);
 
     let dto = crate::dto::CreateMemoDto {
         title: form.title.clone(),
         description: form.description.clone(),
-        date_to,
+        date_to: Some(date_to),
+        date_to_text: None,
+        timezone: None,
+        color: form_color(form.color.as_deref()),
     };
 
-    let _memo = service.create_memo(dto).await?;
+    let attachments = AttachmentService::new(state.db.clone())
+        .scan_with(state.upload_scanners.clone())
+        .prepare(images)
+        .await?;
+    let memo = service
+        .create_memo_with_attachments(dto, attachments)
+        .await?;
+    state
+        .notify_webhooks(WebhookMessage {
+            event: WebhookEvent::MemoCreated,
+            memo_id: memo.id,
+            title: memo.title,
+            due: memo.date_to,
+        })
+        .await;
 
     let params = PaginationParams::default();
-    let result = service.get_all_memos(params).await?;
+    let mut result = service.get_all_memos(params).await?;
+    sign_attachment_links(&state.url_signer, &mut result.data)?;
 
-    let template = MemoListTemplate { memos: result.data };
+    let template = MemoListTemplate {
+        memos: result.data,
+        locale,
+    };
 
     match template.render() {
         Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
@@ -151,18 +543,38 @@ pub async fn create_memo_web(
     }
 }
 
+/// Edit memo form
+#[utoipa::path(
+    get,
+    path = "/web/memos/{id}/edit",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Rendered form", content_type = "text/html", body = String),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
 #[get("/web/memos/{id}/edit")]
 pub async fn get_edit_memo_form(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
+    locale: Locale,
 ) -> Result<HttpResponse, AppError> {
     let id = path.into_inner();
     tracing::debug!(memo_id = %id, "Rendering edit memo form");
 
-    let service = MemoService::new(state.db.clone());
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
     let memo = service.get_memo_by_id(id).await?;
 
-    let template = MemoFormTemplate { memo: Some(memo) };
+    let template = MemoFormTemplate {
+        memo: Some(memo),
+        templates: Vec::new(),
+        prefill: None,
+        locale,
+    };
 
     match template.render() {
         Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
@@ -173,39 +585,69 @@ pub async fn get_edit_memo_form(
     }
 }
 
+/// Update a memo from the web form
+#[utoipa::path(
+    put,
+    path = "/web/memos/{id}",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    request_body(content = WebUpdateMemoForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Memo updated; rendered memo item", content_type = "text/html", body = String),
+        (status = 400, description = "Invalid form fields or due date", body = ErrorResponse),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 409, description = "The memo changed since the form was loaded", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
 #[put("/web/memos/{id}")]
 pub async fn update_memo_web(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
     form: web::Form<WebUpdateMemoForm>,
+    locale: Locale,
 ) -> Result<HttpResponse, AppError> {
     let id = path.into_inner();
     tracing::debug!(memo_id = %id, "Updating memo from web form");
 
-    form.validate()
-        .map_err(|e| AppError::Validation(format!("Validation failed: {}", e)))?;
+    let result = update_memo_fragment(&state, id, &form, locale).await;
+    Ok(with_flash(result, "flash-memo-updated", locale))
+}
+
+async fn update_memo_fragment(
+    state: &AppState,
+    id: Uuid,
+    form: &WebUpdateMemoForm,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    form.validate()?;
 
-    let date_to: DateTime<Utc> =
-        chrono::NaiveDateTime::parse_from_str(&form.date_to, "%Y-%m-%dT%H:%M")
-            .map_err(|_| {
-                AppError::Validation("Invalid date format. Expected YYYY-MM-DDTHH:MM".to_string())
-            })?
-            .and_utc();
+    // The form showed the due date in the viewer's timezone; the memo keeps its own
+    let date_to = form_due_date(&form.date_to, form.date_to_text.as_deref())?
+        .resolve(ViewerTimezone::current())
+        .map_err(AppError::Validation)?
+        .into();
 
     let completed = form.completed.is_some();
 
-    let service = MemoService::new(state.db.clone());
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
 
     let dto = crate::dto::UpdateMemoDto {
         title: form.title.clone(),
         description: form.description.clone(),
         date_to,
         completed,
+        timezone: None,
+        color: form_color(form.color.as_deref()),
+        version: form.version,
     };
 
-    let memo = service.update_memo(id, dto).await?;
+    let mut memo = service.update_memo(id, dto).await?;
 
-    let template = MemoItemTemplate { memo };
+    sign_attachment_links(&state.url_signer, [&mut memo])?;
+    let template = MemoItemTemplate { memo, locale };
 
     match template.render() {
         Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
@@ -216,32 +658,276 @@ pub async fn update_memo_web(
     }
 }
 
+/// Inline title form
+#[utoipa::path(
+    get,
+    path = "/web/memos/{id}/title/edit",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Rendered form", content_type = "text/html", body = String),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[get("/web/memos/{id}/title/edit")]
+pub async fn get_edit_title_form(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+    tracing::debug!(memo_id = %id, "Rendering inline title form");
+
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    let memo = service.get_memo_by_id(id).await?;
+
+    let template = MemoTitleFormTemplate { memo, locale };
+
+    match template.render() {
+        Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render memo title form template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+/// Rename a memo inline
+#[utoipa::path(
+    patch,
+    path = "/web/memos/{id}/title",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    request_body(content = WebUpdateTitleForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Memo renamed; rendered title", content_type = "text/html", body = String),
+        (status = 400, description = "Invalid title", body = ErrorResponse),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 409, description = "The memo changed since the form was loaded", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[patch("/web/memos/{id}/title")]
+pub async fn update_memo_title_web(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    form: web::Form<WebUpdateTitleForm>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+    tracing::debug!(memo_id = %id, "Updating memo title inline");
+
+    form.validate()?;
+
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+
+    let form = form.into_inner();
+    let dto = crate::dto::PatchMemoDto {
+        title: Some(form.title),
+        description: None,
+        date_to: None,
+        date_to_text: None,
+        completed: None,
+        timezone: None,
+        color: None,
+        version: form.version,
+    };
+
+    let memo = service.patch_memo(id, dto).await?;
+
+    let template = MemoTitleTemplate { memo, locale };
+
+    match template.render() {
+        Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render memo title template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+/// Delete a memo from the web
+///
+/// Responds with a flash message offering to undo the deletion.
+#[utoipa::path(
+    delete,
+    path = "/web/memos/{id}",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Memo deleted; rendered flash message", content_type = "text/html", body = String),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
 #[delete("/web/memos/{id}")]
 pub async fn delete_memo_web(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
+    locale: Locale,
 ) -> Result<HttpResponse, AppError> {
     let id = path.into_inner();
     tracing::debug!(memo_id = %id, "Deleting memo from web");
 
-    let service = MemoService::new(state.db.clone());
-    service.delete_memo(id).await?;
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    let deleted = match service.delete_memo(id, state.config.undo.window()).await {
+        Ok(deleted) => deleted,
+        Err(err) => return Ok(with_flash(Err(err), "flash-memo-deleted", locale)),
+    };
+
+    // Rendered right away instead of through the flash cookie so that it can
+    // carry the undo button.
+    let template = FlashTemplate {
+        flash: Some(
+            FlashMessage::success(locale.t("flash-memo-deleted")).with_undo(deleted.undo_token),
+        ),
+        locale,
+    };
+
+    match template.render() {
+        Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render flash template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+/// Undo a deletion from the web
+#[utoipa::path(
+    post,
+    path = "/web/undo/{token}",
+    tag = "web",
+    params(
+        ("token" = Uuid, Path, description = "Undo token from the deletion")
+    ),
+    responses(
+        (status = 200, description = "Memos restored"),
+        (status = 404, description = "Unknown token, already used, or its undo window has closed", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[post("/web/undo/{token}")]
+pub async fn undo_delete_web(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    let token = path.into_inner();
+    tracing::debug!(%token, "Undoing deletion from web");
+
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    let result = service
+        .undo_delete(token)
+        .await
+        .map(|_| HttpResponse::Ok().body(""));
 
-    Ok(HttpResponse::Ok().body(""))
+    Ok(with_flash(result, "flash-memo-restored", locale))
 }
 
+/// Pending flash message
+#[utoipa::path(
+    get,
+    path = "/web/flash",
+    tag = "web",
+    responses(
+        (status = 200, description = "Rendered flash message, empty when there is none", content_type = "text/html", body = String)
+    )
+)]
+#[get("/web/flash")]
+pub async fn get_flash(
+    flash: Option<FlashMessage>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    tracing::debug!("Rendering pending flash message");
+
+    let template = FlashTemplate { flash, locale };
+
+    match template.render() {
+        Ok(html) => Ok(render_page(html, template.flash.is_some())),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render flash template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+/// Toggle memo completion from the web
+#[utoipa::path(
+    patch,
+    path = "/web/memos/{id}/toggle",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Rendered memo item", content_type = "text/html", body = String),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 409, description = "The memo has open blockers", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
 #[patch("/web/memos/{id}/toggle")]
 pub async fn toggle_memo_complete_web(
     state: web::Data<AppState>,
     path: web::Path<Uuid>,
+    locale: Locale,
 ) -> Result<HttpResponse, AppError> {
     let id = path.into_inner();
     tracing::debug!(memo_id = %id, "Toggling memo completion status");
 
-    let service = MemoService::new(state.db.clone());
-    let memo = service.toggle_complete(id).await?;
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    let mut memo = service.toggle_complete(id, Default::default()).await?;
+
+    sign_attachment_links(&state.url_signer, [&mut memo])?;
+    let template = MemoItemTemplate { memo, locale };
+
+    match template.render() {
+        Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render memo item template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+/// Toggle memo pin from the web
+#[utoipa::path(
+    patch,
+    path = "/web/memos/{id}/pin",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Rendered memo item", content_type = "text/html", body = String),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[patch("/web/memos/{id}/pin")]
+pub async fn toggle_memo_pinned_web(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+    tracing::debug!(memo_id = %id, "Toggling memo pin from web");
+
+    let mut memo = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .toggle_pinned(id)
+        .await?;
 
-    let template = MemoItemTemplate { memo };
+    sign_attachment_links(&state.url_signer, [&mut memo])?;
+    let template = MemoItemTemplate { memo, locale };
 
     match template.render() {
         Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
@@ -251,3 +937,541 @@ pub async fn toggle_memo_complete_web(
         }
     }
 }
+
+/// Toggle memo star from the web
+#[utoipa::path(
+    patch,
+    path = "/web/memos/{id}/star",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    responses(
+        (status = 200, description = "Rendered memo item", content_type = "text/html", body = String),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[patch("/web/memos/{id}/star")]
+pub async fn toggle_memo_starred_web(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+    tracing::debug!(memo_id = %id, "Toggling memo star from web");
+
+    let mut memo = MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .toggle_starred(id)
+        .await?;
+
+    sign_attachment_links(&state.url_signer, [&mut memo])?;
+    let template = MemoItemTemplate { memo, locale };
+
+    match template.render() {
+        Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render memo item template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct WebSnoozeForm {
+    pub preset: SnoozePreset,
+}
+
+/// Snooze a memo from the web
+#[utoipa::path(
+    post,
+    path = "/web/memos/{id}/snooze",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    request_body(content = WebSnoozeForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Rendered memo item", content_type = "text/html", body = String),
+        (status = 400, description = "The memo is completed", body = ErrorResponse),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[post("/web/memos/{id}/snooze")]
+pub async fn snooze_memo_web(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    form: web::Form<WebSnoozeForm>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+    tracing::debug!(memo_id = %id, preset = form.preset.as_str(), "Snoozing memo from web");
+
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    let dto = SnoozeMemoDto {
+        preset: Some(form.preset),
+        minutes: None,
+    };
+    let mut memo = service.snooze_memo(id, dto).await?;
+
+    sign_attachment_links(&state.url_signer, [&mut memo])?;
+    let template = MemoItemTemplate { memo, locale };
+
+    match template.render() {
+        Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render memo item template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct WebArchiveForm {
+    pub archived: bool,
+}
+
+/// Archives or unarchives a memo. The item leaves the current view either way,
+/// so the response is empty.
+#[utoipa::path(
+    post,
+    path = "/web/memos/{id}/archive",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    request_body(content = WebArchiveForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Archive state changed"),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[post("/web/memos/{id}/archive")]
+pub async fn archive_memo_web(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    form: web::Form<WebArchiveForm>,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+    tracing::debug!(memo_id = %id, archived = form.archived, "Changing memo archive state from web");
+
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    if form.archived {
+        service.archive_memo(id).await?;
+    } else {
+        service.unarchive_memo(id).await?;
+    }
+
+    Ok(HttpResponse::Ok().finish())
+}
+
+/// Reorder memos from the web
+#[utoipa::path(
+    post,
+    path = "/web/memos/reorder",
+    tag = "web",
+    request_body(content = WebReorderMemosForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 204, description = "Memos reordered"),
+        (status = 400, description = "Invalid memo id in order", body = ErrorResponse),
+        (status = 404, description = "A memo in the order does not exist", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[post("/web/memos/reorder")]
+pub async fn reorder_memos_web(
+    state: web::Data<AppState>,
+    form: web::Form<WebReorderMemosForm>,
+) -> Result<HttpResponse, AppError> {
+    tracing::debug!("Reordering memos from web");
+
+    let ids = form
+        .order
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(Uuid::parse_str)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| AppError::Validation("Invalid memo id in order".to_string()))?;
+
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    service.reorder_memos(ids).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Calendar page
+#[utoipa::path(
+    get,
+    path = "/web/calendar",
+    tag = "web",
+    params(
+        ("year" = Option<i32>, Query, description = "Year to show (default: current)"),
+        ("month" = Option<u32>, Query, description = "Month to show, 1 to 12 (default: current)")
+    ),
+    responses(
+        (status = 200, description = "Rendered calendar", content_type = "text/html", body = String),
+        (status = 400, description = "Year or month out of range", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[get("/web/calendar")]
+pub async fn get_calendar(
+    state: web::Data<AppState>,
+    query: web::Query<CalendarParams>,
+    flash: Option<FlashMessage>,
+    locale: Locale,
+    csp_nonce: CspNonce,
+) -> Result<HttpResponse, AppError> {
+    tracing::debug!("Rendering calendar page");
+
+    let calendar = load_calendar_month(&state, &query, locale).await?;
+    let template = CalendarTemplate {
+        calendar,
+        flash,
+        locale,
+        csp_nonce,
+    };
+
+    match template.render() {
+        Ok(html) => Ok(render_page(html, template.flash.is_some())),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render calendar template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+/// Calendar grid fragment
+#[utoipa::path(
+    get,
+    path = "/web/calendar/grid",
+    tag = "web",
+    params(
+        ("year" = Option<i32>, Query, description = "Year to show (default: current)"),
+        ("month" = Option<u32>, Query, description = "Month to show, 1 to 12 (default: current)")
+    ),
+    responses(
+        (status = 200, description = "Rendered month grid", content_type = "text/html", body = String),
+        (status = 400, description = "Year or month out of range", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[get("/web/calendar/grid")]
+pub async fn get_calendar_grid(
+    state: web::Data<AppState>,
+    query: web::Query<CalendarParams>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    tracing::debug!("Rendering calendar grid");
+
+    let calendar = load_calendar_month(&state, &query, locale).await?;
+    let template = CalendarGridTemplate { calendar, locale };
+
+    match template.render() {
+        Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render calendar grid template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+/// Kanban board page
+#[utoipa::path(
+    get,
+    path = "/web/board",
+    tag = "web",
+    responses(
+        (status = 200, description = "Rendered board with Overdue, Today, Upcoming and Done columns", content_type = "text/html", body = String),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[get("/web/board")]
+pub async fn get_board(
+    state: web::Data<AppState>,
+    flash: Option<FlashMessage>,
+    locale: Locale,
+    csp_nonce: CspNonce,
+) -> Result<HttpResponse, AppError> {
+    tracing::debug!("Rendering board page");
+
+    let board = load_board(&state).await?;
+    let template = BoardTemplate {
+        board,
+        flash,
+        locale,
+        csp_nonce,
+    };
+
+    match template.render() {
+        Ok(html) => Ok(render_page(html, template.flash.is_some())),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render board template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+/// Board columns fragment
+#[utoipa::path(
+    get,
+    path = "/web/board/columns",
+    tag = "web",
+    responses(
+        (status = 200, description = "Rendered board columns", content_type = "text/html", body = String),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[get("/web/board/columns")]
+pub async fn get_board_columns(
+    state: web::Data<AppState>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    tracing::debug!("Rendering board columns");
+
+    render_board_columns(load_board(&state).await?, locale)
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct WebBoardMoveForm {
+    /// Column the memo was dropped into; `overdue` is not accepted
+    pub column: BoardColumn,
+}
+
+/// Moves a memo to another board column: `done` completes it, `today` and
+/// `upcoming` reopen it and move its due date there. Answers with the
+/// re-rendered columns.
+#[utoipa::path(
+    post,
+    path = "/web/memos/{id}/column",
+    tag = "web",
+    params(
+        ("id" = Uuid, Path, description = "Memo ID")
+    ),
+    request_body(content = WebBoardMoveForm, content_type = "application/x-www-form-urlencoded"),
+    responses(
+        (status = 200, description = "Rendered board columns", content_type = "text/html", body = String),
+        (status = 400, description = "Moved to Overdue, or the due date does not exist locally", body = ErrorResponse),
+        (status = 404, description = "Memo not found", body = ErrorResponse),
+        (status = 409, description = "Completing the memo is blocked by open memos", body = ErrorResponse),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[post("/web/memos/{id}/column")]
+pub async fn move_memo_on_board_web(
+    state: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    form: web::Form<WebBoardMoveForm>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    let id = path.into_inner();
+    tracing::debug!(memo_id = %id, column = form.column.as_str(), "Moving memo on board");
+
+    let now = Utc::now().with_timezone(&ViewerTimezone::current());
+    MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .move_on_board(id, form.column, now)
+        .await?;
+
+    render_board_columns(load_board(&state).await?, locale)
+}
+
+async fn load_board(state: &AppState) -> Result<MemoBoard, AppError> {
+    let now = Utc::now().with_timezone(&ViewerTimezone::current());
+    MemoService::new(state.db.clone())
+        .with_cache(state.memo_cache.clone())
+        .get_board(now, BOARD_COLUMN_LIMIT)
+        .await
+}
+
+fn render_board_columns(board: MemoBoard, locale: Locale) -> Result<HttpResponse, AppError> {
+    let template = BoardColumnsTemplate { board, locale };
+
+    match template.render() {
+        Ok(html) => Ok(HttpResponse::Ok().content_type("text/html").body(html)),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render board columns template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
+
+/// Stores a success flash on `Ok` responses, or renders the error with an error flash.
+fn with_flash(
+    result: Result<HttpResponse, AppError>,
+    success_key: &str,
+    locale: Locale,
+) -> HttpResponse {
+    let (mut response, flash) = match result {
+        Ok(response) => (response, FlashMessage::success(locale.t(success_key))),
+        Err(err) => (
+            err.error_response(),
+            FlashMessage::error(err.localized_message(locale)),
+        ),
+    };
+    flash.attach(&mut response);
+    response
+}
+
+/// Parses a `datetime-local` form value as a local due date.
+fn parse_form_date(value: &str) -> Result<DueDate, AppError> {
+    chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+        .map(DueDate::Local)
+        .map_err(|_| {
+            AppError::Validation("Invalid date format. Expected YYYY-MM-DDTHH:MM".to_string())
+        })
+}
+
+/// The form's color label; the empty "no color" option means none.
+fn form_color(color: Option<&str>) -> Option<String> {
+    color
+        .map(str::trim)
+        .filter(|color| !color.is_empty())
+        .map(str::to_string)
+}
+
+/// Wraps rendered HTML, expiring the flash cookie if the page displayed it.
+fn render_page(html: String, shows_flash: bool) -> HttpResponse {
+    let mut response = HttpResponse::Ok().content_type("text/html").body(html);
+    if shows_flash {
+        FlashMessage::clear(&mut response);
+    }
+    response
+}
+
+/// The form's due date in the viewer's timezone: the free-text description
+/// when one was typed, else the `datetime-local` value.
+fn form_due_date(date_to: &str, date_to_text: Option<&str>) -> Result<DueDate, AppError> {
+    match date_to_text.map(str::trim).filter(|text| !text.is_empty()) {
+        Some(text) => {
+            let now = Utc::now().with_timezone(&ViewerTimezone::current());
+            DueDate::parse_natural(text, now).map_err(AppError::Validation)
+        }
+        None if date_to.is_empty() => Err(AppError::Validation(
+            "Enter a due date or describe one, e.g. 'tomorrow 5pm'".to_string(),
+        )),
+        None => parse_form_date(date_to),
+    }
+}
+
+/// Points the attachment links of `memos` at signed URLs, which pages embed
+/// directly; they stay valid for `SIGNED_URL_MAX_TTL_SECS`.
+fn sign_attachment_links<'a>(
+    signer: &UrlSigner,
+    memos: impl IntoIterator<Item = &'a mut MemoResponseDto>,
+) -> Result<(), AppError> {
+    let now = Utc::now();
+    for attachment in memos.into_iter().flat_map(|memo| &mut memo.attachments) {
+        attachment.url = signer.sign(&attachment.url, signer.max_ttl(), now)?.url;
+        attachment.thumbnail_url = signer
+            .sign(&attachment.thumbnail_url, signer.max_ttl(), now)?
+            .url;
+    }
+    Ok(())
+}
+
+/// Builds a Monday-first month grid, padded with days from the adjacent months.
+async fn load_calendar_month(
+    state: &AppState,
+    params: &CalendarParams,
+    locale: Locale,
+) -> Result<CalendarMonth, AppError> {
+    params.validate()?;
+
+    let today = Utc::now().date_naive();
+    let year = params.year.unwrap_or(today.year());
+    let month = params.month.unwrap_or(today.month());
+
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| AppError::Validation("Invalid calendar month".to_string()))?;
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    let (prev_year, prev_month) = if month == 1 {
+        (year - 1, 12)
+    } else {
+        (year, month - 1)
+    };
+    let next_first = NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .ok_or_else(|| AppError::Validation("Invalid calendar month".to_string()))?;
+
+    let grid_start = first - Days::new(first.weekday().num_days_from_monday().into());
+    let trailing = (7 - next_first.weekday().num_days_from_monday()) % 7;
+    let grid_end = next_first + Days::new(trailing.into());
+
+    let service = MemoService::new(state.db.clone()).with_cache(state.memo_cache.clone());
+    let mut memos_by_day = service.get_memos_by_day(grid_start, grid_end).await?;
+
+    let days: Vec<CalendarDay> = grid_start
+        .iter_days()
+        .take_while(|date| *date < grid_end)
+        .map(|date| CalendarDay {
+            date,
+            day: date.day(),
+            in_month: date.month() == month,
+            is_today: date == today,
+            memos: memos_by_day.remove(&date).unwrap_or_default(),
+        })
+        .collect();
+
+    let mut weeks = Vec::with_capacity(days.len() / 7);
+    let mut days = days.into_iter().peekable();
+    while days.peek().is_some() {
+        weeks.push(days.by_ref().take(7).collect());
+    }
+
+    Ok(CalendarMonth {
+        title: format!("{} {}", locale.t(MONTH_KEYS[month as usize - 1]), year),
+        weeks,
+        weekday_keys: WEEKDAY_KEYS,
+        prev_year,
+        prev_month,
+        next_year,
+        next_month,
+    })
+}
+
+/// Shows the newest notifications, unread ones highlighted.
+#[utoipa::path(
+    get,
+    path = "/web/notifications",
+    tag = "web",
+    responses(
+        (status = 200, description = "Rendered notifications page", content_type = "text/html", body = String),
+        (status = 500, description = "Internal server error", body = ErrorResponse)
+    )
+)]
+#[get("/web/notifications")]
+pub async fn get_notifications(
+    state: web::Data<AppState>,
+    flash: Option<FlashMessage>,
+    locale: Locale,
+) -> Result<HttpResponse, AppError> {
+    tracing::debug!("Rendering notifications page");
+
+    let page = NotificationService::new(state.db.clone())
+        .list(NotificationListParams {
+            limit: Some(50),
+            ..Default::default()
+        })
+        .await?;
+    let template = NotificationsTemplate {
+        notifications: page.data,
+        flash,
+        locale,
+    };
+
+    match template.render() {
+        Ok(html) => Ok(render_page(html, template.flash.is_some())),
+        Err(err) => {
+            tracing::error!(error = ?err, "Failed to render notifications template");
+            Err(AppError::Internal("Failed to render template".to_string()))
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use actix_web::{FromRequest, HttpMessage, HttpRequest, dev::Payload, http::header};
+use fluent_bundle::{FluentResource, concurrent::FluentBundle};
+use std::{
+    borrow::Cow,
+    convert::Infallible,
+    future::{Ready, ready},
+    sync::OnceLock,
+};
+
+pub mod timezone;
+
+pub use timezone::ViewerTimezone;
+
+const EN_CATALOG: &str = include_str!("../../locales/en.ftl");
+const ES_CATALOG: &str = include_str!("../../locales/es.ftl");
+
+tokio::task_local! {
+    static CURRENT_LOCALE: Locale;
+}
+
+/// Languages the UI and error messages are translated into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Es,
+}
+
+impl Locale {
+    pub const SUPPORTED: [Locale; 2] = [Locale::En, Locale::Es];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Es => "es",
+        }
+    }
+
+    /// Matches a language tag such as `es` or `es-MX` by its primary subtag.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_']).next()?.trim();
+        Self::SUPPORTED
+            .into_iter()
+            .find(|locale| locale.code().eq_ignore_ascii_case(primary))
+    }
+
+    /// Picks the best supported locale from an `Accept-Language` header value.
+    pub fn negotiate(accept_language: &str) -> Self {
+        let mut candidates: Vec<(&str, f32)> = accept_language
+            .split(',')
+            .filter_map(|entry| {
+                let mut parts = entry.split(';');
+                let tag = parts.next()?.trim();
+                let quality = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.trim().parse().ok())
+                    .unwrap_or(1.0);
+                (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        candidates
+            .into_iter()
+            .find_map(|(tag, _)| Self::from_tag(tag))
+            .unwrap_or_default()
+    }
+
+    pub fn for_request(req: &HttpRequest) -> Self {
+        if let Some(locale) = req.extensions().get::<Locale>() {
+            return *locale;
+        }
+
+        req.headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|value| value.to_str().ok())
+            .map(Self::negotiate)
+            .unwrap_or_default()
+    }
+
+    /// Looks up a message, falling back to English and then to the key itself.
+    pub fn t<'a>(&self, key: &'a str) -> Cow<'a, str> {
+        message(*self, key)
+            .or_else(|| message(Locale::En, key))
+            .unwrap_or(Cow::Borrowed(key))
+    }
+
+    /// Locale of the request currently being handled, if set by `LocaleNegotiation`.
+    pub fn current() -> Self {
+        CURRENT_LOCALE
+            .try_with(|locale| *locale)
+            .unwrap_or_default()
+    }
+
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        CURRENT_LOCALE.scope(self, fut).await
+    }
+
+    pub fn sync_scope<F: FnOnce() -> R, R>(self, f: F) -> R {
+        CURRENT_LOCALE.sync_scope(self, f)
+    }
+}
+
+impl FromRequest for Locale {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(Locale::for_request(req)))
+    }
+}
+
+type Bundle = FluentBundle<FluentResource>;
+
+fn bundle(locale: Locale) -> &'static Bundle {
+    static EN: OnceLock<Bundle> = OnceLock::new();
+    static ES: OnceLock<Bundle> = OnceLock::new();
+
+    match locale {
+        Locale::En => EN.get_or_init(|| load_bundle(locale, EN_CATALOG)),
+        Locale::Es => ES.get_or_init(|| load_bundle(locale, ES_CATALOG)),
+    }
+}
+
+/// Builds the Fluent bundle of a catalog; the catalogs are compiled in, so a
+/// syntax error is a bug caught by the tests.
+fn load_bundle(locale: Locale, source: &str) -> Bundle {
+    let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(|(_, errors)| {
+        panic!("invalid {} message catalog: {errors:?}", locale.code())
+    });
+    let language = locale
+        .code()
+        .parse()
+        .expect("locale codes are valid language tags");
+    let mut bundle = FluentBundle::new_concurrent(vec![language]);
+    // Messages end up in JSON, headers and emails, not only in HTML, so no
+    // Unicode isolation marks around placeables
+    bundle.set_use_isolating(false);
+    if let Err(errors) = bundle.add_resource(resource) {
+        panic!("duplicate {} messages: {errors:?}", locale.code());
+    }
+    bundle
+}
+
+fn message(locale: Locale, key: &str) -> Option<Cow<'static, str>> {
+    let bundle = bundle(locale);
+    let pattern = bundle.get_message(key)?.value()?;
+    let mut errors = Vec::new();
+    let text = bundle.format_pattern(pattern, None, &mut errors);
+    if !errors.is_empty() {
+        tracing::warn!(
+            locale = locale.code(),
+            key,
+            ?errors,
+            "Failed to format message"
+        );
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate_prefers_highest_quality() {
+        assert_eq!(Locale::negotiate("en;q=0.5, es-MX;q=0.9"), Locale::Es);
+        assert_eq!(Locale::negotiate("fr, es;q=0.1"), Locale::Es);
+    }
+
+    #[test]
+    fn test_negotiate_falls_back_to_english() {
+        assert_eq!(Locale::negotiate("fr-FR, de;q=0.8"), Locale::En);
+        assert_eq!(Locale::negotiate(""), Locale::En);
+        assert_eq!(Locale::negotiate("es;q=0"), Locale::En);
+    }
+
+    #[test]
+    fn test_translate_with_fallback() {
+        assert_eq!(Locale::Es.t("action-delete"), "Eliminar");
+        assert_eq!(Locale::En.t("action-delete"), "Delete");
+        assert_eq!(Locale::Es.t("missing-key"), "missing-key");
+    }
+
+    #[test]
+    fn test_catalogs_have_same_keys() {
+        let en = bundle(Locale::En);
+        let es = bundle(Locale::Es);
+        let mut missing: Vec<_> = FluentResource::try_new(EN_CATALOG.to_string())
+            .expect("valid English catalog")
+            .entries()
+            .filter_map(|entry| match entry {
+                fluent_syntax::ast::Entry::Message(message) => Some(message.id.name.to_string()),
+                _ => None,
+            })
+            .filter(|key| en.has_message(key) && !es.has_message(key))
+            .collect();
+        missing.sort();
+        assert!(missing.is_empty(), "missing Spanish keys: {:?}", missing);
+    }
+}
@@ -0,0 +1,123 @@
+use actix_web::{FromRequest, HttpMessage, HttpRequest, dev::Payload};
+use chrono::{DateTime, SecondsFormat, Utc};
+use chrono_tz::Tz;
+use serde::Serializer;
+use std::{
+    convert::Infallible,
+    future::{Ready, ready},
+};
+
+/// Request header carrying the viewer's IANA timezone, e.g. `Europe/Berlin`.
+pub const TIMEZONE_HEADER: &str = "Time-Zone";
+
+/// Cookie the web UI stores the browser's timezone in.
+pub const TIMEZONE_COOKIE: &str = "tz";
+
+tokio::task_local! {
+    static CURRENT_TIMEZONE: Tz;
+}
+
+/// Timezone due dates are rendered in for the current viewer: the
+/// `Time-Zone` header, else the `tz` cookie, else UTC. Unknown names are
+/// ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViewerTimezone(pub Tz);
+
+impl ViewerTimezone {
+    pub fn for_request(req: &HttpRequest) -> Self {
+        if let Some(timezone) = req.extensions().get::<ViewerTimezone>() {
+            return *timezone;
+        }
+
+        let header = req
+            .headers()
+            .get(TIMEZONE_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|name| parse(name).ok());
+        let cookie = || {
+            req.cookie(TIMEZONE_COOKIE)
+                .and_then(|cookie| parse(cookie.value()).ok())
+        };
+        Self(header.or_else(cookie).unwrap_or(Tz::UTC))
+    }
+
+    /// Timezone of the request currently being handled, if set by
+    /// `LocaleNegotiation`; UTC otherwise.
+    pub fn current() -> Tz {
+        CURRENT_TIMEZONE.try_with(|tz| *tz).unwrap_or(Tz::UTC)
+    }
+
+    pub async fn scope<F: Future>(self, fut: F) -> F::Output {
+        CURRENT_TIMEZONE.scope(self.0, fut).await
+    }
+
+    pub fn sync_scope<F: FnOnce() -> R, R>(self, f: F) -> R {
+        CURRENT_TIMEZONE.sync_scope(self.0, f)
+    }
+}
+
+impl FromRequest for ViewerTimezone {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(Ok(Self::for_request(req)))
+    }
+}
+
+/// Parses an IANA timezone name such as `America/New_York`.
+pub fn parse(name: &str) -> Result<Tz, String> {
+    name.trim()
+        .parse()
+        .map_err(|_| format!("Unknown timezone '{}'", name))
+}
+
+/// Serializes an instant as RFC 3339 in the viewer's timezone, with its
+/// offset (`Z` for UTC).
+pub fn serialize_in_viewer_timezone<S: Serializer>(
+    value: &DateTime<Utc>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let local = value.with_timezone(&ViewerTimezone::current());
+    serializer.serialize_str(&local.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{cookie::Cookie, test::TestRequest};
+
+    #[test]
+    fn test_header_wins_over_cookie() {
+        let req = TestRequest::default()
+            .insert_header((TIMEZONE_HEADER, "Asia/Tokyo"))
+            .cookie(Cookie::new(TIMEZONE_COOKIE, "Europe/Berlin"))
+            .to_http_request();
+        assert_eq!(ViewerTimezone::for_request(&req).0, Tz::Asia__Tokyo);
+
+        let req = TestRequest::default()
+            .insert_header((TIMEZONE_HEADER, "Mars/Olympus_Mons"))
+            .cookie(Cookie::new(TIMEZONE_COOKIE, "Europe/Berlin"))
+            .to_http_request();
+        assert_eq!(ViewerTimezone::for_request(&req).0, Tz::Europe__Berlin);
+
+        let req = TestRequest::default().to_http_request();
+        assert_eq!(ViewerTimezone::for_request(&req).0, Tz::UTC);
+    }
+
+    #[test]
+    fn test_instants_serialize_with_the_viewer_offset() {
+        let instant = "2025-07-01T10:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let json = |tz| {
+            ViewerTimezone(tz).sync_scope(|| {
+                let mut out = Vec::new();
+                serialize_in_viewer_timezone(&instant, &mut serde_json::Serializer::new(&mut out))
+                    .unwrap();
+                String::from_utf8(out).unwrap()
+            })
+        };
+
+        assert_eq!(json(Tz::UTC), "\"2025-07-01T10:00:00Z\"");
+        assert_eq!(json(Tz::Europe__Berlin), "\"2025-07-01T12:00:00+02:00\"");
+    }
+}
@@ -0,0 +1,7 @@
+//! Third-party chat integrations.
+
+pub mod webhooks;
+
+pub use webhooks::{
+    WebhookDelivery, WebhookEvent, WebhookMessage, WebhookProvider, WebhookQueue, WebhookSender,
+};
@@ -0,0 +1,451 @@
+//! Slack and Discord incoming webhooks.
+//!
+//! [`WebhookQueue::notify`] stores a job per integration subscribed to a memo
+//! event, so deliveries survive restarts and are spread over the job workers.
+//! A worker formats the message for the provider and posts it with
+//! [`WebhookSender`]; transient failures are retried with backoff, up to
+//! [`MAX_ATTEMPTS`] attempts. Posts carry no trace context headers, as the
+//! providers are third parties.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use thiserror::Error;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::GuardedConnection;
+use crate::jobs::{JobQueue, OperationKind};
+use crate::repository::WebhookIntegrationRepository;
+
+/// Attempts at posting a message before the delivery job fails.
+pub const MAX_ATTEMPTS: i32 = 5;
+/// Discord rejects embed titles longer than this.
+const DISCORD_TITLE_LIMIT: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookProvider {
+    Slack,
+    Discord,
+}
+
+impl WebhookProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookProvider::Slack => "slack",
+            WebhookProvider::Discord => "discord",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "slack" => Some(WebhookProvider::Slack),
+            "discord" => Some(WebhookProvider::Discord),
+            _ => None,
+        }
+    }
+
+    /// Whether `url` is an incoming-webhook URL issued by this provider.
+    ///
+    /// Only the providers' own hosts are accepted, so a registration cannot
+    /// point the server at internal addresses.
+    pub fn accepts_url(&self, url: &str) -> bool {
+        let Ok(url) = url::Url::parse(url) else {
+            return false;
+        };
+        if url.scheme() != "https" || url.port().is_some() {
+            return false;
+        }
+        let (hosts, path_prefix): (&[&str], &str) = match self {
+            WebhookProvider::Slack => (&["hooks.slack.com"], "/services/"),
+            WebhookProvider::Discord => (&["discord.com", "discordapp.com"], "/api/webhooks/"),
+        };
+        url.host_str().is_some_and(|host| hosts.contains(&host))
+            && url.path().starts_with(path_prefix)
+            && url.path().len() > path_prefix.len()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    /// A memo was created
+    MemoCreated,
+    /// An incomplete memo passed its due date
+    MemoOverdue,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::MemoCreated => "memo_created",
+            WebhookEvent::MemoOverdue => "memo_overdue",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "memo_created" => Some(WebhookEvent::MemoCreated),
+            "memo_overdue" => Some(WebhookEvent::MemoOverdue),
+            _ => None,
+        }
+    }
+
+    fn headline(&self) -> &'static str {
+        match self {
+            WebhookEvent::MemoCreated => "New memo",
+            WebhookEvent::MemoOverdue => "Memo overdue",
+        }
+    }
+}
+
+/// A memo event to post to every integration subscribed to it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookMessage {
+    pub event: WebhookEvent,
+    pub memo_id: Uuid,
+    pub title: String,
+    pub due: DateTime<Utc>,
+}
+
+impl WebhookMessage {
+    /// Formats the message as the provider's incoming-webhook payload.
+    /// `public_url` is the base URL for the link back to the app.
+    pub fn payload(&self, provider: WebhookProvider, public_url: &str) -> Value {
+        let due = self.due.format("%Y-%m-%d %H:%M UTC");
+        let link = format!("{}/", public_url.trim_end_matches('/'));
+
+        match provider {
+            WebhookProvider::Slack => {
+                let title = escape_slack(&self.title);
+                json!({
+                    "text": format!("{}: {}", self.event.headline(), title),
+                    "blocks": [{
+                        "type": "section",
+                        "text": {
+                            "type": "mrkdwn",
+                            "text": format!(
+                                "*{}*\n<{}|{}>\nDue {}",
+                                self.event.headline(),
+                                link,
+                                title,
+                                due
+                            ),
+                        },
+                    }],
+                })
+            }
+            WebhookProvider::Discord => {
+                let title: String = format!("{}: {}", self.event.headline(), self.title)
+                    .chars()
+                    .take(DISCORD_TITLE_LIMIT)
+                    .collect();
+                json!({
+                    "embeds": [{
+                        "title": title,
+                        "description": format!("Due {}", due),
+                        "url": link,
+                        "timestamp": self.due.to_rfc3339(),
+                    }],
+                    // Memo titles must never ping @everyone or a role.
+                    "allowed_mentions": { "parse": [] },
+                })
+            }
+        }
+    }
+}
+
+/// Slack treats `&`, `<` and `>` as control characters in message text.
+fn escape_slack(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[derive(Debug, Error)]
+pub enum DeliveryError {
+    /// Network error, rate limit or server error; worth retrying
+    #[error("transient failure: {0}")]
+    Transient(String),
+
+    /// The provider refused the request; retrying will not help
+    #[error("rejected: {0}")]
+    Rejected(String),
+}
+
+type PostFuture<'a> = Pin<Box<dyn Future<Output = Result<(), DeliveryError>> + Send + 'a>>;
+
+/// Object-safe HTTP client for posting webhook payloads.
+pub trait WebhookTransport: Send + Sync {
+    fn post<'a>(&'a self, url: &'a str, payload: &'a Value) -> PostFuture<'a>;
+}
+
+impl WebhookTransport for reqwest::Client {
+    fn post<'a>(&'a self, url: &'a str, payload: &'a Value) -> PostFuture<'a> {
+        Box::pin(async move {
+            let response = reqwest::Client::post(self, url)
+                .json(payload)
+                .send()
+                .await
+                .map_err(|e| DeliveryError::Transient(e.without_url().to_string()))?;
+            let status = response.status();
+            if status.is_success() {
+                Ok(())
+            } else if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                Err(DeliveryError::Transient(status.to_string()))
+            } else {
+                Err(DeliveryError::Rejected(status.to_string()))
+            }
+        })
+    }
+}
+
+/// Input of an [`OperationKind::DeliverWebhook`] job: `message` for one
+/// integration.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub integration_id: Uuid,
+    pub message: WebhookMessage,
+}
+
+/// Handle for queueing webhook messages; cheap to clone.
+#[derive(Clone)]
+pub struct WebhookQueue {
+    db: GuardedConnection,
+    jobs: JobQueue,
+}
+
+impl WebhookQueue {
+    pub fn new(db: GuardedConnection, jobs: JobQueue) -> Self {
+        Self { db, jobs }
+    }
+
+    /// Stores a delivery job for each integration subscribed to the event of
+    /// `message` and returns how many were queued. Failures are logged, not
+    /// returned; webhooks must never fail the memo change behind them.
+    #[tracing::instrument(skip(self, message), fields(event = message.event.as_str(), memo_id = %message.memo_id))]
+    pub async fn notify(&self, message: WebhookMessage) -> usize {
+        let integrations =
+            match WebhookIntegrationRepository::find_for_event(&self.db, message.event.as_str())
+                .await
+            {
+                Ok(integrations) => integrations,
+                Err(e) => {
+                    tracing::error!(error = %e, "Failed to load webhook integrations");
+                    return 0;
+                }
+            };
+
+        let mut queued = 0;
+        for integration in integrations {
+            let delivery = WebhookDelivery {
+                integration_id: integration.id,
+                message: message.clone(),
+            };
+            match self
+                .jobs
+                .submit(OperationKind::DeliverWebhook, &delivery, None)
+                .await
+            {
+                Ok(_) => queued += 1,
+                Err(e) => {
+                    tracing::error!(integration_id = %integration.id, error = %e, "Failed to queue webhook delivery")
+                }
+            }
+        }
+        queued
+    }
+}
+
+/// Posts queued deliveries; run by the job workers.
+#[derive(Clone)]
+pub struct WebhookSender {
+    transport: Arc<dyn WebhookTransport>,
+    /// Base URL for the links back to the app
+    public_url: String,
+}
+
+impl WebhookSender {
+    pub fn new(transport: impl WebhookTransport + 'static, public_url: String) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            public_url,
+        }
+    }
+
+    /// Posts `delivery`. An integration removed since it was queued is
+    /// skipped.
+    #[tracing::instrument(
+        name = "Webhook delivery",
+        skip(self, db, delivery),
+        fields(
+            integration_id = %delivery.integration_id,
+            event = delivery.message.event.as_str(),
+            memo_id = %delivery.message.memo_id,
+        )
+    )]
+    pub async fn deliver(
+        &self,
+        db: &GuardedConnection,
+        delivery: &WebhookDelivery,
+    ) -> Result<(), DeliveryError> {
+        let integration = WebhookIntegrationRepository::find_by_id(db, delivery.integration_id)
+            .await
+            .map_err(|e| DeliveryError::Transient(e.to_string()))?;
+        let Some(integration) = integration else {
+            tracing::info!("Webhook integration removed, skipping delivery");
+            return Ok(());
+        };
+        let provider = WebhookProvider::parse(&integration.provider).ok_or_else(|| {
+            DeliveryError::Rejected(format!("unknown provider '{}'", integration.provider))
+        })?;
+
+        let payload = delivery.message.payload(provider, &self.public_url);
+        self.transport.post(&integration.url, &payload).await?;
+        tracing::info!("Webhook delivered");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn message() -> WebhookMessage {
+        WebhookMessage {
+            event: WebhookEvent::MemoOverdue,
+            memo_id: Uuid::nil(),
+            title: "Pay <rent> & bills @everyone".to_string(),
+            due: Utc.with_ymd_and_hms(2025, 1, 31, 12, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_slack_payload_escapes_control_characters() {
+        let payload = message().payload(WebhookProvider::Slack, "https://memos.example.com/");
+
+        assert_eq!(
+            payload["text"],
+            "Memo overdue: Pay &lt;rent&gt; &amp; bills @everyone"
+        );
+        let block = payload["blocks"][0]["text"]["text"].as_str().unwrap();
+        assert!(block.contains("<https://memos.example.com/|Pay &lt;rent&gt;"));
+        assert!(block.contains("Due 2025-01-31 12:00 UTC"));
+    }
+
+    #[test]
+    fn test_discord_payload_disables_mentions() {
+        let payload = message().payload(WebhookProvider::Discord, "https://memos.example.com");
+
+        assert_eq!(
+            payload["embeds"][0]["title"],
+            "Memo overdue: Pay <rent> & bills @everyone"
+        );
+        assert_eq!(payload["embeds"][0]["url"], "https://memos.example.com/");
+        assert_eq!(payload["allowed_mentions"]["parse"], json!([]));
+    }
+
+    #[test]
+    fn test_discord_title_is_truncated() {
+        let mut message = message();
+        message.title = "x".repeat(500);
+
+        let payload = message.payload(WebhookProvider::Discord, "https://memos.example.com");
+
+        assert_eq!(
+            payload["embeds"][0]["title"]
+                .as_str()
+                .unwrap()
+                .chars()
+                .count(),
+            DISCORD_TITLE_LIMIT
+        );
+    }
+
+    #[test]
+    fn test_only_provider_webhook_urls_are_accepted() {
+        let slack = WebhookProvider::Slack;
+        let discord = WebhookProvider::Discord;
+
+        assert!(slack.accepts_url("https://hooks.slack.com/services/T000/B000/XXXX"));
+        assert!(discord.accepts_url("https://discord.com/api/webhooks/123/abc"));
+        assert!(discord.accepts_url("https://discordapp.com/api/webhooks/123/abc"));
+
+        assert!(!slack.accepts_url("http://hooks.slack.com/services/T000/B000/XXXX"));
+        assert!(!slack.accepts_url("https://hooks.slack.com/services/"));
+        assert!(!slack.accepts_url("https://hooks.slack.com:8443/services/T000/B000/XXXX"));
+        assert!(!slack.accepts_url("https://discord.com/api/webhooks/123/abc"));
+        assert!(!discord.accepts_url("https://discord.com.evil.example/api/webhooks/1/a"));
+        assert!(!discord.accepts_url("https://169.254.169.254/api/webhooks/1/a"));
+        assert!(!discord.accepts_url("not a url"));
+    }
+
+    /// Answers one request with `status` and returns its head, lowercased.
+    async fn serve_once(status: u16) -> (String, tokio::task::JoinHandle<String>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            while !String::from_utf8_lossy(&request).contains("\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let response =
+                format!("HTTP/1.1 {status} X\r\ncontent-length: 0\r\nconnection: close\r\n\r\n");
+            socket.write_all(response.as_bytes()).await.unwrap();
+            String::from_utf8_lossy(&request).to_lowercase()
+        });
+        (url, server)
+    }
+
+    #[tokio::test]
+    async fn test_posts_carry_no_trace_context() {
+        use opentelemetry::trace::TracerProvider;
+        use opentelemetry_sdk::propagation::TraceContextPropagator;
+        use opentelemetry_sdk::trace::SdkTracerProvider;
+        use tracing::Instrument;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        let tracer = SdkTracerProvider::builder().build().tracer("test");
+        let _guard = tracing::subscriber::set_default(
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer)),
+        );
+
+        let (url, server) = serve_once(204).await;
+        let client = reqwest::Client::new();
+        let result = WebhookTransport::post(&client, &url, &json!({ "text": "hi" }))
+            .instrument(tracing::info_span!("request"))
+            .await;
+
+        assert!(result.is_ok());
+        let request = server.await.unwrap();
+        assert!(request.starts_with("post /hook"));
+        assert!(!request.contains("traceparent"));
+        assert!(!request.contains("tracestate"));
+    }
+
+    #[tokio::test]
+    async fn test_only_server_errors_and_rate_limits_are_transient() {
+        let client = reqwest::Client::new();
+        for (status, transient) in [(503, true), (429, true), (404, false), (400, false)] {
+            let (url, server) = serve_once(status).await;
+            let result = WebhookTransport::post(&client, &url, &json!({})).await;
+            server.await.unwrap();
+            match result {
+                Err(DeliveryError::Transient(_)) => assert!(transient, "{status}"),
+                Err(DeliveryError::Rejected(_)) => assert!(!transient, "{status}"),
+                Ok(()) => panic!("{status} was accepted"),
+            }
+        }
+    }
+}
@@ -0,0 +1,369 @@
+//! Job queue behind long-running operations.
+//!
+//! Requests sent with `Prefer: respond-async` are stored as pending rows in
+//! the `operations` table and answered with `202 Accepted`. Workers claim the
+//! oldest pending row with `FOR UPDATE SKIP LOCKED`, so any number of workers
+//! and replicas can share the table, and record progress and the outcome on
+//! it. Submitting wakes a local worker right away; idle workers also poll, so
+//! operations submitted by another replica or left pending by a restart still
+//! run. An operation whose process died while running it stays `running`.
+//!
+//! Internal jobs such as webhook deliveries use the same table. Kinds with
+//! more than one attempt go back to `pending` after a transient failure and
+//! run again after an exponential backoff.
+
+use actix_web::ResponseError;
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::sync::Arc;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::config::settings::JobsConfig;
+use crate::db::GuardedConnection;
+use crate::dto::{BatchCompleteDto, BatchDeleteDto, MemoImportResult, OperationErrorDto};
+use crate::entities::operations;
+use crate::error::{AppError, ErrorCode};
+use crate::i18n::Locale;
+use crate::integrations::webhooks::{self, DeliveryError, WebhookDelivery, WebhookSender};
+use crate::repository::{MemoImportRepository, OperationRepository};
+use crate::services::{MemoCache, MemoService};
+
+/// The requests that can run as operations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    /// `PATCH /api/v1/memos/complete`; input is a `BatchCompleteDto`
+    CompleteMemos,
+    /// `POST /api/v1/memos/delete`; input is a `BatchDeleteDto`
+    DeleteMemos,
+    /// `POST /api/v1/memos/import/async`; input is an [`ImportMemosInput`]
+    ImportMemos,
+    /// A Slack/Discord post; input is a [`WebhookDelivery`]
+    DeliverWebhook,
+}
+
+impl OperationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperationKind::CompleteMemos => "complete_memos",
+            OperationKind::DeleteMemos => "delete_memos",
+            OperationKind::ImportMemos => "import_memos",
+            OperationKind::DeliverWebhook => "deliver_webhook",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "complete_memos" => Some(OperationKind::CompleteMemos),
+            "delete_memos" => Some(OperationKind::DeleteMemos),
+            "import_memos" => Some(OperationKind::ImportMemos),
+            "deliver_webhook" => Some(OperationKind::DeliverWebhook),
+            _ => None,
+        }
+    }
+
+    /// Attempts before a transient failure fails the operation. Requests made
+    /// by clients run once, as they may have done part of their work.
+    pub fn max_attempts(&self) -> i32 {
+        match self {
+            OperationKind::DeliverWebhook => webhooks::MAX_ATTEMPTS,
+            _ => 1,
+        }
+    }
+}
+
+/// Wait before the attempt after `attempts` failed ones: 30 seconds, doubling
+/// each time.
+const RETRY_BASE_DELAY_SECS: i64 = 30;
+
+fn retry_delay(attempts: i32) -> Duration {
+    Duration::seconds(RETRY_BASE_DELAY_SECS << (attempts.clamp(1, 10) - 1))
+}
+
+/// Why an attempt failed, and whether another one may succeed.
+enum JobError {
+    Transient(AppError),
+    Permanent(AppError),
+}
+
+impl From<AppError> for JobError {
+    fn from(error: AppError) -> Self {
+        JobError::Permanent(error)
+    }
+}
+
+/// Lines of an import file handled per transaction.
+pub const IMPORT_CHUNK_SIZE: usize = 500;
+
+/// Input of an [`OperationKind::ImportMemos`] operation.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportMemosInput {
+    /// Stored upload holding the file, one `CreateMemoDto` JSON object per line
+    pub import_id: Uuid,
+    /// Reject memos due in the past, as `POST /api/v1/memos` would
+    pub require_future_due_dates: bool,
+}
+
+/// Handle for submitting operations; cheap to clone.
+#[derive(Clone)]
+pub struct JobQueue {
+    db: GuardedConnection,
+    wake: Arc<Notify>,
+}
+
+/// What jobs need besides the database.
+struct JobContext {
+    db: GuardedConnection,
+    undo_window: Duration,
+    memo_cache: MemoCache,
+    /// Posts webhook deliveries; without one they fail
+    webhooks: Option<WebhookSender>,
+}
+
+impl JobQueue {
+    /// Spawns `config.workers` workers. They run until their handles are
+    /// aborted; a job in progress is cancelled at its next await point.
+    pub fn start(
+        db: GuardedConnection,
+        config: &JobsConfig,
+        undo_window: Duration,
+        memo_cache: MemoCache,
+        webhooks: Option<WebhookSender>,
+    ) -> (Self, Vec<JoinHandle<()>>) {
+        let wake = Arc::new(Notify::new());
+        let ctx = Arc::new(JobContext {
+            db: db.clone(),
+            undo_window,
+            memo_cache,
+            webhooks,
+        });
+        let poll_interval = std::time::Duration::from_millis(config.poll_interval_ms);
+
+        let workers = (0..config.workers)
+            .map(|_| tokio::spawn(work_loop(ctx.clone(), wake.clone(), poll_interval)))
+            .collect();
+
+        (Self { db, wake }, workers)
+    }
+
+    /// Stores a pending operation for `input` and wakes a worker.
+    #[tracing::instrument(skip(self, input), fields(kind = kind.as_str()))]
+    pub async fn submit(
+        &self,
+        kind: OperationKind,
+        input: &impl Serialize,
+        total: Option<usize>,
+    ) -> Result<operations::Model, AppError> {
+        let input = serde_json::to_value(input)
+            .map_err(|e| AppError::Internal(format!("Failed to store operation input: {}", e)))?;
+
+        let operation =
+            OperationRepository::create(&self.db, kind.as_str(), input, total.map(|n| n as i64))
+                .await?;
+        self.wake.notify_one();
+
+        tracing::info!(operation_id = %operation.id, "Operation submitted");
+        Ok(operation)
+    }
+}
+
+async fn work_loop(ctx: Arc<JobContext>, wake: Arc<Notify>, poll_interval: std::time::Duration) {
+    loop {
+        match OperationRepository::claim_next(&ctx.db).await {
+            Ok(Some(operation)) => {
+                run(&ctx, operation).await;
+                continue;
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!(error = %e, "Failed to claim an operation"),
+        }
+
+        tokio::select! {
+            _ = wake.notified() => {}
+            _ = tokio::time::sleep(poll_interval) => {}
+        }
+    }
+}
+
+/// Runs a claimed operation and stores its result or error, or schedules
+/// another attempt.
+#[tracing::instrument(skip(ctx, operation), fields(operation_id = %operation.id, kind = %operation.kind, attempt = operation.attempts))]
+async fn run(ctx: &JobContext, operation: operations::Model) {
+    let id = operation.id;
+    let attempts = operation.attempts;
+    let max_attempts = OperationKind::parse(&operation.kind).map_or(1, |kind| kind.max_attempts());
+    let stored = match execute(ctx, operation).await {
+        Ok(result) => {
+            tracing::info!("Operation succeeded");
+            OperationRepository::succeed(&ctx.db, id, result).await
+        }
+        Err(JobError::Transient(e)) if attempts < max_attempts => {
+            let delay = retry_delay(attempts);
+            tracing::warn!(error = %e, retry_in_secs = delay.num_seconds(), "Operation failed, retrying later");
+            OperationRepository::retry_later(&ctx.db, id, Utc::now() + delay, error_json(&e)).await
+        }
+        Err(JobError::Transient(e) | JobError::Permanent(e)) => {
+            tracing::warn!(error = %e, "Operation failed");
+            OperationRepository::fail(&ctx.db, id, error_json(&e)).await
+        }
+    };
+
+    if let Err(e) = stored {
+        tracing::error!(error = %e, "Failed to store operation outcome");
+    }
+}
+
+fn error_json(e: &AppError) -> serde_json::Value {
+    serde_json::json!(OperationErrorDto {
+        code: e.code().as_str().to_string(),
+        message: e.localized_message(Locale::default()),
+        status: e.status_code().as_u16(),
+    })
+}
+
+async fn execute(
+    ctx: &JobContext,
+    operation: operations::Model,
+) -> Result<serde_json::Value, JobError> {
+    let kind = OperationKind::parse(&operation.kind).ok_or_else(|| {
+        AppError::Internal(format!("Unknown operation kind '{}'", operation.kind))
+    })?;
+    let service = MemoService::new(ctx.db.clone()).with_cache(ctx.memo_cache.clone());
+
+    let progress = Progress {
+        db: &ctx.db,
+        id: operation.id,
+        total: operation.total,
+    };
+
+    // Both batches run in a single transaction, so they report once, when
+    // done; imports report after each chunk.
+    match kind {
+        OperationKind::CompleteMemos => {
+            let result = service
+                .complete_memos(input::<BatchCompleteDto>(&operation)?)
+                .await?;
+            let processed = result.updated.len() + result.not_found.len() + result.blocked.len();
+            progress.report(processed, None).await;
+            output(&result)
+        }
+        OperationKind::DeleteMemos => {
+            let result = service
+                .delete_memos(input::<BatchDeleteDto>(&operation)?, ctx.undo_window)
+                .await?;
+            let processed = result.deleted.len() + result.not_found.len();
+            progress.report(processed, None).await;
+            output(&result)
+        }
+        OperationKind::ImportMemos => {
+            let job = input::<ImportMemosInput>(&operation)?;
+            let result = import_memos(ctx, &progress, job).await?;
+            output(&result)
+        }
+        OperationKind::DeliverWebhook => {
+            let sender = ctx.webhooks.as_ref().ok_or_else(|| {
+                AppError::FeatureDisabled("Webhook delivery is not configured".to_string())
+            })?;
+            let delivery = input::<WebhookDelivery>(&operation)?;
+            sender
+                .deliver(&ctx.db, &delivery)
+                .await
+                .map_err(|e| match e {
+                    DeliveryError::Transient(_) => JobError::Transient(delivery_error(e)),
+                    DeliveryError::Rejected(_) => JobError::Permanent(delivery_error(e)),
+                })?;
+            output(&serde_json::json!({ "delivered": true }))
+        }
+    }
+}
+
+fn delivery_error(e: DeliveryError) -> AppError {
+    AppError::Internal(format!("Webhook delivery failed: {}", e))
+}
+
+/// Creates the memos of an uploaded file chunk by chunk, reporting progress
+/// after each. The upload is removed once the job ends, whether or not it
+/// got through the whole file, since failed operations are not retried.
+async fn import_memos(
+    ctx: &JobContext,
+    progress: &Progress<'_>,
+    job: ImportMemosInput,
+) -> Result<MemoImportResult, AppError> {
+    let upload = MemoImportRepository::find_by_id(&ctx.db, job.import_id)
+        .await?
+        .ok_or_else(|| {
+            AppError::not_found(
+                ErrorCode::ImportNotFound,
+                format!("Import file {} not found", job.import_id),
+            )
+        })?;
+    let service = MemoService::new(ctx.db.clone())
+        .with_cache(ctx.memo_cache.clone())
+        .require_future_due_dates(job.require_future_due_dates);
+
+    let result = import_lines(&service, progress, &upload.content).await;
+    MemoImportRepository::delete(&ctx.db, upload.id).await?;
+    result
+}
+
+async fn import_lines(
+    service: &MemoService,
+    progress: &Progress<'_>,
+    content: &str,
+) -> Result<MemoImportResult, AppError> {
+    let lines: Vec<(i32, &str)> = content
+        .lines()
+        .zip(1..)
+        .filter(|(text, _)| !text.trim().is_empty())
+        .map(|(text, line)| (line, text))
+        .collect();
+
+    let mut result = MemoImportResult::default();
+    for chunk in lines.chunks(IMPORT_CHUNK_SIZE) {
+        let (imported, failed) = service.import_chunk(progress.id, chunk).await?;
+        result.imported += imported;
+        result.failed += failed;
+        let last_line = chunk.last().map(|(line, _)| format!("line {}", line));
+        progress
+            .report(result.imported + result.failed, last_line.as_deref())
+            .await;
+    }
+
+    Ok(result)
+}
+
+/// Lets a running job record how far it got, for status requests and the
+/// progress stream.
+struct Progress<'a> {
+    db: &'a GuardedConnection,
+    id: Uuid,
+    /// Items to handle, when known up front
+    total: Option<i64>,
+}
+
+impl Progress<'_> {
+    /// Records `processed` items and the one being worked on now. Jobs that
+    /// learn their size as they go count it as the total. Failing to record
+    /// progress does not fail the job.
+    async fn report(&self, processed: usize, current_item: Option<&str>) {
+        let processed = processed as i64;
+        let total = self.total.or(Some(processed));
+        if let Err(e) =
+            OperationRepository::set_progress(self.db, self.id, processed, total, current_item)
+                .await
+        {
+            tracing::warn!(operation_id = %self.id, error = %e, "Failed to record operation progress");
+        }
+    }
+}
+
+fn input<T: DeserializeOwned>(operation: &operations::Model) -> Result<T, AppError> {
+    serde_json::from_value(operation.input.clone())
+        .map_err(|e| AppError::Internal(format!("Unreadable operation input: {}", e)))
+}
+
+fn output(result: &impl Serialize) -> Result<serde_json::Value, JobError> {
+    serde_json::to_value(result)
+        .map_err(|e| AppError::Internal(format!("Failed to store operation result: {}", e)).into())
+}
@@ -1,12 +1,25 @@
+pub mod assets;
+pub mod attachments;
+pub mod auth;
 pub mod config;
+pub mod crypto;
+pub mod db;
 pub mod docs;
 pub mod dto;
 pub mod entities;
 pub mod error;
+pub mod events;
+pub mod flash;
 pub mod handlers;
+pub mod i18n;
+pub mod integrations;
+pub mod jobs;
 pub mod middleware;
+pub mod notifications;
 pub mod observability;
 pub mod repository;
+pub mod scheduler;
+pub mod seed;
 pub mod services;
 pub mod state;
 pub mod utils;
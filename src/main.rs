@@ -1,25 +1,53 @@
-use actix_cors::Cors;
-use actix_governor::{Governor, GovernorConfigBuilder};
 use actix_web::{
-    App, HttpServer,
-    middleware::{Compress, Logger},
+    App, HttpServer, guard,
+    middleware::{Compress, Condition, Logger},
     web,
 };
-use actix_web_prom::PrometheusMetricsBuilder;
 use actix_web_template::{
-    config::Settings, docs::ApiDoc, handlers, middleware::SecurityHeaders,
-    observability::tracing::init_tracing_with_otlp, state::AppState,
+    config::Settings,
+    db::{
+        CircuitBreaker, GuardedConnection, ReadReplicas, RetryPolicy, SlowQueryLog,
+        connect_options, connect_with_retry,
+    },
+    docs::{ApiDoc, SECTIONS},
+    error::extractors,
+    events, handlers,
+    integrations::{WebhookQueue, WebhookSender},
+    jobs::JobQueue,
+    middleware::{
+        ApiKeyAuth, AssignRequestId, CacheHeaders, Chaos, ContentSecurityPolicy, DatabaseCircuit,
+        HideManagementRoutes, LocaleNegotiation, ObservabilityAuth, PanicCapture, RateLimit,
+        RequestTimeout, ResponseValidation, SecurityHeaders, cors,
+    },
+    notifications::{
+        email::{EmailQueue, smtp_transport},
+        push::WebPushSender,
+    },
+    observability::{
+        exemplars::TraceExemplars, metrics::prometheus_metrics, trace_context::TraceContext,
+        tracing::init_tracing_with_otlp,
+    },
+    scheduler::{
+        Schedule, Scheduler,
+        tasks::{
+            CompletedMemoPurge, DeletedMemoFinalizer, DuePushNotifications, DueReminders,
+            OperationCleanup, OutboxRelay, OverdueWebhooks, ReminderEmail,
+        },
+    },
+    services::MemoCache,
+    state::AppState,
 };
-use sea_orm::{ConnectOptions, Database};
+use listenfd::ListenFd;
 use std::time::Duration;
 use utoipa::OpenApi;
-use utoipa_swagger_ui::SwaggerUi;
+use utoipa_swagger_ui::{SwaggerUi, Url};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let settings = Settings::load()?;
 
     let otlp_endpoint = std::env::var("OTLP_ENDPOINT").ok();
+    let traces_exported = otlp_endpoint.is_some();
     init_tracing_with_otlp("memos-api", otlp_endpoint)
         .map_err(|e| anyhow::anyhow!("Failed to initialize tracing: {}", e))?;
 
@@ -31,6 +59,13 @@ async fn main() -> anyhow::Result<()> {
 
     settings.validate()?;
 
+    if actix_web_template::crypto::init(&settings.encryption)? {
+        tracing::info!("Memo descriptions are encrypted at rest");
+    }
+    if actix_web_template::utils::sanitize::init(&settings.sanitize) {
+        tracing::info!(tags = ?settings.sanitize.allowed_tags, "Using the configured HTML allowlist");
+    }
+
     tracing::info!(
         url = %settings.database.url.split('@').next_back().unwrap_or("***"),
         max_connections = settings.database.max_connections,
@@ -38,106 +73,513 @@ async fn main() -> anyhow::Result<()> {
         "Connecting to database with tuned connection pool"
     );
 
-    let mut opt = ConnectOptions::new(&settings.database.url);
-    opt.max_connections(settings.database.max_connections)
-        .min_connections(2)
-        .connect_timeout(Duration::from_secs(settings.database.connect_timeout))
-        .acquire_timeout(Duration::from_secs(settings.database.connect_timeout))
-        .idle_timeout(Duration::from_secs(300))
-        .max_lifetime(Duration::from_secs(1800))
-        .sqlx_logging(true)
-        .sqlx_logging_level(tracing::log::LevelFilter::Debug);
-
-    let db = Database::connect(opt).await?;
+    let opt = connect_options(&settings.database.url, &settings.database);
+    let retry_policy = RetryPolicy::from_config(&settings.database);
+    let mut db = connect_with_retry(opt, &retry_policy).await?;
     tracing::info!("Database connection established with optimized pool settings");
 
+    let mut replicas = ReadReplicas::connect(&settings.database).await?;
+    if !replicas.is_empty() {
+        tracing::info!(
+            replicas = replicas.len(),
+            "Routing memo reads to read replicas"
+        );
+    }
+
     tracing::info!("Initializing Prometheus metrics exporter");
-    let prometheus = PrometheusMetricsBuilder::new("actix_web")
-        .endpoint("/metrics")
-        .build()
-        .unwrap();
-
-    let state = AppState::new(settings.clone(), db);
-
-    let bind_address = format!("{}:{}", settings.server.host, settings.server.port);
-    tracing::info!(address = %bind_address, "Starting HTTP server");
-
-    tracing::info!("Configuring rate limiting: 100 requests per minute per IP");
-    let governor_conf = GovernorConfigBuilder::default()
-        .milliseconds_per_request(600)
-        .burst_size(100)
-        .finish()
-        .unwrap();
-
-    HttpServer::new(move || {
-        let rate_limiter = Governor::new(&governor_conf);
-        let cors = if state.config.cors.allowed_origins.len() == 1
-            && state.config.cors.allowed_origins[0] == "*"
-        {
-            Cors::permissive()
-        } else {
-            let mut cors = Cors::default();
-            for origin in &state.config.cors.allowed_origins {
-                cors = cors.allowed_origin(origin.as_str());
+    let prometheus = prometheus_metrics(&settings.observability)?;
+    let trace_exemplars = TraceExemplars::new(&prometheus, &settings.observability);
+    let slow_queries =
+        SlowQueryLog::from_config(&settings.database).with_metrics(&prometheus.registry)?;
+    if slow_queries.is_enabled() {
+        tracing::info!(
+            threshold_ms = settings.database.slow_query_ms,
+            "Logging slow database queries"
+        );
+        slow_queries.install(&mut db);
+        replicas = replicas.with_slow_query_log(&slow_queries);
+    }
+    // Shared by requests, jobs and scheduled tasks, so all of their calls
+    // count towards opening the circuit
+    let db_circuit =
+        CircuitBreaker::from_config(&settings.database).with_metrics(&prometheus.registry)?;
+    let db = GuardedConnection::new(db, db_circuit);
+    if traces_exported {
+        tracing::info!("Attaching trace exemplars to request latency metrics");
+    }
+    let observability_auth = ObservabilityAuth::from_config(&settings.observability);
+    if observability_auth.is_enabled() {
+        tracing::info!("Requiring credentials for /metrics and the API docs");
+    } else if settings.is_production() {
+        tracing::warn!(
+            "/metrics and the API docs are public; set OBSERVABILITY_AUTH_TOKEN or OBSERVABILITY_BASIC_AUTH"
+        );
+    }
+
+    let request_timeout =
+        RequestTimeout::from_config(&settings.server).with_metrics(&prometheus.registry)?;
+    tracing::info!(
+        default_ms = settings.server.request_timeout_ms,
+        overrides = settings.server.request_timeout_overrides.len(),
+        "Configured request timeouts"
+    );
+
+    let panic_capture = PanicCapture::new().with_metrics(&prometheus.registry)?;
+
+    let response_validation = if settings.api.validate_responses {
+        tracing::info!("Validating JSON responses against the OpenAPI document");
+        ResponseValidation::new(&ApiDoc::openapi())?
+    } else {
+        ResponseValidation::default()
+    };
+
+    let security_headers =
+        SecurityHeaders::from_config(&settings.security).map_err(anyhow::Error::msg)?;
+    let cache_headers =
+        CacheHeaders::from_config(&settings.http_cache).map_err(anyhow::Error::msg)?;
+
+    let chaos = Chaos::from_config(&settings.chaos);
+    if settings.chaos.enabled {
+        tracing::warn!(
+            path_prefix = %settings.chaos.path_prefix,
+            latency_percent = settings.chaos.latency_percent,
+            latency_ms = settings.chaos.latency_ms,
+            error_percent = settings.chaos.error_percent,
+            drop_percent = settings.chaos.drop_percent,
+            "Chaos fault injection enabled"
+        );
+    }
+
+    let email_queue = match smtp_transport(&settings.email)? {
+        Some(transport) => {
+            tracing::info!(
+                host = settings.email.smtp_host.as_deref().unwrap_or_default(),
+                "Email notifications enabled"
+            );
+            Some(EmailQueue::start(transport, settings.email.queue_capacity).0)
+        }
+        None => {
+            tracing::info!("Email notifications disabled (SMTP_HOST not set)");
+            None
+        }
+    };
+
+    let push_sender = WebPushSender::from_config(&settings.push)?;
+    if push_sender.is_some() {
+        tracing::info!("Web push notifications enabled");
+    } else {
+        tracing::info!("Web push notifications disabled (VAPID_PRIVATE_KEY not set)");
+    }
+
+    let webhook_client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(settings.webhooks.timeout_secs))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+    let webhook_sender = WebhookSender::new(webhook_client, settings.app.public_url.clone());
+
+    let memo_cache =
+        MemoCache::from_config(&settings.memo_cache).with_metrics(&prometheus.registry)?;
+    let (jobs, job_workers) = JobQueue::start(
+        db.clone(),
+        &settings.jobs,
+        settings.undo.window(),
+        memo_cache.clone(),
+        Some(webhook_sender),
+    );
+    tracing::info!(workers = settings.jobs.workers, "Started operation workers");
+    let webhooks = WebhookQueue::new(db.clone(), jobs.clone());
+
+    let event_publisher = events::publisher::connect(&settings.events).await?;
+    if event_publisher.is_some() {
+        events::enable();
+        tracing::info!(
+            broker = ?settings.events.broker,
+            topic = %settings.events.topic,
+            "Domain event publishing enabled"
+        );
+    }
+
+    let scheduler = if settings.scheduler.enabled {
+        let mut due_reminders = DueReminders::new(
+            db.clone(),
+            chrono::Duration::minutes(settings.scheduler.reminder_lead_minutes as i64),
+        );
+        if let (Some(queue), Some(to)) = (&email_queue, &settings.email.reminder_to) {
+            due_reminders = due_reminders.with_email(ReminderEmail {
+                queue: queue.clone(),
+                from: settings.email.from.parse()?,
+                to: to.parse()?,
+                public_url: settings.app.public_url.clone(),
+            });
+        }
+
+        let mut scheduler = Scheduler::new()
+            .with_metrics(&prometheus.registry)?
+            .register(
+                Schedule::cron(&settings.scheduler.due_reminders_cron)?,
+                due_reminders,
+            );
+        if let Some(sender) = push_sender {
+            scheduler = scheduler.register(
+                Schedule::cron(&settings.push.due_cron)?,
+                DuePushNotifications::new(db.clone(), sender, settings.app.public_url.clone()),
+            );
+        }
+        scheduler = scheduler.register(
+            Schedule::cron(&settings.webhooks.overdue_cron)?,
+            OverdueWebhooks::new(db.clone(), webhooks.clone()),
+        );
+        if let Some(publisher) = event_publisher {
+            scheduler = scheduler.register(
+                Schedule::every(Duration::from_millis(settings.events.relay_interval_ms)),
+                OutboxRelay::new(db.clone(), publisher, &settings.events)
+                    .with_metrics(&prometheus.registry)?,
+            );
+        }
+        if let Some(purge) = CompletedMemoPurge::from_config(db.clone(), &settings.retention) {
+            scheduler = scheduler.register(
+                Schedule::cron(&settings.retention.cron)?,
+                purge.with_metrics(&prometheus.registry)?,
+            );
+        }
+        scheduler = scheduler.register(
+            Schedule::cron(&settings.undo.finalize_cron)?,
+            DeletedMemoFinalizer::new(db.clone()),
+        );
+        scheduler = scheduler.register(
+            Schedule::every(Duration::from_secs(3600)),
+            OperationCleanup::new(db.clone()),
+        );
+        tracing::info!(tasks = ?scheduler.task_names(), "Starting task scheduler");
+        Some(scheduler.start())
+    } else {
+        tracing::info!("Task scheduler disabled");
+        None
+    };
+
+    let state = AppState::with_guarded_db(settings.clone(), db)
+        .with_replicas(replicas)
+        .with_memo_cache(memo_cache)
+        .with_webhooks(webhooks)
+        .with_jobs(jobs);
+    if scheduler.is_some() {
+        state.startup.mark_scheduler_started();
+    }
+    let api_keys = state.api_keys.clone();
+    let usage_flusher = (!api_keys.is_empty()).then(|| {
+        tracing::info!(
+            keys = settings.api_keys.keys.len(),
+            flush_secs = settings.api_keys.usage_flush_secs,
+            "API keys enabled"
+        );
+        api_keys.start_flusher(Duration::from_secs(settings.api_keys.usage_flush_secs))
+    });
+    if state.maintenance.is_enabled() {
+        tracing::warn!("Starting in maintenance mode");
+    }
+
+    // A socket passed in by systemd socket activation or `systemfd` survives
+    // restarts of the process, so connections queue instead of being refused.
+    let inherited_listener = ListenFd::from_env().take_tcp_listener(0)?;
+    let bind_address = match &inherited_listener {
+        Some(listener) => listener.local_addr()?.to_string(),
+        None => format!("{}:{}", settings.server.host, settings.server.port),
+    };
+    tracing::info!(
+        address = %bind_address,
+        inherited = inherited_listener.is_some(),
+        workers = settings.server.workers,
+        max_connections = settings.server.max_connections,
+        "Starting HTTP server"
+    );
+
+    let rate_limit = RateLimit::connect(&settings.rate_limit)
+        .await
+        .with_metrics(&prometheus.registry)?;
+    tracing::info!(
+        read_per_minute = settings.rate_limit.read.per_minute,
+        write_per_minute = settings.rate_limit.write.per_minute,
+        web_form_per_minute = settings.rate_limit.web_form.per_minute,
+        distributed = rate_limit.is_distributed(),
+        "Configured rate limiting"
+    );
+    {
+        let rate_limit = rate_limit.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+            loop {
+                interval.tick().await;
+                rate_limit.retain_recent();
             }
-            cors.allowed_methods(vec!["GET", "POST", "PUT", "DELETE", "PATCH"])
-                .allowed_headers(vec![
-                    actix_web::http::header::AUTHORIZATION,
-                    actix_web::http::header::ACCEPT,
-                    actix_web::http::header::CONTENT_TYPE,
-                ])
-                .max_age(3600)
-        };
+        });
+    }
+
+    if settings.api.enable_swagger {
+        tracing::info!("Swagger UI enabled at /swagger-ui/");
+    } else {
+        tracing::info!("Swagger UI disabled (ENABLE_SWAGGER=false)");
+    }
+    if let Some(ui) = settings.api.docs_ui {
+        tracing::info!(?ui, "API reference enabled at /docs");
+        let csp = ContentSecurityPolicy::from_config(&settings.security.csp)
+            .map_err(anyhow::Error::msg)?;
+        if !csp.allows_script(&settings.api.docs_script_url) {
+            tracing::warn!(
+                script_url = %settings.api.docs_script_url,
+                "CSP script-src blocks the /docs renderer; add its origin to CSP_SCRIPT_SRC"
+            );
+        }
+    }
+
+    let mount_test_routes = settings.is_development() && cfg!(feature = "dev-routes");
+    if mount_test_routes {
+        tracing::info!("Mounting /test/* diagnostics routes (development only)");
+    }
 
-        let openapi = ApiDoc::openapi();
+    let management_server = match settings.server.management_port {
+        Some(port) => {
+            let address = format!("{}:{}", settings.server.management_host, port);
+            tracing::info!(
+                %address,
+                "Serving probes, /metrics and the admin API on the management listener"
+            );
+            let state = state.clone();
+            let prometheus = prometheus.clone();
+            let observability_auth = observability_auth.clone();
+            let trace_exemplars = trace_exemplars.clone();
+            let panic_capture = panic_capture.clone();
+            let server = HttpServer::new(move || {
+                App::new()
+                    .app_data(web::Data::new(state.clone()))
+                    .app_data(extractors::query_config())
+                    .app_data(extractors::path_config())
+                    .wrap(panic_capture.clone())
+                    .wrap(prometheus.clone())
+                    .wrap(Condition::new(
+                        observability_auth.is_enabled(),
+                        observability_auth.clone(),
+                    ))
+                    .wrap(Condition::new(traces_exported, trace_exemplars.clone()))
+                    .wrap(Logger::default())
+                    .wrap(AssignRequestId)
+                    .service(
+                        extractors::limited_scope(state.config.api.body_limits.api)
+                            .configure(configure_management),
+                    )
+                    .default_service(web::to(handlers::fallback))
+            })
+            .workers(1)
+            .bind(&address)?;
+            Some(server.run())
+        }
+        None => None,
+    };
+    let public_management = management_server.is_none();
 
+    let server = HttpServer::new(move || {
+        let limits = state.config.api.body_limits;
         App::new()
             .app_data(web::Data::new(state.clone()))
-            .app_data(web::JsonConfig::default().limit(state.config.api.max_request_size))
-            .app_data(web::PayloadConfig::default().limit(state.config.api.max_request_size))
+            .app_data(extractors::query_config())
+            .app_data(extractors::path_config())
+            .wrap(panic_capture.clone())
+            .wrap(Condition::new(
+                state.config.api.validate_responses,
+                response_validation.clone(),
+            ))
+            .wrap(Condition::new(state.config.chaos.enabled, chaos.clone()))
+            .wrap(request_timeout.clone())
             .wrap(prometheus.clone())
+            .wrap(Condition::new(
+                observability_auth.is_enabled(),
+                observability_auth.clone(),
+            ))
+            .wrap(Condition::new(traces_exported, trace_exemplars.clone()))
+            .wrap(Condition::new(!public_management, HideManagementRoutes))
+            .wrap(Condition::new(traces_exported, TraceContext))
+            .wrap(cache_headers.clone())
+            .wrap(DatabaseCircuit::new(state.db.breaker().clone()))
             .wrap(Compress::default())
-            .wrap(SecurityHeaders)
-            .wrap(rate_limiter)
-            .wrap(cors)
+            .wrap(state.maintenance.clone())
+            .wrap(security_headers.clone())
+            .wrap(rate_limit.clone())
+            .wrap(ApiKeyAuth::new(state.api_keys.clone()))
+            // Outside the limiter and key checks so their errors are
+            // translated too
+            .wrap(LocaleNegotiation)
+            .wrap(cors::build(&state.config.cors))
             .wrap(Logger::default())
-            .service(actix_files::Files::new("/static", "./static").show_files_listing())
+            .wrap(AssignRequestId)
+            .service(handlers::static_asset)
+            .configure(|cfg| {
+                if state.config.api.enable_swagger {
+                    let swagger = SwaggerUi::new("/swagger-ui/{_:.*}").url(
+                        Url::with_primary("Memos API", handlers::docs::OPENAPI_JSON_PATH, true),
+                        ApiDoc::openapi(),
+                    );
+                    cfg.service(SECTIONS.iter().fold(swagger, |swagger, section| {
+                        swagger.url(Url::new(section.name, section.url), section.openapi())
+                    }));
+                } else if state.config.api.docs_ui.is_some() {
+                    cfg.service(handlers::openapi_json);
+                }
+                if state.config.api.docs_ui.is_some() {
+                    cfg.service(handlers::api_docs);
+                }
+            })
+            // Route groups with their own body limits. Uploads come first,
+            // web pages are told apart by path, and everything else gets the
+            // API limit.
             .service(
-                SwaggerUi::new("/swagger-ui/{_:.*}").url("/api-docs/openapi.json", openapi.clone()),
+                extractors::limited_scope(limits.upload)
+                    .guard(guard::fn_guard(|ctx| {
+                        let head = ctx.head();
+                        match head.uri.path() {
+                            "/api/v1/memos/import/async" => true,
+                            "/web/memos" => handlers::web::is_multipart(&head.headers),
+                            _ => false,
+                        }
+                    }))
+                    .service(handlers::import_memos_async)
+                    .service(handlers::create_memo_web),
             )
-            .service(handlers::index)
-            .service(handlers::get_memos_list)
-            .service(handlers::get_new_memo_form)
-            .service(handlers::create_memo_web)
-            .service(handlers::get_edit_memo_form)
-            .service(handlers::update_memo_web)
-            .service(handlers::delete_memo_web)
-            .service(handlers::toggle_memo_complete_web)
-            .service(handlers::health_check)
-            .service(handlers::ready)
-            .service(handlers::list_memos)
-            .service(handlers::get_memo)
-            .service(handlers::create_memo)
-            .service(handlers::update_memo)
-            .service(handlers::patch_memo)
-            .service(handlers::delete_memo)
-            .service(handlers::toggle_complete)
-            .service(handlers::test_not_found)
-            .service(handlers::test_validation)
-            .service(handlers::test_internal)
-            .service(handlers::test_database)
-            .service(handlers::test_create_dto)
-            .service(handlers::test_repo)
-            .service(handlers::test_svc)
+            .service(
+                extractors::limited_scope(limits.web)
+                    .guard(guard::fn_guard(|ctx| {
+                        let path = ctx.head().uri.path();
+                        path == "/" || path.starts_with("/web/")
+                    }))
+                    .service(handlers::index)
+                    .service(handlers::get_memos_list)
+                    .service(handlers::get_new_memo_form)
+                    .service(handlers::create_memo_web)
+                    .service(handlers::get_edit_memo_form)
+                    .service(handlers::update_memo_web)
+                    .service(handlers::get_edit_title_form)
+                    .service(handlers::update_memo_title_web)
+                    .service(handlers::delete_memo_web)
+                    .service(handlers::undo_delete_web)
+                    .service(handlers::toggle_memo_complete_web)
+                    .service(handlers::toggle_memo_pinned_web)
+                    .service(handlers::toggle_memo_starred_web)
+                    .service(handlers::archive_memo_web)
+                    .service(handlers::snooze_memo_web)
+                    .service(handlers::reorder_memos_web)
+                    .service(handlers::get_flash)
+                    .service(handlers::get_calendar)
+                    .service(handlers::get_calendar_grid)
+                    .service(handlers::get_board)
+                    .service(handlers::get_board_columns)
+                    .service(handlers::move_memo_on_board_web)
+                    .service(handlers::get_notifications)
+                    .service(handlers::get_attachment)
+                    .service(handlers::get_attachment_thumbnail),
+            )
+            .service(
+                extractors::limited_scope(limits.api)
+                    .configure(|cfg| {
+                        if public_management {
+                            configure_management(cfg);
+                        }
+                    })
+                    .service(handlers::version)
+                    .service(handlers::list_memos)
+                    .service(handlers::memo_stats)
+                    .service(handlers::export_memos)
+                    .service(handlers::list_import_failures)
+                    .service(handlers::complete_memos)
+                    .service(handlers::delete_memos)
+                    .service(handlers::undo_delete)
+                    .service(handlers::get_operation)
+                    .service(handlers::operation_events)
+                    .service(handlers::create_memo_from_template)
+                    .service(handlers::get_memo)
+                    .service(handlers::create_memo)
+                    .service(handlers::update_memo)
+                    .service(handlers::patch_memo)
+                    .service(handlers::delete_memo)
+                    .service(handlers::toggle_complete)
+                    .service(handlers::move_memo)
+                    .service(handlers::duplicate_memo)
+                    .service(handlers::archive_memo)
+                    .service(handlers::unarchive_memo)
+                    .service(handlers::toggle_pinned)
+                    .service(handlers::toggle_starred)
+                    .service(handlers::snooze_memo)
+                    .service(handlers::list_memo_snoozes)
+                    .service(handlers::add_memo_blocker)
+                    .service(handlers::remove_memo_blocker)
+                    .service(handlers::get_attachment_url)
+                    .service(handlers::list_memo_templates)
+                    .service(handlers::create_memo_template)
+                    .service(handlers::get_memo_template)
+                    .service(handlers::update_memo_template)
+                    .service(handlers::delete_memo_template)
+                    .service(handlers::get_push_public_key)
+                    .service(handlers::subscribe_push)
+                    .service(handlers::unsubscribe_push)
+                    .service(handlers::list_notifications)
+                    .service(handlers::mark_notification_read)
+                    .service(handlers::list_webhook_integrations)
+                    .service(handlers::create_webhook_integration)
+                    .service(handlers::delete_webhook_integration)
+                    .service(handlers::get_my_usage)
+                    .configure(|cfg| {
+                        if mount_test_routes {
+                            handlers::configure_test_routes(cfg);
+                        }
+                    }),
+            )
+            .default_service(web::to(handlers::fallback))
     })
-    .workers(num_cpus::get() * 2)
-    .keep_alive(Duration::from_secs(75))
-    .client_request_timeout(Duration::from_secs(60))
-    .client_disconnect_timeout(Duration::from_secs(5))
-    .bind(&bind_address)?
-    .run()
-    .await?;
+    .workers(settings.server.workers)
+    .keep_alive(settings.server.keep_alive())
+    .client_request_timeout(Duration::from_millis(
+        settings.server.client_request_timeout_ms,
+    ))
+    .client_disconnect_timeout(Duration::from_millis(
+        settings.server.client_disconnect_timeout_ms,
+    ))
+    .backlog(settings.server.backlog)
+    .max_connections(settings.server.max_connections);
+
+    let server = match inherited_listener {
+        Some(listener) => server.listen(listener)?,
+        None => server.bind(&bind_address)?,
+    };
+    match management_server {
+        Some(management_server) => {
+            tokio::try_join!(server.run(), management_server)?;
+        }
+        None => server.run().await?,
+    }
+
+    if let Some(scheduler) = scheduler {
+        scheduler.shutdown();
+    }
+    for worker in job_workers {
+        worker.abort();
+    }
+    if let Some(flusher) = usage_flusher {
+        flusher.abort();
+        if let Err(e) = api_keys.flush().await {
+            tracing::warn!(error = %e, "Failed to record the last API key usage");
+        }
+    }
 
     tracing::info!("Application shutdown complete");
     Ok(())
 }
+
+/// Probes and the admin API, mounted on the management listener when
+/// `MANAGEMENT_PORT` is set and on the main one otherwise. `/metrics` is
+/// answered by the Prometheus middleware of whichever listener hosts them.
+fn configure_management(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::health_check)
+        .service(handlers::ready)
+        .service(handlers::startup)
+        .service(handlers::get_maintenance)
+        .service(handlers::set_maintenance)
+        .service(handlers::preview_retention)
+        .service(handlers::get_runtime_diagnostics);
+}
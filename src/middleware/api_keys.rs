@@ -0,0 +1,141 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage, ResponseError};
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::rc::Rc;
+
+use crate::auth::{ApiKeys, Principal, QuotaCheck, QuotaStatus};
+use crate::error::AppError;
+
+pub const API_KEY_HEADER: HeaderName = HeaderName::from_static("x-api-key");
+pub const QUOTA_LIMIT_HEADER: HeaderName = HeaderName::from_static("x-quota-limit");
+pub const QUOTA_REMAINING_HEADER: HeaderName = HeaderName::from_static("x-quota-remaining");
+pub const QUOTA_RESET_HEADER: HeaderName = HeaderName::from_static("x-quota-reset");
+
+/// API paths that authenticate but are not counted, so integrators can check
+/// their usage after running out of quota.
+const UNMETERED_PATHS: &[&str] = &["/api/v1/me/usage"];
+
+impl QuotaStatus {
+    fn insert_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(QUOTA_LIMIT_HEADER, HeaderValue::from(self.limit));
+        headers.insert(QUOTA_REMAINING_HEADER, HeaderValue::from(self.remaining));
+        headers.insert(QUOTA_RESET_HEADER, HeaderValue::from(self.reset_secs));
+    }
+}
+
+/// Authenticates `/api` requests sent with `X-API-Key` and enforces the
+/// key's monthly quota.
+///
+/// A valid key becomes the request's [`Principal`]; an unknown one is
+/// rejected with `401`. Requests without the header stay anonymous. Must
+/// wrap outside the rate limiter so it budgets per key.
+#[derive(Clone)]
+pub struct ApiKeyAuth {
+    keys: ApiKeys,
+}
+
+impl ApiKeyAuth {
+    pub fn new(keys: ApiKeys) -> Self {
+        Self { keys }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiKeyAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ApiKeyAuthMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ApiKeyAuthMiddleware {
+            service: Rc::new(service),
+            keys: self.keys.clone(),
+        }))
+    }
+}
+
+pub struct ApiKeyAuthMiddleware<S> {
+    service: Rc<S>,
+    keys: ApiKeys,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiKeyAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let keys = self.keys.clone();
+
+        Box::pin(async move {
+            let provided = req
+                .headers()
+                .get(API_KEY_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            let Some(provided) = provided.filter(|_| req.path().starts_with("/api/")) else {
+                return service
+                    .call(req)
+                    .await
+                    .map(ServiceResponse::map_into_left_body);
+            };
+
+            let Some(key) = keys.authenticate(&provided).cloned() else {
+                tracing::warn!("Rejected request with an unknown API key");
+                let response =
+                    AppError::Unauthorized("Invalid API key".to_string()).error_response();
+                return Ok(req.into_response(response).map_into_right_body());
+            };
+            req.extensions_mut()
+                .insert(Principal::ApiKey(key.id.clone()));
+
+            if UNMETERED_PATHS.contains(&req.path()) {
+                return service
+                    .call(req)
+                    .await
+                    .map(ServiceResponse::map_into_left_body);
+            }
+
+            let status = match keys.charge(&key).await {
+                Ok(QuotaCheck::Unlimited) => None,
+                Ok(QuotaCheck::Within(status)) => Some(status),
+                Ok(QuotaCheck::Exceeded(status)) => {
+                    tracing::warn!(api_key = %key.id, limit = status.limit, "Monthly quota exceeded");
+                    let mut response = AppError::QuotaExceeded {
+                        retry_after_secs: status.reset_secs,
+                    }
+                    .error_response();
+                    status.insert_headers(response.headers_mut());
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+                Err(e) => {
+                    let response = AppError::Database(e).error_response();
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+            };
+
+            let mut res = service.call(req).await?;
+            if let Some(status) = status {
+                status.insert_headers(res.headers_mut());
+            }
+            Ok(res.map_into_left_body())
+        })
+    }
+}
@@ -0,0 +1,298 @@
+use actix_web::Error;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use actix_web::http::{Method, StatusCode};
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::config::settings::HttpCacheConfig;
+use crate::middleware::api_keys::API_KEY_HEADER;
+
+/// Sets `Cache-Control` on successful `GET` and `HEAD` responses from the
+/// first matching rule of [`HttpCacheConfig`]: an exact path, or else the
+/// longest `prefix*`. Responses that set their own `Cache-Control`, like
+/// assets and attachments, keep it.
+///
+/// Responses that may be stored also get the configured request headers added
+/// to their `Vary`, next to the `Accept` and `Prefer` the negotiated API
+/// responses already vary on, so caches keep one copy per format and
+/// timezone. `public` becomes `private` for requests sent with credentials, as
+/// their responses carry per-key quota headers.
+#[derive(Clone)]
+pub struct CacheHeaders {
+    rules: Arc<Vec<CacheRule>>,
+    /// As configured; `HeaderName` would lowercase them
+    vary: Arc<Vec<String>>,
+}
+
+struct CacheRule {
+    path: String,
+    prefix: bool,
+    value: HeaderValue,
+    /// `value` with `public` replaced by `private`
+    private: HeaderValue,
+    storable: bool,
+}
+
+impl CacheHeaders {
+    pub fn from_config(config: &HttpCacheConfig) -> Result<Self, String> {
+        let rules = config
+            .rules
+            .iter()
+            .map(|(path, directives)| {
+                let invalid = || format!("invalid Cache-Control value '{directives}'");
+                let private = directives
+                    .split(',')
+                    .map(|directive| match directive.trim() {
+                        d if d.eq_ignore_ascii_case("public") => "private",
+                        d => d,
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                Ok(CacheRule {
+                    prefix: path.ends_with('*'),
+                    path: path.trim_end_matches('*').to_string(),
+                    value: HeaderValue::from_str(directives).map_err(|_| invalid())?,
+                    private: HeaderValue::from_str(&private).map_err(|_| invalid())?,
+                    storable: !directives.to_ascii_lowercase().contains("no-store"),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        for name in &config.vary {
+            HeaderName::from_str(name).map_err(|_| format!("invalid Vary header '{name}'"))?;
+        }
+        let vary = config.vary.clone();
+
+        Ok(Self {
+            rules: Arc::new(rules),
+            vary: Arc::new(vary),
+        })
+    }
+
+    fn rule_for(&self, path: &str) -> Option<&CacheRule> {
+        self.rules
+            .iter()
+            .find(|rule| !rule.prefix && rule.path == path)
+            .or_else(|| {
+                self.rules
+                    .iter()
+                    .filter(|rule| rule.prefix && path.starts_with(rule.path.as_str()))
+                    .max_by_key(|rule| rule.path.len())
+            })
+    }
+}
+
+/// Adds `names` missing from the response's `Vary`; `Vary: *` is left alone.
+fn merge_vary(headers: &mut HeaderMap, names: &[String]) {
+    let mut values: Vec<String> = headers
+        .get_all(header::VARY)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect();
+    if values.iter().any(|name| name == "*") {
+        return;
+    }
+    for name in names {
+        if !values
+            .iter()
+            .any(|value| value.eq_ignore_ascii_case(name.as_str()))
+        {
+            values.push(name.to_string());
+        }
+    }
+    if let Ok(value) = HeaderValue::from_str(&values.join(", ")) {
+        headers.insert(header::VARY, value);
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CacheHeaders
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = CacheHeadersMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(CacheHeadersMiddleware {
+            service,
+            config: self.clone(),
+        }))
+    }
+}
+
+pub struct CacheHeadersMiddleware<S> {
+    service: S,
+    config: CacheHeaders,
+}
+
+impl<S, B> Service<ServiceRequest> for CacheHeadersMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let cacheable = matches!(*req.method(), Method::GET | Method::HEAD);
+        let credentials = req.headers().contains_key(header::AUTHORIZATION)
+            || req.headers().contains_key(API_KEY_HEADER);
+        let policy = cacheable
+            .then(|| self.config.rule_for(req.path()))
+            .flatten()
+            .map(|rule| {
+                let value = if credentials {
+                    rule.private.clone()
+                } else {
+                    rule.value.clone()
+                };
+                (value, rule.storable)
+            });
+        let vary = self.config.vary.clone();
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut res = fut.await?;
+            let Some((value, storable)) = policy else {
+                return Ok(res);
+            };
+            let status = res.status();
+            if !(status.is_success() || status == StatusCode::NOT_MODIFIED)
+                || res.headers().contains_key(header::CACHE_CONTROL)
+            {
+                return Ok(res);
+            }
+
+            let headers = res.headers_mut();
+            headers.insert(header::CACHE_CONTROL, value);
+            if storable {
+                merge_vary(headers, &vary);
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::settings::{DEFAULT_HTTP_CACHE_RULES, parse_cache_rules};
+    use actix_web::test::{TestRequest, call_service, init_service};
+    use actix_web::{App, HttpResponse, web};
+
+    fn cache_headers() -> CacheHeaders {
+        CacheHeaders::from_config(&HttpCacheConfig {
+            rules: parse_cache_rules(DEFAULT_HTTP_CACHE_RULES).unwrap(),
+            vary: vec!["Accept".to_string(), "Time-Zone".to_string()],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_exact_paths_win_over_the_longest_prefix() {
+        let headers = cache_headers();
+        let directives = |path| {
+            headers
+                .rule_for(path)
+                .map(|rule| rule.value.to_str().unwrap().to_string())
+        };
+        assert_eq!(
+            directives("/api/v1/memos").as_deref(),
+            Some("public, max-age=5, stale-while-revalidate=30")
+        );
+        assert_eq!(
+            directives("/api/v1/memos/123").as_deref(),
+            Some("private, no-cache")
+        );
+        assert_eq!(directives("/").as_deref(), Some("private, no-cache"));
+        assert_eq!(directives("/health"), None);
+    }
+
+    #[test]
+    fn test_merges_vary_without_duplicates() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::VARY, HeaderValue::from_static("accept, Prefer"));
+        merge_vary(
+            &mut headers,
+            &["Accept".to_string(), "Accept-Encoding".to_string()],
+        );
+        assert_eq!(
+            headers.get(header::VARY).unwrap(),
+            "accept, Prefer, Accept-Encoding"
+        );
+
+        headers.insert(header::VARY, HeaderValue::from_static("*"));
+        merge_vary(&mut headers, &["Accept".to_string()]);
+        assert_eq!(headers.get(header::VARY).unwrap(), "*");
+    }
+
+    #[actix_web::test]
+    async fn test_sets_headers_on_successful_reads_only() {
+        let app = init_service(
+            App::new()
+                .wrap(cache_headers())
+                .route("/api/v1/memos", web::get().to(HttpResponse::Ok))
+                .route("/api/v1/memos", web::post().to(HttpResponse::Created))
+                .route("/api/v1/missing", web::get().to(HttpResponse::NotFound))
+                .route(
+                    "/api/v1/own",
+                    web::get().to(|| async {
+                        HttpResponse::Ok()
+                            .insert_header((header::CACHE_CONTROL, "no-store"))
+                            .finish()
+                    }),
+                ),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::get().uri("/api/v1/memos").to_request()).await;
+        assert_eq!(
+            res.headers().get(header::CACHE_CONTROL).unwrap(),
+            "public, max-age=5, stale-while-revalidate=30"
+        );
+        assert_eq!(
+            res.headers().get(header::VARY).unwrap(),
+            "Accept, Time-Zone"
+        );
+
+        let res = call_service(
+            &app,
+            TestRequest::get()
+                .uri("/api/v1/memos")
+                .insert_header((API_KEY_HEADER, "key"))
+                .to_request(),
+        )
+        .await;
+        assert_eq!(
+            res.headers().get(header::CACHE_CONTROL).unwrap(),
+            "private, max-age=5, stale-while-revalidate=30"
+        );
+
+        for req in [
+            TestRequest::post().uri("/api/v1/memos").to_request(),
+            TestRequest::get().uri("/api/v1/missing").to_request(),
+        ] {
+            let res = call_service(&app, req).await;
+            assert!(!res.headers().contains_key(header::CACHE_CONTROL));
+        }
+
+        let res = call_service(&app, TestRequest::get().uri("/api/v1/own").to_request()).await;
+        assert_eq!(
+            res.headers().get(header::CACHE_CONTROL).unwrap(),
+            "no-store"
+        );
+    }
+}
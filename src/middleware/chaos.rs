@@ -0,0 +1,249 @@
+use actix_web::body::{BodySize, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::error::{Error, InternalError};
+use actix_web::web::Bytes;
+use actix_web::{HttpResponse, ResponseError};
+use rand::Rng;
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use crate::config::settings::ChaosConfig;
+use crate::error::AppError;
+
+/// Injects faults into a share of requests so clients and their retry logic
+/// can be exercised against realistic failures. Development only; settings
+/// validation rejects it in production.
+///
+/// For requests under the configured path prefix:
+/// - `latency_percent` of them are delayed by `latency` before the handler runs;
+/// - `error_percent` are answered with 500 without running the handler;
+/// - `drop_percent` run the handler, then the connection is closed before a
+///   complete response is sent, so the client cannot tell whether the change
+///   was applied.
+///
+/// Error and drop are exclusive; latency can be combined with either.
+#[derive(Clone)]
+pub struct Chaos {
+    path_prefix: String,
+    latency: Duration,
+    latency_percent: u8,
+    error_percent: u8,
+    drop_percent: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fault {
+    Error,
+    Drop,
+}
+
+impl Chaos {
+    pub fn from_config(config: &ChaosConfig) -> Self {
+        Self {
+            path_prefix: config.path_prefix.clone(),
+            latency: Duration::from_millis(config.latency_ms),
+            latency_percent: config.latency_percent,
+            error_percent: config.error_percent,
+            drop_percent: config.drop_percent,
+        }
+    }
+
+    fn delay(&self, rng: &mut impl Rng) -> Option<Duration> {
+        (self.latency_percent > 0
+            && !self.latency.is_zero()
+            && rng.random_range(0..100) < self.latency_percent)
+            .then_some(self.latency)
+    }
+
+    fn fault(&self, rng: &mut impl Rng) -> Option<Fault> {
+        let roll = rng.random_range(0..100);
+        if roll < self.error_percent {
+            Some(Fault::Error)
+        } else if roll < self.error_percent + self.drop_percent {
+            Some(Fault::Drop)
+        } else {
+            None
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Chaos
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ChaosMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ChaosMiddleware {
+            service,
+            config: self.clone(),
+        }))
+    }
+}
+
+pub struct ChaosMiddleware<S> {
+    service: S,
+    config: Chaos,
+}
+
+impl<S, B> Service<ServiceRequest> for ChaosMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !req.path().starts_with(self.config.path_prefix.as_str()) {
+            return Box::pin(self.service.call(req));
+        }
+
+        let (delay, fault) = {
+            let mut rng = rand::rng();
+            (self.config.delay(&mut rng), self.config.fault(&mut rng))
+        };
+        if delay.is_none() && fault.is_none() {
+            return Box::pin(self.service.call(req));
+        }
+
+        let method = req.method().clone();
+        let path = req.path().to_string();
+        let delay = async move {
+            if let Some(delay) = delay {
+                tracing::warn!(%method, %path, delay_ms = delay.as_millis() as u64, "Chaos: delaying request");
+                tokio::time::sleep(delay).await;
+            }
+            (method, path)
+        };
+
+        if fault == Some(Fault::Error) {
+            return Box::pin(async move {
+                let (method, path) = delay.await;
+                tracing::warn!(%method, %path, "Chaos: failing request with 500");
+                let error = AppError::Internal("Injected fault".to_string());
+                let response = error.error_response();
+                Err(InternalError::from_response(error, response).into())
+            });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let (method, path) = delay.await;
+            let res = fut.await;
+            if fault != Some(Fault::Drop) {
+                return res;
+            }
+
+            let status = res.map(|res| res.status());
+            tracing::warn!(%method, %path, ?status, "Chaos: dropping connection");
+            let error = AppError::Internal("Injected connection drop".to_string());
+            let response = HttpResponse::Ok().body(AbortedBody);
+            Err(InternalError::from_response(error, response).into())
+        })
+    }
+}
+
+/// Body that fails on first read, which makes the server close the
+/// connection without finishing the response.
+struct AbortedBody;
+
+impl MessageBody for AbortedBody {
+    type Error = std::io::Error;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        Poll::Ready(Some(Err(std::io::Error::new(
+            std::io::ErrorKind::ConnectionAborted,
+            "connection dropped by chaos middleware",
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, body, http::StatusCode, test, web};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn chaos(latency_percent: u8, error_percent: u8, drop_percent: u8) -> Chaos {
+        Chaos::from_config(&ChaosConfig {
+            enabled: true,
+            path_prefix: "/api".to_string(),
+            latency_percent,
+            latency_ms: 50,
+            error_percent,
+            drop_percent,
+        })
+    }
+
+    async fn call(chaos: Chaos, path: &str) -> (HttpResponse, usize) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let handler_calls = calls.clone();
+        let app = test::init_service(App::new().wrap(chaos).default_service(web::to(move || {
+            handler_calls.fetch_add(1, Ordering::SeqCst);
+            async { HttpResponse::Ok().body("ok") }
+        })))
+        .await;
+
+        let req = test::TestRequest::get().uri(path).to_request();
+        // Faults come back as errors carrying their response, as with timeouts.
+        let res = match test::try_call_service(&app, req).await {
+            Ok(res) => res.into_parts().1,
+            Err(e) => e.error_response(),
+        };
+        (res, calls.load(Ordering::SeqCst))
+    }
+
+    #[actix_web::test]
+    async fn test_injected_errors_skip_the_handler() {
+        let (res, calls) = call(chaos(0, 100, 0), "/api/v1/memos").await;
+
+        assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(calls, 0);
+    }
+
+    #[actix_web::test]
+    async fn test_dropped_connections_run_the_handler_but_never_finish_the_body() {
+        let (res, calls) = call(chaos(0, 0, 100), "/api/v1/memos").await;
+
+        assert_eq!(calls, 1);
+        assert!(body::to_bytes(res.into_body()).await.is_err());
+    }
+
+    #[actix_web::test]
+    async fn test_latency_delays_the_response() {
+        let started = std::time::Instant::now();
+        let (res, _) = call(chaos(100, 0, 0), "/api/v1/memos").await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[actix_web::test]
+    async fn test_paths_outside_the_prefix_are_untouched() {
+        let (res, calls) = call(chaos(0, 100, 0), "/health").await;
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(calls, 1);
+    }
+}
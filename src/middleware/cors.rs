@@ -0,0 +1,178 @@
+use actix_cors::Cors;
+use actix_web::http::{Method, header};
+use url::Url;
+
+use crate::config::settings::CorsConfig;
+
+/// Builds the CORS policy from configuration.
+///
+/// Origins may be `*` (any origin, never with credentials), an exact origin
+/// such as `https://app.example.com`, or a subdomain pattern such as
+/// `https://*.example.com`, which matches any subdomain at any depth but not
+/// `example.com` itself.
+pub fn build(config: &CorsConfig) -> Cors {
+    let mut cors = Cors::default()
+        .allowed_methods([
+            Method::GET,
+            Method::POST,
+            Method::PUT,
+            Method::DELETE,
+            Method::PATCH,
+        ])
+        .allowed_headers([
+            header::AUTHORIZATION,
+            header::ACCEPT,
+            header::CONTENT_TYPE,
+            header::IF_MATCH,
+            header::HeaderName::from_static("prefer"),
+            header::HeaderName::from_static("x-request-id"),
+        ])
+        .max_age(config.max_age_secs as usize);
+    if !config.exposed_headers.is_empty() {
+        cors = cors.expose_headers(config.exposed_headers.iter().map(String::as_str));
+    }
+    if config.allow_credentials {
+        cors = cors.supports_credentials();
+    }
+
+    let mut patterns = Vec::new();
+    for origin in &config.allowed_origins {
+        match OriginPattern::parse(origin) {
+            Ok(OriginPattern::Any) => cors = cors.allow_any_origin().send_wildcard(),
+            Ok(OriginPattern::Exact(origin)) => cors = cors.allowed_origin(&origin),
+            Ok(pattern) => patterns.push(pattern),
+            // Settings validation rejects these before the server starts.
+            Err(e) => tracing::warn!(origin = %origin, error = %e, "Ignoring CORS origin"),
+        }
+    }
+    if !patterns.is_empty() {
+        cors = cors.allowed_origin_fn(move |origin, _| {
+            origin
+                .to_str()
+                .is_ok_and(|origin| patterns.iter().any(|pattern| pattern.matches(origin)))
+        });
+    }
+
+    cors
+}
+
+/// One entry of `CORS_ALLOWED_ORIGINS`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OriginPattern {
+    Any,
+    Exact(String),
+    /// `scheme://*.suffix[:port]`; `suffix` is stored with its leading dot
+    Subdomain {
+        scheme: String,
+        suffix: String,
+        port: Option<u16>,
+    },
+}
+
+impl OriginPattern {
+    pub fn parse(origin: &str) -> Result<Self, String> {
+        if origin == "*" {
+            return Ok(Self::Any);
+        }
+
+        let (scheme, rest) = origin
+            .split_once("://")
+            .ok_or_else(|| format!("'{origin}' is not an origin like https://example.com"))?;
+        if let Some(suffix) = rest.strip_prefix("*.") {
+            let url = Self::normalized(&format!("{scheme}://{suffix}"))?;
+            return Ok(Self::Subdomain {
+                scheme: url.scheme().to_string(),
+                suffix: format!(".{}", url.host_str().unwrap_or_default()),
+                port: url.port(),
+            });
+        }
+        if origin.contains('*') {
+            return Err(format!(
+                "'{origin}': a wildcard is only allowed as the first label, e.g. https://*.example.com"
+            ));
+        }
+
+        Self::normalized(origin).map(|url| Self::Exact(url.origin().ascii_serialization()))
+    }
+
+    /// Parses `origin`, requiring it to be written exactly as browsers send it.
+    fn normalized(origin: &str) -> Result<Url, String> {
+        let url = Url::parse(origin).map_err(|e| format!("'{origin}': {e}"))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!(
+                "'{origin}': only http and https origins are supported"
+            ));
+        }
+        let serialized = url.origin().ascii_serialization();
+        if serialized != origin {
+            return Err(format!(
+                "'{origin}' is not a bare origin; browsers send '{serialized}'"
+            ));
+        }
+        Ok(url)
+    }
+
+    pub fn matches(&self, origin: &str) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Exact(exact) => exact == origin,
+            Self::Subdomain {
+                scheme,
+                suffix,
+                port,
+            } => Url::parse(origin).is_ok_and(|url| {
+                url.scheme() == scheme
+                    && url.port() == *port
+                    && url.host_str().is_some_and(|host| {
+                        host.len() > suffix.len() && host.ends_with(suffix.as_str())
+                    })
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subdomain_patterns_match_subdomains_only() {
+        let pattern = OriginPattern::parse("https://*.example.com").unwrap();
+
+        assert!(pattern.matches("https://app.example.com"));
+        assert!(pattern.matches("https://a.b.example.com"));
+        assert!(!pattern.matches("https://example.com"));
+        assert!(!pattern.matches("https://evilexample.com"));
+        assert!(!pattern.matches("https://app.example.com.evil.io"));
+        assert!(!pattern.matches("http://app.example.com"));
+        assert!(!pattern.matches("https://app.example.com:8443"));
+    }
+
+    #[test]
+    fn test_ports_must_match() {
+        let pattern = OriginPattern::parse("http://*.localhost:3000").unwrap();
+
+        assert!(pattern.matches("http://web.localhost:3000"));
+        assert!(!pattern.matches("http://web.localhost"));
+    }
+
+    #[test]
+    fn test_origins_must_be_written_as_browsers_send_them() {
+        assert_eq!(
+            OriginPattern::parse("https://app.example.com"),
+            Ok(OriginPattern::Exact("https://app.example.com".to_string()))
+        );
+        assert_eq!(OriginPattern::parse("*"), Ok(OriginPattern::Any));
+
+        for invalid in [
+            "app.example.com",
+            "https://app.example.com/",
+            "https://App.example.com",
+            "https://app.example.com:443",
+            "https://app.*.example.com",
+            "ftp://files.example.com",
+        ] {
+            assert!(OriginPattern::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,146 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::{Error, ResponseError};
+use std::future::{Ready, ready};
+use std::pin::Pin;
+
+use crate::db::CircuitBreaker;
+use crate::middleware::management::is_management_path;
+
+/// Paths that don't need the database besides the management routes: assets
+/// and the API docs.
+const EXEMPT_PREFIXES: &[&str] = &["/static", "/swagger-ui", "/api-docs"];
+
+/// Fails requests fast while the database [`CircuitBreaker`] is open.
+///
+/// Such requests get the 503 `ErrorResponse` with `Retry-After` without
+/// reaching a handler. Outcomes aren't recorded here but by
+/// [`crate::db::GuardedConnection`], so only requests that use the database
+/// count. Probes, metrics and the admin API are exempt, so `/health` keeps
+/// reporting the circuit's state.
+#[derive(Clone)]
+pub struct DatabaseCircuit {
+    breaker: CircuitBreaker,
+}
+
+impl DatabaseCircuit {
+    pub fn new(breaker: CircuitBreaker) -> Self {
+        Self { breaker }
+    }
+
+    fn is_exempt(path: &str) -> bool {
+        is_management_path(path)
+            || EXEMPT_PREFIXES
+                .iter()
+                .any(|prefix| path.starts_with(prefix))
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for DatabaseCircuit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = DatabaseCircuitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(DatabaseCircuitMiddleware {
+            service,
+            breaker: self.breaker.clone(),
+        }))
+    }
+}
+
+pub struct DatabaseCircuitMiddleware<S> {
+    service: S,
+    breaker: CircuitBreaker,
+}
+
+impl<S, B> Service<ServiceRequest> for DatabaseCircuitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if !DatabaseCircuit::is_exempt(req.path())
+            && let Err(error) = self.breaker.check()
+        {
+            tracing::debug!(path = %req.path(), "Rejecting request, database circuit open");
+            let response = error.error_response();
+            return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::test::{TestRequest, call_service, init_service};
+    use actix_web::{App, HttpResponse, web};
+    use std::time::Duration;
+
+    #[test]
+    fn test_probes_and_assets_are_exempt() {
+        for path in [
+            "/health",
+            "/ready",
+            "/metrics",
+            "/admin/maintenance",
+            "/static/app.js",
+        ] {
+            assert!(DatabaseCircuit::is_exempt(path), "{path} should be exempt");
+        }
+        for path in ["/", "/api/v1/memos", "/web/memos"] {
+            assert!(
+                !DatabaseCircuit::is_exempt(path),
+                "{path} should be guarded"
+            );
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_fails_fast_while_open_and_lets_requests_through_for_the_trial() {
+        let breaker = CircuitBreaker::new(1, Duration::from_millis(50));
+        let app = init_service(
+            App::new()
+                .wrap(DatabaseCircuit::new(breaker.clone()))
+                .route("/api/v1/memos", web::get().to(HttpResponse::Ok))
+                .route("/health", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        breaker.record(false);
+        let req = TestRequest::get().uri("/api/v1/memos").to_request();
+        let res = call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert!(res.headers().contains_key("retry-after"));
+
+        let req = TestRequest::get().uri("/health").to_request();
+        assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        // Requests that don't reach the database neither close the circuit
+        // nor use up the trial
+        for _ in 0..2 {
+            let req = TestRequest::get().uri("/api/v1/memos").to_request();
+            assert_eq!(call_service(&app, req).await.status(), StatusCode::OK);
+        }
+        assert_eq!(breaker.state(), crate::db::CircuitState::HalfOpen);
+    }
+}
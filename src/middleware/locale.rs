@@ -0,0 +1,66 @@
+use actix_web::Error;
+use actix_web::HttpMessage;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::{CONTENT_LANGUAGE, HeaderValue};
+use std::future::{Ready, ready};
+use std::pin::Pin;
+
+use crate::i18n::{Locale, ViewerTimezone};
+
+/// Negotiates the request locale from `Accept-Language` and makes it available
+/// to extractors and to `AppError` responses for the rest of the request.
+/// Likewise scopes the viewer's [`ViewerTimezone`], which due dates are
+/// rendered in.
+pub struct LocaleNegotiation;
+
+impl<S, B> Transform<S, ServiceRequest> for LocaleNegotiation
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = LocaleNegotiationMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(LocaleNegotiationMiddleware { service }))
+    }
+}
+
+pub struct LocaleNegotiationMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for LocaleNegotiationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let locale = Locale::for_request(req.request());
+        let timezone = ViewerTimezone::for_request(req.request());
+        req.extensions_mut().insert(locale);
+        req.extensions_mut().insert(timezone);
+
+        let fut = locale.sync_scope(|| timezone.sync_scope(|| self.service.call(req)));
+
+        Box::pin(async move {
+            let mut res = locale.scope(timezone.scope(fut)).await?;
+
+            res.headers_mut()
+                .insert(CONTENT_LANGUAGE, HeaderValue::from_static(locale.code()));
+
+            Ok(res)
+        })
+    }
+}
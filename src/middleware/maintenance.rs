@@ -0,0 +1,185 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header;
+use actix_web::{Error, HttpResponse, ResponseError};
+use askama::Template;
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::settings::MaintenanceConfig;
+use crate::error::AppError;
+use crate::flash::FlashMessage;
+use crate::i18n::Locale;
+
+/// Paths that keep working during maintenance: probes, metrics, the admin API
+/// (so the switch can be turned off again), runtime diagnostics and the assets
+/// of the maintenance page.
+const EXEMPT_PREFIXES: &[&str] = &[
+    "/health", "/ready", "/startup", "/metrics", "/admin", "/debug", "/static",
+];
+
+#[derive(Template)]
+#[template(path = "pages/maintenance.html")]
+struct MaintenanceTemplate {
+    flash: Option<FlashMessage>,
+    locale: Locale,
+}
+
+/// Runtime maintenance switch, shared between the middleware and `AppState`.
+///
+/// While enabled, every non-exempt request gets a 503 with `Retry-After`:
+/// JSON for the API, a maintenance page for browsers.
+#[derive(Clone)]
+pub struct MaintenanceMode {
+    enabled: Arc<AtomicBool>,
+    retry_after_secs: u64,
+}
+
+impl MaintenanceMode {
+    pub fn new(enabled: bool, retry_after_secs: u64) -> Self {
+        Self {
+            enabled: Arc::new(AtomicBool::new(enabled)),
+            retry_after_secs,
+        }
+    }
+
+    pub fn from_config(config: &MaintenanceConfig) -> Self {
+        Self::new(config.enabled, config.retry_after_secs)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn retry_after_secs(&self) -> u64 {
+        self.retry_after_secs
+    }
+
+    fn is_exempt(path: &str) -> bool {
+        EXEMPT_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+    }
+
+    fn response(&self, req: &ServiceRequest) -> HttpResponse {
+        let error = AppError::Maintenance {
+            retry_after_secs: self.retry_after_secs,
+        };
+
+        let wants_html = !req.path().starts_with("/api")
+            && req
+                .headers()
+                .get(header::ACCEPT)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|accept| accept.contains("text/html"));
+        if !wants_html {
+            return error.error_response();
+        }
+
+        let template = MaintenanceTemplate {
+            flash: None,
+            locale: Locale::current(),
+        };
+        match template.render() {
+            Ok(html) => HttpResponse::ServiceUnavailable()
+                .insert_header((header::RETRY_AFTER, self.retry_after_secs.to_string()))
+                .content_type("text/html; charset=utf-8")
+                .body(html),
+            Err(e) => {
+                tracing::error!(error = %e, "Failed to render maintenance page");
+                error.error_response()
+            }
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for MaintenanceMode
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = MaintenanceMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(MaintenanceMiddleware {
+            service,
+            mode: self.clone(),
+        }))
+    }
+}
+
+pub struct MaintenanceMiddleware<S> {
+    service: S,
+    mode: MaintenanceMode,
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if self.mode.is_enabled() && !MaintenanceMode::is_exempt(req.path()) {
+            tracing::debug!(path = %req.path(), "Rejecting request during maintenance");
+            let response = self.mode.response(&req);
+            return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probes_and_admin_are_exempt() {
+        for path in [
+            "/health",
+            "/ready",
+            "/startup",
+            "/metrics",
+            "/admin/maintenance",
+            "/debug/runtime",
+            "/static/css/style.css",
+        ] {
+            assert!(MaintenanceMode::is_exempt(path), "{path} should be exempt");
+        }
+        for path in ["/", "/api/v1/memos", "/web/memos", "/swagger-ui/"] {
+            assert!(
+                !MaintenanceMode::is_exempt(path),
+                "{path} should be blocked"
+            );
+        }
+    }
+
+    #[test]
+    fn test_clones_share_the_switch() {
+        let mode = MaintenanceMode::new(false, 60);
+        let handle = mode.clone();
+
+        handle.set_enabled(true);
+
+        assert!(mode.is_enabled());
+    }
+}
@@ -0,0 +1,96 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::{Error, ResponseError};
+use std::future::{Ready, ready};
+use std::pin::Pin;
+
+use crate::error::AppError;
+
+/// Operational routes served by the management listener when
+/// `MANAGEMENT_PORT` is set: probes, metrics, the admin API and runtime
+/// diagnostics.
+pub const MANAGEMENT_PREFIXES: &[&str] = &[
+    "/health", "/ready", "/startup", "/metrics", "/admin", "/debug",
+];
+
+pub fn is_management_path(path: &str) -> bool {
+    MANAGEMENT_PREFIXES.iter().any(|prefix| {
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+/// Answers the [`MANAGEMENT_PREFIXES`] with `404` on the public listener
+/// once they are served on their own port.
+///
+/// Must wrap outside the Prometheus middleware, which answers `/metrics`
+/// before any route runs.
+#[derive(Clone, Copy, Default)]
+pub struct HideManagementRoutes;
+
+impl<S, B> Transform<S, ServiceRequest> for HideManagementRoutes
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = HideManagementRoutesMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(HideManagementRoutesMiddleware { service }))
+    }
+}
+
+pub struct HideManagementRoutesMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for HideManagementRoutesMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if is_management_path(req.path()) {
+            let response = AppError::RouteNotFound(format!("{} {}", req.method(), req.path()))
+                .error_response();
+            return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matches_operational_routes_only() {
+        for path in [
+            "/health",
+            "/ready",
+            "/startup",
+            "/metrics",
+            "/admin/maintenance",
+            "/debug/runtime",
+        ] {
+            assert!(is_management_path(path), "{path} should be hidden");
+        }
+        for path in ["/", "/api/v1/memos", "/healthz", "/version", "/web/memos"] {
+            assert!(!is_management_path(path), "{path} should stay public");
+        }
+    }
+}
@@ -1,3 +1,30 @@
+pub mod api_keys;
+pub mod cache_headers;
+pub mod chaos;
+pub mod cors;
+pub mod db_circuit;
+pub mod locale;
+pub mod maintenance;
+pub mod management;
+pub mod observability_auth;
+pub mod panic_capture;
+pub mod rate_limit;
+pub mod request_id;
+pub mod response_validation;
 pub mod security_headers;
+pub mod timeout;
 
-pub use security_headers::SecurityHeaders;
+pub use api_keys::ApiKeyAuth;
+pub use cache_headers::CacheHeaders;
+pub use chaos::Chaos;
+pub use db_circuit::DatabaseCircuit;
+pub use locale::LocaleNegotiation;
+pub use maintenance::MaintenanceMode;
+pub use management::HideManagementRoutes;
+pub use observability_auth::ObservabilityAuth;
+pub use panic_capture::PanicCapture;
+pub use rate_limit::RateLimit;
+pub use request_id::{AssignRequestId, RequestId};
+pub use response_validation::ResponseValidation;
+pub use security_headers::{ContentSecurityPolicy, CspNonce, SecurityHeaders};
+pub use timeout::RequestTimeout;
@@ -0,0 +1,191 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::{Error, ResponseError};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::sync::Arc;
+
+use crate::auth::constant_time_eq;
+use crate::config::settings::{BasicCredentials, ObservabilityConfig};
+use crate::error::AppError;
+
+/// Routes that expose internals: the metrics endpoint, Swagger UI, the
+/// OpenAPI documents it loads and the `/docs` API reference.
+const PROTECTED_PREFIXES: &[&str] = &["/metrics", "/swagger-ui", "/api-docs", "/docs"];
+
+/// Guards `/metrics` and the API docs with the bearer token and/or basic
+/// credentials from [`ObservabilityConfig`], so they can be served on the
+/// main port without being public.
+///
+/// Either credential is accepted when both are set. Without any the
+/// middleware lets everything through. Must wrap outside the Prometheus
+/// middleware, which answers `/metrics` itself.
+#[derive(Clone)]
+pub struct ObservabilityAuth {
+    token: Option<Arc<str>>,
+    basic: Option<Arc<BasicCredentials>>,
+}
+
+impl ObservabilityAuth {
+    pub fn from_config(config: &ObservabilityConfig) -> Self {
+        Self {
+            token: config.auth_token.as_deref().map(Arc::from),
+            basic: config.basic_auth.clone().map(Arc::new),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.token.is_some() || self.basic.is_some()
+    }
+
+    fn is_protected(path: &str) -> bool {
+        PROTECTED_PREFIXES.iter().any(|prefix| {
+            path.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    fn is_authorized(&self, authorization: &str) -> bool {
+        if let (Some(expected), Some(provided)) =
+            (&self.token, authorization.strip_prefix("Bearer "))
+        {
+            return constant_time_eq(provided.as_bytes(), expected.as_bytes());
+        }
+
+        let (Some(expected), Some(encoded)) = (&self.basic, authorization.strip_prefix("Basic "))
+        else {
+            return false;
+        };
+        let Some(decoded) = STANDARD
+            .decode(encoded.trim())
+            .ok()
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+        else {
+            return false;
+        };
+        let Some((username, password)) = decoded.split_once(':') else {
+            return false;
+        };
+        // Both compared so a wrong username takes as long as a wrong password
+        let username_ok = constant_time_eq(username.as_bytes(), expected.username.as_bytes());
+        let password_ok = constant_time_eq(password.as_bytes(), expected.password.as_bytes());
+        username_ok & password_ok
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ObservabilityAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ObservabilityAuthMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ObservabilityAuthMiddleware {
+            service,
+            auth: self.clone(),
+        }))
+    }
+}
+
+pub struct ObservabilityAuthMiddleware<S> {
+    service: S,
+    auth: ObservabilityAuth,
+}
+
+impl<S, B> Service<ServiceRequest> for ObservabilityAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let authorized = !self.auth.is_enabled()
+            || !ObservabilityAuth::is_protected(req.path())
+            || req
+                .headers()
+                .get(header::AUTHORIZATION)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| self.auth.is_authorized(value));
+
+        if !authorized {
+            tracing::warn!(path = %req.path(), "Rejected observability request with missing or invalid credentials");
+            let mut response = AppError::Unauthorized("Missing or invalid credentials".to_string())
+                .error_response();
+            // Lets browsers prompt for the credentials of Swagger UI
+            if self.auth.basic.is_some() {
+                response.headers_mut().insert(
+                    header::WWW_AUTHENTICATE,
+                    HeaderValue::from_static("Basic realm=\"observability\", charset=\"UTF-8\""),
+                );
+            }
+            return Box::pin(async move { Ok(req.into_response(response).map_into_right_body()) });
+        }
+
+        let fut = self.service.call(req);
+        Box::pin(async move { fut.await.map(ServiceResponse::map_into_left_body) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth() -> ObservabilityAuth {
+        ObservabilityAuth {
+            token: Some(Arc::from("scrape-token")),
+            basic: Some(Arc::new(BasicCredentials {
+                username: "ops".to_string(),
+                password: "s3cret".to_string(),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_protects_metrics_and_docs_only() {
+        for path in [
+            "/metrics",
+            "/swagger-ui/",
+            "/swagger-ui/index.html",
+            "/api-docs/openapi.json",
+            "/docs",
+        ] {
+            assert!(
+                ObservabilityAuth::is_protected(path),
+                "{path} should be protected"
+            );
+        }
+        for path in ["/", "/health", "/api/v1/memos", "/metricsfoo", "/docs-old"] {
+            assert!(
+                !ObservabilityAuth::is_protected(path),
+                "{path} should be open"
+            );
+        }
+    }
+
+    #[test]
+    fn test_accepts_token_or_basic_credentials() {
+        let auth = auth();
+        assert!(auth.is_authorized("Bearer scrape-token"));
+        assert!(auth.is_authorized(&format!("Basic {}", STANDARD.encode("ops:s3cret"))));
+
+        assert!(!auth.is_authorized("Bearer wrong"));
+        assert!(!auth.is_authorized(&format!("Basic {}", STANDARD.encode("ops:wrong"))));
+        assert!(!auth.is_authorized(&format!("Basic {}", STANDARD.encode("admin:s3cret"))));
+        assert!(!auth.is_authorized("Basic not-base64!"));
+        assert!(!auth.is_authorized("scrape-token"));
+    }
+}
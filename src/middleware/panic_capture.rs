@@ -0,0 +1,228 @@
+use actix_web::ResponseError;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::error::{Error, InternalError};
+use prometheus::{IntCounterVec, Opts, Registry};
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::{Cell, RefCell};
+use std::future::{Future, Ready, ready};
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::Once;
+use std::task::{Context, Poll};
+
+use crate::error::AppError;
+use crate::middleware::RequestId;
+
+thread_local! {
+    /// Set while a request is being handled on this thread, so the panic hook
+    /// keeps the panic for the middleware instead of printing it.
+    static CAPTURING: Cell<bool> = const { Cell::new(false) };
+    static CAPTURED: RefCell<Option<CapturedPanic>> = const { RefCell::new(None) };
+}
+
+static INSTALL_HOOK: Once = Once::new();
+
+/// What the panic hook saw; the backtrace has to be taken there, as the stack
+/// is unwound by the time the middleware gets control back.
+struct CapturedPanic {
+    message: String,
+    location: Option<String>,
+    backtrace: Backtrace,
+}
+
+/// Catches panics in the wrapped middleware and handlers and answers them with
+/// the standard 500 [`ErrorResponse`](crate::error::ErrorResponse) instead of
+/// dropping the connection.
+///
+/// Each panic is logged with its message, location, backtrace and the
+/// request's method, path and ID, and counted in
+/// `http_requests_panicked_total{method}`. Panics in tasks the handler spawns
+/// are not caught here; they surface as a failed `JoinHandle`.
+///
+/// Like [`super::RequestTimeout`], the 500 is returned as an error carrying a
+/// rendered response, so the Prometheus request metrics don't see it. Wrap it
+/// innermost so every other middleware still runs.
+#[derive(Clone)]
+pub struct PanicCapture {
+    panicked: Option<IntCounterVec>,
+}
+
+impl Default for PanicCapture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PanicCapture {
+    /// Installs the process-wide panic hook on first use; panics outside of
+    /// requests still go to the previous hook.
+    pub fn new() -> Self {
+        INSTALL_HOOK.call_once(|| {
+            let previous = panic::take_hook();
+            panic::set_hook(Box::new(move |info| {
+                if !CAPTURING.get() {
+                    previous(info);
+                    return;
+                }
+                let captured = CapturedPanic {
+                    message: payload_message(info.payload()),
+                    location: info.location().map(ToString::to_string),
+                    backtrace: Backtrace::force_capture(),
+                };
+                CAPTURED.set(Some(captured));
+            }));
+        });
+        Self { panicked: None }
+    }
+
+    pub fn with_metrics(mut self, registry: &Registry) -> prometheus::Result<Self> {
+        let counter = IntCounterVec::new(
+            Opts::new(
+                "http_requests_panicked_total",
+                "Requests whose handler panicked",
+            ),
+            &["method"],
+        )?;
+        registry.register(Box::new(counter.clone()))?;
+        self.panicked = Some(counter);
+        Ok(self)
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for PanicCapture
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = PanicCaptureMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(PanicCaptureMiddleware {
+            service,
+            config: self.clone(),
+        }))
+    }
+}
+
+pub struct PanicCaptureMiddleware<S> {
+    service: S,
+    config: PanicCapture,
+}
+
+impl<S, B> Service<ServiceRequest> for PanicCaptureMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let context = RequestContext {
+            method: req.method().to_string(),
+            path: req.path().to_string(),
+            request_id: RequestId::for_request(req.request()).map(|id| id.as_str().to_string()),
+            panicked: self.config.panicked.clone(),
+        };
+
+        let fut = match capture(|| self.service.call(req)) {
+            Ok(fut) => fut,
+            Err(panic) => return Box::pin(ready(Err(context.into_error(panic)))),
+        };
+        let mut fut = Box::pin(fut);
+
+        Box::pin(async move {
+            let polled = std::future::poll_fn(|cx: &mut Context<'_>| {
+                match capture(|| fut.as_mut().poll(cx)) {
+                    Ok(Poll::Ready(res)) => Poll::Ready(Ok(res)),
+                    Ok(Poll::Pending) => Poll::Pending,
+                    Err(panic) => Poll::Ready(Err(panic)),
+                }
+            })
+            .await;
+            polled.unwrap_or_else(|panic| Err(context.into_error(panic)))
+        })
+    }
+}
+
+struct RequestContext {
+    method: String,
+    path: String,
+    request_id: Option<String>,
+    panicked: Option<IntCounterVec>,
+}
+
+impl RequestContext {
+    fn into_error(self, panic: CapturedPanic) -> Error {
+        tracing::error!(
+            method = %self.method,
+            path = %self.path,
+            request_id = self.request_id.as_deref().unwrap_or("-"),
+            location = panic.location.as_deref().unwrap_or("unknown"),
+            backtrace = %panic.backtrace,
+            "Request handler panicked: {}",
+            panic.message
+        );
+        if let Some(counter) = self.panicked {
+            counter.with_label_values(&[&self.method]).inc();
+        }
+
+        // The panic message may carry internals, so it only goes to the log.
+        let error = AppError::Internal("The request could not be completed".to_string());
+        let response = error.error_response();
+        InternalError::from_response(error, response).into()
+    }
+}
+
+/// Runs `f` with the hook capturing, returning what it panicked with instead
+/// of unwinding further.
+fn capture<T>(f: impl FnOnce() -> T) -> Result<T, CapturedPanic> {
+    let outer = CAPTURING.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CAPTURING.set(outer);
+
+    result.map_err(|payload| {
+        CAPTURED.take().unwrap_or_else(|| CapturedPanic {
+            message: payload_message(payload.as_ref()),
+            location: None,
+            backtrace: Backtrace::disabled(),
+        })
+    })
+}
+
+fn payload_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "non-string panic payload".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_keeps_message_and_location() {
+        PanicCapture::new();
+        let panic = capture(|| panic!("boom {}", 42)).err().unwrap();
+        assert_eq!(panic.message, "boom 42");
+        assert!(panic.location.unwrap().contains("panic_capture.rs"));
+        assert!(!CAPTURING.get());
+    }
+
+    #[test]
+    fn test_capture_passes_values_through() {
+        assert_eq!(capture(|| 7).ok(), Some(7));
+    }
+}
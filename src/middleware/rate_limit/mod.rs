@@ -0,0 +1,463 @@
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::Method;
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage, ResponseError};
+use governor::clock::{Clock, DefaultClock};
+use governor::middleware::StateInformationMiddleware;
+use governor::state::keyed::DefaultKeyedStateStore;
+use governor::{Quota, RateLimiter};
+use prometheus::{IntCounter, Registry};
+use std::future::{Ready, ready};
+use std::num::NonZeroU32;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::auth::Principal;
+use crate::config::settings::{RateLimitBudget, RateLimitConfig};
+use crate::error::AppError;
+
+mod redis_store;
+
+pub use redis_store::RedisStore;
+
+/// How often an unreachable Redis is tried again after startup.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Paths that are never rate limited: probes, metrics and static assets.
+const EXEMPT_PREFIXES: &[&str] = &["/health", "/ready", "/startup", "/metrics", "/static"];
+
+pub const LIMIT_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+pub const REMAINING_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+pub const RESET_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+type KeyedLimiter =
+    RateLimiter<String, DefaultKeyedStateStore<String>, DefaultClock, StateInformationMiddleware>;
+
+/// A caller's budget after a request was checked, sent as `X-RateLimit-*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimitStatus {
+    /// Requests a full budget allows at once
+    pub limit: u32,
+    /// Requests left right now
+    pub remaining: u32,
+    /// Seconds until the budget is full again
+    pub reset_secs: u64,
+}
+
+impl RateLimitStatus {
+    fn insert_headers(&self, headers: &mut HeaderMap) {
+        headers.insert(LIMIT_HEADER, HeaderValue::from(self.limit));
+        headers.insert(REMAINING_HEADER, HeaderValue::from(self.remaining));
+        headers.insert(RESET_HEADER, HeaderValue::from(self.reset_secs));
+    }
+}
+
+/// A request over budget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rejected {
+    pub status: RateLimitStatus,
+    /// Seconds until the next request would be allowed
+    pub retry_after_secs: u64,
+}
+
+/// Which budget a request is charged against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RouteClass {
+    Read,
+    Write,
+    WebForm,
+}
+
+impl RouteClass {
+    /// Classifies a request, or returns `None` for exempt paths.
+    pub fn of(method: &Method, path: &str) -> Option<Self> {
+        if EXEMPT_PREFIXES
+            .iter()
+            .any(|prefix| path.starts_with(prefix))
+        {
+            return None;
+        }
+
+        let class = if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+            RouteClass::Read
+        } else if path.starts_with("/web") {
+            RouteClass::WebForm
+        } else {
+            RouteClass::Write
+        };
+        Some(class)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteClass::Read => "read",
+            RouteClass::Write => "write",
+            RouteClass::WebForm => "web_form",
+        }
+    }
+}
+
+/// Per-caller rate limiting with separate budgets for reads, API writes and
+/// `/web` form posts.
+///
+/// Callers are keyed by their [`Principal`] when authenticated and by peer IP
+/// otherwise. The proxy-supplied `X-Forwarded-For` is deliberately ignored so
+/// clients cannot pick their own bucket.
+///
+/// With a [`RedisStore`] the budgets are shared by all replicas. Whenever Redis
+/// fails, is slow or has not been reached yet, requests are checked against the
+/// in-memory limiter instead, so an outage loosens limits to per-replica rather
+/// than failing requests. Such requests are counted in
+/// `rate_limit_local_fallbacks_total`.
+#[derive(Clone)]
+pub struct RateLimit {
+    read: Arc<KeyedLimiter>,
+    write: Arc<KeyedLimiter>,
+    web_form: Arc<KeyedLimiter>,
+    budgets: RateLimitConfig,
+    redis: Option<RedisStore>,
+    degraded: Arc<AtomicBool>,
+    fallbacks: Option<IntCounter>,
+}
+
+impl RateLimit {
+    /// In-memory limiter; `redis_url` is ignored.
+    pub fn from_config(config: &RateLimitConfig) -> Self {
+        Self {
+            read: Arc::new(Self::limiter(config.read)),
+            write: Arc::new(Self::limiter(config.write)),
+            web_form: Arc::new(Self::limiter(config.web_form)),
+            budgets: config.clone(),
+            redis: None,
+            degraded: Arc::new(AtomicBool::new(false)),
+            fallbacks: None,
+        }
+    }
+
+    /// Connects to `redis_url` when configured. If Redis is unreachable the
+    /// limiter starts in-memory only, so a Redis outage never blocks startup,
+    /// and keeps trying to connect in the background.
+    pub async fn connect(config: &RateLimitConfig) -> Self {
+        let mut limits = Self::from_config(config);
+        let Some(url) = config.redis_url.as_deref() else {
+            return limits;
+        };
+
+        let store = match RedisStore::new(url, Duration::from_millis(config.redis_timeout_ms)) {
+            Ok(store) => store,
+            Err(e) => {
+                tracing::warn!(error = %e, "Invalid Redis URL, rate limits are per instance");
+                return limits;
+            }
+        };
+        match store.connect().await {
+            Ok(()) => tracing::info!("Rate limits are shared through Redis"),
+            Err(e) => {
+                tracing::warn!(
+                    error = %e,
+                    "Redis unavailable, rate limits are per instance until it can be reached"
+                );
+                let store = store.clone();
+                tokio::spawn(async move {
+                    store.connect_with_retry(RECONNECT_INTERVAL).await;
+                    tracing::info!("Redis reached, rate limits are shared through it");
+                });
+            }
+        }
+        limits.redis = Some(store);
+        limits
+    }
+
+    pub fn with_metrics(mut self, registry: &Registry) -> prometheus::Result<Self> {
+        let fallbacks = IntCounter::new(
+            "rate_limit_local_fallbacks_total",
+            "Requests checked against the in-memory limiter because Redis was unavailable",
+        )?;
+        registry.register(Box::new(fallbacks.clone()))?;
+        self.fallbacks = Some(fallbacks);
+        Ok(self)
+    }
+
+    /// Whether budgets are currently shared through Redis.
+    pub fn is_distributed(&self) -> bool {
+        self.redis.as_ref().is_some_and(RedisStore::is_connected)
+    }
+
+    fn budget_for(&self, class: RouteClass) -> RateLimitBudget {
+        match class {
+            RouteClass::Read => self.budgets.read,
+            RouteClass::Write => self.budgets.write,
+            RouteClass::WebForm => self.budgets.web_form,
+        }
+    }
+
+    fn limiter(budget: RateLimitBudget) -> KeyedLimiter {
+        let per_minute = NonZeroU32::new(budget.per_minute).unwrap_or(NonZeroU32::MIN);
+        let burst = NonZeroU32::new(budget.burst).unwrap_or(NonZeroU32::MIN);
+        RateLimiter::keyed(Quota::per_minute(per_minute).allow_burst(burst))
+            .with_middleware::<StateInformationMiddleware>()
+    }
+
+    fn limiter_for(&self, class: RouteClass) -> &KeyedLimiter {
+        match class {
+            RouteClass::Read => &self.read,
+            RouteClass::Write => &self.write,
+            RouteClass::WebForm => &self.web_form,
+        }
+    }
+
+    /// Charges one request to `key` and reports what is left of its budget.
+    pub async fn check(&self, class: RouteClass, key: &str) -> Result<RateLimitStatus, Rejected> {
+        let Some(redis) = &self.redis else {
+            return self.check_local(class, key);
+        };
+
+        let shared_key = format!("{}:{}", class.as_str(), key);
+        match redis
+            .check(&shared_key, self.budget_for(class).per_minute)
+            .await
+        {
+            Ok(result) => {
+                if self.degraded.swap(false, Ordering::Relaxed) {
+                    tracing::info!("Redis rate limit store recovered");
+                }
+                result
+            }
+            Err(e) => {
+                if !self.degraded.swap(true, Ordering::Relaxed) {
+                    tracing::warn!(
+                        error = %e,
+                        "Redis rate limit store failed, falling back to in-memory limits"
+                    );
+                }
+                if let Some(fallbacks) = &self.fallbacks {
+                    fallbacks.inc();
+                }
+                self.check_local(class, key)
+            }
+        }
+    }
+
+    /// Checks the in-memory limiter only.
+    pub fn check_local(&self, class: RouteClass, key: &str) -> Result<RateLimitStatus, Rejected> {
+        let budget = self.budget_for(class);
+        let limit = budget.burst.max(1);
+        let interval = Duration::from_secs(60) / budget.per_minute.max(1);
+
+        match self.limiter_for(class).check_key(&key.to_string()) {
+            Ok(snapshot) => {
+                let remaining = snapshot.remaining_burst_capacity();
+                Ok(RateLimitStatus {
+                    limit,
+                    remaining,
+                    reset_secs: ceil_secs(interval * (limit - remaining.min(limit))),
+                })
+            }
+            Err(not_until) => {
+                let wait = not_until.wait_time_from(DefaultClock::default().now());
+                Err(Rejected {
+                    status: RateLimitStatus {
+                        limit,
+                        remaining: 0,
+                        reset_secs: ceil_secs(wait + interval * (limit - 1)),
+                    },
+                    retry_after_secs: ceil_secs(wait),
+                })
+            }
+        }
+    }
+
+    /// Drops state for callers whose buckets have fully refilled.
+    ///
+    /// Call periodically; keyed limiters otherwise keep every caller ever seen.
+    pub fn retain_recent(&self) {
+        for limiter in [&self.read, &self.write, &self.web_form] {
+            limiter.retain_recent();
+            limiter.shrink_to_fit();
+        }
+    }
+
+    fn key_for(req: &ServiceRequest) -> String {
+        if let Some(principal) = req.extensions().get::<Principal>() {
+            return principal.to_string();
+        }
+        req.peer_addr()
+            .map(|addr| format!("ip:{}", addr.ip()))
+            .unwrap_or_else(|| "ip:unknown".to_string())
+    }
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimit
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimitMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RateLimitMiddleware {
+            service: Rc::new(service),
+            limits: self.clone(),
+        }))
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<S>,
+    limits: RateLimit,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let limits = self.limits.clone();
+
+        Box::pin(async move {
+            let Some(class) = RouteClass::of(req.method(), req.path()) else {
+                return service
+                    .call(req)
+                    .await
+                    .map(ServiceResponse::map_into_left_body);
+            };
+
+            let key = RateLimit::key_for(&req);
+            let status = match limits.check(class, &key).await {
+                Ok(status) => status,
+                Err(Rejected {
+                    status,
+                    retry_after_secs,
+                }) => {
+                    tracing::warn!(
+                        key = %key,
+                        class = class.as_str(),
+                        retry_after_secs,
+                        "Rate limit exceeded"
+                    );
+                    let mut response = AppError::RateLimited { retry_after_secs }.error_response();
+                    status.insert_headers(response.headers_mut());
+                    return Ok(req.into_response(response).map_into_right_body());
+                }
+            };
+
+            let mut res = service.call(req).await?;
+            status.insert_headers(res.headers_mut());
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(burst: u32) -> RateLimitConfig {
+        let budget = RateLimitBudget {
+            per_minute: 1,
+            burst,
+        };
+        RateLimitConfig {
+            read: budget,
+            write: budget,
+            web_form: budget,
+            redis_url: None,
+            redis_timeout_ms: 50,
+        }
+    }
+
+    #[test]
+    fn test_classifies_routes() {
+        assert_eq!(
+            RouteClass::of(&Method::GET, "/api/v1/memos"),
+            Some(RouteClass::Read)
+        );
+        assert_eq!(
+            RouteClass::of(&Method::POST, "/api/v1/memos"),
+            Some(RouteClass::Write)
+        );
+        assert_eq!(
+            RouteClass::of(&Method::DELETE, "/web/memos/1"),
+            Some(RouteClass::WebForm)
+        );
+        assert_eq!(RouteClass::of(&Method::GET, "/health"), None);
+        assert_eq!(RouteClass::of(&Method::GET, "/static/css/style.css"), None);
+    }
+
+    #[test]
+    fn test_budgets_are_tracked_per_class_and_key() {
+        let limits = RateLimit::from_config(&config(2));
+
+        assert!(limits.check_local(RouteClass::Write, "ip:1.1.1.1").is_ok());
+        assert!(limits.check_local(RouteClass::Write, "ip:1.1.1.1").is_ok());
+        let rejected = limits
+            .check_local(RouteClass::Write, "ip:1.1.1.1")
+            .unwrap_err();
+        assert!(rejected.retry_after_secs > 0 && rejected.retry_after_secs <= 60);
+
+        assert!(limits.check_local(RouteClass::Read, "ip:1.1.1.1").is_ok());
+        assert!(limits.check_local(RouteClass::Write, "ip:2.2.2.2").is_ok());
+    }
+
+    #[test]
+    fn test_status_reports_the_remaining_budget() {
+        let limits = RateLimit::from_config(&config(3));
+
+        let first = limits.check_local(RouteClass::Read, "ip:1.1.1.1").unwrap();
+        assert_eq!((first.limit, first.remaining), (3, 2));
+        assert!(first.reset_secs > 0 && first.reset_secs <= 60);
+
+        limits.check_local(RouteClass::Read, "ip:1.1.1.1").unwrap();
+        let last = limits.check_local(RouteClass::Read, "ip:1.1.1.1").unwrap();
+        assert_eq!(last.remaining, 0);
+        assert!(last.reset_secs > 120 && last.reset_secs <= 180);
+
+        let rejected = limits
+            .check_local(RouteClass::Read, "ip:1.1.1.1")
+            .unwrap_err();
+        assert_eq!(rejected.status.remaining, 0);
+        assert!(rejected.status.reset_secs >= rejected.retry_after_secs);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_redis_falls_back_to_memory() {
+        let mut config = config(1);
+        config.redis_url = Some("redis://127.0.0.1:1".to_string());
+
+        let registry = Registry::new();
+        let limits = RateLimit::connect(&config)
+            .await
+            .with_metrics(&registry)
+            .unwrap();
+
+        assert!(!limits.is_distributed());
+        assert!(limits.check(RouteClass::Write, "ip:1.1.1.1").await.is_ok());
+        assert!(limits.check(RouteClass::Write, "ip:1.1.1.1").await.is_err());
+        assert_eq!(limits.fallbacks.as_ref().unwrap().get(), 2);
+        // The store stays configured, so requests go back to Redis once the
+        // background reconnect gets through
+        assert!(limits.redis.is_some());
+    }
+}
@@ -0,0 +1,149 @@
+use redis::aio::ConnectionManager;
+use redis::{AsyncCommands, Script};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::{RateLimitStatus, Rejected};
+
+/// Sliding-window log: drop entries older than the window, then admit the
+/// request only if fewer than `limit` remain. Returns `{wait, remaining, reset}`:
+/// `wait` is 0 when admitted, otherwise the milliseconds until the oldest entry
+/// leaves the window; `reset` is the milliseconds until the newest one does.
+///
+/// The time comes from the Redis server, so replicas with skewed clocks still
+/// share one window.
+const SLIDING_WINDOW: &str = r#"
+local key = KEYS[1]
+local window = tonumber(ARGV[1])
+local limit = tonumber(ARGV[2])
+local member = ARGV[3]
+
+-- Needed before TIME on Redis < 5, where scripts replicate verbatim
+if redis.replicate_commands then
+    redis.replicate_commands()
+end
+local time = redis.call('TIME')
+local now = tonumber(time[1]) * 1000 + math.floor(tonumber(time[2]) / 1000)
+
+redis.call('ZREMRANGEBYSCORE', key, 0, now - window)
+local count = redis.call('ZCARD', key)
+if count < limit then
+    redis.call('ZADD', key, now, member)
+    redis.call('PEXPIRE', key, window)
+    return {0, limit - count - 1, window}
+end
+
+local oldest = redis.call('ZRANGE', key, 0, 0, 'WITHSCORES')
+local newest = redis.call('ZRANGE', key, -1, -1, 'WITHSCORES')
+return {
+    math.max(1, tonumber(oldest[2]) + window - now),
+    0,
+    math.max(1, tonumber(newest[2]) + window - now)
+}
+"#;
+
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Rate limit counters shared by all replicas through Redis.
+///
+/// Each caller gets a sliding 60-second window holding at most `per_minute`
+/// requests. Burst sizes only apply to the in-memory limiter.
+///
+/// Checks fail until [`Self::connect`] succeeds once; after that the
+/// connection reconnects on its own. Clones share the connection.
+#[derive(Clone)]
+pub struct RedisStore {
+    client: redis::Client,
+    conn: Arc<Mutex<Option<ConnectionManager>>>,
+    script: Arc<Script>,
+    timeout: Duration,
+}
+
+impl RedisStore {
+    /// A store for `url`, not connected yet. Fails only for invalid URLs.
+    pub fn new(url: &str, timeout: Duration) -> redis::RedisResult<Self> {
+        Ok(Self {
+            client: redis::Client::open(url)?,
+            conn: Arc::new(Mutex::new(None)),
+            script: Arc::new(Script::new(SLIDING_WINDOW)),
+            timeout,
+        })
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.connection().is_some()
+    }
+
+    fn connection(&self) -> Option<ConnectionManager> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Opens the shared connection and checks it with a `PING`.
+    pub async fn connect(&self) -> redis::RedisResult<()> {
+        let mut conn = tokio::time::timeout(
+            self.timeout.max(Duration::from_secs(1)),
+            self.client.get_connection_manager(),
+        )
+        .await
+        .map_err(|_| {
+            redis::RedisError::from(std::io::Error::from(std::io::ErrorKind::TimedOut))
+        })??;
+        let _: () = conn.ping().await?;
+
+        *self.conn.lock().unwrap_or_else(|e| e.into_inner()) = Some(conn);
+        Ok(())
+    }
+
+    /// Calls [`Self::connect`] every `interval` until it succeeds.
+    pub async fn connect_with_retry(&self, interval: Duration) {
+        loop {
+            tokio::time::sleep(interval).await;
+            match self.connect().await {
+                Ok(()) => return,
+                Err(e) => tracing::debug!(error = %e, "Redis rate limit store still unreachable"),
+            }
+        }
+    }
+
+    /// Records a request for `key`. `Ok(Err(_))` means the window is full;
+    /// `Err` means Redis is not connected or could not answer in time, and the
+    /// caller should fall back.
+    pub async fn check(
+        &self,
+        key: &str,
+        limit: u32,
+    ) -> redis::RedisResult<Result<RateLimitStatus, Rejected>> {
+        // Only has to be unique within the window
+        let member = format!("{:016x}", rand::random::<u64>());
+
+        let mut conn = self.connection().ok_or_else(|| {
+            redis::RedisError::from(std::io::Error::from(std::io::ErrorKind::NotConnected))
+        })?;
+        let mut invocation = self.script.key(format!("ratelimit:{}", key));
+        invocation
+            .arg(WINDOW.as_millis() as u64)
+            .arg(limit)
+            .arg(member);
+
+        let (wait_ms, remaining, reset_ms): (u64, u32, u64) =
+            tokio::time::timeout(self.timeout, invocation.invoke_async(&mut conn))
+                .await
+                .map_err(|_| {
+                    redis::RedisError::from(std::io::Error::from(std::io::ErrorKind::TimedOut))
+                })??;
+
+        let status = RateLimitStatus {
+            limit,
+            remaining,
+            reset_secs: reset_ms.div_ceil(1000),
+        };
+        Ok(if wait_ms == 0 {
+            Ok(status)
+        } else {
+            Err(Rejected {
+                status,
+                retry_after_secs: wait_ms.div_ceil(1000),
+            })
+        })
+    }
+}
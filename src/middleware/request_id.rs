@@ -0,0 +1,161 @@
+use actix_web::Error;
+use actix_web::HttpMessage;
+use actix_web::HttpRequest;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest client-supplied request ID that is kept rather than replaced.
+const MAX_LEN: usize = 128;
+
+/// Identifies one request and when it arrived, for response metadata and
+/// logs. Set by [`AssignRequestId`].
+#[derive(Debug, Clone)]
+pub struct RequestId {
+    id: String,
+    received_at: Instant,
+}
+
+impl RequestId {
+    /// The request ID assigned to `req`, if the middleware ran.
+    pub fn for_request(req: &HttpRequest) -> Option<Self> {
+        req.extensions().get::<Self>().cloned()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.id
+    }
+
+    /// Time since the request reached the middleware.
+    pub fn elapsed(&self) -> Duration {
+        self.received_at.elapsed()
+    }
+
+    /// Keeps the caller's `X-Request-Id` so IDs can be followed across
+    /// services, unless it is empty, too long or not a plain token; otherwise
+    /// a new UUID is used.
+    fn from_header(value: Option<&HeaderValue>) -> Self {
+        let id = value
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            .filter(|id| {
+                !id.is_empty()
+                    && id.len() <= MAX_LEN
+                    && id
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b"-_.:".contains(&b))
+            })
+            .map_or_else(|| Uuid::new_v4().to_string(), str::to_string);
+        Self {
+            id,
+            received_at: Instant::now(),
+        }
+    }
+}
+
+/// Assigns each request a [`RequestId`] and echoes it in the `X-Request-Id`
+/// response header. Wrap it outermost so the timing covers the whole request.
+pub struct AssignRequestId;
+
+impl<S, B> Transform<S, ServiceRequest> for AssignRequestId
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AssignRequestIdMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AssignRequestIdMiddleware { service }))
+    }
+}
+
+pub struct AssignRequestIdMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for AssignRequestIdMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = RequestId::from_header(req.headers().get(REQUEST_ID_HEADER));
+        // Checked when the ID was accepted or generated, so always valid.
+        let header = HeaderValue::from_str(request_id.as_str()).ok();
+        req.extensions_mut().insert(request_id);
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let mut res = fut.await?;
+            if let Some(header) = header {
+                res.headers_mut().insert(REQUEST_ID_HEADER, header);
+            }
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, test as actix_test, web};
+
+    #[test]
+    fn test_keeps_plain_client_ids_and_replaces_others() {
+        let kept = RequestId::from_header(Some(&HeaderValue::from_static("abc-123_x.y:z")));
+        assert_eq!(kept.as_str(), "abc-123_x.y:z");
+
+        let long = "a".repeat(MAX_LEN + 1);
+        for value in ["", "has space", "<script>", long.as_str()] {
+            let replaced = RequestId::from_header(Some(&HeaderValue::from_str(value).unwrap()));
+            assert!(
+                Uuid::parse_str(replaced.as_str()).is_ok(),
+                "{value:?} should be replaced"
+            );
+        }
+        assert!(Uuid::parse_str(RequestId::from_header(None).as_str()).is_ok());
+    }
+
+    #[actix_web::test]
+    async fn test_exposes_the_id_to_handlers_and_the_response() {
+        let app = actix_test::init_service(App::new().wrap(AssignRequestId).route(
+            "/",
+            web::get().to(|req: HttpRequest| async move {
+                HttpResponse::Ok().body(RequestId::for_request(&req).unwrap().as_str().to_string())
+            }),
+        ))
+        .await;
+
+        let req = actix_test::TestRequest::get()
+            .uri("/")
+            .insert_header((REQUEST_ID_HEADER, "client-id"))
+            .to_request();
+        let resp = actix_test::call_service(&app, req).await;
+        assert_eq!(resp.headers().get(REQUEST_ID_HEADER).unwrap(), "client-id");
+        assert_eq!(actix_test::read_body(resp).await, "client-id");
+
+        let resp =
+            actix_test::call_service(&app, actix_test::TestRequest::get().uri("/").to_request())
+                .await;
+        let header = resp.headers().get(REQUEST_ID_HEADER).unwrap().clone();
+        assert_eq!(actix_test::read_body(resp).await, header.as_bytes());
+    }
+}
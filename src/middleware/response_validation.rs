@@ -0,0 +1,315 @@
+use actix_web::body::{self, BodySize, EitherBody, MessageBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::error::Error;
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::web::Bytes;
+use jsonschema::Validator;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use utoipa::openapi::OpenApi;
+
+use crate::handlers::negotiate::PREFER_ENVELOPE;
+
+/// Set on responses that do not match the OpenAPI document; the value is the
+/// number of problems found.
+pub const MISMATCH_HEADER: HeaderName = HeaderName::from_static("x-response-schema-errors");
+
+/// Checks JSON responses of documented operations against the OpenAPI
+/// document, so drift between handlers and docs shows up while developing
+/// rather than in generated clients. Development only; it buffers every
+/// checked body.
+///
+/// Responses are matched by route pattern, method and status. An undocumented
+/// status or a body that fails its schema is logged and flagged with
+/// [`MISMATCH_HEADER`]; the response itself is passed through unchanged.
+/// Documented objects are treated as closed, so undocumented fields count as
+/// mismatches too. Streaming bodies, enveloped responses (`Prefer: envelope`)
+/// and routes missing from the document are not checked. The default instance
+/// documents nothing and checks nothing.
+#[derive(Clone, Default)]
+pub struct ResponseValidation {
+    operations: Arc<HashMap<(String, String), Operation>>,
+}
+
+/// Carries `envelope` on responses wrapped at the client's request, whose
+/// bodies are not what the document describes.
+const PREFERENCE_APPLIED: HeaderName = HeaderName::from_static("preference-applied");
+
+/// Documented responses of one operation: status to body validator, `None`
+/// for responses without a JSON body.
+type Operation = HashMap<u16, Option<Arc<Validator>>>;
+
+impl ResponseValidation {
+    /// Compiles a validator for every documented JSON response.
+    pub fn new(doc: &OpenApi) -> anyhow::Result<Self> {
+        let mut doc = serde_json::to_value(doc)?;
+        close_objects(&mut doc["components"]);
+
+        let mut operations = HashMap::new();
+        let paths = doc["paths"].as_object().cloned().unwrap_or_default();
+        for (path, item) in paths {
+            let Some(methods) = item.as_object() else {
+                continue;
+            };
+            for (method, operation) in methods {
+                let Some(responses) = operation["responses"].as_object() else {
+                    continue;
+                };
+
+                let mut documented = HashMap::new();
+                for (status, response) in responses {
+                    let Ok(status) = status.parse::<u16>() else {
+                        continue;
+                    };
+                    let schema = &response["content"]["application/json"]["schema"];
+                    let validator = if schema.is_null() {
+                        None
+                    } else {
+                        let root = json!({ "allOf": [schema], "components": doc["components"] });
+                        let validator = jsonschema::validator_for(&root).map_err(|e| {
+                            anyhow::anyhow!("Schema for {method} {path} {status}: {e}")
+                        })?;
+                        Some(Arc::new(validator))
+                    };
+                    documented.insert(status, validator);
+                }
+                operations.insert((method.to_uppercase(), path.clone()), documented);
+            }
+        }
+
+        Ok(Self {
+            operations: Arc::new(operations),
+        })
+    }
+
+    /// Problems with a response; empty when it matches the document or the
+    /// operation is not documented.
+    fn check(&self, method: &str, pattern: &str, status: u16, body: Option<&Value>) -> Vec<String> {
+        let Some(operation) = self
+            .operations
+            .get(&(method.to_string(), pattern.to_string()))
+        else {
+            return Vec::new();
+        };
+
+        match operation.get(&status) {
+            None => vec![format!("status {status} is not documented")],
+            Some(Some(validator)) => match body {
+                Some(body) => validator
+                    .iter_errors(body)
+                    .map(|e| format!("{} at {}", e, e.instance_path))
+                    .collect(),
+                None => vec!["documented JSON body is missing".to_string()],
+            },
+            Some(None) => Vec::new(),
+        }
+    }
+}
+
+/// Adds `additionalProperties: false` to object schemas that list their
+/// properties.
+fn close_objects(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if map.contains_key("properties") && !map.contains_key("additionalProperties") {
+                map.insert("additionalProperties".to_string(), Value::Bool(false));
+            }
+            map.values_mut().for_each(close_objects);
+        }
+        Value::Array(items) => items.iter_mut().for_each(close_objects),
+        _ => {}
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ResponseValidation
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, Bytes>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = ResponseValidationMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ResponseValidationMiddleware {
+            service: Rc::new(service),
+            config: self.clone(),
+        }))
+    }
+}
+
+pub struct ResponseValidationMiddleware<S> {
+    service: Rc<S>,
+    config: ResponseValidation,
+}
+
+impl<S, B> Service<ServiceRequest> for ResponseValidationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B, Bytes>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            let res = service.call(req).await?;
+
+            let Some(pattern) = res.request().match_pattern() else {
+                return Ok(res.map_into_left_body());
+            };
+            let method = res.request().method().to_string();
+            if !config
+                .operations
+                .contains_key(&(method.clone(), pattern.clone()))
+                || matches!(res.response().body().size(), BodySize::Stream)
+                || res
+                    .headers()
+                    .get_all(PREFERENCE_APPLIED)
+                    .any(|value| value.as_bytes() == PREFER_ENVELOPE.as_bytes())
+            {
+                return Ok(res.map_into_left_body());
+            }
+
+            let is_json = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .is_some_and(|value| value.starts_with("application/json"));
+            let status = res.status().as_u16();
+            let (req, res) = res.into_parts();
+            let (mut res, body) = res.into_parts();
+            let bytes = match body::to_bytes(body).await {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    let e: Box<dyn std::error::Error> = e.into();
+                    tracing::warn!(%method, %pattern, error = %e, "Could not buffer response for schema validation");
+                    Bytes::new()
+                }
+            };
+
+            let parsed = if is_json && !bytes.is_empty() {
+                match serde_json::from_slice::<Value>(&bytes) {
+                    Ok(value) => Some(value),
+                    Err(e) => {
+                        tracing::warn!(%method, %pattern, error = %e, "Response is not valid JSON");
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+            let problems = config.check(&method, &pattern, status, parsed.as_ref());
+            if !problems.is_empty() {
+                tracing::warn!(
+                    %method,
+                    %pattern,
+                    status,
+                    problems = ?problems,
+                    "Response does not match the OpenAPI document"
+                );
+                res.headers_mut()
+                    .insert(MISMATCH_HEADER, HeaderValue::from(problems.len()));
+            }
+
+            Ok(ServiceResponse::new(req, res.set_body(bytes)).map_into_right_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, get, test};
+    use serde::Serialize;
+    use utoipa::{OpenApi, ToSchema};
+
+    #[derive(Serialize, ToSchema)]
+    struct Thing {
+        id: u32,
+        name: String,
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/things/{id}",
+        params(("id" = u32, Path)),
+        responses((status = 200, body = Thing), (status = 404))
+    )]
+    #[get("/things/{id}")]
+    async fn get_thing(id: actix_web::web::Path<u32>) -> HttpResponse {
+        match id.into_inner() {
+            1 => HttpResponse::Ok().json(json!({ "id": 1, "name": "first" })),
+            2 => HttpResponse::Ok().json(json!({ "id": "2", "colour": "red" })),
+            3 => HttpResponse::Conflict().finish(),
+            _ => HttpResponse::NotFound().finish(),
+        }
+    }
+
+    #[derive(OpenApi)]
+    #[openapi(paths(get_thing), components(schemas(Thing)))]
+    struct Doc;
+
+    async fn mismatches(uri: &str) -> Option<String> {
+        let validation = ResponseValidation::new(&Doc::openapi()).unwrap();
+        let app = test::init_service(App::new().wrap(validation).service(get_thing)).await;
+        let res = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        res.headers()
+            .get(MISMATCH_HEADER)
+            .map(|value| value.to_str().unwrap().to_string())
+    }
+
+    #[actix_web::test]
+    async fn test_matching_responses_are_not_flagged() {
+        assert_eq!(mismatches("/things/1").await, None);
+        assert_eq!(mismatches("/things/9").await, None);
+    }
+
+    #[actix_web::test]
+    async fn test_wrong_types_and_undocumented_fields_are_flagged() {
+        assert_eq!(mismatches("/things/2").await.as_deref(), Some("3"));
+    }
+
+    #[actix_web::test]
+    async fn test_undocumented_statuses_are_flagged() {
+        assert_eq!(mismatches("/things/3").await.as_deref(), Some("1"));
+    }
+
+    #[actix_web::test]
+    async fn test_the_api_document_compiles() {
+        let validation = ResponseValidation::new(&crate::docs::ApiDoc::openapi()).unwrap();
+
+        let operation =
+            &validation.operations[&("GET".to_string(), "/api/v1/memos/{id}".to_string())];
+        assert!(matches!(operation.get(&200), Some(Some(_))));
+    }
+
+    #[actix_web::test]
+    async fn test_bodies_are_passed_through() {
+        let validation = ResponseValidation::new(&Doc::openapi()).unwrap();
+        let app = test::init_service(App::new().wrap(validation).service(get_thing)).await;
+
+        let body: Value = test::call_and_read_body_json(
+            &app,
+            test::TestRequest::get().uri("/things/1").to_request(),
+        )
+        .await;
+
+        assert_eq!(body, json!({ "id": 1, "name": "first" }));
+    }
+}
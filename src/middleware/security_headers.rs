@@ -1,10 +1,265 @@
-use actix_web::Error;
-use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
-use actix_web::http::header::HeaderValue;
+use actix_web::dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::{Error, FromRequest, HttpMessage, HttpRequest};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use rand::RngCore;
+use std::convert::Infallible;
+use std::fmt;
 use std::future::{Ready, ready};
 use std::pin::Pin;
+use std::sync::Arc;
 
-pub struct SecurityHeaders;
+use crate::config::settings::{CspConfig, HstsConfig, SecurityConfig};
+
+/// Smallest HSTS max-age browsers accept onto their preload lists.
+pub const HSTS_PRELOAD_MIN_AGE_SECS: u64 = 31_536_000;
+
+/// Sets security headers on every response. Values come from
+/// [`SecurityConfig`]; `X-Content-Type-Options` and `X-XSS-Protection` are
+/// always sent.
+///
+/// With `script_nonce` enabled, every request gets a fresh [`CspNonce`] that
+/// is added to the policy's `script-src`; templates put it on their inline
+/// `<script>` tags.
+#[derive(Clone)]
+pub struct SecurityHeaders {
+    headers: Arc<Vec<(HeaderName, HeaderValue)>>,
+    /// Policy to complete with the request's nonce, when nonces are enabled
+    nonce_csp: Option<Arc<ContentSecurityPolicy>>,
+}
+
+impl SecurityHeaders {
+    pub fn from_config(config: &SecurityConfig) -> Result<Self, String> {
+        let mut headers = vec![
+            (
+                header::X_CONTENT_TYPE_OPTIONS,
+                HeaderValue::from_static("nosniff"),
+            ),
+            (
+                header::X_XSS_PROTECTION,
+                HeaderValue::from_static("1; mode=block"),
+            ),
+        ];
+
+        let mut add = |name: HeaderName, value: &str| {
+            HeaderValue::from_str(value)
+                .map(|value| headers.push((name.clone(), value)))
+                .map_err(|_| format!("invalid {name} value '{value}'"))
+        };
+        if let Some(frame_options) = &config.frame_options {
+            add(header::X_FRAME_OPTIONS, frame_options)?;
+        }
+        if let Some(hsts) = &config.hsts {
+            add(header::STRICT_TRANSPORT_SECURITY, &hsts_value(hsts)?)?;
+        }
+        if !config.referrer_policy.is_empty() {
+            add(header::REFERRER_POLICY, &config.referrer_policy)?;
+        }
+        let csp = ContentSecurityPolicy::from_config(&config.csp)?;
+        let mut nonce_csp = None;
+        if config.csp.script_nonce && csp.has_directive("script-src") {
+            nonce_csp = Some(Arc::new(csp));
+        } else if !csp.is_empty() {
+            add(header::CONTENT_SECURITY_POLICY, &csp.to_string())?;
+        }
+        if !config.permissions_policy.is_empty() {
+            add(
+                HeaderName::from_static("permissions-policy"),
+                &config.permissions_policy,
+            )?;
+        }
+
+        Ok(Self {
+            headers: Arc::new(headers),
+            nonce_csp,
+        })
+    }
+}
+
+/// Per-request nonce allowing inline scripts under the CSP.
+///
+/// Taken from the request when [`SecurityHeaders`] set one; otherwise a fresh
+/// nonce is generated, which is harmless because no policy then refers to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CspNonce(String);
+
+impl CspNonce {
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 16];
+        rand::rng().fill_bytes(&mut bytes);
+        Self(STANDARD.encode(bytes))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CspNonce {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromRequest for CspNonce {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let nonce = req.extensions().get::<CspNonce>().cloned();
+        ready(Ok(nonce.unwrap_or_else(CspNonce::generate)))
+    }
+}
+
+fn hsts_value(hsts: &HstsConfig) -> Result<String, String> {
+    if hsts.preload && (!hsts.include_subdomains || hsts.max_age_secs < HSTS_PRELOAD_MIN_AGE_SECS) {
+        return Err(format!(
+            "HSTS preload requires includeSubDomains and a max-age of at least {HSTS_PRELOAD_MIN_AGE_SECS}"
+        ));
+    }
+
+    let mut value = format!("max-age={}", hsts.max_age_secs);
+    if hsts.include_subdomains {
+        value.push_str("; includeSubDomains");
+    }
+    if hsts.preload {
+        value.push_str("; preload");
+    }
+    Ok(value)
+}
+
+/// A `Content-Security-Policy` built directive by directive.
+///
+/// ```
+/// use actix_web_template::middleware::security_headers::ContentSecurityPolicy;
+///
+/// let csp = ContentSecurityPolicy::new()
+///     .directive("default-src", ["'self'"])
+///     .directive("frame-src", ["https://www.youtube-nocookie.com"])
+///     .report_uri("/csp-reports");
+/// assert_eq!(
+///     csp.to_string(),
+///     "default-src 'self'; frame-src https://www.youtube-nocookie.com; report-uri /csp-reports"
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentSecurityPolicy {
+    directives: Vec<(String, Vec<String>)>,
+}
+
+impl ContentSecurityPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_config(config: &CspConfig) -> Result<Self, String> {
+        let mut csp = Self::new();
+        for (name, sources) in &config.directives {
+            csp = csp.directive(name, sources);
+        }
+        if let Some(uri) = &config.report_uri {
+            csp = csp.report_uri(uri);
+        }
+        csp.validate()?;
+        Ok(csp)
+    }
+
+    /// Sets the sources of a directive, replacing any earlier ones. A
+    /// directive without sources (like `upgrade-insecure-requests`) is
+    /// written as its bare name.
+    pub fn directive<I, S>(mut self, name: &str, sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let name = name.to_ascii_lowercase();
+        let sources = sources
+            .into_iter()
+            .map(|source| source.as_ref().to_string())
+            .collect();
+        match self.directives.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = sources,
+            None => self.directives.push((name, sources)),
+        }
+        self
+    }
+
+    /// Where browsers post violation reports.
+    pub fn report_uri(self, uri: &str) -> Self {
+        self.directive("report-uri", [uri])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.directives.is_empty()
+    }
+
+    pub fn has_directive(&self, name: &str) -> bool {
+        self.directives.iter().any(|(n, _)| n == name)
+    }
+
+    /// Whether a `<script src>` of `url` may load under `script-src`, or
+    /// `default-src` without it. A prefix match against host and scheme
+    /// sources, good enough to warn about a policy that blocks it.
+    pub fn allows_script(&self, url: &str) -> bool {
+        let sources = ["script-src", "default-src"]
+            .iter()
+            .find_map(|name| self.directives.iter().find(|(n, _)| n == name));
+        let Some((_, sources)) = sources else {
+            return true;
+        };
+        let same_origin = url.starts_with('/') && !url.starts_with("//");
+        sources.iter().any(|source| match source.as_str() {
+            "*" => true,
+            "'self'" => same_origin,
+            source if source.starts_with('\'') => false,
+            source => url.starts_with(source),
+        })
+    }
+
+    /// Allows inline scripts carrying `nonce`. Browsers ignore
+    /// `'unsafe-inline'` once a nonce is present, so it is dropped.
+    pub fn with_script_nonce(mut self, nonce: &CspNonce) -> Self {
+        if let Some((_, sources)) = self.directives.iter_mut().find(|(n, _)| n == "script-src") {
+            sources.retain(|source| source != "'unsafe-inline'");
+            sources.push(format!("'nonce-{nonce}'"));
+        }
+        self
+    }
+
+    /// Rejects names and sources that would break out of their directive.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, sources) in &self.directives {
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_lowercase() || c == '-') {
+                return Err(format!("invalid CSP directive '{name}'"));
+            }
+            for source in sources {
+                if source.is_empty()
+                    || source
+                        .chars()
+                        .any(|c| c.is_whitespace() || c.is_control() || matches!(c, ';' | ','))
+                {
+                    return Err(format!("invalid source '{source}' in CSP directive {name}"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ContentSecurityPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, (name, sources)) in self.directives.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            f.write_str(name)?;
+            for source in sources {
+                write!(f, " {source}")?;
+            }
+        }
+        Ok(())
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for SecurityHeaders
 where
@@ -19,12 +274,18 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ready(Ok(SecurityHeadersMiddleware { service }))
+        ready(Ok(SecurityHeadersMiddleware {
+            service,
+            headers: self.headers.clone(),
+            nonce_csp: self.nonce_csp.clone(),
+        }))
     }
 }
 
 pub struct SecurityHeadersMiddleware<S> {
     service: S,
+    headers: Arc<Vec<(HeaderName, HeaderValue)>>,
+    nonce_csp: Option<Arc<ContentSecurityPolicy>>,
 }
 
 impl<S, B> Service<ServiceRequest> for SecurityHeadersMiddleware<S>
@@ -40,46 +301,166 @@ where
     forward_ready!(service);
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
+        let csp = self.nonce_csp.as_ref().map(|csp| {
+            let nonce = CspNonce::generate();
+            let csp = csp.as_ref().clone().with_script_nonce(&nonce);
+            req.extensions_mut().insert(nonce);
+            csp
+        });
         let fut = self.service.call(req);
+        let headers = self.headers.clone();
 
         Box::pin(async move {
             let mut res = fut.await?;
 
-            let headers = res.headers_mut();
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("x-content-type-options"),
-                HeaderValue::from_static("nosniff"),
-            );
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("x-frame-options"),
-                HeaderValue::from_static("DENY"),
-            );
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("x-xss-protection"),
-                HeaderValue::from_static("1; mode=block"),
-            );
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("strict-transport-security"),
-                HeaderValue::from_static("max-age=31536000; includeSubDomains"),
-            );
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("referrer-policy"),
-                HeaderValue::from_static("strict-origin-when-cross-origin"),
-            );
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static(
-                    "content-security-policy",
-                ),
-                HeaderValue::from_static(
-                    "default-src 'self'; script-src 'self' 'unsafe-inline'; style-src 'self' 'unsafe-inline'; img-src 'self' data:; font-src 'self' data:; connect-src 'self'; frame-ancestors 'none';",
-                ),
-            );
-            headers.insert(
-                actix_web::http::header::HeaderName::from_static("permissions-policy"),
-                HeaderValue::from_static("geolocation=(), microphone=(), camera=()"),
-            );
+            for (name, value) in headers.iter() {
+                res.headers_mut().insert(name.clone(), value.clone());
+            }
+            // Validated in `from_config`; a nonce is plain base64
+            if let Some(csp) = csp
+                && let Ok(value) = HeaderValue::from_str(&csp.to_string())
+            {
+                res.headers_mut()
+                    .insert(header::CONTENT_SECURITY_POLICY, value);
+            }
 
             Ok(res)
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::{TestRequest, call_service, init_service};
+    use actix_web::{App, HttpResponse, web};
+
+    fn config() -> SecurityConfig {
+        SecurityConfig {
+            frame_options: Some("DENY".to_string()),
+            referrer_policy: "no-referrer".to_string(),
+            permissions_policy: String::new(),
+            hsts: Some(HstsConfig {
+                max_age_secs: HSTS_PRELOAD_MIN_AGE_SECS,
+                include_subdomains: true,
+                preload: true,
+            }),
+            csp: CspConfig {
+                directives: vec![
+                    ("default-src".to_string(), vec!["'self'".to_string()]),
+                    (
+                        "script-src".to_string(),
+                        vec!["'self'".to_string(), "https://cdn.example.com".to_string()],
+                    ),
+                ],
+                report_uri: Some("/csp-reports".to_string()),
+                script_nonce: false,
+            },
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_configured_headers_are_sent() {
+        let headers = SecurityHeaders::from_config(&config()).unwrap();
+        let app = init_service(
+            App::new()
+                .wrap(headers)
+                .default_service(web::to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res = call_service(&app, TestRequest::get().to_request()).await;
+        let header = |name: &str| res.headers().get(name).map(|v| v.to_str().unwrap());
+
+        assert_eq!(
+            header("content-security-policy"),
+            Some(
+                "default-src 'self'; script-src 'self' https://cdn.example.com; report-uri /csp-reports"
+            )
+        );
+        assert_eq!(
+            header("strict-transport-security"),
+            Some("max-age=31536000; includeSubDomains; preload")
+        );
+        assert_eq!(header("x-frame-options"), Some("DENY"));
+        assert_eq!(header("x-content-type-options"), Some("nosniff"));
+        assert_eq!(header("permissions-policy"), None);
+    }
+
+    #[actix_web::test]
+    async fn test_each_request_gets_its_own_script_nonce() {
+        let mut config = config();
+        config.csp.script_nonce = true;
+        config.csp.directives[1].1 = vec!["'self'".to_string(), "'unsafe-inline'".to_string()];
+        let headers = SecurityHeaders::from_config(&config).unwrap();
+        let app = init_service(App::new().wrap(headers).default_service(web::to(
+            |nonce: CspNonce| async move { HttpResponse::Ok().body(nonce.to_string()) },
+        )))
+        .await;
+
+        let mut policies = Vec::new();
+        for _ in 0..2 {
+            let res = call_service(&app, TestRequest::get().to_request()).await;
+            let csp = res
+                .headers()
+                .get(header::CONTENT_SECURITY_POLICY)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string();
+            let nonce = actix_web::body::to_bytes(res.into_body()).await.unwrap();
+            let nonce = std::str::from_utf8(&nonce).unwrap();
+
+            assert!(csp.contains(&format!("script-src 'self' 'nonce-{nonce}';")));
+            policies.push(csp);
+        }
+        assert_ne!(policies[0], policies[1]);
+    }
+
+    #[test]
+    fn test_later_directives_replace_earlier_ones() {
+        let csp = ContentSecurityPolicy::new()
+            .directive("img-src", ["'self'"])
+            .directive("upgrade-insecure-requests", [] as [&str; 0])
+            .directive("IMG-SRC", ["'self'", "data:"]);
+
+        assert_eq!(
+            csp.to_string(),
+            "img-src 'self' data:; upgrade-insecure-requests"
+        );
+    }
+
+    #[test]
+    fn test_script_sources_fall_back_to_default_src() {
+        let csp = ContentSecurityPolicy::from_config(&config().csp).unwrap();
+        assert!(csp.allows_script("/static/app.js"));
+        assert!(csp.allows_script("https://cdn.example.com/redoc.js"));
+        assert!(!csp.allows_script("https://unpkg.com/rapidoc/dist/rapidoc-min.js"));
+
+        let csp = ContentSecurityPolicy::new().directive("default-src", ["'self'"]);
+        assert!(!csp.allows_script("//cdn.example.com/redoc.js"));
+        assert!(ContentSecurityPolicy::new().allows_script("https://unpkg.com/a.js"));
+    }
+
+    #[test]
+    fn test_sources_cannot_inject_directives() {
+        let mut config = config();
+        config.csp.directives[1]
+            .1
+            .push("'self'; script-src *".to_string());
+
+        assert!(SecurityHeaders::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_preload_requires_a_long_max_age_and_subdomains() {
+        let mut config = config();
+        config.hsts = Some(HstsConfig {
+            max_age_secs: 300,
+            include_subdomains: true,
+            preload: true,
+        });
+
+        assert!(SecurityHeaders::from_config(&config).is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use actix_web::ResponseError;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::error::{Error, InternalError};
+use prometheus::{IntCounterVec, Opts, Registry};
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::config::settings::ServerConfig;
+use crate::error::AppError;
+
+/// Aborts handlers that run longer than their route's timeout and answers 504.
+///
+/// Dropping the handler future cancels whatever it was awaiting (e.g. a slow
+/// query), so the worker and the keep-alive connection are freed right away.
+///
+/// The request can't be cloned before routing, so the 504 is returned as an
+/// error carrying an already rendered (and localized) response. Outer
+/// middleware such as the Prometheus exporter does not see it; timeouts are
+/// counted by `http_requests_timed_out_total` instead.
+#[derive(Clone)]
+pub struct RequestTimeout {
+    default: Duration,
+    routes: Arc<Vec<(String, Duration)>>,
+    timed_out: Option<IntCounterVec>,
+}
+
+impl RequestTimeout {
+    pub fn new(default: Duration) -> Self {
+        Self {
+            default,
+            routes: Arc::new(Vec::new()),
+            timed_out: None,
+        }
+    }
+
+    pub fn from_config(config: &ServerConfig) -> Self {
+        config.request_timeout_overrides.iter().fold(
+            Self::new(Duration::from_millis(config.request_timeout_ms)),
+            |timeout, (prefix, ms)| timeout.route(prefix.clone(), Duration::from_millis(*ms)),
+        )
+    }
+
+    /// Overrides the timeout for paths starting with `prefix`.
+    pub fn route(mut self, prefix: impl Into<String>, timeout: Duration) -> Self {
+        let mut routes = (*self.routes).clone();
+        routes.push((prefix.into(), timeout));
+        // Longest prefix first so the most specific override wins.
+        routes.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+        self.routes = Arc::new(routes);
+        self
+    }
+
+    /// Counts timed-out requests as `http_requests_timed_out_total{method, route}`,
+    /// where `route` is the matching override prefix or `default`.
+    pub fn with_metrics(mut self, registry: &Registry) -> prometheus::Result<Self> {
+        let counter = IntCounterVec::new(
+            Opts::new(
+                "http_requests_timed_out_total",
+                "Requests aborted by the request timeout middleware",
+            ),
+            &["method", "route"],
+        )?;
+        registry.register(Box::new(counter.clone()))?;
+        self.timed_out = Some(counter);
+        Ok(self)
+    }
+
+    pub fn timeout_for(&self, path: &str) -> Duration {
+        self.matching_route(path)
+            .map_or(self.default, |(_, timeout)| *timeout)
+    }
+
+    fn matching_route(&self, path: &str) -> Option<&(String, Duration)> {
+        self.routes
+            .iter()
+            .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTimeout
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequestTimeoutMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestTimeoutMiddleware {
+            service,
+            config: self.clone(),
+        }))
+    }
+}
+
+pub struct RequestTimeoutMiddleware<S> {
+    service: S,
+    config: RequestTimeout,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTimeoutMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let (route, timeout) = self.config.matching_route(req.path()).map_or(
+            ("default".to_string(), self.config.default),
+            |(prefix, timeout)| (prefix.clone(), *timeout),
+        );
+        let method = req.method().clone();
+        let path = req.path().to_string();
+        let timed_out = self.config.timed_out.clone();
+
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            match tokio::time::timeout(timeout, fut).await {
+                Ok(res) => res,
+                Err(_) => {
+                    tracing::warn!(
+                        method = %method,
+                        path = %path,
+                        route = %route,
+                        timeout_ms = timeout.as_millis() as u64,
+                        "Request timed out"
+                    );
+                    if let Some(counter) = timed_out {
+                        counter.with_label_values(&[method.as_str(), &route]).inc();
+                    }
+
+                    let error = AppError::Timeout(format!(
+                        "Request did not complete within {} ms",
+                        timeout.as_millis()
+                    ));
+                    let response = error.error_response();
+                    Err(InternalError::from_response(error, response).into())
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_longest_prefix_override_wins() {
+        let timeout = RequestTimeout::new(Duration::from_secs(30))
+            .route("/api", Duration::from_secs(10))
+            .route("/api/v1/memos/export", Duration::from_secs(120));
+
+        assert_eq!(
+            timeout.timeout_for("/api/v1/memos/export"),
+            Duration::from_secs(120)
+        );
+        assert_eq!(
+            timeout.timeout_for("/api/v1/memos"),
+            Duration::from_secs(10)
+        );
+        assert_eq!(timeout.timeout_for("/web"), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_metrics_register_once_per_registry() {
+        let registry = Registry::new();
+        let timeout = RequestTimeout::new(Duration::from_secs(1));
+
+        assert!(timeout.clone().with_metrics(&registry).is_ok());
+        assert!(timeout.with_metrics(&registry).is_err());
+    }
+}
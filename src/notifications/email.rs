@@ -0,0 +1,262 @@
+//! SMTP email delivery through a bounded background queue.
+//!
+//! Callers render an [`EmailTemplate`] into a [`Message`] with
+//! [`build_message`] and hand it to [`EmailQueue::enqueue`], which never waits
+//! on the network. A single sender task delivers messages in order and retries
+//! transient failures.
+
+use askama::Template;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::config::settings::{EmailConfig, SmtpTls};
+use crate::i18n::Locale;
+
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Error)]
+pub enum EmailError {
+    #[error("Invalid address: {0}")]
+    Address(#[from] lettre::address::AddressError),
+
+    #[error("Failed to build message: {0}")]
+    Build(#[from] lettre::error::Error),
+
+    #[error("Failed to render template: {0}")]
+    Template(#[from] askama::Error),
+
+    #[error("SMTP error: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
+
+    #[error("Email queue is full")]
+    QueueFull,
+
+    #[error("Email queue is closed")]
+    QueueClosed,
+}
+
+/// An email with a subject and matching plain-text and HTML bodies.
+pub trait EmailTemplate {
+    fn subject(&self) -> String;
+    fn text(&self) -> Result<String, askama::Error>;
+    fn html(&self) -> Result<String, askama::Error>;
+}
+
+/// Renders `template` into a multipart message.
+pub fn build_message(
+    from: &Mailbox,
+    to: &Mailbox,
+    template: &impl EmailTemplate,
+) -> Result<Message, EmailError> {
+    Ok(Message::builder()
+        .from(from.clone())
+        .to(to.clone())
+        .subject(template.subject())
+        .multipart(MultiPart::alternative_plain_html(
+            template.text()?,
+            template.html()?,
+        ))?)
+}
+
+/// Reminder that a memo is coming due.
+pub struct MemoReminder {
+    pub title: String,
+    pub description: Option<String>,
+    /// Due date, already formatted for display
+    pub due: String,
+    /// Link back to the app
+    pub url: String,
+    pub locale: Locale,
+}
+
+#[derive(Template)]
+#[template(path = "emails/memo_reminder.txt")]
+struct MemoReminderText<'a> {
+    reminder: &'a MemoReminder,
+}
+
+#[derive(Template)]
+#[template(path = "emails/memo_reminder.html")]
+struct MemoReminderHtml<'a> {
+    reminder: &'a MemoReminder,
+}
+
+impl EmailTemplate for MemoReminder {
+    fn subject(&self) -> String {
+        format!(
+            "{}: {}",
+            self.locale.t("email-reminder-subject"),
+            self.title
+        )
+    }
+
+    fn text(&self) -> Result<String, askama::Error> {
+        MemoReminderText { reminder: self }.render()
+    }
+
+    fn html(&self) -> Result<String, askama::Error> {
+        MemoReminderHtml { reminder: self }.render()
+    }
+}
+
+type SendFuture<'a> = Pin<Box<dyn Future<Output = Result<(), String>> + Send + 'a>>;
+
+/// Object-safe view of any lettre async transport.
+pub trait EmailTransport: Send + Sync {
+    fn deliver(&self, message: Message) -> SendFuture<'_>;
+}
+
+impl<T> EmailTransport for T
+where
+    T: AsyncTransport + Send + Sync,
+    T::Error: std::fmt::Display,
+{
+    fn deliver(&self, message: Message) -> SendFuture<'_> {
+        Box::pin(async move {
+            self.send(message)
+                .await
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+    }
+}
+
+/// Builds the SMTP transport described by `config`; `None` when email is disabled.
+pub fn smtp_transport(
+    config: &EmailConfig,
+) -> Result<Option<AsyncSmtpTransport<Tokio1Executor>>, EmailError> {
+    let Some(host) = config.smtp_host.as_deref() else {
+        return Ok(None);
+    };
+
+    let mut builder = match config.smtp_tls {
+        SmtpTls::StartTls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)?,
+        SmtpTls::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(host)?,
+        SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host),
+    }
+    .port(config.smtp_port);
+
+    if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+        builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+
+    Ok(Some(builder.build()))
+}
+
+/// Handle for queueing messages; cheap to clone.
+#[derive(Clone)]
+pub struct EmailQueue {
+    tx: mpsc::Sender<Message>,
+}
+
+impl EmailQueue {
+    /// Spawns the sender task. It exits once every queue handle is dropped and
+    /// the remaining messages are delivered.
+    pub fn start(
+        transport: impl EmailTransport + 'static,
+        capacity: usize,
+    ) -> (Self, JoinHandle<()>) {
+        let (tx, rx) = mpsc::channel(capacity);
+        let worker = tokio::spawn(deliver_loop(Arc::new(transport), rx));
+        (Self { tx }, worker)
+    }
+
+    /// Queues `message` without waiting; fails when the queue is full.
+    pub fn enqueue(&self, message: Message) -> Result<(), EmailError> {
+        self.tx.try_send(message).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => EmailError::QueueFull,
+            mpsc::error::TrySendError::Closed(_) => EmailError::QueueClosed,
+        })
+    }
+}
+
+async fn deliver_loop(transport: Arc<dyn EmailTransport>, mut rx: mpsc::Receiver<Message>) {
+    while let Some(message) = rx.recv().await {
+        let recipients = message
+            .envelope()
+            .to()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            match transport.deliver(message.clone()).await {
+                Ok(()) => {
+                    tracing::info!(to = %recipients, "Email sent");
+                    break;
+                }
+                Err(e) if attempt < MAX_ATTEMPTS => {
+                    tracing::warn!(
+                        to = %recipients,
+                        attempt,
+                        error = %e,
+                        "Email delivery failed, retrying"
+                    );
+                    tokio::time::sleep(RETRY_DELAY * attempt).await;
+                }
+                Err(e) => {
+                    tracing::error!(
+                        to = %recipients,
+                        error = %e,
+                        "Email delivery failed, giving up"
+                    );
+                }
+            }
+        }
+    }
+
+    tracing::debug!("Email queue closed");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lettre::transport::stub::AsyncStubTransport;
+
+    fn reminder() -> MemoReminder {
+        MemoReminder {
+            title: "Pay <rent>".to_string(),
+            description: Some("Before noon".to_string()),
+            due: "2025-01-31 12:00 UTC".to_string(),
+            url: "http://localhost:3737/".to_string(),
+            locale: Locale::En,
+        }
+    }
+
+    #[test]
+    fn test_reminder_renders_both_bodies() {
+        let reminder = reminder();
+
+        assert_eq!(reminder.subject(), "Reminder: Pay <rent>");
+        assert!(reminder.text().unwrap().contains("Pay <rent>"));
+        assert!(reminder.html().unwrap().contains("Pay &#60;rent&#62;"));
+    }
+
+    #[tokio::test]
+    async fn test_queued_messages_are_delivered() {
+        let stub = AsyncStubTransport::new_ok();
+        let (queue, worker) = EmailQueue::start(stub.clone(), 4);
+        let from = "Memos <noreply@example.com>".parse().unwrap();
+        let to = "ops@example.com".parse().unwrap();
+
+        queue
+            .enqueue(build_message(&from, &to, &reminder()).unwrap())
+            .unwrap();
+        drop(queue);
+        worker.await.unwrap();
+
+        let sent = stub.messages().await;
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].1.contains("Subject: Reminder: Pay <rent>"));
+    }
+}
@@ -0,0 +1,4 @@
+//! Outbound notifications.
+
+pub mod email;
+pub mod push;
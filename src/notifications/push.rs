@@ -0,0 +1,118 @@
+//! Web Push delivery signed with the server's VAPID key.
+//!
+//! Browsers subscribe with the public half of the key (see
+//! [`WebPushSender::public_key`]); the service worker in `static/sw.js`
+//! displays the JSON [`PushPayload`] it receives.
+
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde::Serialize;
+use web_push::{
+    ContentEncoding, HyperWebPushClient, PartialVapidSignatureBuilder, SubscriptionInfo,
+    VapidSignatureBuilder, WebPushClient, WebPushError, WebPushMessageBuilder,
+};
+
+use crate::config::settings::PushConfig;
+
+/// How long push services keep an undelivered notification, in seconds.
+const TTL_SECS: u32 = 60 * 60;
+
+/// Notification shown by the service worker.
+#[derive(Debug, Serialize)]
+pub struct PushPayload {
+    pub title: String,
+    pub body: String,
+    /// Opened when the notification is clicked
+    pub url: String,
+    /// Notifications with the same tag replace each other
+    pub tag: String,
+}
+
+/// Sends notifications to browser push services. Cheap to clone.
+#[derive(Clone)]
+pub struct WebPushSender {
+    vapid: PartialVapidSignatureBuilder,
+    subject: String,
+    client: HyperWebPushClient,
+}
+
+impl WebPushSender {
+    /// `None` when `VAPID_PRIVATE_KEY` is not set.
+    pub fn from_config(config: &PushConfig) -> Result<Option<Self>, WebPushError> {
+        let Some(key) = config.vapid_private_key.as_deref() else {
+            return Ok(None);
+        };
+
+        Ok(Some(Self {
+            vapid: VapidSignatureBuilder::from_base64_no_sub(key)?,
+            subject: config.vapid_subject.clone(),
+            client: HyperWebPushClient::new(),
+        }))
+    }
+
+    /// Uncompressed public key, URL-safe base64 without padding, as expected by
+    /// `pushManager.subscribe({ applicationServerKey })`.
+    pub fn public_key(&self) -> String {
+        URL_SAFE_NO_PAD.encode(self.vapid.get_public_key())
+    }
+
+    pub async fn send(
+        &self,
+        subscription: &SubscriptionInfo,
+        payload: &PushPayload,
+    ) -> Result<(), WebPushError> {
+        let body = serde_json::to_vec(payload).map_err(|_| WebPushError::InvalidClaims)?;
+
+        let mut signature = self.vapid.clone().add_sub_info(subscription);
+        signature.add_claim("sub", self.subject.as_str());
+
+        let mut message = WebPushMessageBuilder::new(subscription);
+        message.set_payload(ContentEncoding::Aes128Gcm, &body);
+        message.set_vapid_signature(signature.build()?);
+        message.set_ttl(TTL_SECS);
+
+        self.client.send(message.build()?).await
+    }
+}
+
+/// Whether the push service reported the subscription as permanently gone,
+/// meaning it should be deleted.
+pub fn is_gone(error: &WebPushError) -> bool {
+    matches!(
+        error,
+        WebPushError::EndpointNotFound(_) | WebPushError::EndpointNotValid(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Test-only key; generate your own with the command in README.md.
+    const KEY: &str = "dH1GTNiMPCxAn6bfPyvOMm-Fc3Sz3gjhMLXmkk2Tx1g";
+
+    #[test]
+    fn test_disabled_without_private_key() {
+        let config = PushConfig {
+            vapid_private_key: None,
+            vapid_subject: "mailto:admin@localhost".to_string(),
+            due_cron: "0 * * * * *".to_string(),
+        };
+
+        assert!(WebPushSender::from_config(&config).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_public_key_is_uncompressed_point() {
+        let config = PushConfig {
+            vapid_private_key: Some(KEY.to_string()),
+            vapid_subject: "mailto:admin@localhost".to_string(),
+            due_cron: "0 * * * * *".to_string(),
+        };
+        let sender = WebPushSender::from_config(&config).unwrap().unwrap();
+
+        let decoded = URL_SAFE_NO_PAD.decode(sender.public_key()).unwrap();
+        assert_eq!(decoded.len(), 65);
+        assert_eq!(decoded[0], 0x04);
+    }
+}
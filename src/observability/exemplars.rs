@@ -0,0 +1,415 @@
+//! Trace exemplars on the request latency histogram.
+//!
+//! The trace ID of the latest request falling into a latency bucket is kept
+//! per series, taken from the root span [`super::trace_context::TraceContext`]
+//! opens. `/metrics` scrapes that
+//! accept OpenMetrics (Prometheus asks for it when exemplar storage is on) get
+//! those IDs as exemplars on the `<namespace>_http_requests_duration_seconds`
+//! buckets, which lets Grafana link a latency spike to the trace behind it.
+//! Other scrapes keep getting the plain text format.
+
+use actix_web::body::EitherBody;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::{Method, header};
+use actix_web::{Error, HttpResponse};
+use actix_web_prom::PrometheusMetrics;
+use opentelemetry::trace::TraceContextExt;
+use prometheus::Registry;
+use prometheus::proto::{Metric, MetricFamily, MetricType};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::future::{Ready, ready};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+use crate::config::settings::ObservabilityConfig;
+
+const OPENMETRICS_CONTENT_TYPE: &str = "application/openmetrics-text; version=1.0.0; charset=utf-8";
+
+/// `(endpoint, method, status)`, the labels of the request metrics.
+type SeriesKey = (String, String, String);
+
+#[derive(Debug, Clone)]
+struct Exemplar {
+    trace_id: String,
+    value: f64,
+    /// Seconds since the Unix epoch
+    timestamp: f64,
+}
+
+/// Middleware recording trace exemplars and serving them on `/metrics`.
+/// Wrap it outside the Prometheus middleware and inside `TraceContext`, and
+/// only with tracing exported: without it, requests have no trace IDs.
+#[derive(Clone)]
+pub struct TraceExemplars {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    registry: Registry,
+    /// Name of the histogram the exemplars belong to
+    histogram: String,
+    buckets: Vec<f64>,
+    /// Latest exemplar per series and bucket; the last slot is `+Inf`
+    latest: Mutex<HashMap<SeriesKey, Vec<Option<Exemplar>>>>,
+}
+
+impl TraceExemplars {
+    pub fn new(metrics: &PrometheusMetrics, config: &ObservabilityConfig) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                registry: metrics.registry.clone(),
+                histogram: format!(
+                    "{}_http_requests_duration_seconds",
+                    config.metrics_namespace
+                ),
+                buckets: config.metrics_latency_buckets.clone(),
+                latest: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    fn record(&self, series: SeriesKey, value: f64, trace_id: String) {
+        let bucket = self
+            .inner
+            .buckets
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.inner.buckets.len());
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64());
+
+        let mut latest = self.inner.latest.lock().unwrap_or_else(|e| e.into_inner());
+        let slots = latest
+            .entry(series)
+            .or_insert_with(|| vec![None; self.inner.buckets.len() + 1]);
+        slots[bucket] = Some(Exemplar {
+            trace_id,
+            value,
+            timestamp,
+        });
+    }
+
+    /// The registry in the OpenMetrics text format, with exemplars.
+    pub fn render(&self) -> String {
+        let latest = self.inner.latest.lock().unwrap_or_else(|e| e.into_inner());
+        let mut out = String::new();
+        for family in self.inner.registry.gather() {
+            let exemplars = (family.name() == self.inner.histogram).then_some(&*latest);
+            write_family(&mut out, &family, exemplars);
+        }
+        out.push_str("# EOF\n");
+        out
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for TraceExemplars
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = TraceExemplarsMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TraceExemplarsMiddleware {
+            service,
+            exemplars: self.clone(),
+        }))
+    }
+}
+
+pub struct TraceExemplarsMiddleware<S> {
+    service: S,
+    exemplars: TraceExemplars,
+}
+
+impl<S, B> Service<ServiceRequest> for TraceExemplarsMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if req.method() == Method::GET && req.path() == "/metrics" && accepts_openmetrics(&req) {
+            let response = HttpResponse::Ok()
+                .content_type(OPENMETRICS_CONTENT_TYPE)
+                .body(self.exemplars.render());
+            return Box::pin(ready(Ok(req.into_response(response).map_into_right_body())));
+        }
+
+        let span = tracing::Span::current();
+        let started = Instant::now();
+        let exemplars = self.exemplars.clone();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            let span_context = span.context().span().span_context().clone();
+            if span_context.is_valid() && span_context.is_sampled() {
+                let req = res.request();
+                let series = (
+                    req.match_pattern()
+                        .unwrap_or_else(|| req.path().to_string()),
+                    req.method().to_string(),
+                    res.status().as_str().to_string(),
+                );
+                exemplars.record(
+                    series,
+                    started.elapsed().as_secs_f64(),
+                    span_context.trace_id().to_string(),
+                );
+            }
+            Ok(res.map_into_left_body())
+        })
+    }
+}
+
+fn accepts_openmetrics(req: &ServiceRequest) -> bool {
+    req.headers()
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("application/openmetrics-text"))
+}
+
+fn write_family(
+    out: &mut String,
+    family: &MetricFamily,
+    exemplars: Option<&HashMap<SeriesKey, Vec<Option<Exemplar>>>>,
+) {
+    let name = family.name();
+    // OpenMetrics names counters without the `_total` their samples carry.
+    let (family_name, kind) = match family.get_field_type() {
+        MetricType::COUNTER => (name.strip_suffix("_total").unwrap_or(name), "counter"),
+        MetricType::GAUGE => (name, "gauge"),
+        MetricType::HISTOGRAM => (name, "histogram"),
+        MetricType::SUMMARY => (name, "summary"),
+        // Not produced by the client library, same as in its text encoder.
+        MetricType::UNTYPED => return,
+    };
+
+    let _ = writeln!(out, "# TYPE {family_name} {kind}");
+    if !family.help().is_empty() {
+        let _ = writeln!(out, "# HELP {family_name} {}", escape(family.help()));
+    }
+
+    for metric in family.get_metric() {
+        match family.get_field_type() {
+            MetricType::COUNTER => {
+                let sample = format!("{family_name}_total");
+                write_sample(out, &sample, metric, None, metric.get_counter().get_value());
+            }
+            MetricType::GAUGE => {
+                write_sample(out, name, metric, None, metric.get_gauge().get_value());
+            }
+            MetricType::UNTYPED => {}
+            MetricType::SUMMARY => {
+                let summary = metric.get_summary();
+                for quantile in summary.get_quantile() {
+                    let label = ("quantile", format_float(quantile.quantile()));
+                    write_sample(out, name, metric, Some(label), quantile.value());
+                }
+                write_sample(
+                    out,
+                    &format!("{name}_sum"),
+                    metric,
+                    None,
+                    summary.sample_sum(),
+                );
+                let count = summary.sample_count() as f64;
+                write_sample(out, &format!("{name}_count"), metric, None, count);
+            }
+            MetricType::HISTOGRAM => {
+                let histogram = metric.get_histogram();
+                let slots = exemplars.and_then(|exemplars| exemplars.get(&series_key(metric)));
+                let bucket_name = format!("{name}_bucket");
+
+                let mut bounds: Vec<(f64, f64)> = histogram
+                    .get_bucket()
+                    .iter()
+                    .map(|bucket| (bucket.upper_bound(), bucket.cumulative_count() as f64))
+                    .filter(|(bound, _)| bound.is_finite())
+                    .collect();
+                bounds.push((f64::INFINITY, histogram.get_sample_count() as f64));
+
+                for (index, (bound, count)) in bounds.into_iter().enumerate() {
+                    write_sample(
+                        out,
+                        &bucket_name,
+                        metric,
+                        Some(("le", format_float(bound))),
+                        count,
+                    );
+                    if let Some(Some(exemplar)) = slots.and_then(|slots| slots.get(index)) {
+                        // Replace the newline with the exemplar and put it back.
+                        out.pop();
+                        let _ = writeln!(
+                            out,
+                            " # {{trace_id=\"{}\"}} {} {:.3}",
+                            exemplar.trace_id,
+                            format_float(exemplar.value),
+                            exemplar.timestamp
+                        );
+                    }
+                }
+                write_sample(
+                    out,
+                    &format!("{name}_sum"),
+                    metric,
+                    None,
+                    histogram.get_sample_sum(),
+                );
+                let count = histogram.get_sample_count() as f64;
+                write_sample(out, &format!("{name}_count"), metric, None, count);
+            }
+        }
+    }
+}
+
+fn write_sample(
+    out: &mut String,
+    name: &str,
+    metric: &Metric,
+    extra_label: Option<(&str, String)>,
+    value: f64,
+) {
+    let mut labels: Vec<String> = metric
+        .get_label()
+        .iter()
+        .map(|pair| format!("{}=\"{}\"", pair.name(), escape(pair.value())))
+        .collect();
+    if let Some((label, label_value)) = extra_label {
+        labels.push(format!("{label}=\"{label_value}\""));
+    }
+
+    out.push_str(name);
+    if !labels.is_empty() {
+        let _ = write!(out, "{{{}}}", labels.join(","));
+    }
+    let _ = writeln!(out, " {}", format_float(value));
+}
+
+fn series_key(metric: &Metric) -> SeriesKey {
+    let label = |name: &str| {
+        metric
+            .get_label()
+            .iter()
+            .find(|pair| pair.name() == name)
+            .map(|pair| pair.value().to_string())
+            .unwrap_or_default()
+    };
+    (label("endpoint"), label("method"), label("status"))
+}
+
+fn format_float(value: f64) -> String {
+    if value == f64::INFINITY {
+        "+Inf".to_string()
+    } else if value == f64::NEG_INFINITY {
+        "-Inf".to_string()
+    } else if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{value:.1}")
+    } else {
+        value.to_string()
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', r"\\")
+        .replace('"', "\\\"")
+        .replace('\n', r"\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::observability::metrics::prometheus_metrics;
+    use actix_web::{App, test, web};
+
+    fn config() -> ObservabilityConfig {
+        ObservabilityConfig {
+            auth_token: None,
+            basic_auth: None,
+            metrics_namespace: "memos".to_string(),
+            metrics_const_labels: Vec::new(),
+            metrics_latency_buckets: vec![0.1, 1.0],
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_exemplars_are_attached_to_their_bucket() {
+        let metrics = prometheus_metrics(&config()).unwrap();
+        let exemplars = TraceExemplars::new(&metrics, &config());
+
+        let app = test::init_service(
+            App::new()
+                .wrap(metrics)
+                .route("/ping", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+        test::call_service(&app, test::TestRequest::get().uri("/ping").to_request()).await;
+        exemplars.record(
+            ("/ping".to_string(), "GET".to_string(), "200".to_string()),
+            0.5,
+            "4bf92f3577b34da6a3ce929d0e0e4736".to_string(),
+        );
+
+        let text = exemplars.render();
+        assert!(text.ends_with("# EOF\n"));
+        assert!(text.contains("# TYPE memos_http_requests counter"));
+        assert!(text.contains(
+            r#"memos_http_requests_duration_seconds_bucket{endpoint="/ping",method="GET",status="200",le="1.0"} 1.0 # {trace_id="4bf92f3577b34da6a3ce929d0e0e4736"} 0.5 "#
+        ));
+        // Only the bucket the exemplar falls into carries it.
+        assert_eq!(text.matches("trace_id=").count(), 1);
+    }
+
+    #[actix_web::test]
+    async fn test_plain_scrapes_skip_openmetrics() {
+        let metrics = prometheus_metrics(&config()).unwrap();
+        let exemplars = TraceExemplars::new(&metrics, &config());
+        let app = test::init_service(
+            App::new()
+                .wrap(metrics)
+                .wrap(exemplars)
+                .route("/ping", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = test::TestRequest::get().uri("/metrics").to_request();
+        let resp = test::call_service(&app, req).await;
+        assert!(
+            resp.headers()
+                .get(header::CONTENT_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .starts_with("text/plain")
+        );
+
+        let req = test::TestRequest::get()
+            .uri("/metrics")
+            .insert_header((header::ACCEPT, "application/openmetrics-text;version=1.0.0"))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(
+            resp.headers().get(header::CONTENT_TYPE).unwrap(),
+            OPENMETRICS_CONTENT_TYPE
+        );
+        let body = test::read_body(resp).await;
+        assert!(body.ends_with(b"# EOF\n"));
+    }
+}
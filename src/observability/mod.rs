@@ -1 +1,4 @@
+pub mod exemplars;
+pub mod metrics;
+pub mod trace_context;
 pub mod tracing;
@@ -0,0 +1,141 @@
+//! W3C trace context propagation.
+//!
+//! [`TraceContext`] runs each request in a root span whose parent comes from
+//! the `traceparent` and `tracestate` headers, so a caller's trace continues
+//! here instead of starting over. [`outgoing_headers`] does the reverse for
+//! requests this service makes to services sharing its trace backend. Posts
+//! to third parties, like Slack and Discord webhooks, don't carry them.
+
+use actix_web::Error;
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready};
+use actix_web::http::header::HeaderMap;
+use opentelemetry::global;
+use opentelemetry::propagation::Extractor;
+use std::collections::HashMap;
+use std::future::{Ready, ready};
+use tracing::Instrument;
+use tracing::instrument::Instrumented;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Middleware opening the root span of each request, continuing the trace
+/// named by the request's trace context headers. Wrap it outermost of the
+/// tracing-aware middleware, and only with tracing exported.
+#[derive(Clone, Copy, Default)]
+pub struct TraceContext;
+
+impl<S, B> Transform<S, ServiceRequest> for TraceContext
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = TraceContextMiddleware<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(TraceContextMiddleware { service }))
+    }
+}
+
+pub struct TraceContextMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for TraceContextMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Instrumented<S::Future>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let parent = global::get_text_map_propagator(|propagator| {
+            propagator.extract(&HeaderExtractor(req.headers()))
+        });
+        let span = tracing::info_span!(
+            "HTTP request",
+            http.method = %req.method(),
+            http.target = %req.path(),
+            otel.kind = "server",
+        );
+        // Only fails when no OpenTelemetry layer is installed, in which case
+        // there is no trace to continue.
+        let _ = span.set_parent(parent);
+
+        span.in_scope(|| self.service.call(req)).instrument(span)
+    }
+}
+
+/// Trace context headers for an outgoing request made within the current
+/// span. Empty when the span is not part of an exported trace.
+pub fn outgoing_headers() -> HashMap<String, String> {
+    let context = tracing::Span::current().context();
+    let mut headers = HashMap::new();
+    global::get_text_map_propagator(|propagator| propagator.inject_context(&context, &mut headers));
+    headers
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{App, HttpResponse, test, web};
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_sdk::propagation::TraceContextPropagator;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+    const CALLER_SPAN_ID: &str = "00f067aa0ba902b7";
+
+    #[actix_web::test]
+    async fn test_request_continues_incoming_trace_and_propagates_it() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        let tracer = SdkTracerProvider::builder().build().tracer("test");
+        let _guard = tracing::subscriber::set_default(
+            tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer)),
+        );
+
+        let app = test::init_service(App::new().wrap(TraceContext).route(
+            "/",
+            web::get().to(|| async { HttpResponse::Ok().json(outgoing_headers()) }),
+        ))
+        .await;
+        let req = test::TestRequest::get()
+            .uri("/")
+            .insert_header(("traceparent", format!("00-{TRACE_ID}-{CALLER_SPAN_ID}-01")))
+            .insert_header(("tracestate", "vendor=value"))
+            .to_request();
+        let headers: HashMap<String, String> = test::call_and_read_body_json(&app, req).await;
+
+        let traceparent = &headers["traceparent"];
+        assert!(traceparent.starts_with(&format!("00-{TRACE_ID}-")));
+        assert!(!traceparent.contains(CALLER_SPAN_ID));
+        assert_eq!(headers["tracestate"], "vendor=value");
+    }
+
+    #[actix_web::test]
+    async fn test_no_headers_outside_a_trace() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+        assert!(outgoing_headers().is_empty());
+    }
+}
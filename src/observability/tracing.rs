@@ -1,7 +1,8 @@
+use opentelemetry::global;
 use opentelemetry::trace::TracerProvider;
 use opentelemetry_otlp::WithExportConfig;
-use opentelemetry_sdk::{Resource, trace as sdktrace};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use opentelemetry_sdk::{Resource, propagation::TraceContextPropagator, trace as sdktrace};
+use tracing_subscriber::{Layer, layer::SubscriberExt, util::SubscriberInitExt};
 
 pub fn init_tracing_with_otlp(
     service_name: &str,
@@ -29,6 +30,9 @@ pub fn init_tracing_with_otlp(
 
         let tracer = provider.tracer(service_name.to_string());
 
+        // W3C `traceparent`/`tracestate`, read from requests and sent on internal calls
+        global::set_text_map_propagator(TraceContextPropagator::new());
+
         Some(tracer)
     } else {
         tracing::info!("OTLP endpoint not configured, skipping OpenTelemetry setup");
@@ -37,15 +41,20 @@ pub fn init_tracing_with_otlp(
 
     let telemetry_layer = tracer.map(|t| tracing_opentelemetry::layer().with_tracer(t));
 
-    let subscriber = tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::from_default_env())
-        .with(tracing_subscriber::fmt::layer());
+    // `RUST_LOG` filters logs and exported spans only: the console layer needs
+    // tokio's own trace-level events whatever the log level.
+    let app_layers = tracing_subscriber::fmt::layer()
+        .and_then(telemetry_layer)
+        .with_filter(tracing_subscriber::EnvFilter::from_default_env());
+    let subscriber = tracing_subscriber::registry().with(app_layers);
 
-    if let Some(layer) = telemetry_layer {
-        subscriber.with(layer).init();
-    } else {
-        subscriber.init();
-    }
+    #[cfg(feature = "tokio-console")]
+    let subscriber = subscriber.with(console_subscriber::spawn());
+
+    subscriber.init();
+
+    #[cfg(feature = "tokio-console")]
+    tracing::info!("tokio-console can connect to 127.0.0.1:6669");
 
     Ok(())
 }
@@ -0,0 +1,84 @@
+use crate::entities::{api_key_usage, prelude::*};
+use chrono::{NaiveDate, Utc};
+use sea_orm::{
+    sea_query::{Alias, Expr, OnConflict},
+    *,
+};
+
+pub struct ApiKeyUsageRepository;
+
+impl ApiKeyUsageRepository {
+    /// Adds request counts to the `(api_key_id, day)` rows, creating the
+    /// missing ones.
+    #[tracing::instrument(skip(db, counts), fields(rows = counts.len()))]
+    pub async fn add<C: ConnectionTrait>(
+        db: &C,
+        counts: Vec<(String, NaiveDate, i64)>,
+    ) -> Result<(), DbErr> {
+        if counts.is_empty() {
+            return Ok(());
+        }
+
+        let now = Utc::now();
+        let rows =
+            counts
+                .into_iter()
+                .map(|(api_key_id, day, requests)| api_key_usage::ActiveModel {
+                    api_key_id: Set(api_key_id),
+                    day: Set(day),
+                    request_count: Set(requests),
+                    updated_at: Set(now.into()),
+                });
+
+        ApiKeyUsage::insert_many(rows)
+            .on_conflict(
+                OnConflict::columns([api_key_usage::Column::ApiKeyId, api_key_usage::Column::Day])
+                    .value(
+                        api_key_usage::Column::RequestCount,
+                        Expr::cust("api_key_usage.request_count + excluded.request_count"),
+                    )
+                    .update_column(api_key_usage::Column::UpdatedAt)
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Requests per key counted from `since` on.
+    #[tracing::instrument(skip(db))]
+    pub async fn totals_since<C: ConnectionTrait>(
+        db: &C,
+        since: NaiveDate,
+    ) -> Result<Vec<(String, i64)>, DbErr> {
+        ApiKeyUsage::find()
+            .select_only()
+            .column(api_key_usage::Column::ApiKeyId)
+            .column_as(
+                Expr::col(api_key_usage::Column::RequestCount)
+                    .sum()
+                    .cast_as(Alias::new("bigint")),
+                "total",
+            )
+            .filter(api_key_usage::Column::Day.gte(since))
+            .group_by(api_key_usage::Column::ApiKeyId)
+            .into_tuple()
+            .all(db)
+            .await
+    }
+
+    /// The daily rows of `api_key_id` from `since` on, oldest first.
+    #[tracing::instrument(skip(db))]
+    pub async fn find_days<C: ConnectionTrait>(
+        db: &C,
+        api_key_id: &str,
+        since: NaiveDate,
+    ) -> Result<Vec<api_key_usage::Model>, DbErr> {
+        ApiKeyUsage::find()
+            .filter(api_key_usage::Column::ApiKeyId.eq(api_key_id))
+            .filter(api_key_usage::Column::Day.gte(since))
+            .order_by_asc(api_key_usage::Column::Day)
+            .all(db)
+            .await
+    }
+}
@@ -0,0 +1,34 @@
+//! OpenTelemetry database attributes for repository spans.
+//!
+//! Repository methods declare `db.system`, `db.operation` and `db.sql.table`
+//! in their `#[tracing::instrument]` fields, along with empty `db.statement`
+//! and `db.response.returned_rows` (or `db.rows_affected`) fields filled in
+//! once the query is built and has run. Statements are recorded as built,
+//! with `$n` placeholders, so bound values like titles and descriptions never
+//! reach a span.
+
+use sea_orm::{DbBackend, QueryTrait};
+
+/// Value of `db.system` for every repository span.
+pub const SYSTEM: &str = "postgresql";
+
+/// Records the SQL of `query` as `db.statement` on the current span.
+pub fn record_statement(query: &impl QueryTrait) {
+    let span = tracing::Span::current();
+    if !span.is_disabled() {
+        span.record(
+            "db.statement",
+            query.build(DbBackend::Postgres).sql.as_str(),
+        );
+    }
+}
+
+/// Records the number of rows a read or `RETURNING` write brought back.
+pub fn record_returned_rows(rows: usize) {
+    tracing::Span::current().record("db.response.returned_rows", rows as u64);
+}
+
+/// Records the number of rows a write changed.
+pub fn record_rows_affected(rows: u64) {
+    tracing::Span::current().record("db.rows_affected", rows);
+}
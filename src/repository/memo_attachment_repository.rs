@@ -0,0 +1,109 @@
+use crate::attachments::NewAttachment;
+use crate::entities::{memo_attachments, prelude::*};
+use chrono::Utc;
+use sea_orm::*;
+use serde::Deserialize;
+use uuid::Uuid;
+
+/// An attachment without its file and thumbnail bytes, for listing. Also the
+/// shape of the entries of `memo_list_view.attachments`.
+#[derive(Debug, Clone, PartialEq, FromQueryResult, Deserialize)]
+pub struct AttachmentMeta {
+    pub id: Uuid,
+    pub memo_id: Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size_bytes: i64,
+    pub width: i32,
+    pub height: i32,
+}
+
+pub struct MemoAttachmentRepository;
+
+impl MemoAttachmentRepository {
+    #[tracing::instrument(skip(db, attachment), fields(file_name = %attachment.file_name, bytes = attachment.data.len()))]
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
+        memo_id: Uuid,
+        attachment: NewAttachment,
+    ) -> Result<Uuid, DbErr> {
+        let id = Uuid::new_v4();
+        let model = memo_attachments::ActiveModel {
+            id: Set(id),
+            memo_id: Set(memo_id),
+            file_name: Set(attachment.file_name),
+            content_type: Set(attachment.content_type.to_string()),
+            size_bytes: Set(attachment.data.len() as i64),
+            width: Set(attachment.width as i32),
+            height: Set(attachment.height as i32),
+            data: Set(attachment.data),
+            thumbnail: Set(attachment.thumbnail),
+            created_at: Set(Utc::now().into()),
+        };
+
+        MemoAttachments::insert(model).exec(db).await?;
+        Ok(id)
+    }
+
+    /// Attachments of `memo_ids`, oldest first, without their content.
+    #[tracing::instrument(skip(db, memo_ids), fields(memos = memo_ids.len()))]
+    pub async fn find_by_memos<C: ConnectionTrait>(
+        db: &C,
+        memo_ids: &[Uuid],
+    ) -> Result<Vec<AttachmentMeta>, DbErr> {
+        if memo_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        MemoAttachments::find()
+            .select_only()
+            .columns([
+                memo_attachments::Column::Id,
+                memo_attachments::Column::MemoId,
+                memo_attachments::Column::FileName,
+                memo_attachments::Column::ContentType,
+                memo_attachments::Column::SizeBytes,
+                memo_attachments::Column::Width,
+                memo_attachments::Column::Height,
+            ])
+            .filter(memo_attachments::Column::MemoId.is_in(memo_ids.iter().copied()))
+            .order_by_asc(memo_attachments::Column::CreatedAt)
+            .order_by_asc(memo_attachments::Column::Id)
+            .into_model::<AttachmentMeta>()
+            .all(db)
+            .await
+    }
+
+    #[tracing::instrument(skip(db))]
+    pub async fn exists<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<bool, DbErr> {
+        let found: Option<Uuid> = MemoAttachments::find_by_id(id)
+            .select_only()
+            .column(memo_attachments::Column::Id)
+            .into_tuple()
+            .one(db)
+            .await?;
+        Ok(found.is_some())
+    }
+
+    /// Content type and bytes of an attachment, or of its thumbnail.
+    #[tracing::instrument(skip(db))]
+    pub async fn find_content<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        thumbnail: bool,
+    ) -> Result<Option<(String, Vec<u8>)>, DbErr> {
+        let content = if thumbnail {
+            memo_attachments::Column::Thumbnail
+        } else {
+            memo_attachments::Column::Data
+        };
+
+        MemoAttachments::find_by_id(id)
+            .select_only()
+            .column(memo_attachments::Column::ContentType)
+            .column(content)
+            .into_tuple()
+            .one(db)
+            .await
+    }
+}
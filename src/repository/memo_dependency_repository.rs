@@ -0,0 +1,144 @@
+use crate::entities::{memo_dependencies, memos, prelude::*};
+use chrono::Utc;
+use sea_orm::{sea_query::OnConflict, *};
+use uuid::Uuid;
+
+pub struct MemoDependencyRepository;
+
+impl MemoDependencyRepository {
+    /// Records that `memo_id` is blocked by `blocker_id`. Returns `false` when
+    /// the link already existed.
+    #[tracing::instrument(skip(db))]
+    pub async fn add<C: ConnectionTrait>(
+        db: &C,
+        memo_id: Uuid,
+        blocker_id: Uuid,
+    ) -> Result<bool, DbErr> {
+        let dependency = memo_dependencies::ActiveModel {
+            memo_id: Set(memo_id),
+            blocker_id: Set(blocker_id),
+            created_at: Set(Utc::now().into()),
+        };
+
+        let inserted = MemoDependencies::insert(dependency)
+            .on_conflict(
+                OnConflict::columns([
+                    memo_dependencies::Column::MemoId,
+                    memo_dependencies::Column::BlockerId,
+                ])
+                .do_nothing()
+                .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+        Ok(inserted > 0)
+    }
+
+    /// Returns `false` when `memo_id` was not blocked by `blocker_id`.
+    #[tracing::instrument(skip(db))]
+    pub async fn remove<C: ConnectionTrait>(
+        db: &C,
+        memo_id: Uuid,
+        blocker_id: Uuid,
+    ) -> Result<bool, DbErr> {
+        let result = MemoDependencies::delete_by_id((memo_id, blocker_id))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Whether `memo_id` waits on `blocker_id`, directly or through other
+    /// blockers.
+    #[tracing::instrument(skip(db))]
+    pub async fn depends_on<C: ConnectionTrait>(
+        db: &C,
+        memo_id: Uuid,
+        blocker_id: Uuid,
+    ) -> Result<bool, DbErr> {
+        let row = db
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                r#"WITH RECURSIVE chain(id) AS (
+                       SELECT blocker_id FROM memo_dependencies WHERE memo_id = $1
+                       UNION
+                       SELECT d.blocker_id FROM memo_dependencies d JOIN chain c ON d.memo_id = c.id
+                   )
+                   SELECT EXISTS (SELECT 1 FROM chain WHERE id = $2) AS "reachable""#,
+                [memo_id.into(), blocker_id.into()],
+            ))
+            .await?
+            .ok_or_else(|| DbErr::Custom("dependency lookup returned no row".into()))?;
+        row.try_get("", "reachable")
+    }
+
+    /// Every link touching one of `ids`, on either side. Links to memos
+    /// inside their undo window are left out.
+    #[tracing::instrument(skip(db, ids), fields(count = ids.len()))]
+    pub async fn find_touching<C: ConnectionTrait>(
+        db: &C,
+        ids: &[Uuid],
+    ) -> Result<Vec<memo_dependencies::Model>, DbErr> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let deleted = Memos::find()
+            .select_only()
+            .column(memos::Column::Id)
+            .filter(memos::Column::UndoToken.is_not_null())
+            .into_query();
+
+        MemoDependencies::find()
+            .filter(
+                Condition::any()
+                    .add(memo_dependencies::Column::MemoId.is_in(ids.to_vec()))
+                    .add(memo_dependencies::Column::BlockerId.is_in(ids.to_vec())),
+            )
+            .filter(memo_dependencies::Column::MemoId.not_in_subquery(deleted.clone()))
+            .filter(memo_dependencies::Column::BlockerId.not_in_subquery(deleted))
+            .order_by_asc(memo_dependencies::Column::CreatedAt)
+            .order_by_asc(memo_dependencies::Column::BlockerId)
+            .all(db)
+            .await
+    }
+
+    /// Incomplete blockers of each incomplete memo among `ids`, as
+    /// `(memo_id, blocker)` pairs.
+    #[tracing::instrument(skip(db, ids), fields(count = ids.len()))]
+    pub async fn find_open_blockers<C: ConnectionTrait>(
+        db: &C,
+        ids: &[Uuid],
+    ) -> Result<Vec<(Uuid, memos::Model)>, DbErr> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let links = MemoDependencies::find()
+            .filter(memo_dependencies::Column::MemoId.is_in(ids.to_vec()))
+            .order_by_asc(memo_dependencies::Column::CreatedAt)
+            .all(db)
+            .await?;
+        let open = Memos::find()
+            .filter(
+                memos::Column::Id.is_in(
+                    links
+                        .iter()
+                        .flat_map(|link| [link.memo_id, link.blocker_id]),
+                ),
+            )
+            .filter(memos::Column::Completed.eq(false))
+            .filter(memos::Column::UndoToken.is_null())
+            .all(db)
+            .await?;
+
+        Ok(links
+            .into_iter()
+            .filter(|link| open.iter().any(|memo| memo.id == link.memo_id))
+            .filter_map(|link| {
+                open.iter()
+                    .find(|blocker| blocker.id == link.blocker_id)
+                    .map(|blocker| (link.memo_id, blocker.clone()))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,77 @@
+use crate::entities::{memo_import_failures, memo_imports, prelude::*};
+use chrono::Utc;
+use sea_orm::*;
+use uuid::Uuid;
+
+/// Uploaded import files waiting for their job, and the lines each import
+/// job rejected.
+pub struct MemoImportRepository;
+
+impl MemoImportRepository {
+    #[tracing::instrument(skip(db, content), fields(bytes = content.len()))]
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
+        content: String,
+    ) -> Result<memo_imports::Model, DbErr> {
+        let upload = memo_imports::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            content: Set(content),
+            created_at: Set(Utc::now().into()),
+        };
+
+        upload.insert(db).await
+    }
+
+    #[tracing::instrument(skip(db))]
+    pub async fn find_by_id<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+    ) -> Result<Option<memo_imports::Model>, DbErr> {
+        MemoImports::find_by_id(id).one(db).await
+    }
+
+    /// Removes an upload once its job has read it.
+    #[tracing::instrument(skip(db))]
+    pub async fn delete<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<(), DbErr> {
+        MemoImports::delete_by_id(id).exec(db).await?;
+        Ok(())
+    }
+
+    /// Records lines of `operation_id`'s file that did not become memos, as
+    /// `(line, code, message)`.
+    #[tracing::instrument(skip(db, failures), fields(failed = failures.len()))]
+    pub async fn record_failures<C: ConnectionTrait>(
+        db: &C,
+        operation_id: Uuid,
+        failures: Vec<(i32, String, String)>,
+    ) -> Result<(), DbErr> {
+        if failures.is_empty() {
+            return Ok(());
+        }
+
+        MemoImportFailures::insert_many(failures.into_iter().map(|(line, code, message)| {
+            memo_import_failures::ActiveModel {
+                operation_id: Set(operation_id),
+                line: Set(line),
+                code: Set(code),
+                message: Set(message),
+            }
+        }))
+        .exec(db)
+        .await?;
+        Ok(())
+    }
+
+    /// Rejected lines of an import, in file order.
+    #[tracing::instrument(skip(db))]
+    pub async fn find_failures<C: ConnectionTrait>(
+        db: &C,
+        operation_id: Uuid,
+    ) -> Result<Vec<memo_import_failures::Model>, DbErr> {
+        MemoImportFailures::find()
+            .filter(memo_import_failures::Column::OperationId.eq(operation_id))
+            .order_by_asc(memo_import_failures::Column::Line)
+            .all(db)
+            .await
+    }
+}
@@ -0,0 +1,74 @@
+use sea_orm::*;
+use uuid::Uuid;
+
+pub struct MemoListViewRepository;
+
+/// Each memo's links and attachments, for the memos listed in `affected`.
+/// Links to memos inside their undo window are left out.
+const UPSERT_AFFECTED: &str = r#"
+    INSERT INTO memo_list_view (memo_id, blocked_by, blocks, attachments, refreshed_at)
+    SELECT m.id,
+           ARRAY(SELECT d.blocker_id FROM memo_dependencies d
+                 JOIN memos b ON b.id = d.blocker_id AND b.undo_token IS NULL
+                 WHERE d.memo_id = m.id ORDER BY d.created_at, d.blocker_id),
+           ARRAY(SELECT d.memo_id FROM memo_dependencies d
+                 JOIN memos w ON w.id = d.memo_id AND w.undo_token IS NULL
+                 WHERE d.blocker_id = m.id ORDER BY d.created_at, d.memo_id),
+           COALESCE((SELECT jsonb_agg(jsonb_build_object(
+                         'id', a.id, 'memo_id', a.memo_id, 'file_name', a.file_name,
+                         'content_type', a.content_type, 'size_bytes', a.size_bytes,
+                         'width', a.width, 'height', a.height)
+                         ORDER BY a.created_at, a.id)
+                     FROM memo_attachments a WHERE a.memo_id = m.id), '[]'::jsonb),
+           NOW()
+    FROM memos m
+    JOIN affected ON affected.id = m.id
+    ON CONFLICT (memo_id) DO UPDATE
+    SET blocked_by = EXCLUDED.blocked_by,
+        blocks = EXCLUDED.blocks,
+        attachments = EXCLUDED.attachments,
+        refreshed_at = EXCLUDED.refreshed_at"#;
+
+impl MemoListViewRepository {
+    /// Rebuilds the rows of `memo_id` and of every memo linked to it, before
+    /// or after the change, and returns how many rows were written.
+    #[tracing::instrument(skip(db))]
+    pub async fn refresh<C: ConnectionTrait>(db: &C, memo_id: Uuid) -> Result<u64, DbErr> {
+        let sql = format!(
+            r#"WITH affected(id) AS (
+                   SELECT $1::uuid
+                   UNION SELECT blocker_id FROM memo_dependencies WHERE memo_id = $1
+                   UNION SELECT memo_id FROM memo_dependencies WHERE blocker_id = $1
+                   UNION SELECT memo_id FROM memo_list_view
+                         WHERE blocked_by @> ARRAY[$1::uuid] OR blocks @> ARRAY[$1::uuid]
+               ){UPSERT_AFFECTED}"#
+        );
+        let result = db
+            .execute(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                sql,
+                [memo_id.into()],
+            ))
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Rebuilds the rows still linking to memos that no longer exist, after
+    /// deletions that record no events.
+    #[tracing::instrument(skip(db))]
+    pub async fn refresh_dangling<C: ConnectionTrait>(db: &C) -> Result<u64, DbErr> {
+        let sql = format!(
+            r#"WITH affected(id) AS (
+                   SELECT v.memo_id FROM memo_list_view v
+                   WHERE EXISTS (
+                       SELECT 1 FROM unnest(v.blocked_by || v.blocks) AS linked(id)
+                       WHERE NOT EXISTS (SELECT 1 FROM memos WHERE memos.id = linked.id)
+                   )
+               ){UPSERT_AFFECTED}"#
+        );
+        let result = db
+            .execute(Statement::from_string(DatabaseBackend::Postgres, sql))
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
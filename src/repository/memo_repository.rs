@@ -1,66 +1,385 @@
-use crate::entities::{memos, prelude::*};
-use chrono::{DateTime, Utc};
-use sea_orm::*;
+use super::db_span;
+use crate::crypto::{self, FieldCryptoError};
+use crate::entities::{memo_list_view, memos, prelude::*};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use futures_util::Stream;
+use sea_orm::{
+    sea_query::{Expr, Func, LikeExpr, NullOrdering},
+    *,
+};
 use uuid::Uuid;
 
+/// Filters applied when listing memos.
+#[derive(Debug, Clone, Default)]
+pub struct MemoFilter {
+    pub completed: Option<bool>,
+    /// When `true`, only incomplete memos whose due date has passed.
+    pub overdue: Option<bool>,
+    /// Case-insensitive substring match against title and description. Only
+    /// titles are searched while descriptions are encrypted.
+    pub search: Option<String>,
+    /// List archived memos instead of the default, unarchived ones.
+    pub archived: bool,
+    pub pinned: Option<bool>,
+    pub starred: Option<bool>,
+    /// Only memos with this color label.
+    pub color: Option<String>,
+}
+
+/// Flags a memo can be marked with from a listing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoFlag {
+    Pinned,
+    Starred,
+}
+
+/// How [`MemoRepository::find_page`] works out the total number of matches.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TotalCount {
+    /// `COUNT(*)` over the filtered query.
+    #[default]
+    Exact,
+    /// The planner's row count for the table from `pg_class.reltuples`. It
+    /// ignores filters, so it is only an upper-bound hint.
+    Estimated,
+    /// No total at all; callers rely on `has_next`.
+    Skip,
+}
+
+/// One page of memos returned by [`MemoRepository::find_page`].
+#[derive(Debug, Clone)]
+pub struct MemoPage {
+    pub memos: Vec<memos::Model>,
+    /// The `memo_list_view` rows of those memos that have one.
+    pub views: Vec<memo_list_view::Model>,
+    /// `None` with [`TotalCount::Skip`].
+    pub total: Option<u64>,
+    /// Whether `total` came from the planner estimate.
+    pub estimated: bool,
+    /// Whether rows exist past this page, known without counting.
+    pub has_next: bool,
+}
+
+/// Aggregates returned by [`MemoRepository::stats`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoStats {
+    pub total: u64,
+    pub completed: u64,
+    /// Incomplete memos whose due date has passed.
+    pub overdue: u64,
+    /// Memos created per UTC day since the requested start, oldest first.
+    /// Days without new memos are absent.
+    pub created_per_day: Vec<(NaiveDate, u64)>,
+}
+
 pub struct MemoRepository;
 
 impl MemoRepository {
-    #[tracing::instrument(skip(db), fields(limit, offset, completed, sort_by, order))]
-    pub async fn find_all(
-        db: &DatabaseConnection,
+    #[tracing::instrument(skip(db), fields(limit, offset, sort_by, order, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn find_all<C: ConnectionTrait>(
+        db: &C,
         limit: u64,
         offset: u64,
-        completed: Option<bool>,
+        filter: &MemoFilter,
         sort_by: &str,
         order: &str,
     ) -> Result<(Vec<memos::Model>, u64), DbErr> {
         tracing::debug!(
             limit,
             offset,
-            completed,
+            completed = ?filter.completed,
+            overdue = ?filter.overdue,
+            search = ?filter.search,
+            archived = filter.archived,
             sort_by,
             order,
             "Finding all memos with filters"
         );
 
-        let mut query = Memos::find();
+        let query = Self::listing_query(filter, sort_by, order);
 
-        if let Some(completed_filter) = completed {
-            query = query.filter(memos::Column::Completed.eq(completed_filter));
-        }
+        let total = query.clone().count(db).await?;
 
-        let sort_column = match sort_by {
-            "title" => memos::Column::Title,
-            "date_to" => memos::Column::DateTo,
-            "completed" => memos::Column::Completed,
-            "updated_at" => memos::Column::UpdatedAt,
-            _ => memos::Column::CreatedAt,
+        let page = query.limit(limit).offset(offset);
+        db_span::record_statement(&page);
+        let memos = open_all(page.all(db).await?)?;
+        db_span::record_returned_rows(memos.len());
+
+        tracing::info!(found = memos.len(), total, "Successfully retrieved memos");
+
+        Ok((memos, total))
+    }
+
+    /// Like [`Self::find_all`], but `count` decides whether to pay for the
+    /// `COUNT(*)`. Fetches one extra row to tell whether a next page exists,
+    /// and the memos' `memo_list_view` rows in the same query.
+    #[tracing::instrument(skip(db), fields(limit, offset, sort_by, order, ?count, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn find_page<C: ConnectionTrait>(
+        db: &C,
+        limit: u64,
+        offset: u64,
+        filter: &MemoFilter,
+        sort_by: &str,
+        order: &str,
+        count: TotalCount,
+    ) -> Result<MemoPage, DbErr> {
+        let query = Self::listing_query(filter, sort_by, order);
+
+        let page = query
+            .clone()
+            .find_also_related(MemoListView)
+            .limit(limit + 1)
+            .offset(offset);
+        db_span::record_statement(&page);
+        let (memos, mut views): (Vec<_>, Vec<_>) = page.all(db).await?.into_iter().unzip();
+        let mut memos = open_all(memos)?;
+        db_span::record_returned_rows(memos.len());
+        let has_next = memos.len() as u64 > limit;
+        memos.truncate(limit as usize);
+        views.truncate(limit as usize);
+        let views = views.into_iter().flatten().collect();
+        let seen = offset + memos.len() as u64;
+
+        let (total, estimated) = match count {
+            TotalCount::Exact => (Some(query.count(db).await?), false),
+            // A short page means the end was reached, so the total is known.
+            TotalCount::Estimated if !has_next => (Some(seen), false),
+            TotalCount::Estimated => match Self::estimated_rows(db).await? {
+                Some(estimate) => (Some(estimate.max(seen + 1)), true),
+                None => (Some(query.count(db).await?), false),
+            },
+            TotalCount::Skip => (None, false),
         };
 
-        query = if order == "asc" {
-            query.order_by_asc(sort_column)
-        } else {
-            query.order_by_desc(sort_column)
+        tracing::info!(
+            found = memos.len(),
+            ?total,
+            estimated,
+            has_next,
+            "Retrieved memo page"
+        );
+
+        Ok(MemoPage {
+            memos,
+            views,
+            total,
+            estimated,
+            has_next,
+        })
+    }
+
+    /// Row estimate for the memos table from the last `ANALYZE`, or `None`
+    /// when the table has never been analyzed.
+    async fn estimated_rows<C: ConnectionTrait>(db: &C) -> Result<Option<u64>, DbErr> {
+        let row = db
+            .query_one(Statement::from_string(
+                DbBackend::Postgres,
+                "SELECT reltuples::bigint AS estimate FROM pg_class WHERE oid = 'memos'::regclass",
+            ))
+            .await?;
+        let estimate = match row {
+            Some(row) => row.try_get::<i64>("", "estimate")?,
+            None => -1,
         };
+        Ok(u64::try_from(estimate).ok())
+    }
 
-        let total = query.clone().count(db).await?;
+    /// Unarchived memos due in `[start, end)`, ordered by due date.
+    #[tracing::instrument(skip(db), fields(%start, %end, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn find_by_date_range<C: ConnectionTrait>(
+        db: &C,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<memos::Model>, DbErr> {
+        tracing::debug!("Finding memos by due date range");
 
-        let memos = query.limit(limit).offset(offset).all(db).await?;
+        let query = Self::live()
+            .filter(memos::Column::Archived.eq(false))
+            .filter(memos::Column::DateTo.gte(start))
+            .filter(memos::Column::DateTo.lt(end))
+            .order_by_asc(memos::Column::DateTo)
+            .order_by_asc(memos::Column::Id);
+        db_span::record_statement(&query);
+        let memos = open_all(query.all(db).await?)?;
+        db_span::record_returned_rows(memos.len());
 
-        tracing::info!(found = memos.len(), total, "Successfully retrieved memos");
+        tracing::info!(
+            found = memos.len(),
+            "Successfully retrieved memos in date range"
+        );
 
-        Ok((memos, total))
+        Ok(memos)
     }
 
-    #[tracing::instrument(skip(db), fields(memo_id = %id))]
-    pub async fn find_by_id(
-        db: &DatabaseConnection,
+    /// Open, unarchived memos due in `[start, end)`, where either bound may be
+    /// left open. Soonest first, at most `limit`.
+    #[tracing::instrument(skip(db), fields(?start, ?end, limit, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn find_open_due_between<C: ConnectionTrait>(
+        db: &C,
+        start: Option<DateTime<Utc>>,
+        end: Option<DateTime<Utc>>,
+        limit: u64,
+    ) -> Result<Vec<memos::Model>, DbErr> {
+        let mut query = Self::live()
+            .filter(memos::Column::Archived.eq(false))
+            .filter(memos::Column::Completed.eq(false));
+        if let Some(start) = start {
+            query = query.filter(memos::Column::DateTo.gte(start));
+        }
+        if let Some(end) = end {
+            query = query.filter(memos::Column::DateTo.lt(end));
+        }
+        let query = query
+            .order_by_asc(memos::Column::DateTo)
+            .order_by_asc(memos::Column::Id)
+            .limit(limit);
+        db_span::record_statement(&query);
+        let memos = open_all(query.all(db).await?)?;
+        db_span::record_returned_rows(memos.len());
+
+        Ok(memos)
+    }
+
+    /// Completed, unarchived memos, most recently completed first, at most
+    /// `limit`.
+    #[tracing::instrument(skip(db), fields(limit, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn find_recently_completed<C: ConnectionTrait>(
+        db: &C,
+        limit: u64,
+    ) -> Result<Vec<memos::Model>, DbErr> {
+        let query = Self::live()
+            .filter(memos::Column::Archived.eq(false))
+            .filter(memos::Column::Completed.eq(true))
+            .order_by_with_nulls(memos::Column::CompletedAt, Order::Desc, NullOrdering::Last)
+            .order_by_desc(memos::Column::UpdatedAt)
+            .order_by_desc(memos::Column::Id)
+            .limit(limit);
+        db_span::record_statement(&query);
+        let memos = open_all(query.all(db).await?)?;
+        db_span::record_returned_rows(memos.len());
+
+        Ok(memos)
+    }
+
+    /// Every live memo, archived or not, oldest first, read through a
+    /// server-side cursor in batches of `batch_size` so the whole table never
+    /// sits in memory. The cursor lives in its own transaction, which ends
+    /// with the stream; dropping the stream early rolls it back.
+    #[tracing::instrument(skip(db), fields(batch_size, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, otel.kind = "client"))]
+    pub async fn stream_all<C: TransactionTrait>(
+        db: &C,
+        batch_size: u64,
+    ) -> Result<impl Stream<Item = Result<Vec<memos::Model>, DbErr>> + Send + 'static, DbErr> {
+        let query = Self::live()
+            .order_by_asc(memos::Column::CreatedAt)
+            .order_by_asc(memos::Column::Id);
+        db_span::record_statement(&query);
+        let select = query.build(DbBackend::Postgres);
+
+        let txn = db.begin().await?;
+        txn.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            format!("DECLARE memo_export NO SCROLL CURSOR FOR {}", select.sql),
+            select.values.map(|values| values.0).unwrap_or_default(),
+        ))
+        .await?;
+
+        let fetch = format!("FETCH FORWARD {} FROM memo_export", batch_size.max(1));
+        Ok(futures_util::stream::unfold(Some(txn), move |txn| {
+            let fetch = fetch.clone();
+            async move {
+                let txn = txn?;
+                let batch = Memos::find()
+                    .from_raw_sql(Statement::from_string(DbBackend::Postgres, fetch))
+                    .all(&txn)
+                    .await
+                    .and_then(open_all);
+                match batch {
+                    Ok(batch) if batch.is_empty() => {
+                        if let Err(e) = txn.commit().await {
+                            return Some((Err(e), None));
+                        }
+                        None
+                    }
+                    Ok(batch) => Some((Ok(batch), Some(txn))),
+                    Err(e) => Some((Err(e), None)),
+                }
+            }
+        }))
+    }
+
+    /// Counts by status plus a per-day series of memos created since `since`,
+    /// computed with two aggregate queries. Archived memos are not counted.
+    #[tracing::instrument(skip(db), fields(%since, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn stats<C: ConnectionTrait>(
+        db: &C,
+        since: DateTime<Utc>,
+    ) -> Result<MemoStats, DbErr> {
+        tracing::debug!("Computing memo statistics");
+
+        let (total, completed, overdue) = Self::live()
+            .select_only()
+            .column_as(Expr::cust("COUNT(*)"), "total")
+            .column_as(Expr::cust("COUNT(*) FILTER (WHERE completed)"), "completed")
+            .column_as(
+                Expr::cust_with_values(
+                    "COUNT(*) FILTER (WHERE NOT completed AND date_to < $1)",
+                    [Utc::now()],
+                ),
+                "overdue",
+            )
+            .filter(memos::Column::Archived.eq(false))
+            .into_tuple::<(i64, i64, i64)>()
+            .one(db)
+            .await?
+            .unwrap_or_default();
+
+        let day = Expr::cust("(created_at AT TIME ZONE 'UTC')::date");
+        let per_day = Self::live()
+            .select_only()
+            .column_as(day.clone(), "day")
+            .column_as(Expr::cust("COUNT(*)"), "count")
+            .filter(memos::Column::Archived.eq(false))
+            .filter(memos::Column::CreatedAt.gte(since))
+            .group_by(day.clone())
+            .order_by_asc(day);
+        // The series is the query whose size varies, so it is the one traced.
+        db_span::record_statement(&per_day);
+        let created_per_day = per_day.into_tuple::<(NaiveDate, i64)>().all(db).await?;
+        db_span::record_returned_rows(created_per_day.len());
+
+        let stats = MemoStats {
+            total: total as u64,
+            completed: completed as u64,
+            overdue: overdue as u64,
+            created_per_day: created_per_day
+                .into_iter()
+                .map(|(day, count)| (day, count as u64))
+                .collect(),
+        };
+
+        tracing::info!(
+            total = stats.total,
+            completed = stats.completed,
+            overdue = stats.overdue,
+            "Computed memo statistics"
+        );
+
+        Ok(stats)
+    }
+
+    #[tracing::instrument(skip(db), fields(memo_id = %id, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn find_by_id<C: ConnectionTrait>(
+        db: &C,
         id: Uuid,
     ) -> Result<Option<memos::Model>, DbErr> {
         tracing::debug!("Finding memo by ID");
 
-        let memo = Memos::find_by_id(id).one(db).await?;
+        let query = Self::live_by_id(id);
+        db_span::record_statement(&query);
+        let memo = query.one(db).await?.map(open).transpose()?;
+        db_span::record_returned_rows(usize::from(memo.is_some()));
 
         if memo.is_some() {
             tracing::info!("Memo found");
@@ -71,57 +390,148 @@ impl MemoRepository {
         Ok(memo)
     }
 
-    #[tracing::instrument(skip(db), fields(title, has_description = description.is_some()))]
-    pub async fn create(
-        db: &DatabaseConnection,
+    /// Like [`Self::find_by_id`], but locks the row until the surrounding
+    /// transaction ends so a read-modify-write cannot interleave with another.
+    #[tracing::instrument(skip(db), fields(memo_id = %id, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn find_by_id_for_update<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+    ) -> Result<Option<memos::Model>, DbErr> {
+        tracing::debug!("Finding memo by ID for update");
+
+        let query = Self::live_by_id(id).lock_exclusive();
+        db_span::record_statement(&query);
+        let memo = query.one(db).await?.map(open).transpose()?;
+        db_span::record_returned_rows(usize::from(memo.is_some()));
+
+        Ok(memo)
+    }
+
+    #[tracing::instrument(skip(db), fields(title, has_description = description.is_some(), db.system = db_span::SYSTEM, db.operation = "INSERT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
         title: String,
         description: Option<String>,
         date_to: DateTime<Utc>,
+        timezone: Tz,
+        color: Option<String>,
     ) -> Result<memos::Model, DbErr> {
         tracing::debug!("Creating new memo");
 
         let now = Utc::now();
         let id = Uuid::new_v4();
+        let position = Self::next_position(db).await?;
 
         let new_memo = memos::ActiveModel {
             id: Set(id),
             title: Set(title),
-            description: Set(description),
+            description: Set(seal(id, description)?),
             date_to: Set(date_to.into()),
             completed: Set(false),
             created_at: Set(now.into()),
             updated_at: Set(now.into()),
+            position: Set(position),
+            version: Set(1),
+            archived: Set(false),
+            timezone: Set(timezone.name().to_string()),
+            completed_at: Set(None),
+            completion_note: Set(None),
+            pinned: Set(false),
+            starred: Set(false),
+            color: Set(color),
+            undo_token: Set(None),
         };
 
-        let memo = new_memo.insert(db).await?;
+        db_span::record_statement(&Memos::insert(new_memo.clone()));
+        let memo = open(new_memo.insert(db).await?)?;
+        db_span::record_returned_rows(1);
 
         tracing::info!(memo_id = %memo.id, "Memo created successfully");
 
         Ok(memo)
     }
 
-    #[tracing::instrument(skip(db), fields(memo_id = %id, has_description = description.is_some(), completed))]
-    pub async fn update(
-        db: &DatabaseConnection,
+    /// Inserts a memo with a caller-chosen id unless one with that id already
+    /// exists. Returns whether a row was inserted.
+    #[tracing::instrument(skip(db), fields(memo_id = %id, title, completed, db.system = db_span::SYSTEM, db.operation = "INSERT", db.sql.table = "memos", db.statement, db.rows_affected, otel.kind = "client"))]
+    pub async fn insert_if_absent<C: ConnectionTrait>(
+        db: &C,
         id: Uuid,
         title: String,
         description: Option<String>,
         date_to: DateTime<Utc>,
         completed: bool,
+    ) -> Result<bool, DbErr> {
+        tracing::debug!("Inserting memo if absent");
+
+        let now = Utc::now();
+        let position = Self::next_position(db).await?;
+
+        let memo = memos::ActiveModel {
+            id: Set(id),
+            title: Set(title),
+            description: Set(seal(id, description)?),
+            date_to: Set(date_to.into()),
+            completed: Set(completed),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+            position: Set(position),
+            version: Set(1),
+            archived: Set(false),
+            timezone: Set(Tz::UTC.name().to_string()),
+            completed_at: Set(completed.then(|| now.into())),
+            completion_note: Set(None),
+            pinned: Set(false),
+            starred: Set(false),
+            color: Set(None),
+            undo_token: Set(None),
+        };
+
+        let insert = Memos::insert(memo).on_conflict(
+            sea_query::OnConflict::column(memos::Column::Id)
+                .do_nothing()
+                .to_owned(),
+        );
+        db_span::record_statement(&insert);
+        let inserted = insert.exec_without_returning(db).await?;
+        db_span::record_rows_affected(inserted);
+
+        Ok(inserted > 0)
+    }
+
+    #[tracing::instrument(skip(db), fields(memo_id = %id, has_description = description.is_some(), completed, db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    #[allow(clippy::too_many_arguments)] // one per editable memo field
+    pub async fn update<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        title: String,
+        description: Option<String>,
+        date_to: DateTime<Utc>,
+        completed: bool,
+        timezone: Tz,
+        color: Option<String>,
     ) -> Result<memos::Model, DbErr> {
         tracing::debug!("Updating memo");
 
-        let memo = Memos::find_by_id(id).one(db).await?;
+        let memo = Self::live_by_id(id).lock_exclusive().one(db).await?;
 
         if let Some(existing_memo) = memo {
+            let next_version = existing_memo.version + 1;
+            let was_completed = existing_memo.completed;
             let mut active_memo: memos::ActiveModel = existing_memo.into();
+            active_memo.version = Set(next_version);
             active_memo.title = Set(title);
-            active_memo.description = Set(description);
+            active_memo.description = Set(seal(id, description)?);
             active_memo.date_to = Set(date_to.into());
             active_memo.completed = Set(completed);
+            track_completion(&mut active_memo, was_completed, completed, None);
+            active_memo.timezone = Set(timezone.name().to_string());
+            active_memo.color = Set(color);
             active_memo.updated_at = Set(Utc::now().into());
 
-            let updated_memo = active_memo.update(db).await?;
+            db_span::record_statement(&Memos::update(active_memo.clone()));
+            let updated_memo = open(active_memo.update(db).await?)?;
+            db_span::record_returned_rows(1);
 
             tracing::info!(memo_id = %updated_memo.id, "Memo updated successfully");
 
@@ -135,15 +545,117 @@ impl MemoRepository {
         }
     }
 
-    #[tracing::instrument(skip(db), fields(memo_id = %id))]
-    pub async fn delete(db: &DatabaseConnection, id: Uuid) -> Result<bool, DbErr> {
+    /// Marks a memo completed with an optional `note`, or reopens it, bumping
+    /// the version. Returns `None` when the memo does not exist.
+    #[tracing::instrument(skip(db, note), fields(memo_id = %id, completed, has_note = note.is_some(), db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn set_completed<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        completed: bool,
+        note: Option<String>,
+    ) -> Result<Option<memos::Model>, DbErr> {
+        tracing::debug!("Setting memo completion");
+
+        let Some(existing_memo) = Self::live_by_id(id).lock_exclusive().one(db).await? else {
+            return Ok(None);
+        };
+
+        let next_version = existing_memo.version + 1;
+        let was_completed = existing_memo.completed;
+        let mut active_memo: memos::ActiveModel = existing_memo.into();
+        active_memo.completed = Set(completed);
+        track_completion(&mut active_memo, was_completed, completed, note);
+        active_memo.version = Set(next_version);
+        active_memo.updated_at = Set(Utc::now().into());
+
+        db_span::record_statement(&Memos::update(active_memo.clone()));
+        let memo = open(active_memo.update(db).await?)?;
+        db_span::record_returned_rows(1);
+
+        tracing::info!(memo_id = %memo.id, completed, "Memo completion updated");
+
+        Ok(Some(memo))
+    }
+
+    /// Sets the archived flag, bumping the version. Returns `None` when the
+    /// memo does not exist.
+    #[tracing::instrument(skip(db), fields(memo_id = %id, archived, db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn set_archived<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        archived: bool,
+    ) -> Result<Option<memos::Model>, DbErr> {
+        tracing::debug!("Setting memo archived flag");
+
+        let Some(existing_memo) = Self::live_by_id(id).lock_exclusive().one(db).await? else {
+            return Ok(None);
+        };
+
+        let next_version = existing_memo.version + 1;
+        let mut active_memo: memos::ActiveModel = existing_memo.into();
+        active_memo.archived = Set(archived);
+        active_memo.version = Set(next_version);
+        active_memo.updated_at = Set(Utc::now().into());
+
+        db_span::record_statement(&Memos::update(active_memo.clone()));
+        let memo = open(active_memo.update(db).await?)?;
+        db_span::record_returned_rows(1);
+
+        tracing::info!(memo_id = %memo.id, archived, "Memo archived flag updated");
+
+        Ok(Some(memo))
+    }
+
+    /// Flips `flag`, bumping the version. Returns `None` when the memo does
+    /// not exist.
+    #[tracing::instrument(skip(db), fields(memo_id = %id, ?flag, db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn toggle_flag<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        flag: MemoFlag,
+    ) -> Result<Option<memos::Model>, DbErr> {
+        tracing::debug!("Toggling memo flag");
+
+        let Some(existing_memo) = Self::live_by_id(id).lock_exclusive().one(db).await? else {
+            return Ok(None);
+        };
+
+        let next_version = existing_memo.version + 1;
+        let (pinned, starred) = (existing_memo.pinned, existing_memo.starred);
+        let mut active_memo: memos::ActiveModel = existing_memo.into();
+        match flag {
+            MemoFlag::Pinned => active_memo.pinned = Set(!pinned),
+            MemoFlag::Starred => active_memo.starred = Set(!starred),
+        }
+        active_memo.version = Set(next_version);
+        active_memo.updated_at = Set(Utc::now().into());
+
+        db_span::record_statement(&Memos::update(active_memo.clone()));
+        let memo = open(active_memo.update(db).await?)?;
+        db_span::record_returned_rows(1);
+
+        tracing::info!(
+            memo_id = %memo.id,
+            pinned = memo.pinned,
+            starred = memo.starred,
+            "Memo flag toggled"
+        );
+
+        Ok(Some(memo))
+    }
+
+    /// Deletes a memo for good, including one still inside its undo window.
+    #[tracing::instrument(skip(db), fields(memo_id = %id, db.system = db_span::SYSTEM, db.operation = "DELETE", db.sql.table = "memos", db.statement, db.rows_affected, otel.kind = "client"))]
+    pub async fn delete<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<bool, DbErr> {
         tracing::debug!("Deleting memo");
 
-        let memo = Memos::find_by_id(id).one(db).await?;
+        let memo = Memos::find_by_id(id).lock_exclusive().one(db).await?;
 
         if let Some(existing_memo) = memo {
             let active_memo: memos::ActiveModel = existing_memo.into();
-            active_memo.delete(db).await?;
+            db_span::record_statement(&Memos::delete(active_memo.clone()));
+            let result = active_memo.delete(db).await?;
+            db_span::record_rows_affected(result.rows_affected);
 
             tracing::info!("Memo deleted successfully");
 
@@ -153,4 +665,549 @@ impl MemoRepository {
             Ok(false)
         }
     }
+
+    /// Hides the memos among `ids` behind `token` until the deletion is undone
+    /// or finalized, and returns the ids that were hidden. Memos that do not
+    /// exist or are already deleted are skipped.
+    #[tracing::instrument(skip(db, ids), fields(count = ids.len(), %token, db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn soft_delete_many<C: ConnectionTrait>(
+        db: &C,
+        ids: &[Uuid],
+        token: Uuid,
+    ) -> Result<Vec<Uuid>, DbErr> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let update = Memos::update_many()
+            .col_expr(memos::Column::UndoToken, Expr::value(token))
+            .filter(memos::Column::Id.is_in(ids.iter().copied()))
+            .filter(memos::Column::UndoToken.is_null());
+        db_span::record_statement(&update);
+        let deleted = update.exec_with_returning(db).await?;
+        db_span::record_returned_rows(deleted.len());
+
+        tracing::info!(deleted = deleted.len(), "Memos soft-deleted");
+
+        Ok(deleted.into_iter().map(|memo| memo.id).collect())
+    }
+
+    /// Brings back the memos hidden behind `token`, in manual order.
+    #[tracing::instrument(skip(db), fields(%token, db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn restore<C: ConnectionTrait>(
+        db: &C,
+        token: Uuid,
+    ) -> Result<Vec<memos::Model>, DbErr> {
+        let update = Memos::update_many()
+            .col_expr(memos::Column::UndoToken, Expr::value(Option::<Uuid>::None))
+            .filter(memos::Column::UndoToken.eq(token));
+        db_span::record_statement(&update);
+        let mut restored = open_all(update.exec_with_returning(db).await?)?;
+        db_span::record_returned_rows(restored.len());
+        restored.sort_by_key(|memo| (memo.position, memo.created_at));
+
+        tracing::info!(restored = restored.len(), "Memos restored");
+
+        Ok(restored)
+    }
+
+    /// Moves a memo to `index` in the manual ordering, shifting the memos in between.
+    ///
+    /// Runs in a single transaction so concurrent moves cannot leave duplicate positions.
+    #[tracing::instrument(skip(db), fields(memo_id = %id, index, db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.rows_affected, otel.kind = "client"))]
+    pub async fn move_to<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        id: Uuid,
+        index: usize,
+    ) -> Result<memos::Model, DbErr> {
+        tracing::debug!("Moving memo");
+
+        let txn = db.begin().await?;
+
+        let mut ordered: Vec<(Uuid, i32)> = Self::live()
+            .select_only()
+            .column(memos::Column::Id)
+            .column(memos::Column::Position)
+            .order_by_asc(memos::Column::Position)
+            .order_by_asc(memos::Column::CreatedAt)
+            .lock_exclusive()
+            .into_tuple()
+            .all(&txn)
+            .await?;
+
+        let current = ordered
+            .iter()
+            .position(|(memo_id, _)| *memo_id == id)
+            .ok_or_else(|| DbErr::RecordNotFound(format!("Memo with id {} not found", id)))?;
+
+        let moved = ordered.remove(current);
+        ordered.insert(index.min(ordered.len()), moved);
+
+        let now = Utc::now();
+        let mut moved = 0;
+        for (new_position, (memo_id, old_position)) in ordered.iter().enumerate() {
+            let new_position = new_position as i32;
+            if new_position != *old_position {
+                moved += Self::set_position(&txn, *memo_id, new_position, now).await?;
+            }
+        }
+        db_span::record_rows_affected(moved);
+
+        let memo = Self::live_by_id(id)
+            .one(&txn)
+            .await?
+            .map(open)
+            .transpose()?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("Memo with id {} not found", id)))?;
+
+        txn.commit().await?;
+
+        tracing::info!(position = memo.position, "Memo moved successfully");
+
+        Ok(memo)
+    }
+
+    /// Reassigns the positions currently held by `ids` so they follow the given order.
+    ///
+    /// Only the listed memos change, which lets a client reorder a single page of
+    /// results without knowing the positions of memos outside it.
+    #[tracing::instrument(skip(db, ids), fields(count = ids.len(), db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.rows_affected, otel.kind = "client"))]
+    pub async fn reorder<C: ConnectionTrait + TransactionTrait>(
+        db: &C,
+        ids: &[Uuid],
+    ) -> Result<(), DbErr> {
+        tracing::debug!("Reordering memos");
+
+        let txn = db.begin().await?;
+
+        let memos = Self::live()
+            .filter(memos::Column::Id.is_in(ids.iter().copied()))
+            .lock_exclusive()
+            .all(&txn)
+            .await?;
+
+        if memos.len() != ids.len() {
+            return Err(DbErr::RecordNotFound(
+                "One or more memos to reorder were not found".to_string(),
+            ));
+        }
+
+        let mut positions: Vec<i32> = memos.iter().map(|m| m.position).collect();
+        positions.sort_unstable();
+
+        let now = Utc::now();
+        let mut moved = 0;
+        for (id, position) in ids.iter().zip(positions) {
+            let current = memos.iter().find(|m| m.id == *id).map(|m| m.position);
+            if current != Some(position) {
+                moved += Self::set_position(&txn, *id, position, now).await?;
+            }
+        }
+        db_span::record_rows_affected(moved);
+
+        txn.commit().await?;
+
+        tracing::info!("Memos reordered successfully");
+
+        Ok(())
+    }
+
+    /// Moves one memo to `position` and returns the number of rows changed.
+    /// Every call runs the same statement, which is recorded on the caller's
+    /// span.
+    async fn set_position<C: ConnectionTrait>(
+        conn: &C,
+        id: Uuid,
+        position: i32,
+        now: DateTime<Utc>,
+    ) -> Result<u64, DbErr> {
+        let update = Memos::update_many()
+            .col_expr(memos::Column::Position, Expr::value(position))
+            .col_expr(memos::Column::UpdatedAt, Expr::value(now))
+            .col_expr(
+                memos::Column::Version,
+                Expr::col(memos::Column::Version).add(1),
+            )
+            .filter(memos::Column::Id.eq(id));
+        db_span::record_statement(&update);
+        Ok(update.exec(conn).await?.rows_affected)
+    }
+
+    /// Ids of the memos matching `filter`.
+    #[tracing::instrument(skip(db), fields(db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn find_ids<C: ConnectionTrait>(
+        db: &C,
+        filter: &MemoFilter,
+    ) -> Result<Vec<Uuid>, DbErr> {
+        let query = Self::filtered(filter)
+            .select_only()
+            .column(memos::Column::Id);
+        db_span::record_statement(&query);
+        let ids = query.into_tuple().all(db).await?;
+        db_span::record_returned_rows(ids.len());
+
+        Ok(ids)
+    }
+
+    /// Which of `ids` exist.
+    #[tracing::instrument(skip(db, ids), fields(count = ids.len(), db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn existing_ids<C: ConnectionTrait>(
+        db: &C,
+        ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, DbErr> {
+        let query = Self::live()
+            .select_only()
+            .column(memos::Column::Id)
+            .filter(memos::Column::Id.is_in(ids.iter().copied()));
+        db_span::record_statement(&query);
+        let existing = query.into_tuple().all(db).await?;
+        db_span::record_returned_rows(existing.len());
+
+        Ok(existing)
+    }
+
+    /// Marks the incomplete memos among `ids` completed, bumping their
+    /// versions, and returns the ids that changed.
+    #[tracing::instrument(skip(db, ids), fields(count = ids.len(), db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn complete_many<C: ConnectionTrait>(
+        db: &C,
+        ids: &[Uuid],
+    ) -> Result<Vec<Uuid>, DbErr> {
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let update = Memos::update_many()
+            .col_expr(memos::Column::Completed, Expr::value(true))
+            .col_expr(memos::Column::CompletedAt, Expr::value(Utc::now()))
+            .col_expr(
+                memos::Column::Version,
+                Expr::col(memos::Column::Version).add(1),
+            )
+            .col_expr(memos::Column::UpdatedAt, Expr::value(Utc::now()))
+            .filter(memos::Column::Id.is_in(ids.iter().copied()))
+            .filter(memos::Column::Completed.eq(false));
+        db_span::record_statement(&update);
+        let updated = update.exec_with_returning(db).await?;
+        db_span::record_returned_rows(updated.len());
+
+        tracing::info!(updated = updated.len(), "Memos marked completed");
+
+        Ok(updated.into_iter().map(|memo| memo.id).collect())
+    }
+
+    /// Memos that are not waiting out an undo window after being deleted.
+    fn live() -> Select<Memos> {
+        Memos::find().filter(memos::Column::UndoToken.is_null())
+    }
+
+    fn live_by_id(id: Uuid) -> Select<Memos> {
+        Self::live().filter(memos::Column::Id.eq(id))
+    }
+
+    fn filtered(filter: &MemoFilter) -> Select<Memos> {
+        let mut query = Self::live().filter(memos::Column::Archived.eq(filter.archived));
+
+        if let Some(completed_filter) = filter.completed {
+            query = query.filter(memos::Column::Completed.eq(completed_filter));
+        }
+
+        if let Some(pinned) = filter.pinned {
+            query = query.filter(memos::Column::Pinned.eq(pinned));
+        }
+
+        if let Some(starred) = filter.starred {
+            query = query.filter(memos::Column::Starred.eq(starred));
+        }
+
+        if let Some(color) = filter.color.as_deref() {
+            query = query.filter(memos::Column::Color.eq(color));
+        }
+
+        match filter.overdue {
+            Some(true) => {
+                query = query
+                    .filter(memos::Column::Completed.eq(false))
+                    .filter(memos::Column::DateTo.lt(Utc::now()));
+            }
+            Some(false) => {
+                query = query.filter(
+                    Condition::any()
+                        .add(memos::Column::Completed.eq(true))
+                        .add(memos::Column::DateTo.gte(Utc::now())),
+                );
+            }
+            None => {}
+        }
+
+        if let Some(search) = filter.search.as_deref() {
+            let pattern = format!("%{}%", escape_like(&search.to_lowercase()));
+            let mut matches = Condition::any().add(
+                Expr::expr(Func::lower(Expr::col(memos::Column::Title)))
+                    .like(LikeExpr::new(pattern.clone()).escape('\\')),
+            );
+            // Ciphertext cannot be matched, so encrypted descriptions are not searched.
+            if crypto::cipher().is_none() {
+                matches = matches.add(
+                    Expr::expr(Func::lower(Expr::col(memos::Column::Description)))
+                        .like(LikeExpr::new(pattern).escape('\\')),
+                );
+            }
+            query = query.filter(matches);
+        }
+
+        query
+    }
+
+    /// Completed memos last updated before `cutoff`, oldest first. Archived
+    /// memos are only included with `include_archived`.
+    fn purgeable(cutoff: DateTime<Utc>, include_archived: bool) -> Select<Memos> {
+        let mut query = Self::live()
+            .filter(memos::Column::Completed.eq(true))
+            .filter(memos::Column::UpdatedAt.lt(cutoff));
+        if !include_archived {
+            query = query.filter(memos::Column::Archived.eq(false));
+        }
+        query
+            .order_by_asc(memos::Column::UpdatedAt)
+            .order_by_asc(memos::Column::Id)
+    }
+
+    /// Number of memos a retention run with `cutoff` would purge.
+    #[tracing::instrument(skip(db), fields(%cutoff, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn count_purgeable<C: ConnectionTrait>(
+        db: &C,
+        cutoff: DateTime<Utc>,
+        include_archived: bool,
+    ) -> Result<u64, DbErr> {
+        let query = Self::purgeable(cutoff, include_archived);
+        db_span::record_statement(&query);
+        let count = query.count(db).await?;
+        db_span::record_returned_rows(1);
+
+        Ok(count)
+    }
+
+    /// The first `limit` memos a retention run with `cutoff` would purge.
+    #[tracing::instrument(skip(db), fields(%cutoff, db.system = db_span::SYSTEM, db.operation = "SELECT", db.sql.table = "memos", db.statement, db.response.returned_rows, otel.kind = "client"))]
+    pub async fn find_purgeable<C: ConnectionTrait>(
+        db: &C,
+        cutoff: DateTime<Utc>,
+        include_archived: bool,
+        limit: u64,
+    ) -> Result<Vec<memos::Model>, DbErr> {
+        let query = Self::purgeable(cutoff, include_archived).limit(limit);
+        db_span::record_statement(&query);
+        let memos = open_all(query.all(db).await?)?;
+        db_span::record_returned_rows(memos.len());
+
+        Ok(memos)
+    }
+
+    /// Archives up to `limit` of the oldest unarchived purgeable memos and
+    /// returns how many changed. `updated_at` is left alone so the memos keep
+    /// their age should the policy later switch to deleting.
+    #[tracing::instrument(skip(db), fields(%cutoff, db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.rows_affected, otel.kind = "client"))]
+    pub async fn archive_purgeable_batch<C: ConnectionTrait>(
+        db: &C,
+        cutoff: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<u64, DbErr> {
+        let batch = Self::purgeable(cutoff, false)
+            .select_only()
+            .column(memos::Column::Id)
+            .limit(limit)
+            .into_query();
+
+        let update = Memos::update_many()
+            .col_expr(memos::Column::Archived, Expr::value(true))
+            .col_expr(
+                memos::Column::Version,
+                Expr::col(memos::Column::Version).add(1),
+            )
+            .filter(memos::Column::Id.in_subquery(batch));
+        db_span::record_statement(&update);
+        let result = update.exec(db).await?;
+        db_span::record_rows_affected(result.rows_affected);
+
+        tracing::info!(archived = result.rows_affected, "Archived purgeable memos");
+
+        Ok(result.rows_affected)
+    }
+
+    /// Deletes up to `limit` of the oldest purgeable memos, archived or not,
+    /// and returns how many were removed.
+    #[tracing::instrument(skip(db), fields(%cutoff, db.system = db_span::SYSTEM, db.operation = "DELETE", db.sql.table = "memos", db.statement, db.rows_affected, otel.kind = "client"))]
+    pub async fn delete_purgeable_batch<C: ConnectionTrait>(
+        db: &C,
+        cutoff: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<u64, DbErr> {
+        let batch = Self::purgeable(cutoff, true)
+            .select_only()
+            .column(memos::Column::Id)
+            .limit(limit)
+            .into_query();
+
+        let delete = Memos::delete_many().filter(memos::Column::Id.in_subquery(batch));
+        db_span::record_statement(&delete);
+        let result = delete.exec(db).await?;
+        db_span::record_rows_affected(result.rows_affected);
+
+        tracing::info!(deleted = result.rows_affected, "Deleted purgeable memos");
+
+        Ok(result.rows_affected)
+    }
+
+    /// Re-encrypts, in id order after `after`, up to `limit` descriptions not
+    /// yet sealed with the current key: plaintext from before encryption was
+    /// enabled, or values under a rotated-out key. Returns how many rows
+    /// changed and the last id seen, or `None` once no rows are left.
+    ///
+    /// Version and `updated_at` are left alone since the content is unchanged.
+    #[tracing::instrument(skip(db), fields(db.system = db_span::SYSTEM, db.operation = "UPDATE", db.sql.table = "memos", db.statement, db.rows_affected, otel.kind = "client"))]
+    pub async fn reencrypt_descriptions<C: ConnectionTrait>(
+        db: &C,
+        after: Option<Uuid>,
+        limit: u64,
+    ) -> Result<Option<(u64, Uuid)>, DbErr> {
+        let cipher = crypto::cipher()
+            .ok_or_else(|| DbErr::Custom("Field encryption is not configured".to_string()))?;
+
+        let current = format!("enc:v1:{}:%", cipher.current_key_id());
+        let mut query = Memos::find()
+            .select_only()
+            .column(memos::Column::Id)
+            .column(memos::Column::Description)
+            .filter(memos::Column::Description.is_not_null())
+            .filter(memos::Column::Description.not_like(current))
+            .order_by_asc(memos::Column::Id)
+            .limit(limit);
+        if let Some(after) = after {
+            query = query.filter(memos::Column::Id.gt(after));
+        }
+        let rows: Vec<(Uuid, String)> = query.into_tuple().all(db).await?;
+        let Some(&(last, _)) = rows.last() else {
+            return Ok(None);
+        };
+
+        let mut updated = 0;
+        for (id, stored) in rows {
+            let plaintext = cipher
+                .decrypt(&stored, id.as_bytes())
+                .map_err(|e| crypto_error(id, e))?;
+            let sealed = cipher
+                .encrypt(&plaintext, id.as_bytes())
+                .map_err(|e| crypto_error(id, e))?;
+            // Skips rows edited since they were read; the edit already sealed them.
+            let update = Memos::update_many()
+                .col_expr(memos::Column::Description, Expr::value(sealed))
+                .filter(memos::Column::Id.eq(id))
+                .filter(memos::Column::Description.eq(stored));
+            db_span::record_statement(&update);
+            updated += update.exec(db).await?.rows_affected;
+        }
+        db_span::record_rows_affected(updated);
+
+        tracing::info!(updated, "Re-encrypted memo descriptions");
+
+        Ok(Some((updated, last)))
+    }
+
+    /// [`Self::filtered`] with pinned memos first, then ordered by `sort_by`
+    /// (unknown fields fall back to `created_at`) in `order`, with `id` as a
+    /// tie-breaker so pages are stable. The default `created_at` ordering
+    /// matches the `idx_memos_archived_*pinned_created_at` indexes, which
+    /// serve it without a sort.
+    pub fn listing_query(filter: &MemoFilter, sort_by: &str, order: &str) -> Select<Memos> {
+        let sort_column = match sort_by {
+            "title" => memos::Column::Title,
+            "date_to" => memos::Column::DateTo,
+            "completed" => memos::Column::Completed,
+            "updated_at" => memos::Column::UpdatedAt,
+            "position" => memos::Column::Position,
+            "starred" => memos::Column::Starred,
+            _ => memos::Column::CreatedAt,
+        };
+
+        let query = Self::filtered(filter).order_by_desc(memos::Column::Pinned);
+        if order == "asc" {
+            query
+                .order_by_asc(sort_column)
+                .order_by_asc(memos::Column::Id)
+        } else {
+            query
+                .order_by_desc(sort_column)
+                .order_by_desc(memos::Column::Id)
+        }
+    }
+
+    async fn next_position<C: ConnectionTrait>(db: &C) -> Result<i32, DbErr> {
+        let max: Option<Option<i32>> = Memos::find()
+            .select_only()
+            .column_as(memos::Column::Position.max(), "max_position")
+            .into_tuple()
+            .one(db)
+            .await?;
+        Ok(max.flatten().map_or(0, |p| p + 1))
+    }
+}
+
+/// Stamps `completed_at` (and stores `note`) when a memo becomes completed,
+/// and clears both when it is reopened. A memo that stays completed keeps
+/// its timestamp and note.
+fn track_completion(
+    memo: &mut memos::ActiveModel,
+    was_completed: bool,
+    completed: bool,
+    note: Option<String>,
+) {
+    match (was_completed, completed) {
+        (false, true) => {
+            memo.completed_at = Set(Some(Utc::now().into()));
+            memo.completion_note = Set(note);
+        }
+        (_, false) => {
+            memo.completed_at = Set(None);
+            memo.completion_note = Set(None);
+        }
+        (true, true) => {}
+    }
+}
+
+/// Encrypts a description for storage in memo `id`'s row.
+fn seal(id: Uuid, description: Option<String>) -> Result<Option<String>, DbErr> {
+    description
+        .map(|text| crypto::encrypt_field(&text, id.as_bytes()))
+        .transpose()
+        .map_err(|e| crypto_error(id, e))
+}
+
+/// Decrypts the description of a memo read from the database.
+fn open(mut memo: memos::Model) -> Result<memos::Model, DbErr> {
+    if let Some(stored) = memo.description.as_deref() {
+        let plaintext = crypto::decrypt_field(stored, memo.id.as_bytes())
+            .map_err(|e| crypto_error(memo.id, e))?;
+        memo.description = Some(plaintext);
+    }
+    Ok(memo)
+}
+
+fn open_all(memos: Vec<memos::Model>) -> Result<Vec<memos::Model>, DbErr> {
+    memos.into_iter().map(open).collect()
+}
+
+fn crypto_error(id: Uuid, error: FieldCryptoError) -> DbErr {
+    DbErr::Custom(format!("Description of memo {}: {}", id, error))
+}
+
+fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
@@ -0,0 +1,43 @@
+use crate::entities::{memo_snoozes, prelude::*};
+use chrono::{DateTime, Utc};
+use sea_orm::*;
+use uuid::Uuid;
+
+pub struct MemoSnoozeRepository;
+
+impl MemoSnoozeRepository {
+    /// Records that `memo_id`'s due date moved from `from` to `until`.
+    #[tracing::instrument(skip(db))]
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
+        memo_id: Uuid,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+        preset: Option<String>,
+    ) -> Result<memo_snoozes::Model, DbErr> {
+        let snooze = memo_snoozes::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            memo_id: Set(memo_id),
+            snoozed_from: Set(from.into()),
+            snoozed_until: Set(until.into()),
+            preset: Set(preset),
+            created_at: Set(Utc::now().into()),
+        };
+
+        snooze.insert(db).await
+    }
+
+    /// Snoozes of a memo, newest first.
+    #[tracing::instrument(skip(db))]
+    pub async fn find_by_memo<C: ConnectionTrait>(
+        db: &C,
+        memo_id: Uuid,
+    ) -> Result<Vec<memo_snoozes::Model>, DbErr> {
+        MemoSnoozes::find()
+            .filter(memo_snoozes::Column::MemoId.eq(memo_id))
+            .order_by_desc(memo_snoozes::Column::CreatedAt)
+            .order_by_desc(memo_snoozes::Column::Id)
+            .all(db)
+            .await
+    }
+}
@@ -0,0 +1,77 @@
+use crate::entities::{memo_templates, prelude::*};
+use chrono::Utc;
+use sea_orm::*;
+use uuid::Uuid;
+
+pub struct MemoTemplateRepository;
+
+impl MemoTemplateRepository {
+    #[tracing::instrument(skip(db, title, description))]
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
+        name: String,
+        title: String,
+        description: Option<String>,
+        due_text: Option<String>,
+    ) -> Result<memo_templates::Model, DbErr> {
+        let now = Utc::now();
+        let template = memo_templates::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            name: Set(name),
+            title: Set(title),
+            description: Set(description),
+            due_text: Set(due_text),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+        };
+
+        template.insert(db).await
+    }
+
+    /// All templates, by name.
+    #[tracing::instrument(skip(db))]
+    pub async fn find_all<C: ConnectionTrait>(db: &C) -> Result<Vec<memo_templates::Model>, DbErr> {
+        MemoTemplates::find()
+            .order_by_asc(memo_templates::Column::Name)
+            .all(db)
+            .await
+    }
+
+    #[tracing::instrument(skip(db))]
+    pub async fn find_by_id<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+    ) -> Result<Option<memo_templates::Model>, DbErr> {
+        MemoTemplates::find_by_id(id).one(db).await
+    }
+
+    /// Replaces every field of a template. Returns `None` when it does not exist.
+    #[tracing::instrument(skip(db, title, description))]
+    pub async fn update<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        name: String,
+        title: String,
+        description: Option<String>,
+        due_text: Option<String>,
+    ) -> Result<Option<memo_templates::Model>, DbErr> {
+        let Some(template) = MemoTemplates::find_by_id(id).one(db).await? else {
+            return Ok(None);
+        };
+
+        let mut template: memo_templates::ActiveModel = template.into();
+        template.name = Set(name);
+        template.title = Set(title);
+        template.description = Set(description);
+        template.due_text = Set(due_text);
+        template.updated_at = Set(Utc::now().into());
+
+        template.update(db).await.map(Some)
+    }
+
+    #[tracing::instrument(skip(db))]
+    pub async fn delete<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<bool, DbErr> {
+        let result = MemoTemplates::delete_by_id(id).exec(db).await?;
+        Ok(result.rows_affected > 0)
+    }
+}
@@ -1,3 +1,30 @@
+pub mod api_key_usage_repository;
+mod db_span;
+pub mod memo_attachment_repository;
+pub mod memo_dependency_repository;
+pub mod memo_import_repository;
+pub mod memo_list_view_repository;
 pub mod memo_repository;
+pub mod memo_snooze_repository;
+pub mod memo_template_repository;
+pub mod notification_repository;
+pub mod operation_repository;
+pub mod outbox_repository;
+pub mod push_subscription_repository;
+pub mod undo_token_repository;
+pub mod webhook_integration_repository;
 
-pub use memo_repository::MemoRepository;
+pub use api_key_usage_repository::ApiKeyUsageRepository;
+pub use memo_attachment_repository::{AttachmentMeta, MemoAttachmentRepository};
+pub use memo_dependency_repository::MemoDependencyRepository;
+pub use memo_import_repository::MemoImportRepository;
+pub use memo_list_view_repository::MemoListViewRepository;
+pub use memo_repository::{MemoFilter, MemoFlag, MemoPage, MemoRepository, MemoStats, TotalCount};
+pub use memo_snooze_repository::MemoSnoozeRepository;
+pub use memo_template_repository::MemoTemplateRepository;
+pub use notification_repository::{NewNotification, NotificationRepository};
+pub use operation_repository::OperationRepository;
+pub use outbox_repository::OutboxRepository;
+pub use push_subscription_repository::PushSubscriptionRepository;
+pub use undo_token_repository::UndoTokenRepository;
+pub use webhook_integration_repository::WebhookIntegrationRepository;
@@ -0,0 +1,99 @@
+use crate::entities::{notifications, prelude::*};
+use chrono::Utc;
+use sea_orm::{sea_query::Expr, *};
+use uuid::Uuid;
+
+/// A notification to add to the inbox.
+#[derive(Debug, Clone)]
+pub struct NewNotification {
+    pub kind: &'static str,
+    pub title: String,
+    pub body: Option<String>,
+    pub memo_id: Option<Uuid>,
+    /// Identifies the event; a second notification with the same key is
+    /// dropped, so producers running on several replicas cannot duplicate it.
+    pub dedupe_key: Option<String>,
+}
+
+pub struct NotificationRepository;
+
+impl NotificationRepository {
+    /// Stores `notification` unless one with the same dedupe key exists.
+    /// Returns whether a row was inserted.
+    #[tracing::instrument(skip(db, notification), fields(kind = notification.kind))]
+    pub async fn create_if_absent<C: ConnectionTrait>(
+        db: &C,
+        notification: NewNotification,
+    ) -> Result<bool, DbErr> {
+        let model = notifications::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            kind: Set(notification.kind.to_string()),
+            title: Set(notification.title),
+            body: Set(notification.body),
+            memo_id: Set(notification.memo_id),
+            dedupe_key: Set(notification.dedupe_key),
+            read_at: Set(None),
+            created_at: Set(Utc::now().into()),
+        };
+
+        let inserted = Notifications::insert(model)
+            .on_conflict(
+                sea_query::OnConflict::column(notifications::Column::DedupeKey)
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Ok(inserted > 0)
+    }
+
+    /// Newest first, optionally only unread ones, with the total matching.
+    #[tracing::instrument(skip(db))]
+    pub async fn find_page<C: ConnectionTrait>(
+        db: &C,
+        unread_only: bool,
+        limit: u64,
+        offset: u64,
+    ) -> Result<(Vec<notifications::Model>, u64), DbErr> {
+        let mut query = Notifications::find();
+        if unread_only {
+            query = query.filter(notifications::Column::ReadAt.is_null());
+        }
+        let query = query
+            .order_by_desc(notifications::Column::CreatedAt)
+            .order_by_desc(notifications::Column::Id);
+
+        let total = query.clone().count(db).await?;
+        let notifications = query.limit(limit).offset(offset).all(db).await?;
+
+        Ok((notifications, total))
+    }
+
+    #[tracing::instrument(skip(db))]
+    pub async fn count_unread<C: ConnectionTrait>(db: &C) -> Result<u64, DbErr> {
+        Notifications::find()
+            .filter(notifications::Column::ReadAt.is_null())
+            .count(db)
+            .await
+    }
+
+    /// Marks a notification read, keeping the first read time if it already
+    /// was. Returns `None` when it does not exist.
+    #[tracing::instrument(skip(db), fields(notification_id = %id))]
+    pub async fn mark_read<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+    ) -> Result<Option<notifications::Model>, DbErr> {
+        let updated = Notifications::update_many()
+            .col_expr(
+                notifications::Column::ReadAt,
+                Expr::col(notifications::Column::ReadAt).if_null(Utc::now()),
+            )
+            .filter(notifications::Column::Id.eq(id))
+            .exec_with_returning(db)
+            .await?;
+
+        Ok(updated.into_iter().next())
+    }
+}
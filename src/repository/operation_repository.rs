@@ -0,0 +1,184 @@
+use crate::entities::{operations, prelude::*};
+use chrono::{DateTime, Utc};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone,
+    sea_query::{Expr, LockBehavior, LockType},
+    *,
+};
+use uuid::Uuid;
+
+/// Operations move from `pending` to `running` when a worker claims them, and
+/// end as `succeeded` or `failed`. A retryable failure sends one back to
+/// `pending` until its `run_after`.
+pub struct OperationRepository;
+
+impl OperationRepository {
+    #[tracing::instrument(skip(db, input))]
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
+        kind: &str,
+        input: serde_json::Value,
+        total: Option<i64>,
+    ) -> Result<operations::Model, DbErr> {
+        let operation = operations::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            kind: Set(kind.to_string()),
+            status: Set("pending".to_string()),
+            input: Set(input),
+            processed: Set(0),
+            total: Set(total),
+            created_at: Set(Utc::now().into()),
+            ..Default::default()
+        };
+
+        operation.insert(db).await
+    }
+
+    #[tracing::instrument(skip(db), fields(operation_id = %id))]
+    pub async fn find_by_id<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+    ) -> Result<Option<operations::Model>, DbErr> {
+        Operations::find_by_id(id).one(db).await
+    }
+
+    /// Marks the oldest pending operation that is due as running, counts the
+    /// attempt and returns it. Rows another worker is claiming at the same
+    /// time are skipped, so every attempt runs once.
+    #[tracing::instrument(skip(db))]
+    pub async fn claim_next<C: ConnectionTrait>(
+        db: &C,
+    ) -> Result<Option<operations::Model>, DbErr> {
+        let now = DateTimeWithTimeZone::from(Utc::now());
+        let next = Operations::find()
+            .select_only()
+            .column(operations::Column::Id)
+            .filter(operations::Column::Status.eq("pending"))
+            .filter(
+                Condition::any()
+                    .add(operations::Column::RunAfter.is_null())
+                    .add(operations::Column::RunAfter.lte(now)),
+            )
+            .order_by_asc(operations::Column::CreatedAt)
+            .limit(1)
+            .lock_with_behavior(LockType::Update, LockBehavior::SkipLocked)
+            .into_query();
+
+        let claimed = Operations::update_many()
+            .col_expr(operations::Column::Status, Expr::value("running"))
+            .col_expr(operations::Column::StartedAt, Expr::value(now))
+            .col_expr(
+                operations::Column::Attempts,
+                Expr::col(operations::Column::Attempts).add(1),
+            )
+            .filter(operations::Column::Id.in_subquery(next))
+            .exec_with_returning(db)
+            .await?;
+
+        Ok(claimed.into_iter().next())
+    }
+
+    /// Records how far a running operation got and what it is working on.
+    #[tracing::instrument(skip(db), fields(operation_id = %id))]
+    pub async fn set_progress<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        processed: i64,
+        total: Option<i64>,
+        current_item: Option<&str>,
+    ) -> Result<(), DbErr> {
+        Operations::update_many()
+            .col_expr(operations::Column::Processed, Expr::value(processed))
+            .col_expr(operations::Column::Total, Expr::value(total))
+            .col_expr(
+                operations::Column::CurrentItem,
+                Expr::value(current_item.map(str::to_string)),
+            )
+            .filter(operations::Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(db, result), fields(operation_id = %id))]
+    pub async fn succeed<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        result: serde_json::Value,
+    ) -> Result<(), DbErr> {
+        Self::finish(db, id, "succeeded", operations::Column::Result, result).await
+    }
+
+    #[tracing::instrument(skip(db, error), fields(operation_id = %id))]
+    pub async fn fail<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        error: serde_json::Value,
+    ) -> Result<(), DbErr> {
+        Self::finish(db, id, "failed", operations::Column::Error, error).await
+    }
+
+    /// Puts a failed attempt back to `pending` until `run_after`, keeping its
+    /// error for status requests in the meantime.
+    #[tracing::instrument(skip(db, error), fields(operation_id = %id))]
+    pub async fn retry_later<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        run_after: DateTime<Utc>,
+        error: serde_json::Value,
+    ) -> Result<(), DbErr> {
+        Operations::update_many()
+            .col_expr(operations::Column::Status, Expr::value("pending"))
+            .col_expr(operations::Column::Error, Expr::value(error))
+            .col_expr(
+                operations::Column::RunAfter,
+                Expr::value(DateTimeWithTimeZone::from(run_after)),
+            )
+            .col_expr(
+                operations::Column::StartedAt,
+                Expr::value(Option::<DateTimeWithTimeZone>::None),
+            )
+            .filter(operations::Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes operations that finished before `cutoff`. Returns the number
+    /// removed.
+    #[tracing::instrument(skip(db))]
+    pub async fn delete_finished_before<C: ConnectionTrait>(
+        db: &C,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, DbErr> {
+        let result = Operations::delete_many()
+            .filter(operations::Column::FinishedAt.lt(cutoff))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+
+    async fn finish<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        status: &str,
+        column: operations::Column,
+        outcome: serde_json::Value,
+    ) -> Result<(), DbErr> {
+        Operations::update_many()
+            .col_expr(operations::Column::Status, Expr::value(status))
+            .col_expr(column, Expr::value(outcome))
+            .col_expr(
+                operations::Column::CurrentItem,
+                Expr::value(Option::<String>::None),
+            )
+            .col_expr(
+                operations::Column::FinishedAt,
+                Expr::value(DateTimeWithTimeZone::from(Utc::now())),
+            )
+            .filter(operations::Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,123 @@
+use crate::entities::{outbox_events, prelude::*};
+use chrono::{DateTime, Utc};
+use sea_orm::{prelude::DateTimeWithTimeZone, sea_query::Expr, *};
+use uuid::Uuid;
+
+/// Arbitrary key for the advisory lock that lets only one relay publish at a
+/// time, so events leave in outbox order even with several replicas.
+const RELAY_LOCK_KEY: i64 = 0x6d656d6f_6f757462;
+
+pub struct OutboxRepository;
+
+impl OutboxRepository {
+    /// Adds an event to the outbox. Call it with the transaction that makes
+    /// the change the event describes.
+    #[tracing::instrument(skip(db, payload))]
+    pub async fn append<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        event_type: &str,
+        aggregate_id: Uuid,
+        payload: serde_json::Value,
+    ) -> Result<(), DbErr> {
+        let event = outbox_events::ActiveModel {
+            id: Set(id),
+            event_type: Set(event_type.to_string()),
+            aggregate_id: Set(aggregate_id),
+            payload: Set(payload),
+            created_at: Set(Utc::now().into()),
+            attempts: Set(0),
+            ..Default::default()
+        };
+
+        OutboxEvents::insert(event)
+            .exec_without_returning(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Takes the relay lock for the rest of the transaction. Returns `false`
+    /// when another relay holds it.
+    pub async fn try_lock_relay(txn: &DatabaseTransaction) -> Result<bool, DbErr> {
+        let row = txn
+            .query_one(Statement::from_sql_and_values(
+                DatabaseBackend::Postgres,
+                "SELECT pg_try_advisory_xact_lock($1) AS locked",
+                [RELAY_LOCK_KEY.into()],
+            ))
+            .await?
+            .ok_or_else(|| DbErr::Custom("pg_try_advisory_xact_lock returned no row".into()))?;
+        row.try_get("", "locked")
+    }
+
+    /// Oldest unpublished events, in the order they were written.
+    #[tracing::instrument(skip(db))]
+    pub async fn find_pending<C: ConnectionTrait>(
+        db: &C,
+        limit: u64,
+    ) -> Result<Vec<outbox_events::Model>, DbErr> {
+        OutboxEvents::find()
+            .filter(outbox_events::Column::PublishedAt.is_null())
+            .order_by_asc(outbox_events::Column::Seq)
+            .limit(limit)
+            .all(db)
+            .await
+    }
+
+    #[tracing::instrument(skip(db))]
+    pub async fn count_pending<C: ConnectionTrait>(db: &C) -> Result<u64, DbErr> {
+        OutboxEvents::find()
+            .filter(outbox_events::Column::PublishedAt.is_null())
+            .count(db)
+            .await
+    }
+
+    #[tracing::instrument(skip(db, ids), fields(count = ids.len()))]
+    pub async fn mark_published<C: ConnectionTrait>(db: &C, ids: &[Uuid]) -> Result<(), DbErr> {
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        OutboxEvents::update_many()
+            .col_expr(
+                outbox_events::Column::PublishedAt,
+                Expr::value(DateTimeWithTimeZone::from(Utc::now())),
+            )
+            .filter(outbox_events::Column::Id.is_in(ids.iter().copied()))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Counts a failed publish attempt; the event stays pending.
+    #[tracing::instrument(skip(db, error))]
+    pub async fn record_failure<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+        error: &str,
+    ) -> Result<(), DbErr> {
+        OutboxEvents::update_many()
+            .col_expr(
+                outbox_events::Column::Attempts,
+                Expr::col(outbox_events::Column::Attempts).add(1),
+            )
+            .col_expr(outbox_events::Column::LastError, Expr::value(error))
+            .filter(outbox_events::Column::Id.eq(id))
+            .exec(db)
+            .await?;
+        Ok(())
+    }
+
+    /// Deletes events published before `cutoff`. Returns the number removed.
+    #[tracing::instrument(skip(db))]
+    pub async fn delete_published_before<C: ConnectionTrait>(
+        db: &C,
+        cutoff: DateTime<Utc>,
+    ) -> Result<u64, DbErr> {
+        let result = OutboxEvents::delete_many()
+            .filter(outbox_events::Column::PublishedAt.lt(cutoff))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected)
+    }
+}
@@ -0,0 +1,66 @@
+use crate::entities::{prelude::*, push_subscriptions};
+use chrono::Utc;
+use sea_orm::*;
+use uuid::Uuid;
+
+pub struct PushSubscriptionRepository;
+
+impl PushSubscriptionRepository {
+    /// Stores a browser subscription. Re-subscribing the same endpoint replaces
+    /// its keys, since browsers rotate them.
+    #[tracing::instrument(skip(db, p256dh, auth))]
+    pub async fn upsert<C: ConnectionTrait>(
+        db: &C,
+        endpoint: String,
+        p256dh: String,
+        auth: String,
+    ) -> Result<(), DbErr> {
+        tracing::debug!("Upserting push subscription");
+
+        let subscription = push_subscriptions::ActiveModel {
+            id: Set(Uuid::new_v4()),
+            endpoint: Set(endpoint),
+            p256dh: Set(p256dh),
+            auth: Set(auth),
+            created_at: Set(Utc::now().into()),
+        };
+
+        PushSubscriptions::insert(subscription)
+            .on_conflict(
+                sea_query::OnConflict::column(push_subscriptions::Column::Endpoint)
+                    .update_columns([
+                        push_subscriptions::Column::P256dh,
+                        push_subscriptions::Column::Auth,
+                    ])
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+
+        Ok(())
+    }
+
+    #[tracing::instrument(skip(db))]
+    pub async fn find_all<C: ConnectionTrait>(
+        db: &C,
+    ) -> Result<Vec<push_subscriptions::Model>, DbErr> {
+        PushSubscriptions::find()
+            .order_by_asc(push_subscriptions::Column::CreatedAt)
+            .all(db)
+            .await
+    }
+
+    /// Returns whether a subscription was removed.
+    #[tracing::instrument(skip(db))]
+    pub async fn delete_by_endpoint<C: ConnectionTrait>(
+        db: &C,
+        endpoint: &str,
+    ) -> Result<bool, DbErr> {
+        let result = PushSubscriptions::delete_many()
+            .filter(push_subscriptions::Column::Endpoint.eq(endpoint))
+            .exec(db)
+            .await?;
+
+        Ok(result.rows_affected > 0)
+    }
+}
@@ -0,0 +1,69 @@
+use crate::entities::{prelude::*, undo_tokens};
+use chrono::{DateTime, Utc};
+use sea_orm::*;
+use uuid::Uuid;
+
+pub struct UndoTokenRepository;
+
+impl UndoTokenRepository {
+    #[tracing::instrument(skip(db), fields(%expires_at))]
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
+        expires_at: DateTime<Utc>,
+    ) -> Result<undo_tokens::Model, DbErr> {
+        let token = undo_tokens::ActiveModel {
+            token: Set(Uuid::new_v4()),
+            created_at: Set(Utc::now().into()),
+            expires_at: Set(expires_at.into()),
+        };
+
+        token.insert(db).await
+    }
+
+    /// Removes `token` if it has not expired by `now`, so it can be redeemed
+    /// only once. Returns `false` for unknown and expired tokens.
+    #[tracing::instrument(skip(db), fields(%token))]
+    pub async fn redeem<C: ConnectionTrait>(
+        db: &C,
+        token: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<bool, DbErr> {
+        let result = UndoTokens::delete_many()
+            .filter(undo_tokens::Column::Token.eq(token))
+            .filter(undo_tokens::Column::ExpiresAt.gt(now))
+            .exec(db)
+            .await?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// Drops up to `limit` tokens that expired before `now`. The memos they
+    /// hid go with them through the foreign key cascade.
+    #[tracing::instrument(skip(db), fields(%now, limit))]
+    pub async fn delete_expired<C: ConnectionTrait>(
+        db: &C,
+        now: DateTime<Utc>,
+        limit: u64,
+    ) -> Result<u64, DbErr> {
+        let batch = UndoTokens::find()
+            .select_only()
+            .column(undo_tokens::Column::Token)
+            .filter(undo_tokens::Column::ExpiresAt.lte(now))
+            .order_by_asc(undo_tokens::Column::ExpiresAt)
+            .limit(limit)
+            .into_query();
+
+        let result = UndoTokens::delete_many()
+            .filter(undo_tokens::Column::Token.in_subquery(batch))
+            .exec(db)
+            .await?;
+
+        if result.rows_affected > 0 {
+            tracing::info!(
+                finalized = result.rows_affected,
+                "Expired undo tokens dropped"
+            );
+        }
+
+        Ok(result.rows_affected)
+    }
+}
@@ -0,0 +1,138 @@
+use crate::crypto::{self, FieldCryptoError};
+use crate::entities::{prelude::*, webhook_integrations};
+use chrono::Utc;
+use sea_orm::{sea_query::Expr, *};
+use uuid::Uuid;
+
+pub struct WebhookIntegrationRepository;
+
+impl WebhookIntegrationRepository {
+    /// Stores an integration. The URL is sealed with the field cipher when
+    /// encryption is configured; the returned model holds the plaintext.
+    #[tracing::instrument(skip(db, url))]
+    pub async fn create<C: ConnectionTrait>(
+        db: &C,
+        provider: &str,
+        url: String,
+        events: Vec<String>,
+    ) -> Result<webhook_integrations::Model, DbErr> {
+        tracing::debug!("Creating webhook integration");
+
+        let id = Uuid::new_v4();
+        let sealed = crypto::encrypt_field(&url, id.as_bytes()).map_err(|e| crypto_error(id, e))?;
+        let integration = webhook_integrations::ActiveModel {
+            id: Set(id),
+            provider: Set(provider.to_string()),
+            url: Set(sealed),
+            events: Set(serde_json::json!(events)),
+            created_at: Set(Utc::now().into()),
+        };
+
+        let mut model = integration.insert(db).await?;
+        model.url = url;
+        Ok(model)
+    }
+
+    #[tracing::instrument(skip(db))]
+    pub async fn find_all<C: ConnectionTrait>(
+        db: &C,
+    ) -> Result<Vec<webhook_integrations::Model>, DbErr> {
+        WebhookIntegrations::find()
+            .order_by_asc(webhook_integrations::Column::CreatedAt)
+            .order_by_asc(webhook_integrations::Column::Id)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(open)
+            .collect()
+    }
+
+    /// Integrations subscribed to `event`.
+    #[tracing::instrument(skip(db))]
+    pub async fn find_for_event<C: ConnectionTrait>(
+        db: &C,
+        event: &str,
+    ) -> Result<Vec<webhook_integrations::Model>, DbErr> {
+        WebhookIntegrations::find()
+            .filter(Expr::cust_with_values(
+                "events @> $1::jsonb",
+                [serde_json::json!([event]).to_string()],
+            ))
+            .order_by_asc(webhook_integrations::Column::CreatedAt)
+            .all(db)
+            .await?
+            .into_iter()
+            .map(open)
+            .collect()
+    }
+
+    /// Encrypts URLs stored in plaintext and re-encrypts those under older
+    /// keys with the current one. Returns the number of rows changed.
+    #[tracing::instrument(skip(db))]
+    pub async fn reencrypt_urls<C: ConnectionTrait>(db: &C) -> Result<u64, DbErr> {
+        let cipher = crypto::cipher()
+            .ok_or_else(|| DbErr::Custom("Field encryption is not configured".to_string()))?;
+
+        let rows: Vec<(Uuid, String)> = WebhookIntegrations::find()
+            .select_only()
+            .column(webhook_integrations::Column::Id)
+            .column(webhook_integrations::Column::Url)
+            .into_tuple()
+            .all(db)
+            .await?;
+
+        let mut updated = 0;
+        for (id, stored) in rows {
+            if cipher.is_current(&stored) {
+                continue;
+            }
+            let plaintext = cipher
+                .decrypt(&stored, id.as_bytes())
+                .map_err(|e| crypto_error(id, e))?;
+            let sealed = cipher
+                .encrypt(&plaintext, id.as_bytes())
+                .map_err(|e| crypto_error(id, e))?;
+            let result = WebhookIntegrations::update_many()
+                .col_expr(webhook_integrations::Column::Url, Expr::value(sealed))
+                .filter(webhook_integrations::Column::Id.eq(id))
+                .filter(webhook_integrations::Column::Url.eq(stored))
+                .exec(db)
+                .await?;
+            updated += result.rows_affected;
+        }
+
+        tracing::info!(updated, "Re-encrypted webhook URLs");
+        Ok(updated)
+    }
+
+    #[tracing::instrument(skip(db))]
+    pub async fn find_by_id<C: ConnectionTrait>(
+        db: &C,
+        id: Uuid,
+    ) -> Result<Option<webhook_integrations::Model>, DbErr> {
+        WebhookIntegrations::find_by_id(id)
+            .one(db)
+            .await?
+            .map(open)
+            .transpose()
+    }
+
+    #[tracing::instrument(skip(db))]
+    pub async fn delete<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<bool, DbErr> {
+        let result = WebhookIntegrations::delete_by_id(id).exec(db).await?;
+        Ok(result.rows_affected > 0)
+    }
+}
+
+/// Decrypts the URL of an integration read from the database.
+fn open(
+    mut integration: webhook_integrations::Model,
+) -> Result<webhook_integrations::Model, DbErr> {
+    integration.url = crypto::decrypt_field(&integration.url, integration.id.as_bytes())
+        .map_err(|e| crypto_error(integration.id, e))?;
+    Ok(integration)
+}
+
+fn crypto_error(id: Uuid, error: FieldCryptoError) -> DbErr {
+    DbErr::Custom(format!("URL of webhook integration {}: {}", id, error))
+}
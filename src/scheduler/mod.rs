@@ -0,0 +1,284 @@
+//! In-process periodic task runner.
+//!
+//! Each registered task runs on its own tokio task, one run at a time: the
+//! next occurrence is computed only after the previous run finished, so a slow
+//! run delays the schedule instead of overlapping with itself. Every replica
+//! runs its own scheduler, so tasks must be safe to run concurrently across
+//! instances.
+
+use chrono::{DateTime, Utc};
+use prometheus::{GaugeVec, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+
+pub mod tasks;
+
+pub type TaskFuture<'a> = Pin<Box<dyn Future<Output = anyhow::Result<()>> + Send + 'a>>;
+
+/// A unit of periodic work.
+pub trait Task: Send + Sync {
+    /// Stable name, used in logs and as the `task` metric label.
+    fn name(&self) -> &'static str;
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a>;
+}
+
+/// Timing of the current run.
+#[derive(Debug, Clone, Copy)]
+pub struct TaskContext {
+    /// Occurrence this run was scheduled for
+    pub scheduled_at: DateTime<Utc>,
+    /// Previous occurrence, or the scheduler start time for the first run.
+    /// `[previous, scheduled_at)` is the period this run is responsible for.
+    pub previous: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Schedule {
+    Every(Duration),
+    /// Six- or seven-field cron expression, evaluated in UTC
+    Cron(Box<cron::Schedule>),
+}
+
+impl Schedule {
+    pub fn every(interval: Duration) -> Self {
+        Schedule::Every(interval)
+    }
+
+    pub fn cron(expression: &str) -> Result<Self, cron::error::Error> {
+        Ok(Schedule::Cron(Box::new(expression.parse()?)))
+    }
+
+    /// First occurrence strictly after `after`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Every(interval) => Some(after + chrono::Duration::from_std(*interval).ok()?),
+            Schedule::Cron(schedule) => schedule.after(&after).next(),
+        }
+    }
+}
+
+#[derive(Clone)]
+struct SchedulerMetrics {
+    runs: IntCounterVec,
+    duration: HistogramVec,
+    last_success: GaugeVec,
+}
+
+impl SchedulerMetrics {
+    fn register(registry: &Registry) -> prometheus::Result<Self> {
+        let runs = IntCounterVec::new(
+            Opts::new("scheduler_task_runs_total", "Scheduled task runs"),
+            &["task", "outcome"],
+        )?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "scheduler_task_duration_seconds",
+                "Duration of scheduled task runs",
+            ),
+            &["task"],
+        )?;
+        let last_success = GaugeVec::new(
+            Opts::new(
+                "scheduler_task_last_success_timestamp_seconds",
+                "Unix time of the last successful run",
+            ),
+            &["task"],
+        )?;
+
+        registry.register(Box::new(runs.clone()))?;
+        registry.register(Box::new(duration.clone()))?;
+        registry.register(Box::new(last_success.clone()))?;
+
+        Ok(Self {
+            runs,
+            duration,
+            last_success,
+        })
+    }
+}
+
+#[derive(Default)]
+pub struct Scheduler {
+    tasks: Vec<(Schedule, Arc<dyn Task>)>,
+    metrics: Option<SchedulerMetrics>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `scheduler_task_runs_total{task, outcome}`,
+    /// `scheduler_task_duration_seconds{task}` and
+    /// `scheduler_task_last_success_timestamp_seconds{task}`.
+    pub fn with_metrics(mut self, registry: &Registry) -> prometheus::Result<Self> {
+        self.metrics = Some(SchedulerMetrics::register(registry)?);
+        Ok(self)
+    }
+
+    pub fn register(mut self, schedule: Schedule, task: impl Task + 'static) -> Self {
+        self.tasks.push((schedule, Arc::new(task)));
+        self
+    }
+
+    pub fn task_names(&self) -> Vec<&'static str> {
+        self.tasks.iter().map(|(_, task)| task.name()).collect()
+    }
+
+    /// Spawns one loop per task; they run until the handle is shut down.
+    pub fn start(self) -> SchedulerHandle {
+        let handles = self
+            .tasks
+            .into_iter()
+            .map(|(schedule, task)| {
+                tracing::info!(task = task.name(), ?schedule, "Scheduling task");
+                tokio::spawn(run_loop(schedule, task, self.metrics.clone()))
+            })
+            .collect();
+
+        SchedulerHandle { handles }
+    }
+}
+
+async fn run_loop(schedule: Schedule, task: Arc<dyn Task>, metrics: Option<SchedulerMetrics>) {
+    let mut previous = Utc::now();
+
+    loop {
+        let Some(next) = schedule.next_after(Utc::now().max(previous)) else {
+            tracing::warn!(task = task.name(), "Schedule has no further occurrences");
+            return;
+        };
+        if let Ok(wait) = (next - Utc::now()).to_std() {
+            tokio::time::sleep(wait).await;
+        }
+
+        let ctx = TaskContext {
+            scheduled_at: next,
+            previous,
+        };
+        run_once(task.as_ref(), &ctx, metrics.as_ref()).await;
+        previous = next;
+    }
+}
+
+/// Runs `task` once, logging and recording the outcome. Errors never stop the loop.
+async fn run_once(task: &dyn Task, ctx: &TaskContext, metrics: Option<&SchedulerMetrics>) -> bool {
+    let name = task.name();
+    let started = Instant::now();
+    let result = task.run(ctx).await;
+    let elapsed = started.elapsed();
+
+    let outcome = match &result {
+        Ok(()) => {
+            tracing::info!(
+                task = name,
+                elapsed_ms = elapsed.as_millis() as u64,
+                "Task run finished"
+            );
+            "success"
+        }
+        Err(e) => {
+            tracing::error!(task = name, error = %e, "Task run failed");
+            "failure"
+        }
+    };
+
+    if let Some(metrics) = metrics {
+        metrics.runs.with_label_values(&[name, outcome]).inc();
+        metrics
+            .duration
+            .with_label_values(&[name])
+            .observe(elapsed.as_secs_f64());
+        if result.is_ok() {
+            metrics
+                .last_success
+                .with_label_values(&[name])
+                .set(Utc::now().timestamp() as f64);
+        }
+    }
+
+    result.is_ok()
+}
+
+/// Running scheduler; dropping it leaves the tasks running.
+pub struct SchedulerHandle {
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl SchedulerHandle {
+    /// Stops all task loops. A run in progress is cancelled at its next await point.
+    pub fn shutdown(self) {
+        for handle in self.handles {
+            handle.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cron_schedule_finds_next_occurrence() {
+        let schedule = Schedule::cron("0 */5 * * * *").unwrap();
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 10, 3, 30).unwrap();
+
+        assert_eq!(
+            schedule.next_after(now),
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 10, 5, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_interval_schedule_adds_interval() {
+        let schedule = Schedule::every(Duration::from_secs(90));
+        let now = Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap();
+
+        assert_eq!(
+            schedule.next_after(now),
+            Some(Utc.with_ymd_and_hms(2025, 1, 1, 10, 1, 30).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_invalid_cron_is_rejected() {
+        assert!(Schedule::cron("every five minutes").is_err());
+    }
+
+    struct Failing;
+
+    impl Task for Failing {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn run<'a>(&'a self, _ctx: &'a TaskContext) -> TaskFuture<'a> {
+            Box::pin(async { anyhow::bail!("boom") })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_runs_are_counted() {
+        let registry = Registry::new();
+        let metrics = SchedulerMetrics::register(&registry).unwrap();
+        let now = Utc::now();
+        let ctx = TaskContext {
+            scheduled_at: now,
+            previous: now,
+        };
+
+        assert!(!run_once(&Failing, &ctx, Some(&metrics)).await);
+        assert_eq!(
+            metrics
+                .runs
+                .with_label_values(&["failing", "failure"])
+                .get(),
+            1
+        );
+    }
+}
@@ -0,0 +1,586 @@
+//! Periodic tasks registered by `main.rs`.
+
+use chrono::Duration;
+use lettre::message::Mailbox;
+use prometheus::{IntCounter, IntCounterVec, IntGauge, Opts, Registry};
+use sea_orm::TransactionTrait;
+use std::sync::Arc;
+use web_push::SubscriptionInfo;
+
+use super::{Task, TaskContext, TaskFuture};
+use crate::config::settings::{EventsConfig, RetentionAction, RetentionConfig};
+use crate::db::GuardedConnection;
+use crate::events::EventPublisher;
+use crate::i18n::{Locale, timezone};
+use crate::integrations::{WebhookEvent, WebhookMessage, WebhookQueue};
+use crate::notifications::email::{EmailQueue, MemoReminder, build_message};
+use crate::notifications::push::{PushPayload, WebPushSender, is_gone};
+use crate::repository::{
+    MemoListViewRepository, MemoRepository, NewNotification, NotificationRepository,
+    OperationRepository, OutboxRepository, PushSubscriptionRepository, UndoTokenRepository,
+};
+
+/// Where reminder emails go.
+pub struct ReminderEmail {
+    pub queue: EmailQueue,
+    pub from: Mailbox,
+    pub to: Mailbox,
+    /// Base URL for the link back to the app
+    pub public_url: String,
+}
+
+/// Reminds about incomplete memos coming due within the configured lead time.
+///
+/// Each run covers the memos whose `date_to - lead` falls in
+/// `[previous, scheduled_at)`, so consecutive runs never remind twice about the
+/// same memo. Every reminder is logged as a `memo_due_soon` event, added to the
+/// notification inbox and, when configured, emailed.
+pub struct DueReminders {
+    db: GuardedConnection,
+    lead: Duration,
+    email: Option<ReminderEmail>,
+}
+
+impl DueReminders {
+    pub fn new(db: GuardedConnection, lead: Duration) -> Self {
+        Self {
+            db,
+            lead,
+            email: None,
+        }
+    }
+
+    pub fn with_email(mut self, email: ReminderEmail) -> Self {
+        self.email = Some(email);
+        self
+    }
+}
+
+impl Task for DueReminders {
+    fn name(&self) -> &'static str {
+        "due_reminders"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let memos = MemoRepository::find_by_date_range(
+                &self.db,
+                ctx.previous + self.lead,
+                ctx.scheduled_at + self.lead,
+            )
+            .await?;
+
+            let mut reminded = 0;
+            for memo in memos.iter().filter(|memo| !memo.completed) {
+                tracing::info!(
+                    event = "memo_due_soon",
+                    memo_id = %memo.id,
+                    title = %memo.title,
+                    date_to = %memo.date_to,
+                    "Memo is coming due"
+                );
+                reminded += 1;
+
+                // There is no viewer here; use the timezone the memo was created in
+                let tz = timezone::parse(&memo.timezone).unwrap_or(chrono_tz::Tz::UTC);
+                let due = memo
+                    .date_to
+                    .with_timezone(&tz)
+                    .format("%Y-%m-%d %H:%M %Z")
+                    .to_string();
+                let notification = NewNotification {
+                    kind: "memo_due_soon",
+                    title: format!("{} is due soon", memo.title),
+                    body: Some(format!("Due {due}")),
+                    memo_id: Some(memo.id),
+                    dedupe_key: Some(format!(
+                        "memo_due_soon:{}:{}",
+                        memo.id,
+                        memo.date_to.timestamp()
+                    )),
+                };
+                if let Err(e) =
+                    NotificationRepository::create_if_absent(&self.db, notification).await
+                {
+                    tracing::warn!(memo_id = %memo.id, error = %e, "Failed to store inbox notification");
+                }
+
+                let Some(email) = &self.email else {
+                    continue;
+                };
+                let reminder = MemoReminder {
+                    title: memo.title.clone(),
+                    description: memo.description.clone(),
+                    due,
+                    url: format!("{}/", email.public_url),
+                    locale: Locale::default(),
+                };
+                let queued = build_message(&email.from, &email.to, &reminder)
+                    .and_then(|message| email.queue.enqueue(message));
+                if let Err(e) = queued {
+                    tracing::warn!(memo_id = %memo.id, error = %e, "Failed to queue reminder email");
+                }
+            }
+
+            tracing::debug!(reminded, "Due-date reminders processed");
+            Ok(())
+        })
+    }
+}
+
+/// Sends a browser push notification when an incomplete memo becomes due.
+///
+/// Each run covers the memos whose `date_to` falls in `[previous, scheduled_at)`.
+/// Subscriptions the push service reports as gone are deleted.
+pub struct DuePushNotifications {
+    db: GuardedConnection,
+    sender: WebPushSender,
+    /// Base URL for the link back to the app
+    public_url: String,
+}
+
+impl DuePushNotifications {
+    pub fn new(db: GuardedConnection, sender: WebPushSender, public_url: String) -> Self {
+        Self {
+            db,
+            sender,
+            public_url,
+        }
+    }
+}
+
+impl Task for DuePushNotifications {
+    fn name(&self) -> &'static str {
+        "due_push_notifications"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let memos =
+                MemoRepository::find_by_date_range(&self.db, ctx.previous, ctx.scheduled_at)
+                    .await?;
+            let due: Vec<_> = memos.into_iter().filter(|memo| !memo.completed).collect();
+            if due.is_empty() {
+                return Ok(());
+            }
+
+            let subscriptions = PushSubscriptionRepository::find_all(&self.db).await?;
+            let locale = Locale::default();
+            let (mut sent, mut removed) = (0, 0);
+
+            for subscription in &subscriptions {
+                let info = SubscriptionInfo::new(
+                    subscription.endpoint.as_str(),
+                    subscription.p256dh.as_str(),
+                    subscription.auth.as_str(),
+                );
+
+                for memo in &due {
+                    let payload = PushPayload {
+                        title: format!("{}: {}", locale.t("push-due-title"), memo.title),
+                        body: memo.description.clone().unwrap_or_default(),
+                        url: format!("{}/", self.public_url),
+                        tag: format!("memo-{}", memo.id),
+                    };
+
+                    match self.sender.send(&info, &payload).await {
+                        Ok(()) => sent += 1,
+                        Err(e) if is_gone(&e) => {
+                            PushSubscriptionRepository::delete_by_endpoint(
+                                &self.db,
+                                &subscription.endpoint,
+                            )
+                            .await?;
+                            removed += 1;
+                            tracing::info!(
+                                subscription_id = %subscription.id,
+                                "Removed expired push subscription"
+                            );
+                            break;
+                        }
+                        Err(e) => {
+                            tracing::warn!(
+                                subscription_id = %subscription.id,
+                                memo_id = %memo.id,
+                                error = %e,
+                                "Failed to send push notification"
+                            );
+                        }
+                    }
+                }
+            }
+
+            tracing::debug!(
+                memos = due.len(),
+                sent,
+                removed,
+                "Due push notifications processed"
+            );
+            Ok(())
+        })
+    }
+}
+
+/// Posts a `memo_overdue` webhook message when an incomplete memo passes its
+/// due date.
+///
+/// Each run covers the memos whose `date_to` falls in `[previous, scheduled_at)`.
+pub struct OverdueWebhooks {
+    db: GuardedConnection,
+    queue: WebhookQueue,
+}
+
+impl OverdueWebhooks {
+    pub fn new(db: GuardedConnection, queue: WebhookQueue) -> Self {
+        Self { db, queue }
+    }
+}
+
+impl Task for OverdueWebhooks {
+    fn name(&self) -> &'static str {
+        "overdue_webhooks"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let memos =
+                MemoRepository::find_by_date_range(&self.db, ctx.previous, ctx.scheduled_at)
+                    .await?;
+
+            let mut queued = 0;
+            for memo in memos.into_iter().filter(|memo| !memo.completed) {
+                queued += self
+                    .queue
+                    .notify(WebhookMessage {
+                        event: WebhookEvent::MemoOverdue,
+                        memo_id: memo.id,
+                        title: memo.title,
+                        due: memo.date_to.into(),
+                    })
+                    .await;
+            }
+
+            tracing::debug!(queued, "Overdue webhooks processed");
+            Ok(())
+        })
+    }
+}
+
+#[derive(Clone)]
+struct PurgeMetrics {
+    purged: IntCounterVec,
+    pending: IntGauge,
+}
+
+/// Applies the retention policy to completed memos: those last updated more
+/// than `completed_after_days` before the run are archived or deleted, one
+/// batch per statement, until none are left.
+pub struct CompletedMemoPurge {
+    db: GuardedConnection,
+    older_than: Duration,
+    action: RetentionAction,
+    batch_size: u64,
+    metrics: Option<PurgeMetrics>,
+}
+
+impl CompletedMemoPurge {
+    /// `None` when no retention period is configured.
+    pub fn from_config(db: GuardedConnection, config: &RetentionConfig) -> Option<Self> {
+        let days = config.completed_after_days?;
+        Some(Self {
+            db,
+            older_than: Duration::days(i64::from(days)),
+            action: config.action,
+            batch_size: config.batch_size,
+            metrics: None,
+        })
+    }
+
+    /// Records `retention_purged_memos_total{action}` and
+    /// `retention_pending_memos`, the memos the current run still has to purge.
+    pub fn with_metrics(mut self, registry: &Registry) -> prometheus::Result<Self> {
+        let purged = IntCounterVec::new(
+            Opts::new("retention_purged_memos_total", "Memos purged by retention"),
+            &["action"],
+        )?;
+        let pending = IntGauge::new(
+            "retention_pending_memos",
+            "Memos the running retention purge has yet to process",
+        )?;
+        registry.register(Box::new(purged.clone()))?;
+        registry.register(Box::new(pending.clone()))?;
+
+        self.metrics = Some(PurgeMetrics { purged, pending });
+        Ok(self)
+    }
+}
+
+impl Task for CompletedMemoPurge {
+    fn name(&self) -> &'static str {
+        "completed_memo_purge"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let cutoff = ctx.scheduled_at - self.older_than;
+            let include_archived = self.action == RetentionAction::Delete;
+            let mut pending =
+                MemoRepository::count_purgeable(&self.db, cutoff, include_archived).await?;
+            if let Some(metrics) = &self.metrics {
+                metrics.pending.set(pending as i64);
+            }
+
+            let mut purged = 0;
+            while pending > 0 {
+                let batch = match self.action {
+                    RetentionAction::Archive => {
+                        MemoRepository::archive_purgeable_batch(&self.db, cutoff, self.batch_size)
+                            .await?
+                    }
+                    RetentionAction::Delete => {
+                        MemoRepository::delete_purgeable_batch(&self.db, cutoff, self.batch_size)
+                            .await?
+                    }
+                };
+                purged += batch;
+                pending = pending.saturating_sub(batch);
+
+                if let Some(metrics) = &self.metrics {
+                    metrics
+                        .purged
+                        .with_label_values(&[self.action.as_str()])
+                        .inc_by(batch);
+                    metrics.pending.set(pending as i64);
+                }
+                // A short batch means nothing older than the cutoff is left,
+                // even if other replicas got to some of the counted memos first.
+                if batch < self.batch_size {
+                    break;
+                }
+            }
+
+            if let Some(metrics) = &self.metrics {
+                metrics.pending.set(0);
+            }
+            // Purged memos record no events, so drop them from the listed
+            // links of the memos they were linked to.
+            if self.action == RetentionAction::Delete && purged > 0 {
+                MemoListViewRepository::refresh_dangling(&self.db).await?;
+            }
+            tracing::info!(
+                action = self.action.as_str(),
+                %cutoff,
+                purged,
+                "Retention purge finished"
+            );
+            Ok(())
+        })
+    }
+}
+
+/// Removes memos for good once the undo window of their deletion has closed.
+///
+/// Expired undo tokens are dropped in batches; the memos they hid, and
+/// everything attached to those memos, follow through the cascade.
+pub struct DeletedMemoFinalizer {
+    db: GuardedConnection,
+}
+
+impl DeletedMemoFinalizer {
+    const BATCH_SIZE: u64 = 500;
+
+    pub fn new(db: GuardedConnection) -> Self {
+        Self { db }
+    }
+}
+
+impl Task for DeletedMemoFinalizer {
+    fn name(&self) -> &'static str {
+        "deleted_memo_finalizer"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let mut finalized = 0;
+            loop {
+                let batch = UndoTokenRepository::delete_expired(
+                    &self.db,
+                    ctx.scheduled_at,
+                    Self::BATCH_SIZE,
+                )
+                .await?;
+                finalized += batch;
+                if batch < Self::BATCH_SIZE {
+                    break;
+                }
+            }
+
+            if finalized > 0 {
+                tracing::info!(finalized, "Finalized deletions past their undo window");
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Deletes operations that finished more than [`OperationCleanup::KEEP_FINISHED`]
+/// ago; their status URLs answer `404` from then on.
+pub struct OperationCleanup {
+    db: GuardedConnection,
+}
+
+impl OperationCleanup {
+    /// How long finished operations can still be polled.
+    pub const KEEP_FINISHED: Duration = Duration::days(7);
+
+    pub fn new(db: GuardedConnection) -> Self {
+        Self { db }
+    }
+}
+
+impl Task for OperationCleanup {
+    fn name(&self) -> &'static str {
+        "operation_cleanup"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let deleted = OperationRepository::delete_finished_before(
+                &self.db,
+                ctx.scheduled_at - Self::KEEP_FINISHED,
+            )
+            .await?;
+
+            if deleted > 0 {
+                tracing::info!(deleted, "Deleted finished operations");
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Publishes outbox events to the broker in the order they were written.
+///
+/// Each run takes an advisory lock so only one replica relays at a time, then
+/// publishes up to `batch_size` pending events. The first failure stops the
+/// batch and is recorded on the event, which is retried on the next run.
+/// Published events are deleted after [`OutboxRelay::KEEP_PUBLISHED`].
+pub struct OutboxRelay {
+    db: GuardedConnection,
+    publisher: Arc<dyn EventPublisher>,
+    batch_size: u64,
+    metrics: Option<RelayMetrics>,
+}
+
+#[derive(Clone)]
+struct RelayMetrics {
+    published: IntCounter,
+    failed: IntCounter,
+    pending: IntGauge,
+}
+
+impl OutboxRelay {
+    /// How long published events stay in the outbox for inspection.
+    pub const KEEP_PUBLISHED: Duration = Duration::days(7);
+
+    pub fn new(
+        db: GuardedConnection,
+        publisher: Arc<dyn EventPublisher>,
+        config: &EventsConfig,
+    ) -> Self {
+        Self {
+            db,
+            publisher,
+            batch_size: config.relay_batch_size,
+            metrics: None,
+        }
+    }
+
+    /// Records `outbox_published_events_total`, `outbox_publish_failures_total`
+    /// and `outbox_pending_events`.
+    pub fn with_metrics(mut self, registry: &Registry) -> prometheus::Result<Self> {
+        let published = IntCounter::new(
+            "outbox_published_events_total",
+            "Outbox events published to the broker",
+        )?;
+        let failed = IntCounter::new(
+            "outbox_publish_failures_total",
+            "Failed attempts to publish an outbox event",
+        )?;
+        let pending = IntGauge::new("outbox_pending_events", "Outbox events not yet published")?;
+        registry.register(Box::new(published.clone()))?;
+        registry.register(Box::new(failed.clone()))?;
+        registry.register(Box::new(pending.clone()))?;
+
+        self.metrics = Some(RelayMetrics {
+            published,
+            failed,
+            pending,
+        });
+        Ok(self)
+    }
+}
+
+impl Task for OutboxRelay {
+    fn name(&self) -> &'static str {
+        "outbox_relay"
+    }
+
+    fn run<'a>(&'a self, ctx: &'a TaskContext) -> TaskFuture<'a> {
+        Box::pin(async move {
+            let txn = self.db.begin().await?;
+            if !OutboxRepository::try_lock_relay(&txn).await? {
+                tracing::debug!("Another replica is relaying outbox events");
+                return Ok(());
+            }
+
+            let events = OutboxRepository::find_pending(&txn, self.batch_size).await?;
+            let mut published = Vec::with_capacity(events.len());
+            let mut failure = None;
+            for event in &events {
+                match self.publisher.publish(event).await {
+                    Ok(()) => published.push(event.id),
+                    Err(e) => {
+                        tracing::warn!(
+                            event_id = %event.id,
+                            event_type = %event.event_type,
+                            attempts = event.attempts + 1,
+                            error = %e,
+                            "Failed to publish outbox event"
+                        );
+                        OutboxRepository::record_failure(&txn, event.id, &e.to_string()).await?;
+                        failure = Some(e);
+                        break;
+                    }
+                }
+            }
+            OutboxRepository::mark_published(&txn, &published).await?;
+            txn.commit().await?;
+
+            let pruned = OutboxRepository::delete_published_before(
+                &self.db,
+                ctx.scheduled_at - Self::KEEP_PUBLISHED,
+            )
+            .await?;
+
+            if let Some(metrics) = &self.metrics {
+                metrics.published.inc_by(published.len() as u64);
+                if failure.is_some() {
+                    metrics.failed.inc();
+                }
+                let pending = OutboxRepository::count_pending(&self.db).await?;
+                metrics.pending.set(pending as i64);
+            }
+
+            tracing::debug!(
+                published = published.len(),
+                pruned,
+                "Outbox relay run finished"
+            );
+            match failure {
+                Some(e) => Err(e.into()),
+                None => Ok(()),
+            }
+        })
+    }
+}
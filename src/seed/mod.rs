@@ -0,0 +1,161 @@
+//! Deterministic demo data for development and demo environments.
+//!
+//! Every generated memo has a stable id derived from its index, so running the
+//! seeder again only inserts memos that are missing and never duplicates them.
+
+use crate::repository::MemoRepository;
+use chrono::{DateTime, Days, NaiveTime, Utc};
+use sea_orm::{ConnectionTrait, DbErr};
+use uuid::Uuid;
+
+/// Namespace for the v5 ids of seeded memos.
+const SEED_NAMESPACE: Uuid = Uuid::from_u128(0x6d656d6f_7365_6564_8000_000000000001);
+
+const ACTIONS: [&str; 10] = [
+    "Review",
+    "Draft",
+    "Schedule",
+    "Renew",
+    "Pay",
+    "Book",
+    "Prepare",
+    "Call about",
+    "Clean up",
+    "Follow up on",
+];
+
+const SUBJECTS: [&str; 12] = [
+    "quarterly report",
+    "dentist appointment",
+    "car insurance",
+    "team offsite agenda",
+    "electricity bill",
+    "flight to Lisbon",
+    "grocery list",
+    "passport application",
+    "project retrospective",
+    "garden shed",
+    "library books",
+    "birthday present for Sam",
+];
+
+const DESCRIPTIONS: [Option<&str>; 5] = [
+    Some("Check the numbers with finance before sending."),
+    None,
+    Some("Bring the paperwork from last year."),
+    Some("Ask for a discount if paying annually."),
+    None,
+];
+
+/// A memo the seeder will create if it does not exist yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedMemo {
+    pub id: Uuid,
+    pub title: String,
+    pub description: Option<String>,
+    pub date_to: DateTime<Utc>,
+    pub completed: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SeedReport {
+    pub created: usize,
+    pub skipped: usize,
+}
+
+/// Builds `count` demo memos with due dates spread around `today`: some overdue,
+/// some completed, most upcoming. The same inputs always yield the same memos.
+pub fn demo_memos(count: usize, today: DateTime<Utc>) -> Vec<SeedMemo> {
+    let morning = today
+        .date_naive()
+        .and_time(NaiveTime::from_hms_opt(9, 0, 0).expect("valid time"))
+        .and_utc();
+
+    (0..count)
+        .map(|i| {
+            let title = format!(
+                "{} {}",
+                ACTIONS[i % ACTIONS.len()],
+                SUBJECTS[(i * 7) % SUBJECTS.len()]
+            );
+            // Spread due dates from a week ago to three weeks ahead.
+            let offset = (i * 5) % 28;
+            let date_to = if offset < 7 {
+                morning - Days::new((7 - offset) as u64)
+            } else {
+                morning + Days::new((offset - 7) as u64)
+            };
+
+            SeedMemo {
+                id: Uuid::new_v5(&SEED_NAMESPACE, format!("memo-{}", i).as_bytes()),
+                title,
+                description: DESCRIPTIONS[i % DESCRIPTIONS.len()].map(str::to_string),
+                date_to,
+                completed: i % 4 == 3,
+            }
+        })
+        .collect()
+}
+
+/// Inserts the demo memos that are not in the database yet.
+#[tracing::instrument(skip(db))]
+pub async fn seed_demo_memos<C: ConnectionTrait>(
+    db: &C,
+    count: usize,
+) -> Result<SeedReport, DbErr> {
+    let mut report = SeedReport::default();
+
+    for memo in demo_memos(count, Utc::now()) {
+        let inserted = MemoRepository::insert_if_absent(
+            db,
+            memo.id,
+            memo.title,
+            memo.description,
+            memo.date_to,
+            memo.completed,
+        )
+        .await?;
+
+        if inserted {
+            report.created += 1;
+        } else {
+            report.skipped += 1;
+        }
+    }
+
+    tracing::info!(
+        created = report.created,
+        skipped = report.skipped,
+        "Demo memos seeded"
+    );
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_demo_memos_are_deterministic() {
+        let today = Utc.with_ymd_and_hms(2025, 3, 10, 15, 30, 0).unwrap();
+        assert_eq!(demo_memos(20, today), demo_memos(20, today));
+        assert_eq!(demo_memos(5, today)[..], demo_memos(20, today)[..5]);
+    }
+
+    #[test]
+    fn test_demo_memos_cover_overdue_and_completed() {
+        let today = Utc.with_ymd_and_hms(2025, 3, 10, 15, 30, 0).unwrap();
+        let memos = demo_memos(20, today);
+
+        assert!(memos.iter().any(|m| m.completed));
+        assert!(memos.iter().any(|m| !m.completed && m.date_to < today));
+        assert!(memos.iter().any(|m| m.date_to > today));
+
+        let mut ids: Vec<_> = memos.iter().map(|m| m.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), memos.len());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::{
+    attachments::{self, NewAttachment, Upload, scan::UploadScanners},
+    auth::UrlSigner,
+    db::GuardedConnection,
+    dto::SignedAttachmentUrlDto,
+    error::{AppError, ErrorCode},
+    repository::MemoAttachmentRepository,
+};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+/// Which file of an attachment to serve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentVariant {
+    Original,
+    Thumbnail,
+}
+
+pub struct AttachmentService {
+    db: GuardedConnection,
+    scanners: UploadScanners,
+}
+
+impl AttachmentService {
+    pub fn new(db: GuardedConnection) -> Self {
+        tracing::debug!("Creating AttachmentService");
+        Self {
+            db,
+            scanners: UploadScanners::default(),
+        }
+    }
+
+    /// Runs every upload through `scanners` before it is decoded.
+    pub fn scan_with(mut self, scanners: UploadScanners) -> Self {
+        self.scanners = scanners;
+        self
+    }
+
+    /// Scans uploaded files, then checks and thumbnails the images on a
+    /// blocking thread, so decoding large images does not stall the worker.
+    #[tracing::instrument(skip(self, uploads), fields(uploads = uploads.len()))]
+    pub async fn prepare(&self, uploads: Vec<Upload>) -> Result<Vec<NewAttachment>, AppError> {
+        let uploads = attachments::select(uploads)?;
+        for upload in &uploads {
+            let file_name = attachments::file_name(upload.file_name.as_deref());
+            self.scanners.scan(&file_name, &upload.data).await?;
+        }
+
+        let prepared = tokio::task::spawn_blocking(move || {
+            uploads
+                .into_iter()
+                .map(attachments::prepare)
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .await
+        .map_err(|e| AppError::Internal(format!("Image processing failed: {}", e)))?;
+
+        Ok(prepared?)
+    }
+
+    /// URLs of an attachment's file and thumbnail, signed for `ttl`.
+    #[tracing::instrument(skip(self, signer), fields(attachment_id = %id))]
+    pub async fn signed_urls(
+        &self,
+        id: Uuid,
+        signer: &UrlSigner,
+        ttl: Duration,
+    ) -> Result<SignedAttachmentUrlDto, AppError> {
+        if !MemoAttachmentRepository::exists(&self.db, id).await? {
+            return Err(AppError::not_found(
+                ErrorCode::AttachmentNotFound,
+                format!("Attachment with id {} not found", id),
+            ));
+        }
+
+        let now = Utc::now();
+        let url = signer.sign(&format!("/web/attachments/{}", id), ttl, now)?;
+        let thumbnail = signer.sign(&format!("/web/attachments/{}/thumbnail", id), ttl, now)?;
+        Ok(SignedAttachmentUrlDto {
+            url: url.url,
+            thumbnail_url: thumbnail.url,
+            expires_at: url.expires_at,
+        })
+    }
+
+    /// Content type and bytes of an attachment's file or thumbnail.
+    #[tracing::instrument(skip(self), fields(attachment_id = %id))]
+    pub async fn get_content(
+        &self,
+        id: Uuid,
+        variant: AttachmentVariant,
+    ) -> Result<(String, Vec<u8>), AppError> {
+        let thumbnail = variant == AttachmentVariant::Thumbnail;
+        let (content_type, data) = MemoAttachmentRepository::find_content(&self.db, id, thumbnail)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::AttachmentNotFound,
+                    format!("Attachment with id {} not found", id),
+                )
+            })?;
+
+        if thumbnail {
+            Ok((attachments::THUMBNAIL_CONTENT_TYPE.to_string(), data))
+        } else {
+            Ok((content_type, data))
+        }
+    }
+}
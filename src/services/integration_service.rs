@@ -0,0 +1,79 @@
+use crate::{
+    db::GuardedConnection,
+    dto::{CreateWebhookIntegrationDto, WebhookIntegrationDto},
+    error::{AppError, ErrorCode},
+    repository::WebhookIntegrationRepository,
+};
+use uuid::Uuid;
+use validator::{Validate, ValidationError, ValidationErrors};
+
+pub struct IntegrationService {
+    db: GuardedConnection,
+}
+
+impl IntegrationService {
+    pub fn new(db: GuardedConnection) -> Self {
+        tracing::debug!("Creating IntegrationService");
+        Self { db }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn list_webhooks(&self) -> Result<Vec<WebhookIntegrationDto>, AppError> {
+        let integrations = WebhookIntegrationRepository::find_all(&self.db).await?;
+        Ok(integrations
+            .into_iter()
+            .filter_map(WebhookIntegrationDto::from_model)
+            .collect())
+    }
+
+    #[tracing::instrument(skip(self, dto), fields(provider = dto.provider.as_str()))]
+    pub async fn create_webhook(
+        &self,
+        dto: CreateWebhookIntegrationDto,
+    ) -> Result<WebhookIntegrationDto, AppError> {
+        dto.validate()?;
+        if !dto.provider.accepts_url(&dto.url) {
+            let mut errors = ValidationErrors::new();
+            errors.add(
+                "url",
+                ValidationError::new("url").with_message(
+                    format!(
+                        "URL must be a {} incoming-webhook URL",
+                        dto.provider.as_str()
+                    )
+                    .into(),
+                ),
+            );
+            return Err(errors.into());
+        }
+
+        let mut events: Vec<String> = dto
+            .events
+            .iter()
+            .map(|event| event.as_str().to_string())
+            .collect();
+        events.sort();
+        events.dedup();
+
+        let integration =
+            WebhookIntegrationRepository::create(&self.db, dto.provider.as_str(), dto.url, events)
+                .await?;
+
+        tracing::info!(integration_id = %integration.id, "Webhook integration registered");
+        WebhookIntegrationDto::from_model(integration)
+            .ok_or_else(|| AppError::Internal("Stored an unknown webhook provider".to_string()))
+    }
+
+    #[tracing::instrument(skip(self), fields(integration_id = %id))]
+    pub async fn delete_webhook(&self, id: Uuid) -> Result<(), AppError> {
+        if !WebhookIntegrationRepository::delete(&self.db, id).await? {
+            return Err(AppError::not_found(
+                ErrorCode::IntegrationNotFound,
+                format!("Webhook integration with id {} not found", id),
+            ));
+        }
+
+        tracing::info!("Webhook integration removed");
+        Ok(())
+    }
+}
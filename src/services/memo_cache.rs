@@ -0,0 +1,165 @@
+use moka::future::Cache;
+use prometheus::{IntCounterVec, Opts, Registry};
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::config::settings::MemoCacheConfig;
+use crate::dto::{MemoResponseDto, PaginatedResponse};
+use crate::error::AppError;
+
+/// In-process TTL cache of memos by ID and of the index page listing, in front
+/// of [`crate::services::MemoService`].
+///
+/// Writes through a `MemoService` holding the same cache invalidate what they
+/// touched. Writes elsewhere (other replicas, the scheduler's purge, the
+/// CLI) show up once the entry expires, after `MEMO_CACHE_TTL_SECS`. Disabled
+/// by default; a disabled cache loads on every call.
+#[derive(Clone, Default)]
+pub struct MemoCache {
+    inner: Option<Arc<Inner>>,
+    lookups: Option<IntCounterVec>,
+}
+
+struct Inner {
+    memos: Cache<Uuid, MemoResponseDto>,
+    index: Cache<(), PaginatedResponse<MemoResponseDto>>,
+    /// Bumped on every invalidation, so a load that raced with a write does
+    /// not leave what it read behind.
+    generation: AtomicU64,
+}
+
+impl MemoCache {
+    pub fn from_config(config: &MemoCacheConfig) -> Self {
+        if config.max_entries == 0 || config.ttl_secs == 0 {
+            return Self::default();
+        }
+        let ttl = Duration::from_secs(config.ttl_secs);
+        Self {
+            inner: Some(Arc::new(Inner {
+                memos: Cache::builder()
+                    .max_capacity(config.max_entries)
+                    .time_to_live(ttl)
+                    .build(),
+                index: Cache::builder().max_capacity(1).time_to_live(ttl).build(),
+                generation: AtomicU64::new(0),
+            })),
+            lookups: None,
+        }
+    }
+
+    pub fn with_metrics(mut self, registry: &Registry) -> prometheus::Result<Self> {
+        let counter = IntCounterVec::new(
+            Opts::new(
+                "memo_cache_lookups_total",
+                "Memo cache lookups by cache and result (hit or miss)",
+            ),
+            &["cache", "result"],
+        )?;
+        registry.register(Box::new(counter.clone()))?;
+        self.lookups = Some(counter);
+        Ok(self)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// The memo with `id`, from the cache or from `load`. Errors, including
+    /// `NotFound`, are not cached.
+    pub async fn memo<F, Fut>(&self, id: Uuid, load: F) -> Result<MemoResponseDto, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<MemoResponseDto, AppError>>,
+    {
+        match &self.inner {
+            Some(inner) => {
+                self.get_or_load("memo", inner, &inner.memos, id, load)
+                    .await
+            }
+            None => load().await,
+        }
+    }
+
+    /// The listing of the index page, from the cache or from `load`.
+    pub async fn index<F, Fut>(
+        &self,
+        load: F,
+    ) -> Result<PaginatedResponse<MemoResponseDto>, AppError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<PaginatedResponse<MemoResponseDto>, AppError>>,
+    {
+        match &self.inner {
+            Some(inner) => {
+                self.get_or_load("index", inner, &inner.index, (), load)
+                    .await
+            }
+            None => load().await,
+        }
+    }
+
+    /// Drops the memos with `ids` and the index listing; for writes that may
+    /// change which memos are listed, pass no IDs.
+    pub async fn invalidate(&self, ids: &[Uuid]) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        inner.generation.fetch_add(1, Ordering::AcqRel);
+        for id in ids {
+            inner.memos.invalidate(id).await;
+        }
+        inner.index.invalidate(&()).await;
+    }
+
+    /// Drops everything, for writes that also change memos linked to the
+    /// ones written, like deletions.
+    pub fn invalidate_all(&self) {
+        let Some(inner) = &self.inner else {
+            return;
+        };
+        inner.generation.fetch_add(1, Ordering::AcqRel);
+        inner.memos.invalidate_all();
+        inner.index.invalidate_all();
+    }
+
+    async fn get_or_load<K, V, F, Fut>(
+        &self,
+        name: &str,
+        inner: &Inner,
+        cache: &Cache<K, V>,
+        key: K,
+        load: F,
+    ) -> Result<V, AppError>
+    where
+        K: Hash + Eq + Send + Sync + Clone + 'static,
+        V: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, AppError>>,
+    {
+        if let Some(value) = cache.get(&key).await {
+            self.count(name, "hit");
+            return Ok(value);
+        }
+        self.count(name, "miss");
+
+        let generation = inner.generation.load(Ordering::Acquire);
+        let value = load().await?;
+        cache.insert(key.clone(), value.clone()).await;
+        // An invalidation since the load started may have run before the
+        // insert; take the possibly outdated value back out.
+        if inner.generation.load(Ordering::Acquire) != generation {
+            cache.invalidate(&key).await;
+        }
+        Ok(value)
+    }
+
+    fn count(&self, cache: &str, result: &str) {
+        if let Some(lookups) = &self.lookups {
+            lookups.with_label_values(&[cache, result]).inc();
+        }
+    }
+}
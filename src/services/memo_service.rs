@@ -1,25 +1,84 @@
 use crate::{
+    attachments::NewAttachment,
+    db::{GuardedConnection, ReadReplicas},
     dto::{
-        CreateMemoDto, MemoResponseDto, PaginatedResponse, PaginationParams, PatchMemoDto,
+        AttachmentDto, BatchCompleteDto, BatchCompleteResponse, BatchDeleteDto, BlockerDto,
+        BoardColumn, CompleteMemoDto, CountMode, CreateMemoDto, DailyCountDto, DeleteMemosResponse,
+        DueDate, DuplicateMemoParams, MemoBoard, MemoResponseDto, MemoSnoozeDto, MemoStatsDto,
+        MoveMemoDto, PaginatedResponse, PaginationParams, PatchMemoDto, SnoozeMemoDto,
         UpdateMemoDto,
     },
-    entities::memos,
-    error::AppError,
-    repository::MemoRepository,
+    entities::{memo_list_view, memo_snoozes, memos},
+    error::{AppError, ErrorCode, FieldError},
+    events::{self, AttachmentRef, DomainEvent, MemoLink, MemoRef, MemoSnapshot},
+    i18n::{Locale, ViewerTimezone, timezone},
+    repository::{
+        AttachmentMeta, MemoAttachmentRepository, MemoDependencyRepository, MemoFilter, MemoFlag,
+        MemoImportRepository, MemoRepository, MemoSnoozeRepository, TotalCount,
+        UndoTokenRepository,
+    },
+    services::{MemoCache, UnitOfWork},
     utils::{sanitize_html, sanitize_optional_html},
 };
-use sea_orm::DatabaseConnection;
+use chrono::TimeZone;
+use chrono::{DateTime, Days, Duration, NaiveDate, NaiveTime, Utc};
+use chrono_tz::Tz;
+use futures_util::{Stream, StreamExt};
+use sea_orm::ConnectionTrait;
+use std::collections::{BTreeMap, HashSet};
 use uuid::Uuid;
 use validator::Validate;
 
 pub struct MemoService {
-    db: DatabaseConnection,
+    db: GuardedConnection,
+    /// Where listings, search and lookups by ID run; empty reads the primary
+    replicas: ReadReplicas,
+    /// Lookups by ID and the index listing; writes invalidate what they touch
+    cache: MemoCache,
+    require_future_due_dates: bool,
+}
+
+/// A validated memo, ready to insert.
+struct NewMemo {
+    title: String,
+    description: Option<String>,
+    date_to: DateTime<Utc>,
+    timezone: Tz,
+    color: Option<String>,
 }
 
 impl MemoService {
-    pub fn new(db: DatabaseConnection) -> Self {
+    pub fn new(db: GuardedConnection) -> Self {
         tracing::debug!("Creating MemoService");
-        Self { db }
+        Self {
+            db,
+            replicas: ReadReplicas::default(),
+            cache: MemoCache::default(),
+            require_future_due_dates: false,
+        }
+    }
+
+    /// Runs [`Self::get_all_memos`] and [`Self::get_memo_by_id`] on `replicas`.
+    /// Only for callers that can live with replication lag, i.e. not right
+    /// after a write they want to show.
+    pub fn with_replicas(mut self, replicas: ReadReplicas) -> Self {
+        self.replicas = replicas;
+        self
+    }
+
+    /// Serves [`Self::get_memo_by_id`] and [`Self::get_index_memos`] from
+    /// `cache`. Every service writing memos should share it, or their writes
+    /// only show up once the entries expire.
+    pub fn with_cache(mut self, cache: MemoCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Rejects new memos whose due date has already passed. Off by default,
+    /// so imports of past memos and internal callers are not affected.
+    pub fn require_future_due_dates(mut self, required: bool) -> Self {
+        self.require_future_due_dates = required;
+        self
     }
 
     #[tracing::instrument(skip(self), fields(limit, offset, completed))]
@@ -34,58 +93,555 @@ impl MemoService {
         let offset = params.offset.unwrap_or(0);
         let sort_by = params.sort_by.as_deref().unwrap_or("created_at");
         let order = params.order.as_deref().unwrap_or("desc");
+        let filter = MemoFilter {
+            completed: params.completed,
+            overdue: params.overdue,
+            search: params
+                .q
+                .as_deref()
+                .map(str::trim)
+                .filter(|q| !q.is_empty())
+                .map(str::to_string),
+            archived: params.archived.unwrap_or(false),
+            pinned: params.pinned,
+            starred: params.starred,
+            color: params.color,
+        };
+
+        tracing::debug!(limit, offset, ?filter, sort_by, order, "Fetching all memos");
 
-        tracing::debug!(
+        let count = match params.count.unwrap_or_default() {
+            CountMode::Exact => TotalCount::Exact,
+            CountMode::Estimated => TotalCount::Estimated,
+            CountMode::Skip => TotalCount::Skip,
+        };
+        let (memo_dtos, page) = self
+            .replicas
+            .read(&self.db, |db| {
+                let filter = &filter;
+                async move {
+                    let mut page = MemoRepository::find_page(
+                        &db, limit, offset, filter, sort_by, order, count,
+                    )
+                    .await?;
+                    let memos = std::mem::take(&mut page.memos);
+                    let views = std::mem::take(&mut page.views);
+                    Ok((Self::listed_dtos(memos, views)?, page))
+                }
+            })
+            .await?;
+
+        tracing::info!(count = memo_dtos.len(), total = ?page.total, "Successfully fetched memos");
+
+        Ok(PaginatedResponse::with_total(
+            memo_dtos,
+            page.total,
+            page.estimated,
+            page.has_next,
             limit,
             offset,
-            completed = ?params.completed,
-            sort_by,
-            order,
-            "Fetching all memos"
-        );
+        ))
+    }
 
-        let (memos, total) =
-            MemoRepository::find_all(&self.db, limit, offset, params.completed, sort_by, order)
-                .await?;
+    /// The first page with the default filters and order, as the index page
+    /// shows it.
+    pub async fn get_index_memos(&self) -> Result<PaginatedResponse<MemoResponseDto>, AppError> {
+        self.cache
+            .index(|| self.get_all_memos(PaginationParams::default()))
+            .await
+    }
+
+    /// Memos due between `start` (inclusive) and `end` (exclusive), grouped by UTC day.
+    #[tracing::instrument(skip(self), fields(%start, %end))]
+    pub async fn get_memos_by_day(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<BTreeMap<NaiveDate, Vec<MemoResponseDto>>, AppError> {
+        if start >= end {
+            return Err(AppError::Validation(
+                "Date range start must be before its end".to_string(),
+            ));
+        }
+
+        let memos = MemoRepository::find_by_date_range(
+            &self.db,
+            start.and_time(NaiveTime::MIN).and_utc(),
+            end.and_time(NaiveTime::MIN).and_utc(),
+        )
+        .await?;
+
+        let mut by_day: BTreeMap<NaiveDate, Vec<MemoResponseDto>> = BTreeMap::new();
+        for dto in Self::to_dtos(&self.db, memos).await? {
+            by_day
+                .entry(dto.date_to.date_naive())
+                .or_default()
+                .push(dto);
+        }
+
+        tracing::info!(days = by_day.len(), "Fetched memos grouped by day");
+
+        Ok(by_day)
+    }
+
+    /// The Kanban board as seen at `now`, in `now`'s timezone: open memos
+    /// split into overdue, due later today and upcoming, soonest first, and
+    /// the most recently completed ones. Each column holds at most `limit`
+    /// memos.
+    #[tracing::instrument(skip(self), fields(%now, limit))]
+    pub async fn get_board(&self, now: DateTime<Tz>, limit: u64) -> Result<MemoBoard, AppError> {
+        let now_utc = now.with_timezone(&Utc);
+        let tomorrow = Self::start_of_day(now.date_naive() + Days::new(1), now.timezone());
+
+        let mut columns = Vec::with_capacity(BoardColumn::ALL.len());
+        for column in BoardColumn::ALL {
+            let memos = match column {
+                BoardColumn::Overdue => {
+                    MemoRepository::find_open_due_between(&self.db, None, Some(now_utc), limit)
+                        .await?
+                }
+                BoardColumn::Today => {
+                    MemoRepository::find_open_due_between(
+                        &self.db,
+                        Some(now_utc),
+                        Some(tomorrow),
+                        limit,
+                    )
+                    .await?
+                }
+                BoardColumn::Upcoming => {
+                    MemoRepository::find_open_due_between(&self.db, Some(tomorrow), None, limit)
+                        .await?
+                }
+                BoardColumn::Done => {
+                    MemoRepository::find_recently_completed(&self.db, limit).await?
+                }
+            };
+            columns.push((column, Self::to_dtos(&self.db, memos).await?));
+        }
+
+        tracing::info!("Fetched Kanban board");
+
+        Ok(MemoBoard { columns })
+    }
+
+    /// Moves a memo to another board column through [`patch_memo`](Self::patch_memo).
+    /// `Done` completes it; `Today` and `Upcoming` reopen it and, unless it is
+    /// already due there, move its due date to today or tomorrow at the same
+    /// time of day (today 23:59 if that time has passed). Nothing is moved
+    /// into `Overdue`.
+    #[tracing::instrument(skip(self), fields(memo_id = %id, column = column.as_str()))]
+    pub async fn move_on_board(
+        &self,
+        id: Uuid,
+        column: BoardColumn,
+        now: DateTime<Tz>,
+    ) -> Result<MemoResponseDto, AppError> {
+        let today = now.date_naive();
+        let day = match column {
+            BoardColumn::Overdue => {
+                return Err(AppError::Validation(
+                    "Memos cannot be moved to Overdue; change the due date instead".to_string(),
+                ));
+            }
+            BoardColumn::Done => {
+                return self.patch_memo(id, PatchMemoDto::completed(true)).await;
+            }
+            BoardColumn::Today => today,
+            BoardColumn::Upcoming => today + Days::new(1),
+        };
+
+        let memo = MemoRepository::find_by_id(&self.db, id)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                )
+            })?;
+        let due = memo.date_to.with_timezone(&now.timezone());
+        let tomorrow = Self::start_of_day(today + Days::new(1), now.timezone());
+        let already_there = match column {
+            BoardColumn::Today => due >= now && due < tomorrow,
+            _ => due >= tomorrow,
+        };
+
+        let mut dto = PatchMemoDto::completed(false);
+        if !already_there {
+            let mut date_to = DueDate::Local(day.and_time(due.time()))
+                .resolve(now.timezone())
+                .map_err(AppError::Validation)?;
+            if date_to <= now {
+                date_to = DueDate::Local(day.and_hms_opt(23, 59, 0).unwrap_or_default())
+                    .resolve(now.timezone())
+                    .map_err(AppError::Validation)?;
+            }
+            dto.date_to = Some(date_to.into());
+        }
+        self.patch_memo(id, dto).await
+    }
+
+    /// Every memo, oldest first, as batches of up to `batch_size` DTOs read
+    /// lazily from a database cursor. Failing to open the cursor is reported
+    /// here; later failures end the stream with an error.
+    #[tracing::instrument(skip(self))]
+    pub async fn export_memos(
+        &self,
+        batch_size: u64,
+    ) -> Result<impl Stream<Item = Result<Vec<MemoResponseDto>, AppError>> + 'static, AppError>
+    {
+        let batches = MemoRepository::stream_all(&self.db, batch_size).await?;
+        let db = self.db.clone();
+
+        Ok(batches.then(move |batch| {
+            let db = db.clone();
+            async move { Self::to_dtos(&db, batch?).await }
+        }))
+    }
+
+    /// Status counts and a zero-filled series of memos created over the last
+    /// `days` UTC days, ending today.
+    #[tracing::instrument(skip(self))]
+    pub async fn get_stats(&self, days: u32) -> Result<MemoStatsDto, AppError> {
+        let today = Utc::now().date_naive();
+        let first_day = today - Days::new(u64::from(days.max(1)) - 1);
 
-        let memo_dtos: Vec<MemoResponseDto> = memos.into_iter().map(Self::entity_to_dto).collect();
+        let stats =
+            MemoRepository::stats(&self.db, first_day.and_time(NaiveTime::MIN).and_utc()).await?;
 
-        tracing::info!(count = memo_dtos.len(), total, "Successfully fetched memos");
+        let created: BTreeMap<NaiveDate, u64> = stats.created_per_day.into_iter().collect();
+        let created_per_day = first_day
+            .iter_days()
+            .take_while(|date| *date <= today)
+            .map(|date| DailyCountDto {
+                date,
+                count: created.get(&date).copied().unwrap_or(0),
+            })
+            .collect();
 
-        Ok(PaginatedResponse::new(memo_dtos, total, limit, offset))
+        Ok(MemoStatsDto {
+            total: stats.total,
+            active: stats.total - stats.completed,
+            completed: stats.completed,
+            overdue: stats.overdue,
+            created_per_day,
+        })
+    }
+
+    /// What a retention run with `cutoff` would purge: how many memos and the
+    /// oldest `sample` of them.
+    #[tracing::instrument(skip(self), fields(%cutoff))]
+    pub async fn preview_purge(
+        &self,
+        cutoff: DateTime<Utc>,
+        include_archived: bool,
+        sample: u64,
+    ) -> Result<(u64, Vec<MemoResponseDto>), AppError> {
+        let matching = MemoRepository::count_purgeable(&self.db, cutoff, include_archived).await?;
+        let oldest =
+            MemoRepository::find_purgeable(&self.db, cutoff, include_archived, sample).await?;
+
+        Ok((matching, Self::to_dtos(&self.db, oldest).await?))
     }
 
     #[tracing::instrument(skip(self), fields(memo_id = %id))]
     pub async fn get_memo_by_id(&self, id: Uuid) -> Result<MemoResponseDto, AppError> {
         tracing::debug!("Fetching memo by ID");
 
-        let memo = MemoRepository::find_by_id(&self.db, id)
-            .await?
-            .ok_or_else(|| AppError::NotFound(format!("Memo with id {} not found", id)))?;
+        let memo = self
+            .cache
+            .memo(id, || {
+                self.replicas.read(&self.db, |db| async move {
+                    let memo = MemoRepository::find_by_id(&db, id).await?.ok_or_else(|| {
+                        AppError::not_found(
+                            ErrorCode::MemoNotFound,
+                            format!("Memo with id {} not found", id),
+                        )
+                    })?;
+                    Self::to_dto(&db, memo).await
+                })
+            })
+            .await?;
 
         tracing::info!("Memo found successfully");
 
-        Ok(Self::entity_to_dto(memo))
+        Ok(memo)
     }
 
-    #[tracing::instrument(skip(self, dto), fields(has_description = dto.description.is_some()))]
     pub async fn create_memo(&self, dto: CreateMemoDto) -> Result<MemoResponseDto, AppError> {
+        self.create_memo_with_attachments(dto, Vec::new()).await
+    }
+
+    /// Creates a memo and stores `attachments` with it, in one transaction.
+    #[tracing::instrument(skip(self, dto, attachments), fields(has_description = dto.description.is_some(), attachments = attachments.len()))]
+    pub async fn create_memo_with_attachments(
+        &self,
+        dto: CreateMemoDto,
+        attachments: Vec<NewAttachment>,
+    ) -> Result<MemoResponseDto, AppError> {
+        let new_memo = self.new_memo(dto)?;
+
+        tracing::debug!(title = %new_memo.title, "Creating new memo with sanitized input");
+
+        let uow = UnitOfWork::begin(&self.db).await?;
+        let memo = Self::insert_memo(uow.conn(), new_memo).await?;
+        let dto = if attachments.is_empty() {
+            // A new memo has no links, so there is nothing else to load.
+            Self::entity_to_dto(memo)
+        } else {
+            for attachment in attachments {
+                let id = MemoAttachmentRepository::create(uow.conn(), memo.id, attachment).await?;
+                events::record(
+                    uow.conn(),
+                    DomainEvent::AttachmentAdded(AttachmentRef {
+                        id,
+                        memo_id: memo.id,
+                    }),
+                )
+                .await?;
+            }
+            Self::to_dto(uow.conn(), memo).await?
+        };
+        uow.commit().await?;
+        self.cache.invalidate(&[]).await;
+
+        tracing::info!(memo_id = %dto.id, "Memo created successfully");
+
+        Ok(dto)
+    }
+
+    /// Creates a memo for each `(line, json)` of an import file in one
+    /// transaction. Lines that are not a valid `CreateMemoDto` are recorded as
+    /// failures of `operation_id` instead. When the transaction itself fails,
+    /// every line of the chunk is recorded as failed. Returns how many memos
+    /// were created and how many lines failed.
+    #[tracing::instrument(skip(self, lines), fields(%operation_id, lines = lines.len()))]
+    pub async fn import_chunk(
+        &self,
+        operation_id: Uuid,
+        lines: &[(i32, &str)],
+    ) -> Result<(usize, usize), AppError> {
+        let mut memos = Vec::new();
+        let mut failures = Vec::new();
+        for &(line, text) in lines {
+            let parsed = serde_json::from_str::<CreateMemoDto>(text)
+                .map_err(|e| AppError::Validation(format!("Line is not a memo: {}", e)))
+                .and_then(|dto| self.new_memo(dto));
+            match parsed {
+                Ok(new_memo) => memos.push(new_memo),
+                Err(e) => failures.push(import_failure(line, &e)),
+            }
+        }
+        let (imported, failed) = (memos.len(), failures.len());
+
+        let inserted = async {
+            let uow = UnitOfWork::begin(&self.db).await?;
+            for new_memo in memos {
+                Self::insert_memo(uow.conn(), new_memo).await?;
+            }
+            MemoImportRepository::record_failures(uow.conn(), operation_id, failures).await?;
+            uow.commit().await?;
+            self.cache.invalidate(&[]).await;
+            Ok(())
+        }
+        .await;
+
+        match inserted {
+            Ok(()) => Ok((imported, failed)),
+            Err(AppError::Database(e)) => {
+                tracing::warn!(error = %e, "Import chunk rolled back");
+                let error = AppError::Database(e);
+                let failures = lines
+                    .iter()
+                    .map(|&(line, _)| import_failure(line, &error))
+                    .collect();
+                MemoImportRepository::record_failures(&self.db, operation_id, failures).await?;
+                Ok((0, lines.len()))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Validates `dto` and works out what to store for it.
+    fn new_memo(&self, dto: CreateMemoDto) -> Result<NewMemo, AppError> {
         dto.validate()?;
 
+        // Descriptions are Markdown and stored as written; they are sanitized
+        // when rendered (see `utils::markdown`).
         let sanitized_title = sanitize_html(&dto.title);
-        let sanitized_description = sanitize_optional_html(dto.description.as_deref());
 
-        tracing::debug!(title = %sanitized_title, "Creating new memo with sanitized input");
+        // Memos created without an explicit timezone belong to the viewer's
+        let tz = match dto.timezone.as_deref() {
+            Some(name) => timezone::parse(name).map_err(AppError::Validation)?,
+            None => ViewerTimezone::current(),
+        };
+        let date_to = Self::due_date(dto.date_to, dto.date_to_text.as_deref(), tz)?
+            .ok_or_else(|| AppError::InvalidFields {
+                message: "date_to is required".to_string(),
+                errors: vec![FieldError {
+                    field: "date_to".to_string(),
+                    code: "required".to_string(),
+                    message: "Provide date_to or date_to_text".to_string(),
+                }],
+            })?
+            .resolve(tz)
+            .map_err(AppError::Validation)?;
+        if self.require_future_due_dates && date_to <= Utc::now() {
+            return Err(AppError::InvalidFields {
+                message: "date_to must be in the future".to_string(),
+                errors: vec![FieldError {
+                    field: "date_to".to_string(),
+                    code: "future".to_string(),
+                    message: format!(
+                        "date_to must be in the future, got {}",
+                        date_to.to_rfc3339()
+                    ),
+                }],
+            });
+        }
+
+        Ok(NewMemo {
+            title: sanitized_title,
+            description: dto.description,
+            date_to,
+            timezone: tz,
+            color: dto.color,
+        })
+    }
 
+    async fn insert_memo<C: ConnectionTrait>(
+        conn: &C,
+        new_memo: NewMemo,
+    ) -> Result<memos::Model, AppError> {
         let memo = MemoRepository::create(
-            &self.db,
-            sanitized_title,
-            sanitized_description,
-            dto.date_to,
+            conn,
+            new_memo.title,
+            new_memo.description,
+            new_memo.date_to,
+            new_memo.timezone,
+            new_memo.color,
         )
         .await?;
+        events::record(conn, DomainEvent::MemoCreated(MemoSnapshot::from(&memo))).await?;
+
+        Ok(memo)
+    }
 
-        tracing::info!(memo_id = %memo.id, "Memo created successfully");
+    /// Marks the selected memos completed in one transaction. Memos that are
+    /// already completed are left untouched and reported in neither list;
+    /// memos waiting on an open blocker outside the batch stay open and are
+    /// reported as `blocked`.
+    #[tracing::instrument(skip(self, dto), fields(ids = dto.ids.as_ref().map(Vec::len)))]
+    pub async fn complete_memos(
+        &self,
+        dto: BatchCompleteDto,
+    ) -> Result<BatchCompleteResponse, AppError> {
+        dto.validate()?;
+
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        let (candidates, not_found) = match (dto.ids, dto.filter) {
+            (Some(ids), _) => {
+                let existing = MemoRepository::existing_ids(uow.conn(), &ids).await?;
+                let not_found = ids
+                    .iter()
+                    .filter(|id| !existing.contains(id))
+                    .copied()
+                    .collect();
+                (existing, not_found)
+            }
+            (None, filter) => {
+                let filter = filter.unwrap_or_default();
+                let filter = MemoFilter {
+                    completed: Some(false),
+                    overdue: filter.overdue,
+                    search: filter
+                        .q
+                        .as_deref()
+                        .map(str::trim)
+                        .filter(|q| !q.is_empty())
+                        .map(str::to_string),
+                    archived: filter.archived.unwrap_or(false),
+                    ..MemoFilter::default()
+                };
+                (
+                    MemoRepository::find_ids(uow.conn(), &filter).await?,
+                    Vec::new(),
+                )
+            }
+        };
+
+        let blocked = Self::blocked_in_batch(uow.conn(), &candidates).await?;
+        let candidates: Vec<Uuid> = candidates
+            .into_iter()
+            .filter(|id| !blocked.contains(id))
+            .collect();
+
+        let updated = MemoRepository::complete_many(uow.conn(), &candidates).await?;
+        for &id in &updated {
+            events::record(uow.conn(), DomainEvent::MemoCompleted(MemoRef { id })).await?;
+        }
+        uow.commit().await?;
+        self.cache.invalidate(&updated).await;
+
+        tracing::info!(
+            updated = updated.len(),
+            not_found = not_found.len(),
+            blocked = blocked.len(),
+            "Batch completion finished"
+        );
+
+        Ok(BatchCompleteResponse {
+            updated,
+            not_found,
+            blocked,
+        })
+    }
+
+    /// Creates an incomplete copy of a memo at the end of the manual ordering,
+    /// with its due date shifted by `days_offset` days.
+    #[tracing::instrument(skip(self, params), fields(memo_id = %id, days_offset = ?params.days_offset))]
+    pub async fn duplicate_memo(
+        &self,
+        id: Uuid,
+        params: DuplicateMemoParams,
+    ) -> Result<MemoResponseDto, AppError> {
+        params.validate()?;
+
+        let source = MemoRepository::find_by_id(&self.db, id)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                )
+            })?;
+
+        let tz = Self::memo_timezone(&source);
+        let date_to = source.date_to.with_timezone(&Utc)
+            + chrono::Duration::days(params.days_offset.unwrap_or(0));
+
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        let memo = MemoRepository::create(
+            uow.conn(),
+            source.title,
+            source.description,
+            date_to,
+            tz,
+            source.color,
+        )
+        .await?;
+        events::record(
+            uow.conn(),
+            DomainEvent::MemoCreated(MemoSnapshot::from(&memo)),
+        )
+        .await?;
+
+        uow.commit().await?;
+        self.cache.invalidate(&[]).await;
+
+        tracing::info!(memo_id = %memo.id, source_id = %id, "Memo duplicated");
 
         Ok(Self::entity_to_dto(memo))
     }
@@ -99,109 +655,854 @@ impl MemoService {
         dto.validate()?;
 
         let sanitized_title = sanitize_html(&dto.title);
-        let sanitized_description = sanitize_optional_html(dto.description.as_deref());
 
         tracing::debug!("Updating memo with sanitized input");
 
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        let existing_memo = MemoRepository::find_by_id_for_update(uow.conn(), id)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                )
+            })?;
+        Self::check_version(&existing_memo, dto.version)?;
+
+        let tz = match dto.timezone.as_deref() {
+            Some(name) => timezone::parse(name).map_err(AppError::Validation)?,
+            None => Self::memo_timezone(&existing_memo),
+        };
+        let date_to = dto.date_to.resolve(tz).map_err(AppError::Validation)?;
+        if dto.completed && !existing_memo.completed {
+            Self::ensure_unblocked(uow.conn(), id).await?;
+        }
+
         let memo = MemoRepository::update(
-            &self.db,
+            uow.conn(),
             id,
             sanitized_title,
-            sanitized_description,
-            dto.date_to,
+            dto.description,
+            date_to,
             dto.completed,
+            tz,
+            dto.color,
         )
-        .await
-        .map_err(|e| match e {
-            sea_orm::DbErr::RecordNotFound(_) => {
-                AppError::NotFound(format!("Memo with id {} not found", id))
-            }
-            _ => AppError::Database(e),
-        })?;
+        .await?;
+
+        events::record(
+            uow.conn(),
+            DomainEvent::MemoUpdated(MemoSnapshot::from(&memo)),
+        )
+        .await?;
+
+        uow.commit().await?;
+        self.cache.invalidate(&[id]).await;
 
         tracing::info!(memo_id = %memo.id, "Memo updated successfully");
 
-        Ok(Self::entity_to_dto(memo))
+        Self::to_dto(&self.db, memo).await
     }
 
-    #[tracing::instrument(skip(self, dto), fields(memo_id = %id))]
     pub async fn patch_memo(
         &self,
         id: Uuid,
         dto: PatchMemoDto,
     ) -> Result<MemoResponseDto, AppError> {
+        self.patch_memo_changed(id, dto).await.map(|(memo, _)| memo)
+    }
+
+    /// [`patch_memo`](Self::patch_memo), also reporting whether the memo
+    /// changed. A patch that would store the values the memo already has
+    /// leaves it alone: no new version, `updated_at` or event.
+    #[tracing::instrument(skip(self, dto), fields(memo_id = %id))]
+    pub async fn patch_memo_changed(
+        &self,
+        id: Uuid,
+        dto: PatchMemoDto,
+    ) -> Result<(MemoResponseDto, bool), AppError> {
         dto.validate()?;
 
         tracing::debug!("Patching memo");
 
-        let existing_memo = MemoRepository::find_by_id(&self.db, id)
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        let existing_memo = MemoRepository::find_by_id_for_update(uow.conn(), id)
             .await?
-            .ok_or_else(|| AppError::NotFound(format!("Memo with id {} not found", id)))?;
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                )
+            })?;
+        Self::check_version(&existing_memo, dto.version)?;
 
+        let tz = match dto.timezone.as_deref() {
+            Some(name) => timezone::parse(name).map_err(AppError::Validation)?,
+            None => Self::memo_timezone(&existing_memo),
+        };
         let title = dto
             .title
             .map(|t| sanitize_html(&t))
-            .unwrap_or(existing_memo.title);
-        let description = match dto.description {
-            Some(d) => sanitize_optional_html(Some(&d)),
-            None => existing_memo.description,
+            .unwrap_or_else(|| existing_memo.title.clone());
+        let description = dto
+            .description
+            .or_else(|| existing_memo.description.clone());
+        let date_to = match Self::due_date(dto.date_to, dto.date_to_text.as_deref(), tz)? {
+            Some(date_to) => date_to.resolve(tz).map_err(AppError::Validation)?,
+            None => existing_memo.date_to.into(),
         };
-        let date_to = dto.date_to.unwrap_or_else(|| existing_memo.date_to.into());
         let completed = dto.completed.unwrap_or(existing_memo.completed);
+        let color = match dto.color {
+            Some(color) => Some(color).filter(|color| !color.is_empty()),
+            None => existing_memo.color.clone(),
+        };
+
+        let unchanged = title == existing_memo.title
+            && description == existing_memo.description
+            && date_to == existing_memo.date_to
+            && completed == existing_memo.completed
+            && tz.name() == existing_memo.timezone
+            && color == existing_memo.color;
+        if unchanged {
+            tracing::debug!("Patch changes nothing; memo left as is");
+            let dto = Self::to_dto(uow.conn(), existing_memo).await?;
+            uow.commit().await?;
+            return Ok((dto, false));
+        }
+
+        if completed && !existing_memo.completed {
+            Self::ensure_unblocked(uow.conn(), id).await?;
+        }
 
         tracing::debug!("Patching memo with sanitized input");
 
-        let memo =
-            MemoRepository::update(&self.db, id, title, description, date_to, completed).await?;
+        let memo = MemoRepository::update(
+            uow.conn(),
+            id,
+            title,
+            description,
+            date_to,
+            completed,
+            tz,
+            color,
+        )
+        .await?;
+
+        events::record(
+            uow.conn(),
+            DomainEvent::MemoUpdated(MemoSnapshot::from(&memo)),
+        )
+        .await?;
+
+        uow.commit().await?;
+        self.cache.invalidate(&[id]).await;
 
         tracing::info!(memo_id = %memo.id, "Memo patched successfully");
 
-        Ok(Self::entity_to_dto(memo))
+        Ok((Self::to_dto(&self.db, memo).await?, true))
     }
 
+    /// Deletes a memo. It can be restored with the returned undo token until
+    /// `undo_window` has passed.
     #[tracing::instrument(skip(self), fields(memo_id = %id))]
-    pub async fn delete_memo(&self, id: Uuid) -> Result<(), AppError> {
+    pub async fn delete_memo(
+        &self,
+        id: Uuid,
+        undo_window: Duration,
+    ) -> Result<DeleteMemosResponse, AppError> {
         tracing::debug!("Deleting memo");
 
-        let deleted = MemoRepository::delete(&self.db, id).await?;
-
-        if !deleted {
+        let result = self.soft_delete(&[id], undo_window).await?;
+        if result.deleted.is_empty() {
             tracing::warn!("Memo not found for deletion");
-            return Err(AppError::NotFound(format!("Memo with id {} not found", id)));
+            return Err(AppError::not_found(
+                ErrorCode::MemoNotFound,
+                format!("Memo with id {} not found", id),
+            ));
         }
 
         tracing::info!("Memo deleted successfully");
 
-        Ok(())
+        Ok(result)
     }
 
+    /// Deletes a memo for good, without an undo window.
     #[tracing::instrument(skip(self), fields(memo_id = %id))]
-    pub async fn toggle_complete(&self, id: Uuid) -> Result<MemoResponseDto, AppError> {
+    pub async fn delete_memo_permanently(&self, id: Uuid) -> Result<(), AppError> {
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        if !MemoRepository::delete(uow.conn(), id).await? {
+            return Err(AppError::not_found(
+                ErrorCode::MemoNotFound,
+                format!("Memo with id {} not found", id),
+            ));
+        }
+        events::record(uow.conn(), DomainEvent::MemoDeleted(MemoRef { id })).await?;
+
+        uow.commit().await?;
+        self.cache.invalidate_all();
+
+        tracing::info!("Memo deleted permanently");
+
+        Ok(())
+    }
+
+    /// Deletes the memos among `dto.ids` in one transaction, under a single
+    /// undo token.
+    #[tracing::instrument(skip(self, dto), fields(ids = dto.ids.len()))]
+    pub async fn delete_memos(
+        &self,
+        dto: BatchDeleteDto,
+        undo_window: Duration,
+    ) -> Result<DeleteMemosResponse, AppError> {
+        dto.validate()?;
+
+        let result = self.soft_delete(&dto.ids, undo_window).await?;
+
+        tracing::info!(
+            deleted = result.deleted.len(),
+            not_found = result.not_found.len(),
+            "Batch deletion finished"
+        );
+
+        Ok(result)
+    }
+
+    /// Restores the memos deleted under `token`. Tokens work once, and only
+    /// until their undo window closes.
+    #[tracing::instrument(skip(self), fields(%token))]
+    pub async fn undo_delete(&self, token: Uuid) -> Result<Vec<MemoResponseDto>, AppError> {
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        // Restore before dropping the token: removing it cascades to the
+        // memos still attached to it.
+        let restored = MemoRepository::restore(uow.conn(), token).await?;
+        if !UndoTokenRepository::redeem(uow.conn(), token, Utc::now()).await? {
+            return Err(AppError::not_found(
+                ErrorCode::MemoNotFound,
+                format!("Undo token {} not found or expired", token),
+            ));
+        }
+        for memo in &restored {
+            events::record(
+                uow.conn(),
+                DomainEvent::MemoRestored(MemoSnapshot::from(memo)),
+            )
+            .await?;
+        }
+        let restored = Self::to_dtos(uow.conn(), restored).await?;
+
+        uow.commit().await?;
+        self.cache.invalidate_all();
+
+        tracing::info!(restored = restored.len(), "Deletion undone");
+
+        Ok(restored)
+    }
+
+    /// Hides the memos among `ids` behind a new undo token. Nothing is stored
+    /// when none of them can be deleted.
+    async fn soft_delete(
+        &self,
+        ids: &[Uuid],
+        undo_window: Duration,
+    ) -> Result<DeleteMemosResponse, AppError> {
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        let token = UndoTokenRepository::create(uow.conn(), Utc::now() + undo_window).await?;
+        let deleted = MemoRepository::soft_delete_many(uow.conn(), ids, token.token).await?;
+        let not_found = ids
+            .iter()
+            .filter(|id| !deleted.contains(id))
+            .copied()
+            .collect();
+
+        if deleted.is_empty() {
+            return Ok(DeleteMemosResponse {
+                deleted,
+                not_found,
+                undo_token: None,
+                undo_expires_at: None,
+            });
+        }
+        for &id in &deleted {
+            events::record(uow.conn(), DomainEvent::MemoDeleted(MemoRef { id })).await?;
+        }
+        uow.commit().await?;
+        self.cache.invalidate_all();
+
+        Ok(DeleteMemosResponse {
+            deleted,
+            not_found,
+            undo_token: Some(token.token),
+            undo_expires_at: Some(token.expires_at.into()),
+        })
+    }
+
+    /// Completes an open memo, recording `dto.note`, or reopens a completed
+    /// one. A note is rejected when reopening, and completing is refused
+    /// while the memo has open blockers.
+    #[tracing::instrument(skip(self, dto), fields(memo_id = %id, has_note = dto.note.is_some()))]
+    pub async fn toggle_complete(
+        &self,
+        id: Uuid,
+        dto: CompleteMemoDto,
+    ) -> Result<MemoResponseDto, AppError> {
+        dto.validate()?;
+
         tracing::debug!("Toggling memo completion status");
 
-        let existing_memo = MemoRepository::find_by_id(&self.db, id)
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        let existing_memo = MemoRepository::find_by_id_for_update(uow.conn(), id)
             .await?
-            .ok_or_else(|| AppError::NotFound(format!("Memo with id {} not found", id)))?;
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                )
+            })?;
 
         let new_completed = !existing_memo.completed;
+        if !new_completed && dto.note.is_some() {
+            return Err(AppError::Validation(
+                "A note can only be recorded when completing a memo".to_string(),
+            ));
+        }
+        if new_completed {
+            Self::ensure_unblocked(uow.conn(), id).await?;
+        }
+        let note = sanitize_optional_html(dto.note.as_deref());
 
-        let memo = MemoRepository::update(
-            &self.db,
-            id,
-            existing_memo.title,
-            existing_memo.description,
-            existing_memo.date_to.into(),
-            new_completed,
+        let memo = MemoRepository::set_completed(uow.conn(), id, new_completed, note)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                )
+            })?;
+
+        events::record(
+            uow.conn(),
+            DomainEvent::MemoUpdated(MemoSnapshot::from(&memo)),
         )
         .await?;
 
+        uow.commit().await?;
+        self.cache.invalidate(&[id]).await;
+
         tracing::info!(
             memo_id = %memo.id,
             completed = new_completed,
             "Memo completion status toggled"
         );
 
-        Ok(Self::entity_to_dto(memo))
+        Self::to_dto(&self.db, memo).await
+    }
+
+    /// Pushes an incomplete memo's due date forward and records the snooze in
+    /// its history. Overdue memos are snoozed from now, so they come due
+    /// again rather than staying in the past.
+    #[tracing::instrument(skip(self, dto), fields(memo_id = %id, preset = ?dto.preset))]
+    pub async fn snooze_memo(
+        &self,
+        id: Uuid,
+        dto: SnoozeMemoDto,
+    ) -> Result<MemoResponseDto, AppError> {
+        dto.validate()?;
+
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        let existing_memo = MemoRepository::find_by_id_for_update(uow.conn(), id)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                )
+            })?;
+        if existing_memo.completed {
+            return Err(AppError::Validation(
+                "Completed memos cannot be snoozed".to_string(),
+            ));
+        }
+
+        let tz = Self::memo_timezone(&existing_memo);
+        let due: DateTime<Utc> = existing_memo.date_to.into();
+        let from = due.max(Utc::now()).with_timezone(&tz);
+        let until = dto
+            .snoozed_until(from)
+            .and_then(|date_to| date_to.resolve(tz))
+            .map_err(AppError::Validation)?;
+
+        let memo = MemoRepository::update(
+            uow.conn(),
+            id,
+            existing_memo.title,
+            existing_memo.description,
+            until,
+            existing_memo.completed,
+            tz,
+            existing_memo.color,
+        )
+        .await?;
+        MemoSnoozeRepository::create(
+            uow.conn(),
+            id,
+            due,
+            until,
+            dto.preset.map(|preset| preset.as_str().to_string()),
+        )
+        .await?;
+
+        events::record(
+            uow.conn(),
+            DomainEvent::MemoUpdated(MemoSnapshot::from(&memo)),
+        )
+        .await?;
+
+        uow.commit().await?;
+        self.cache.invalidate(&[id]).await;
+
+        tracing::info!(memo_id = %memo.id, until = %until, "Memo snoozed");
+
+        Self::to_dto(&self.db, memo).await
+    }
+
+    /// A memo's snoozes, newest first.
+    #[tracing::instrument(skip(self), fields(memo_id = %id))]
+    pub async fn get_snoozes(&self, id: Uuid) -> Result<Vec<MemoSnoozeDto>, AppError> {
+        MemoRepository::find_by_id(&self.db, id)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                )
+            })?;
+
+        let snoozes = MemoSnoozeRepository::find_by_memo(&self.db, id).await?;
+        Ok(snoozes.into_iter().map(Self::snooze_to_dto).collect())
+    }
+
+    /// Makes `blocker_id` a blocker of memo `id`. Linking twice is a no-op;
+    /// self-links and links that would close a cycle are rejected.
+    #[tracing::instrument(skip(self), fields(memo_id = %id, blocker_id = %blocker_id))]
+    pub async fn add_blocker(
+        &self,
+        id: Uuid,
+        blocker_id: Uuid,
+    ) -> Result<MemoResponseDto, AppError> {
+        if id == blocker_id {
+            return Err(AppError::Validation(
+                "A memo cannot block itself".to_string(),
+            ));
+        }
+
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        // Lock both memos in a fixed order so concurrent links between the
+        // same pair serialize instead of racing past the cycle check.
+        let (first, second) = if id < blocker_id {
+            (id, blocker_id)
+        } else {
+            (blocker_id, id)
+        };
+        let mut locked = Vec::with_capacity(2);
+        for memo_id in [first, second] {
+            locked.push(
+                MemoRepository::find_by_id_for_update(uow.conn(), memo_id)
+                    .await?
+                    .ok_or_else(|| {
+                        AppError::not_found(
+                            ErrorCode::MemoNotFound,
+                            format!("Memo with id {} not found", memo_id),
+                        )
+                    })?,
+            );
+        }
+        let memo = locked
+            .into_iter()
+            .find(|memo| memo.id == id)
+            .expect("both memos were locked");
+
+        if memo.completed {
+            return Err(AppError::Validation(
+                "Completed memos cannot gain blockers".to_string(),
+            ));
+        }
+        if MemoDependencyRepository::depends_on(uow.conn(), blocker_id, id).await? {
+            return Err(AppError::Validation(format!(
+                "Memo with id {} already waits on memo {}; linking it back would create a cycle",
+                blocker_id, id
+            )));
+        }
+
+        let added = MemoDependencyRepository::add(uow.conn(), id, blocker_id).await?;
+        if added {
+            events::record(
+                uow.conn(),
+                DomainEvent::BlockerAdded(MemoLink {
+                    memo_id: id,
+                    blocker_id,
+                }),
+            )
+            .await?;
+        }
+        let dto = Self::to_dto(uow.conn(), memo).await?;
+
+        uow.commit().await?;
+        self.cache.invalidate(&[id, blocker_id]).await;
+
+        tracing::info!(added, "Memo blocker linked");
+
+        Ok(dto)
+    }
+
+    #[tracing::instrument(skip(self), fields(memo_id = %id, blocker_id = %blocker_id))]
+    pub async fn remove_blocker(&self, id: Uuid, blocker_id: Uuid) -> Result<(), AppError> {
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        let removed = MemoDependencyRepository::remove(uow.conn(), id, blocker_id).await?;
+        if !removed {
+            return Err(AppError::not_found(
+                ErrorCode::MemoNotFound,
+                format!("Memo with id {} is not blocked by memo {}", id, blocker_id),
+            ));
+        }
+        events::record(
+            uow.conn(),
+            DomainEvent::BlockerRemoved(MemoLink {
+                memo_id: id,
+                blocker_id,
+            }),
+        )
+        .await?;
+
+        uow.commit().await?;
+        self.cache.invalidate(&[id, blocker_id]).await;
+
+        tracing::info!("Memo blocker unlinked");
+
+        Ok(())
+    }
+
+    /// Hides a memo from default listings, the calendar and reminders without
+    /// deleting it.
+    #[tracing::instrument(skip(self), fields(memo_id = %id))]
+    pub async fn archive_memo(&self, id: Uuid) -> Result<MemoResponseDto, AppError> {
+        self.set_archived(id, true).await
+    }
+
+    #[tracing::instrument(skip(self), fields(memo_id = %id))]
+    pub async fn unarchive_memo(&self, id: Uuid) -> Result<MemoResponseDto, AppError> {
+        self.set_archived(id, false).await
+    }
+
+    async fn set_archived(&self, id: Uuid, archived: bool) -> Result<MemoResponseDto, AppError> {
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        let memo = MemoRepository::set_archived(uow.conn(), id, archived)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                )
+            })?;
+        events::record(
+            uow.conn(),
+            DomainEvent::MemoUpdated(MemoSnapshot::from(&memo)),
+        )
+        .await?;
+
+        uow.commit().await?;
+        self.cache.invalidate(&[id]).await;
+
+        tracing::info!(memo_id = %memo.id, archived, "Memo archive state changed");
+
+        Self::to_dto(&self.db, memo).await
+    }
+
+    /// Pins or unpins a memo; pinned memos are listed first.
+    #[tracing::instrument(skip(self), fields(memo_id = %id))]
+    pub async fn toggle_pinned(&self, id: Uuid) -> Result<MemoResponseDto, AppError> {
+        self.toggle_flag(id, MemoFlag::Pinned).await
+    }
+
+    #[tracing::instrument(skip(self), fields(memo_id = %id))]
+    pub async fn toggle_starred(&self, id: Uuid) -> Result<MemoResponseDto, AppError> {
+        self.toggle_flag(id, MemoFlag::Starred).await
+    }
+
+    async fn toggle_flag(&self, id: Uuid, flag: MemoFlag) -> Result<MemoResponseDto, AppError> {
+        let uow = UnitOfWork::begin(&self.db).await?;
+
+        let memo = MemoRepository::toggle_flag(uow.conn(), id, flag)
+            .await?
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                )
+            })?;
+        events::record(
+            uow.conn(),
+            DomainEvent::MemoUpdated(MemoSnapshot::from(&memo)),
+        )
+        .await?;
+
+        uow.commit().await?;
+        self.cache.invalidate(&[id]).await;
+
+        tracing::info!(memo_id = %memo.id, ?flag, "Memo flag changed");
+
+        Self::to_dto(&self.db, memo).await
+    }
+
+    #[tracing::instrument(skip(self, dto), fields(memo_id = %id, position = dto.position))]
+    pub async fn move_memo(&self, id: Uuid, dto: MoveMemoDto) -> Result<MemoResponseDto, AppError> {
+        tracing::debug!("Moving memo in manual ordering");
+
+        let memo = MemoRepository::move_to(&self.db, id, dto.position as usize)
+            .await
+            .map_err(|e| match e {
+                sea_orm::DbErr::RecordNotFound(_) => AppError::not_found(
+                    ErrorCode::MemoNotFound,
+                    format!("Memo with id {} not found", id),
+                ),
+                _ => AppError::Database(e),
+            })?;
+        // Moving shifts the memos in between
+        self.cache.invalidate_all();
+
+        tracing::info!(memo_id = %memo.id, position = memo.position, "Memo moved successfully");
+
+        Self::to_dto(&self.db, memo).await
+    }
+
+    #[tracing::instrument(skip(self, ids), fields(count = ids.len()))]
+    pub async fn reorder_memos(&self, ids: Vec<Uuid>) -> Result<(), AppError> {
+        tracing::debug!("Reordering memos");
+
+        if ids.is_empty() {
+            return Err(AppError::Validation(
+                "At least one memo id is required to reorder".to_string(),
+            ));
+        }
+
+        let mut unique = ids.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        if unique.len() != ids.len() {
+            return Err(AppError::Validation(
+                "Memo ids to reorder must be unique".to_string(),
+            ));
+        }
+
+        MemoRepository::reorder(&self.db, &ids)
+            .await
+            .map_err(|e| match e {
+                sea_orm::DbErr::RecordNotFound(msg) => {
+                    AppError::not_found(ErrorCode::MemoNotFound, msg)
+                }
+                _ => AppError::Database(e),
+            })?;
+        self.cache.invalidate(&ids).await;
+
+        tracing::info!("Memos reordered successfully");
+
+        Ok(())
+    }
+
+    /// Rejects a write based on a stale read, returning the current memo in the error.
+    fn check_version(existing: &memos::Model, expected: Option<i32>) -> Result<(), AppError> {
+        match expected {
+            Some(expected) if expected != existing.version => {
+                tracing::warn!(
+                    expected,
+                    current = existing.version,
+                    "Rejecting update with stale version"
+                );
+                Err(AppError::Conflict {
+                    message: format!(
+                        "Memo with id {} was modified (expected version {}, current version {})",
+                        existing.id, expected, existing.version
+                    ),
+                    current: serde_json::to_value(Self::entity_to_dto(existing.clone())).ok(),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The due date a client sent, reading `date_to_text` relative to the
+    /// current time in `tz`.
+    fn due_date(
+        date_to: Option<DueDate>,
+        date_to_text: Option<&str>,
+        tz: Tz,
+    ) -> Result<Option<DueDate>, AppError> {
+        match date_to_text {
+            Some(text) => DueDate::parse_natural(text, Utc::now().with_timezone(&tz))
+                .map(Some)
+                .map_err(AppError::Validation),
+            None => Ok(date_to),
+        }
+    }
+
+    /// Stored timezone of a memo; names no longer known to the tz database
+    /// fall back to UTC.
+    /// The first instant of `day` in `tz`, or its UTC midnight when the day
+    /// starts inside a daylight saving gap.
+    fn start_of_day(day: NaiveDate, tz: Tz) -> DateTime<Utc> {
+        let midnight = day.and_time(NaiveTime::MIN);
+        tz.from_local_datetime(&midnight)
+            .earliest()
+            .map_or_else(|| midnight.and_utc(), |start| start.with_timezone(&Utc))
+    }
+
+    fn memo_timezone(memo: &memos::Model) -> Tz {
+        timezone::parse(&memo.timezone).unwrap_or(Tz::UTC)
+    }
+
+    fn snooze_to_dto(snooze: memo_snoozes::Model) -> MemoSnoozeDto {
+        MemoSnoozeDto {
+            id: snooze.id,
+            snoozed_from: snooze.snoozed_from.into(),
+            snoozed_until: snooze.snoozed_until.into(),
+            preset: snooze.preset,
+            created_at: snooze.created_at.into(),
+        }
+    }
+
+    /// Refuses to complete `id` while any of its blockers is open, listing
+    /// them in the error.
+    async fn ensure_unblocked<C: ConnectionTrait>(db: &C, id: Uuid) -> Result<(), AppError> {
+        let open = MemoDependencyRepository::find_open_blockers(db, &[id]).await?;
+        if open.is_empty() {
+            return Ok(());
+        }
+
+        let blockers: Vec<BlockerDto> = open
+            .into_iter()
+            .map(|(_, blocker)| BlockerDto {
+                id: blocker.id,
+                title: blocker.title,
+            })
+            .collect();
+        tracing::warn!(
+            blockers = blockers.len(),
+            "Refusing to complete blocked memo"
+        );
+
+        Err(AppError::Blocked {
+            message: format!(
+                "Memo with id {} is waiting on {} open memo(s)",
+                id,
+                blockers.len()
+            ),
+            blockers: serde_json::to_value(blockers).unwrap_or_default(),
+        })
+    }
+
+    /// Memos among `candidates` that cannot be completed with the batch: an
+    /// open blocker is outside it, or is itself blocked.
+    async fn blocked_in_batch<C: ConnectionTrait>(
+        db: &C,
+        candidates: &[Uuid],
+    ) -> Result<Vec<Uuid>, AppError> {
+        let open = MemoDependencyRepository::find_open_blockers(db, candidates).await?;
+        let in_batch: HashSet<Uuid> = candidates.iter().copied().collect();
+
+        let mut blocked: Vec<Uuid> = Vec::new();
+        loop {
+            let newly: Vec<Uuid> = open
+                .iter()
+                .filter(|(memo_id, blocker)| {
+                    !blocked.contains(memo_id)
+                        && (!in_batch.contains(&blocker.id) || blocked.contains(&blocker.id))
+                })
+                .map(|(memo_id, _)| *memo_id)
+                .collect();
+            if newly.is_empty() {
+                return Ok(blocked);
+            }
+            for memo_id in newly {
+                if !blocked.contains(&memo_id) {
+                    blocked.push(memo_id);
+                }
+            }
+        }
+    }
+
+    async fn to_dto<C: ConnectionTrait>(
+        db: &C,
+        memo: memos::Model,
+    ) -> Result<MemoResponseDto, AppError> {
+        let mut dtos = Self::to_dtos(db, vec![memo]).await?;
+        Ok(dtos.remove(0))
+    }
+
+    /// DTOs of `memos` with their `blocked_by` and `blocks` links and their
+    /// attachments.
+    async fn to_dtos<C: ConnectionTrait>(
+        db: &C,
+        memos: Vec<memos::Model>,
+    ) -> Result<Vec<MemoResponseDto>, AppError> {
+        let ids: Vec<Uuid> = memos.iter().map(|memo| memo.id).collect();
+        let links = MemoDependencyRepository::find_touching(db, &ids).await?;
+        let attachments = MemoAttachmentRepository::find_by_memos(db, &ids).await?;
+
+        Ok(memos
+            .into_iter()
+            .map(|memo| {
+                let mut dto = Self::entity_to_dto(memo);
+                for link in &links {
+                    if link.memo_id == dto.id {
+                        dto.blocked_by.push(link.blocker_id);
+                    }
+                    if link.blocker_id == dto.id {
+                        dto.blocks.push(link.memo_id);
+                    }
+                }
+                dto.attachments = attachments
+                    .iter()
+                    .filter(|attachment| attachment.memo_id == dto.id)
+                    .cloned()
+                    .map(AttachmentDto::from)
+                    .collect();
+                dto
+            })
+            .collect())
+    }
+
+    /// DTOs of a listing page, with the links and attachments of each memo
+    /// taken from its `memo_list_view` row among `views`.
+    fn listed_dtos(
+        memos: Vec<memos::Model>,
+        views: Vec<memo_list_view::Model>,
+    ) -> Result<Vec<MemoResponseDto>, AppError> {
+        memos
+            .into_iter()
+            .map(|memo| {
+                let mut dto = Self::entity_to_dto(memo);
+                let Some(view) = views.iter().find(|view| view.memo_id == dto.id) else {
+                    return Ok(dto);
+                };
+                let attachments: Vec<AttachmentMeta> =
+                    serde_json::from_value(view.attachments.clone()).map_err(|e| {
+                        AppError::Internal(format!("Listed attachments of memo {}: {}", dto.id, e))
+                    })?;
+                dto.blocked_by = view.blocked_by.clone();
+                dto.blocks = view.blocks.clone();
+                dto.attachments = attachments.into_iter().map(AttachmentDto::from).collect();
+                Ok(dto)
+            })
+            .collect()
     }
 
     fn entity_to_dto(entity: memos::Model) -> MemoResponseDto {
@@ -209,10 +1510,33 @@ impl MemoService {
             id: entity.id,
             title: entity.title,
             description: entity.description,
+            description_html: None,
             date_to: entity.date_to.into(),
+            timezone: entity.timezone,
             completed: entity.completed,
             created_at: entity.created_at.into(),
             updated_at: entity.updated_at.into(),
+            position: entity.position,
+            version: entity.version,
+            archived: entity.archived,
+            pinned: entity.pinned,
+            starred: entity.starred,
+            color: entity.color,
+            completed_at: entity.completed_at.map(Into::into),
+            completion_note: entity.completion_note,
+            blocked_by: Vec::new(),
+            blocks: Vec::new(),
+            attachments: Vec::new(),
+            links: None,
         }
     }
 }
+
+/// `(line, code, message)` of an import line that failed with `error`.
+fn import_failure(line: i32, error: &AppError) -> (i32, String, String) {
+    (
+        line,
+        error.code().as_str().to_string(),
+        error.localized_message(Locale::default()),
+    )
+}
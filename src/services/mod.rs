@@ -1,3 +1,19 @@
+pub mod attachment_service;
+pub mod integration_service;
+pub mod memo_cache;
 pub mod memo_service;
+pub mod notification_service;
+pub mod operation_service;
+pub mod push_service;
+pub mod template_service;
+pub mod unit_of_work;
 
+pub use attachment_service::{AttachmentService, AttachmentVariant};
+pub use integration_service::IntegrationService;
+pub use memo_cache::MemoCache;
 pub use memo_service::MemoService;
+pub use notification_service::NotificationService;
+pub use operation_service::OperationService;
+pub use push_service::PushService;
+pub use template_service::MemoTemplateService;
+pub use unit_of_work::UnitOfWork;
@@ -0,0 +1,65 @@
+use crate::{
+    db::GuardedConnection,
+    dto::{NotificationDto, NotificationListParams, NotificationListResponse},
+    error::{AppError, ErrorCode},
+    repository::NotificationRepository,
+};
+use uuid::Uuid;
+use validator::Validate;
+
+const DEFAULT_LIMIT: u64 = 20;
+
+pub struct NotificationService {
+    db: GuardedConnection,
+}
+
+impl NotificationService {
+    pub fn new(db: GuardedConnection) -> Self {
+        tracing::debug!("Creating NotificationService");
+        Self { db }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn list(
+        &self,
+        params: NotificationListParams,
+    ) -> Result<NotificationListResponse, AppError> {
+        params.validate()?;
+
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+        let offset = params.offset.unwrap_or(0);
+        let (notifications, total) = NotificationRepository::find_page(
+            &self.db,
+            params.unread.unwrap_or(false),
+            limit,
+            offset,
+        )
+        .await?;
+        let unread = NotificationRepository::count_unread(&self.db).await?;
+
+        Ok(NotificationListResponse {
+            has_next: offset + (notifications.len() as u64) < total,
+            data: notifications
+                .into_iter()
+                .map(NotificationDto::from)
+                .collect(),
+            total,
+            unread,
+            limit,
+            offset,
+        })
+    }
+
+    #[tracing::instrument(skip(self), fields(notification_id = %id))]
+    pub async fn mark_read(&self, id: Uuid) -> Result<NotificationDto, AppError> {
+        NotificationRepository::mark_read(&self.db, id)
+            .await?
+            .map(NotificationDto::from)
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::NotificationNotFound,
+                    format!("Notification with id {} not found", id),
+                )
+            })
+    }
+}
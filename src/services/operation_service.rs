@@ -0,0 +1,31 @@
+use crate::{
+    db::GuardedConnection,
+    dto::OperationDto,
+    error::{AppError, ErrorCode},
+    repository::OperationRepository,
+};
+use uuid::Uuid;
+
+pub struct OperationService {
+    db: GuardedConnection,
+}
+
+impl OperationService {
+    pub fn new(db: GuardedConnection) -> Self {
+        tracing::debug!("Creating OperationService");
+        Self { db }
+    }
+
+    #[tracing::instrument(skip(self), fields(operation_id = %id))]
+    pub async fn get_operation(&self, id: Uuid) -> Result<OperationDto, AppError> {
+        OperationRepository::find_by_id(&self.db, id)
+            .await?
+            .map(OperationDto::from)
+            .ok_or_else(|| {
+                AppError::not_found(
+                    ErrorCode::OperationNotFound,
+                    format!("Operation with id {} not found", id),
+                )
+            })
+    }
+}
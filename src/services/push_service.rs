@@ -0,0 +1,42 @@
+use crate::{
+    db::GuardedConnection,
+    dto::{PushSubscriptionDto, PushUnsubscribeDto},
+    error::AppError,
+    repository::PushSubscriptionRepository,
+};
+use validator::Validate;
+
+pub struct PushService {
+    db: GuardedConnection,
+}
+
+impl PushService {
+    pub fn new(db: GuardedConnection) -> Self {
+        tracing::debug!("Creating PushService");
+        Self { db }
+    }
+
+    #[tracing::instrument(skip(self, dto))]
+    pub async fn subscribe(&self, dto: PushSubscriptionDto) -> Result<(), AppError> {
+        dto.validate()?;
+
+        PushSubscriptionRepository::upsert(&self.db, dto.endpoint, dto.keys.p256dh, dto.keys.auth)
+            .await?;
+
+        tracing::info!("Push subscription stored");
+        Ok(())
+    }
+
+    /// Idempotent: unknown endpoints are ignored, since the subscription may
+    /// already have been dropped after the push service reported it gone.
+    #[tracing::instrument(skip(self, dto))]
+    pub async fn unsubscribe(&self, dto: PushUnsubscribeDto) -> Result<(), AppError> {
+        dto.validate()?;
+
+        let removed =
+            PushSubscriptionRepository::delete_by_endpoint(&self.db, &dto.endpoint).await?;
+
+        tracing::info!(removed, "Push subscription removed");
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+use crate::{
+    db::GuardedConnection,
+    dto::{
+        CreateMemoDto, DueDate, InstantiateTemplateDto, MemoResponseDto, MemoTemplateDto,
+        SaveMemoTemplateDto,
+    },
+    entities::memo_templates,
+    error::{AppError, ErrorCode, FieldError},
+    i18n::{ViewerTimezone, timezone},
+    repository::MemoTemplateRepository,
+    services::{MemoCache, MemoService},
+    utils::placeholders,
+};
+use chrono::Utc;
+use chrono_tz::Tz;
+use sea_orm::{DbErr, SqlErr};
+use std::collections::BTreeMap;
+use uuid::Uuid;
+use validator::Validate;
+
+pub struct MemoTemplateService {
+    db: GuardedConnection,
+    memo_cache: MemoCache,
+}
+
+impl MemoTemplateService {
+    pub fn new(db: GuardedConnection) -> Self {
+        tracing::debug!("Creating MemoTemplateService");
+        Self {
+            db,
+            memo_cache: MemoCache::default(),
+        }
+    }
+
+    /// Invalidates `cache` when instantiating creates a memo.
+    pub fn with_memo_cache(mut self, cache: MemoCache) -> Self {
+        self.memo_cache = cache;
+        self
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn list_templates(&self) -> Result<Vec<MemoTemplateDto>, AppError> {
+        let templates = MemoTemplateRepository::find_all(&self.db).await?;
+        Ok(templates.into_iter().map(MemoTemplateDto::from).collect())
+    }
+
+    #[tracing::instrument(skip(self), fields(template_id = %id))]
+    pub async fn get_template(&self, id: Uuid) -> Result<MemoTemplateDto, AppError> {
+        Ok(self.find(id).await?.into())
+    }
+
+    #[tracing::instrument(skip(self, dto), fields(name = %dto.name))]
+    pub async fn create_template(
+        &self,
+        dto: SaveMemoTemplateDto,
+    ) -> Result<MemoTemplateDto, AppError> {
+        let (name, title, description, due_text) = Self::prepare(dto)?;
+
+        let template =
+            MemoTemplateRepository::create(&self.db, name.clone(), title, description, due_text)
+                .await
+                .map_err(|e| Self::name_taken(e, &name))?;
+
+        tracing::info!(template_id = %template.id, "Memo template created");
+        Ok(template.into())
+    }
+
+    #[tracing::instrument(skip(self, dto), fields(template_id = %id, name = %dto.name))]
+    pub async fn update_template(
+        &self,
+        id: Uuid,
+        dto: SaveMemoTemplateDto,
+    ) -> Result<MemoTemplateDto, AppError> {
+        let (name, title, description, due_text) = Self::prepare(dto)?;
+
+        let template = MemoTemplateRepository::update(
+            &self.db,
+            id,
+            name.clone(),
+            title,
+            description,
+            due_text,
+        )
+        .await
+        .map_err(|e| Self::name_taken(e, &name))?
+        .ok_or_else(|| Self::not_found(id))?;
+
+        tracing::info!("Memo template updated");
+        Ok(template.into())
+    }
+
+    #[tracing::instrument(skip(self), fields(template_id = %id))]
+    pub async fn delete_template(&self, id: Uuid) -> Result<(), AppError> {
+        if !MemoTemplateRepository::delete(&self.db, id).await? {
+            return Err(Self::not_found(id));
+        }
+
+        tracing::info!("Memo template deleted");
+        Ok(())
+    }
+
+    /// The template with its placeholders filled in for the current time in
+    /// `tz`, as a memo created from it would read.
+    #[tracing::instrument(skip(self, variables), fields(template_id = %id))]
+    pub async fn render_template(
+        &self,
+        id: Uuid,
+        variables: &BTreeMap<String, String>,
+        tz: Tz,
+    ) -> Result<MemoTemplateDto, AppError> {
+        let mut values = placeholders::builtins(Utc::now().with_timezone(&tz));
+        values.extend(variables.clone());
+
+        let mut template = MemoTemplateDto::from(self.find(id).await?);
+        template.title =
+            placeholders::substitute(&template.title, &values).map_err(AppError::Validation)?;
+        template.description = template
+            .description
+            .map(|description| placeholders::substitute(&description, &values))
+            .transpose()
+            .map_err(AppError::Validation)?;
+        Ok(template)
+    }
+
+    /// Creates a memo from a template. The due date comes from the request
+    /// or, failing that, from the template's `due_text`.
+    #[tracing::instrument(skip(self, dto), fields(template_id = %id))]
+    pub async fn instantiate(
+        &self,
+        id: Uuid,
+        dto: InstantiateTemplateDto,
+    ) -> Result<MemoResponseDto, AppError> {
+        dto.validate()?;
+
+        let tz = match dto.timezone.as_deref() {
+            Some(name) => timezone::parse(name).map_err(AppError::Validation)?,
+            None => ViewerTimezone::current(),
+        };
+        let template = self.render_template(id, &dto.variables, tz).await?;
+
+        let date_to_text = match (&dto.date_to, dto.date_to_text) {
+            (None, None) => template.due_text,
+            (_, text) => text,
+        };
+        let memo = CreateMemoDto {
+            title: template.title,
+            description: template.description,
+            date_to: dto.date_to,
+            date_to_text,
+            timezone: dto.timezone,
+            color: None,
+        };
+
+        let memo = MemoService::new(self.db.clone())
+            .with_cache(self.memo_cache.clone())
+            .create_memo(memo)
+            .await?;
+
+        tracing::info!(memo_id = %memo.id, "Memo created from template");
+        Ok(memo)
+    }
+
+    async fn find(&self, id: Uuid) -> Result<memo_templates::Model, AppError> {
+        MemoTemplateRepository::find_by_id(&self.db, id)
+            .await?
+            .ok_or_else(|| Self::not_found(id))
+    }
+
+    /// Validates a template; `due_text` must be readable now so that memos
+    /// created from it later are too. Title and description are stored as
+    /// given and sanitized like any memo input once substituted.
+    fn prepare(
+        dto: SaveMemoTemplateDto,
+    ) -> Result<(String, String, Option<String>, Option<String>), AppError> {
+        dto.validate()?;
+
+        let due_text = dto
+            .due_text
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty());
+        if let Some(text) = &due_text {
+            DueDate::parse_natural(text, Utc::now().with_timezone(&Tz::UTC))
+                .map_err(AppError::Validation)?;
+        }
+
+        Ok((dto.name, dto.title, dto.description, due_text))
+    }
+
+    fn name_taken(err: DbErr, name: &str) -> AppError {
+        match err.sql_err() {
+            Some(SqlErr::UniqueConstraintViolation(_)) => AppError::InvalidFields {
+                message: format!("A template named '{}' already exists", name),
+                errors: vec![FieldError {
+                    field: "name".to_string(),
+                    code: "unique".to_string(),
+                    message: "Template names must be unique".to_string(),
+                }],
+            },
+            _ => AppError::Database(err),
+        }
+    }
+
+    fn not_found(id: Uuid) -> AppError {
+        AppError::not_found(
+            ErrorCode::TemplateNotFound,
+            format!("Memo template with id {} not found", id),
+        )
+    }
+}
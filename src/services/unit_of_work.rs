@@ -0,0 +1,35 @@
+use crate::error::AppError;
+use sea_orm::{DatabaseTransaction, TransactionTrait};
+
+/// Groups several repository calls into a single database transaction.
+///
+/// Pass [`UnitOfWork::conn`] to the repository methods that make up the
+/// operation, then call [`UnitOfWork::commit`]. Dropping the unit of work
+/// without committing rolls everything back, so an early `?` return never
+/// leaves a half-applied change behind.
+pub struct UnitOfWork {
+    txn: DatabaseTransaction,
+}
+
+impl UnitOfWork {
+    #[tracing::instrument(skip(db))]
+    pub async fn begin<C: TransactionTrait>(db: &C) -> Result<Self, AppError> {
+        tracing::debug!("Beginning unit of work");
+
+        let txn = db.begin().await?;
+        Ok(Self { txn })
+    }
+
+    pub fn conn(&self) -> &DatabaseTransaction {
+        &self.txn
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn commit(self) -> Result<(), AppError> {
+        self.txn.commit().await?;
+
+        tracing::debug!("Unit of work committed");
+
+        Ok(())
+    }
+}
@@ -1,21 +1,123 @@
+use crate::attachments::scan::{UploadScanner, UploadScanners};
+use crate::auth::{ApiKeys, UrlSigner};
 use crate::config::Settings;
+use crate::db::{CircuitBreaker, GuardedConnection, HealthProbes, ReadReplicas, StartupChecks};
+use crate::integrations::{WebhookMessage, WebhookQueue};
+use crate::jobs::JobQueue;
+use crate::middleware::MaintenanceMode;
+use crate::notifications::push::WebPushSender;
+use crate::services::MemoCache;
 use sea_orm::DatabaseConnection;
 use std::time::Instant;
 
 #[derive(Clone)]
 pub struct AppState {
     pub config: Settings,
-    pub db: DatabaseConnection,
+    /// The primary, reporting to the circuit breaker that fails requests
+    /// fast while the database is unreachable
+    pub db: GuardedConnection,
+    /// `DATABASE_REPLICA_URLS`; empty until attached with [`AppState::with_replicas`]
+    pub replicas: ReadReplicas,
+    /// Memo lookups and the index listing; disabled until attached with
+    /// [`AppState::with_memo_cache`]
+    pub memo_cache: MemoCache,
     pub start_time: Instant,
+    pub maintenance: MaintenanceMode,
+    /// Cached, time-limited database checks for the probes
+    pub health: HealthProbes,
+    /// Startup progress reported by `/startup`
+    pub startup: StartupChecks,
+    /// `None` when web push is not configured
+    pub push: Option<WebPushSender>,
+    /// Signs and verifies expiring download URLs
+    pub url_signer: UrlSigner,
+    /// `None` until a webhook sender is attached with [`AppState::with_webhooks`]
+    pub webhooks: Option<WebhookQueue>,
+    /// Runtime the state was created on, which runs the background work;
+    /// `None` outside a runtime
+    pub runtime: Option<tokio::runtime::Handle>,
+    /// `None` until workers are attached with [`AppState::with_jobs`]; requests
+    /// asking to run asynchronously then run synchronously
+    pub jobs: Option<JobQueue>,
+    /// Checks uploads must pass before they are stored
+    pub upload_scanners: UploadScanners,
+    /// Keys from `API_KEYS` with their quota counters
+    pub api_keys: ApiKeys,
 }
 
 impl AppState {
+    /// Guards `db` with a circuit breaker built from the config.
     pub fn new(config: Settings, db: DatabaseConnection) -> Self {
+        let breaker = CircuitBreaker::from_config(&config.database);
+        Self::with_guarded_db(config, GuardedConnection::new(db, breaker))
+    }
+
+    /// Shares `db`, and with it the circuit breaker, e.g. with the job
+    /// workers and the scheduler.
+    pub fn with_guarded_db(config: Settings, db: GuardedConnection) -> Self {
         tracing::debug!("Creating application state");
+        let maintenance = MaintenanceMode::from_config(&config.maintenance);
+        // The key is checked by `Settings::validate`.
+        let push = WebPushSender::from_config(&config.push).ok().flatten();
+        let url_signer = UrlSigner::from_config(&config.url_signing);
+        let health = HealthProbes::from_config(&config.health);
+        let startup = StartupChecks::new(config.scheduler.enabled);
+        let upload_scanners = UploadScanners::from_config(&config.upload_scan);
+        let api_keys = ApiKeys::new(&config.api_keys, db.clone());
         Self {
             config,
             db,
+            replicas: ReadReplicas::default(),
+            memo_cache: MemoCache::default(),
             start_time: Instant::now(),
+            maintenance,
+            health,
+            startup,
+            push,
+            url_signer,
+            webhooks: None,
+            runtime: tokio::runtime::Handle::try_current().ok(),
+            jobs: None,
+            upload_scanners,
+            api_keys,
+        }
+    }
+
+    /// Posts memo events to the registered Slack/Discord webhooks.
+    pub fn with_webhooks(mut self, queue: WebhookQueue) -> Self {
+        self.webhooks = Some(queue);
+        self
+    }
+
+    /// Serves memo listings, search and lookups from read replicas.
+    pub fn with_replicas(mut self, replicas: ReadReplicas) -> Self {
+        self.replicas = replicas;
+        self
+    }
+
+    /// Caches memo lookups; share `cache` with the job queue so its writes
+    /// invalidate it too.
+    pub fn with_memo_cache(mut self, cache: MemoCache) -> Self {
+        self.memo_cache = cache;
+        self
+    }
+
+    /// Runs requests sent with `Prefer: respond-async` as operations.
+    pub fn with_jobs(mut self, queue: JobQueue) -> Self {
+        self.jobs = Some(queue);
+        self
+    }
+
+    /// Runs `scanner` on uploads after the configured scanners.
+    pub fn with_upload_scanner(mut self, scanner: impl UploadScanner + 'static) -> Self {
+        self.upload_scanners = self.upload_scanners.with(scanner);
+        self
+    }
+
+    /// Queues `message` for the registered webhooks; a no-op without a queue.
+    pub async fn notify_webhooks(&self, message: WebhookMessage) {
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.notify(message).await;
         }
     }
 
@@ -0,0 +1,46 @@
+//! Markdown memo descriptions. The raw text is stored as written; it is
+//! rendered to HTML with pulldown-cmark and then cleaned with the
+//! [`sanitize`](super::sanitize) allowlist, so Markdown cannot produce markup
+//! that plain input could not.
+
+use pulldown_cmark::{Options, Parser, html};
+
+use super::sanitize_html;
+
+/// Renders `source` as sanitized HTML. Tables, strikethrough and task lists
+/// are enabled on top of CommonMark.
+pub fn to_html(source: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut rendered = String::with_capacity(source.len() * 3 / 2);
+    html::push_html(&mut rendered, Parser::new_ext(source, options));
+    sanitize_html(&rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_markdown() {
+        let html = to_html("Pay **rent**\n\n- [landlord](https://example.com)\n- `IBAN`");
+        assert!(html.contains("<p>Pay <strong>rent</strong></p>"));
+        assert!(
+            html.contains(
+                "<a href=\"https://example.com\" rel=\"noopener noreferrer\">landlord</a>"
+            )
+        );
+        assert!(html.contains("<code>IBAN</code>"));
+    }
+
+    #[test]
+    fn test_raw_html_and_script_links_are_removed() {
+        let html = to_html(
+            "<script>alert(1)</script>\n\n[click](javascript:alert(1)) <img src=x onerror=alert(1)>",
+        );
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("javascript:"));
+        assert!(!html.contains("onerror"));
+        assert!(html.contains("click"));
+    }
+}
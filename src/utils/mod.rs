@@ -1,3 +1,5 @@
+pub mod markdown;
+pub mod placeholders;
 pub mod sanitize;
 pub mod tracing;
 
@@ -0,0 +1,99 @@
+use chrono::DateTime;
+use chrono_tz::Tz;
+use std::collections::BTreeMap;
+
+/// Placeholders every template can use, for the moment `now`:
+/// `date`, `time`, `weekday`, `month` and `year`.
+pub fn builtins(now: DateTime<Tz>) -> BTreeMap<String, String> {
+    [
+        ("date", "%Y-%m-%d"),
+        ("time", "%H:%M"),
+        ("weekday", "%A"),
+        ("month", "%B"),
+        ("year", "%Y"),
+    ]
+    .into_iter()
+    .map(|(name, format)| (name.to_string(), now.format(format).to_string()))
+    .collect()
+}
+
+/// Replaces each `{{name}}` in `text` with its value; spaces inside the
+/// braces are ignored. Braces around anything but a name are kept as they
+/// are, while an unknown name is an error so typos do not end up in memos.
+pub fn substitute(text: &str, values: &BTreeMap<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let inner = &rest[start + 2..];
+        let Some(end) = inner.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let name = inner[..end].trim();
+        if !is_name(name) {
+            out.push('{');
+            rest = &rest[start + 1..];
+            continue;
+        }
+        match values.get(name) {
+            Some(value) => out.push_str(value),
+            None => return Err(format!("Unknown placeholder '{{{{{}}}}}'", name)),
+        }
+        rest = &inner[end + 2..];
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Placeholder and variable names: ASCII letters, digits and underscores.
+pub fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn values() -> BTreeMap<String, String> {
+        let now = Tz::Europe__Berlin
+            .with_ymd_and_hms(2025, 3, 7, 16, 30, 0)
+            .unwrap();
+        let mut values = builtins(now);
+        values.insert("client".to_string(), "ACME".to_string());
+        values
+    }
+
+    #[test]
+    fn test_substitutes_builtins_and_variables() {
+        assert_eq!(
+            substitute("Invoice {{client}} for {{ month }} {{year}}", &values()).unwrap(),
+            "Invoice ACME for March 2025"
+        );
+        assert_eq!(
+            substitute("{{weekday}} {{date}} {{time}}", &values()).unwrap(),
+            "Friday 2025-03-07 16:30"
+        );
+    }
+
+    #[test]
+    fn test_keeps_braces_that_are_not_placeholders() {
+        assert_eq!(
+            substitute("{{ not a name }} and {{", &values()).unwrap(),
+            "{{ not a name }} and {{"
+        );
+        assert_eq!(substitute("{{{client}}}", &values()).unwrap(), "{ACME}");
+    }
+
+    #[test]
+    fn test_rejects_unknown_placeholders() {
+        assert_eq!(
+            substitute("Call {{cleint}}", &values()).unwrap_err(),
+            "Unknown placeholder '{{cleint}}'"
+        );
+    }
+}
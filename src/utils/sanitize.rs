@@ -1,5 +1,44 @@
+//! HTML sanitization of user-supplied memo text. The services run it on
+//! titles and notes before they are stored, and [`markdown`](super::markdown)
+//! runs it on rendered descriptions, so markup outside the allowlist never
+//! reaches a page or API client; templates still escape whatever they render.
+//!
+//! The allowlist is ammonia's default unless [`init`] installs the one from
+//! `SANITIZE_ALLOWED_TAGS`, once per process.
+
+use ammonia::Builder;
+use std::sync::OnceLock;
+
+use crate::config::settings::SanitizeConfig;
+
+/// Tags removed together with their content; they can never be allowed.
+pub const CONTENT_TAGS: [&str; 2] = ["script", "style"];
+
+static ALLOWED_TAGS: OnceLock<Vec<String>> = OnceLock::new();
+static CLEANER: OnceLock<Builder<'static>> = OnceLock::new();
+
+/// Installs the configured allowlist. Returns whether it replaces the
+/// default; a second call in the same process keeps the first one.
+pub fn init(config: &SanitizeConfig) -> bool {
+    let Some(tags) = &config.allowed_tags else {
+        return false;
+    };
+    let tags = ALLOWED_TAGS.get_or_init(|| tags.clone());
+    let _ = CLEANER.set(builder(tags.iter().map(String::as_str)));
+    true
+}
+
+fn builder<'a>(tags: impl IntoIterator<Item = &'a str>) -> Builder<'a> {
+    let mut builder = Builder::default();
+    builder.tags(tags.into_iter().collect());
+    builder
+}
+
 pub fn sanitize_html(input: &str) -> String {
-    ammonia::clean(input)
+    CLEANER
+        .get_or_init(Builder::default)
+        .clean(input)
+        .to_string()
 }
 
 pub fn sanitize_optional_html(input: Option<&str>) -> Option<String> {
@@ -35,6 +74,17 @@ mod tests {
         assert!(result.contains("</strong>"));
     }
 
+    #[test]
+    fn test_configured_allowlist_replaces_the_default() {
+        let input = "<p>Hello <strong>World</strong> <a href=\"https://example.com\">link</a></p>";
+
+        let result = builder(["strong"]).clean(input).to_string();
+        assert_eq!(result, "Hello <strong>World</strong> link");
+
+        let text_only = builder([]).clean(input).to_string();
+        assert_eq!(text_only, "Hello World link");
+    }
+
     #[test]
     fn test_sanitize_optional_html_none() {
         let result = sanitize_optional_html(None);
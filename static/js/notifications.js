@@ -0,0 +1,47 @@
+// Keeps the header inbox badge current and handles "mark as read" on the
+// notifications page.
+(function () {
+    const POLL_INTERVAL_MS = 60000;
+
+    function showUnread(count) {
+        const badge = document.getElementById('notification-badge');
+        if (!badge) {
+            return;
+        }
+        badge.textContent = count > 99 ? '99+' : String(count);
+        badge.hidden = count === 0;
+    }
+
+    async function refreshBadge() {
+        try {
+            const response = await fetch('/api/v1/notifications?unread=true&limit=1');
+            if (response.ok) {
+                showUnread((await response.json()).unread);
+            }
+        } catch (e) {
+            // Offline or server restarting; try again on the next tick.
+        }
+    }
+
+    document.addEventListener('click', async (e) => {
+        const button = e.target.closest('[data-action="mark-read"]');
+        if (!button) {
+            return;
+        }
+
+        const id = button.dataset.notificationId;
+        const response = await fetch('/api/v1/notifications/' + id + '/read', { method: 'POST' });
+        if (!response.ok) {
+            return;
+        }
+        document.getElementById('notification-' + id)?.classList.remove('unread');
+        button.textContent = button.dataset.readLabel;
+        button.disabled = true;
+        refreshBadge();
+    });
+
+    document.addEventListener('DOMContentLoaded', () => {
+        refreshBadge();
+        setInterval(refreshBadge, POLL_INTERVAL_MS);
+    });
+})();
@@ -0,0 +1,60 @@
+// Offers browser notifications for due memos when the server has web push
+// configured (GET /api/v1/push/public-key answers 404 otherwise).
+(function () {
+    if (!('serviceWorker' in navigator) || !('PushManager' in window)) {
+        return;
+    }
+
+    function urlBase64ToUint8Array(value) {
+        const padded = value + '='.repeat((4 - (value.length % 4)) % 4);
+        const raw = atob(padded.replace(/-/g, '+').replace(/_/g, '/'));
+        return Uint8Array.from(raw, (c) => c.charCodeAt(0));
+    }
+
+    function markEnabled(button) {
+        button.textContent = button.dataset.enabledLabel;
+        button.disabled = true;
+    }
+
+    async function init() {
+        const button = document.getElementById('push-toggle');
+        if (!button) {
+            return;
+        }
+
+        const response = await fetch('/api/v1/push/public-key');
+        if (!response.ok) {
+            return;
+        }
+        const { public_key: publicKey } = await response.json();
+
+        const registration = await navigator.serviceWorker.register('/static/sw.js');
+        button.hidden = false;
+        if (await registration.pushManager.getSubscription()) {
+            markEnabled(button);
+            return;
+        }
+
+        button.addEventListener('click', async () => {
+            if ((await Notification.requestPermission()) !== 'granted') {
+                return;
+            }
+            const subscription = await registration.pushManager.subscribe({
+                userVisibleOnly: true,
+                applicationServerKey: urlBase64ToUint8Array(publicKey),
+            });
+            const saved = await fetch('/api/v1/push/subscribe', {
+                method: 'POST',
+                headers: { 'Content-Type': 'application/json' },
+                body: JSON.stringify(subscription.toJSON()),
+            });
+            if (saved.ok) {
+                markEnabled(button);
+            }
+        });
+    }
+
+    document.addEventListener('DOMContentLoaded', () => {
+        init().catch((e) => console.warn('Push notifications unavailable:', e));
+    });
+})();
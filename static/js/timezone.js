@@ -0,0 +1,16 @@
+// Remembers the browser's timezone in the `tz` cookie, which the server
+// renders due dates and reads `datetime-local` form values in. A change
+// takes effect from the next request.
+(function () {
+    const timezone = Intl.DateTimeFormat().resolvedOptions().timeZone;
+    if (!timezone) {
+        return;
+    }
+
+    const current = document.cookie
+        .split('; ')
+        .find((cookie) => cookie.startsWith('tz='));
+    if (current !== 'tz=' + encodeURIComponent(timezone)) {
+        document.cookie = 'tz=' + encodeURIComponent(timezone) + '; path=/; max-age=31536000; samesite=lax';
+    }
+})();
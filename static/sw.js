@@ -0,0 +1,17 @@
+// Service worker for memo due-date notifications. Payloads are the JSON
+// `PushPayload` sent by the `due_push_notifications` scheduler task.
+self.addEventListener('push', (event) => {
+    const data = event.data ? event.data.json() : {};
+    event.waitUntil(
+        self.registration.showNotification(data.title || 'Memos', {
+            body: data.body,
+            tag: data.tag,
+            data: { url: data.url || '/' },
+        })
+    );
+});
+
+self.addEventListener('notificationclick', (event) => {
+    event.notification.close();
+    event.waitUntil(clients.openWindow(event.notification.data.url));
+});
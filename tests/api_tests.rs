@@ -1,19 +1,31 @@
+mod common;
+
 use actix_web::{App, test, web};
 use actix_web_template::{
-    config::Settings,
-    dto::{CreateMemoDto, MemoResponseDto, PaginatedResponse, PatchMemoDto, UpdateMemoDto},
+    dto::{
+        BatchCompleteResponse, DeleteMemosResponse, MemoResponseDto, MemoTemplateDto, OperationDto,
+        OperationStatus, PaginatedResponse, PatchMemoDto, UndoResponse, UpdateMemoDto,
+    },
     handlers,
+    integrations::{
+        WebhookQueue, WebhookSender,
+        webhooks::{DeliveryError, WebhookTransport},
+    },
+    jobs::JobQueue,
+    middleware::{AssignRequestId, DatabaseCircuit, LocaleNegotiation},
+    services::{MemoCache, MemoService},
     state::AppState,
 };
 use chrono::Utc;
-use sea_orm::Database;
+use chrono_tz::Tz;
+use common::{TestDb, fixtures::MemoFixture};
+use futures_util::StreamExt;
 
 #[tokio::test]
 async fn test_create_memo_endpoint() {
-    let settings = Settings::load().expect("Failed to load settings");
-    let db = Database::connect(&settings.database.url)
-        .await
-        .expect("Failed to connect to database");
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
     let state = AppState::new(settings.clone(), db);
 
     let app = test::init_service(
@@ -25,11 +37,9 @@ async fn test_create_memo_endpoint() {
     )
     .await;
 
-    let create_dto = CreateMemoDto {
-        title: "Test API Memo".to_string(),
-        description: Some("Created via API test".to_string()),
-        date_to: Utc::now(),
-    };
+    let create_dto = MemoFixture::new("Test API Memo")
+        .description("Created via API test")
+        .build();
 
     let req = test::TestRequest::post()
         .uri("/api/v1/memos")
@@ -43,19 +53,135 @@ async fn test_create_memo_endpoint() {
     assert_eq!(memo.title, "Test API Memo");
     assert_eq!(memo.description, Some("Created via API test".to_string()));
     assert!(!memo.completed);
+}
 
-    let delete_req = test::TestRequest::delete()
-        .uri(&format!("/api/v1/memos/{}", memo.id))
+#[tokio::test]
+async fn test_local_due_dates_use_the_memo_timezone_and_render_in_the_viewers() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings.clone(), db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .wrap(LocaleNegotiation)
+            .service(handlers::create_memo)
+            .service(handlers::get_memo)
+            .service(handlers::patch_memo)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(serde_json::json!({
+            "title": "Berlin lunch",
+            "date_to": "2025-07-01T12:00",
+            "timezone": "Europe/Berlin",
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let created: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(created["date_to"], "2025-07-01T10:00:00Z");
+    assert_eq!(created["timezone"], "Europe/Berlin");
+    let id = created["id"].as_str().unwrap().to_string();
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", id))
+        .insert_header(("Time-Zone", "America/New_York"))
+        .to_request();
+    let memo: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(memo["date_to"], "2025-07-01T06:00:00-04:00");
+
+    // Local times skipped by the spring-forward change name no instant
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}", id))
+        .set_json(serde_json::json!({ "date_to": "2025-03-30T02:30" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(serde_json::json!({
+            "title": "Nowhere",
+            "date_to": "2025-07-01T12:00",
+            "timezone": "Mars/Olympus_Mons",
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn test_natural_language_due_dates() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings.clone(), db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .wrap(LocaleNegotiation)
+            .service(handlers::create_memo)
+            .service(handlers::patch_memo)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(serde_json::json!({
+            "title": "Call the bank",
+            "date_to_text": "tomorrow 5pm",
+            "timezone": "Asia/Tokyo",
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let created: MemoResponseDto = test::read_body_json(resp).await;
+    let tomorrow = Utc::now().with_timezone(&Tz::Asia__Tokyo).date_naive() + chrono::Days::new(1);
+    let expected = tomorrow
+        .and_hms_opt(17, 0, 0)
+        .unwrap()
+        .and_local_timezone(Tz::Asia__Tokyo)
+        .unwrap();
+    assert_eq!(created.date_to, expected);
+
+    // A bare hour could be morning or evening
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}", created.id))
+        .set_json(serde_json::json!({ "date_to_text": "friday 5" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}", created.id))
+        .set_json(serde_json::json!({
+            "date_to": "2025-07-01T12:00",
+            "date_to_text": "next friday",
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(serde_json::json!({ "title": "No due date" }))
         .to_request();
-    test::call_service(&app, delete_req).await;
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
 }
 
 #[tokio::test]
 async fn test_get_memo_endpoint() {
-    let settings = Settings::load().expect("Failed to load settings");
-    let db = Database::connect(&settings.database.url)
-        .await
-        .expect("Failed to connect to database");
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
     let state = AppState::new(settings.clone(), db);
 
     let app = test::init_service(
@@ -68,11 +194,7 @@ async fn test_get_memo_endpoint() {
     )
     .await;
 
-    let create_dto = CreateMemoDto {
-        title: "Get Test Memo".to_string(),
-        description: None,
-        date_to: Utc::now(),
-    };
+    let create_dto = MemoFixture::new("Get Test Memo").build();
 
     let create_req = test::TestRequest::post()
         .uri("/api/v1/memos")
@@ -92,19 +214,136 @@ async fn test_get_memo_endpoint() {
     let memo: MemoResponseDto = test::read_body_json(get_resp).await;
     assert_eq!(memo.id, created_memo.id);
     assert_eq!(memo.title, "Get Test Memo");
+}
 
-    let delete_req = test::TestRequest::delete()
-        .uri(&format!("/api/v1/memos/{}", memo.id))
+#[tokio::test]
+async fn test_get_memo_negotiates_xml_and_yaml() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings.clone(), db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::list_memos)
+            .service(handlers::get_memo)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let create_dto = MemoFixture::new("Negotiated Memo").build();
+    let create_req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(&create_dto)
+        .to_request();
+    let created: MemoResponseDto =
+        test::read_body_json(test::call_service(&app, create_req).await).await;
+
+    let xml_req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", created.id))
+        .insert_header(("Accept", "application/xml"))
+        .to_request();
+    let xml_resp = test::call_service(&app, xml_req).await;
+    assert_eq!(xml_resp.status(), 200);
+    assert_eq!(
+        xml_resp.headers().get("content-type").unwrap(),
+        "application/xml"
+    );
+    assert!(xml_resp.headers().contains_key("etag"));
+    let xml = String::from_utf8(test::read_body(xml_resp).await.to_vec()).unwrap();
+    assert!(xml.starts_with("<memo>"));
+    assert!(xml.contains("<title>Negotiated Memo</title>"));
+
+    let yaml_req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", created.id))
+        .insert_header(("Accept", "application/json;q=0.5, application/yaml"))
+        .to_request();
+    let yaml_resp = test::call_service(&app, yaml_req).await;
+    assert_eq!(
+        yaml_resp.headers().get("content-type").unwrap(),
+        "application/yaml"
+    );
+    let yaml = String::from_utf8(test::read_body(yaml_resp).await.to_vec()).unwrap();
+    assert!(yaml.contains(&format!("id: {}", created.id)));
+
+    let list_req = test::TestRequest::get()
+        .uri("/api/v1/memos?q=Negotiated")
+        .insert_header(("Accept", "text/xml"))
+        .to_request();
+    let list_resp = test::call_service(&app, list_req).await;
+    assert_eq!(list_resp.status(), 200);
+    assert!(list_resp.headers().contains_key("link"));
+    let xml = String::from_utf8(test::read_body(list_resp).await.to_vec()).unwrap();
+    assert!(xml.starts_with("<memos><data>"));
+}
+
+#[tokio::test]
+async fn test_memo_endpoints_accept_and_return_binary_encodings() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings.clone(), db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::patch_memo)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let create_dto = MemoFixture::new("MessagePack Memo")
+        .description("binary")
+        .build();
+    let create_req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .insert_header(("Content-Type", "application/msgpack"))
+        .insert_header(("Accept", "application/msgpack"))
+        .set_payload(rmp_serde::to_vec_named(&create_dto).unwrap())
+        .to_request();
+    let create_resp = test::call_service(&app, create_req).await;
+    assert_eq!(create_resp.status(), 201);
+    assert_eq!(
+        create_resp.headers().get("content-type").unwrap(),
+        "application/msgpack"
+    );
+    let created: MemoResponseDto =
+        rmp_serde::from_slice(&test::read_body(create_resp).await).unwrap();
+    assert_eq!(created.title, "MessagePack Memo");
+    assert_eq!(created.description.as_deref(), Some("binary"));
+
+    let patch = serde_json::json!({ "completed": true });
+    let mut cbor = Vec::new();
+    ciborium::into_writer(&patch, &mut cbor).unwrap();
+    let patch_req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}", created.id))
+        .insert_header(("Content-Type", "application/cbor"))
+        .insert_header(("Accept", "application/cbor"))
+        .set_payload(cbor)
+        .to_request();
+    let patch_resp = test::call_service(&app, patch_req).await;
+    assert_eq!(patch_resp.status(), 200);
+    let patched: MemoResponseDto =
+        ciborium::from_reader(test::read_body(patch_resp).await.as_ref()).unwrap();
+    assert!(patched.completed);
+
+    let bad_req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .insert_header(("Content-Type", "application/msgpack"))
+        .set_payload(vec![0xc1])
         .to_request();
-    test::call_service(&app, delete_req).await;
+    let bad_resp = test::call_service(&app, bad_req).await;
+    assert_eq!(bad_resp.status(), 400);
 }
 
 #[tokio::test]
 async fn test_get_memo_not_found() {
-    let settings = Settings::load().expect("Failed to load settings");
-    let db = Database::connect(&settings.database.url)
-        .await
-        .expect("Failed to connect to database");
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
     let state = AppState::new(settings, db);
 
     let app = test::init_service(
@@ -121,14 +360,16 @@ async fn test_get_memo_not_found() {
 
     let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), 404);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "MEMO_NOT_FOUND");
 }
 
 #[tokio::test]
 async fn test_update_memo_endpoint() {
-    let settings = Settings::load().expect("Failed to load settings");
-    let db = Database::connect(&settings.database.url)
-        .await
-        .expect("Failed to connect to database");
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
     let state = AppState::new(settings.clone(), db);
 
     let app = test::init_service(
@@ -141,11 +382,9 @@ async fn test_update_memo_endpoint() {
     )
     .await;
 
-    let create_dto = CreateMemoDto {
-        title: "Original Title".to_string(),
-        description: Some("Original description".to_string()),
-        date_to: Utc::now(),
-    };
+    let create_dto = MemoFixture::new("Original Title")
+        .description("Original description")
+        .build();
 
     let create_req = test::TestRequest::post()
         .uri("/api/v1/memos")
@@ -158,8 +397,11 @@ async fn test_update_memo_endpoint() {
     let update_dto = UpdateMemoDto {
         title: "Updated Title".to_string(),
         description: Some("Updated description".to_string()),
-        date_to: Utc::now(),
+        date_to: Utc::now().into(),
         completed: true,
+        version: None,
+        timezone: None,
+        color: None,
     };
 
     let update_req = test::TestRequest::put()
@@ -177,19 +419,13 @@ async fn test_update_memo_endpoint() {
         Some("Updated description".to_string())
     );
     assert!(updated_memo.completed);
-
-    let delete_req = test::TestRequest::delete()
-        .uri(&format!("/api/v1/memos/{}", created_memo.id))
-        .to_request();
-    test::call_service(&app, delete_req).await;
 }
 
 #[tokio::test]
 async fn test_patch_memo_endpoint() {
-    let settings = Settings::load().expect("Failed to load settings");
-    let db = Database::connect(&settings.database.url)
-        .await
-        .expect("Failed to connect to database");
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
     let state = AppState::new(settings.clone(), db);
 
     let app = test::init_service(
@@ -202,11 +438,9 @@ async fn test_patch_memo_endpoint() {
     )
     .await;
 
-    let create_dto = CreateMemoDto {
-        title: "Original Title".to_string(),
-        description: Some("Original description".to_string()),
-        date_to: Utc::now(),
-    };
+    let create_dto = MemoFixture::new("Original Title")
+        .description("Original description")
+        .build();
 
     let create_req = test::TestRequest::post()
         .uri("/api/v1/memos")
@@ -220,7 +454,11 @@ async fn test_patch_memo_endpoint() {
         title: Some("Patched Title".to_string()),
         description: None,
         date_to: None,
+        date_to_text: None,
         completed: None,
+        version: None,
+        timezone: None,
+        color: None,
     };
 
     let patch_req = test::TestRequest::patch()
@@ -239,122 +477,194 @@ async fn test_patch_memo_endpoint() {
     );
     assert!(!patched_memo.completed);
 
-    let delete_req = test::TestRequest::delete()
+    // The same patch again changes nothing.
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}", created_memo.id))
+        .set_json(&patch_dto)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let unchanged: MemoResponseDto = test::read_body_json(resp).await;
+    assert_eq!(unchanged.version, patched_memo.version);
+    assert_eq!(unchanged.updated_at, patched_memo.updated_at);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}", created_memo.id))
+        .insert_header(("Prefer", "return=minimal"))
+        .set_json(&patch_dto)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 304);
+    assert_eq!(
+        resp.headers().get("etag").unwrap(),
+        &format!("\"{}\"", patched_memo.version)
+    );
+
+    let req = test::TestRequest::patch()
         .uri(&format!("/api/v1/memos/{}", created_memo.id))
+        .set_json(serde_json::json!({ "version": patched_memo.version }))
         .to_request();
-    test::call_service(&app, delete_req).await;
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["code"], "empty");
 }
 
 #[tokio::test]
-async fn test_delete_memo_endpoint() {
-    let settings = Settings::load().expect("Failed to load settings");
-    let db = Database::connect(&settings.database.url)
-        .await
-        .expect("Failed to connect to database");
-    let state = AppState::new(settings.clone(), db);
+async fn test_future_due_dates_can_be_required() {
+    let test_db = TestDb::new().await;
+    let mut settings = test_db.settings.clone();
+    settings.api.require_future_due_dates = true;
+    let state = AppState::new(settings, test_db.db.clone());
 
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(state))
-            .app_data(web::JsonConfig::default().limit(1048576))
-            .service(handlers::create_memo)
-            .service(handlers::delete_memo)
-            .service(handlers::get_memo),
+            .service(handlers::create_memo),
     )
     .await;
 
-    let create_dto = CreateMemoDto {
-        title: "To Delete".to_string(),
-        description: None,
-        date_to: Utc::now(),
-    };
+    let memo = |date_to: chrono::DateTime<Utc>| serde_json::json!({ "title": "Due date", "date_to": date_to.to_rfc3339() });
 
-    let create_req = test::TestRequest::post()
+    let req = test::TestRequest::post()
         .uri("/api/v1/memos")
-        .set_json(&create_dto)
+        .set_json(memo(Utc::now() - chrono::Duration::days(1)))
         .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["field"], "date_to");
+    assert_eq!(body["errors"][0]["code"], "future");
 
-    let create_resp = test::call_service(&app, create_req).await;
-    let created_memo: MemoResponseDto = test::read_body_json(create_resp).await;
-
-    let delete_req = test::TestRequest::delete()
-        .uri(&format!("/api/v1/memos/{}", created_memo.id))
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos?historical=true")
+        .set_json(memo(Utc::now() - chrono::Duration::days(1)))
         .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
 
-    let delete_resp = test::call_service(&app, delete_req).await;
-    assert_eq!(delete_resp.status(), 204);
-
-    let get_req = test::TestRequest::get()
-        .uri(&format!("/api/v1/memos/{}", created_memo.id))
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(memo(Utc::now() + chrono::Duration::days(1)))
         .to_request();
-
-    let get_resp = test::call_service(&app, get_req).await;
-    assert_eq!(get_resp.status(), 404);
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
 }
 
 #[tokio::test]
-async fn test_toggle_complete_endpoint() {
-    let settings = Settings::load().expect("Failed to load settings");
-    let db = Database::connect(&settings.database.url)
-        .await
-        .expect("Failed to connect to database");
-    let state = AppState::new(settings.clone(), db);
+async fn test_description_html_is_rendered_on_request() {
+    let test_db = TestDb::new().await;
+    let state = AppState::new(test_db.settings.clone(), test_db.db.clone());
 
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(state))
-            .app_data(web::JsonConfig::default().limit(1048576))
             .service(handlers::create_memo)
-            .service(handlers::toggle_complete)
-            .service(handlers::delete_memo),
+            .service(handlers::get_memo)
+            .service(handlers::list_memos),
     )
     .await;
 
-    let create_dto = CreateMemoDto {
-        title: "Toggle Test".to_string(),
-        description: None,
-        date_to: Utc::now(),
-    };
-
-    let create_req = test::TestRequest::post()
+    let req = test::TestRequest::post()
         .uri("/api/v1/memos")
-        .set_json(&create_dto)
+        .set_json(serde_json::json!({
+            "title": "Markdown",
+            "description": "Pay **rent** <script>alert(1)</script>",
+            "date_to": "2030-01-31T12:00:00Z",
+        }))
         .to_request();
+    let created: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(
+        created["description"],
+        "Pay **rent** <script>alert(1)</script>"
+    );
+    assert!(created.get("description_html").is_none());
+
+    let id = created["id"].as_str().unwrap();
+    for uri in [
+        format!("/api/v1/memos/{id}?description_html=true"),
+        "/api/v1/memos?q=Markdown&description_html=true".to_string(),
+    ] {
+        let req = test::TestRequest::get().uri(&uri).to_request();
+        let body: serde_json::Value =
+            test::read_body_json(test::call_service(&app, req).await).await;
+        let memo = body.get("data").map_or(&body, |data| &data[0]);
+        assert_eq!(
+            memo["description_html"], "<p>Pay <strong>rent</strong> </p>\n",
+            "{uri}"
+        );
+    }
+}
 
-    let create_resp = test::call_service(&app, create_req).await;
-    let created_memo: MemoResponseDto = test::read_body_json(create_resp).await;
-    assert!(!created_memo.completed);
+#[tokio::test]
+async fn test_memo_color_labels() {
+    let test_db = TestDb::new().await;
+    let state = AppState::new(test_db.settings.clone(), test_db.db.clone());
 
-    let toggle_req = test::TestRequest::patch()
-        .uri(&format!("/api/v1/memos/{}/complete", created_memo.id))
-        .to_request();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::patch_memo)
+            .service(handlers::list_memos),
+    )
+    .await;
 
-    let toggle_resp = test::call_service(&app, toggle_req).await;
-    assert_eq!(toggle_resp.status(), 200);
+    let create = |title: &str, color: Option<&str>| {
+        test::TestRequest::post()
+            .uri("/api/v1/memos")
+            .set_json(serde_json::json!({
+                "title": title,
+                "date_to": "2030-01-31T12:00:00Z",
+                "color": color,
+            }))
+            .to_request()
+    };
 
-    let toggled_memo: MemoResponseDto = test::read_body_json(toggle_resp).await;
-    assert!(toggled_memo.completed);
+    let resp = test::call_service(&app, create("ColorLabel teal", Some("teal"))).await;
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["field"], "color");
+    assert_eq!(body["errors"][0]["code"], "color");
 
-    let toggle_req2 = test::TestRequest::patch()
-        .uri(&format!("/api/v1/memos/{}/complete", created_memo.id))
+    let blue: serde_json::Value = test::read_body_json(
+        test::call_service(&app, create("ColorLabel blue", Some("blue"))).await,
+    )
+    .await;
+    assert_eq!(blue["color"], "blue");
+    let plain: serde_json::Value =
+        test::read_body_json(test::call_service(&app, create("ColorLabel plain", None)).await)
+            .await;
+    assert!(plain["color"].is_null());
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos?q=ColorLabel&color=blue")
         .to_request();
+    let body: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["data"][0]["id"], blue["id"]);
 
-    let toggle_resp2 = test::call_service(&app, toggle_req2).await;
-    let toggled_memo2: MemoResponseDto = test::read_body_json(toggle_resp2).await;
-    assert!(!toggled_memo2.completed);
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos?color=teal")
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
 
-    let delete_req = test::TestRequest::delete()
-        .uri(&format!("/api/v1/memos/{}", created_memo.id))
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}", blue["id"].as_str().unwrap()))
+        .set_json(serde_json::json!({ "color": "" }))
         .to_request();
-    test::call_service(&app, delete_req).await;
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["color"].is_null());
 }
 
 #[tokio::test]
-async fn test_list_memos_endpoint() {
-    let settings = Settings::load().expect("Failed to load settings");
-    let db = Database::connect(&settings.database.url)
-        .await
-        .expect("Failed to connect to database");
+async fn test_delete_memo_endpoint() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
     let state = AppState::new(settings.clone(), db);
 
     let app = test::init_service(
@@ -362,30 +672,341 @@ async fn test_list_memos_endpoint() {
             .app_data(web::Data::new(state))
             .app_data(web::JsonConfig::default().limit(1048576))
             .service(handlers::create_memo)
-            .service(handlers::list_memos)
-            .service(handlers::delete_memo),
+            .service(handlers::delete_memo)
+            .service(handlers::get_memo),
     )
     .await;
 
-    let create_dto1 = CreateMemoDto {
-        title: "List Test 1".to_string(),
-        description: None,
-        date_to: Utc::now(),
-    };
+    let create_dto = MemoFixture::new("To Delete").build();
 
-    let create_req1 = test::TestRequest::post()
+    let create_req = test::TestRequest::post()
         .uri("/api/v1/memos")
-        .set_json(&create_dto1)
+        .set_json(&create_dto)
         .to_request();
 
-    let create_resp1 = test::call_service(&app, create_req1).await;
-    let memo1: MemoResponseDto = test::read_body_json(create_resp1).await;
-
-    let create_dto2 = CreateMemoDto {
-        title: "List Test 2".to_string(),
-        description: None,
-        date_to: Utc::now(),
-    };
+    let create_resp = test::call_service(&app, create_req).await;
+    let created_memo: MemoResponseDto = test::read_body_json(create_resp).await;
+
+    let delete_req = test::TestRequest::delete()
+        .uri(&format!("/api/v1/memos/{}", created_memo.id))
+        .to_request();
+
+    let delete_resp = test::call_service(&app, delete_req).await;
+    assert_eq!(delete_resp.status(), 200);
+    let deleted: DeleteMemosResponse = test::read_body_json(delete_resp).await;
+    assert_eq!(deleted.deleted, vec![created_memo.id]);
+    assert!(deleted.not_found.is_empty());
+    assert!(deleted.undo_token.is_some());
+
+    let get_req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", created_memo.id))
+        .to_request();
+
+    let get_resp = test::call_service(&app, get_req).await;
+    assert_eq!(get_resp.status(), 404);
+
+    let delete_req = test::TestRequest::delete()
+        .uri(&format!("/api/v1/memos/{}", created_memo.id))
+        .to_request();
+    let delete_resp = test::call_service(&app, delete_req).await;
+    assert_eq!(delete_resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_bulk_delete_can_be_undone() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let service = MemoService::new(db.clone().into());
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::delete_memos)
+            .service(handlers::undo_delete)
+            .service(handlers::list_memos)
+            .service(handlers::get_memo),
+    )
+    .await;
+
+    let first = service
+        .create_memo(MemoFixture::new("Bulk delete first").build())
+        .await
+        .unwrap();
+    let second = service
+        .create_memo(MemoFixture::new("Bulk delete second").build())
+        .await
+        .unwrap();
+    let missing = uuid::Uuid::new_v4();
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos/delete")
+        .set_json(serde_json::json!({ "ids": [first.id, second.id, missing] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let deleted: DeleteMemosResponse = test::read_body_json(resp).await;
+    assert_eq!(deleted.deleted.len(), 2);
+    assert!(deleted.deleted.contains(&first.id) && deleted.deleted.contains(&second.id));
+    assert_eq!(deleted.not_found, vec![missing]);
+    let token = deleted
+        .undo_token
+        .expect("deleting memos should issue an undo token");
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos?q=Bulk%20delete")
+        .to_request();
+    let page: PaginatedResponse<MemoResponseDto> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert!(
+        page.data
+            .iter()
+            .all(|memo| memo.id != first.id && memo.id != second.id)
+    );
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/undo/{token}"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let undone: UndoResponse = test::read_body_json(resp).await;
+    let restored: Vec<_> = undone.restored.iter().map(|memo| memo.id).collect();
+    assert_eq!(restored, vec![first.id, second.id]);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", second.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    // Tokens are single use.
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/undo/{token}"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    // Nothing to delete, so no token is issued.
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos/delete")
+        .set_json(serde_json::json!({ "ids": [missing] }))
+        .to_request();
+    let deleted: DeleteMemosResponse =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert!(deleted.deleted.is_empty());
+    assert!(deleted.undo_token.is_none());
+}
+
+#[tokio::test]
+async fn test_bulk_complete_runs_as_operation_when_asked() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let service = MemoService::new(db.clone().into());
+    let (jobs, workers) = JobQueue::start(
+        db.clone().into(),
+        &settings.jobs,
+        settings.undo.window(),
+        MemoCache::default(),
+        None,
+    );
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone().with_jobs(jobs)))
+            .service(handlers::complete_memos)
+            .service(handlers::get_operation)
+            .service(handlers::operation_events),
+    )
+    .await;
+
+    let mut ids = Vec::new();
+    for title in ["Async batch first", "Async batch second"] {
+        let memo = service
+            .create_memo(MemoFixture::new(title).build())
+            .await
+            .unwrap();
+        ids.push(memo.id);
+    }
+    let missing = uuid::Uuid::new_v4();
+
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/memos/complete")
+        .insert_header(("Prefer", "respond-async, wait=10"))
+        .set_json(serde_json::json!({ "ids": [ids[0], ids[1], missing] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 202);
+    assert_eq!(
+        resp.headers().get("Preference-Applied").unwrap(),
+        "respond-async"
+    );
+    let location = resp
+        .headers()
+        .get("Location")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let operation: OperationDto = test::read_body_json(resp).await;
+    assert_eq!(location, format!("/api/v1/operations/{}", operation.id));
+    assert_eq!(operation.kind, "complete_memos");
+    assert_eq!(operation.total, Some(3));
+
+    let mut operation = operation;
+    for _ in 0..100 {
+        if matches!(
+            operation.status,
+            OperationStatus::Succeeded | OperationStatus::Failed
+        ) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let req = test::TestRequest::get().uri(&location).to_request();
+        operation = test::read_body_json(test::call_service(&app, req).await).await;
+    }
+    assert_eq!(
+        operation.status,
+        OperationStatus::Succeeded,
+        "{:?}",
+        operation
+    );
+    assert_eq!(operation.processed, 3);
+    assert_eq!(operation.percent, Some(100));
+    assert!(operation.finished_at.is_some());
+
+    // The stream of a finished operation is its final event.
+    let req = test::TestRequest::get()
+        .uri(&format!("{location}/events"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("Content-Type").unwrap(),
+        "text/event-stream"
+    );
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    let data = body
+        .strip_prefix("event: succeeded\ndata: ")
+        .and_then(|rest| rest.strip_suffix("\n\n"))
+        .unwrap_or_else(|| panic!("unexpected stream: {body}"));
+    let streamed: OperationDto = serde_json::from_str(data).unwrap();
+    assert_eq!(streamed.id, operation.id);
+    assert_eq!(streamed.status, OperationStatus::Succeeded);
+    let result: BatchCompleteResponse = serde_json::from_value(operation.result.unwrap()).unwrap();
+    assert_eq!(result.updated.len(), 2);
+    assert_eq!(result.not_found, vec![missing]);
+
+    // Invalid batches are rejected up front instead of failing later.
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/memos/complete")
+        .insert_header(("Prefer", "respond-async"))
+        .set_json(serde_json::json!({}))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    let unknown = format!("/api/v1/operations/{}", uuid::Uuid::new_v4());
+    let req = test::TestRequest::get().uri(&unknown).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+    let req = test::TestRequest::get()
+        .uri(&format!("{unknown}/events"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    // Without workers the preference is ignored.
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::complete_memos),
+    )
+    .await;
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/memos/complete")
+        .insert_header(("Prefer", "respond-async"))
+        .set_json(serde_json::json!({ "ids": [ids[0]] }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 200);
+
+    for worker in workers {
+        worker.abort();
+    }
+}
+
+#[tokio::test]
+async fn test_toggle_complete_endpoint() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings.clone(), db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .app_data(web::JsonConfig::default().limit(1048576))
+            .service(handlers::create_memo)
+            .service(handlers::toggle_complete)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let create_dto = MemoFixture::new("Toggle Test").build();
+
+    let create_req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(&create_dto)
+        .to_request();
+
+    let create_resp = test::call_service(&app, create_req).await;
+    let created_memo: MemoResponseDto = test::read_body_json(create_resp).await;
+    assert!(!created_memo.completed);
+
+    let toggle_req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}/complete", created_memo.id))
+        .to_request();
+
+    let toggle_resp = test::call_service(&app, toggle_req).await;
+    assert_eq!(toggle_resp.status(), 200);
+
+    let toggled_memo: MemoResponseDto = test::read_body_json(toggle_resp).await;
+    assert!(toggled_memo.completed);
+
+    let toggle_req2 = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}/complete", created_memo.id))
+        .to_request();
+
+    let toggle_resp2 = test::call_service(&app, toggle_req2).await;
+    let toggled_memo2: MemoResponseDto = test::read_body_json(toggle_resp2).await;
+    assert!(!toggled_memo2.completed);
+}
+
+#[tokio::test]
+async fn test_list_memos_endpoint() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings.clone(), db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .app_data(web::JsonConfig::default().limit(1048576))
+            .service(handlers::create_memo)
+            .service(handlers::list_memos)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let create_dto1 = MemoFixture::new("List Test 1").build();
+
+    let create_req1 = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(&create_dto1)
+        .to_request();
+
+    let create_resp1 = test::call_service(&app, create_req1).await;
+    let memo1: MemoResponseDto = test::read_body_json(create_resp1).await;
+
+    let create_dto2 = MemoFixture::new("List Test 2").build();
 
     let create_req2 = test::TestRequest::post()
         .uri("/api/v1/memos")
@@ -401,28 +1022,23 @@ async fn test_list_memos_endpoint() {
 
     let list_resp = test::call_service(&app, list_req).await;
     assert_eq!(list_resp.status(), 200);
+    let link = list_resp.headers().get("Link").unwrap().to_str().unwrap();
+    assert!(link.contains("rel=\"first\""));
+    assert!(link.contains("sort_by=created_at"));
 
     let response: PaginatedResponse<MemoResponseDto> = test::read_body_json(list_resp).await;
-    assert!(response.total >= 2);
-    assert!(!response.data.is_empty());
-
-    let delete_req1 = test::TestRequest::delete()
-        .uri(&format!("/api/v1/memos/{}", memo1.id))
-        .to_request();
-    test::call_service(&app, delete_req1).await;
-
-    let delete_req2 = test::TestRequest::delete()
-        .uri(&format!("/api/v1/memos/{}", memo2.id))
-        .to_request();
-    test::call_service(&app, delete_req2).await;
+    assert_eq!(response.total, Some(2));
+    let ids: Vec<_> = response.data.iter().map(|memo| memo.id).collect();
+    assert_eq!(ids, [memo2.id, memo1.id]);
+    assert_eq!(response.page, 1);
+    assert_eq!(response.total_pages, Some(1));
 }
 
 #[tokio::test]
 async fn test_list_memos_with_pagination() {
-    let settings = Settings::load().expect("Failed to load settings");
-    let db = Database::connect(&settings.database.url)
-        .await
-        .expect("Failed to connect to database");
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
     let state = AppState::new(settings.clone(), db);
 
     let app = test::init_service(
@@ -437,11 +1053,7 @@ async fn test_list_memos_with_pagination() {
 
     let mut memo_ids = Vec::new();
     for i in 0..5 {
-        let create_dto = CreateMemoDto {
-            title: format!("Pagination Test {}", i),
-            description: None,
-            date_to: Utc::now(),
-        };
+        let create_dto = MemoFixture::new(&format!("Pagination Test {}", i)).build();
 
         let create_req = test::TestRequest::post()
             .uri("/api/v1/memos")
@@ -463,42 +1075,2884 @@ async fn test_list_memos_with_pagination() {
     let response: PaginatedResponse<MemoResponseDto> = test::read_body_json(list_resp).await;
     assert_eq!(response.limit, 2);
     assert!(response.data.len() <= 2);
+}
+
+#[tokio::test]
+async fn test_envelope_wraps_memo_responses_when_preferred() {
+    let test_db = TestDb::new().await;
+    let state = test_db.state();
+
+    let app = test::init_service(
+        App::new()
+            .wrap(AssignRequestId)
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::list_memos)
+            .service(handlers::get_memo),
+    )
+    .await;
 
-    for id in memo_ids {
-        let delete_req = test::TestRequest::delete()
-            .uri(&format!("/api/v1/memos/{}", id))
+    for i in 0..3 {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/memos")
+            .set_json(MemoFixture::new(&format!("Envelope {}", i)).build())
             .to_request();
-        test::call_service(&app, delete_req).await;
+        assert_eq!(test::call_service(&app, req).await.status(), 201);
     }
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos?limit=2&offset=0")
+        .insert_header(("Prefer", "envelope"))
+        .insert_header(("X-Request-Id", "envelope-test"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("Preference-Applied").unwrap(),
+        "envelope"
+    );
+    assert!(resp.headers().contains_key("Link"));
+    let body: serde_json::Value = test::read_body_json(resp).await;
+
+    assert_eq!(body["data"]["data"].as_array().unwrap().len(), 2);
+    assert_eq!(body["data"]["total"], 3);
+    assert_eq!(body["meta"]["request_id"], "envelope-test");
+    assert!(body["meta"]["duration_ms"].as_f64().unwrap() >= 0.0);
+    assert!(
+        body["links"]["self"]
+            .as_str()
+            .unwrap()
+            .ends_with("/api/v1/memos?limit=2&offset=0")
+    );
+    assert!(
+        body["links"]["next"]
+            .as_str()
+            .unwrap()
+            .ends_with("/api/v1/memos?limit=2&offset=2")
+    );
+    assert!(body["links"]["prev"].is_null());
+
+    let id = body["data"]["data"][0]["id"].as_str().unwrap().to_string();
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", id))
+        .insert_header(("Prefer", "envelope"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let generated = resp.headers().get("X-Request-Id").unwrap().clone();
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["data"]["id"], id.as_str());
+    assert_eq!(body["meta"]["request_id"], generated.to_str().unwrap());
+    assert!(body["links"]["self"].as_str().unwrap().ends_with(&id));
+
+    // Without the preference the body keeps its documented shape.
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", id))
+        .to_request();
+    let memo: MemoResponseDto = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(memo.id.to_string(), id);
 }
 
 #[tokio::test]
-async fn test_create_memo_validation_error() {
-    let settings = Settings::load().expect("Failed to load settings");
-    let db = Database::connect(&settings.database.url)
-        .await
-        .expect("Failed to connect to database");
-    let state = AppState::new(settings, db);
+async fn test_json_api_renders_memos_as_resources() {
+    let test_db = TestDb::new().await;
+    let state = test_db.state();
 
     let app = test::init_service(
         App::new()
             .app_data(web::Data::new(state))
-            .app_data(web::JsonConfig::default().limit(1048576))
-            .service(handlers::create_memo),
+            .service(handlers::create_memo)
+            .service(handlers::memo_stats)
+            .service(handlers::list_memos)
+            .service(handlers::get_memo),
     )
     .await;
 
-    let create_dto = CreateMemoDto {
-        title: "".to_string(),
-        description: None,
-        date_to: Utc::now(),
-    };
+    let mut ids = Vec::new();
+    for i in 0..3 {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/memos")
+            .set_json(MemoFixture::new(&format!("JSON:API {}", i)).build())
+            .to_request();
+        let memo: MemoResponseDto = test::read_body_json(test::call_service(&app, req).await).await;
+        ids.push(memo.id.to_string());
+    }
 
-    let req = test::TestRequest::post()
-        .uri("/api/v1/memos")
-        .set_json(&create_dto)
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", ids[0]))
+        .insert_header(("Accept", "application/vnd.api+json"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("Content-Type").unwrap(),
+        "application/vnd.api+json"
+    );
+    let document: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(document["data"]["type"], "memos");
+    assert_eq!(document["data"]["id"], ids[0].as_str());
+    assert_eq!(document["data"]["attributes"]["title"], "JSON:API 0");
+    assert!(document["data"]["attributes"].get("id").is_none());
+    assert_eq!(
+        document["data"]["relationships"]["blocked_by"]["data"],
+        serde_json::json!([])
+    );
+    assert!(
+        document["links"]["self"]
+            .as_str()
+            .unwrap()
+            .ends_with(&ids[0])
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos?limit=2&sort_by=title&order=asc")
+        .insert_header(("Accept", "application/vnd.api+json"))
         .to_request();
+    let document: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    let data = document["data"].as_array().unwrap();
+    assert_eq!(data.len(), 2);
+    assert_eq!(data[0]["id"], ids[0].as_str());
+    assert_eq!(document["meta"]["total"], 3);
+    assert!(
+        document["links"]["next"]
+            .as_str()
+            .unwrap()
+            .contains("offset=2")
+    );
 
+    // Endpoints without a JSON:API rendering answer with plain JSON.
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos/stats")
+        .insert_header(("Accept", "application/vnd.api+json"))
+        .to_request();
     let resp = test::call_service(&app, req).await;
-    assert_eq!(resp.status(), 400);
+    assert_eq!(
+        resp.headers().get("Content-Type").unwrap(),
+        "application/json"
+    );
+}
+
+#[tokio::test]
+async fn test_memo_links_follow_registered_routes() {
+    let test_db = TestDb::new().await;
+    let state = test_db.state();
+    let memo = MemoFixture::new("Links").persist(&test_db.db).await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(handlers::get_memo)
+            .service(handlers::list_memos)
+            .service(handlers::toggle_complete)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", memo.id))
+        .to_request();
+    let body: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let links = &body["links"];
+    assert!(
+        links["self"]["href"]
+            .as_str()
+            .unwrap()
+            .ends_with(&format!("/api/v1/memos/{}", memo.id))
+    );
+    assert_eq!(links["self"]["method"], "GET");
+    assert!(
+        links["complete"]["href"]
+            .as_str()
+            .unwrap()
+            .ends_with(&format!("/api/v1/memos/{}/complete", memo.id))
+    );
+    assert_eq!(links["complete"]["method"], "PATCH");
+    assert_eq!(links["delete"]["method"], "DELETE");
+
+    // Completing the memo withdraws the action.
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}/complete", memo.id))
+        .to_request();
+    let completed: MemoResponseDto =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    let links = completed.links.expect("links");
+    assert!(links.complete.is_none());
+    assert!(links.delete.is_some());
+
+    let req = test::TestRequest::get().uri("/api/v1/memos").to_request();
+    let body: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert!(body["data"][0]["links"]["self"]["href"].is_string());
+
+    // Routes the app does not serve get no link.
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::get_memo),
+    )
+    .await;
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", memo.id))
+        .to_request();
+    let fetched: MemoResponseDto = test::read_body_json(test::call_service(&app, req).await).await;
+    let links = fetched.links.expect("links");
+    assert!(links.complete.is_none());
+    assert!(links.delete.is_none());
+}
+
+#[tokio::test]
+async fn test_create_memo_validation_error() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .app_data(web::JsonConfig::default().limit(1048576))
+            .service(handlers::create_memo),
+    )
+    .await;
+
+    let create_dto = MemoFixture::new("").build();
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(&create_dto)
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn test_error_message_localized_from_accept_language() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .wrap(LocaleNegotiation)
+            .service(handlers::get_memo),
+    )
+    .await;
+
+    let fake_id = uuid::Uuid::new_v4();
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", fake_id))
+        .insert_header(("Accept-Language", "es-ES,es;q=0.9,en;q=0.8"))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    assert_eq!(resp.headers().get("content-language").unwrap(), "es");
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "NotFound");
+    assert!(
+        body["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Memo no encontrado: ")
+    );
+}
+
+#[tokio::test]
+async fn test_move_memo_endpoint() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::move_memo)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let create_req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(MemoFixture::new("Move Endpoint Memo").build())
+        .to_request();
+    let created: MemoResponseDto =
+        test::read_body_json(test::call_service(&app, create_req).await).await;
+
+    let move_req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}/move", created.id))
+        .set_json(serde_json::json!({ "position": 0 }))
+        .to_request();
+    let resp = test::call_service(&app, move_req).await;
+    assert_eq!(resp.status(), 200);
+
+    let moved: MemoResponseDto = test::read_body_json(resp).await;
+    assert_eq!(moved.id, created.id);
+    assert_eq!(moved.position, 0);
+
+    let missing_req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}/move", uuid::Uuid::new_v4()))
+        .set_json(serde_json::json!({ "position": 0 }))
+        .to_request();
+    let resp = test::call_service(&app, missing_req).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn test_update_memo_with_stale_if_match_returns_conflict() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::get_memo)
+            .service(handlers::update_memo)
+            .service(handlers::patch_memo)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let create_req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(MemoFixture::new("Optimistic Lock Memo").build())
+        .to_request();
+    let created: MemoResponseDto =
+        test::read_body_json(test::call_service(&app, create_req).await).await;
+
+    let get_req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", created.id))
+        .to_request();
+    let resp = test::call_service(&app, get_req).await;
+    assert_eq!(resp.headers().get("etag").unwrap(), "\"1\"");
+
+    let patch_req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}", created.id))
+        .insert_header(("If-Match", "\"1\""))
+        .set_json(serde_json::json!({ "title": "Won the race" }))
+        .to_request();
+    let resp = test::call_service(&app, patch_req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("etag").unwrap(), "\"2\"");
+
+    let update_req = test::TestRequest::put()
+        .uri(&format!("/api/v1/memos/{}", created.id))
+        .insert_header(("If-Match", "\"1\""))
+        .set_json(UpdateMemoDto {
+            title: "Lost the race".to_string(),
+            description: None,
+            date_to: Utc::now().into(),
+            completed: true,
+            version: None,
+            timezone: None,
+            color: None,
+        })
+        .to_request();
+    let resp = test::call_service(&app, update_req).await;
+    assert_eq!(resp.status(), 409);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "Conflict");
+    assert_eq!(body["code"], "VERSION_CONFLICT");
+    assert_eq!(body["current"]["title"], "Won the race");
+    assert_eq!(body["current"]["version"], 2);
+
+    let bad_req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}", created.id))
+        .insert_header(("If-Match", "\"abc\""))
+        .set_json(serde_json::json!({ "completed": true }))
+        .to_request();
+    let resp = test::call_service(&app, bad_req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn test_validation_error_lists_field_errors() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(MemoFixture::new("").description(&"x".repeat(1001)).build())
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "ValidationError");
+    assert_eq!(body["code"], "VALIDATION_DESCRIPTION_LENGTH");
+    assert_eq!(
+        body["errors"],
+        serde_json::json!([
+            {
+                "field": "description",
+                "code": "length",
+                "message": "Description must not exceed 1000 characters"
+            },
+            {
+                "field": "title",
+                "code": "length",
+                "message": "Title must be between 1 and 200 characters"
+            }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_descriptions_with_the_encryption_prefix_are_rejected() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::list_memos),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(
+            MemoFixture::new("Looks sealed")
+                .description("enc:v1:x")
+                .build(),
+        )
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["field"], "description");
+    assert_eq!(body["errors"][0]["code"], "reserved_prefix");
+
+    // Mentioning the prefix later in the text is fine
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(
+            MemoFixture::new("Mentions it")
+                .description("values look like enc:v1:x")
+                .build(),
+        )
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let req = test::TestRequest::get().uri("/api/v1/memos").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let page: PaginatedResponse<MemoResponseDto> = test::read_body_json(resp).await;
+    assert_eq!(page.data.len(), 1);
+    assert_eq!(page.data[0].title, "Mentions it");
+}
+
+#[actix_web::get("/slow")]
+async fn slow_handler() -> actix_web::HttpResponse {
+    tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+    actix_web::HttpResponse::Ok().finish()
+}
+
+#[tokio::test]
+async fn test_slow_handler_times_out_with_504() {
+    use actix_web_template::middleware::RequestTimeout;
+    use std::time::Duration;
+
+    let registry = prometheus::Registry::new();
+    let timeout = RequestTimeout::new(Duration::from_secs(30))
+        .route("/slow", Duration::from_millis(50))
+        .with_metrics(&registry)
+        .unwrap();
+
+    let app = test::init_service(App::new().wrap(timeout).service(slow_handler)).await;
+
+    let req = test::TestRequest::get().uri("/slow").to_request();
+    // The middleware answers with an error carrying the rendered 504, which
+    // the server turns into the response.
+    let err = test::try_call_service(&app, req)
+        .await
+        .expect_err("slow handler should time out");
+    let resp = err.error_response();
+    assert_eq!(resp.status(), 504);
+
+    let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["code"], "REQUEST_TIMEOUT");
+    assert_eq!(body["error"], "Timeout");
+
+    let timed_out = registry
+        .gather()
+        .into_iter()
+        .find(|family| family.name() == "http_requests_timed_out_total")
+        .expect("timeout counter registered");
+    let metric = &timed_out.get_metric()[0];
+    assert!(
+        metric
+            .get_label()
+            .iter()
+            .any(|label| label.name() == "route" && label.value() == "/slow")
+    );
+    assert_eq!(metric.get_counter().get_value(), 1.0);
+}
+
+#[tokio::test]
+async fn test_database_circuit_fails_fast_after_connection_errors() {
+    use actix_web_template::repository::MemoRepository;
+
+    let test_db = TestDb::new().await;
+    let mut settings = test_db.settings.clone();
+    settings.database.circuit_failure_threshold = 2;
+    settings.database.circuit_open_secs = 30;
+    // A closed pool fails every query with a connection error
+    let db = sea_orm::Database::connect(&settings.database.url)
+        .await
+        .unwrap();
+    db.clone().close().await.unwrap();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .wrap(DatabaseCircuit::new(state.db.breaker().clone()))
+            .service(handlers::list_memos)
+            .service(handlers::health_check),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/api/v1/memos").to_request();
+    let resp = test::call_service(&app, req).await;
+    // Failing to reach the database is retryable even before the circuit opens
+    assert_eq!(resp.status(), 503);
+    assert!(!resp.headers().contains_key("Retry-After"));
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "DB_UNAVAILABLE");
+    // Calls outside a request, like a job's, count too
+    assert!(
+        MemoRepository::find_by_id(&state.db, uuid::Uuid::new_v4())
+            .await
+            .is_err()
+    );
+
+    let req = test::TestRequest::get().uri("/api/v1/memos").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+    assert!(resp.headers().contains_key("Retry-After"));
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "DB_UNAVAILABLE");
+
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["database_circuit"], "open");
+}
+
+#[tokio::test]
+async fn test_maintenance_mode_blocks_api_but_not_probes() {
+    let test_db = TestDb::new().await;
+    let mut settings = test_db.settings.clone();
+    settings.admin.token = Some("test-admin-token".to_string());
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .wrap(state.maintenance.clone())
+            .service(handlers::list_memos)
+            .service(handlers::health_check)
+            .service(handlers::get_maintenance)
+            .service(handlers::set_maintenance),
+    )
+    .await;
+
+    let req = test::TestRequest::put()
+        .uri("/admin/maintenance")
+        .set_json(serde_json::json!({ "enabled": true }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+    assert!(!state.maintenance.is_enabled());
+
+    let req = test::TestRequest::put()
+        .uri("/admin/maintenance")
+        .insert_header(("Authorization", "Bearer test-admin-token"))
+        .set_json(serde_json::json!({ "enabled": true }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["enabled"], true);
+
+    let req = test::TestRequest::get().uri("/api/v1/memos").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+    assert_eq!(
+        resp.headers().get("Retry-After").unwrap(),
+        &state.maintenance.retry_after_secs().to_string()
+    );
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "MAINTENANCE_MODE");
+
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::put()
+        .uri("/admin/maintenance")
+        .insert_header(("Authorization", "Bearer test-admin-token"))
+        .set_json(serde_json::json!({ "enabled": false }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::get().uri("/api/v1/memos").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn test_metrics_require_observability_credentials() {
+    use actix_web_template::{
+        config::settings::{BasicCredentials, ObservabilityConfig},
+        middleware::ObservabilityAuth,
+        observability::metrics::prometheus_metrics,
+    };
+    use base64::{Engine, engine::general_purpose::STANDARD};
+
+    let config = ObservabilityConfig {
+        auth_token: Some("scrape-token".to_string()),
+        basic_auth: Some(BasicCredentials {
+            username: "ops".to_string(),
+            password: "s3cret".to_string(),
+        }),
+        metrics_namespace: "memos".to_string(),
+        metrics_const_labels: Vec::new(),
+        metrics_latency_buckets: vec![0.1, 1.0],
+    };
+    let app = test::init_service(
+        App::new()
+            .wrap(prometheus_metrics(&config).unwrap())
+            .wrap(ObservabilityAuth::from_config(&config))
+            .route("/health", web::get().to(actix_web::HttpResponse::Ok)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+    assert!(resp.headers().contains_key("WWW-Authenticate"));
+
+    let req = test::TestRequest::get()
+        .uri("/metrics")
+        .insert_header(("Authorization", "Bearer wrong-token"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+
+    let req = test::TestRequest::get()
+        .uri("/metrics")
+        .insert_header(("Authorization", "Bearer scrape-token"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::get()
+        .uri("/metrics")
+        .insert_header((
+            "Authorization",
+            format!("Basic {}", STANDARD.encode("ops:s3cret")),
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn test_public_listener_hides_management_routes() {
+    use actix_web_template::{
+        config::settings::ObservabilityConfig, middleware::HideManagementRoutes,
+        observability::metrics::prometheus_metrics,
+    };
+
+    let config = ObservabilityConfig {
+        auth_token: None,
+        basic_auth: None,
+        metrics_namespace: "memos".to_string(),
+        metrics_const_labels: Vec::new(),
+        metrics_latency_buckets: vec![0.1, 1.0],
+    };
+    let app = test::init_service(
+        App::new()
+            .wrap(prometheus_metrics(&config).unwrap())
+            .wrap(HideManagementRoutes)
+            .route("/health", web::get().to(actix_web::HttpResponse::Ok))
+            .route("/version", web::get().to(actix_web::HttpResponse::Ok)),
+    )
+    .await;
+
+    for uri in ["/metrics", "/health", "/admin/maintenance"] {
+        let req = test::TestRequest::get().uri(uri).to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 404, "{uri} should be hidden");
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["code"], "ROUTE_NOT_FOUND");
+    }
+
+    let req = test::TestRequest::get().uri("/version").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::test]
+async fn test_handler_panics_become_500_responses() {
+    use actix_web_template::middleware::PanicCapture;
+
+    async fn explode() -> actix_web::HttpResponse {
+        panic!("handler bug");
+    }
+
+    let registry = prometheus::Registry::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(PanicCapture::new().with_metrics(&registry).unwrap())
+            .wrap(AssignRequestId)
+            .route("/explode", web::get().to(explode))
+            .route("/fine", web::get().to(actix_web::HttpResponse::Ok)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/explode").to_request();
+    // Like timeouts, the 500 comes back as an error carrying the response
+    let err = test::try_call_service(&app, req)
+        .await
+        .expect_err("panic should be turned into an error");
+    let resp = err.error_response();
+    assert_eq!(resp.status(), 500);
+    let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+    let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(body["code"], "INTERNAL_ERROR");
+    assert!(!body.to_string().contains("handler bug"));
+
+    let panicked = registry
+        .gather()
+        .into_iter()
+        .find(|family| family.name() == "http_requests_panicked_total")
+        .expect("panic counter registered");
+    assert_eq!(panicked.get_metric()[0].get_counter().get_value(), 1.0);
+
+    // The worker keeps serving after the panic
+    let req = test::TestRequest::get().uri("/fine").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[actix_web::post("/api/v1/ping")]
+async fn ping_handler() -> actix_web::HttpResponse {
+    actix_web::HttpResponse::Ok().finish()
+}
+
+#[tokio::test]
+async fn test_write_budget_returns_429_with_retry_after() {
+    use actix_web_template::{
+        config::settings::{RateLimitBudget, RateLimitConfig},
+        middleware::RateLimit,
+    };
+
+    let budget = |burst| RateLimitBudget {
+        per_minute: 1,
+        burst,
+    };
+    let limits = RateLimit::from_config(&RateLimitConfig {
+        read: budget(100),
+        write: budget(1),
+        web_form: budget(1),
+        redis_url: None,
+        redis_timeout_ms: 50,
+    });
+
+    let app = test::init_service(App::new().wrap(limits).service(ping_handler)).await;
+    let request = |ip: &str| {
+        test::TestRequest::post()
+            .uri("/api/v1/ping")
+            .peer_addr(format!("{}:4000", ip).parse().unwrap())
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, request("10.0.0.1")).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("X-RateLimit-Limit").unwrap(), "1");
+    assert_eq!(resp.headers().get("X-RateLimit-Remaining").unwrap(), "0");
+    assert!(resp.headers().contains_key("X-RateLimit-Reset"));
+
+    let resp = test::call_service(&app, request("10.0.0.1")).await;
+    assert_eq!(resp.status(), 429);
+    assert!(resp.headers().contains_key("Retry-After"));
+    assert_eq!(resp.headers().get("X-RateLimit-Remaining").unwrap(), "0");
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "RATE_LIMITED");
+    assert!(body["message"].is_string());
+
+    // Budgets are per caller.
+    let resp = test::call_service(&app, request("10.0.0.2")).await;
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn test_rate_limit_errors_are_localized() {
+    use actix_web_template::{
+        config::settings::{RateLimitBudget, RateLimitConfig},
+        middleware::RateLimit,
+    };
+
+    let budget = RateLimitBudget {
+        per_minute: 1,
+        burst: 1,
+    };
+    let limits = RateLimit::from_config(&RateLimitConfig {
+        read: budget,
+        write: budget,
+        web_form: budget,
+        redis_url: None,
+        redis_timeout_ms: 50,
+    });
+
+    // Wrapped as in the app: locale negotiation outside the limiter
+    let app = test::init_service(
+        App::new()
+            .wrap(limits)
+            .wrap(LocaleNegotiation)
+            .service(ping_handler),
+    )
+    .await;
+    let request = || {
+        test::TestRequest::post()
+            .uri("/api/v1/ping")
+            .peer_addr("10.0.0.1:4000".parse().unwrap())
+            .insert_header(("Accept-Language", "es"))
+            .to_request()
+    };
+
+    assert_eq!(test::call_service(&app, request()).await.status(), 200);
+    let resp = test::call_service(&app, request()).await;
+    assert_eq!(resp.status(), 429);
+    assert_eq!(resp.headers().get("content-language").unwrap(), "es");
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "RATE_LIMITED");
+    assert!(
+        body["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("Demasiadas solicitudes: ")
+    );
+}
+
+#[tokio::test]
+async fn test_api_key_quota_and_usage() {
+    use actix_web_template::{
+        auth::{ApiKeys, QuotaCheck},
+        config::settings::ApiKeyConfig,
+        dto::ApiKeyUsageDto,
+        middleware::ApiKeyAuth,
+    };
+
+    let test_db = TestDb::new().await;
+    let mut settings = test_db.settings.clone();
+    settings.api_keys.keys = vec![
+        ApiKeyConfig {
+            id: "acme".to_string(),
+            key: "acme-secret".to_string(),
+            monthly_quota: Some(2),
+        },
+        ApiKeyConfig {
+            id: "ops".to_string(),
+            key: "ops-secret".to_string(),
+            monthly_quota: None,
+        },
+    ];
+    let state = AppState::new(settings.clone(), test_db.db.clone());
+    let api_keys = state.api_keys.clone();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .wrap(ApiKeyAuth::new(api_keys.clone()))
+            .service(ping_handler)
+            .service(handlers::get_my_usage),
+    )
+    .await;
+    let ping = |key: Option<&str>| {
+        let mut req = test::TestRequest::post().uri("/api/v1/ping");
+        if let Some(key) = key {
+            req = req.insert_header(("X-API-Key", key));
+        }
+        req.to_request()
+    };
+    let usage = |key: &str| {
+        test::TestRequest::get()
+            .uri("/api/v1/me/usage")
+            .insert_header(("X-API-Key", key))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, ping(Some("acme-secret"))).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("X-Quota-Limit").unwrap(), "2");
+    assert_eq!(resp.headers().get("X-Quota-Remaining").unwrap(), "1");
+    assert!(resp.headers().contains_key("X-Quota-Reset"));
+
+    let resp = test::call_service(&app, ping(Some("acme-secret"))).await;
+    assert_eq!(resp.headers().get("X-Quota-Remaining").unwrap(), "0");
+
+    let resp = test::call_service(&app, ping(Some("acme-secret"))).await;
+    assert_eq!(resp.status(), 429);
+    assert!(resp.headers().contains_key("Retry-After"));
+    assert_eq!(resp.headers().get("X-Quota-Remaining").unwrap(), "0");
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "QUOTA_EXCEEDED");
+
+    // Unlimited keys and anonymous callers carry no quota.
+    let resp = test::call_service(&app, ping(Some("ops-secret"))).await;
+    assert_eq!(resp.status(), 200);
+    assert!(!resp.headers().contains_key("X-Quota-Limit"));
+    let resp = test::call_service(&app, ping(None)).await;
+    assert_eq!(resp.status(), 200);
+
+    let resp = test::call_service(&app, ping(Some("wrong-secret"))).await;
+    assert_eq!(resp.status(), 401);
+
+    // Usage stays readable past the quota and is not counted itself.
+    let report: ApiKeyUsageDto = test::call_and_read_body_json(&app, usage("acme-secret")).await;
+    assert_eq!(report.api_key_id, "acme");
+    assert_eq!(report.requests, 2);
+    assert_eq!(report.monthly_quota, Some(2));
+    assert_eq!(report.remaining, Some(0));
+    assert_eq!(report.days.len(), 1);
+    assert_eq!(report.days[0].day, Utc::now().date_naive());
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/api/v1/me/usage")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 401);
+
+    // Flushed counts survive a restart.
+    api_keys.flush().await.unwrap();
+    let restarted = ApiKeys::new(&settings.api_keys, test_db.db.clone().into());
+    let acme = restarted.find("acme").unwrap().clone();
+    assert!(matches!(
+        restarted.charge(&acme).await.unwrap(),
+        QuotaCheck::Exceeded(_)
+    ));
+    let report = restarted.month_usage("ops").await.unwrap();
+    assert_eq!(report.requests(), 1);
+}
+
+#[actix_web::test]
+async fn test_cors_allows_configured_subdomains_with_credentials() {
+    use actix_web_template::{config::settings::CorsConfig, middleware::cors};
+
+    let config = CorsConfig {
+        allowed_origins: vec![
+            "https://*.example.com".to_string(),
+            "http://localhost:3000".to_string(),
+        ],
+        allow_credentials: true,
+        exposed_headers: vec!["ETag".to_string(), "X-RateLimit-Remaining".to_string()],
+        max_age_secs: 600,
+    };
+    let app = test::init_service(App::new().wrap(cors::build(&config)).service(ping_handler)).await;
+    let preflight = |origin: &str| {
+        test::TestRequest::default()
+            .method(actix_web::http::Method::OPTIONS)
+            .uri("/api/v1/ping")
+            .insert_header(("Origin", origin))
+            .insert_header(("Access-Control-Request-Method", "POST"))
+            .insert_header(("Access-Control-Request-Headers", "if-match"))
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, preflight("https://app.example.com")).await;
+    assert_eq!(resp.status(), 200);
+    let headers = resp.headers();
+    assert_eq!(
+        headers.get("Access-Control-Allow-Origin").unwrap(),
+        "https://app.example.com"
+    );
+    assert_eq!(
+        headers.get("Access-Control-Allow-Credentials").unwrap(),
+        "true"
+    );
+    assert_eq!(headers.get("Access-Control-Max-Age").unwrap(), "600");
+
+    let resp = test::call_service(&app, preflight("https://example.org")).await;
+    assert!(!resp.headers().contains_key("Access-Control-Allow-Origin"));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/ping")
+        .insert_header(("Origin", "http://localhost:3000"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let exposed = resp
+        .headers()
+        .get("Access-Control-Expose-Headers")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_lowercase();
+    assert!(exposed.contains("etag") && exposed.contains("x-ratelimit-remaining"));
+}
+
+#[actix_web::test]
+async fn test_push_subscription_lifecycle() {
+    let test_db = TestDb::new().await;
+    let mut settings = test_db.settings.clone();
+    settings.push.vapid_private_key =
+        Some("dH1GTNiMPCxAn6bfPyvOMm-Fc3Sz3gjhMLXmkk2Tx1g".to_string());
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(handlers::get_push_public_key)
+            .service(handlers::subscribe_push)
+            .service(handlers::unsubscribe_push),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/push/public-key")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(!body["public_key"].as_str().unwrap().is_empty());
+
+    let endpoint = format!("https://push.example.com/send/{}", uuid::Uuid::new_v4());
+    let req = test::TestRequest::post()
+        .uri("/api/v1/push/subscribe")
+        .set_json(serde_json::json!({
+            "endpoint": "http://169.254.169.254/latest",
+            "keys": { "p256dh": "key", "auth": "secret" }
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/push/subscribe")
+        .set_json(serde_json::json!({
+            "endpoint": endpoint,
+            "keys": { "p256dh": "key", "auth": "secret" }
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+
+    let req = test::TestRequest::delete()
+        .uri("/api/v1/push/subscribe")
+        .set_json(serde_json::json!({ "endpoint": endpoint }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 204);
+}
+
+#[actix_web::test]
+async fn test_push_disabled_without_vapid_key() {
+    let test_db = TestDb::new().await;
+    let mut settings = test_db.settings.clone();
+    settings.push.vapid_private_key = None;
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::get_push_public_key),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/push/public-key")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "FEATURE_DISABLED");
+}
+
+#[cfg(feature = "dev-routes")]
+#[actix_web::test]
+async fn test_configure_test_routes_mounts_diagnostics() {
+    let app = test::init_service(App::new().configure(handlers::configure_test_routes)).await;
+
+    let req = test::TestRequest::get()
+        .uri("/test/error/notfound")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "RESOURCE_NOT_FOUND");
+}
+
+#[actix_web::test]
+async fn test_memo_stats_endpoint() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::memo_stats)
+            .service(handlers::get_memo),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos/stats")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(
+        body["active"].as_u64().unwrap() + body["completed"].as_u64().unwrap(),
+        body["total"].as_u64().unwrap()
+    );
+    let series = body["created_per_day"].as_array().unwrap();
+    assert_eq!(series.len(), 30);
+    assert_eq!(
+        series.last().unwrap()["date"],
+        Utc::now().date_naive().to_string()
+    );
+}
+
+#[actix_web::test]
+async fn test_completion_note_and_timestamp() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::delete_memo)
+            .service(handlers::toggle_complete),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(MemoFixture::new("Pay rent").due_in_days(1).build())
+        .to_request();
+    let memo: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(memo.completed_at, None);
+    let complete =
+        || test::TestRequest::patch().uri(&format!("/api/v1/memos/{}/complete", memo.id));
+
+    let before = Utc::now();
+    let req = complete()
+        .set_json(serde_json::json!({ "note": "Paid by bank transfer" }))
+        .to_request();
+    let completed: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+    assert!(completed.completed);
+    assert!(completed.completed_at.unwrap() >= before);
+    assert_eq!(
+        completed.completion_note.as_deref(),
+        Some("Paid by bank transfer")
+    );
+
+    let resp = test::call_service(
+        &app,
+        complete()
+            .set_json(serde_json::json!({ "note": "Reopened" }))
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 400);
+
+    let resp = test::call_service(
+        &app,
+        complete()
+            .insert_header(("Content-Type", "application/json"))
+            .set_payload("{\"note\":")
+            .to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 400);
+
+    let reopened: MemoResponseDto =
+        test::call_and_read_body_json(&app, complete().to_request()).await;
+    assert!(!reopened.completed);
+    assert_eq!(reopened.completed_at, None);
+    assert_eq!(reopened.completion_note, None);
+}
+
+#[actix_web::test]
+async fn test_snooze_pushes_due_date_and_records_history() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::delete_memo)
+            .service(handlers::toggle_complete)
+            .service(handlers::snooze_memo)
+            .service(handlers::list_memo_snoozes),
+    )
+    .await;
+
+    let due = "2099-03-04T15:00:00Z"
+        .parse::<chrono::DateTime<Utc>>()
+        .unwrap();
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(serde_json::json!({
+            "title": "Snooze me",
+            "date_to": due,
+            "timezone": "UTC",
+        }))
+        .to_request();
+    let memo: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+    let snooze = |body: serde_json::Value| {
+        test::TestRequest::post()
+            .uri(&format!("/api/v1/memos/{}/snooze", memo.id))
+            .set_json(body)
+            .to_request()
+    };
+
+    let snoozed: MemoResponseDto =
+        test::call_and_read_body_json(&app, snooze(serde_json::json!({ "minutes": 90 }))).await;
+    assert_eq!(snoozed.date_to, due + chrono::Duration::minutes(90));
+
+    let snoozed: MemoResponseDto =
+        test::call_and_read_body_json(&app, snooze(serde_json::json!({ "preset": "next_week" })))
+            .await;
+    assert_eq!(snoozed.date_to.to_rfc3339(), "2099-03-09T09:00:00+00:00");
+
+    let resp = test::call_service(&app, snooze(serde_json::json!({}))).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}/snoozes", memo.id))
+        .to_request();
+    let history: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(history.as_array().unwrap().len(), 2);
+    assert_eq!(history[0]["preset"], "next_week");
+    assert_eq!(history[0]["snoozed_until"], "2099-03-09T09:00:00Z");
+    assert_eq!(history[1]["preset"], serde_json::Value::Null);
+    assert_eq!(history[1]["snoozed_from"], "2099-03-04T15:00:00Z");
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}/complete", memo.id))
+        .to_request();
+    test::call_service(&app, req).await;
+    let resp = test::call_service(&app, snooze(serde_json::json!({ "preset": "1h" }))).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_snoozing_an_overdue_memo_counts_from_now() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::delete_memo)
+            .service(handlers::snooze_memo),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(MemoFixture::new("Overdue").due_in_days(-2).build())
+        .to_request();
+    let memo: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+
+    let before = Utc::now();
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/memos/{}/snooze", memo.id))
+        .set_json(serde_json::json!({ "preset": "1h" }))
+        .to_request();
+    let snoozed: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+    assert!(snoozed.date_to >= before + chrono::Duration::hours(1));
+    assert!(snoozed.date_to <= Utc::now() + chrono::Duration::hours(1));
+}
+
+#[actix_web::test]
+async fn test_archive_hides_memo_from_default_listing() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::list_memos)
+            .service(handlers::create_memo)
+            .service(handlers::delete_memo)
+            .service(handlers::archive_memo)
+            .service(handlers::unarchive_memo),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(MemoFixture::new("Archive me 5c1e").due_in_days(1).build())
+        .to_request();
+    let memo: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+    assert!(!memo.archived);
+
+    let listed = |archived: bool| {
+        test::TestRequest::get()
+            .uri(&format!(
+                "/api/v1/memos?q=Archive%20me%205c1e&archived={archived}"
+            ))
+            .to_request()
+    };
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/memos/{}/archive", memo.id))
+        .to_request();
+    let archived: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+    assert!(archived.archived);
+    assert_eq!(archived.version, memo.version + 1);
+
+    let page: PaginatedResponse<MemoResponseDto> =
+        test::call_and_read_body_json(&app, listed(false)).await;
+    assert!(!page.data.iter().any(|m| m.id == memo.id));
+    let page: PaginatedResponse<MemoResponseDto> =
+        test::call_and_read_body_json(&app, listed(true)).await;
+    assert!(page.data.iter().any(|m| m.id == memo.id));
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/memos/{}/unarchive", memo.id))
+        .to_request();
+    let unarchived: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+    assert!(!unarchived.archived);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/memos/{}/archive", uuid::Uuid::new_v4()))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_pinned_memos_are_listed_first() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::list_memos)
+            .service(handlers::create_memo)
+            .service(handlers::delete_memo)
+            .service(handlers::toggle_pinned)
+            .service(handlers::toggle_starred),
+    )
+    .await;
+
+    let mut ids = Vec::new();
+    for title in ["Pin me 9d2a older", "Pin me 9d2a newer"] {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/memos")
+            .set_json(serde_json::json!({ "title": title, "date_to": "2099-03-04T15:00:00Z" }))
+            .to_request();
+        let memo: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+        assert!(!memo.pinned && !memo.starred);
+        ids.push(memo.id);
+    }
+    let (older, newer) = (ids[0], ids[1]);
+    let listed = |filter: &str| {
+        test::TestRequest::get()
+            .uri(&format!("/api/v1/memos?q=Pin%20me%209d2a{filter}"))
+            .to_request()
+    };
+    let toggle = |id: uuid::Uuid, flag: &str| {
+        test::TestRequest::patch()
+            .uri(&format!("/api/v1/memos/{id}/{flag}"))
+            .to_request()
+    };
+
+    let page: PaginatedResponse<MemoResponseDto> =
+        test::call_and_read_body_json(&app, listed("")).await;
+    assert_eq!(
+        page.data.iter().map(|m| m.id).collect::<Vec<_>>(),
+        [newer, older]
+    );
+
+    let pinned: MemoResponseDto = test::call_and_read_body_json(&app, toggle(older, "pin")).await;
+    assert!(pinned.pinned);
+    let page: PaginatedResponse<MemoResponseDto> =
+        test::call_and_read_body_json(&app, listed("&order=asc")).await;
+    assert_eq!(
+        page.data.iter().map(|m| m.id).collect::<Vec<_>>(),
+        [older, newer],
+        "pinned first in either order"
+    );
+    let page: PaginatedResponse<MemoResponseDto> =
+        test::call_and_read_body_json(&app, listed("")).await;
+    assert_eq!(
+        page.data.iter().map(|m| m.id).collect::<Vec<_>>(),
+        [older, newer]
+    );
+
+    let starred: MemoResponseDto = test::call_and_read_body_json(&app, toggle(newer, "star")).await;
+    assert!(starred.starred);
+    assert_eq!(starred.version, 2);
+    let page: PaginatedResponse<MemoResponseDto> =
+        test::call_and_read_body_json(&app, listed("&starred=true")).await;
+    assert_eq!(page.data.iter().map(|m| m.id).collect::<Vec<_>>(), [newer]);
+    let page: PaginatedResponse<MemoResponseDto> =
+        test::call_and_read_body_json(&app, listed("&pinned=false")).await;
+    assert_eq!(page.data.iter().map(|m| m.id).collect::<Vec<_>>(), [newer]);
+
+    let unpinned: MemoResponseDto = test::call_and_read_body_json(&app, toggle(older, "pin")).await;
+    assert!(!unpinned.pinned);
+    let resp = test::call_service(&app, toggle(uuid::Uuid::new_v4(), "star")).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_duplicate_memo_shifts_due_date() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::delete_memo)
+            .service(handlers::duplicate_memo),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(
+            MemoFixture::new("Weekly review")
+                .description("Inbox zero")
+                .due_in_days(1)
+                .build(),
+        )
+        .to_request();
+    let source: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/v1/memos/{}/duplicate?days_offset=7",
+            source.id
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let copy: MemoResponseDto = test::read_body_json(resp).await;
+
+    assert_ne!(copy.id, source.id);
+    assert_eq!(copy.title, source.title);
+    assert_eq!(copy.description, source.description);
+    assert_eq!(copy.date_to, source.date_to + chrono::Duration::days(7));
+    assert!(!copy.completed);
+
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/v1/memos/{}/duplicate?days_offset=99999",
+            source.id
+        ))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+}
+
+#[actix_web::test]
+async fn test_batch_complete_by_ids() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::complete_memos)
+            .service(handlers::create_memo)
+            .service(handlers::get_memo)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let mut ids = Vec::new();
+    for title in ["Batch one", "Batch two"] {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/memos")
+            .set_json(MemoFixture::new(title).due_in_days(1).build())
+            .to_request();
+        let memo: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+        ids.push(memo.id);
+    }
+    let missing = uuid::Uuid::new_v4();
+
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/memos/complete")
+        .set_json(serde_json::json!({ "ids": [ids[0], ids[1], missing] }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["updated"].as_array().unwrap().len(), 2);
+    assert_eq!(body["not_found"], serde_json::json!([missing]));
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", ids[0]))
+        .to_request();
+    let memo: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+    assert!(memo.completed);
+    assert_eq!(memo.version, 2);
+
+    // Already completed memos are not reported again.
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/memos/complete")
+        .set_json(serde_json::json!({ "ids": [ids[0]] }))
+        .to_request();
+    let body: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(body["updated"], serde_json::json!([]));
+
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/memos/complete")
+        .set_json(serde_json::json!({ "ids": [ids[0]], "filter": {} }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+}
+
+#[tokio::test]
+async fn test_ready_reports_pending_migrations() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::ready),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/ready").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["ready"], true);
+    assert_eq!(body["pending_migrations"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_startup_waits_for_scheduler_and_health_is_liveness_only() {
+    let test_db = TestDb::new().await;
+    let mut settings = test_db.settings.clone();
+    settings.scheduler.enabled = true;
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(handlers::health_check)
+            .service(handlers::startup),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/startup").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "starting");
+    assert_eq!(body["pending"], serde_json::json!(["scheduler"]));
+
+    state.startup.mark_scheduler_started();
+    let req = test::TestRequest::get().uri("/startup").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "started");
+
+    let req = test::TestRequest::get().uri("/health").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["status"], "healthy");
+    assert!(body.get("database").is_none());
+}
+
+#[tokio::test]
+async fn test_unmatched_routes_return_json_errors() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::list_memos)
+            .service(handlers::memo_stats)
+            .service(handlers::create_memo)
+            .default_service(web::to(handlers::fallback)),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/api/v1/nothing").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "ROUTE_NOT_FOUND");
+    assert_eq!(body["status"], 404);
+
+    let req = test::TestRequest::delete()
+        .uri("/api/v1/memos")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers().get("allow").unwrap(), "GET, POST");
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "METHOD_NOT_ALLOWED");
+    assert_eq!(body["error"], "MethodNotAllowed");
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos/stats")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.headers().get("allow").unwrap(), "GET");
+}
+
+#[tokio::test]
+async fn test_extractor_errors_use_the_standard_error_shape() {
+    use actix_web_template::error::extractors;
+
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .app_data(extractors::json_config(4096))
+            .app_data(extractors::query_config())
+            .app_data(extractors::path_config())
+            .service(handlers::list_memos)
+            .service(handlers::get_memo)
+            .service(handlers::create_memo),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos/not-a-uuid")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "VALIDATION_FAILED");
+    assert_eq!(body["errors"][0]["field"], "id");
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .insert_header(("Content-Type", "application/json"))
+        .set_payload(r#"{"title": "x""#)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["error"], "ValidationError");
+    assert!(body["message"].as_str().unwrap().contains("Malformed JSON"));
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(serde_json::json!({ "title": "No due date" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["field"], "date_to");
+    assert_eq!(body["errors"][0]["code"], "required");
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos?limit=lots")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["field"], "query");
+}
+
+#[tokio::test]
+async fn test_body_limits_apply_per_route_group() {
+    use actix_web::guard;
+    use actix_web_template::error::extractors;
+
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(
+                extractors::limited_scope(512)
+                    .guard(guard::fn_guard(|ctx| {
+                        ctx.head().uri.path().starts_with("/web/")
+                    }))
+                    .service(handlers::create_memo_web),
+            )
+            .service(extractors::limited_scope(64).service(handlers::create_memo)),
+    )
+    .await;
+
+    let oversized = [
+        (
+            "application/json",
+            format!(r#"{{"title": "{}"}}"#, "x".repeat(100)),
+        ),
+        ("application/msgpack", "x".repeat(100)),
+    ];
+    for (content_type, body) in oversized {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/memos")
+            .insert_header(("Content-Type", content_type))
+            .set_payload(body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 413, "{content_type}");
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["error"], "PayloadTooLarge");
+        assert_eq!(body["code"], "PAYLOAD_TOO_LARGE");
+        assert_eq!(body["limit"], 64);
+    }
+
+    // Over the API limit but within the web group's.
+    let req = test::TestRequest::post()
+        .uri("/web/memos")
+        .insert_header(("Content-Type", "application/x-www-form-urlencoded"))
+        .set_payload(format!("title={}", "x".repeat(100)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_ne!(resp.status(), 413);
+
+    let req = test::TestRequest::post()
+        .uri("/web/memos")
+        .insert_header(("Content-Type", "application/x-www-form-urlencoded"))
+        .set_payload(format!("title={}", "x".repeat(600)))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 413);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["limit"], 512);
+}
+
+#[tokio::test]
+async fn test_strict_bodies_reject_unknown_fields() {
+    let test_db = TestDb::new().await;
+    let body = serde_json::json!({
+        "title": "Pay rent",
+        "descripton": "Typo",
+        "date_to": "2030-01-31T12:00:00Z",
+    });
+
+    for strict in [true, false] {
+        let mut settings = test_db.settings.clone();
+        settings.api.strict_bodies = strict;
+        let state = AppState::new(settings, test_db.db.clone());
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::new(state))
+                .service(handlers::create_memo),
+        )
+        .await;
+
+        let req = test::TestRequest::post()
+            .uri("/api/v1/memos")
+            .set_json(&body)
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        if !strict {
+            assert_eq!(resp.status(), 201);
+            continue;
+        }
+        assert_eq!(resp.status(), 400);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["code"], "VALIDATION_FAILED");
+        assert_eq!(body["errors"][0]["field"], "descripton");
+        assert_eq!(body["errors"][0]["code"], "unknown_field");
+    }
+}
+
+#[tokio::test]
+async fn test_list_memos_without_count() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::list_memos),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos?limit=1&count=false")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let link = resp
+        .headers()
+        .get("link")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    assert!(!link.contains("rel=\"last\""));
+
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["total"].is_null());
+    assert!(body["total_pages"].is_null());
+    assert_eq!(body["total_estimated"], false);
+    assert!(body["has_next"].is_boolean());
+}
+
+#[tokio::test]
+async fn test_retention_preview_reports_old_completed_memos() {
+    use actix_web_template::repository::MemoRepository;
+    use sea_orm::ConnectionTrait;
+
+    let test_db = TestDb::new().await;
+    let mut settings = test_db.settings.clone();
+    settings.admin.token = Some("test-admin-token".to_string());
+    settings.retention.completed_after_days = None;
+    let db = test_db.db.clone();
+    let memo = MemoRepository::create(
+        &db,
+        "Retention preview".to_string(),
+        None,
+        Utc::now(),
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+    db.execute_unprepared(&format!(
+        "UPDATE memos SET completed = true, updated_at = '2000-01-01T00:00:00Z' WHERE id = '{}'",
+        memo.id
+    ))
+    .await
+    .unwrap();
+
+    let state = AppState::new(settings, db.clone());
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::preview_retention),
+    )
+    .await;
+
+    // Without a configured period the caller has to pick one.
+    let req = test::TestRequest::get()
+        .uri("/admin/retention/preview")
+        .insert_header(("Authorization", "Bearer test-admin-token"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::get()
+        .uri("/admin/retention/preview?older_than_days=3650")
+        .insert_header(("Authorization", "Bearer test-admin-token"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["action"], "archive");
+    assert_eq!(body["older_than_days"], 3650);
+    assert!(body["matching"].as_u64().unwrap() >= 1);
+    let ids: Vec<&str> = body["oldest"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|memo| memo["id"].as_str().unwrap())
+        .collect();
+    assert!(ids.contains(&memo.id.to_string().as_str()));
+
+    // A dry run changes nothing.
+    let unchanged = MemoRepository::find_by_id(&db, memo.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!unchanged.archived);
+
+    MemoRepository::delete(&db, memo.id).await.ok();
+}
+
+#[actix_web::get("/files/{name}")]
+async fn signed_file(_signed: actix_web_template::auth::SignedRequest) -> actix_web::HttpResponse {
+    actix_web::HttpResponse::Ok().body("file contents")
+}
+
+#[tokio::test]
+async fn test_signed_url_grants_access_without_auth_headers() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+    let signed = state
+        .url_signer
+        .sign(
+            "/files/report.pdf",
+            chrono::Duration::minutes(5),
+            Utc::now(),
+        )
+        .unwrap();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(signed_file),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri(&signed.url).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    // The signature is bound to the path.
+    let other = signed.url.replace("report.pdf", "salaries.pdf");
+    let req = test::TestRequest::get().uri(&other).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "UNAUTHORIZED");
+
+    let req = test::TestRequest::get()
+        .uri("/files/report.pdf")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+}
+
+#[tokio::test]
+async fn test_attachment_url_signs_the_image_and_thumbnail() {
+    use actix_web_template::{
+        attachments::Upload, dto::SignedAttachmentUrlDto, services::AttachmentService,
+    };
+
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db.clone());
+
+    let mut png = Vec::new();
+    image::RgbImage::from_pixel(64, 48, image::Rgb([20, 120, 200]))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+    let attachments = AttachmentService::new(db.clone().into())
+        .prepare(vec![Upload {
+            file_name: Some("chart.png".to_string()),
+            data: png,
+        }])
+        .await
+        .unwrap();
+    let memo = MemoService::new(db.clone().into())
+        .create_memo_with_attachments(MemoFixture::new("Signed attachment").build(), attachments)
+        .await
+        .unwrap();
+    let attachment = &memo.attachments[0];
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::get_attachment_url)
+            .service(handlers::get_attachment)
+            .service(handlers::get_attachment_thumbnail),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri(&attachment.url).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/attachments/{}/url?ttl=60", attachment.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let signed: SignedAttachmentUrlDto = test::read_body_json(resp).await;
+    let expires_in = signed.expires_at - Utc::now();
+    assert!(expires_in <= chrono::Duration::seconds(60));
+    assert!(expires_in > chrono::Duration::seconds(50));
+
+    let req = test::TestRequest::get().uri(&signed.url).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("Content-Type").unwrap(), "image/png");
+    let req = test::TestRequest::get()
+        .uri(&signed.thumbnail_url)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("Content-Type").unwrap(), "image/jpeg");
+
+    // Longer than SIGNED_URL_MAX_TTL_SECS
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/v1/attachments/{}/url?ttl=99999999999999999",
+            attachment.id
+        ))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/attachments/{}/url", uuid::Uuid::new_v4()))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "ATTACHMENT_NOT_FOUND");
+}
+
+#[tokio::test]
+async fn test_notification_inbox_lists_and_marks_read() {
+    use actix_web_template::repository::{MemoRepository, NewNotification, NotificationRepository};
+
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db.clone());
+
+    // Notifications are removed with their memo, which keeps the table clean.
+    let memo = MemoRepository::create(
+        &db,
+        "Inbox API memo".to_string(),
+        None,
+        Utc::now(),
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+    NotificationRepository::create_if_absent(
+        &db,
+        NewNotification {
+            kind: "memo_due_soon",
+            title: "Inbox API memo is due soon".to_string(),
+            body: Some("Due tomorrow".to_string()),
+            memo_id: Some(memo.id),
+            dedupe_key: Some(format!("memo_due_soon:{}", memo.id)),
+        },
+    )
+    .await
+    .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::list_notifications)
+            .service(handlers::mark_notification_read),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/notifications?unread=true&limit=100")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    let unread = body["unread"].as_u64().unwrap();
+    assert!(unread >= 1);
+    let notification = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|n| n["memo_id"] == memo.id.to_string())
+        .expect("new notification is listed")
+        .clone();
+    assert_eq!(notification["kind"], "memo_due_soon");
+    assert!(notification["read_at"].is_null());
+
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/v1/notifications/{}/read",
+            notification["id"].as_str().unwrap()
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["read_at"].is_string());
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/notifications?unread=true&limit=1")
+        .to_request();
+    let body: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(body["unread"].as_u64().unwrap(), unread - 1);
+
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/v1/notifications/{}/read",
+            uuid::Uuid::new_v4()
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/notifications?limit=0")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    MemoRepository::delete(&db, memo.id).await.unwrap();
+}
+
+/// Answers with the scripted results in order, recording each payload.
+#[derive(Clone, Default)]
+struct ScriptedWebhooks {
+    results: std::sync::Arc<std::sync::Mutex<Vec<Result<(), DeliveryError>>>>,
+    posted: std::sync::Arc<std::sync::Mutex<Vec<(String, serde_json::Value)>>>,
+}
+
+impl WebhookTransport for ScriptedWebhooks {
+    fn post<'a>(
+        &'a self,
+        url: &'a str,
+        payload: &'a serde_json::Value,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<(), DeliveryError>> + Send + 'a>>
+    {
+        self.posted
+            .lock()
+            .unwrap()
+            .push((url.to_string(), payload.clone()));
+        let result = self.results.lock().unwrap().pop().unwrap_or(Ok(()));
+        Box::pin(async move { result })
+    }
+}
+
+#[tokio::test]
+async fn test_webhook_deliveries_are_jobs_retried_after_transient_failures() {
+    use actix_web_template::entities::{operations, prelude::Operations};
+    use actix_web_template::repository::WebhookIntegrationRepository;
+    use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter};
+
+    let test_db = TestDb::new().await;
+    let mut settings = test_db.settings.clone();
+    settings.jobs.poll_interval_ms = 20;
+    let db = test_db.db.clone();
+    let url = "https://hooks.slack.com/services/T000/B000/retry";
+    WebhookIntegrationRepository::create(
+        &db,
+        "slack",
+        url.to_string(),
+        vec!["memo_created".to_string()],
+    )
+    .await
+    .unwrap();
+
+    let transport = ScriptedWebhooks::default();
+    // Popped from the end: a 503, then success
+    *transport.results.lock().unwrap() = vec![
+        Ok(()),
+        Err(DeliveryError::Transient(
+            "503 Service Unavailable".to_string(),
+        )),
+    ];
+    let (jobs, workers) = JobQueue::start(
+        db.clone().into(),
+        &settings.jobs,
+        settings.undo.window(),
+        MemoCache::default(),
+        Some(WebhookSender::new(
+            transport.clone(),
+            "https://memos.example.com".to_string(),
+        )),
+    );
+    let state = AppState::new(settings, db.clone())
+        .with_webhooks(WebhookQueue::new(db.clone().into(), jobs.clone()))
+        .with_jobs(jobs);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(MemoFixture::new("Posted to Slack").build())
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 201);
+
+    let delivery = || async {
+        Operations::find()
+            .filter(operations::Column::Kind.eq("deliver_webhook"))
+            .one(&db)
+            .await
+            .unwrap()
+            .expect("a delivery job")
+    };
+    let mut job = delivery().await;
+    for _ in 0..100 {
+        if job.attempts == 1 && job.status == "pending" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        job = delivery().await;
+    }
+    assert_eq!((job.attempts, job.status.as_str()), (1, "pending"));
+    assert!(job.run_after.is_some());
+    assert!(job.error.is_some());
+
+    // Skip the backoff
+    db.execute_unprepared("UPDATE operations SET run_after = now()")
+        .await
+        .unwrap();
+    for _ in 0..100 {
+        if job.status == "succeeded" {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        job = delivery().await;
+    }
+    assert_eq!((job.attempts, job.status.as_str()), (2, "succeeded"));
+
+    let posted = transport.posted.lock().unwrap().clone();
+    assert_eq!(posted.len(), 2);
+    assert_eq!(posted[1].0, url);
+    assert_eq!(posted[1].1["text"], "New memo: Posted to Slack");
+
+    for worker in workers {
+        worker.abort();
+    }
+}
+
+#[tokio::test]
+async fn test_webhook_integration_lifecycle() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::list_webhook_integrations)
+            .service(handlers::create_webhook_integration)
+            .service(handlers::delete_webhook_integration),
+    )
+    .await;
+
+    // A Slack integration may not point anywhere but Slack.
+    let req = test::TestRequest::post()
+        .uri("/api/v1/integrations/webhooks")
+        .set_json(serde_json::json!({
+            "provider": "slack",
+            "url": "https://169.254.169.254/services/T000/B000/XXXX",
+            "events": ["memo_created"]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["errors"][0]["field"], "url");
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/integrations/webhooks")
+        .set_json(serde_json::json!({
+            "provider": "discord",
+            "url": "https://discord.com/api/webhooks/123/abc",
+            "events": []
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/integrations/webhooks")
+        .set_json(serde_json::json!({
+            "provider": "discord",
+            "url": "https://discord.com/api/webhooks/123/secretTOKEN",
+            "events": ["memo_overdue", "memo_created", "memo_overdue"]
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    let created: serde_json::Value = test::read_body_json(resp).await;
+    let id = created["id"].as_str().unwrap().to_string();
+    assert_eq!(created["provider"], "discord");
+    assert_eq!(created["url_hint"], "https://discord.com/…OKEN");
+    assert_eq!(
+        created["events"],
+        serde_json::json!(["memo_created", "memo_overdue"])
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/integrations/webhooks")
+        .to_request();
+    let listed: serde_json::Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let listed = listed
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|integration| integration["id"] == id.as_str())
+        .expect("new integration is listed");
+    assert!(!listed.to_string().contains("secretTOKEN"));
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/v1/integrations/webhooks/{}", id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/v1/integrations/webhooks/{}", id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+}
+
+#[actix_web::test]
+async fn test_blockers_prevent_completion_until_resolved() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::complete_memos)
+            .service(handlers::create_memo)
+            .service(handlers::get_memo)
+            .service(handlers::patch_memo)
+            .service(handlers::delete_memo)
+            .service(handlers::toggle_complete)
+            .service(handlers::add_memo_blocker)
+            .service(handlers::remove_memo_blocker),
+    )
+    .await;
+
+    let mut ids = Vec::new();
+    for title in ["Pay rent", "Get invoice", "Ask landlord for IBAN"] {
+        let req = test::TestRequest::post()
+            .uri("/api/v1/memos")
+            .set_json(serde_json::json!({ "title": title, "date_to": "2099-03-04T15:00:00Z" }))
+            .to_request();
+        let memo: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+        ids.push(memo.id);
+    }
+    let (rent, invoice, iban) = (ids[0], ids[1], ids[2]);
+    let link = |memo: uuid::Uuid, blocker: uuid::Uuid| {
+        test::TestRequest::post()
+            .uri(&format!("/api/v1/memos/{}/blockers", memo))
+            .set_json(serde_json::json!({ "blocker_id": blocker }))
+            .to_request()
+    };
+
+    let linked: MemoResponseDto = test::call_and_read_body_json(&app, link(rent, invoice)).await;
+    assert_eq!(linked.blocked_by, vec![invoice]);
+    let resp = test::call_service(&app, link(rent, invoice)).await;
+    assert_eq!(resp.status(), 200, "linking twice is a no-op");
+    let linked: MemoResponseDto = test::call_and_read_body_json(&app, link(invoice, iban)).await;
+    assert_eq!(linked.blocked_by, vec![iban]);
+    assert_eq!(linked.blocks, vec![rent]);
+
+    let resp = test::call_service(&app, link(rent, rent)).await;
+    assert_eq!(resp.status(), 400);
+    let resp = test::call_service(&app, link(iban, rent)).await;
+    assert_eq!(
+        resp.status(),
+        400,
+        "rent -> invoice -> iban -> rent is a cycle"
+    );
+    let resp = test::call_service(&app, link(rent, uuid::Uuid::new_v4())).await;
+    assert_eq!(resp.status(), 404);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}/complete", rent))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 409);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert_eq!(body["code"], "MEMO_BLOCKED");
+    assert_eq!(body["blockers"][0]["id"], invoice.to_string());
+    assert_eq!(body["blockers"][0]["title"], "Get invoice");
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/api/v1/memos/{}", invoice))
+        .set_json(serde_json::json!({ "completed": true }))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 409);
+
+    // Rent and invoice go in one batch, but invoice still waits on iban.
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/memos/complete")
+        .set_json(serde_json::json!({ "ids": [rent, invoice] }))
+        .to_request();
+    let result: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(result["updated"], serde_json::json!([]));
+    assert_eq!(result["blocked"].as_array().unwrap().len(), 2);
+
+    let req = test::TestRequest::patch()
+        .uri("/api/v1/memos/complete")
+        .set_json(serde_json::json!({ "ids": [rent, invoice, iban] }))
+        .to_request();
+    let result: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(result["updated"].as_array().unwrap().len(), 3);
+    assert_eq!(result["blocked"], serde_json::json!([]));
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/v1/memos/{}/blockers/{}", rent, invoice))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/v1/memos/{}/blockers/{}", rent, invoice))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", invoice))
+        .to_request();
+    let memo: MemoResponseDto = test::call_and_read_body_json(&app, req).await;
+    assert!(memo.blocks.is_empty());
+    assert_eq!(memo.blocked_by, vec![iban]);
+}
+
+#[tokio::test]
+async fn test_memo_templates_fill_in_placeholders() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::list_memo_templates)
+            .service(handlers::create_memo_template)
+            .service(handlers::get_memo_template)
+            .service(handlers::update_memo_template)
+            .service(handlers::delete_memo_template)
+            .service(handlers::create_memo_from_template)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let name = format!("Invoice {}", uuid::Uuid::new_v4());
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memo-templates")
+        .set_json(serde_json::json!({
+            "name": name,
+            "title": "Invoice {{client}} for {{month}} {{year}}",
+            "description": "Prepared on {{date}}",
+            "due_text": "in 3 days",
+        }))
+        .to_request();
+    let template: MemoTemplateDto = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(template.name, name);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memo-templates")
+        .set_json(serde_json::json!({ "name": name, "title": "Taken" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400, "template names are unique");
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memo-templates")
+        .set_json(serde_json::json!({ "name": "Unreadable", "title": "x", "due_text": "someday" }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400, "due_text must be readable");
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memo-templates")
+        .to_request();
+    let templates: Vec<MemoTemplateDto> = test::call_and_read_body_json(&app, req).await;
+    assert!(templates.iter().any(|t| t.id == template.id));
+
+    let instantiate = |body: Option<serde_json::Value>| {
+        let req =
+            test::TestRequest::post().uri(&format!("/api/v1/memos/from-template/{}", template.id));
+        match body {
+            Some(body) => req.set_json(body).to_request(),
+            None => req.to_request(),
+        }
+    };
+
+    let resp = test::call_service(&app, instantiate(None)).await;
+    assert_eq!(resp.status(), 400, "{{{{client}}}} has no value");
+
+    let now = Utc::now();
+    let resp = test::call_service(
+        &app,
+        instantiate(Some(serde_json::json!({
+            "variables": { "client": "ACME" },
+            "timezone": "UTC",
+        }))),
+    )
+    .await;
+    assert_eq!(resp.status(), 201);
+    let memo: MemoResponseDto = test::read_body_json(resp).await;
+    assert_eq!(
+        memo.title,
+        format!("Invoice ACME for {}", now.format("%B %Y"))
+    );
+    assert_eq!(
+        memo.description.as_deref(),
+        Some(format!("Prepared on {}", now.format("%Y-%m-%d")).as_str())
+    );
+    assert_eq!(
+        memo.date_to.date_naive(),
+        (now + chrono::Duration::days(3)).date_naive(),
+        "due_text 'in 3 days' is read when the memo is created"
+    );
+
+    let resp = test::call_service(
+        &app,
+        instantiate(Some(serde_json::json!({
+            "variables": { "client": "Initech" },
+            "date_to": "2099-01-31T12:00:00Z",
+        }))),
+    )
+    .await;
+    assert_eq!(resp.status(), 201);
+    let dated: MemoResponseDto = test::read_body_json(resp).await;
+    assert_eq!(dated.date_to.to_rfc3339(), "2099-01-31T12:00:00+00:00");
+
+    let req = test::TestRequest::put()
+        .uri(&format!("/api/v1/memo-templates/{}", template.id))
+        .set_json(serde_json::json!({ "name": name, "title": "Weekly sync" }))
+        .to_request();
+    let updated: MemoTemplateDto = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(updated.title, "Weekly sync");
+    assert_eq!(updated.due_text, None);
+    let resp = test::call_service(&app, instantiate(None)).await;
+    assert_eq!(resp.status(), 400, "no due date anywhere");
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/v1/memo-templates/{}", template.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 204);
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memo-templates/{}", template.id))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+}
+
+#[tokio::test]
+async fn test_runtime_diagnostics_require_admin() {
+    let test_db = TestDb::new().await;
+    let mut settings = test_db.settings.clone();
+    settings.admin.token = Some("test-admin-token".to_string());
+    let db = test_db.db.clone();
+
+    let state = AppState::new(settings, db);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::get_runtime_diagnostics),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/debug/runtime").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 401);
+
+    let req = test::TestRequest::get()
+        .uri("/debug/runtime")
+        .insert_header(("Authorization", "Bearer test-admin-token"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body: serde_json::Value = test::read_body_json(resp).await;
+    assert!(body["background"]["workers"].as_u64().unwrap() >= 1);
+    assert!(body["http_worker"]["alive_tasks"].is_u64());
+    assert_eq!(body["tokio_console"], cfg!(feature = "tokio-console"));
+}
+
+#[actix_web::test]
+async fn test_export_streams_ndjson() {
+    let test_db = TestDb::new().await;
+    let state = AppState::new(test_db.settings.clone(), test_db.db.clone());
+    let service = MemoService::new(test_db.db.clone().into());
+
+    let mut ids = Vec::new();
+    for title in ["Export first", "Export second", "Export third"] {
+        let memo = service
+            .create_memo(MemoFixture::new(title).description("**body**").build())
+            .await
+            .unwrap();
+        ids.push(memo.id);
+    }
+    service.archive_memo(ids[1]).await.unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .app_data(actix_web_template::error::extractors::query_config())
+            .service(handlers::export_memos),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos/export?format=ndjson")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get("content-type").unwrap(),
+        "application/x-ndjson"
+    );
+    let body = test::read_body(resp).await;
+    let body = std::str::from_utf8(&body).unwrap();
+    assert!(body.ends_with('\n'));
+    let exported: Vec<MemoResponseDto> = body
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(exported.iter().map(|memo| memo.id).collect::<Vec<_>>(), ids);
+    assert!(exported[1].archived);
+    assert_eq!(exported[0].description.as_deref(), Some("**body**"));
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos/export?format=csv")
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    // Batches follow the cursor fetch size.
+    let batches: Vec<usize> = service
+        .export_memos(2)
+        .await
+        .unwrap()
+        .map(|batch| batch.unwrap().len())
+        .collect()
+        .await;
+    assert_eq!(batches, vec![2, 1]);
+}
+
+#[tokio::test]
+async fn test_import_runs_in_background_and_keeps_failures() {
+    let test_db = TestDb::new().await;
+    let settings = test_db.settings.clone();
+    let db = test_db.db.clone();
+    let (jobs, workers) = JobQueue::start(
+        db.clone().into(),
+        &settings.jobs,
+        settings.undo.window(),
+        MemoCache::default(),
+        None,
+    );
+    let state = AppState::new(settings, db);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone().with_jobs(jobs)))
+            .service(handlers::import_memos_async)
+            .service(handlers::list_import_failures)
+            .service(handlers::get_operation)
+            .service(handlers::list_memos),
+    )
+    .await;
+
+    let file = [
+        r#"{"title": "Imported first", "date_to": "2030-01-31T12:00:00Z", "color": "green"}"#,
+        "",
+        r#"{"title": "", "date_to": "2030-01-31T12:00:00Z"}"#,
+        "not json",
+        r#"{"title": "Imported second", "date_to_text": "tomorrow 5pm", "timezone": "Europe/Berlin"}"#,
+    ]
+    .join("\n");
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos/import/async")
+        .insert_header(("Content-Type", "application/x-ndjson"))
+        .set_payload(file)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 202);
+    assert!(resp.headers().get("Preference-Applied").is_none());
+    let location = resp
+        .headers()
+        .get("Location")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let mut operation: OperationDto = test::read_body_json(resp).await;
+    assert_eq!(operation.kind, "import_memos");
+    assert_eq!(operation.total, Some(4));
+
+    for _ in 0..100 {
+        if matches!(
+            operation.status,
+            OperationStatus::Succeeded | OperationStatus::Failed
+        ) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let req = test::TestRequest::get().uri(&location).to_request();
+        operation = test::read_body_json(test::call_service(&app, req).await).await;
+    }
+    assert_eq!(
+        operation.status,
+        OperationStatus::Succeeded,
+        "{:?}",
+        operation
+    );
+    assert_eq!(operation.processed, 4);
+    assert_eq!(
+        operation.result.unwrap(),
+        serde_json::json!({ "imported": 2, "failed": 2 })
+    );
+
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos?q=Imported&sort_by=title&order=asc")
+        .to_request();
+    let listed: PaginatedResponse<MemoResponseDto> =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    let titles: Vec<&str> = listed.data.iter().map(|memo| memo.title.as_str()).collect();
+    assert_eq!(titles, ["Imported first", "Imported second"]);
+    assert_eq!(listed.data[0].color.as_deref(), Some("green"));
+    assert_eq!(listed.data[1].timezone, "Europe/Berlin");
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/import/{}/failures", operation.id))
+        .to_request();
+    let failures: serde_json::Value =
+        test::read_body_json(test::call_service(&app, req).await).await;
+    assert_eq!(failures[0]["line"], 3);
+    assert_eq!(failures[0]["code"], "VALIDATION_TITLE_LENGTH");
+    assert_eq!(failures[1]["line"], 4);
+    assert_eq!(failures[1]["code"], "VALIDATION_FAILED");
+    assert_eq!(failures.as_array().unwrap().len(), 2);
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/v1/memos/import/{}/failures",
+            uuid::Uuid::new_v4()
+        ))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos/import/async")
+        .set_payload("\n  \n")
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 400);
+
+    for worker in workers {
+        worker.abort();
+    }
+}
+
+#[tokio::test]
+async fn test_get_responses_carry_cache_headers() {
+    use actix_web::http::header;
+    use actix_web_template::middleware::CacheHeaders;
+
+    let test_db = TestDb::new().await;
+    let cache_headers = CacheHeaders::from_config(&test_db.settings.http_cache).unwrap();
+    let state = AppState::new(test_db.settings.clone(), test_db.db.clone());
+
+    let app = test::init_service(
+        App::new()
+            .wrap(cache_headers)
+            .app_data(web::Data::new(state))
+            .service(handlers::create_memo)
+            .service(handlers::get_memo)
+            .service(handlers::list_memos),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(serde_json::json!({
+            "title": "Cacheable",
+            "date_to": "2030-01-31T12:00:00Z",
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 201);
+    assert!(resp.headers().get(header::CACHE_CONTROL).is_none());
+    let created: serde_json::Value = test::read_body_json(resp).await;
+
+    // The list may be served stale while a cache refreshes it, once per format
+    let req = test::TestRequest::get()
+        .uri("/api/v1/memos")
+        .insert_header((header::ACCEPT, "application/xml"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+        resp.headers().get(header::CACHE_CONTROL).unwrap(),
+        "public, max-age=5, stale-while-revalidate=30"
+    );
+    assert_eq!(
+        resp.headers().get(header::VARY).unwrap(),
+        "Accept, Prefer, Time-Zone"
+    );
+
+    let req = test::TestRequest::get()
+        .uri(&format!(
+            "/api/v1/memos/{}",
+            created["id"].as_str().unwrap()
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(
+        resp.headers().get(header::CACHE_CONTROL).unwrap(),
+        "private, no-cache"
+    );
+
+    // Errors are left alone
+    let req = test::TestRequest::get()
+        .uri(&format!("/api/v1/memos/{}", uuid::Uuid::new_v4()))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+    assert!(resp.headers().get(header::CACHE_CONTROL).is_none());
 }
@@ -1,10 +1,128 @@
-use actix_web_template::dto::CreateMemoDto;
-use chrono::Utc;
-
-pub fn create_test_memo_dto(title: &str, description: Option<&str>) -> CreateMemoDto {
-    CreateMemoDto {
-        title: title.to_string(),
-        description: description.map(|s| s.to_string()),
-        date_to: Utc::now(),
+use actix_web_template::{
+    dto::{CreateMemoDto, PatchMemoDto},
+    entities::memos,
+    repository::MemoRepository,
+};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+use sea_orm::ConnectionTrait;
+
+/// Fluent builder for memo test data.
+///
+/// Use [`MemoFixture::build`] when a test needs a `CreateMemoDto` to send
+/// through the API or service layer, [`MemoFixture::patch`] to change an
+/// existing memo to the fixture's title and description, and
+/// [`MemoFixture::persist`] when it just needs a row in the database.
+#[derive(Debug, Clone)]
+pub struct MemoFixture {
+    title: String,
+    description: Option<String>,
+    date_to: DateTime<Utc>,
+    completed: bool,
+}
+
+#[allow(dead_code)]
+impl MemoFixture {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            description: None,
+            date_to: Utc::now(),
+            completed: false,
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn completed(mut self) -> Self {
+        self.completed = true;
+        self
+    }
+
+    pub fn due_at(mut self, date_to: DateTime<Utc>) -> Self {
+        self.date_to = date_to;
+        self
+    }
+
+    pub fn due_in_days(mut self, days: i64) -> Self {
+        self.date_to = Utc::now() + Duration::days(days);
+        self
+    }
+
+    pub fn build(&self) -> CreateMemoDto {
+        CreateMemoDto {
+            title: self.title.clone(),
+            description: self.description.clone(),
+            date_to: Some(self.date_to.into()),
+            date_to_text: None,
+            timezone: None,
+            color: None,
+        }
+    }
+
+    /// A patch setting the title, and the description when one was given,
+    /// guarded by `version` when it is `Some`.
+    pub fn patch(&self, version: Option<i32>) -> PatchMemoDto {
+        PatchMemoDto {
+            title: Some(self.title.clone()),
+            description: self.description.clone(),
+            date_to: None,
+            date_to_text: None,
+            completed: None,
+            timezone: None,
+            color: None,
+            version,
+        }
+    }
+
+    pub async fn persist<C: ConnectionTrait>(self, db: &C) -> memos::Model {
+        let memo = MemoRepository::create(
+            db,
+            self.title,
+            self.description,
+            self.date_to,
+            Tz::UTC,
+            None,
+        )
+        .await
+        .expect("Failed to persist memo fixture");
+
+        if !self.completed {
+            return memo;
+        }
+
+        MemoRepository::update(
+            db,
+            memo.id,
+            memo.title,
+            memo.description,
+            memo.date_to.into(),
+            true,
+            Tz::UTC,
+            None,
+        )
+        .await
+        .expect("Failed to mark memo fixture as completed")
+    }
+
+    /// Persists `count` copies of this fixture, suffixing each title with its index.
+    pub async fn persist_many<C: ConnectionTrait>(self, db: &C, count: usize) -> Vec<memos::Model> {
+        let mut memos = Vec::with_capacity(count);
+        for i in 0..count {
+            let mut fixture = self.clone();
+            fixture.title = format!("{} {}", self.title, i);
+            memos.push(fixture.persist(db).await);
+        }
+        memos
+    }
+}
+
+#[allow(dead_code)]
+pub async fn cleanup_memos<C: ConnectionTrait>(db: &C, memos: &[memos::Model]) {
+    for memo in memos {
+        MemoRepository::delete(db, memo.id).await.ok();
     }
 }
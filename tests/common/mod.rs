@@ -1,10 +1,12 @@
 pub mod fixtures;
 
 use actix_web_template::{config::Settings, state::AppState};
-use sea_orm::Database;
+use migration::{Migrator, MigratorTrait};
+use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection};
+use uuid::Uuid;
 
 pub async fn setup_test_db() -> sea_orm::DatabaseConnection {
-    let settings = Settings::load().expect("Failed to load settings");
+    let settings = Settings::load_profile("test").expect("Failed to load settings");
     Database::connect(&settings.database.url)
         .await
         .expect("Failed to connect to test database")
@@ -12,7 +14,96 @@ pub async fn setup_test_db() -> sea_orm::DatabaseConnection {
 
 #[allow(dead_code)]
 pub async fn setup_test_state() -> AppState {
-    let settings = Settings::load().expect("Failed to load settings");
+    let settings = Settings::load_profile("test").expect("Failed to load settings");
     let db = setup_test_db().await;
     AppState::new(settings, db)
 }
+
+/// A Postgres schema of its own for one test, migrated from scratch and
+/// dropped with this value. Tests using it see only their own rows, so they
+/// can run in parallel and need no clean-up.
+///
+/// Keep it alive for the whole test: `let test_db = TestDb::new().await;`.
+#[allow(dead_code)]
+pub struct TestDb {
+    pub settings: Settings,
+    pub db: DatabaseConnection,
+    schema: String,
+}
+
+#[allow(dead_code)]
+impl TestDb {
+    pub async fn new() -> Self {
+        let settings = Settings::load_profile("test").expect("Failed to load settings");
+        let schema = format!("test_{}", Uuid::new_v4().simple());
+
+        // Tagged so Drop can find and end this pool's connections.
+        let separator = if settings.database.url.contains('?') {
+            '&'
+        } else {
+            '?'
+        };
+        let url = format!(
+            "{}{separator}application_name={schema}",
+            settings.database.url
+        );
+        let mut options = ConnectOptions::new(url);
+        options
+            .max_connections(5)
+            .set_schema_search_path(&schema)
+            .sqlx_logging(false);
+        let db = Database::connect(options)
+            .await
+            .expect("Failed to connect to test database");
+        db.execute_unprepared(&format!("CREATE SCHEMA \"{}\"", schema))
+            .await
+            .expect("Failed to create test schema");
+        Migrator::up(&db, None)
+            .await
+            .expect("Failed to migrate test schema");
+
+        Self {
+            settings,
+            db,
+            schema,
+        }
+    }
+
+    /// Application state on this schema with the test settings.
+    pub fn state(&self) -> AppState {
+        AppState::new(self.settings.clone(), self.db.clone())
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        // Drop runs on the test's runtime, so the schema is removed from a
+        // thread with a runtime of its own. While the test's runtime is
+        // blocked its pool cannot roll back abandoned transactions, whose
+        // locks would stall DROP SCHEMA, so its connections are ended first.
+        let url = self.settings.database.url.clone();
+        let schema = self.schema.clone();
+        let dropped = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()?;
+            runtime.block_on(async {
+                let db = Database::connect(&url).await?;
+                db.execute_unprepared(&format!(
+                    "SELECT pg_terminate_backend(pid) FROM pg_stat_activity \
+                     WHERE application_name = '{}'",
+                    schema
+                ))
+                .await?;
+                db.execute_unprepared(&format!("DROP SCHEMA IF EXISTS \"{}\" CASCADE", schema))
+                    .await?;
+                db.close().await?;
+                Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+            })
+        })
+        .join();
+        if !matches!(dropped, Ok(Ok(()))) {
+            eprintln!("Failed to drop test schema {}", self.schema);
+        }
+    }
+}
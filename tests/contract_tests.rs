@@ -0,0 +1,749 @@
+//! Checks real handler responses against the generated OpenAPI document, so
+//! the docs cannot silently drift from the code.
+
+mod common;
+
+use actix_web::{App, http::Method, test, web};
+use actix_web_template::{
+    docs::{ApiDoc, SECTIONS},
+    handlers,
+    repository::MemoRepository,
+};
+use common::{fixtures::MemoFixture, setup_test_state};
+use serde_json::{Value, json};
+use utoipa::OpenApi;
+
+/// The OpenAPI document as JSON, with every documented object closed to
+/// undocumented properties.
+struct Spec(Value);
+
+impl Spec {
+    fn load() -> Self {
+        let mut doc = serde_json::to_value(ApiDoc::openapi()).expect("OpenAPI serializes");
+        close_objects(&mut doc["components"]);
+        Self(doc)
+    }
+
+    /// Asserts that `status` is documented for `method path` and that `body`
+    /// matches its schema. `path` is the template, e.g. `/api/v1/memos/{id}`.
+    fn assert_response(&self, method: &Method, path: &str, status: u16, body: Option<&Value>) {
+        let operation = &self.0["paths"][path][method.as_str().to_lowercase()];
+        assert!(
+            !operation.is_null(),
+            "{method} {path} is not documented in the OpenAPI spec"
+        );
+
+        let response = &operation["responses"][status.to_string()];
+        assert!(
+            !response.is_null(),
+            "{method} {path} returned undocumented status {status}"
+        );
+
+        let schema = &response["content"]["application/json"]["schema"];
+        let Some(body) = body else {
+            assert!(
+                schema.is_null(),
+                "{method} {path} {status} is documented with a body but returned none"
+            );
+            return;
+        };
+        assert!(
+            !schema.is_null(),
+            "{method} {path} {status} returned a body but documents none"
+        );
+
+        self.assert_valid(schema, body, &format!("{method} {path} {status}"));
+    }
+
+    fn assert_valid(&self, schema: &Value, instance: &Value, context: &str) {
+        let root = json!({ "allOf": [schema], "components": self.0["components"] });
+        let validator = jsonschema::validator_for(&root).expect("schema compiles");
+        let errors: Vec<String> = validator
+            .iter_errors(instance)
+            .map(|e| format!("{} at {}", e, e.instance_path))
+            .collect();
+        assert!(
+            errors.is_empty(),
+            "{context} does not match the OpenAPI schema:\n{}\nbody: {instance:#}",
+            errors.join("\n")
+        );
+    }
+}
+
+/// Adds `additionalProperties: false` to object schemas that list their
+/// properties, so fields added to a DTO without docs fail the test.
+fn close_objects(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if map.contains_key("properties") && !map.contains_key("additionalProperties") {
+                map.insert("additionalProperties".to_string(), Value::Bool(false));
+            }
+            map.values_mut().for_each(close_objects);
+        }
+        Value::Array(items) => items.iter_mut().for_each(close_objects),
+        _ => {}
+    }
+}
+
+#[actix_web::test]
+async fn test_schema_examples_match_their_schemas() {
+    let spec = Spec::load();
+    let schemas = spec.0["components"]["schemas"]
+        .as_object()
+        .expect("components.schemas present");
+
+    let mut checked = 0;
+    for (name, schema) in schemas {
+        let Some(properties) = schema["properties"].as_object() else {
+            continue;
+        };
+        for (field, property) in properties {
+            if let Some(example) = property.get("example") {
+                spec.assert_valid(property, example, &format!("{name}.{field} example"));
+                checked += 1;
+            }
+        }
+    }
+
+    assert!(checked > 0, "no examples found in the OpenAPI spec");
+}
+
+#[actix_web::test]
+async fn test_request_dtos_have_examples() {
+    let spec = Spec::load();
+    let schemas = &spec.0["components"]["schemas"];
+
+    for name in [
+        "CreateMemoDto",
+        "UpdateMemoDto",
+        "PatchMemoDto",
+        "MoveMemoDto",
+        "LinkBlockerDto",
+        "SaveMemoTemplateDto",
+        "InstantiateTemplateDto",
+        "MemoResponseDto",
+        "PushSubscriptionDto",
+        "PushKeysDto",
+        "PushUnsubscribeDto",
+        "VapidPublicKeyResponse",
+        "HealthResponse",
+        "MaintenanceStatus",
+    ] {
+        let properties = schemas[name]["properties"]
+            .as_object()
+            .unwrap_or_else(|| panic!("{name} is not in the spec"));
+        for (field, property) in properties {
+            // References, including nullable ones, carry their own examples.
+            if property.get("$ref").is_some()
+                || property.get("oneOf").is_some()
+                || property["type"] == "array"
+            {
+                continue;
+            }
+            assert!(
+                property.get("example").is_some(),
+                "{name}.{field} has no example"
+            );
+        }
+    }
+}
+
+#[actix_web::test]
+async fn test_memo_endpoints_match_spec() {
+    let spec = Spec::load();
+    let state = setup_test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::list_memos)
+            .service(handlers::memo_stats)
+            .service(handlers::complete_memos)
+            .service(handlers::delete_memos)
+            .service(handlers::undo_delete)
+            .service(handlers::get_memo)
+            .service(handlers::create_memo)
+            .service(handlers::update_memo)
+            .service(handlers::patch_memo)
+            .service(handlers::delete_memo)
+            .service(handlers::toggle_complete)
+            .service(handlers::move_memo)
+            .service(handlers::duplicate_memo)
+            .service(handlers::archive_memo)
+            .service(handlers::unarchive_memo)
+            .service(handlers::toggle_pinned)
+            .service(handlers::toggle_starred)
+            .service(handlers::snooze_memo)
+            .service(handlers::list_memo_snoozes)
+            .service(handlers::add_memo_blocker)
+            .service(handlers::remove_memo_blocker)
+            .service(handlers::get_attachment_url)
+            .service(handlers::get_operation)
+            .service(handlers::operation_events),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(
+            MemoFixture::new("Contract test")
+                .description("Desc")
+                .build(),
+        )
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status().as_u16();
+    let created: Value = test::read_body_json(resp).await;
+    spec.assert_response(&Method::POST, "/api/v1/memos", status, Some(&created));
+    let id = created["id"].as_str().unwrap().to_string();
+    let memo_path = format!("/api/v1/memos/{id}");
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(MemoFixture::new("Contract blocker").build())
+        .to_request();
+    let blocker: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let blocker_id = blocker["id"].as_str().unwrap().to_string();
+
+    let cases = [
+        (
+            Method::GET,
+            "/api/v1/memos?limit=5".to_string(),
+            "/api/v1/memos",
+            None,
+        ),
+        (Method::GET, memo_path.clone(), "/api/v1/memos/{id}", None),
+        (
+            Method::PATCH,
+            memo_path.clone(),
+            "/api/v1/memos/{id}",
+            Some(json!({ "title": "Contract test (edited)" })),
+        ),
+        (
+            Method::PATCH,
+            memo_path.clone(),
+            "/api/v1/memos/{id}",
+            Some(json!({ "title": "Stale", "version": 0 })),
+        ),
+        (
+            Method::PUT,
+            memo_path.clone(),
+            "/api/v1/memos/{id}",
+            Some(json!({ "title": "", "date_to": created["date_to"], "completed": false })),
+        ),
+        (
+            Method::POST,
+            format!("{memo_path}/snooze"),
+            "/api/v1/memos/{id}/snooze",
+            Some(json!({ "preset": "tomorrow" })),
+        ),
+        (
+            Method::POST,
+            format!("{memo_path}/snooze"),
+            "/api/v1/memos/{id}/snooze",
+            Some(json!({ "preset": "1h", "minutes": 30 })),
+        ),
+        (
+            Method::GET,
+            format!("{memo_path}/snoozes"),
+            "/api/v1/memos/{id}/snoozes",
+            None,
+        ),
+        (
+            Method::GET,
+            format!("/api/v1/attachments/{id}/url"),
+            "/api/v1/attachments/{id}/url",
+            None,
+        ),
+        (
+            Method::POST,
+            format!("{memo_path}/blockers"),
+            "/api/v1/memos/{id}/blockers",
+            Some(json!({ "blocker_id": blocker_id })),
+        ),
+        (
+            Method::POST,
+            format!("{memo_path}/blockers"),
+            "/api/v1/memos/{id}/blockers",
+            Some(json!({ "blocker_id": id })),
+        ),
+        (
+            Method::PATCH,
+            format!("{memo_path}/complete"),
+            "/api/v1/memos/{id}/complete",
+            None,
+        ),
+        (
+            Method::DELETE,
+            format!("{memo_path}/blockers/{blocker_id}"),
+            "/api/v1/memos/{id}/blockers/{blocker_id}",
+            None,
+        ),
+        (
+            Method::DELETE,
+            format!("{memo_path}/blockers/{blocker_id}"),
+            "/api/v1/memos/{id}/blockers/{blocker_id}",
+            None,
+        ),
+        (
+            Method::PATCH,
+            format!("{memo_path}/complete"),
+            "/api/v1/memos/{id}/complete",
+            Some(json!({ "note": "Resolved in the contract test" })),
+        ),
+        (
+            Method::PATCH,
+            format!("{memo_path}/complete"),
+            "/api/v1/memos/{id}/complete",
+            Some(json!({ "note": "Cannot reopen with a note" })),
+        ),
+        (
+            Method::PATCH,
+            format!("{memo_path}/move"),
+            "/api/v1/memos/{id}/move",
+            Some(json!({ "position": 0 })),
+        ),
+        (
+            Method::PATCH,
+            "/api/v1/memos/complete".to_string(),
+            "/api/v1/memos/complete",
+            Some(json!({ "ids": [id] })),
+        ),
+        (
+            Method::PATCH,
+            "/api/v1/memos/complete".to_string(),
+            "/api/v1/memos/complete",
+            Some(json!({})),
+        ),
+        (
+            Method::POST,
+            format!("{memo_path}/duplicate?days_offset=99999"),
+            "/api/v1/memos/{id}/duplicate",
+            None,
+        ),
+        (
+            Method::PATCH,
+            format!("{memo_path}/pin"),
+            "/api/v1/memos/{id}/pin",
+            None,
+        ),
+        (
+            Method::PATCH,
+            format!("{memo_path}/star"),
+            "/api/v1/memos/{id}/star",
+            None,
+        ),
+        (
+            Method::POST,
+            format!("{memo_path}/archive"),
+            "/api/v1/memos/{id}/archive",
+            None,
+        ),
+        (
+            Method::POST,
+            format!("{memo_path}/unarchive"),
+            "/api/v1/memos/{id}/unarchive",
+            None,
+        ),
+        (
+            Method::GET,
+            format!("/api/v1/memos/{}", uuid::Uuid::new_v4()),
+            "/api/v1/memos/{id}",
+            None,
+        ),
+        (
+            Method::DELETE,
+            memo_path.clone(),
+            "/api/v1/memos/{id}",
+            None,
+        ),
+        (
+            Method::POST,
+            "/api/v1/memos/delete".to_string(),
+            "/api/v1/memos/delete",
+            Some(json!({ "ids": [blocker_id, id] })),
+        ),
+        (
+            Method::POST,
+            "/api/v1/memos/delete".to_string(),
+            "/api/v1/memos/delete",
+            Some(json!({ "ids": [] })),
+        ),
+        (
+            Method::POST,
+            format!("/api/v1/undo/{}", uuid::Uuid::new_v4()),
+            "/api/v1/undo/{token}",
+            None,
+        ),
+        (
+            Method::GET,
+            format!("/api/v1/operations/{}", uuid::Uuid::new_v4()),
+            "/api/v1/operations/{id}",
+            None,
+        ),
+        (
+            Method::GET,
+            format!("/api/v1/operations/{}/events", uuid::Uuid::new_v4()),
+            "/api/v1/operations/{id}/events",
+            None,
+        ),
+    ];
+
+    for (method, uri, template, payload) in cases {
+        let mut req = test::TestRequest::default()
+            .method(method.clone())
+            .uri(&uri);
+        if let Some(payload) = payload {
+            req = req.set_json(payload);
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        let status = resp.status().as_u16();
+        let bytes = test::read_body(resp).await;
+        let body: Option<Value> = if bytes.is_empty() {
+            None
+        } else {
+            Some(serde_json::from_slice(&bytes).expect("JSON body"))
+        };
+
+        spec.assert_response(&method, template, status, body.as_ref());
+    }
+
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memos")
+        .set_json(MemoFixture::new("Contract undo").build())
+        .to_request();
+    let memo: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let req = test::TestRequest::delete()
+        .uri(&format!("/api/v1/memos/{}", memo["id"].as_str().unwrap()))
+        .to_request();
+    let deleted: Value = test::read_body_json(test::call_service(&app, req).await).await;
+    let req = test::TestRequest::post()
+        .uri(&format!(
+            "/api/v1/undo/{}",
+            deleted["undo_token"].as_str().unwrap()
+        ))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status().as_u16();
+    let restored: Value = test::read_body_json(resp).await;
+    spec.assert_response(
+        &Method::POST,
+        "/api/v1/undo/{token}",
+        status,
+        Some(&restored),
+    );
+
+    for memo in [&created, &blocker, &memo] {
+        let id = memo["id"].as_str().unwrap().parse().unwrap();
+        MemoRepository::delete(&db, id).await.ok();
+    }
+}
+
+#[actix_web::test]
+async fn test_template_endpoints_match_spec() {
+    let spec = Spec::load();
+    let state = setup_test_state().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::list_memo_templates)
+            .service(handlers::create_memo_template)
+            .service(handlers::get_memo_template)
+            .service(handlers::update_memo_template)
+            .service(handlers::delete_memo_template)
+            .service(handlers::create_memo_from_template)
+            .service(handlers::delete_memo),
+    )
+    .await;
+
+    let name = format!("Contract template {}", uuid::Uuid::new_v4());
+    let req = test::TestRequest::post()
+        .uri("/api/v1/memo-templates")
+        .set_json(json!({
+            "name": name,
+            "title": "Invoice {{client}} for {{month}}",
+            "description": "Prepared on {{date}}",
+            "due_text": "in 3 days",
+        }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status().as_u16();
+    let created: Value = test::read_body_json(resp).await;
+    spec.assert_response(
+        &Method::POST,
+        "/api/v1/memo-templates",
+        status,
+        Some(&created),
+    );
+    let id = created["id"].as_str().unwrap().to_string();
+    let template_path = format!("/api/v1/memo-templates/{id}");
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/api/v1/memos/from-template/{id}"))
+        .set_json(json!({ "variables": { "client": "ACME" } }))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let status = resp.status().as_u16();
+    let memo: Value = test::read_body_json(resp).await;
+    spec.assert_response(
+        &Method::POST,
+        "/api/v1/memos/from-template/{id}",
+        status,
+        Some(&memo),
+    );
+    let memo_id = memo["id"].as_str().unwrap().to_string();
+
+    let cases = [
+        (
+            Method::POST,
+            "/api/v1/memo-templates".to_string(),
+            "/api/v1/memo-templates",
+            Some(json!({ "name": name, "title": "Duplicate" })),
+        ),
+        (
+            Method::GET,
+            "/api/v1/memo-templates".to_string(),
+            "/api/v1/memo-templates",
+            None,
+        ),
+        (
+            Method::GET,
+            template_path.clone(),
+            "/api/v1/memo-templates/{id}",
+            None,
+        ),
+        (
+            Method::PUT,
+            template_path.clone(),
+            "/api/v1/memo-templates/{id}",
+            Some(json!({ "name": name, "title": "Invoice {{client}}", "due_text": "tomorrow" })),
+        ),
+        (
+            Method::POST,
+            format!("/api/v1/memos/from-template/{id}"),
+            "/api/v1/memos/from-template/{id}",
+            None,
+        ),
+        (
+            Method::DELETE,
+            template_path.clone(),
+            "/api/v1/memo-templates/{id}",
+            None,
+        ),
+        (
+            Method::DELETE,
+            template_path.clone(),
+            "/api/v1/memo-templates/{id}",
+            None,
+        ),
+        (
+            Method::DELETE,
+            format!("/api/v1/memos/{memo_id}"),
+            "/api/v1/memos/{id}",
+            None,
+        ),
+    ];
+
+    for (method, uri, template, payload) in cases {
+        let mut req = test::TestRequest::default()
+            .method(method.clone())
+            .uri(&uri);
+        if let Some(payload) = payload {
+            req = req.set_json(payload);
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        let status = resp.status().as_u16();
+        let bytes = test::read_body(resp).await;
+        let body: Option<Value> = if bytes.is_empty() {
+            None
+        } else {
+            Some(serde_json::from_slice(&bytes).expect("JSON body"))
+        };
+
+        spec.assert_response(&method, template, status, body.as_ref());
+    }
+}
+
+#[actix_web::test]
+async fn test_observability_and_push_endpoints_match_spec() {
+    let spec = Spec::load();
+    let mut state = setup_test_state().await;
+    state.push = None;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::health_check)
+            .service(handlers::ready)
+            .service(handlers::startup)
+            .service(handlers::version)
+            .service(handlers::get_maintenance)
+            .service(handlers::preview_retention)
+            .service(handlers::get_runtime_diagnostics)
+            .service(handlers::get_push_public_key)
+            .service(handlers::list_notifications)
+            .service(handlers::list_webhook_integrations),
+    )
+    .await;
+
+    for (path, template) in [
+        ("/health", "/health"),
+        ("/ready", "/ready"),
+        ("/startup", "/startup"),
+        ("/version", "/version"),
+        ("/admin/maintenance", "/admin/maintenance"),
+        ("/admin/retention/preview", "/admin/retention/preview"),
+        ("/debug/runtime", "/debug/runtime"),
+        ("/api/v1/push/public-key", "/api/v1/push/public-key"),
+        ("/api/v1/notifications", "/api/v1/notifications"),
+        (
+            "/api/v1/integrations/webhooks",
+            "/api/v1/integrations/webhooks",
+        ),
+    ] {
+        let req = test::TestRequest::get().uri(path).to_request();
+        let resp = test::call_service(&app, req).await;
+        let status = resp.status().as_u16();
+        let body: Value = test::read_body_json(resp).await;
+
+        spec.assert_response(&Method::GET, template, status, Some(&body));
+    }
+}
+
+#[actix_web::test]
+async fn test_web_endpoints_match_spec() {
+    let spec = Spec::load();
+    let state = setup_test_state().await;
+    let db = state.db.clone();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(handlers::index)
+            .service(handlers::get_memos_list)
+            .service(handlers::get_edit_memo_form)
+            .service(handlers::create_memo_web)
+            .service(handlers::toggle_memo_complete_web)
+            .service(handlers::get_flash)
+            .service(handlers::get_calendar),
+    )
+    .await;
+
+    let memo = MemoFixture::new("Contract web").persist(&db).await;
+    let date_to = memo.date_to.format("%Y-%m-%dT%H:%M").to_string();
+    let missing = uuid::Uuid::new_v4();
+
+    type Form<'a> = Vec<(&'a str, &'a str)>;
+    let cases: Vec<(Method, String, &str, Option<Form>)> = vec![
+        (Method::GET, "/".to_string(), "/", None),
+        (Method::GET, "/web/memos".to_string(), "/web/memos", None),
+        (
+            Method::GET,
+            format!("/web/memos/{}/edit", memo.id),
+            "/web/memos/{id}/edit",
+            None,
+        ),
+        (
+            Method::GET,
+            format!("/web/memos/{missing}/edit"),
+            "/web/memos/{id}/edit",
+            None,
+        ),
+        (
+            Method::POST,
+            "/web/memos".to_string(),
+            "/web/memos",
+            Some(vec![("title", ""), ("date_to", &date_to)]),
+        ),
+        (
+            Method::PATCH,
+            format!("/web/memos/{}/toggle", memo.id),
+            "/web/memos/{id}/toggle",
+            None,
+        ),
+        (Method::GET, "/web/flash".to_string(), "/web/flash", None),
+        (
+            Method::GET,
+            "/web/calendar".to_string(),
+            "/web/calendar",
+            None,
+        ),
+    ];
+
+    for (method, uri, template, form) in cases {
+        let mut req = test::TestRequest::default()
+            .method(method.clone())
+            .uri(&uri);
+        if let Some(form) = form {
+            req = req.set_form(form);
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        let status = resp.status().as_u16();
+        let is_json = resp
+            .headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.starts_with("application/json"));
+        let bytes = test::read_body(resp).await;
+        // HTML pages and fragments have no JSON schema to check; errors do.
+        let body: Option<Value> =
+            is_json.then(|| serde_json::from_slice(&bytes).expect("JSON body"));
+
+        spec.assert_response(&method, template, status, body.as_ref());
+    }
+
+    MemoRepository::delete(&db, memo.id).await.ok();
+}
+
+#[actix_web::test]
+async fn test_sections_cover_the_whole_document() {
+    let full = serde_json::to_value(ApiDoc::openapi()).expect("OpenAPI serializes");
+    let mut covered: Vec<String> = Vec::new();
+
+    for section in SECTIONS {
+        let doc = serde_json::to_value(section.openapi()).expect("OpenAPI serializes");
+        let paths = doc["paths"].as_object().expect("section has paths");
+        assert!(!paths.is_empty(), "section {} is empty", section.name);
+
+        for (path, item) in paths {
+            for (method, operation) in item.as_object().unwrap() {
+                assert_eq!(
+                    operation["tags"],
+                    json!([section.tag]),
+                    "{method} {path} is in section {} but not tagged {}",
+                    section.name,
+                    section.tag
+                );
+            }
+            covered.push(path.clone());
+        }
+
+        let mut refs = Vec::new();
+        collect_refs(&doc, &mut refs);
+        for reference in refs {
+            let name = reference.trim_start_matches("#/components/schemas/");
+            assert!(
+                !doc["components"]["schemas"][name].is_null(),
+                "section {} refers to {reference} without defining it",
+                section.name
+            );
+        }
+    }
+
+    let mut all: Vec<String> = full["paths"].as_object().unwrap().keys().cloned().collect();
+    all.sort();
+    covered.sort();
+    covered.dedup();
+    assert_eq!(covered, all, "every path belongs to a section");
+}
+
+fn collect_refs(value: &Value, refs: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get("$ref") {
+                refs.push(reference.clone());
+            }
+            map.values().for_each(|v| collect_refs(v, refs));
+        }
+        Value::Array(items) => items.iter().for_each(|v| collect_refs(v, refs)),
+        _ => {}
+    }
+}
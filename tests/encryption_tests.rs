@@ -0,0 +1,214 @@
+//! Runs as its own test binary: the field cipher is process-wide, and the
+//! other suites expect descriptions in plaintext. Every test works inside a
+//! transaction that is rolled back, so no ciphertext is left behind.
+
+mod common;
+
+use actix_web_template::{
+    config::settings::EncryptionKey,
+    crypto::{self, FieldCipher},
+    repository::{MemoFilter, MemoRepository, WebhookIntegrationRepository},
+};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use chrono::Utc;
+use chrono_tz::Tz;
+use common::setup_test_db;
+use sea_orm::{ConnectionTrait, DatabaseBackend, Statement, TransactionTrait};
+use uuid::Uuid;
+
+fn install_cipher() {
+    let cipher = FieldCipher::new(&[EncryptionKey {
+        id: "test".to_string(),
+        key: STANDARD.encode([7u8; 32]),
+    }])
+    .unwrap();
+    let _ = crypto::install(cipher);
+}
+
+async fn stored_description<C: ConnectionTrait>(db: &C, id: Uuid) -> Option<String> {
+    db.query_one(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "SELECT description FROM memos WHERE id = $1",
+        [id.into()],
+    ))
+    .await
+    .unwrap()
+    .unwrap()
+    .try_get("", "description")
+    .unwrap()
+}
+
+#[tokio::test]
+async fn test_descriptions_are_encrypted_at_rest() {
+    install_cipher();
+    let db = setup_test_db().await;
+    let txn = db.begin().await.unwrap();
+
+    let marker = format!("encrypted-{}", Uuid::new_v4());
+    let memo = MemoRepository::create(
+        &txn,
+        marker.clone(),
+        Some("Door code 4711".to_string()),
+        Utc::now(),
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+    assert_eq!(memo.description.as_deref(), Some("Door code 4711"));
+
+    let stored = stored_description(&txn, memo.id).await.unwrap();
+    assert!(stored.starts_with("enc:v1:test:"), "{}", stored);
+    assert!(!stored.contains("4711"));
+
+    let found = MemoRepository::find_by_id(&txn, memo.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.description.as_deref(), Some("Door code 4711"));
+
+    // Archiving rewrites the row without touching the sealed description.
+    let archived = MemoRepository::set_archived(&txn, memo.id, true)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(archived.description.as_deref(), Some("Door code 4711"));
+    assert_eq!(stored_description(&txn, memo.id).await.unwrap(), stored);
+
+    // Ciphertext is not searchable; titles still are.
+    let filter = |search: &str| MemoFilter {
+        search: Some(search.to_string()),
+        archived: true,
+        ..MemoFilter::default()
+    };
+    let (by_description, _) =
+        MemoRepository::find_all(&txn, 10, 0, &filter("Door code"), "created_at", "desc")
+            .await
+            .unwrap();
+    assert!(by_description.iter().all(|m| m.id != memo.id));
+    let (by_title, _) =
+        MemoRepository::find_all(&txn, 10, 0, &filter(&marker), "created_at", "desc")
+            .await
+            .unwrap();
+    assert_eq!(by_title.len(), 1);
+
+    txn.rollback().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_backfill_encrypts_plaintext_descriptions() {
+    install_cipher();
+    let db = setup_test_db().await;
+    let txn = db.begin().await.unwrap();
+
+    let memo = MemoRepository::create(
+        &txn,
+        "Written before encryption".to_string(),
+        None,
+        Utc::now(),
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+    txn.execute(Statement::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        "UPDATE memos SET description = 'legacy plaintext' WHERE id = $1",
+        [memo.id.into()],
+    ))
+    .await
+    .unwrap();
+
+    // Plaintext rows stay readable before the backfill.
+    let legacy = MemoRepository::find_by_id(&txn, memo.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(legacy.description.as_deref(), Some("legacy plaintext"));
+
+    let mut after = None;
+    while let Some((_, last)) = MemoRepository::reencrypt_descriptions(&txn, after, 50)
+        .await
+        .unwrap()
+    {
+        after = Some(last);
+    }
+
+    let stored = stored_description(&txn, memo.id).await.unwrap();
+    assert!(stored.starts_with("enc:v1:test:"), "{}", stored);
+    let backfilled = MemoRepository::find_by_id(&txn, memo.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(backfilled.description.as_deref(), Some("legacy plaintext"));
+    assert_eq!(backfilled.version, memo.version);
+
+    // A second pass finds nothing left to do.
+    assert!(
+        MemoRepository::reencrypt_descriptions(&txn, None, 50)
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    txn.rollback().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_webhook_urls_are_encrypted_at_rest() {
+    install_cipher();
+    let db = setup_test_db().await;
+    let txn = db.begin().await.unwrap();
+
+    let url = "https://hooks.slack.com/services/T000/B000/s3cret".to_string();
+    let integration = WebhookIntegrationRepository::create(
+        &txn,
+        "slack",
+        url.clone(),
+        vec!["memo_created".to_string()],
+    )
+    .await
+    .unwrap();
+    assert_eq!(integration.url, url);
+
+    let stored: String = txn
+        .query_one(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT url FROM webhook_integrations WHERE id = $1",
+            [integration.id.into()],
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "url")
+        .unwrap();
+    assert!(stored.starts_with("enc:v1:test:"), "{}", stored);
+    assert!(!stored.contains("s3cret"));
+
+    let found = WebhookIntegrationRepository::find_for_event(&txn, "memo_created")
+        .await
+        .unwrap()
+        .into_iter()
+        .find(|found| found.id == integration.id)
+        .unwrap();
+    assert_eq!(found.url, url);
+
+    // Already under the current key, so the backfill leaves it alone.
+    WebhookIntegrationRepository::reencrypt_urls(&txn)
+        .await
+        .unwrap();
+    let after: String = txn
+        .query_one(Statement::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            "SELECT url FROM webhook_integrations WHERE id = $1",
+            [integration.id.into()],
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get("", "url")
+        .unwrap();
+    assert_eq!(after, stored);
+
+    txn.rollback().await.unwrap();
+}
@@ -0,0 +1,172 @@
+//! Runs as its own test binary: event recording is switched on process-wide,
+//! and the other suites do not expect outbox rows. Services commit their own
+//! transactions, so each test deletes the memos and events it created.
+
+mod common;
+
+use actix_web_template::{
+    config::settings::{EventBroker, EventsConfig},
+    entities::{outbox_events, prelude::*},
+    events::{self, EventPublisher, PublishError},
+    scheduler::{Task, TaskContext, tasks::OutboxRelay},
+    services::MemoService,
+};
+use chrono::{Duration, Utc};
+use common::{fixtures::MemoFixture, setup_test_db};
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+async fn events_for(db: &DatabaseConnection, memo_id: Uuid) -> Vec<outbox_events::Model> {
+    OutboxEvents::find()
+        .filter(outbox_events::Column::AggregateId.eq(memo_id))
+        .order_by_asc(outbox_events::Column::Seq)
+        .all(db)
+        .await
+        .unwrap()
+}
+
+async fn cleanup(db: &DatabaseConnection, memo_id: Uuid) {
+    Memos::delete_by_id(memo_id).exec(db).await.unwrap();
+    OutboxEvents::delete_many()
+        .filter(outbox_events::Column::AggregateId.eq(memo_id))
+        .exec(db)
+        .await
+        .unwrap();
+}
+
+/// Records published event ids, failing once for the event in `fail_once`.
+#[derive(Default)]
+struct RecordingPublisher {
+    published: Mutex<Vec<Uuid>>,
+    fail_once: Mutex<Option<Uuid>>,
+}
+
+impl EventPublisher for RecordingPublisher {
+    fn publish<'a>(
+        &'a self,
+        event: &'a outbox_events::Model,
+    ) -> Pin<Box<dyn Future<Output = Result<(), PublishError>> + Send + 'a>> {
+        Box::pin(async move {
+            let mut fail_once = self.fail_once.lock().unwrap();
+            if *fail_once == Some(event.id) {
+                *fail_once = None;
+                return Err(PublishError::Broker("broker unavailable".to_string()));
+            }
+            self.published.lock().unwrap().push(event.id);
+            Ok(())
+        })
+    }
+}
+
+fn relay_context() -> TaskContext {
+    TaskContext {
+        scheduled_at: Utc::now(),
+        previous: Utc::now(),
+    }
+}
+
+#[tokio::test]
+async fn test_memo_changes_are_recorded_in_the_outbox() {
+    events::enable();
+    let db = setup_test_db().await;
+    let service = MemoService::new(db.clone().into());
+
+    let memo = service
+        .create_memo(
+            MemoFixture::new("Outbox recorded")
+                .description("Not for the broker")
+                .build(),
+        )
+        .await
+        .unwrap();
+    service
+        .patch_memo(
+            memo.id,
+            MemoFixture::new("Outbox renamed").patch(Some(memo.version)),
+        )
+        .await
+        .unwrap();
+    // A rejected change must not leave an event behind.
+    assert!(
+        service
+            .patch_memo(memo.id, MemoFixture::new("Stale").patch(Some(memo.version)))
+            .await
+            .is_err()
+    );
+    service
+        .delete_memo(memo.id, Duration::seconds(30))
+        .await
+        .unwrap();
+
+    let recorded = events_for(&db, memo.id).await;
+    let types: Vec<_> = recorded.iter().map(|e| e.event_type.as_str()).collect();
+    assert_eq!(types, ["memo.created", "memo.updated", "memo.deleted"]);
+
+    let created = &recorded[0].payload;
+    assert_eq!(created["id"], recorded[0].id.to_string());
+    assert_eq!(created["schema_version"], events::SCHEMA_VERSION);
+    assert_eq!(created["aggregate_id"], memo.id.to_string());
+    assert_eq!(created["data"]["title"], "Outbox recorded");
+    assert!(created["data"].get("description").is_none());
+    assert_eq!(recorded[1].payload["data"]["title"], "Outbox renamed");
+
+    cleanup(&db, memo.id).await;
+}
+
+#[tokio::test]
+async fn test_relay_publishes_in_order_and_retries_failures() {
+    events::enable();
+    let db = setup_test_db().await;
+    let service = MemoService::new(db.clone().into());
+
+    let memo = service
+        .create_memo(
+            MemoFixture::new("Outbox relay")
+                .description("Not for the broker")
+                .build(),
+        )
+        .await
+        .unwrap();
+    service
+        .patch_memo(memo.id, MemoFixture::new("Outbox relay again").patch(None))
+        .await
+        .unwrap();
+    let recorded = events_for(&db, memo.id).await;
+    let (created_id, updated_id) = (recorded[0].id, recorded[1].id);
+
+    let publisher = Arc::new(RecordingPublisher::default());
+    *publisher.fail_once.lock().unwrap() = Some(updated_id);
+    let relay = OutboxRelay::new(
+        db.clone().into(),
+        publisher.clone(),
+        &EventsConfig {
+            broker: EventBroker::None,
+            broker_url: None,
+            topic: "memos.events".to_string(),
+            relay_batch_size: 1000,
+            relay_interval_ms: 1000,
+        },
+    );
+
+    assert!(relay.run(&relay_context()).await.is_err());
+    let after_failure = events_for(&db, memo.id).await;
+    assert!(after_failure[0].published_at.is_some());
+    assert!(after_failure[1].published_at.is_none());
+    assert_eq!(after_failure[1].attempts, 1);
+    assert_eq!(
+        after_failure[1].last_error.as_deref(),
+        Some("broker error: broker unavailable")
+    );
+
+    relay.run(&relay_context()).await.unwrap();
+    assert!(events_for(&db, memo.id).await[1].published_at.is_some());
+
+    let published = publisher.published.lock().unwrap().clone();
+    let position = |id| published.iter().position(|p| *p == id).unwrap();
+    assert!(position(created_id) < position(updated_id));
+
+    cleanup(&db, memo.id).await;
+}
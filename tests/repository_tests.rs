@@ -1,16 +1,38 @@
 mod common;
 
-use actix_web_template::repository::MemoRepository;
-use chrono::Utc;
-use common::{fixtures::create_test_memo_dto, setup_test_db};
+use actix_web_template::{
+    db::{SlowQueryLog, pending_migrations},
+    repository::{
+        MemoFilter, MemoRepository, NewNotification, NotificationRepository,
+        PushSubscriptionRepository, TotalCount, UndoTokenRepository, WebhookIntegrationRepository,
+    },
+    seed::{demo_memos, seed_demo_memos},
+    services::UnitOfWork,
+};
+use chrono::{Duration, Utc};
+use chrono_tz::Tz;
+use common::{
+    TestDb,
+    fixtures::{MemoFixture, cleanup_memos},
+    setup_test_db,
+};
 
 #[tokio::test]
 async fn test_repository_create() {
     let db = setup_test_db().await;
-    let dto = create_test_memo_dto("Repository Test", Some("Test desc"));
+    let dto = MemoFixture::new("Repository Test")
+        .description("Test desc")
+        .build();
 
-    let result =
-        MemoRepository::create(&db, dto.title.clone(), dto.description.clone(), dto.date_to).await;
+    let result = MemoRepository::create(
+        &db,
+        dto.title.clone(),
+        dto.description.clone(),
+        dto.date_to.unwrap().resolve(Tz::UTC).unwrap(),
+        Tz::UTC,
+        None,
+    )
+    .await;
 
     assert!(result.is_ok());
     let memo = result.unwrap();
@@ -24,12 +46,7 @@ async fn test_repository_create() {
 #[tokio::test]
 async fn test_repository_find_by_id() {
     let db = setup_test_db().await;
-    let dto = create_test_memo_dto("Find By ID Test", None);
-
-    let created =
-        MemoRepository::create(&db, dto.title.clone(), dto.description.clone(), dto.date_to)
-            .await
-            .unwrap();
+    let created = MemoFixture::new("Find By ID Test").persist(&db).await;
 
     let result = MemoRepository::find_by_id(&db, created.id).await;
     assert!(result.is_ok());
@@ -56,12 +73,10 @@ async fn test_repository_find_by_id_not_found() {
 #[tokio::test]
 async fn test_repository_update() {
     let db = setup_test_db().await;
-    let dto = create_test_memo_dto("Original", Some("Original desc"));
-
-    let created =
-        MemoRepository::create(&db, dto.title.clone(), dto.description.clone(), dto.date_to)
-            .await
-            .unwrap();
+    let created = MemoFixture::new("Original")
+        .description("Original desc")
+        .persist(&db)
+        .await;
 
     let result = MemoRepository::update(
         &db,
@@ -70,6 +85,8 @@ async fn test_repository_update() {
         Some("Updated desc".to_string()),
         Utc::now(),
         true,
+        Tz::UTC,
+        None,
     )
     .await;
 
@@ -87,8 +104,17 @@ async fn test_repository_update_not_found() {
     let db = setup_test_db().await;
     let fake_id = uuid::Uuid::new_v4();
 
-    let result =
-        MemoRepository::update(&db, fake_id, "Test".to_string(), None, Utc::now(), false).await;
+    let result = MemoRepository::update(
+        &db,
+        fake_id,
+        "Test".to_string(),
+        None,
+        Utc::now(),
+        false,
+        Tz::UTC,
+        None,
+    )
+    .await;
 
     assert!(result.is_err());
 }
@@ -96,12 +122,7 @@ async fn test_repository_update_not_found() {
 #[tokio::test]
 async fn test_repository_delete() {
     let db = setup_test_db().await;
-    let dto = create_test_memo_dto("To Delete", None);
-
-    let created =
-        MemoRepository::create(&db, dto.title.clone(), dto.description.clone(), dto.date_to)
-            .await
-            .unwrap();
+    let created = MemoFixture::new("To Delete").persist(&db).await;
 
     let result = MemoRepository::delete(&db, created.id).await;
     assert!(result.is_ok());
@@ -111,6 +132,45 @@ async fn test_repository_delete() {
     assert!(found.is_none());
 }
 
+#[tokio::test]
+async fn test_expired_undo_token_finalizes_soft_deleted_memos() {
+    let db = setup_test_db().await;
+    let memo = MemoFixture::new("Soft Deleted").persist(&db).await;
+    let other = MemoFixture::new("Left Alone").persist(&db).await;
+
+    let token = UndoTokenRepository::create(&db, Utc::now() - Duration::seconds(1))
+        .await
+        .unwrap();
+    let deleted = MemoRepository::soft_delete_many(&db, &[memo.id], token.token)
+        .await
+        .unwrap();
+    assert_eq!(deleted, vec![memo.id]);
+    assert!(
+        MemoRepository::find_by_id(&db, memo.id)
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        !UndoTokenRepository::redeem(&db, token.token, Utc::now())
+            .await
+            .unwrap()
+    );
+
+    UndoTokenRepository::delete_expired(&db, Utc::now(), 500)
+        .await
+        .unwrap();
+    assert!(!MemoRepository::delete(&db, memo.id).await.unwrap());
+    assert!(
+        MemoRepository::find_by_id(&db, other.id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    MemoRepository::delete(&db, other.id).await.ok();
+}
+
 #[tokio::test]
 async fn test_repository_delete_not_found() {
     let db = setup_test_db().await;
@@ -125,82 +185,56 @@ async fn test_repository_delete_not_found() {
 async fn test_repository_find_all_basic() {
     let db = setup_test_db().await;
 
-    let dto1 = create_test_memo_dto("Repo List 1", None);
-    let memo1 = MemoRepository::create(
-        &db,
-        dto1.title.clone(),
-        dto1.description.clone(),
-        dto1.date_to,
-    )
-    .await
-    .unwrap();
-
-    let dto2 = create_test_memo_dto("Repo List 2", None);
-    let memo2 = MemoRepository::create(
-        &db,
-        dto2.title.clone(),
-        dto2.description.clone(),
-        dto2.date_to,
-    )
-    .await
-    .unwrap();
+    let created = MemoFixture::new("Repo List").persist_many(&db, 2).await;
 
-    let result = MemoRepository::find_all(&db, 10, 0, None, "created_at", "desc").await;
+    let result =
+        MemoRepository::find_all(&db, 10, 0, &MemoFilter::default(), "created_at", "desc").await;
     assert!(result.is_ok());
 
     let (memos, total) = result.unwrap();
     assert!(total >= 2);
     assert!(!memos.is_empty());
 
-    MemoRepository::delete(&db, memo1.id).await.ok();
-    MemoRepository::delete(&db, memo2.id).await.ok();
+    cleanup_memos(&db, &created).await;
 }
 
 #[tokio::test]
 async fn test_repository_find_all_with_pagination() {
     let db = setup_test_db().await;
 
-    let mut ids = Vec::new();
-    for i in 0..5 {
-        let dto = create_test_memo_dto(&format!("Pagination {}", i), None);
-        let memo = MemoRepository::create(&db, dto.title, dto.description, dto.date_to)
-            .await
-            .unwrap();
-        ids.push(memo.id);
-    }
+    let created = MemoFixture::new("Pagination").persist_many(&db, 5).await;
 
-    let result = MemoRepository::find_all(&db, 2, 0, None, "created_at", "desc").await;
+    let result =
+        MemoRepository::find_all(&db, 2, 0, &MemoFilter::default(), "created_at", "desc").await;
     assert!(result.is_ok());
 
     let (memos, _) = result.unwrap();
     assert!(memos.len() <= 2);
 
-    for id in ids {
-        MemoRepository::delete(&db, id).await.ok();
-    }
+    cleanup_memos(&db, &created).await;
 }
 
 #[tokio::test]
 async fn test_repository_find_all_with_completed_filter() {
     let db = setup_test_db().await;
 
-    let dto = create_test_memo_dto("Completed Memo", None);
-    let created = MemoRepository::create(&db, dto.title, dto.description, dto.date_to)
-        .await
-        .unwrap();
+    let created = MemoFixture::new("Completed Memo")
+        .completed()
+        .persist(&db)
+        .await;
 
-    MemoRepository::update(
+    let result = MemoRepository::find_all(
         &db,
-        created.id,
-        created.title.clone(),
-        None,
-        created.date_to.with_timezone(&Utc),
-        true,
+        10,
+        0,
+        &MemoFilter {
+            completed: Some(true),
+            ..MemoFilter::default()
+        },
+        "created_at",
+        "desc",
     )
-    .await
-    .unwrap();
-
-    let result = MemoRepository::find_all(&db, 10, 0, Some(true), "created_at", "desc").await;
+    .await;
     assert!(result.is_ok());
 
     let (memos, total) = result.unwrap();
@@ -214,17 +248,11 @@ async fn test_repository_find_all_with_completed_filter() {
 async fn test_repository_find_all_sorting() {
     let db = setup_test_db().await;
 
-    let dto1 = create_test_memo_dto("AAA Sort Test", None);
-    let memo1 = MemoRepository::create(&db, dto1.title, dto1.description, dto1.date_to)
-        .await
-        .unwrap();
+    let memo1 = MemoFixture::new("AAA Sort Test").persist(&db).await;
+    let memo2 = MemoFixture::new("ZZZ Sort Test").persist(&db).await;
 
-    let dto2 = create_test_memo_dto("ZZZ Sort Test", None);
-    let memo2 = MemoRepository::create(&db, dto2.title, dto2.description, dto2.date_to)
-        .await
-        .unwrap();
-
-    let result_asc = MemoRepository::find_all(&db, 100, 0, None, "title", "asc").await;
+    let result_asc =
+        MemoRepository::find_all(&db, 100, 0, &MemoFilter::default(), "title", "asc").await;
     assert!(result_asc.is_ok());
 
     let (memos_asc, _) = result_asc.unwrap();
@@ -238,3 +266,728 @@ async fn test_repository_find_all_sorting() {
     MemoRepository::delete(&db, memo1.id).await.ok();
     MemoRepository::delete(&db, memo2.id).await.ok();
 }
+
+#[tokio::test]
+async fn test_repository_find_all_with_search() {
+    let db = setup_test_db().await;
+
+    let matching = MemoFixture::new("Quarterly REPORT draft 7f3a")
+        .persist(&db)
+        .await;
+    let by_description = MemoFixture::new("Unrelated title")
+        .description("Attach the quarterly report 7f3a")
+        .persist(&db)
+        .await;
+    let other = MemoFixture::new("Grocery list 7f3a").persist(&db).await;
+
+    let filter = MemoFilter {
+        search: Some("quarterly report".to_string()),
+        ..MemoFilter::default()
+    };
+    let (memos, _) = MemoRepository::find_all(&db, 100, 0, &filter, "created_at", "desc")
+        .await
+        .unwrap();
+
+    assert!(memos.iter().any(|m| m.id == matching.id));
+    assert!(memos.iter().any(|m| m.id == by_description.id));
+    assert!(!memos.iter().any(|m| m.id == other.id));
+
+    let wildcard = MemoFilter {
+        search: Some("%".to_string()),
+        ..MemoFilter::default()
+    };
+    let (memos, _) = MemoRepository::find_all(&db, 100, 0, &wildcard, "created_at", "desc")
+        .await
+        .unwrap();
+    assert!(!memos.iter().any(|m| m.id == other.id));
+
+    cleanup_memos(&db, &[matching, by_description, other]).await;
+}
+
+#[tokio::test]
+async fn test_repository_find_all_overdue_filter() {
+    let db = setup_test_db().await;
+
+    let overdue = MemoFixture::new("Overdue Memo")
+        .due_in_days(-2)
+        .persist(&db)
+        .await;
+    let upcoming = MemoFixture::new("Upcoming Memo")
+        .due_in_days(2)
+        .persist(&db)
+        .await;
+    let done_late = MemoFixture::new("Done Late Memo")
+        .due_in_days(-2)
+        .completed()
+        .persist(&db)
+        .await;
+
+    let filter = MemoFilter {
+        overdue: Some(true),
+        ..MemoFilter::default()
+    };
+    let (memos, _) = MemoRepository::find_all(&db, 100, 0, &filter, "date_to", "desc")
+        .await
+        .unwrap();
+
+    assert!(memos.iter().any(|m| m.id == overdue.id));
+    assert!(!memos.iter().any(|m| m.id == upcoming.id));
+    assert!(!memos.iter().any(|m| m.id == done_late.id));
+    assert!(memos.iter().all(|m| !m.completed));
+
+    cleanup_memos(&db, &[overdue, upcoming, done_late]).await;
+}
+
+#[tokio::test]
+async fn test_repository_reorder() {
+    let db = setup_test_db().await;
+
+    let created = MemoFixture::new("Reorder").persist_many(&db, 3).await;
+    let (a, b, c) = (created[0].id, created[1].id, created[2].id);
+    assert!(created[0].position < created[1].position);
+    assert!(created[1].position < created[2].position);
+
+    MemoRepository::reorder(&db, &[c, a, b]).await.unwrap();
+
+    let position = |id| {
+        let db = &db;
+        async move {
+            MemoRepository::find_by_id(db, id)
+                .await
+                .unwrap()
+                .unwrap()
+                .position
+        }
+    };
+    let (pa, pb, pc) = (position(a).await, position(b).await, position(c).await);
+    assert!(pc < pa && pa < pb);
+
+    let missing = MemoRepository::reorder(&db, &[a, uuid::Uuid::new_v4()]).await;
+    assert!(missing.is_err());
+
+    cleanup_memos(&db, &created).await;
+}
+
+#[tokio::test]
+async fn test_repository_move_to_front() {
+    let db = setup_test_db().await;
+
+    let created = MemoFixture::new("Move To Front").persist_many(&db, 2).await;
+    let last = &created[1];
+
+    let moved = MemoRepository::move_to(&db, last.id, 0).await.unwrap();
+    assert_eq!(moved.position, 0);
+
+    let first = MemoRepository::find_by_id(&db, created[0].id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(first.position > moved.position);
+
+    let missing = MemoRepository::move_to(&db, uuid::Uuid::new_v4(), 0).await;
+    assert!(missing.is_err());
+
+    cleanup_memos(&db, &created).await;
+}
+
+#[tokio::test]
+async fn test_unit_of_work_rolls_back_when_dropped() {
+    let db = setup_test_db().await;
+
+    let uow = UnitOfWork::begin(&db).await.unwrap();
+    let memo = MemoRepository::create(
+        uow.conn(),
+        "Uncommitted".to_string(),
+        None,
+        Utc::now(),
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+    assert!(
+        MemoRepository::find_by_id(uow.conn(), memo.id)
+            .await
+            .unwrap()
+            .is_some()
+    );
+    drop(uow);
+
+    let found = MemoRepository::find_by_id(&db, memo.id).await.unwrap();
+    assert!(found.is_none());
+}
+
+#[tokio::test]
+async fn test_unit_of_work_commit_persists_all_steps() {
+    let db = setup_test_db().await;
+
+    let uow = UnitOfWork::begin(&db).await.unwrap();
+    let memo = MemoRepository::create(
+        uow.conn(),
+        "Committed".to_string(),
+        None,
+        Utc::now(),
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+    MemoRepository::update(
+        uow.conn(),
+        memo.id,
+        "Committed and updated".to_string(),
+        None,
+        Utc::now(),
+        true,
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+    uow.commit().await.unwrap();
+
+    let found = MemoRepository::find_by_id(&db, memo.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.title, "Committed and updated");
+    assert!(found.completed);
+
+    MemoRepository::delete(&db, memo.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_seed_demo_memos_is_idempotent() {
+    let db = setup_test_db().await;
+    let seeded = demo_memos(3, Utc::now());
+    for memo in &seeded {
+        MemoRepository::delete(&db, memo.id).await.ok();
+    }
+
+    let first = seed_demo_memos(&db, 3).await.unwrap();
+    assert_eq!(first.created, 3);
+    assert_eq!(first.skipped, 0);
+
+    let second = seed_demo_memos(&db, 3).await.unwrap();
+    assert_eq!(second.created, 0);
+    assert_eq!(second.skipped, 3);
+
+    let found = MemoRepository::find_by_id(&db, seeded[0].id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.title, seeded[0].title);
+
+    for memo in &seeded {
+        MemoRepository::delete(&db, memo.id).await.ok();
+    }
+}
+
+#[tokio::test]
+async fn test_push_subscription_upsert_replaces_keys() {
+    let db = setup_test_db().await;
+    let endpoint = format!("https://push.example.com/send/{}", uuid::Uuid::new_v4());
+
+    PushSubscriptionRepository::upsert(&db, endpoint.clone(), "old".into(), "old".into())
+        .await
+        .unwrap();
+    PushSubscriptionRepository::upsert(&db, endpoint.clone(), "new".into(), "new".into())
+        .await
+        .unwrap();
+
+    let matching: Vec<_> = PushSubscriptionRepository::find_all(&db)
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|subscription| subscription.endpoint == endpoint)
+        .collect();
+    assert_eq!(matching.len(), 1);
+    assert_eq!(matching[0].p256dh, "new");
+
+    assert!(
+        PushSubscriptionRepository::delete_by_endpoint(&db, &endpoint)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !PushSubscriptionRepository::delete_by_endpoint(&db, &endpoint)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn test_repository_stats_counts_new_memos() {
+    let db = setup_test_db().await;
+    let since = Utc::now() - chrono::Duration::days(1);
+    let before = MemoRepository::stats(&db, since).await.unwrap();
+
+    let overdue = MemoRepository::create(
+        &db,
+        "Stats overdue".to_string(),
+        None,
+        Utc::now() - chrono::Duration::days(2),
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+    let done = MemoRepository::create(
+        &db,
+        "Stats done".to_string(),
+        None,
+        Utc::now() + chrono::Duration::days(2),
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+    let done = MemoRepository::update(
+        &db,
+        done.id,
+        done.title.clone(),
+        None,
+        done.date_to.with_timezone(&Utc),
+        true,
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let after = MemoRepository::stats(&db, since).await.unwrap();
+    cleanup_memos(&db, &[overdue, done]).await;
+
+    // Other tests run concurrently, so only lower bounds are reliable.
+    assert!(after.total >= before.total + 2);
+    assert!(after.completed > 0);
+    assert!(after.overdue > 0);
+    let created_today = after
+        .created_per_day
+        .iter()
+        .find(|(day, _)| *day == Utc::now().date_naive())
+        .map(|(_, count)| *count)
+        .unwrap();
+    assert!(created_today >= 2);
+}
+
+#[tokio::test]
+async fn test_pending_migrations_lists_unapplied_versions() {
+    use sea_orm::{ConnectionTrait, DatabaseBackend, Statement, TransactionTrait};
+
+    let db = setup_test_db().await;
+    assert!(pending_migrations(&db).await.unwrap().is_empty());
+
+    // Forget the newest migration inside a transaction that is rolled back.
+    let txn = db.begin().await.unwrap();
+    let latest = txn
+        .query_one(Statement::from_string(
+            DatabaseBackend::Postgres,
+            "DELETE FROM seaql_migrations WHERE version = \
+             (SELECT max(version) FROM seaql_migrations) RETURNING version",
+        ))
+        .await
+        .unwrap()
+        .unwrap()
+        .try_get::<String>("", "version")
+        .unwrap();
+
+    assert_eq!(pending_migrations(&txn).await.unwrap(), vec![latest]);
+    txn.rollback().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_find_page_count_modes() {
+    let db = setup_test_db().await;
+    let marker = format!("page-count-{}", uuid::Uuid::new_v4());
+    let mut created = Vec::new();
+    for i in 0..3 {
+        let memo = MemoRepository::create(
+            &db,
+            format!("{} {}", marker, i),
+            None,
+            Utc::now(),
+            Tz::UTC,
+            None,
+        )
+        .await
+        .unwrap();
+        created.push(memo);
+    }
+    let filter = MemoFilter {
+        search: Some(marker),
+        ..MemoFilter::default()
+    };
+
+    let exact = MemoRepository::find_page(&db, 2, 0, &filter, "title", "asc", TotalCount::Exact)
+        .await
+        .unwrap();
+    assert_eq!(exact.memos.len(), 2);
+    assert_eq!(exact.total, Some(3));
+    assert!(exact.has_next && !exact.estimated);
+
+    let skipped = MemoRepository::find_page(&db, 2, 2, &filter, "title", "asc", TotalCount::Skip)
+        .await
+        .unwrap();
+    assert_eq!(skipped.memos.len(), 1);
+    assert_eq!(skipped.total, None);
+    assert!(!skipped.has_next);
+
+    // The last page is short, so even the estimated mode knows the exact total.
+    let estimated =
+        MemoRepository::find_page(&db, 2, 2, &filter, "title", "asc", TotalCount::Estimated)
+            .await
+            .unwrap();
+    assert_eq!(estimated.total, Some(3));
+    assert!(!estimated.estimated);
+
+    let first =
+        MemoRepository::find_page(&db, 2, 0, &filter, "title", "asc", TotalCount::Estimated)
+            .await
+            .unwrap();
+    assert!(first.total.unwrap() >= 3);
+
+    cleanup_memos(&db, &created).await;
+}
+
+/// Plan for the default listing query, with sequential scans disabled so the
+/// result does not depend on how many rows the test database happens to hold.
+///
+/// Runs on a schema of its own holding a fixed, analyzed set of memos, a
+/// tenth of them still inside their undo window, so the planner sees the same
+/// statistics on every run.
+async fn listing_plan(filter: &MemoFilter) -> String {
+    use sea_orm::{
+        ConnectionTrait, DatabaseBackend, QuerySelect, QueryTrait, Statement, TransactionTrait,
+    };
+
+    let test_db = TestDb::new().await;
+    test_db
+        .db
+        .execute_unprepared(
+            "INSERT INTO memos (title, date_to, completed, pinned, created_at) \
+             SELECT 'Listed ' || n, now() + n * interval '1 hour', n % 3 = 0, n % 50 = 0, \
+                    now() - n * interval '1 minute' \
+             FROM generate_series(1, 2000) AS n; \
+             INSERT INTO undo_tokens (token, expires_at) \
+             VALUES ('00000000-0000-0000-0000-000000000001', now() + interval '1 hour'); \
+             UPDATE memos SET undo_token = '00000000-0000-0000-0000-000000000001' \
+             WHERE title LIKE '%0'; \
+             ANALYZE memos",
+        )
+        .await
+        .unwrap();
+    let txn = test_db.db.begin().await.unwrap();
+    txn.execute_unprepared("SET LOCAL enable_seqscan = off")
+        .await
+        .unwrap();
+
+    let query = MemoRepository::listing_query(filter, "created_at", "desc")
+        .limit(20)
+        .build(DatabaseBackend::Postgres);
+    let rows = txn
+        .query_all(Statement {
+            sql: format!("EXPLAIN {}", query.sql),
+            ..query
+        })
+        .await
+        .unwrap();
+    txn.rollback().await.unwrap();
+
+    rows.iter()
+        .map(|row| row.try_get::<String>("", "QUERY PLAN").unwrap())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Whether the plan reads one of the listing indexes in order, i.e. without a
+/// separate sort step.
+fn uses_listing_index(plan: &str) -> bool {
+    [
+        "idx_memos_archived_pinned_created_at",
+        "idx_memos_archived_completed_pinned_created_at",
+    ]
+    .iter()
+    .any(|index| plan.contains(&format!("Index Scan using {}", index)))
+        && !plan.contains("Sort")
+}
+
+#[tokio::test]
+async fn test_listing_queries_use_composite_indexes() {
+    let plan = listing_plan(&MemoFilter::default()).await;
+    assert!(uses_listing_index(&plan), "{}", plan);
+
+    // With few rows the planner may pick either index here; both avoid a sort.
+    let plan = listing_plan(&MemoFilter {
+        completed: Some(false),
+        ..MemoFilter::default()
+    })
+    .await;
+    assert!(uses_listing_index(&plan), "{}", plan);
+}
+
+#[tokio::test]
+async fn test_purge_batches_respect_limit_and_action() {
+    use chrono::TimeZone;
+    use sea_orm::{ConnectionTrait, TransactionTrait};
+
+    let db = setup_test_db().await;
+    let txn = db.begin().await.unwrap();
+    let marker = format!("purge-{}", uuid::Uuid::new_v4());
+    let mut ids = Vec::new();
+    for i in 0..4 {
+        let memo = MemoRepository::create(
+            &txn,
+            format!("{} {}", marker, i),
+            None,
+            Utc::now(),
+            Tz::UTC,
+            None,
+        )
+        .await
+        .unwrap();
+        ids.push(memo.id);
+    }
+    // Three old completed memos, one of them archived; the fourth stays open.
+    txn.execute_unprepared(&format!(
+        "UPDATE memos SET completed = true, updated_at = '1990-01-01T00:00:00Z' \
+         WHERE id IN ('{}', '{}', '{}')",
+        ids[0], ids[1], ids[2]
+    ))
+    .await
+    .unwrap();
+    MemoRepository::set_archived(&txn, ids[2], true)
+        .await
+        .unwrap();
+    txn.execute_unprepared(&format!(
+        "UPDATE memos SET updated_at = '1990-01-01T00:00:00Z' WHERE id = '{}'",
+        ids[2]
+    ))
+    .await
+    .unwrap();
+
+    let cutoff = Utc.with_ymd_and_hms(1995, 1, 1, 0, 0, 0).unwrap();
+    assert_eq!(
+        MemoRepository::count_purgeable(&txn, cutoff, false)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        MemoRepository::count_purgeable(&txn, cutoff, true)
+            .await
+            .unwrap(),
+        3
+    );
+
+    for expected in [1, 1, 0] {
+        assert_eq!(
+            MemoRepository::archive_purgeable_batch(&txn, cutoff, 1)
+                .await
+                .unwrap(),
+            expected
+        );
+    }
+    let archived = MemoRepository::find_by_id(&txn, ids[0])
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(archived.archived);
+    assert_eq!(archived.version, 2);
+
+    for expected in [2, 1, 0] {
+        assert_eq!(
+            MemoRepository::delete_purgeable_batch(&txn, cutoff, 2)
+                .await
+                .unwrap(),
+            expected
+        );
+    }
+    assert_eq!(
+        MemoRepository::existing_ids(&txn, &ids).await.unwrap(),
+        vec![ids[3]]
+    );
+
+    txn.rollback().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_notifications_dedupe_and_mark_read() {
+    use sea_orm::TransactionTrait;
+
+    let db = setup_test_db().await;
+    let txn = db.begin().await.unwrap();
+    let memo = MemoRepository::create(
+        &txn,
+        "Inbox memo".to_string(),
+        None,
+        Utc::now(),
+        Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+    let unread_before = NotificationRepository::count_unread(&txn).await.unwrap();
+
+    let notification = NewNotification {
+        kind: "memo_due_soon",
+        title: "Inbox memo is due soon".to_string(),
+        body: None,
+        memo_id: Some(memo.id),
+        dedupe_key: Some(format!("memo_due_soon:{}", memo.id)),
+    };
+    assert!(
+        NotificationRepository::create_if_absent(&txn, notification.clone())
+            .await
+            .unwrap()
+    );
+    assert!(
+        !NotificationRepository::create_if_absent(&txn, notification)
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        NotificationRepository::count_unread(&txn).await.unwrap(),
+        unread_before + 1
+    );
+
+    let (unread, _) = NotificationRepository::find_page(&txn, true, 100, 0)
+        .await
+        .unwrap();
+    let stored = unread
+        .into_iter()
+        .find(|n| n.memo_id == Some(memo.id))
+        .expect("notification is listed as unread");
+
+    let read = NotificationRepository::mark_read(&txn, stored.id)
+        .await
+        .unwrap()
+        .unwrap();
+    let read_at = read.read_at.expect("read_at is set");
+    let again = NotificationRepository::mark_read(&txn, stored.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(again.read_at, Some(read_at));
+    assert_eq!(
+        NotificationRepository::count_unread(&txn).await.unwrap(),
+        unread_before
+    );
+    assert!(
+        NotificationRepository::mark_read(&txn, uuid::Uuid::new_v4())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    txn.rollback().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_webhook_integrations_filter_by_event() {
+    use sea_orm::TransactionTrait;
+
+    let db = setup_test_db().await;
+    let txn = db.begin().await.unwrap();
+
+    let created = WebhookIntegrationRepository::create(
+        &txn,
+        "slack",
+        "https://hooks.slack.com/services/T000/B000/created".to_string(),
+        vec!["memo_created".to_string()],
+    )
+    .await
+    .unwrap();
+    let both = WebhookIntegrationRepository::create(
+        &txn,
+        "discord",
+        "https://discord.com/api/webhooks/1/both".to_string(),
+        vec!["memo_created".to_string(), "memo_overdue".to_string()],
+    )
+    .await
+    .unwrap();
+
+    let ids = |integrations: Vec<actix_web_template::entities::webhook_integrations::Model>| {
+        integrations
+            .into_iter()
+            .map(|integration| integration.id)
+            .filter(|id| *id == created.id || *id == both.id)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        ids(
+            WebhookIntegrationRepository::find_for_event(&txn, "memo_created")
+                .await
+                .unwrap()
+        ),
+        vec![created.id, both.id]
+    );
+    let overdue = WebhookIntegrationRepository::find_for_event(&txn, "memo_overdue")
+        .await
+        .unwrap();
+    let found = overdue
+        .iter()
+        .find(|integration| integration.id == both.id)
+        .unwrap();
+    assert_eq!(found.url, "https://discord.com/api/webhooks/1/both");
+    assert!(
+        overdue
+            .iter()
+            .all(|integration| integration.id != created.id)
+    );
+
+    assert!(
+        WebhookIntegrationRepository::delete(&txn, both.id)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !WebhookIntegrationRepository::delete(&txn, both.id)
+            .await
+            .unwrap()
+    );
+
+    txn.rollback().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_slow_queries_are_counted_by_name() {
+    use sea_orm::{ConnectionTrait, DbBackend, Statement};
+    use tracing::Instrument;
+
+    let registry = prometheus::Registry::new();
+    let log = SlowQueryLog::new(std::time::Duration::from_millis(5))
+        .with_metrics(&registry)
+        .unwrap();
+    let mut db = setup_test_db().await;
+    log.install(&mut db);
+    let sleep = || {
+        Statement::from_sql_and_values(DbBackend::Postgres, "SELECT pg_sleep($1)", [0.02f64.into()])
+    };
+
+    db.execute(sleep()).await.unwrap();
+    // Named after the current span when there is one
+    let _subscriber = tracing::subscriber::set_default(tracing_subscriber::registry());
+    db.execute(sleep())
+        .instrument(tracing::info_span!("sleep_probe"))
+        .await
+        .unwrap();
+
+    let counted = |query: &str| {
+        registry
+            .gather()
+            .iter()
+            .find(|family| family.name() == "db_slow_queries_total")
+            .and_then(|family| {
+                family.get_metric().iter().find(|metric| {
+                    metric
+                        .get_label()
+                        .iter()
+                        .any(|label| label.name() == "query" && label.value() == query)
+                })
+            })
+            .map(|metric| metric.get_counter().get_value())
+    };
+    assert_eq!(counted("SELECT"), Some(1.0));
+    assert_eq!(counted("repository_tests::sleep_probe"), Some(1.0));
+}
@@ -2,14 +2,25 @@ mod common;
 
 use actix_web::{App, test, web};
 use actix_web_template::{
+    assets::asset_url,
+    config::settings::DocsUi,
+    dto::SaveMemoTemplateDto,
+    flash::{FLASH_COOKIE, FlashLevel, FlashMessage},
+    handlers::static_asset,
     handlers::web::{
-        create_memo_web, delete_memo_web, get_edit_memo_form, get_memos_list, get_new_memo_form,
-        index, toggle_memo_complete_web, update_memo_web,
+        archive_memo_web, create_memo_web, delete_memo_web, get_board, get_calendar,
+        get_calendar_grid, get_edit_memo_form, get_edit_title_form, get_flash, get_memos_list,
+        get_new_memo_form, get_notifications, index, move_memo_on_board_web, reorder_memos_web,
+        toggle_memo_complete_web, toggle_memo_pinned_web, toggle_memo_starred_web, undo_delete_web,
+        update_memo_title_web, update_memo_web,
     },
-    services::MemoService,
+    handlers::{api_docs, get_attachment_thumbnail, openapi_json},
+    middleware::SecurityHeaders,
+    repository::{MemoRepository, NewNotification, NotificationRepository},
+    services::{MemoService, MemoTemplateService},
 };
-use chrono::Utc;
-use common::{fixtures::create_test_memo_dto, setup_test_state};
+use chrono::{TimeZone, Utc};
+use common::{fixtures::MemoFixture, setup_test_state};
 
 #[tokio::test]
 async fn test_index_page() {
@@ -25,6 +36,62 @@ async fn test_index_page() {
     let body = test::read_body(resp).await;
     let html = String::from_utf8(body.to_vec()).unwrap();
     assert!(html.contains("<!DOCTYPE html") || html.contains("<html"));
+    assert!(html.contains("class=\"stats-card\""));
+    assert_eq!(html.matches("class=\"stats-bar\"").count(), 30);
+}
+
+#[tokio::test]
+async fn test_index_page_inline_script_carries_csp_nonce() {
+    let state = setup_test_state().await;
+    let security_headers = SecurityHeaders::from_config(&state.config.security).unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .wrap(security_headers)
+            .service(index),
+    )
+    .await;
+
+    let resp = test::call_service(&app, test::TestRequest::get().uri("/").to_request()).await;
+    let csp = resp
+        .headers()
+        .get("content-security-policy")
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+
+    let nonce = html
+        .split("<script nonce=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("inline script without nonce");
+    assert!(csp.contains(&format!("'nonce-{nonce}'")));
+    assert!(!csp.contains("script-src 'self' 'unsafe-inline'"));
+    assert!(!html.contains("onclick=") && !html.contains("onsubmit="));
+}
+
+#[tokio::test]
+async fn test_index_page_localized() {
+    let state = setup_test_state().await;
+
+    let app = test::init_service(App::new().app_data(web::Data::new(state)).service(index)).await;
+
+    let req = test::TestRequest::get()
+        .uri("/")
+        .insert_header(("Accept-Language", "es"))
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("<html lang=\"es\">"));
+    assert!(html.contains("Mis memos"));
+    assert!(html.contains("Vencidos"));
 }
 
 #[tokio::test]
@@ -32,7 +99,7 @@ async fn test_get_memos_list() {
     let state = setup_test_state().await;
 
     let service = MemoService::new(state.db.clone());
-    let dto = create_test_memo_dto("Web List Test", None);
+    let dto = MemoFixture::new("Web List Test").build();
     let created = service.create_memo(dto).await.unwrap();
 
     let app = test::init_service(
@@ -53,12 +120,210 @@ async fn test_get_memos_list() {
     let html = String::from_utf8(body.to_vec()).unwrap();
     assert!(html.contains("Web List Test"));
 
-    service.delete_memo(created.id).await.ok();
+    service.delete_memo_permanently(created.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_stored_markup_cannot_reach_pages() {
+    let state = setup_test_state().await;
+    let service = MemoService::new(state.db.clone());
+
+    // Titles are sanitized before they are stored; descriptions are kept as
+    // Markdown source and sanitized when rendered.
+    let sanitized = service
+        .create_memo(
+            MemoFixture::new("XssGuard <script>alert('title')</script>sanitized")
+                .description("**Note** <img src=x onerror=alert('description')>")
+                .build(),
+        )
+        .await
+        .unwrap();
+    assert!(!sanitized.title.contains("<script"));
+    assert_eq!(
+        sanitized.description.as_deref(),
+        Some("**Note** <img src=x onerror=alert('description')>")
+    );
+
+    // Rows written around the service (older data, manual fixes) are still
+    // escaped by the templates.
+    let raw = MemoRepository::create(
+        &state.db,
+        "XssGuard <script>alert('raw')</script>".to_string(),
+        Some("<img src=x onerror=alert('raw')>".to_string()),
+        Utc::now(),
+        chrono_tz::Tz::UTC,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_memos_list)
+            .service(get_edit_memo_form),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/web/memos?q=XssGuard")
+            .to_request(),
+    )
+    .await;
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(html.contains("<strong>Note</strong>"));
+    assert!(!html.contains("<img"));
+
+    for uri in [
+        "/web/memos?q=XssGuard".to_string(),
+        format!("/web/memos/{}/edit", raw.id),
+    ] {
+        let resp = test::call_service(&app, test::TestRequest::get().uri(&uri).to_request()).await;
+        assert_eq!(resp.status(), 200);
+        let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+        assert!(html.contains("XssGuard"), "{uri}");
+        assert!(!html.contains("<script>alert"), "{uri}");
+        assert!(!html.contains("<img src=x"), "{uri}");
+        assert!(html.contains("&#60;script&#62;"), "{uri}");
+    }
+
+    service.delete_memo_permanently(sanitized.id).await.ok();
+    service.delete_memo_permanently(raw.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_color_labels_render_as_accents() {
+    let state = setup_test_state().await;
+    let service = MemoService::new(state.db.clone());
+
+    let mut dto = MemoFixture::new("ColorAccent green").build();
+    dto.color = Some("green".to_string());
+    let green = service.create_memo(dto).await.unwrap();
+    let plain = service
+        .create_memo(MemoFixture::new("ColorAccent plain").build())
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_memos_list)
+            .service(get_edit_memo_form),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri("/web/memos?q=ColorAccent&color=green")
+            .to_request(),
+    )
+    .await;
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(html.contains(&format!("memo-{}", green.id)));
+    assert!(!html.contains(&format!("memo-{}", plain.id)));
+    assert!(html.contains("color-green"));
+    assert!(html.contains("<span class=\"memo-color color-green\">Green</span>"));
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get()
+            .uri(&format!("/web/memos/{}/edit", green.id))
+            .to_request(),
+    )
+    .await;
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(html.contains("<option value=\"green\" selected>"));
+
+    service.delete_memo_permanently(green.id).await.ok();
+    service.delete_memo_permanently(plain.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_get_memos_list_with_search() {
+    let state = setup_test_state().await;
+
+    let service = MemoService::new(state.db.clone());
+    let wanted = service
+        .create_memo(MemoFixture::new("Searchable Web Memo b21c").build())
+        .await
+        .unwrap();
+    let unwanted = service
+        .create_memo(MemoFixture::new("Hidden Web Memo").build())
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_memos_list),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/web/memos?q=searchable%20web%20memo%20b21c")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Searchable Web Memo b21c"));
+    assert!(!html.contains("Hidden Web Memo"));
+
+    service.delete_memo_permanently(wanted.id).await.ok();
+    service.delete_memo_permanently(unwanted.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_get_memos_list_overdue_tab() {
+    let state = setup_test_state().await;
+
+    let service = MemoService::new(state.db.clone());
+    let overdue = service
+        .create_memo(MemoFixture::new("Overdue Tab Memo").due_in_days(-1).build())
+        .await
+        .unwrap();
+    let upcoming = service
+        .create_memo(MemoFixture::new("Upcoming Tab Memo").due_in_days(1).build())
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_memos_list),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/web/memos?overdue=true&limit=100")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Overdue Tab Memo"));
+    assert!(!html.contains("Upcoming Tab Memo"));
+
+    service.delete_memo_permanently(overdue.id).await.ok();
+    service.delete_memo_permanently(upcoming.id).await.ok();
 }
 
 #[tokio::test]
 async fn test_get_new_memo_form() {
-    let app = test::init_service(App::new().service(get_new_memo_form)).await;
+    let state = setup_test_state().await;
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_new_memo_form),
+    )
+    .await;
 
     let req = test::TestRequest::get().uri("/web/memos/new").to_request();
 
@@ -70,6 +335,44 @@ async fn test_get_new_memo_form() {
     assert!(html.contains("<form") || html.contains("form"));
 }
 
+#[tokio::test]
+async fn test_get_new_memo_form_prefills_from_template() {
+    let state = setup_test_state().await;
+    let service = MemoTemplateService::new(state.db.clone());
+    let template = service
+        .create_template(SaveMemoTemplateDto {
+            name: format!("Web template {}", uuid::Uuid::new_v4()),
+            title: "Report for {{year}}".to_string(),
+            description: Some("Ask {{client}}".to_string()),
+            due_text: Some("next friday".to_string()),
+        })
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_new_memo_form),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/web/memos/new?template_id={}", template.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(html.contains("id=\"template-select\""));
+    assert!(html.contains(&format!("value=\"{}\" selected", template.id)));
+    // Placeholders without a value are left for the user to fill in
+    assert!(html.contains("value=\"Report for {{year}}\""));
+    assert!(html.contains("Ask {{client}}"));
+    assert!(html.contains("value=\"next friday\""));
+
+    service.delete_template(template.id).await.unwrap();
+}
+
 #[tokio::test]
 async fn test_create_memo_web() {
     let state = setup_test_state().await;
@@ -104,7 +407,135 @@ async fn test_create_memo_web() {
         .find(|m| m.title == "Web Created Memo")
         .expect("Memo should exist");
 
-    service.delete_memo(created.id).await.ok();
+    service.delete_memo_permanently(created.id).await.ok();
+}
+
+/// A multipart body with the memo fields and one file per image.
+fn multipart_memo(boundary: &str, fields: &[(&str, &str)], images: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            )
+            .as_bytes(),
+        );
+    }
+    for (file_name, data) in images {
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"images\"; filename=\"{file_name}\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+    body
+}
+
+#[tokio::test]
+async fn test_create_memo_web_with_images() {
+    let state = setup_test_state().await;
+    let service = MemoService::new(state.db.clone());
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(create_memo_web)
+            .service(get_memos_list)
+            .service(get_attachment_thumbnail),
+    )
+    .await;
+
+    let mut png = Vec::new();
+    image::RgbImage::from_pixel(640, 480, image::Rgb([200, 40, 40]))
+        .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+        .unwrap();
+
+    let boundary = "memo-upload-7f3a";
+    let date_str = Utc::now().format("%Y-%m-%dT%H:%M").to_string();
+    let fields = [
+        ("title", "Memo With Photo 5e1d"),
+        ("date_to", date_str.as_str()),
+    ];
+    let content_type = format!("multipart/form-data; boundary={boundary}");
+
+    let req = test::TestRequest::post()
+        .uri("/web/memos")
+        .insert_header(("Content-Type", content_type.clone()))
+        .set_payload(multipart_memo(boundary, &fields, &[("photo.png", &png)]))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    // Files the upload scanners object to are turned away before anything is stored
+    let rejected: [(&str, &[u8]); 3] = [
+        ("notes.png", b"not an image"),
+        ("setup.png", b"MZ\x90\0\x03\0\0\0"),
+        ("photo.exe", &png),
+    ];
+    for (file_name, data) in rejected {
+        let req = test::TestRequest::post()
+            .uri("/web/memos")
+            .insert_header(("Content-Type", content_type.clone()))
+            .set_payload(multipart_memo(
+                boundary,
+                &[
+                    ("title", "Memo With Rejected File 5e1d"),
+                    ("date_to", date_str.as_str()),
+                ],
+                &[(file_name, data)],
+            ))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), 422, "{} should be rejected", file_name);
+        let body: serde_json::Value = test::read_body_json(resp).await;
+        assert_eq!(body["code"], "UPLOAD_REJECTED");
+    }
+
+    let req = test::TestRequest::get()
+        .uri("/web/memos?q=5e1d")
+        .to_request();
+    let html = String::from_utf8(test::call_and_read_body(&app, req).await.to_vec()).unwrap();
+    assert!(html.contains("Memo With Photo 5e1d"));
+    assert!(!html.contains("Memo With Rejected File 5e1d"));
+
+    let params = actix_web_template::dto::PaginationParams {
+        q: Some("Memo With Photo 5e1d".to_string()),
+        ..Default::default()
+    };
+    let created = service.get_all_memos(params).await.unwrap().data.remove(0);
+    assert_eq!(created.attachments.len(), 1);
+    let attachment = &created.attachments[0];
+    assert_eq!(attachment.file_name, "photo.png");
+    assert_eq!(attachment.content_type, "image/png");
+    assert_eq!((attachment.width, attachment.height), (640, 480));
+
+    // The page links to a signed URL; the bare path is refused
+    let req = test::TestRequest::get()
+        .uri(&attachment.thumbnail_url)
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 401);
+    let start = html
+        .find(&format!("{}?expires=", attachment.thumbnail_url))
+        .expect("signed thumbnail link");
+    let signed = html[start..]
+        .split('"')
+        .next()
+        .unwrap()
+        .replace("&#38;", "&");
+
+    let req = test::TestRequest::get().uri(&signed).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("Content-Type").unwrap(), "image/jpeg");
+    let thumbnail = test::read_body(resp).await;
+    let thumbnail = image::load_from_memory(&thumbnail).unwrap();
+    assert!(thumbnail.width() <= 320 && thumbnail.height() <= 320);
+
+    service.delete_memo_permanently(created.id).await.ok();
 }
 
 #[tokio::test]
@@ -112,7 +543,7 @@ async fn test_get_edit_memo_form() {
     let state = setup_test_state().await;
     let service = MemoService::new(state.db.clone());
 
-    let dto = create_test_memo_dto("Edit Form Test", None);
+    let dto = MemoFixture::new("Edit Form Test").build();
     let created = service.create_memo(dto).await.unwrap();
 
     let app = test::init_service(
@@ -133,7 +564,7 @@ async fn test_get_edit_memo_form() {
     let html = String::from_utf8(body.to_vec()).unwrap();
     assert!(html.contains("Edit Form Test"));
 
-    service.delete_memo(created.id).await.ok();
+    service.delete_memo_permanently(created.id).await.ok();
 }
 
 #[tokio::test]
@@ -141,7 +572,7 @@ async fn test_update_memo_web() {
     let state = setup_test_state().await;
     let service = MemoService::new(state.db.clone());
 
-    let dto = create_test_memo_dto("Original Web Title", None);
+    let dto = MemoFixture::new("Original Web Title").build();
     let created = service.create_memo(dto).await.unwrap();
 
     let app = test::init_service(
@@ -170,7 +601,7 @@ async fn test_update_memo_web() {
     assert_eq!(updated.title, "Updated Web Title");
     assert!(updated.completed);
 
-    service.delete_memo(created.id).await.ok();
+    service.delete_memo_permanently(created.id).await.ok();
 }
 
 #[tokio::test]
@@ -178,7 +609,7 @@ async fn test_delete_memo_web() {
     let state = setup_test_state().await;
     let service = MemoService::new(state.db.clone());
 
-    let dto = create_test_memo_dto("To Delete Web", None);
+    let dto = MemoFixture::new("To Delete Web").build();
     let created = service.create_memo(dto).await.unwrap();
 
     let app = test::init_service(
@@ -197,6 +628,8 @@ async fn test_delete_memo_web() {
 
     let result = service.get_memo_by_id(created.id).await;
     assert!(result.is_err());
+
+    service.delete_memo_permanently(created.id).await.ok();
 }
 
 #[tokio::test]
@@ -204,7 +637,7 @@ async fn test_toggle_memo_complete_web() {
     let state = setup_test_state().await;
     let service = MemoService::new(state.db.clone());
 
-    let dto = create_test_memo_dto("Toggle Web Test", None);
+    let dto = MemoFixture::new("Toggle Web Test").build();
     let created = service.create_memo(dto).await.unwrap();
     assert!(!created.completed);
 
@@ -225,7 +658,83 @@ async fn test_toggle_memo_complete_web() {
     let toggled = service.get_memo_by_id(created.id).await.unwrap();
     assert!(toggled.completed);
 
-    service.delete_memo(created.id).await.ok();
+    service.delete_memo_permanently(created.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_pin_and_star_memo_web() {
+    let state = setup_test_state().await;
+    let service = MemoService::new(state.db.clone());
+
+    let created = service
+        .create_memo(MemoFixture::new("Pin Web Test").build())
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(toggle_memo_pinned_web)
+            .service(toggle_memo_starred_web),
+    )
+    .await;
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/web/memos/{}/pin", created.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(html.contains("pinned"));
+    assert!(html.contains("btn-pin active"));
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/web/memos/{}/star", created.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(html.contains("btn-star active"));
+
+    let memo = service.get_memo_by_id(created.id).await.unwrap();
+    assert!(memo.pinned && memo.starred);
+
+    service.delete_memo_permanently(created.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_archive_memo_web() {
+    let state = setup_test_state().await;
+    let service = MemoService::new(state.db.clone());
+
+    let created = service
+        .create_memo(MemoFixture::new("Archive Web Test").build())
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(archive_memo_web),
+    )
+    .await;
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/web/memos/{}/archive", created.id))
+        .set_form([("archived", "true")])
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(service.get_memo_by_id(created.id).await.unwrap().archived);
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/web/memos/{}/archive", created.id))
+        .set_form([("archived", "false")])
+        .to_request();
+    test::call_service(&app, req).await;
+    assert!(!service.get_memo_by_id(created.id).await.unwrap().archived);
+
+    service.delete_memo_permanently(created.id).await.ok();
 }
 
 #[tokio::test]
@@ -249,3 +758,511 @@ async fn test_create_memo_web_validation_error() {
     let resp = test::call_service(&app, req).await;
     assert!(resp.status().is_client_error() || resp.status().is_server_error());
 }
+
+#[tokio::test]
+async fn test_calendar_page_shows_memos_on_due_day() {
+    let state = setup_test_state().await;
+
+    let service = MemoService::new(state.db.clone());
+    let due = Utc.with_ymd_and_hms(2031, 3, 14, 9, 30, 0).unwrap();
+    let created = service
+        .create_memo(MemoFixture::new("Calendar Pi Day Memo").due_at(due).build())
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_calendar),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/web/calendar?year=2031&month=3")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("March 2031"));
+    assert!(html.contains("data-date=\"2031-03-14\""));
+    assert!(html.contains("Calendar Pi Day Memo"));
+    assert!(html.contains("month=2"));
+    assert!(html.contains("month=4"));
+
+    service.delete_memo_permanently(created.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_board_moves_memos_between_columns() {
+    let state = setup_test_state().await;
+
+    let service = MemoService::new(state.db.clone());
+    let title = format!("Board web memo {}", uuid::Uuid::new_v4());
+    let created = service
+        .create_memo(MemoFixture::new(&title).due_in_days(-1).build())
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_board)
+            .service(move_memo_on_board_web),
+    )
+    .await;
+
+    let resp = test::call_service(
+        &app,
+        test::TestRequest::get().uri("/web/board").to_request(),
+    )
+    .await;
+    assert_eq!(resp.status(), 200);
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    for column in ["overdue", "today", "upcoming", "done"] {
+        assert!(
+            html.contains(&format!("data-column=\"{column}\"")),
+            "{column}"
+        );
+    }
+    assert!(!html.contains("board-column-overdue board-drop-target"));
+
+    let move_to = |column: &str| {
+        test::TestRequest::post()
+            .uri(&format!("/web/memos/{}/column", created.id))
+            .set_form([("column", column)])
+            .to_request()
+    };
+
+    let resp = test::call_service(&app, move_to("done")).await;
+    assert_eq!(resp.status(), 200);
+    let html = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    let done = html.split("data-column=\"done\"").nth(1).unwrap();
+    assert!(done.contains(&title));
+    assert!(service.get_memo_by_id(created.id).await.unwrap().completed);
+
+    let resp = test::call_service(&app, move_to("overdue")).await;
+    assert_eq!(resp.status(), 400);
+
+    let resp = test::call_service(&app, move_to("upcoming")).await;
+    assert_eq!(resp.status(), 200);
+    let memo = service.get_memo_by_id(created.id).await.unwrap();
+    assert!(!memo.completed);
+    assert!(memo.date_to > Utc::now());
+
+    service.delete_memo_permanently(created.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_calendar_grid_rejects_invalid_month() {
+    let state = setup_test_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_calendar_grid),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/web/calendar/grid?year=2031&month=13")
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+}
+
+#[tokio::test]
+async fn test_reorder_memos_web() {
+    let state = setup_test_state().await;
+
+    let service = MemoService::new(state.db.clone());
+    let first = service
+        .create_memo(MemoFixture::new("Reorder Web First").build())
+        .await
+        .unwrap();
+    let second = service
+        .create_memo(MemoFixture::new("Reorder Web Second").build())
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(reorder_memos_web),
+    )
+    .await;
+
+    let order = format!("{},{}", second.id, first.id);
+    let req = test::TestRequest::post()
+        .uri("/web/memos/reorder")
+        .set_form([("order", order.as_str())])
+        .to_request();
+
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 204);
+
+    let first = service.get_memo_by_id(first.id).await.unwrap();
+    let second = service.get_memo_by_id(second.id).await.unwrap();
+    assert!(second.position < first.position);
+
+    let req = test::TestRequest::post()
+        .uri("/web/memos/reorder")
+        .set_form([("order", "not-a-uuid")])
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    service.delete_memo_permanently(first.id).await.ok();
+    service.delete_memo_permanently(second.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_inline_title_editing() {
+    let state = setup_test_state().await;
+
+    let service = MemoService::new(state.db.clone());
+    let memo = service
+        .create_memo(MemoFixture::new("Inline Title Before").build())
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_edit_title_form)
+            .service(update_memo_title_web),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("/web/memos/{}/title/edit", memo.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("memo-title-form"));
+    assert!(html.contains("value=\"Inline Title Before\""));
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/web/memos/{}/title", memo.id))
+        .set_form([("title", "Inline Title After")])
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("class=\"memo-title\""));
+    assert!(html.contains("Inline Title After"));
+
+    let updated = service.get_memo_by_id(memo.id).await.unwrap();
+    assert_eq!(updated.title, "Inline Title After");
+    assert_eq!(updated.date_to, memo.date_to);
+
+    let req = test::TestRequest::patch()
+        .uri(&format!("/web/memos/{}/title", memo.id))
+        .set_form([("title", "")])
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 400);
+
+    service.delete_memo_permanently(memo.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_delete_memo_web_offers_undo() {
+    let state = setup_test_state().await;
+
+    let service = MemoService::new(state.db.clone());
+    let memo = service
+        .create_memo(MemoFixture::new("Flash Delete").build())
+        .await
+        .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(delete_memo_web)
+            .service(undo_delete_web)
+            .service(get_flash)
+            .service(index),
+    )
+    .await;
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/web/memos/{}", memo.id))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    // The message comes back in the response, with its undo button.
+    assert!(resp.response().cookies().all(|c| c.name() != FLASH_COOKIE));
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("flash-success"));
+    assert!(html.contains("Memo deleted."));
+    let token = html
+        .split("data-token=\"")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+        .expect("undo button should carry the token")
+        .to_string();
+    assert!(service.get_memo_by_id(memo.id).await.is_err());
+
+    let req = test::TestRequest::post()
+        .uri(&format!("/web/undo/{token}"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(service.get_memo_by_id(memo.id).await.is_ok());
+
+    let cookie = resp
+        .response()
+        .cookies()
+        .find(|c| c.name() == FLASH_COOKIE)
+        .expect("flash cookie should be set")
+        .into_owned();
+    let flash = FlashMessage::read(
+        &test::TestRequest::default()
+            .cookie(cookie.clone())
+            .to_http_request(),
+    )
+    .expect("flash cookie should parse");
+    assert_eq!(flash.level, FlashLevel::Success);
+    assert_eq!(flash.message, "Memo restored.");
+
+    let req = test::TestRequest::get()
+        .uri("/web/flash")
+        .cookie(cookie.clone())
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let removal = resp
+        .response()
+        .cookies()
+        .find(|c| c.name() == FLASH_COOKIE)
+        .expect("flash cookie should be cleared");
+    assert_eq!(removal.value(), "");
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("flash-success"));
+    assert!(html.contains("Memo restored."));
+    assert!(!html.contains("data-action=\"undo\""));
+
+    let req = test::TestRequest::get()
+        .uri("/")
+        .cookie(cookie)
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Memo restored."));
+
+    // The token is spent.
+    let req = test::TestRequest::post()
+        .uri(&format!("/web/undo/{token}"))
+        .to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), 404);
+
+    let req = test::TestRequest::delete()
+        .uri(&format!("/web/memos/{}", uuid::Uuid::new_v4()))
+        .insert_header(("Accept-Language", "es"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+
+    let cookie = resp
+        .response()
+        .cookies()
+        .find(|c| c.name() == FLASH_COOKIE)
+        .expect("error flash cookie should be set")
+        .into_owned();
+    let flash = FlashMessage::read(
+        &test::TestRequest::default()
+            .cookie(cookie)
+            .to_http_request(),
+    )
+    .unwrap();
+    assert_eq!(flash.level, FlashLevel::Error);
+    assert!(flash.message.starts_with("Memo no encontrado"));
+
+    service.delete_memo_permanently(memo.id).await.ok();
+}
+
+#[tokio::test]
+async fn test_get_flash_without_cookie_is_empty() {
+    let state = setup_test_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(get_flash),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/web/flash").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(resp.response().cookies().next().is_none());
+
+    let body = test::read_body(resp).await;
+    assert!(!String::from_utf8(body.to_vec()).unwrap().contains("flash"));
+}
+
+#[tokio::test]
+async fn test_maintenance_page_for_browsers() {
+    let state = setup_test_state().await;
+    state.maintenance.set_enabled(true);
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .wrap(state.maintenance.clone())
+            .service(index),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/")
+        .insert_header(("Accept", "text/html,application/xhtml+xml"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 503);
+    assert!(resp.headers().contains_key("Retry-After"));
+
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Down for maintenance"));
+}
+
+#[tokio::test]
+async fn test_static_assets_are_fingerprinted() {
+    let state = setup_test_state().await;
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(index)
+            .service(static_asset),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/").to_request();
+    let html = String::from_utf8(
+        test::read_body(test::call_service(&app, req).await)
+            .await
+            .to_vec(),
+    )
+    .unwrap();
+    let css_url = asset_url("css/style.css");
+    assert!(css_url.starts_with("/static/css/style."));
+    assert!(html.contains(&format!("href=\"{}\"", css_url)));
+
+    let req = test::TestRequest::get().uri(&css_url).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("content-type").unwrap(), "text/css");
+    assert_eq!(
+        resp.headers().get("cache-control").unwrap(),
+        "public, max-age=31536000, immutable"
+    );
+    let etag = resp.headers().get("etag").unwrap().clone();
+
+    let req = test::TestRequest::get()
+        .uri("/static/css/style.css")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers().get("cache-control").unwrap(), "no-cache");
+
+    let req = test::TestRequest::get()
+        .uri("/static/css/style.css")
+        .insert_header(("If-None-Match", etag))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 304);
+
+    let req = test::TestRequest::get()
+        .uri("/static/missing.css")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 404);
+}
+
+#[tokio::test]
+async fn test_notifications_page_lists_unread_notifications() {
+    let state = setup_test_state().await;
+
+    let service = MemoService::new(state.db.clone());
+    let memo = service
+        .create_memo(MemoFixture::new("Inbox page memo").build())
+        .await
+        .unwrap();
+    NotificationRepository::create_if_absent(
+        &state.db,
+        NewNotification {
+            kind: "memo_due_soon",
+            title: "Inbox page memo is due soon".to_string(),
+            body: None,
+            memo_id: Some(memo.id),
+            dedupe_key: None,
+        },
+    )
+    .await
+    .unwrap();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state.clone()))
+            .service(get_notifications),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri("/web/notifications")
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("Inbox page memo is due soon"));
+    assert!(html.contains("data-action=\"mark-read\""));
+    assert!(html.contains("id=\"notification-badge\""));
+
+    MemoRepository::delete(&state.db, memo.id).await.unwrap();
+}
+
+#[tokio::test]
+async fn test_api_docs_page_renders_configured_ui() {
+    let mut state = setup_test_state().await;
+    state.config.api.docs_ui = Some(DocsUi::Rapidoc);
+    state.config.api.docs_script_url = "/static/js/rapidoc-min.js".to_string();
+
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(state))
+            .service(api_docs)
+            .service(openapi_json),
+    )
+    .await;
+
+    let req = test::TestRequest::get().uri("/docs").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), 200);
+    let body = test::read_body(resp).await;
+    let html = String::from_utf8(body.to_vec()).unwrap();
+    assert!(html.contains("<script type=\"module\" src=\"/static/js/rapidoc-min.js\">"));
+    assert!(html.contains("<rapi-doc spec-url=\"/api-docs/openapi.json\""));
+    assert!(!html.contains("<redoc"));
+
+    let req = test::TestRequest::get()
+        .uri("/api-docs/openapi.json")
+        .to_request();
+    let spec: serde_json::Value = test::call_and_read_body_json(&app, req).await;
+    assert_eq!(spec["info"]["title"], "Memos API");
+}